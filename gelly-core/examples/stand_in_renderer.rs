@@ -0,0 +1,37 @@
+//! Runs the stand-in UPnP renderer so "Play On" can be tried without real
+//! hardware. Pass the address to listen on, which must be reachable from
+//! Gelly, e.g. `cargo run -p gelly-core --example stand_in_renderer 192.168.1.20:49200`.
+
+use std::time::Duration;
+
+use gelly_core::audio::renderer::stand_in::StandInRenderer;
+
+#[tokio::main]
+async fn main() -> std::io::Result<()> {
+    let bind = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "127.0.0.1:49200".to_string());
+    let renderer = StandInRenderer::start(&bind, "Gelly Stand-in").await?;
+    println!("Serving {}", renderer.description_url());
+
+    let ssdp = async {
+        if let Err(e) = renderer.answer_ssdp().await {
+            eprintln!("Not answering SSDP searches: {e}");
+            std::future::pending::<()>().await;
+        }
+    };
+    let report = async {
+        let mut seen = 0;
+        loop {
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            let actions = renderer.actions();
+            let state = renderer.state();
+            for action in &actions[seen..] {
+                println!("{action}: {} {:?}", state.transport_state, state.title);
+            }
+            seen = actions.len();
+        }
+    };
+    tokio::join!(ssdp, report);
+    Ok(())
+}
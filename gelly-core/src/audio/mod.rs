@@ -0,0 +1,3 @@
+pub mod player;
+pub mod queue;
+pub mod renderer;
@@ -2,7 +2,6 @@ use async_channel::{Receiver, Sender};
 use futures_util::StreamExt;
 use gstreamer as gst;
 use gstreamer::prelude::*;
-use gtk::glib;
 use log::{debug, warn};
 use std::sync::{Arc, Mutex};
 
@@ -22,6 +21,8 @@ pub enum PlayerEvent {
     DurationChanged(u64),
     AboutToFinish,
     StreamStarted,
+    /// Now playing title from the stream, radio stations send these as they go
+    TitleChanged(String),
 }
 
 #[derive(Debug)]
@@ -29,6 +30,8 @@ pub struct AudioPlayer {
     pipeline: gst::Pipeline,
     event_sender: Sender<PlayerEvent>,
     next_uri_cache: Arc<Mutex<Option<String>>>,
+    /// Playback speed, setting a new uri starts over at normal speed
+    rate: Arc<Mutex<f64>>,
 }
 
 impl AudioPlayer {
@@ -42,10 +45,20 @@ impl AudioPlayer {
             .downcast::<gst::Pipeline>()
             .expect("Failed to downcast to pipeline");
 
+        // Keeps voices at their pitch when played faster or slower
+        match gst::ElementFactory::make("scaletempo").build() {
+            Ok(scaletempo) => pipeline.set_property("audio-filter", &scaletempo),
+            Err(err) => warn!(
+                "scaletempo unavailable, speed changes will alter pitch: {}",
+                err
+            ),
+        }
+
         let player_instance = Self {
             pipeline,
             event_sender,
             next_uri_cache: Arc::new(Mutex::new(None)),
+            rate: Arc::new(Mutex::new(1.0)),
         };
 
         player_instance.setup_bus_handling();
@@ -56,6 +69,7 @@ impl AudioPlayer {
     }
 
     pub fn set_uri(&self, uri: &str) {
+        *self.rate.lock().unwrap() = 1.0;
         self.pipeline.set_property("uri", uri);
     }
 
@@ -82,10 +96,20 @@ impl AudioPlayer {
 
     pub fn seek(&self, position_s: u64) -> Result<(), ()> {
         let position = gst::ClockTime::from_seconds(position_s);
-        match self
-            .pipeline
-            .seek_simple(gst::SeekFlags::FLUSH | gst::SeekFlags::KEY_UNIT, position)
-        {
+        match self.seek_at_rate(gst::SeekFlags::FLUSH | gst::SeekFlags::KEY_UNIT, position) {
+            Ok(_) => Ok(()),
+            Err(err) => {
+                warn!("Failed to seek player: {}", err);
+                Err(())
+            }
+        }
+    }
+
+    /// Accurate seek for when the position has to line up with other players,
+    /// slower than [`Self::seek`] as it can't snap to a keyframe.
+    pub fn seek_ms(&self, position_ms: u64) -> Result<(), ()> {
+        let position = gst::ClockTime::from_mseconds(position_ms);
+        match self.seek_at_rate(gst::SeekFlags::FLUSH | gst::SeekFlags::ACCURATE, position) {
             Ok(_) => Ok(()),
             Err(err) => {
                 warn!("Failed to seek player: {}", err);
@@ -94,6 +118,50 @@ impl AudioPlayer {
         }
     }
 
+    /// A simple seek would drop back to normal speed
+    fn seek_at_rate(
+        &self,
+        flags: gst::SeekFlags,
+        position: gst::ClockTime,
+    ) -> Result<(), glib::BoolError> {
+        let rate = *self.rate.lock().unwrap();
+        self.pipeline.seek(
+            rate,
+            flags,
+            gst::SeekType::Set,
+            position,
+            gst::SeekType::None,
+            gst::ClockTime::NONE,
+        )
+    }
+
+    pub fn rate(&self) -> f64 {
+        *self.rate.lock().unwrap()
+    }
+
+    /// Changes speed from where we are now, the pipeline has to be playing
+    /// or paused
+    pub fn set_rate(&self, rate: f64) {
+        if (self.rate() - rate).abs() < f64::EPSILON {
+            return;
+        }
+        let Some(position) = self.pipeline.query_position::<gst::ClockTime>() else {
+            return;
+        };
+        *self.rate.lock().unwrap() = rate;
+        if let Err(err) =
+            self.seek_at_rate(gst::SeekFlags::FLUSH | gst::SeekFlags::ACCURATE, position)
+        {
+            warn!("Failed to change playback rate: {}", err);
+        }
+    }
+
+    pub fn get_position_ms(&self) -> Option<u64> {
+        self.pipeline
+            .query_position::<gst::ClockTime>()
+            .map(|pos| pos.mseconds())
+    }
+
     pub fn is_playing(&self) -> bool {
         let (state_result, current_state, _pending_state) =
             self.pipeline.state(gst::ClockTime::ZERO);
@@ -188,6 +256,15 @@ impl AudioPlayer {
                         debug!("Playback stream started");
                         let _ = sender.send(PlayerEvent::StreamStarted).await;
                     }
+                    gst::MessageView::Tag(tag) => {
+                        // ICY StreamTitle ends up in the title tag
+                        if let Some(title) = tag.tags().get::<gst::tags::Title>() {
+                            let title = title.get().trim().to_string();
+                            if !title.is_empty() {
+                                let _ = sender.send(PlayerEvent::TitleChanged(title)).await;
+                            }
+                        }
+                    }
                     _ => {}
                 }
             }
@@ -0,0 +1,138 @@
+//! The songs lined up to play and which one is playing, kept apart from the
+//! player so front ends share it and it can be tested without gstreamer.
+
+#[derive(Debug, Clone)]
+pub struct PlayQueue<T> {
+    items: Vec<T>,
+    current: Option<usize>,
+}
+
+impl<T> Default for PlayQueue<T> {
+    fn default() -> Self {
+        Self {
+            items: Vec::new(),
+            current: None,
+        }
+    }
+}
+
+impl<T> PlayQueue<T> {
+    pub fn items(&self) -> &[T] {
+        &self.items
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn current(&self) -> Option<usize> {
+        self.current
+    }
+
+    pub fn current_item(&self) -> Option<&T> {
+        self.current.and_then(|index| self.items.get(index))
+    }
+
+    /// Nothing is playing from the new songs until one is selected
+    pub fn replace(&mut self, items: Vec<T>) {
+        self.items = items;
+        self.current = None;
+    }
+
+    pub fn extend(&mut self, items: impl IntoIterator<Item = T>) {
+        self.items.extend(items);
+    }
+
+    /// Makes `index` the current song, or clears it when out of range
+    pub fn select(&mut self, index: usize) -> Option<&T> {
+        self.current = (index < self.items.len()).then_some(index);
+        self.current_item()
+    }
+
+    /// Takes the song at `index` out, keeping `current` on the same song.
+    /// Returns true when that was the current song, which leaves nothing
+    /// current.
+    pub fn remove(&mut self, index: usize) -> bool {
+        if index >= self.items.len() {
+            return false;
+        }
+        self.items.remove(index);
+        match self.current {
+            Some(current) if current == index => {
+                self.current = None;
+                true
+            }
+            Some(current) if current > index => {
+                self.current = Some(current - 1);
+                false
+            }
+            _ => false,
+        }
+    }
+
+    pub fn next_index(&self) -> Option<usize> {
+        self.current
+            .map(|current| current + 1)
+            .filter(|next| *next < self.items.len())
+    }
+
+    /// The first song stays where it is
+    pub fn prev_index(&self) -> Option<usize> {
+        self.current.map(|current| current.saturating_sub(1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn queue(items: &[&'static str], current: usize) -> PlayQueue<&'static str> {
+        let mut queue = PlayQueue::default();
+        queue.replace(items.to_vec());
+        queue.select(current);
+        queue
+    }
+
+    #[test]
+    fn test_remove_keeps_current_song() {
+        let mut queue = queue(&["a", "b", "c", "d"], 2);
+        assert!(!queue.remove(0));
+        assert_eq!(queue.current_item(), Some(&"c"));
+        assert!(!queue.remove(2));
+        assert_eq!(queue.current_item(), Some(&"c"));
+        assert!(!queue.remove(10));
+        assert_eq!(queue.items(), ["b", "c"]);
+
+        assert!(queue.remove(1));
+        assert_eq!(queue.current(), None);
+        assert_eq!(queue.items(), ["b"]);
+    }
+
+    #[test]
+    fn test_remove_last_song_while_playing() {
+        let mut queue = queue(&["a", "b"], 1);
+        assert!(queue.remove(1));
+        assert_eq!(queue.current_item(), None);
+        assert_eq!(queue.next_index(), None);
+    }
+
+    #[test]
+    fn test_navigation() {
+        let mut queue = queue(&["a", "b", "c"], 0);
+        assert_eq!(queue.prev_index(), Some(0));
+        assert_eq!(queue.next_index(), Some(1));
+        queue.select(2);
+        assert_eq!(queue.next_index(), None);
+        assert_eq!(queue.prev_index(), Some(1));
+
+        assert_eq!(queue.select(5), None);
+        assert_eq!(queue.current(), None);
+        assert_eq!(queue.next_index(), None);
+
+        queue.extend(["d"]);
+        assert_eq!(queue.select(3), Some(&"d"));
+        queue.replace(vec!["e"]);
+        assert_eq!(queue.current(), None);
+        assert!(!queue.is_empty());
+    }
+}
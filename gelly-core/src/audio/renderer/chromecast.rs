@@ -0,0 +1,381 @@
+//! Chromecast output through the Default Media Receiver app. The cast
+//! protocol client is blocking, so each device gets its own thread.
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use async_channel::{Receiver, Sender, TryRecvError};
+use log::{debug, warn};
+use mdns_sd::{ServiceDaemon, ServiceEvent};
+use reqwest::Url;
+use reqwest::header::CONTENT_TYPE;
+use rust_cast::channels::connection::ConnectionResponse;
+use rust_cast::channels::heartbeat::HeartbeatResponse;
+use rust_cast::channels::media::{
+    IdleReason, Image, Media, MediaResponse, Metadata, MusicTrackMediaMetadata,
+    PlayerState as CastState, StatusEntry, StreamType,
+};
+use rust_cast::channels::receiver::{CastDeviceApp, ReceiverResponse};
+use rust_cast::{CastDevice, ChannelMessage};
+use tokio::runtime::Handle;
+
+use crate::audio::player::{PlayerEvent, PlayerState};
+use crate::audio::renderer::{
+    RendererCommand, RendererDevice, RendererError, RendererKind, TrackMetadata,
+};
+
+const SERVICE_TYPE: &str = "_googlecast._tcp.local.";
+const RECEIVER: &str = "receiver-0";
+const COMMAND_INTERVAL: Duration = Duration::from_millis(200);
+const STATUS_INTERVAL: Duration = Duration::from_secs(1);
+
+impl From<rust_cast::errors::Error> for RendererError {
+    fn from(error: rust_cast::errors::Error) -> Self {
+        RendererError::Cast(error.to_string())
+    }
+}
+
+pub async fn discover(timeout: Duration) -> Result<Vec<RendererDevice>, RendererError> {
+    let mdns = ServiceDaemon::new().map_err(|e| RendererError::Cast(e.to_string()))?;
+    let browser = mdns
+        .browse(SERVICE_TYPE)
+        .map_err(|e| RendererError::Cast(e.to_string()))?;
+
+    let mut devices: Vec<RendererDevice> = Vec::new();
+    let deadline = tokio::time::Instant::now() + timeout;
+    while let Ok(Ok(event)) = tokio::time::timeout_at(deadline, browser.recv_async()).await {
+        let ServiceEvent::ServiceResolved(service) = event else {
+            continue;
+        };
+        let Some(address) = service.get_addresses_v4().into_iter().next() else {
+            continue;
+        };
+        let id = service
+            .get_property_val_str("id")
+            .unwrap_or(&service.fullname)
+            .to_string();
+        if devices.iter().any(|device| device.id == id) {
+            continue;
+        }
+        devices.push(RendererDevice {
+            id,
+            name: service
+                .get_property_val_str("fn")
+                .unwrap_or(&service.host)
+                .to_string(),
+            kind: RendererKind::Chromecast,
+            location: format!("{}:{}", address, service.port),
+        });
+    }
+    if let Err(e) = mdns.shutdown() {
+        debug!("Failed to shut down mDNS browser: {}", e);
+    }
+    Ok(devices)
+}
+
+pub(crate) fn spawn(
+    location: String,
+    runtime: Handle,
+    commands: Receiver<RendererCommand>,
+    events: Sender<PlayerEvent>,
+) {
+    thread::spawn(move || {
+        if let Err(e) = run(&location, &runtime, &commands, &events) {
+            warn!("Chromecast {} failed: {}", location, e);
+            let _ = events.send_blocking(PlayerEvent::Error(e.to_string()));
+        }
+        debug!("Chromecast {} disconnected", location);
+    });
+}
+
+/// The receiver app and the media session playing in it
+struct Session {
+    transport_id: String,
+    session_id: String,
+    media_session_id: Option<i32>,
+}
+
+fn run(
+    location: &str,
+    runtime: &Handle,
+    commands: &Receiver<RendererCommand>,
+    events: &Sender<PlayerEvent>,
+) -> Result<(), RendererError> {
+    let (host, port) = location
+        .rsplit_once(':')
+        .and_then(|(host, port)| Some((host, port.parse::<u16>().ok()?)))
+        .ok_or_else(|| RendererError::InvalidResponse(format!("Bad cast address {location}")))?;
+    let device = CastDevice::connect_without_host_verification(host, port)?;
+    device.connection.connect(RECEIVER)?;
+    device.heartbeat.ping()?;
+
+    let app = device
+        .receiver
+        .launch_app(&CastDeviceApp::DefaultMediaReceiver)?;
+    device.connection.connect(app.transport_id.as_str())?;
+    let mut session = Session {
+        transport_id: app.transport_id,
+        session_id: app.session_id,
+        media_session_id: None,
+    };
+
+    let mut last_state = None;
+    let mut last_status = Instant::now();
+    loop {
+        match commands.try_recv() {
+            Ok(command) => {
+                if let Err(e) = execute(&device, &mut session, runtime, command) {
+                    warn!("Chromecast command failed: {}", e);
+                    let _ = events.send_blocking(PlayerEvent::Error(e.to_string()));
+                }
+                continue;
+            }
+            Err(TryRecvError::Closed) => break,
+            Err(TryRecvError::Empty) => {}
+        }
+
+        if last_status.elapsed() >= STATUS_INTERVAL {
+            last_status = Instant::now();
+            if !read_pending(&device, &session, &mut last_state, events)? {
+                debug!("Chromecast {} closed the session", location);
+                let _ = events.send_blocking(PlayerEvent::StateChanged(PlayerState::Stopped));
+                return Ok(());
+            }
+            if let Some(media_session_id) = session.media_session_id {
+                poll_status(&device, &session, media_session_id, &mut last_state, events)?;
+            }
+        }
+        thread::sleep(COMMAND_INTERVAL);
+    }
+
+    // Leave the TV or speaker idle rather than playing on
+    device.receiver.stop_app(session.session_id.as_str())?;
+    Ok(())
+}
+
+/// Handles what the device sent on its own since the last pass: answers its
+/// heartbeat and passes on the media status it reports. Reading goes up to
+/// the answer to a ping of our own, so it never waits longer than a round
+/// trip. False once the session has been closed on the device.
+fn read_pending(
+    device: &CastDevice,
+    session: &Session,
+    last_state: &mut Option<PlayerState>,
+    events: &Sender<PlayerEvent>,
+) -> Result<bool, RendererError> {
+    device.heartbeat.ping()?;
+    loop {
+        match device.receive()? {
+            ChannelMessage::Heartbeat(HeartbeatResponse::Ping) => device.heartbeat.pong()?,
+            ChannelMessage::Heartbeat(HeartbeatResponse::Pong) => return Ok(true),
+            ChannelMessage::Connection(ConnectionResponse::Close) => return Ok(false),
+            ChannelMessage::Receiver(ReceiverResponse::Status(status)) => {
+                let running = status
+                    .applications
+                    .iter()
+                    .any(|app| app.session_id == session.session_id);
+                if !running {
+                    return Ok(false);
+                }
+            }
+            ChannelMessage::Media(MediaResponse::Status(status)) => {
+                if let Some(entry) = status.entries.first() {
+                    report_status(entry, last_state, events);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn execute(
+    device: &CastDevice,
+    session: &mut Session,
+    runtime: &Handle,
+    command: RendererCommand,
+) -> Result<(), RendererError> {
+    let destination = session.transport_id.as_str();
+    match command {
+        RendererCommand::Load(uri, metadata) => {
+            let content_type = runtime
+                .block_on(stream_content_type(&uri))
+                .unwrap_or_else(|| content_type_from_uri(&uri).to_string());
+            debug!("Casting {} as {}", uri, content_type);
+            let status = device.media.load(
+                destination,
+                session.session_id.as_str(),
+                &media_for(uri, content_type, &metadata),
+            )?;
+            session.media_session_id = status.entries.first().map(|entry| entry.media_session_id);
+        }
+        RendererCommand::Volume(volume) => {
+            device.receiver.set_volume(volume as f32)?;
+        }
+        RendererCommand::Mute(muted) => {
+            device.receiver.set_volume(muted)?;
+        }
+        RendererCommand::Play => {
+            with_media(session, |media| device.media.play(destination, media))?;
+        }
+        RendererCommand::Pause => {
+            with_media(session, |media| device.media.pause(destination, media))?;
+        }
+        RendererCommand::Stop => {
+            with_media(session, |media| device.media.stop(destination, media))?;
+            session.media_session_id = None;
+        }
+        RendererCommand::Seek(position_ms) => {
+            let seconds = position_ms as f32 / 1000.0;
+            with_media(session, |media| {
+                device.media.seek(destination, media, Some(seconds), None)
+            })?;
+        }
+    }
+    Ok(())
+}
+
+/// Runs `action` on the loaded media session, there's nothing to control
+/// before something is loaded
+fn with_media<T>(
+    session: &Session,
+    action: impl FnOnce(i32) -> Result<T, rust_cast::errors::Error>,
+) -> Result<(), RendererError> {
+    if let Some(media_session_id) = session.media_session_id {
+        action(media_session_id)?;
+    }
+    Ok(())
+}
+
+fn poll_status(
+    device: &CastDevice,
+    session: &Session,
+    media_session_id: i32,
+    last_state: &mut Option<PlayerState>,
+    events: &Sender<PlayerEvent>,
+) -> Result<(), RendererError> {
+    let status = device
+        .media
+        .get_status(session.transport_id.as_str(), Some(media_session_id))?;
+    if let Some(entry) = status.entries.first() {
+        report_status(entry, last_state, events);
+    }
+    Ok(())
+}
+
+fn report_status(
+    entry: &StatusEntry,
+    last_state: &mut Option<PlayerState>,
+    events: &Sender<PlayerEvent>,
+) {
+    let state = match entry.player_state {
+        CastState::Playing | CastState::Buffering => PlayerState::Playing,
+        CastState::Paused => PlayerState::Paused,
+        CastState::Idle => PlayerState::Stopped,
+    };
+    if let Some(current_time) = entry.current_time {
+        let _ = events.send_blocking(PlayerEvent::PositionChanged(current_time as u64));
+    }
+    if let Some(duration) = entry.media.as_ref().and_then(|media| media.duration) {
+        let _ = events.send_blocking(PlayerEvent::DurationChanged(duration as u64));
+    }
+    if last_state.as_ref() != Some(&state) {
+        *last_state = Some(state.clone());
+        let _ = events.send_blocking(PlayerEvent::StateChanged(state));
+        if matches!(entry.idle_reason, Some(IdleReason::Finished)) {
+            let _ = events.send_blocking(PlayerEvent::EndOfStream);
+        }
+    }
+}
+
+/// Asks the server what it will send, following the redirect the Jellyfin
+/// universal endpoint answers with, so the original file, a transcode and an
+/// HLS playlist are each announced as what they are.
+async fn stream_content_type(uri: &str) -> Option<String> {
+    let response = reqwest::Client::new().head(uri).send().await.ok()?;
+    if !response.status().is_success() {
+        return None;
+    }
+    let content_type = response.headers().get(CONTENT_TYPE)?.to_str().ok()?;
+    let content_type = content_type.split(';').next()?.trim();
+    (!content_type.is_empty() && content_type != "application/octet-stream")
+        .then(|| content_type.to_string())
+}
+
+/// Falls back on the format the stream URL asks the server for, for servers
+/// that don't answer HEAD.
+fn content_type_from_uri(uri: &str) -> &'static str {
+    let Ok(url) = Url::parse(uri) else {
+        return "audio/mpeg";
+    };
+    let param = |name: &str| {
+        url.query_pairs()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.to_lowercase())
+    };
+    if param("transcodingProtocol").as_deref() == Some("hls") {
+        return "application/x-mpegURL";
+    }
+    let format = param("transcodingContainer").or_else(|| param("format"));
+    match format.as_deref() {
+        Some("opus" | "ogg" | "oga") => "audio/ogg",
+        Some("flac") => "audio/flac",
+        Some("aac") => "audio/aac",
+        Some("mp4" | "m4a") => "audio/mp4",
+        Some("ts") => "video/mp2t",
+        Some("wav") => "audio/wav",
+        Some("webm" | "webma") => "audio/webm",
+        _ => "audio/mpeg",
+    }
+}
+
+fn media_for(uri: String, content_type: String, metadata: &TrackMetadata) -> Media {
+    Media {
+        content_id: uri,
+        stream_type: StreamType::Buffered,
+        content_type,
+        metadata: Some(Metadata::MusicTrack(MusicTrackMediaMetadata {
+            album_name: Some(metadata.album.clone()),
+            title: Some(metadata.title.clone()),
+            album_artist: None,
+            artist: Some(metadata.artist.clone()),
+            composer: None,
+            track_number: None,
+            disc_number: None,
+            images: metadata
+                .image_url
+                .iter()
+                .map(|url| Image::new(url.clone()))
+                .collect(),
+            release_date: None,
+        })),
+        duration: Some(metadata.duration_seconds as f32),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_content_type_from_uri() {
+        assert_eq!(
+            content_type_from_uri(
+                "http://jf/Audio/1/universal?container=flac&transcodingContainer=mp4&transcodingProtocol=hls"
+            ),
+            "application/x-mpegURL"
+        );
+        assert_eq!(
+            content_type_from_uri("http://jf/Audio/1/universal?transcodingContainer=ts"),
+            "video/mp2t"
+        );
+        assert_eq!(
+            content_type_from_uri("http://navidrome/rest/stream?id=1&format=opus"),
+            "audio/ogg"
+        );
+        assert_eq!(
+            content_type_from_uri("http://navidrome/rest/stream?id=1&format=raw"),
+            "audio/mpeg"
+        );
+        assert_eq!(content_type_from_uri("not a url"), "audio/mpeg");
+    }
+}
@@ -0,0 +1,165 @@
+//! Network renderers (UPnP/DLNA MediaRenderers and Chromecasts) that can play
+//! the stream in place of the local pipeline. The queue stays with the caller,
+//! a renderer is only ever handed one stream URL at a time and reports back
+//! with the same [`PlayerEvent`]s as [`super::player::AudioPlayer`].
+
+use std::time::Duration;
+
+use async_channel::{Receiver, Sender};
+use log::{debug, warn};
+use thiserror::Error;
+use tokio::runtime::Handle;
+
+use crate::audio::player::PlayerEvent;
+
+pub mod chromecast;
+pub mod stand_in;
+pub mod upnp;
+
+#[derive(Error, Debug)]
+pub enum RendererError {
+    #[error("Network error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("HTTP error: {0}")]
+    Http(#[from] reqwest::Error),
+
+    #[error("Renderer refused {action}: {message}")]
+    Action { action: String, message: String },
+
+    #[error("Unexpected renderer response: {0}")]
+    InvalidResponse(String),
+
+    #[error("Cast error: {0}")]
+    Cast(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RendererKind {
+    Upnp,
+    Chromecast,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RendererDevice {
+    /// UPnP UDN or Chromecast id, stable across restarts
+    pub id: String,
+    pub name: String,
+    pub kind: RendererKind,
+    /// Device description URL for UPnP, `host:port` for Chromecast
+    pub location: String,
+}
+
+/// What the renderer shows about the current track.
+#[derive(Debug, Clone, Default)]
+pub struct TrackMetadata {
+    pub title: String,
+    pub artist: String,
+    pub album: String,
+    pub image_url: Option<String>,
+    pub duration_seconds: u64,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) enum RendererCommand {
+    Load(String, TrackMetadata),
+    Play,
+    Pause,
+    Stop,
+    Seek(u64),
+    Volume(f64),
+    Mute(bool),
+}
+
+/// Finds renderers on the local network, giving each protocol `timeout` to
+/// answer.
+pub async fn discover(timeout: Duration) -> Vec<RendererDevice> {
+    let (upnp, cast) = tokio::join!(upnp::discover(timeout), chromecast::discover(timeout));
+    let mut devices = Vec::new();
+    match upnp {
+        Ok(found) => devices.extend(found),
+        Err(e) => warn!("UPnP discovery failed: {}", e),
+    }
+    match cast {
+        Ok(found) => devices.extend(found),
+        Err(e) => warn!("Chromecast discovery failed: {}", e),
+    }
+    devices.sort_by_key(|device| device.name.to_lowercase());
+    devices
+}
+
+/// Handle to a connected renderer. Commands are queued and sent in order on a
+/// background task, so none of these calls block. Dropping it disconnects.
+#[derive(Debug)]
+pub struct RemoteRenderer {
+    device: RendererDevice,
+    commands: Sender<RendererCommand>,
+}
+
+impl RemoteRenderer {
+    pub fn connect(device: RendererDevice, runtime: &Handle) -> (Self, Receiver<PlayerEvent>) {
+        let (commands, command_receiver) = async_channel::unbounded();
+        let (event_sender, events) = async_channel::unbounded();
+        debug!("Connecting to {:?} renderer {}", device.kind, device.name);
+        match device.kind {
+            RendererKind::Upnp => {
+                let location = device.location.clone();
+                runtime.spawn(upnp::run(location, command_receiver, event_sender));
+            }
+            RendererKind::Chromecast => {
+                chromecast::spawn(
+                    device.location.clone(),
+                    runtime.clone(),
+                    command_receiver,
+                    event_sender,
+                );
+            }
+        }
+        (Self { device, commands }, events)
+    }
+
+    pub fn device(&self) -> &RendererDevice {
+        &self.device
+    }
+
+    fn send(&self, command: RendererCommand) {
+        if self.commands.try_send(command).is_err() {
+            warn!("Renderer {} is no longer connected", self.device.name);
+        }
+    }
+
+    pub fn load(&self, uri: &str, metadata: TrackMetadata) {
+        self.send(RendererCommand::Load(uri.to_string(), metadata));
+    }
+
+    pub fn play(&self) {
+        self.send(RendererCommand::Play);
+    }
+
+    pub fn pause(&self) {
+        self.send(RendererCommand::Pause);
+    }
+
+    pub fn stop(&self) {
+        self.send(RendererCommand::Stop);
+    }
+
+    pub fn seek_ms(&self, position_ms: u64) {
+        self.send(RendererCommand::Seek(position_ms));
+    }
+
+    /// Linear volume, 0.0 to 1.0
+    pub fn set_volume(&self, volume: f64) {
+        self.send(RendererCommand::Volume(volume.clamp(0.0, 1.0)));
+    }
+
+    pub fn set_mute(&self, muted: bool) {
+        self.send(RendererCommand::Mute(muted));
+    }
+}
+
+impl Drop for RemoteRenderer {
+    fn drop(&mut self) {
+        self.commands.close();
+    }
+}
@@ -0,0 +1,320 @@
+//! A tiny UPnP MediaRenderer that plays nothing. It answers the same SOAP
+//! actions a real renderer would and fakes the clock, so renderer output can
+//! be tried without a speaker on the network.
+
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use log::{debug, warn};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream, UdpSocket};
+
+use crate::audio::renderer::upnp::{MEDIA_RENDERER, format_time, header, parse_time};
+use crate::xml::{element_text, escape_xml};
+
+/// What the renderer was last told to do.
+#[derive(Debug, Clone, Default)]
+pub struct StandInState {
+    pub uri: String,
+    pub title: String,
+    pub transport_state: &'static str,
+    pub volume: u32,
+    pub muted: bool,
+    duration_ms: u64,
+    offset_ms: u64,
+    playing_since: Option<Instant>,
+    actions: Vec<String>,
+}
+
+impl StandInState {
+    fn position_ms(&self) -> u64 {
+        let elapsed = self
+            .playing_since
+            .map(|since| since.elapsed().as_millis() as u64)
+            .unwrap_or(0);
+        self.offset_ms + elapsed
+    }
+
+    /// Moves the fake clock along, stopping at the end of the track.
+    fn tick(&mut self) {
+        if self.playing_since.is_some()
+            && self.duration_ms > 0
+            && self.position_ms() >= self.duration_ms
+        {
+            self.transport_state = "STOPPED";
+            self.playing_since = None;
+            self.offset_ms = 0;
+        }
+    }
+}
+
+pub struct StandInRenderer {
+    name: String,
+    address: SocketAddr,
+    state: Arc<Mutex<StandInState>>,
+}
+
+impl StandInRenderer {
+    /// Starts serving on `bind`, e.g. "127.0.0.1:0" for a random port.
+    pub async fn start(bind: &str, name: &str) -> std::io::Result<Self> {
+        let listener = TcpListener::bind(bind).await?;
+        let address = listener.local_addr()?;
+        let state = Arc::new(Mutex::new(StandInState {
+            transport_state: "NO_MEDIA_PRESENT",
+            volume: 100,
+            ..Default::default()
+        }));
+        let description = Self::description(name);
+
+        let server_state = state.clone();
+        tokio::spawn(async move {
+            loop {
+                let stream = match listener.accept().await {
+                    Ok((stream, _)) => stream,
+                    Err(e) => {
+                        warn!("Stand-in renderer stopped accepting: {}", e);
+                        break;
+                    }
+                };
+                let state = server_state.clone();
+                let description = description.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = serve(stream, &state, &description).await {
+                        debug!("Stand-in renderer request failed: {}", e);
+                    }
+                });
+            }
+        });
+
+        Ok(Self {
+            name: name.to_string(),
+            address,
+            state,
+        })
+    }
+
+    pub fn description_url(&self) -> String {
+        format!("http://{}/description.xml", self.address)
+    }
+
+    pub fn state(&self) -> StandInState {
+        let mut state = self.state.lock().unwrap();
+        state.tick();
+        state.clone()
+    }
+
+    /// Names of the SOAP actions received so far, oldest first
+    pub fn actions(&self) -> Vec<String> {
+        self.state.lock().unwrap().actions.clone()
+    }
+
+    /// Answers SSDP searches for media renderers so the stand-in shows up in
+    /// discovery. Runs until the socket fails.
+    pub async fn answer_ssdp(&self) -> std::io::Result<()> {
+        let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 1900)).await?;
+        socket.join_multicast_v4(Ipv4Addr::new(239, 255, 255, 250), Ipv4Addr::UNSPECIFIED)?;
+        let mut buffer = [0u8; 2048];
+        loop {
+            let (len, from) = socket.recv_from(&mut buffer).await?;
+            let request = String::from_utf8_lossy(&buffer[..len]);
+            let wanted = header(&request, "ST").unwrap_or_default();
+            if !request.starts_with("M-SEARCH")
+                || !(wanted == MEDIA_RENDERER || wanted == "ssdp:all")
+            {
+                continue;
+            }
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nCACHE-CONTROL: max-age=1800\r\nEXT:\r\nLOCATION: {}\r\nST: {MEDIA_RENDERER}\r\nUSN: uuid:gelly-stand-in-{}::{MEDIA_RENDERER}\r\n\r\n",
+                self.description_url(),
+                self.address.port()
+            );
+            debug!("Answering SSDP search from {} as {}", from, self.name);
+            socket.send_to(response.as_bytes(), from).await?;
+        }
+    }
+
+    pub fn description(name: &str) -> String {
+        format!(
+            r#"<?xml version="1.0"?>
+<root xmlns="urn:schemas-upnp-org:device-1-0">
+  <specVersion><major>1</major><minor>0</minor></specVersion>
+  <device>
+    <deviceType>{MEDIA_RENDERER}</deviceType>
+    <friendlyName>{}</friendlyName>
+    <manufacturer>Gelly</manufacturer>
+    <modelName>Stand-in Renderer</modelName>
+    <UDN>uuid:gelly-stand-in</UDN>
+    <serviceList>
+      <service>
+        <serviceType>urn:schemas-upnp-org:service:AVTransport:1</serviceType>
+        <serviceId>urn:upnp-org:serviceId:AVTransport</serviceId>
+        <controlURL>/AVTransport/control</controlURL>
+        <eventSubURL>/AVTransport/event</eventSubURL>
+        <SCPDURL>/AVTransport/scpd.xml</SCPDURL>
+      </service>
+      <service>
+        <serviceType>urn:schemas-upnp-org:service:RenderingControl:1</serviceType>
+        <serviceId>urn:upnp-org:serviceId:RenderingControl</serviceId>
+        <controlURL>/RenderingControl/control</controlURL>
+        <eventSubURL>/RenderingControl/event</eventSubURL>
+        <SCPDURL>/RenderingControl/scpd.xml</SCPDURL>
+      </service>
+    </serviceList>
+  </device>
+</root>"#,
+            escape_xml(name)
+        )
+    }
+}
+
+async fn serve(
+    mut stream: TcpStream,
+    state: &Mutex<StandInState>,
+    description: &str,
+) -> std::io::Result<()> {
+    let mut request = Vec::new();
+    let mut buffer = [0u8; 4096];
+    let header_end = loop {
+        let len = stream.read(&mut buffer).await?;
+        if len == 0 {
+            return Ok(());
+        }
+        request.extend_from_slice(&buffer[..len]);
+        if let Some(end) = request.windows(4).position(|w| w == b"\r\n\r\n") {
+            break end + 4;
+        }
+    };
+    let head = String::from_utf8_lossy(&request[..header_end]).to_string();
+    let content_length: usize = header(&head, "Content-Length")
+        .and_then(|length| length.parse().ok())
+        .unwrap_or(0);
+    while request.len() < header_end + content_length {
+        let len = stream.read(&mut buffer).await?;
+        if len == 0 {
+            break;
+        }
+        request.extend_from_slice(&buffer[..len]);
+    }
+    let body = String::from_utf8_lossy(&request[header_end..]);
+
+    let path = head.split_whitespace().nth(1).unwrap_or("/");
+    let (status, response) = if path == "/description.xml" {
+        ("200 OK", description.to_string())
+    } else if path.ends_with("/control") {
+        let action = header(&head, "SOAPAction")
+            .and_then(|action| action.trim_matches('"').rsplit_once('#'))
+            .map(|(service, action)| (service.to_string(), action.to_string()));
+        match action {
+            Some((service, action)) => {
+                let mut state = state.lock().unwrap();
+                state.tick();
+                match handle_action(&mut state, &action, &body) {
+                    Some(arguments) => ("200 OK", soap_response(&service, &action, &arguments)),
+                    None => ("500 Internal Server Error", soap_fault(&action)),
+                }
+            }
+            None => ("400 Bad Request", String::new()),
+        }
+    } else {
+        ("404 Not Found", String::new())
+    };
+
+    let reply = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: text/xml; charset=\"utf-8\"\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{response}",
+        response.len()
+    );
+    stream.write_all(reply.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+/// Applies one action, returning the reply arguments or `None` for a fault.
+fn handle_action(
+    state: &mut StandInState,
+    action: &str,
+    body: &str,
+) -> Option<Vec<(&'static str, String)>> {
+    state.actions.push(action.to_string());
+    match action {
+        "SetAVTransportURI" => {
+            let metadata = element_text(body, "CurrentURIMetaData").unwrap_or_default();
+            state.uri = element_text(body, "CurrentURI")?;
+            state.title = element_text(&metadata, "title").unwrap_or_default();
+            state.duration_ms = metadata
+                .split("duration=\"")
+                .nth(1)
+                .and_then(|rest| rest.split('"').next())
+                .and_then(parse_time)
+                .unwrap_or(0)
+                * 1000;
+            state.transport_state = "STOPPED";
+            state.offset_ms = 0;
+            state.playing_since = None;
+        }
+        "Play" => {
+            if state.uri.is_empty() {
+                return None;
+            }
+            if state.playing_since.is_none() {
+                state.playing_since = Some(Instant::now());
+            }
+            state.transport_state = "PLAYING";
+        }
+        "Pause" => {
+            state.offset_ms = state.position_ms();
+            state.playing_since = None;
+            state.transport_state = "PAUSED_PLAYBACK";
+        }
+        "Stop" => {
+            state.offset_ms = 0;
+            state.playing_since = None;
+            state.transport_state = "STOPPED";
+        }
+        "Seek" => {
+            state.offset_ms = element_text(body, "Target").and_then(|t| parse_time(&t))? * 1000;
+            if state.playing_since.is_some() {
+                state.playing_since = Some(Instant::now());
+            }
+        }
+        "GetTransportInfo" => {
+            return Some(vec![
+                ("CurrentTransportState", state.transport_state.to_string()),
+                ("CurrentTransportStatus", "OK".to_string()),
+                ("CurrentSpeed", "1".to_string()),
+            ]);
+        }
+        "GetPositionInfo" => {
+            return Some(vec![
+                ("Track", "1".to_string()),
+                ("TrackDuration", format_time(state.duration_ms)),
+                ("TrackURI", escape_xml(&state.uri)),
+                ("RelTime", format_time(state.position_ms())),
+                ("AbsTime", format_time(state.position_ms())),
+            ]);
+        }
+        "SetVolume" => {
+            state.volume = element_text(body, "DesiredVolume")?.parse().ok()?;
+        }
+        "SetMute" => {
+            state.muted = element_text(body, "DesiredMute")? == "1";
+        }
+        _ => return None,
+    }
+    Some(Vec::new())
+}
+
+fn soap_response(service: &str, action: &str, arguments: &[(&str, String)]) -> String {
+    let arguments: String = arguments
+        .iter()
+        .map(|(name, value)| format!("<{name}>{value}</{name}>"))
+        .collect();
+    format!(
+        r#"<?xml version="1.0"?><s:Envelope xmlns:s="http://schemas.xmlsoap.org/soap/envelope/" s:encodingStyle="http://schemas.xmlsoap.org/soap/encoding/"><s:Body><u:{action}Response xmlns:u="{service}">{arguments}</u:{action}Response></s:Body></s:Envelope>"#
+    )
+}
+
+fn soap_fault(action: &str) -> String {
+    format!(
+        r#"<?xml version="1.0"?><s:Envelope xmlns:s="http://schemas.xmlsoap.org/soap/envelope/"><s:Body><s:Fault><faultcode>s:Client</faultcode><faultstring>UPnPError</faultstring><detail><UPnPError xmlns="urn:schemas-upnp-org:control-1-0"><errorCode>701</errorCode><errorDescription>{action} not possible now</errorDescription></UPnPError></detail></s:Fault></s:Body></s:Envelope>"#
+    )
+}
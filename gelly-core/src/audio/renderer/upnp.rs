@@ -0,0 +1,531 @@
+//! UPnP/DLNA MediaRenderer control: SSDP discovery plus the handful of
+//! AVTransport and RenderingControl SOAP actions needed to play one stream.
+
+use std::collections::HashSet;
+use std::time::Duration;
+
+use async_channel::{Receiver, Sender};
+use log::{debug, warn};
+use reqwest::{Client, StatusCode};
+use tokio::net::UdpSocket;
+use tokio::time::Instant;
+
+use crate::audio::player::{PlayerEvent, PlayerState};
+use crate::audio::renderer::{
+    RendererCommand, RendererDevice, RendererError, RendererKind, TrackMetadata,
+};
+use crate::xml::{element_text, elements, escape_xml};
+
+pub const MEDIA_RENDERER: &str = "urn:schemas-upnp-org:device:MediaRenderer:1";
+const AV_TRANSPORT: &str = "urn:schemas-upnp-org:service:AVTransport:1";
+const RENDERING_CONTROL: &str = "urn:schemas-upnp-org:service:RenderingControl:1";
+const SSDP_ADDRESS: &str = "239.255.255.250:1900";
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+pub async fn discover(timeout: Duration) -> Result<Vec<RendererDevice>, RendererError> {
+    let socket = UdpSocket::bind("0.0.0.0:0").await?;
+    let search = format!(
+        "M-SEARCH * HTTP/1.1\r\nHOST: {SSDP_ADDRESS}\r\nMAN: \"ssdp:discover\"\r\nMX: {}\r\nST: {MEDIA_RENDERER}\r\n\r\n",
+        timeout.as_secs().clamp(1, 5)
+    );
+    socket.send_to(search.as_bytes(), SSDP_ADDRESS).await?;
+
+    let mut locations = HashSet::new();
+    let deadline = Instant::now() + timeout;
+    let mut buffer = [0u8; 2048];
+    while let Ok(Ok((len, from))) =
+        tokio::time::timeout_at(deadline, socket.recv_from(&mut buffer)).await
+    {
+        let response = String::from_utf8_lossy(&buffer[..len]);
+        match header(&response, "LOCATION") {
+            Some(location) => {
+                locations.insert(location.to_string());
+            }
+            None => debug!("SSDP response from {} without a location", from),
+        }
+    }
+
+    let client = Client::new();
+    let mut devices = Vec::new();
+    for location in locations {
+        let description = match client.get(&location).send().await {
+            Ok(response) => response.text().await,
+            Err(e) => Err(e),
+        };
+        match description {
+            Ok(description) => {
+                if let Some(device) = parse_device(&description, &location) {
+                    devices.push(device);
+                }
+            }
+            Err(e) => warn!("Failed to read renderer description {}: {}", location, e),
+        }
+    }
+    Ok(devices)
+}
+
+fn parse_device(description: &str, location: &str) -> Option<RendererDevice> {
+    if !description.contains(AV_TRANSPORT) {
+        return None;
+    }
+    Some(RendererDevice {
+        id: element_text(description, "UDN").unwrap_or_else(|| location.to_string()),
+        name: element_text(description, "friendlyName").unwrap_or_else(|| location.to_string()),
+        kind: RendererKind::Upnp,
+        location: location.to_string(),
+    })
+}
+
+/// Drives one renderer until `commands` is closed, polling its transport state
+/// to turn it into player events.
+pub(crate) async fn run(
+    location: String,
+    commands: Receiver<RendererCommand>,
+    events: Sender<PlayerEvent>,
+) {
+    let client = match UpnpClient::connect(&location).await {
+        Ok(client) => client,
+        Err(e) => {
+            let _ = events.send(PlayerEvent::Error(e.to_string())).await;
+            return;
+        }
+    };
+    let mut poll = tokio::time::interval(POLL_INTERVAL);
+    let mut last_state = None;
+    // A stop we asked for is not the end of the track
+    let mut expect_stop = false;
+
+    loop {
+        tokio::select! {
+            command = commands.recv() => {
+                let Ok(command) = command else { break };
+                if matches!(command, RendererCommand::Load(..) | RendererCommand::Stop) {
+                    expect_stop = true;
+                }
+                if let Err(e) = client.execute(command).await {
+                    warn!("Renderer command failed: {}", e);
+                    let _ = events.send(PlayerEvent::Error(e.to_string())).await;
+                }
+            }
+            _ = poll.tick() => {
+                let state = match client.transport_state().await {
+                    Ok(state) => state,
+                    Err(e) => {
+                        warn!("Failed to poll renderer: {}", e);
+                        continue;
+                    }
+                };
+                if state == PlayerState::Playing {
+                    expect_stop = false;
+                    if let Ok((position, duration)) = client.position().await {
+                        let _ = events.send(PlayerEvent::PositionChanged(position)).await;
+                        if duration > 0 {
+                            let _ = events.send(PlayerEvent::DurationChanged(duration)).await;
+                        }
+                    }
+                }
+                if last_state.as_ref() == Some(&state) {
+                    continue;
+                }
+                let finished = last_state == Some(PlayerState::Playing)
+                    && state == PlayerState::Stopped
+                    && !expect_stop;
+                last_state = Some(state.clone());
+                let _ = events.send(PlayerEvent::StateChanged(state)).await;
+                if finished {
+                    let _ = events.send(PlayerEvent::EndOfStream).await;
+                }
+            }
+        }
+    }
+    debug!("Renderer {} disconnected", location);
+}
+
+#[derive(Debug, Clone)]
+pub struct UpnpClient {
+    http: Client,
+    av_transport_url: String,
+    rendering_control_url: Option<String>,
+}
+
+impl UpnpClient {
+    pub async fn connect(location: &str) -> Result<Self, RendererError> {
+        let http = Client::new();
+        let description = http.get(location).send().await?.text().await?;
+        let base = element_text(&description, "URLBase").unwrap_or_else(|| location.to_string());
+        let mut av_transport_url = None;
+        let mut rendering_control_url = None;
+        for service in elements(&description, "service") {
+            let (Some(service_type), Some(control_url)) = (
+                element_text(service, "serviceType"),
+                element_text(service, "controlURL"),
+            ) else {
+                continue;
+            };
+            let url = resolve_url(&base, &control_url);
+            if service_type == AV_TRANSPORT {
+                av_transport_url = Some(url);
+            } else if service_type == RENDERING_CONTROL {
+                rendering_control_url = Some(url);
+            }
+        }
+        let av_transport_url = av_transport_url.ok_or_else(|| {
+            RendererError::InvalidResponse(format!("{location} has no AVTransport service"))
+        })?;
+        Ok(Self {
+            http,
+            av_transport_url,
+            rendering_control_url,
+        })
+    }
+
+    async fn execute(&self, command: RendererCommand) -> Result<(), RendererError> {
+        match command {
+            RendererCommand::Load(uri, metadata) => self.set_uri(&uri, &metadata).await,
+            RendererCommand::Play => self.play().await,
+            RendererCommand::Pause => self.pause().await,
+            RendererCommand::Stop => self.stop().await,
+            RendererCommand::Seek(position_ms) => self.seek(position_ms).await,
+            RendererCommand::Volume(volume) => {
+                self.set_volume((volume * 100.0).round() as u32).await
+            }
+            RendererCommand::Mute(muted) => self.set_mute(muted).await,
+        }
+    }
+
+    pub async fn set_uri(&self, uri: &str, metadata: &TrackMetadata) -> Result<(), RendererError> {
+        let didl = didl_metadata(uri, metadata);
+        self.transport_action(
+            "SetAVTransportURI",
+            &[("CurrentURI", uri), ("CurrentURIMetaData", &didl)],
+        )
+        .await?;
+        Ok(())
+    }
+
+    pub async fn play(&self) -> Result<(), RendererError> {
+        self.transport_action("Play", &[("Speed", "1")]).await?;
+        Ok(())
+    }
+
+    pub async fn pause(&self) -> Result<(), RendererError> {
+        self.transport_action("Pause", &[]).await?;
+        Ok(())
+    }
+
+    pub async fn stop(&self) -> Result<(), RendererError> {
+        self.transport_action("Stop", &[]).await?;
+        Ok(())
+    }
+
+    pub async fn seek(&self, position_ms: u64) -> Result<(), RendererError> {
+        let target = format_time(position_ms);
+        self.transport_action("Seek", &[("Unit", "REL_TIME"), ("Target", &target)])
+            .await?;
+        Ok(())
+    }
+
+    pub async fn transport_state(&self) -> Result<PlayerState, RendererError> {
+        let response = self.transport_action("GetTransportInfo", &[]).await?;
+        let state = element_text(&response, "CurrentTransportState").unwrap_or_default();
+        Ok(match state.as_str() {
+            "PLAYING" | "TRANSITIONING" => PlayerState::Playing,
+            "PAUSED_PLAYBACK" | "PAUSED_RECORDING" => PlayerState::Paused,
+            _ => PlayerState::Stopped,
+        })
+    }
+
+    /// Position and duration of the current track in seconds
+    pub async fn position(&self) -> Result<(u64, u64), RendererError> {
+        let response = self.transport_action("GetPositionInfo", &[]).await?;
+        let position = element_text(&response, "RelTime")
+            .and_then(|t| parse_time(&t))
+            .unwrap_or(0);
+        let duration = element_text(&response, "TrackDuration")
+            .and_then(|t| parse_time(&t))
+            .unwrap_or(0);
+        Ok((position, duration))
+    }
+
+    /// Volume from 0 to 100
+    pub async fn set_volume(&self, volume: u32) -> Result<(), RendererError> {
+        let volume = volume.min(100).to_string();
+        self.rendering_action(
+            "SetVolume",
+            &[("Channel", "Master"), ("DesiredVolume", &volume)],
+        )
+        .await
+    }
+
+    pub async fn set_mute(&self, muted: bool) -> Result<(), RendererError> {
+        let muted = if muted { "1" } else { "0" };
+        self.rendering_action("SetMute", &[("Channel", "Master"), ("DesiredMute", muted)])
+            .await
+    }
+
+    async fn transport_action(
+        &self,
+        action: &str,
+        arguments: &[(&str, &str)],
+    ) -> Result<String, RendererError> {
+        self.soap(&self.av_transport_url, AV_TRANSPORT, action, arguments)
+            .await
+    }
+
+    async fn rendering_action(
+        &self,
+        action: &str,
+        arguments: &[(&str, &str)],
+    ) -> Result<(), RendererError> {
+        // Some renderers only do fixed volume
+        let Some(url) = &self.rendering_control_url else {
+            return Ok(());
+        };
+        self.soap(url, RENDERING_CONTROL, action, arguments).await?;
+        Ok(())
+    }
+
+    async fn soap(
+        &self,
+        url: &str,
+        service: &str,
+        action: &str,
+        arguments: &[(&str, &str)],
+    ) -> Result<String, RendererError> {
+        let arguments: String = std::iter::once(("InstanceID", "0"))
+            .chain(arguments.iter().copied())
+            .map(|(name, value)| format!("<{name}>{}</{name}>", escape_xml(value)))
+            .collect();
+        let body = format!(
+            r#"<?xml version="1.0" encoding="utf-8"?><s:Envelope xmlns:s="http://schemas.xmlsoap.org/soap/envelope/" s:encodingStyle="http://schemas.xmlsoap.org/soap/encoding/"><s:Body><u:{action} xmlns:u="{service}">{arguments}</u:{action}></s:Body></s:Envelope>"#
+        );
+        let response = self
+            .http
+            .post(url)
+            .header("Content-Type", r#"text/xml; charset="utf-8""#)
+            .header("SOAPAction", format!(r#""{service}#{action}""#))
+            .body(body)
+            .send()
+            .await?;
+        let status = response.status();
+        let text = response.text().await?;
+        if status != StatusCode::OK {
+            let message = element_text(&text, "errorDescription").unwrap_or(status.to_string());
+            return Err(RendererError::Action {
+                action: action.to_string(),
+                message,
+            });
+        }
+        Ok(text)
+    }
+}
+
+fn didl_metadata(uri: &str, metadata: &TrackMetadata) -> String {
+    let art = metadata
+        .image_url
+        .as_deref()
+        .map(|url| format!("<upnp:albumArtURI>{}</upnp:albumArtURI>", escape_xml(url)))
+        .unwrap_or_default();
+    format!(
+        r#"<DIDL-Lite xmlns="urn:schemas-upnp-org:metadata-1-0/DIDL-Lite/" xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:upnp="urn:schemas-upnp-org:metadata-1-0/upnp/"><item id="0" parentID="-1" restricted="1"><dc:title>{}</dc:title><upnp:artist>{}</upnp:artist><upnp:album>{}</upnp:album>{art}<upnp:class>object.item.audioItem.musicTrack</upnp:class><res protocolInfo="http-get:*:audio/*:*" duration="{}">{}</res></item></DIDL-Lite>"#,
+        escape_xml(&metadata.title),
+        escape_xml(&metadata.artist),
+        escape_xml(&metadata.album),
+        format_time(metadata.duration_seconds * 1000),
+        escape_xml(uri),
+    )
+}
+
+fn resolve_url(base: &str, path: &str) -> String {
+    if path.starts_with("http://") || path.starts_with("https://") {
+        return path.to_string();
+    }
+    // Everything up to the path of the base url
+    let authority_end = base
+        .find("://")
+        .and_then(|scheme| base[scheme + 3..].find('/').map(|i| scheme + 3 + i))
+        .unwrap_or(base.len());
+    format!(
+        "{}/{}",
+        &base[..authority_end],
+        path.trim_start_matches('/')
+    )
+}
+
+pub(super) fn header<'a>(response: &'a str, name: &str) -> Option<&'a str> {
+    response.lines().find_map(|line| {
+        let (key, value) = line.split_once(':')?;
+        key.trim()
+            .eq_ignore_ascii_case(name)
+            .then_some(value.trim())
+    })
+}
+
+/// "H:MM:SS" as used by Seek and GetPositionInfo
+pub fn format_time(position_ms: u64) -> String {
+    let seconds = position_ms / 1000;
+    format!(
+        "{}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+/// Parses "H:MM:SS" with optional fraction into whole seconds.
+pub fn parse_time(time: &str) -> Option<u64> {
+    let time = time.split('.').next()?;
+    let mut parts = time.split(':').map(|part| part.trim().parse::<u64>());
+    let (Some(Ok(hours)), Some(Ok(minutes)), Some(Ok(seconds)), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return None;
+    };
+    Some(hours * 3600 + minutes * 60 + seconds)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::renderer::stand_in::StandInRenderer;
+
+    #[test]
+    fn test_time_round_trip() {
+        assert_eq!(format_time(3_725_400), "1:02:05");
+        assert_eq!(parse_time("1:02:05"), Some(3725));
+        assert_eq!(parse_time("0:00:07.250"), Some(7));
+        assert_eq!(parse_time("NOT_IMPLEMENTED"), None);
+    }
+
+    #[test]
+    fn test_parse_device_and_resolve_urls() {
+        let description = StandInRenderer::description("Kitchen & Co");
+        let device = parse_device(&description, "http://10.0.0.5:4000/description.xml").unwrap();
+        assert_eq!(device.name, "Kitchen & Co");
+        assert_eq!(device.kind, RendererKind::Upnp);
+        assert_eq!(
+            resolve_url(
+                "http://10.0.0.5:4000/description.xml",
+                "/AVTransport/control"
+            ),
+            "http://10.0.0.5:4000/AVTransport/control"
+        );
+        assert_eq!(
+            resolve_url("http://10.0.0.5:4000", "control"),
+            "http://10.0.0.5:4000/control"
+        );
+    }
+
+    #[test]
+    fn test_didl_metadata_is_escaped() {
+        let metadata = TrackMetadata {
+            title: "Rock & Roll".to_string(),
+            duration_seconds: 61,
+            ..Default::default()
+        };
+        let didl = didl_metadata("http://host/a?x=1&y=2", &metadata);
+        assert!(didl.contains("<dc:title>Rock &amp; Roll</dc:title>"));
+        assert!(didl.contains(r#"duration="0:01:01""#));
+        assert!(didl.contains(">http://host/a?x=1&amp;y=2</res>"));
+    }
+
+    #[tokio::test]
+    async fn test_client_against_stand_in() {
+        let renderer = StandInRenderer::start("127.0.0.1:0", "Stand-in")
+            .await
+            .unwrap();
+        let client = UpnpClient::connect(&renderer.description_url())
+            .await
+            .unwrap();
+
+        let metadata = TrackMetadata {
+            title: "Song".to_string(),
+            duration_seconds: 180,
+            ..Default::default()
+        };
+        client
+            .set_uri("http://server/Audio/1", &metadata)
+            .await
+            .unwrap();
+        assert_eq!(
+            client.transport_state().await.unwrap(),
+            PlayerState::Stopped
+        );
+        client.play().await.unwrap();
+        assert_eq!(
+            client.transport_state().await.unwrap(),
+            PlayerState::Playing
+        );
+        client.seek(65_000).await.unwrap();
+        let (position, duration) = client.position().await.unwrap();
+        assert_eq!(position, 65);
+        assert_eq!(duration, 180);
+        client.pause().await.unwrap();
+        assert_eq!(client.transport_state().await.unwrap(), PlayerState::Paused);
+        client.set_volume(40).await.unwrap();
+        client.set_mute(true).await.unwrap();
+
+        let state = renderer.state();
+        assert_eq!(state.uri, "http://server/Audio/1");
+        assert_eq!(state.title, "Song");
+        assert_eq!(state.volume, 40);
+        assert!(state.muted);
+        assert_eq!(
+            renderer.actions(),
+            vec![
+                "SetAVTransportURI",
+                "GetTransportInfo",
+                "Play",
+                "GetTransportInfo",
+                "Seek",
+                "GetPositionInfo",
+                "Pause",
+                "GetTransportInfo",
+                "SetVolume",
+                "SetMute",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_run_reports_end_of_stream() {
+        let renderer = StandInRenderer::start("127.0.0.1:0", "Stand-in")
+            .await
+            .unwrap();
+        let (commands, command_receiver) = async_channel::unbounded();
+        let (event_sender, events) = async_channel::unbounded();
+        tokio::spawn(run(
+            renderer.description_url(),
+            command_receiver,
+            event_sender,
+        ));
+
+        let metadata = TrackMetadata {
+            duration_seconds: 2,
+            ..Default::default()
+        };
+        commands
+            .send(RendererCommand::Load(
+                "http://server/Audio/1".into(),
+                metadata,
+            ))
+            .await
+            .unwrap();
+        commands.send(RendererCommand::Play).await.unwrap();
+
+        let mut saw_playing = false;
+        loop {
+            let event = tokio::time::timeout(Duration::from_secs(10), events.recv())
+                .await
+                .expect("renderer never finished the track")
+                .unwrap();
+            match event {
+                PlayerEvent::StateChanged(PlayerState::Playing) => saw_playing = true,
+                PlayerEvent::EndOfStream => break,
+                _ => {}
+            }
+        }
+        assert!(saw_playing);
+        commands.close();
+    }
+}
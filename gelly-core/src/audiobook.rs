@@ -0,0 +1,67 @@
+//! Getting around an audiobook by its chapters. Positions are in seconds.
+
+use crate::jellyfin::api::Chapter;
+
+/// How far the skip buttons jump
+pub const SKIP_SECONDS: u32 = 30;
+
+/// Going back this far into a chapter starts it over rather than going to the
+/// one before
+const RESTART_CHAPTER_SECONDS: u64 = 3;
+
+/// The chapter `position` is in
+pub fn chapter_at(chapters: &[Chapter], position: u64) -> Option<usize> {
+    chapters
+        .iter()
+        .rposition(|chapter| chapter.start_seconds() <= position)
+}
+
+pub fn previous_chapter_start(chapters: &[Chapter], position: u64) -> Option<u64> {
+    let index = chapter_at(chapters, position)?;
+    let start = chapters[index].start_seconds();
+    if position >= start + RESTART_CHAPTER_SECONDS || index == 0 {
+        return Some(start);
+    }
+    Some(chapters[index - 1].start_seconds())
+}
+
+pub fn next_chapter_start(chapters: &[Chapter], position: u64) -> Option<u64> {
+    chapters
+        .iter()
+        .map(Chapter::start_seconds)
+        .find(|start| *start > position)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chapters() -> Vec<Chapter> {
+        [0, 600, 1200]
+            .into_iter()
+            .enumerate()
+            .map(|(i, start)| Chapter {
+                name: format!("Chapter {}", i + 1),
+                start_ticks: start * 10_000_000,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_chapter_navigation() {
+        let chapters = chapters();
+        assert_eq!(chapter_at(&chapters, 0), Some(0));
+        assert_eq!(chapter_at(&chapters, 900), Some(1));
+        assert_eq!(chapter_at(&chapters, 5000), Some(2));
+        assert_eq!(chapter_at(&[], 10), None);
+
+        assert_eq!(next_chapter_start(&chapters, 0), Some(600));
+        assert_eq!(next_chapter_start(&chapters, 1200), None);
+
+        // Well into a chapter goes back to its start, right at the start goes
+        // to the one before
+        assert_eq!(previous_chapter_start(&chapters, 900), Some(600));
+        assert_eq!(previous_chapter_start(&chapters, 601), Some(0));
+        assert_eq!(previous_chapter_start(&chapters, 1), Some(0));
+    }
+}
@@ -0,0 +1,475 @@
+use gio::prelude::SettingsExt;
+use reqwest::StatusCode;
+use thiserror::Error;
+
+use crate::config::{
+    self, BackendType, retrieve_jellyfin_api_token, retrieve_subsonic_password, settings,
+};
+use crate::jellyfin::{
+    Jellyfin,
+    api::{
+        AlbumInfo, ArtistInfo, Audiobook, FavoriteDtoList, Folder, ImageType, ItemType,
+        LibraryDtoList, LyricsResponse, MusicDtoList, PlaybackInfo, PlaybackReport,
+        PlaybackReportStatus, PlaylistDetails, PlaylistDtoList, PlaylistItems, PodcastChannel,
+        PodcastEpisode, RadioStation, RatingDtoList, SongCreditsDto, SongFileDto,
+    },
+};
+use crate::models::Category;
+use crate::subsonic::Subsonic;
+
+#[derive(Error, Debug)]
+pub enum BackendError {
+    #[error("Transport error: {0}")]
+    Transport(#[from] reqwest::Error),
+
+    #[error("HTTP error: {status} - {message}")]
+    Http { status: StatusCode, message: String },
+
+    #[error("Authentication failed: {message}")]
+    AuthenticationFailed { message: String },
+
+    #[error("JSON parsing error: {0}")]
+    JsonParsing(#[from] serde_json::Error),
+}
+
+#[derive(Debug, Clone)]
+pub enum Backend {
+    Jellyfin(Jellyfin),
+    Subsonic(Subsonic),
+}
+
+impl Backend {
+    /// Builds the backend from the stored settings, reading credentials from the keyring.
+    pub fn from_settings() -> Self {
+        let host = settings().string("hostname");
+
+        match config::get_backend_type() {
+            BackendType::Jellyfin => {
+                let user_id = settings().string("user-id");
+                let token = retrieve_jellyfin_api_token(host.as_str(), user_id.as_str())
+                    .unwrap_or_default();
+                Self::Jellyfin(Jellyfin::new(host.as_str(), &token, user_id.as_str()))
+            }
+            BackendType::Subsonic => {
+                let username = settings().string("subsonic-username");
+                let password = retrieve_subsonic_password(host.as_str(), username.as_str())
+                    .unwrap_or_default();
+                Self::Subsonic(Subsonic::new(host.as_str(), username.as_str(), &password))
+            }
+        }
+    }
+
+    pub fn is_authenticated(&self) -> bool {
+        match self {
+            Self::Jellyfin(jellyfin) => jellyfin.is_authenticated(),
+            Self::Subsonic(subsonic) => subsonic.is_authenticated(),
+        }
+    }
+
+    /// SyncPlay and the session websocket only exist on Jellyfin servers.
+    pub fn jellyfin(&self) -> Option<&Jellyfin> {
+        match self {
+            Self::Jellyfin(jellyfin) => Some(jellyfin),
+            Self::Subsonic(_) => None,
+        }
+    }
+
+    pub fn subsonic(&self) -> Option<&Subsonic> {
+        match self {
+            Self::Jellyfin(_) => None,
+            Self::Subsonic(subsonic) => Some(subsonic),
+        }
+    }
+
+    pub async fn get_views(&self) -> Result<LibraryDtoList, BackendError> {
+        match self {
+            Self::Jellyfin(jellyfin) => jellyfin.get_views().await,
+            Self::Subsonic(subsonic) => subsonic.get_views().await,
+        }
+    }
+
+    pub async fn get_library(&self, library_id: &str) -> Result<MusicDtoList, BackendError> {
+        match self {
+            Self::Jellyfin(jellyfin) => jellyfin.get_library(library_id).await,
+            Self::Subsonic(subsonic) => subsonic.get_library(library_id).await,
+        }
+    }
+
+    /// Composers or labels by song for browsing by them. Subsonic songs come
+    /// with theirs, so there's nothing to fetch.
+    pub async fn get_song_credits(
+        &self,
+        library_id: &str,
+        category: Category,
+    ) -> Result<Option<Vec<SongCreditsDto>>, BackendError> {
+        let fields = match category {
+            Category::Composer => "People",
+            Category::Label => "Studios",
+            _ => return Ok(None),
+        };
+        match self {
+            Self::Jellyfin(jellyfin) => jellyfin
+                .get_song_credits(library_id, fields)
+                .await
+                .map(Some),
+            Self::Subsonic(_) => Ok(None),
+        }
+    }
+
+    /// Where songs' files are and their MusicBrainz ids, for playlist files.
+    /// `None` asks for all of the library's songs. Subsonic songs come with
+    /// theirs, so there's nothing to fetch.
+    pub async fn get_song_files(
+        &self,
+        library_id: &str,
+        item_ids: Option<&[String]>,
+    ) -> Result<Option<Vec<SongFileDto>>, BackendError> {
+        match (self, item_ids) {
+            (Self::Jellyfin(jellyfin), Some(item_ids)) => jellyfin
+                .get_song_files(library_id, item_ids)
+                .await
+                .map(Some),
+            (Self::Jellyfin(jellyfin), None) => {
+                jellyfin.get_all_song_files(library_id).await.map(Some)
+            }
+            (Self::Subsonic(_), _) => Ok(None),
+        }
+    }
+
+    pub async fn get_favorites(&self) -> Result<FavoriteDtoList, BackendError> {
+        match self {
+            Self::Jellyfin(jellyfin) => jellyfin.get_favorites().await,
+            Self::Subsonic(subsonic) => subsonic.get_favorites().await,
+        }
+    }
+
+    pub async fn set_favorite(
+        &self,
+        item_id: &str,
+        item_type: &ItemType,
+        is_favorite: bool,
+    ) -> Result<(), BackendError> {
+        match self {
+            Self::Jellyfin(jellyfin) => jellyfin.set_favorite(item_id, is_favorite).await,
+            Self::Subsonic(subsonic) => {
+                subsonic.set_favorite(item_id, item_type, is_favorite).await
+            }
+        }
+    }
+
+    pub async fn get_album_ratings(&self, library_id: &str) -> Result<RatingDtoList, BackendError> {
+        match self {
+            Self::Jellyfin(jellyfin) => jellyfin.get_album_ratings(library_id).await,
+            Self::Subsonic(subsonic) => subsonic.get_album_ratings(library_id).await,
+        }
+    }
+
+    /// `stars` from one to five, zero clears the rating
+    pub async fn set_rating(&self, item_id: &str, stars: u32) -> Result<(), BackendError> {
+        match self {
+            Self::Jellyfin(jellyfin) => jellyfin.set_rating(item_id, stars).await,
+            Self::Subsonic(subsonic) => subsonic.set_rating(item_id, stars).await,
+        }
+    }
+
+    pub async fn get_playlists(&self) -> Result<PlaylistDtoList, BackendError> {
+        match self {
+            Self::Jellyfin(jellyfin) => jellyfin.get_playlists().await,
+            Self::Subsonic(subsonic) => subsonic.get_playlists().await,
+        }
+    }
+
+    pub async fn get_playlist_items(
+        &self,
+        playlist_id: &str,
+    ) -> Result<PlaylistItems, BackendError> {
+        match self {
+            Self::Jellyfin(jellyfin) => jellyfin.get_playlist_items(playlist_id).await,
+            Self::Subsonic(subsonic) => subsonic.get_playlist_items(playlist_id).await,
+        }
+    }
+
+    pub async fn new_playlist(
+        &self,
+        name: &str,
+        items: Vec<String>,
+    ) -> Result<String, BackendError> {
+        match self {
+            Self::Jellyfin(jellyfin) => jellyfin.new_playlist(name, items).await,
+            Self::Subsonic(subsonic) => subsonic.new_playlist(name, items).await,
+        }
+    }
+
+    pub async fn add_playlist_items(
+        &self,
+        playlist_id: &str,
+        item_ids: &[String],
+    ) -> Result<(), BackendError> {
+        match self {
+            Self::Jellyfin(jellyfin) => jellyfin.add_playlist_items(playlist_id, item_ids).await,
+            Self::Subsonic(subsonic) => subsonic.add_playlist_items(playlist_id, item_ids).await,
+        }
+    }
+
+    pub async fn move_playlist_item(
+        &self,
+        playlist_id: &str,
+        item_id: &str,
+        new_index: i32,
+    ) -> Result<(), BackendError> {
+        match self {
+            Self::Jellyfin(jellyfin) => {
+                jellyfin
+                    .move_playlist_item(playlist_id, item_id, new_index)
+                    .await
+            }
+            Self::Subsonic(subsonic) => {
+                subsonic
+                    .move_playlist_item(playlist_id, item_id, new_index)
+                    .await
+            }
+        }
+    }
+
+    pub async fn remove_playlist_item(
+        &self,
+        playlist_id: &str,
+        item_id: &str,
+    ) -> Result<(), BackendError> {
+        match self {
+            Self::Jellyfin(jellyfin) => jellyfin.remove_playlist_item(playlist_id, item_id).await,
+            Self::Subsonic(subsonic) => subsonic.remove_playlist_item(playlist_id, item_id).await,
+        }
+    }
+
+    pub async fn get_playlist_details(
+        &self,
+        playlist_id: &str,
+    ) -> Result<PlaylistDetails, BackendError> {
+        match self {
+            Self::Jellyfin(jellyfin) => jellyfin.get_playlist_details(playlist_id).await,
+            Self::Subsonic(subsonic) => subsonic.get_playlist_details(playlist_id).await,
+        }
+    }
+
+    pub async fn update_playlist(
+        &self,
+        playlist_id: &str,
+        details: &PlaylistDetails,
+    ) -> Result<(), BackendError> {
+        match self {
+            Self::Jellyfin(jellyfin) => jellyfin.update_playlist(playlist_id, details).await,
+            Self::Subsonic(subsonic) => subsonic.update_playlist(playlist_id, details).await,
+        }
+    }
+
+    /// Subsonic keeps the description with the rest of the details, so it's
+    /// already saved by `update_playlist`
+    pub async fn set_playlist_description(
+        &self,
+        playlist_id: &str,
+        description: &str,
+    ) -> Result<(), BackendError> {
+        match self {
+            Self::Jellyfin(jellyfin) => {
+                jellyfin
+                    .set_playlist_description(playlist_id, description)
+                    .await
+            }
+            Self::Subsonic(_) => Ok(()),
+        }
+    }
+
+    /// Subsonic has no way to upload images, check `jellyfin()` first
+    pub async fn set_playlist_image(
+        &self,
+        playlist_id: &str,
+        image: &[u8],
+        content_type: &str,
+    ) -> Result<(), BackendError> {
+        match self {
+            Self::Jellyfin(jellyfin) => {
+                jellyfin
+                    .set_primary_image(playlist_id, image, content_type)
+                    .await
+            }
+            Self::Subsonic(_) => Err(BackendError::Http {
+                status: StatusCode::NOT_IMPLEMENTED,
+                message: "Subsonic servers don't accept playlist images".to_string(),
+            }),
+        }
+    }
+
+    pub async fn delete_item(&self, item_id: &str) -> Result<(), BackendError> {
+        match self {
+            Self::Jellyfin(jellyfin) => jellyfin.delete_item(item_id).await,
+            Self::Subsonic(subsonic) => subsonic.delete_item(item_id).await,
+        }
+    }
+
+    pub async fn request_library_rescan(&self, library_id: &str) -> Result<(), BackendError> {
+        match self {
+            Self::Jellyfin(jellyfin) => jellyfin.request_library_rescan(library_id).await,
+            Self::Subsonic(subsonic) => subsonic.request_library_rescan(library_id).await,
+        }
+    }
+
+    pub async fn get_image(
+        &self,
+        item_id: &str,
+        image_type: ImageType,
+        scale: f32,
+    ) -> Result<Vec<u8>, BackendError> {
+        match self {
+            Self::Jellyfin(jellyfin) => jellyfin.get_image(item_id, image_type, scale).await,
+            Self::Subsonic(subsonic) => subsonic.get_image(item_id, image_type, scale).await,
+        }
+    }
+
+    pub fn get_stream_uri(&self, item_id: &str) -> String {
+        match self {
+            Self::Jellyfin(jellyfin) => jellyfin.get_stream_uri(item_id),
+            Self::Subsonic(subsonic) => subsonic.get_stream_uri(item_id),
+        }
+    }
+
+    pub async fn get_playback_info(&self, item_id: &str) -> Result<PlaybackInfo, BackendError> {
+        match self {
+            Self::Jellyfin(jellyfin) => jellyfin.get_playback_info(item_id).await,
+            Self::Subsonic(subsonic) => subsonic.get_playback_info(item_id).await,
+        }
+    }
+
+    pub async fn playback_report(
+        &self,
+        report: &PlaybackReport,
+        state: &PlaybackReportStatus,
+    ) -> Result<(), BackendError> {
+        match self {
+            Self::Jellyfin(jellyfin) => jellyfin.playback_report(report, state).await,
+            Self::Subsonic(subsonic) => subsonic.playback_report(report, state).await,
+        }
+    }
+
+    pub async fn get_album_info(&self, album_id: &str) -> Result<AlbumInfo, BackendError> {
+        match self {
+            Self::Jellyfin(jellyfin) => jellyfin.get_album_info(album_id).await,
+            Self::Subsonic(subsonic) => subsonic.get_album_info(album_id).await,
+        }
+    }
+
+    pub async fn get_artist_info(&self, artist_id: &str) -> Result<ArtistInfo, BackendError> {
+        match self {
+            Self::Jellyfin(jellyfin) => jellyfin.get_artist_info(artist_id).await,
+            Self::Subsonic(subsonic) => subsonic.get_artist_info(artist_id).await,
+        }
+    }
+
+    /// `None` opens the top of the library's directory tree
+    pub async fn get_folder(
+        &self,
+        library_id: &str,
+        folder_id: Option<&str>,
+    ) -> Result<Folder, BackendError> {
+        match self {
+            Self::Jellyfin(jellyfin) => jellyfin.get_folder(library_id, folder_id).await,
+            Self::Subsonic(subsonic) => subsonic.get_folder(library_id, folder_id).await,
+        }
+    }
+
+    /// Every song under the folder, including its subfolders
+    pub async fn get_folder_song_ids(
+        &self,
+        library_id: &str,
+        folder_id: Option<&str>,
+    ) -> Result<Vec<String>, BackendError> {
+        match self {
+            Self::Jellyfin(jellyfin) => jellyfin.get_folder_song_ids(library_id, folder_id).await,
+            Self::Subsonic(subsonic) => subsonic.get_folder_song_ids(library_id, folder_id).await,
+        }
+    }
+
+    pub async fn get_song_folder_id(&self, song_id: &str) -> Result<Option<String>, BackendError> {
+        match self {
+            Self::Jellyfin(jellyfin) => jellyfin.get_song_folder_id(song_id).await,
+            Self::Subsonic(subsonic) => subsonic.get_song_folder_id(song_id).await,
+        }
+    }
+
+    pub async fn get_podcasts(&self) -> Result<Vec<PodcastChannel>, BackendError> {
+        match self {
+            Self::Jellyfin(jellyfin) => jellyfin.get_podcasts().await,
+            Self::Subsonic(subsonic) => subsonic.get_podcasts().await,
+        }
+    }
+
+    pub async fn get_newest_podcast_episodes(
+        &self,
+        count: u32,
+    ) -> Result<Vec<PodcastEpisode>, BackendError> {
+        match self {
+            Self::Jellyfin(jellyfin) => jellyfin.get_newest_podcast_episodes(count).await,
+            Self::Subsonic(subsonic) => subsonic.get_newest_podcast_episodes(count).await,
+        }
+    }
+
+    pub async fn set_played(&self, item_id: &str, played: bool) -> Result<(), BackendError> {
+        match self {
+            Self::Jellyfin(jellyfin) => jellyfin.set_played(item_id, played).await,
+            // Subsonic has no played state, it's only kept locally
+            Self::Subsonic(_) => Ok(()),
+        }
+    }
+
+    pub async fn set_playback_position(
+        &self,
+        item_id: &str,
+        position_ticks: u64,
+    ) -> Result<(), BackendError> {
+        match self {
+            Self::Jellyfin(jellyfin) => {
+                jellyfin
+                    .set_playback_position(item_id, position_ticks)
+                    .await
+            }
+            // Subsonic only keeps a position for the whole play queue
+            Self::Subsonic(_) => Ok(()),
+        }
+    }
+
+    pub async fn get_audiobooks(&self) -> Result<Vec<Audiobook>, BackendError> {
+        match self {
+            Self::Jellyfin(jellyfin) => jellyfin.get_audiobooks().await,
+            // Subsonic has no notion of audiobooks
+            Self::Subsonic(_) => Ok(vec![]),
+        }
+    }
+
+    pub async fn get_radio_stations(&self) -> Result<Vec<RadioStation>, BackendError> {
+        match self {
+            Self::Jellyfin(jellyfin) => jellyfin.get_radio_stations().await,
+            Self::Subsonic(subsonic) => subsonic.get_radio_stations().await,
+        }
+    }
+
+    /// The original file rather than a transcoded stream
+    pub fn get_download_uri(&self, item_id: &str) -> String {
+        match self {
+            Self::Jellyfin(jellyfin) => jellyfin.get_download_uri(item_id),
+            Self::Subsonic(subsonic) => subsonic.get_download_uri(item_id),
+        }
+    }
+
+    pub async fn fetch_lyrics(&self, item_id: &str) -> Result<LyricsResponse, BackendError> {
+        match self {
+            Self::Jellyfin(jellyfin) => jellyfin.fetch_lyrics(item_id).await,
+            Self::Subsonic(subsonic) => subsonic.fetch_lyrics(item_id).await,
+        }
+    }
+}
+
+impl Default for Backend {
+    fn default() -> Self {
+        Self::Jellyfin(Jellyfin::default())
+    }
+}
@@ -0,0 +1,179 @@
+use std::{fs, path::PathBuf};
+
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use thiserror::Error;
+
+use crate::{
+    backend::BackendError,
+    config::APP_ID,
+    jellyfin::api::{
+        FavoriteDto, FavoriteDtoList, MusicDto, MusicDtoList, PlaylistDto, PlaylistDtoList,
+        RatingDto, RatingDtoList,
+    },
+};
+
+// Cache versions of the library structs that fail on deserialization errors instead of skipping.
+// We need this so that we fail reading from the cache if any item fails to deserialize.
+// This is opposed to the behavior of reading from the server where we want to skip items.
+// The application will refresh from the server (its probably because structs are out of date).
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct MusicDtoListCache {
+    pub items: Vec<MusicDto>,
+    pub total_record_count: u64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct PlaylistDtoListCache {
+    pub items: Vec<PlaylistDto>,
+    pub total_record_count: u64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct FavoritesDtoListCache {
+    pub items: Vec<FavoriteDto>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct RatingsDtoListCache {
+    pub items: Vec<RatingDto>,
+}
+
+impl From<MusicDtoListCache> for MusicDtoList {
+    fn from(cache: MusicDtoListCache) -> Self {
+        Self {
+            items: cache.items,
+            total_record_count: cache.total_record_count,
+        }
+    }
+}
+
+impl From<PlaylistDtoListCache> for PlaylistDtoList {
+    fn from(cache: PlaylistDtoListCache) -> Self {
+        Self {
+            items: cache.items,
+            total_record_count: cache.total_record_count,
+        }
+    }
+}
+
+impl From<FavoritesDtoListCache> for FavoriteDtoList {
+    fn from(cache: FavoritesDtoListCache) -> Self {
+        Self { items: cache.items }
+    }
+}
+
+impl From<RatingsDtoListCache> for RatingDtoList {
+    fn from(cache: RatingsDtoListCache) -> Self {
+        Self { items: cache.items }
+    }
+}
+
+pub trait Cacheable: DeserializeOwned + Serialize {
+    type Loader: DeserializeOwned + Into<Self>;
+    const CACHE_FILE_NAME: &'static str;
+}
+
+impl Cacheable for MusicDtoList {
+    type Loader = MusicDtoListCache;
+    const CACHE_FILE_NAME: &'static str = "library.json";
+}
+
+impl Cacheable for PlaylistDtoList {
+    type Loader = PlaylistDtoListCache;
+    const CACHE_FILE_NAME: &'static str = "playlists.json";
+}
+
+impl Cacheable for FavoriteDtoList {
+    type Loader = FavoritesDtoListCache;
+    const CACHE_FILE_NAME: &'static str = "favorites.json";
+}
+
+impl Cacheable for RatingDtoList {
+    type Loader = RatingsDtoListCache;
+    const CACHE_FILE_NAME: &'static str = "ratings.json";
+}
+
+#[derive(Error, Debug)]
+pub enum CacheError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Jellyfin error: {0}")]
+    Jellyfin(#[from] BackendError),
+
+    #[error("Deserialization error: {0}")]
+    Deserialize(#[from] serde_json::Error),
+
+    #[error("Image decode error: {0}")]
+    Decode(String),
+}
+
+pub fn get_cache_directory(name: &str) -> Result<PathBuf, CacheError> {
+    let cache_dir = if let Ok(xdg_cache) = std::env::var("XDG_CACHE_HOME") {
+        PathBuf::from(xdg_cache)
+    } else if let Ok(home) = std::env::var("HOME") {
+        PathBuf::from(home).join(".cache")
+    } else {
+        PathBuf::from("/tmp")
+    };
+    Ok(cache_dir.join(APP_ID).join(name))
+}
+
+/// Like [`get_cache_directory`], for things that can't just be fetched again
+pub fn get_data_directory(name: &str) -> Result<PathBuf, CacheError> {
+    let data_dir = if let Ok(xdg_data) = std::env::var("XDG_DATA_HOME") {
+        PathBuf::from(xdg_data)
+    } else if let Ok(home) = std::env::var("HOME") {
+        PathBuf::from(home).join(".local").join("share")
+    } else {
+        PathBuf::from("/tmp")
+    };
+    Ok(data_dir.join(APP_ID).join(name))
+}
+
+#[derive(Debug, Clone)]
+pub struct LibraryCache {
+    cache_dir: PathBuf,
+}
+
+impl LibraryCache {
+    pub fn new() -> Result<Self, CacheError> {
+        let cache_dir = get_cache_directory("library")?;
+        fs::create_dir_all(&cache_dir)?;
+        Ok(Self { cache_dir })
+    }
+
+    fn save_to_disk(&self, fname: &str, data: &[u8]) -> Result<(), CacheError> {
+        let path = self.cache_dir.join(fname);
+        fs::write(path, data)?;
+        Ok(())
+    }
+
+    fn load_from_disk(&self, fname: &str) -> Result<Vec<u8>, CacheError> {
+        let path = self.cache_dir.join(fname);
+        Ok(fs::read(path)?)
+    }
+
+    pub fn clear(&self) -> Result<(), CacheError> {
+        fs::remove_dir_all(&self.cache_dir)?;
+        fs::create_dir_all(&self.cache_dir)?;
+        Ok(())
+    }
+
+    pub fn load<T: Cacheable>(&self) -> Result<T, CacheError> {
+        let fname = T::CACHE_FILE_NAME;
+        let data = self.load_from_disk(fname)?;
+        let parsed: T::Loader = serde_json::from_slice(&data)?;
+        Ok(parsed.into())
+    }
+
+    pub fn save<T: Cacheable>(&self, data: &T) -> Result<(), CacheError> {
+        let data = serde_json::to_string(data)?;
+        self.save_to_disk(T::CACHE_FILE_NAME, data.as_bytes())?;
+        Ok(())
+    }
+}
@@ -1,5 +1,4 @@
-use gtk::gio;
-use gtk::gio::prelude::SettingsExt;
+use gio::prelude::SettingsExt;
 use oo7::{Error, Keyring};
 use std::cell::RefCell;
 use uuid::Uuid;
@@ -62,11 +61,6 @@ impl TranscodingProfile {
     };
 
     pub const PROFILES: [Self; 2] = [Self::OPUS_MP4, Self::AAC_TS];
-
-    pub fn as_string_list() -> gtk::StringList {
-        let names: Vec<&str> = Self::PROFILES.iter().map(|p| p.name).collect();
-        gtk::StringList::new(&names)
-    }
 }
 
 /// Returns the application settings. Constructor called at most once per thread.
@@ -239,6 +233,10 @@ pub fn get_playlist_most_played_enabled() -> bool {
     settings().boolean("playlist-most-played-enabled")
 }
 
+pub fn get_playlist_top_rated_enabled() -> bool {
+    settings().boolean("playlist-top-rated-enabled")
+}
+
 pub fn get_playlist_favorites_enabled() -> bool {
     settings().boolean("playlist-favorites-enabled")
 }
@@ -255,6 +253,31 @@ pub fn get_inhibit_suspend_enabled() -> bool {
     settings().boolean("inhibit-suspend")
 }
 
+/// Tracks at least this long remember where they were stopped, `None` when
+/// turned off
+pub fn get_resume_threshold_seconds() -> Option<u64> {
+    match settings().uint("resume-threshold-minutes") {
+        0 => None,
+        minutes => Some(minutes as u64 * 60),
+    }
+}
+
+pub fn get_lyrics_folder() -> String {
+    settings().string("lyrics-folder").to_string()
+}
+
+pub fn set_lyrics_folder(folder: &str) {
+    settings().set_string("lyrics-folder", folder).unwrap();
+}
+
+pub fn get_lyrics_online_enabled() -> bool {
+    settings().boolean("lyrics-online-enabled")
+}
+
+pub fn get_lyrics_provider_url() -> String {
+    settings().string("lyrics-provider-url").to_string()
+}
+
 pub fn get_albums_sort_by() -> u32 {
     settings().uint("sort-albums-by")
 }
@@ -291,6 +314,32 @@ pub fn set_artists_sort_direction(direction: u32) {
         .unwrap();
 }
 
+pub fn get_categories_sort_by() -> u32 {
+    settings().uint("sort-categories-by")
+}
+
+pub fn set_categories_sort_by(sort_by: u32) {
+    settings().set_uint("sort-categories-by", sort_by).unwrap();
+}
+
+pub fn get_categories_sort_direction() -> u32 {
+    settings().uint("sort-categories-direction")
+}
+
+pub fn set_categories_sort_direction(direction: u32) {
+    settings()
+        .set_uint("sort-categories-direction", direction)
+        .unwrap();
+}
+
+pub fn get_browse_category() -> u32 {
+    settings().uint("browse-category")
+}
+
+pub fn set_browse_category(category: u32) {
+    settings().set_uint("browse-category", category).unwrap();
+}
+
 pub fn get_playlists_sort_by() -> u32 {
     settings().uint("sort-playlists-by")
 }
@@ -317,6 +366,16 @@ pub fn set_volume(volume: f64) {
     settings().set_double("volume", volume).unwrap();
 }
 
+pub fn get_podcast_playback_rate() -> f64 {
+    settings().double("podcast-playback-rate")
+}
+
+pub fn set_podcast_playback_rate(rate: f64) {
+    settings()
+        .set_double("podcast-playback-rate", rate)
+        .unwrap();
+}
+
 pub fn get_playback_mode() -> u32 {
     settings().uint("playback-mode")
 }
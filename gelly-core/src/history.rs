@@ -0,0 +1,197 @@
+//! Every play, kept on this computer: what was played, when, for how long and
+//! whether it was listened to the end or skipped. Plays are appended to a
+//! JSON lines log as they finish.
+
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::cache::CacheError;
+use crate::store::StoreFile;
+
+/// Plays stopped this close to the end still count as listened through. For
+/// short songs it's a tenth of the length, but at least the second positions
+/// are rounded to.
+const COMPLETED_MARGIN_SECONDS: u64 = 10;
+/// Songs need this many plays before they can count as often skipped
+const OFTEN_SKIPPED_MIN_PLAYS: usize = 3;
+const LOG_FILE_NAME: &str = "plays.jsonl";
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PlayRecord {
+    pub song_id: String,
+    pub title: String,
+    #[serde(default)]
+    pub artists: Vec<String>,
+    #[serde(default)]
+    pub album: String,
+    #[serde(default)]
+    pub album_id: String,
+    /// Unix time it started playing
+    pub played_at: i64,
+    /// Seconds long
+    pub duration: u64,
+    /// Seconds actually listened to, leaving out anything seeked past
+    pub listened: u64,
+    pub completed: bool,
+}
+
+/// Whether a play that stopped at `position` got to the end
+pub fn is_completed(position: u64, duration: u64) -> bool {
+    let margin = (duration / 10).clamp(1, COMPLETED_MARGIN_SECONDS);
+    duration > 0 && position + margin >= duration
+}
+
+#[derive(Debug, Default)]
+pub struct PlayHistory {
+    file: StoreFile,
+    /// Oldest first
+    plays: Vec<PlayRecord>,
+}
+
+impl PlayHistory {
+    pub fn load() -> Self {
+        let file = StoreFile::new("history", LOG_FILE_NAME);
+        Self {
+            plays: file.load_lines(),
+            file,
+        }
+    }
+
+    pub fn plays(&self) -> &[PlayRecord] {
+        &self.plays
+    }
+
+    /// Adds a finished play, the returned future appends it to the log
+    pub fn record(
+        &mut self,
+        play: PlayRecord,
+    ) -> impl Future<Output = Result<(), CacheError>> + Send + use<> {
+        let append = self.file.append(&play);
+        self.plays.push(play);
+        append
+    }
+
+    pub fn clear(&mut self) -> Result<(), CacheError> {
+        self.plays.clear();
+        self.file.remove()
+    }
+
+    /// Songs skipped in most of their plays, once they've been played a few
+    /// times, for smart playlists to leave out
+    pub fn often_skipped(&self) -> HashSet<String> {
+        let mut counts: HashMap<&str, (usize, usize)> = HashMap::new();
+        for play in &self.plays {
+            let (plays, skips) = counts.entry(&play.song_id).or_default();
+            *plays += 1;
+            if !play.completed {
+                *skips += 1;
+            }
+        }
+        counts
+            .into_iter()
+            .filter(|(_, (plays, skips))| *plays >= OFTEN_SKIPPED_MIN_PLAYS && skips * 2 > *plays)
+            .map(|(song_id, _)| song_id.to_string())
+            .collect()
+    }
+
+    pub fn to_json(&self) -> Result<String, CacheError> {
+        Ok(serde_json::to_string_pretty(&self.plays)?)
+    }
+
+    /// One row per play with a header, times in UTC
+    pub fn to_csv(&self) -> String {
+        let mut csv =
+            String::from("played_at,song_id,title,artists,album,duration,listened,completed\n");
+        for play in &self.plays {
+            let played_at = glib::DateTime::from_unix_utc(play.played_at)
+                .and_then(|time| time.format_iso8601())
+                .map(|time| time.to_string())
+                .unwrap_or_else(|_| play.played_at.to_string());
+            let fields = [
+                played_at,
+                play.song_id.clone(),
+                play.title.clone(),
+                play.artists.join(", "),
+                play.album.clone(),
+                play.duration.to_string(),
+                play.listened.to_string(),
+                play.completed.to_string(),
+            ];
+            let row: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+            csv.push_str(&row.join(","));
+            csv.push('\n');
+        }
+        csv
+    }
+}
+
+/// Quotes a field when it has anything CSV would otherwise split on
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn play(song_id: &str, completed: bool) -> PlayRecord {
+        PlayRecord {
+            song_id: song_id.to_string(),
+            title: format!("Song {}", song_id),
+            artists: vec!["Artist".to_string()],
+            album: "Album".to_string(),
+            album_id: "album".to_string(),
+            played_at: 1_700_000_000,
+            duration: 200,
+            listened: if completed { 200 } else { 20 },
+            completed,
+        }
+    }
+
+    #[test]
+    fn test_is_completed() {
+        assert!(is_completed(195, 200));
+        assert!(is_completed(200, 200));
+        assert!(!is_completed(120, 200));
+        assert!(!is_completed(0, 0));
+    }
+
+    #[test]
+    fn test_short_track_skipped() {
+        assert!(!is_completed(0, 8));
+        assert!(is_completed(7, 8));
+        assert!(!is_completed(5, 10));
+        assert!(is_completed(9, 10));
+    }
+
+    #[test]
+    fn test_often_skipped() {
+        let mut history = PlayHistory::default();
+        for completed in [false, false, true] {
+            history.plays.push(play("skipped", completed));
+        }
+        for completed in [false, true, true] {
+            history.plays.push(play("liked", completed));
+        }
+        // Two skips aren't enough to go on yet
+        for _ in 0..2 {
+            history.plays.push(play("new", false));
+        }
+        assert_eq!(
+            history.often_skipped(),
+            HashSet::from(["skipped".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_csv_field() {
+        assert_eq!(csv_field("Plain"), "Plain");
+        assert_eq!(csv_field("Simon, Garfunkel"), "\"Simon, Garfunkel\"");
+        assert_eq!(csv_field("12\" Mix"), "\"12\"\" Mix\"");
+    }
+}
@@ -0,0 +1,1347 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+
+use crate::jellyfin::utils::strip_html;
+
+/// For the endpoints that return a collection of items, we want to skip any
+/// items that do not deserialize so that we can still return a usable library.
+fn deserialize_items_skip_errors<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    let items = Vec::<Value>::deserialize(deserializer)?;
+    let result: Vec<T> = items
+        .into_iter()
+        .filter_map(|item| match T::deserialize(item) {
+            Ok(d_item) => Some(d_item),
+            Err(e) => {
+                log::warn!("Failed to deserialize jellyfin item, skipping: {}", e);
+                None
+            }
+        })
+        .collect();
+
+    Ok(result)
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct AuthenticateResponse {
+    pub access_token: String,
+    pub user: User,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct QuickConnectResponse {
+    pub authenticated: bool,
+    pub secret: String,
+    pub code: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct User {
+    pub id: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct LibraryDto {
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct LibraryDtoList {
+    #[serde(deserialize_with = "deserialize_items_skip_errors")]
+    pub items: Vec<LibraryDto>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct MusicDtoList {
+    // Update the Cache version of this struct in cache.rs if changes are needed
+    #[serde(deserialize_with = "deserialize_items_skip_errors")]
+    pub items: Vec<MusicDto>,
+    pub total_record_count: u64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct MusicDto {
+    pub name: String,
+    pub id: String,
+    pub date_created: Option<String>,
+    pub run_time_ticks: u64,
+    pub album: Option<String>,
+    pub album_artists: Vec<ArtistItemsDto>,
+    pub artist_items: Vec<ArtistItemsDto>, // This is song artists
+    pub album_id: Option<String>,
+    pub normalization_gain: Option<f64>,
+    pub production_year: Option<u32>,
+    pub index_number: Option<u32>,
+    pub parent_index_number: Option<u32>,
+    pub user_data: UserDataDto,
+    pub has_lyrics: bool,
+    #[serde(deserialize_with = "deserialize_items_skip_errors")]
+    pub genres: Vec<String>,
+    pub cover_art: Option<String>, // This is to accommodate SubSonic
+    #[serde(default)]
+    pub is_compilation: bool, // Only Subsonic flags compilations
+    #[serde(default, deserialize_with = "deserialize_items_skip_errors")]
+    pub people: Vec<PersonDto>,
+    /// Record labels
+    #[serde(default, deserialize_with = "deserialize_items_skip_errors")]
+    pub studios: Vec<StudioDto>,
+    /// MusicBrainz ids and the like, keyed the way Jellyfin does. Jellyfin
+    /// only sends them when asked, for playlist files.
+    #[serde(default)]
+    pub provider_ids: HashMap<String, String>,
+    /// Where the file is on the server, Jellyfin only sends it when asked
+    #[serde(default)]
+    pub path: Option<String>,
+    /// What the server says the item is, like "Audio" or "AudioBook"
+    #[serde(rename = "Type", default)]
+    pub item_type: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct UserDataDto {
+    pub play_count: u64,
+    pub last_played_date: Option<String>,
+    /// Where the server remembers we got to, in ticks
+    #[serde(default)]
+    pub playback_position_ticks: u64,
+    /// Out of ten, see [`crate::rating`]
+    #[serde(default)]
+    pub rating: Option<f64>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct FavoriteDtoList {
+    // Update the Cache version of this struct in cache.rs if changes are needed
+    #[serde(deserialize_with = "deserialize_items_skip_errors")]
+    pub items: Vec<FavoriteDto>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub enum ItemType {
+    Audio,
+    MusicAlbum,
+    MusicArtist,
+    Playlist,
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct FavoriteDto {
+    pub id: String,
+    #[serde(rename = "Type")]
+    pub item_type: ItemType,
+    pub user_data: FavoriteUserDataDto,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct FavoriteUserDataDto {
+    pub is_favorite: bool,
+}
+
+/// Album ratings. Song ratings come with the songs' own user data.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct RatingDtoList {
+    // Update the Cache version of this struct in cache.rs if changes are needed
+    #[serde(deserialize_with = "deserialize_items_skip_errors")]
+    pub items: Vec<RatingDto>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct RatingDto {
+    pub id: String,
+    pub user_data: RatingUserDataDto,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct RatingUserDataDto {
+    /// Out of ten, see [`crate::rating`]
+    #[serde(default)]
+    pub rating: Option<f64>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct PlaylistDtoList {
+    // Update the Cache version of this struct in cache.rs if changes are needed
+    #[serde(deserialize_with = "deserialize_items_skip_errors")]
+    pub items: Vec<PlaylistDto>,
+    pub total_record_count: u64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct PlaylistDto {
+    pub name: String,
+    pub id: String,
+    pub child_count: u64,
+    #[serde(default)]
+    pub overview: Option<String>,
+}
+
+/// The parts of a playlist that can be edited, apart from its songs
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PlaylistDetails {
+    pub name: String,
+    pub description: String,
+    pub is_public: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ArtistItemsDto {
+    pub name: String,
+    pub id: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct PlaylistItems {
+    #[serde(deserialize_with = "deserialize_items_skip_errors")]
+    pub items: Vec<MusicDto>,
+    pub total_record_count: u64,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct MediaStream {
+    #[serde(rename = "Type")]
+    pub type_: Option<String>,
+    pub codec: Option<String>,
+    pub bit_rate: Option<u64>,
+    pub sample_rate: Option<u64>,
+    pub channels: Option<u32>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct MediaSource {
+    #[serde(deserialize_with = "deserialize_items_skip_errors")]
+    pub media_streams: Vec<MediaStream>,
+    pub id: Option<String>,
+    pub path: Option<String>,
+    pub container: Option<String>,
+    pub size: Option<u64>,
+    pub supports_direct_stream: Option<bool>,
+    pub supports_direct_play: Option<bool>,
+    pub supports_transcoding: Option<bool>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct PlaybackInfo {
+    #[serde(deserialize_with = "deserialize_items_skip_errors")]
+    pub media_sources: Vec<MediaSource>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct PlaylistUserPermissions {
+    pub user_id: String,
+    pub can_edit: bool,
+}
+
+/// From `GET /Playlists/{id}`
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct PlaylistAccessDto {
+    #[serde(default)]
+    pub open_access: bool,
+    #[serde(default)]
+    pub shares: Vec<PlaylistUserPermissions>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct UpdatePlaylistUser {
+    pub can_edit: bool,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct UpdatePlaylist {
+    pub name: String,
+    pub is_public: bool,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct NewPlaylist {
+    pub name: String,
+    pub ids: Vec<String>,
+    pub user_id: String,
+    pub media_type: String,
+    pub users: Vec<PlaylistUserPermissions>,
+    pub is_public: bool,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct NewPlaylistResponse {
+    pub id: String,
+}
+
+pub enum PlaybackReportStatus {
+    Started,
+    InProgress,
+    Stopped,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct PlaybackReport {
+    pub item_id: String,
+    pub session_id: String,
+    pub play_session_id: String,
+    pub can_seek: bool,
+    pub is_paused: bool,
+    pub is_muted: bool,
+    pub position_ticks: u64,
+}
+
+/// Album details only needed on the album page, fetched when it's opened
+/// rather than stored in the library.
+#[derive(Clone, Debug, Default)]
+pub struct AlbumInfo {
+    pub disc_titles: Vec<DiscTitle>,
+    pub overview: Option<String>,
+    pub labels: Vec<String>,
+    /// album, ep, single, live... as tagged, only known to OpenSubsonic
+    pub release_types: Vec<String>,
+    pub is_compilation: bool,
+    pub musicbrainz_release_id: Option<String>,
+    pub musicbrainz_release_group_id: Option<String>,
+    pub credits: Vec<Credit>,
+    /// Bytes across all tracks
+    pub total_size: u64,
+    /// File formats of the tracks, e.g. FLAC
+    pub formats: Vec<String>,
+}
+
+impl AlbumInfo {
+    pub fn from_jellyfin(album: AlbumDetailsDto, tracks: Vec<TrackDetailsDto>) -> Self {
+        let mut info = Self {
+            overview: album
+                .overview
+                .filter(|overview| !overview.trim().is_empty()),
+            labels: album
+                .studios
+                .into_iter()
+                .map(|studio| studio.name)
+                .collect(),
+            musicbrainz_release_id: album.provider_ids.get("MusicBrainzAlbum").cloned(),
+            musicbrainz_release_group_id: album
+                .provider_ids
+                .get("MusicBrainzReleaseGroup")
+                .cloned(),
+            ..Default::default()
+        };
+        let people = album
+            .people
+            .iter()
+            .chain(tracks.iter().flat_map(|track| track.people.iter()));
+        // Artists are already on the album page
+        for person in people.filter(|person| !person.person_type.ends_with("Artist")) {
+            // Performers carry their instrument as the role
+            let role = match person.role.as_deref() {
+                Some(role) if !role.is_empty() && person.person_type != "Composer" => {
+                    format!("{} ({})", person.person_type, role)
+                }
+                _ => person.person_type.clone(),
+            };
+            info.add_credit(&person.name, &role);
+        }
+        for source in tracks
+            .iter()
+            .filter_map(|track| track.media_sources.first())
+        {
+            info.add_file(
+                source.size.unwrap_or(0),
+                source.container.as_deref().unwrap_or(""),
+            );
+        }
+        info
+    }
+
+    pub fn add_credit(&mut self, name: &str, role: &str) {
+        let (name, role) = (name.trim(), role.trim());
+        if name.is_empty()
+            || self
+                .credits
+                .iter()
+                .any(|credit| credit.name == name && credit.role == role)
+        {
+            return;
+        }
+        self.credits.push(Credit {
+            name: name.to_string(),
+            role: role.to_string(),
+        });
+    }
+
+    pub fn add_file(&mut self, size: u64, format: &str) {
+        self.total_size += size;
+        let format = format.trim().to_uppercase();
+        if !format.is_empty() && !self.formats.contains(&format) {
+            self.formats.push(format);
+        }
+    }
+
+    /// Credited names grouped by role, roles in the order first seen
+    pub fn credits_by_role(&self) -> Vec<(&str, Vec<&str>)> {
+        let mut roles: Vec<(&str, Vec<&str>)> = Vec::new();
+        for credit in &self.credits {
+            match roles.iter_mut().find(|(role, _)| *role == credit.role) {
+                Some((_, names)) => names.push(&credit.name),
+                None => roles.push((&credit.role, vec![&credit.name])),
+            }
+        }
+        roles
+    }
+
+    pub fn musicbrainz_url(&self) -> Option<String> {
+        match (
+            &self.musicbrainz_release_id,
+            &self.musicbrainz_release_group_id,
+        ) {
+            (Some(id), _) => Some(format!("https://musicbrainz.org/release/{id}")),
+            (None, Some(id)) => Some(format!("https://musicbrainz.org/release-group/{id}")),
+            (None, None) => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ArtistInfo {
+    pub biography: Option<String>,
+    /// As the server knows them, they may not be in the library
+    pub similar_artists: Vec<ArtistItemsDto>,
+}
+
+impl ArtistInfo {
+    pub fn new(biography: Option<String>, similar_artists: Vec<ArtistItemsDto>) -> Self {
+        Self {
+            biography: biography
+                .map(|biography| strip_html(&biography))
+                .filter(|biography| !biography.is_empty()),
+            similar_artists,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct OverviewDto {
+    pub overview: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct OverviewDtoList {
+    #[serde(deserialize_with = "deserialize_items_skip_errors")]
+    pub items: Vec<OverviewDto>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ArtistItemsDtoList {
+    #[serde(deserialize_with = "deserialize_items_skip_errors")]
+    pub items: Vec<ArtistItemsDto>,
+}
+
+/// A directory on the server. The library root has no parent.
+#[derive(Clone, Debug, Default)]
+pub struct Folder {
+    pub id: String,
+    pub name: String,
+    pub parent_id: Option<String>,
+    /// Where the folder is on the server's disk, when it's known
+    pub path: Option<String>,
+    pub folders: Vec<FolderEntry>,
+    pub song_ids: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct FolderEntry {
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct FolderItemDto {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub is_folder: bool,
+    #[serde(rename = "Type", default)]
+    pub item_type: String,
+    pub parent_id: Option<String>,
+    pub path: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct FolderItemDtoList {
+    #[serde(deserialize_with = "deserialize_items_skip_errors")]
+    pub items: Vec<FolderItemDto>,
+}
+
+impl Folder {
+    /// Splits Jellyfin children into subfolders and songs
+    pub fn from_jellyfin(
+        folder: FolderItemDto,
+        library_id: &str,
+        children: Vec<FolderItemDto>,
+    ) -> Self {
+        let mut result = Folder {
+            parent_id: folder.parent_id.filter(|_| folder.id != library_id),
+            id: folder.id,
+            name: folder.name,
+            path: folder.path,
+            ..Default::default()
+        };
+        for child in children {
+            if child.is_folder {
+                result.folders.push(FolderEntry {
+                    id: child.id,
+                    name: child.name,
+                });
+            } else if child.item_type == "Audio" {
+                result.song_ids.push(child.id);
+            }
+        }
+        result
+    }
+}
+
+/// A podcast feed the server is subscribed to
+#[derive(Clone, Debug, Default)]
+pub struct PodcastChannel {
+    pub id: String,
+    pub title: String,
+    pub description: Option<String>,
+    /// Item to load the channel artwork from
+    pub image_id: Option<String>,
+    pub episodes: Vec<PodcastEpisode>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PodcastEpisode {
+    pub id: String,
+    /// What to stream, `None` until the server has downloaded the episode
+    pub stream_id: Option<String>,
+    pub channel_id: String,
+    pub channel_title: String,
+    pub title: String,
+    pub description: Option<String>,
+    pub publish_date: Option<String>,
+    pub run_time_ticks: u64,
+    pub image_id: Option<String>,
+    /// Where the server thinks we are, Jellyfin only
+    pub position_ticks: u64,
+    pub played: bool,
+}
+
+impl PodcastEpisode {
+    /// The id the episode is streamed and remembered by
+    pub fn play_id(&self) -> &str {
+        self.stream_id.as_deref().unwrap_or(&self.id)
+    }
+
+    /// The episode as a song, so it can go in the play queue
+    pub fn to_music_dto(&self) -> MusicDto {
+        MusicDto {
+            name: self.title.clone(),
+            id: self.play_id().to_string(),
+            date_created: self.publish_date.clone(),
+            run_time_ticks: self.run_time_ticks,
+            album: Some(self.channel_title.clone()),
+            album_artists: vec![ArtistItemsDto {
+                name: self.channel_title.clone(),
+                id: self.channel_id.clone(),
+            }],
+            artist_items: vec![],
+            album_id: self.image_id.clone(),
+            normalization_gain: None,
+            production_year: None,
+            index_number: None,
+            parent_index_number: None,
+            user_data: UserDataDto {
+                play_count: 0,
+                last_played_date: None,
+                playback_position_ticks: 0,
+                rating: None,
+            },
+            has_lyrics: false,
+            genres: vec![],
+            cover_art: self.image_id.clone(),
+            is_compilation: false,
+            people: vec![],
+            studios: vec![],
+            provider_ids: HashMap::new(),
+            path: None,
+            item_type: None,
+        }
+    }
+}
+
+/// A live stream to listen to, from the server or added by hand
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct RadioStation {
+    pub id: String,
+    pub name: String,
+    pub stream_url: String,
+    #[serde(default)]
+    pub homepage_url: Option<String>,
+    /// Kept on this computer rather than on the server
+    #[serde(default)]
+    pub user_added: bool,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ChannelDto {
+    pub id: String,
+    pub name: String,
+    pub overview: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ChannelDtoList {
+    #[serde(deserialize_with = "deserialize_items_skip_errors")]
+    pub items: Vec<ChannelDto>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ChannelItemDto {
+    pub id: String,
+    pub name: String,
+    pub overview: Option<String>,
+    pub premiere_date: Option<String>,
+    pub date_created: Option<String>,
+    #[serde(default)]
+    pub run_time_ticks: u64,
+    pub channel_id: Option<String>,
+    pub channel_name: Option<String>,
+    pub media_type: Option<String>,
+    pub user_data: Option<ResumeUserDataDto>,
+}
+
+/// How far the user got in something that can be resumed
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ResumeUserDataDto {
+    #[serde(default)]
+    pub played: bool,
+    #[serde(default)]
+    pub playback_position_ticks: u64,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ChannelItemDtoList {
+    #[serde(deserialize_with = "deserialize_items_skip_errors")]
+    pub items: Vec<ChannelItemDto>,
+}
+
+impl ChannelItemDto {
+    pub fn is_audio(&self) -> bool {
+        self.media_type.as_deref() == Some("Audio")
+    }
+
+    pub fn into_episode(self, channel: Option<&ChannelDto>) -> PodcastEpisode {
+        let user_data = self.user_data.unwrap_or_default();
+        PodcastEpisode {
+            stream_id: Some(self.id.clone()),
+            image_id: Some(self.id.clone()),
+            channel_id: self
+                .channel_id
+                .or_else(|| channel.map(|c| c.id.clone()))
+                .unwrap_or_default(),
+            channel_title: self
+                .channel_name
+                .or_else(|| channel.map(|c| c.name.clone()))
+                .unwrap_or_default(),
+            id: self.id,
+            title: self.name,
+            description: self.overview,
+            publish_date: self.premiere_date.or(self.date_created),
+            run_time_ticks: self.run_time_ticks,
+            position_ticks: user_data.playback_position_ticks,
+            played: user_data.played,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Chapter {
+    pub name: String,
+    pub start_ticks: u64,
+}
+
+impl Chapter {
+    pub fn start_seconds(&self) -> u64 {
+        self.start_ticks / 10_000_000
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Audiobook {
+    pub id: String,
+    pub title: String,
+    pub authors: Vec<String>,
+    /// The book a part belongs to when it's split over several files
+    pub album: Option<String>,
+    pub album_id: Option<String>,
+    pub overview: Option<String>,
+    pub run_time_ticks: u64,
+    /// Where the server remembers we got to
+    pub position_ticks: u64,
+    pub played: bool,
+    pub chapters: Vec<Chapter>,
+}
+
+impl Audiobook {
+    /// The book as a song, so it can go in the play queue
+    pub fn to_music_dto(&self) -> MusicDto {
+        MusicDto {
+            name: self.title.clone(),
+            id: self.id.clone(),
+            date_created: None,
+            run_time_ticks: self.run_time_ticks,
+            album: Some(self.album.clone().unwrap_or_else(|| self.title.clone())),
+            album_artists: self
+                .authors
+                .iter()
+                .map(|name| ArtistItemsDto {
+                    name: name.clone(),
+                    id: String::new(),
+                })
+                .collect(),
+            artist_items: vec![],
+            // Books on their own carry their cover themselves
+            album_id: Some(self.album_id.clone().unwrap_or_else(|| self.id.clone())),
+            normalization_gain: None,
+            production_year: None,
+            index_number: None,
+            parent_index_number: None,
+            user_data: UserDataDto {
+                play_count: 0,
+                last_played_date: None,
+                playback_position_ticks: 0,
+                rating: None,
+            },
+            has_lyrics: false,
+            genres: vec![],
+            cover_art: None,
+            is_compilation: false,
+            people: vec![],
+            studios: vec![],
+            provider_ids: HashMap::new(),
+            path: None,
+            item_type: Some("AudioBook".to_string()),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct AudiobookDto {
+    pub id: String,
+    pub name: String,
+    pub album: Option<String>,
+    pub album_id: Option<String>,
+    pub album_artist: Option<String>,
+    #[serde(default)]
+    pub artists: Vec<String>,
+    pub overview: Option<String>,
+    #[serde(default)]
+    pub run_time_ticks: u64,
+    pub user_data: Option<ResumeUserDataDto>,
+    #[serde(default)]
+    pub chapters: Vec<ChapterDto>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ChapterDto {
+    pub name: Option<String>,
+    #[serde(default)]
+    pub start_position_ticks: u64,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct AudiobookDtoList {
+    #[serde(deserialize_with = "deserialize_items_skip_errors")]
+    pub items: Vec<AudiobookDto>,
+}
+
+impl From<AudiobookDto> for Audiobook {
+    fn from(dto: AudiobookDto) -> Self {
+        let user_data = dto.user_data.unwrap_or_default();
+        let authors = if dto.artists.is_empty() {
+            dto.album_artist.into_iter().collect()
+        } else {
+            dto.artists
+        };
+        let chapters = dto
+            .chapters
+            .into_iter()
+            .enumerate()
+            .map(|(index, chapter)| Chapter {
+                name: chapter
+                    .name
+                    .filter(|name| !name.is_empty())
+                    .unwrap_or_else(|| format!("Chapter {}", index + 1)),
+                start_ticks: chapter.start_position_ticks,
+            })
+            .collect();
+        Self {
+            id: dto.id,
+            title: dto.name,
+            authors,
+            album: dto.album,
+            album_id: dto.album_id,
+            overview: dto.overview,
+            run_time_ticks: dto.run_time_ticks,
+            position_ticks: user_data.playback_position_ticks,
+            played: user_data.played,
+            chapters,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Credit {
+    pub name: String,
+    /// Composer, Lyricist, Performer... as the server names it
+    pub role: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct AlbumDetailsDto {
+    pub overview: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_items_skip_errors")]
+    pub studios: Vec<StudioDto>,
+    #[serde(default)]
+    pub provider_ids: HashMap<String, String>,
+    #[serde(default, deserialize_with = "deserialize_items_skip_errors")]
+    pub people: Vec<PersonDto>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct AlbumDetailsDtoList {
+    #[serde(deserialize_with = "deserialize_items_skip_errors")]
+    pub items: Vec<AlbumDetailsDto>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct StudioDto {
+    pub name: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct PersonDto {
+    pub name: String,
+    pub role: Option<String>,
+    #[serde(rename = "Type", default)]
+    pub person_type: String,
+}
+
+/// The people or studios of a song, fetched on their own because they make
+/// the library download much bigger
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct SongCreditsDto {
+    pub id: String,
+    #[serde(default, deserialize_with = "deserialize_items_skip_errors")]
+    pub people: Vec<PersonDto>,
+    #[serde(default, deserialize_with = "deserialize_items_skip_errors")]
+    pub studios: Vec<StudioDto>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct SongCreditsDtoList {
+    #[serde(deserialize_with = "deserialize_items_skip_errors")]
+    pub items: Vec<SongCreditsDto>,
+    pub total_record_count: u64,
+}
+
+/// Where a song's file is and the ids other services know it by, for
+/// playlist files
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct SongFileDto {
+    pub id: String,
+    #[serde(default)]
+    pub path: Option<String>,
+    #[serde(default)]
+    pub provider_ids: HashMap<String, String>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct SongFileDtoList {
+    #[serde(deserialize_with = "deserialize_items_skip_errors")]
+    pub items: Vec<SongFileDto>,
+    #[serde(default)]
+    pub total_record_count: u64,
+}
+
+/// Tracks of an album with the fields the album details need
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct TrackDetailsDtoList {
+    #[serde(deserialize_with = "deserialize_items_skip_errors")]
+    pub items: Vec<TrackDetailsDto>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct TrackDetailsDto {
+    #[serde(default, deserialize_with = "deserialize_items_skip_errors")]
+    pub media_sources: Vec<MediaSource>,
+    #[serde(default, deserialize_with = "deserialize_items_skip_errors")]
+    pub people: Vec<PersonDto>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DiscTitle {
+    pub disc: u32,
+    pub title: String,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct LyricsResponse {
+    #[serde(deserialize_with = "deserialize_items_skip_errors")]
+    pub lyrics: Vec<Lyric>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Lyric {
+    pub text: String,
+    pub start: Option<u64>,
+    /// Word timings from enhanced LRC, empty for line synced lyrics
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub words: Vec<LyricWord>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct LyricWord {
+    pub text: String,
+    pub start: u64,
+}
+
+impl Lyric {
+    /// Splits `text` into words at the given char indexes, each paired with
+    /// its start in ticks. A word runs up to the next one so spaces are kept,
+    /// and anything before the first index joins the first word.
+    pub fn with_word_starts(text: String, start: Option<u64>, starts: &[(usize, u64)]) -> Self {
+        let chars: Vec<char> = text.chars().collect();
+        let words = starts
+            .iter()
+            .enumerate()
+            .filter_map(|(i, &(position, word_start))| {
+                let position = if i == 0 { 0 } else { position };
+                let end = starts
+                    .get(i + 1)
+                    .map_or(chars.len(), |(next, _)| *next)
+                    .min(chars.len());
+                let word: String = chars.get(position..end)?.iter().collect();
+                Some(LyricWord {
+                    text: word,
+                    start: word_start,
+                })
+            })
+            .collect();
+        Self { text, start, words }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct UserDto {
+    #[serde(default)]
+    pub id: String,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub policy: UserPolicyDto,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct UserPolicyDto {
+    #[serde(default)]
+    pub is_administrator: bool,
+    #[serde(default)]
+    pub enable_lyric_management: bool,
+}
+
+/// Lyrics as the server sends them. Jellyfin 10.11 adds word cues.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct LyricsDto {
+    #[serde(default)]
+    pub lyrics: Vec<LyricLineDto>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct LyricLineDto {
+    pub text: String,
+    pub start: Option<u64>,
+    #[serde(default)]
+    pub cues: Vec<LyricCueDto>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct LyricCueDto {
+    /// Char index into the line text
+    pub position: usize,
+    pub start: u64,
+}
+
+impl From<LyricLineDto> for Lyric {
+    fn from(line: LyricLineDto) -> Self {
+        let starts: Vec<(usize, u64)> = line
+            .cues
+            .iter()
+            .map(|cue| (cue.position, cue.start))
+            .collect();
+        Self::with_word_starts(line.text, line.start, &starts)
+    }
+}
+
+pub const NO_ALBUM_ID: &str = "__gelly_no_album__";
+
+/// Album artist most taggers use for compilations
+pub const VARIOUS_ARTISTS: &str = "Various Artists";
+
+impl MusicDto {
+    /// Returns the real album_id when present, or a per-artist sentinel so that
+    /// no-album songs from different artists don't collide in the album detail view
+    pub fn effective_album_id(&self) -> String {
+        match &self.album_id {
+            Some(id) => id.clone(),
+            None => {
+                let artist_id = self
+                    .album_artists
+                    .first()
+                    .map(|a| a.id.as_str())
+                    .unwrap_or("");
+                format!("{NO_ALBUM_ID}{artist_id}")
+            }
+        }
+    }
+
+    /// Although both Jellyfin and Subsonic return genres as a list of strings,
+    /// sometimes those strings are themselves comma-separated, semi-colon separated,
+    /// or even slash-separated. This function attempts to flatten those into a single
+    /// list of genres, lowercased and trimmed.
+    pub fn effective_genres(&self) -> Vec<String> {
+        self.genres
+            .iter()
+            .flat_map(|genre| {
+                genre
+                    .split(&[',', ';', '/'])
+                    .map(|s| s.trim().to_lowercase())
+                    .filter(|s| !s.is_empty())
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    /// MusicBrainz urls for the recording and the release track, whichever
+    /// the server knows
+    pub fn musicbrainz_urls(&self) -> Vec<String> {
+        [
+            ("MusicBrainzRecording", "recording"),
+            ("MusicBrainzTrack", "track"),
+        ]
+        .into_iter()
+        .filter_map(|(key, kind)| {
+            self.provider_ids
+                .get(key)
+                .filter(|id| !id.is_empty())
+                .map(|id| format!("https://musicbrainz.org/{kind}/{id}"))
+        })
+        .collect()
+    }
+
+    /// Jellyfin doesn't flag compilations, so fall back to the album artist
+    pub fn effective_is_compilation(&self) -> bool {
+        self.is_compilation
+            || self
+                .album_artists
+                .iter()
+                .any(|artist| artist.name.eq_ignore_ascii_case(VARIOUS_ARTISTS))
+    }
+
+    pub fn composers(&self) -> Vec<String> {
+        let mut composers: Vec<String> = Vec::new();
+        for person in &self.people {
+            let name = person.name.trim();
+            if person.person_type == "Composer"
+                && !name.is_empty()
+                && !composers.iter().any(|c| c == name)
+            {
+                composers.push(name.to_string());
+            }
+        }
+        composers
+    }
+
+    pub fn labels(&self) -> Vec<String> {
+        let mut labels: Vec<String> = Vec::new();
+        for studio in &self.studios {
+            let name = studio.name.trim();
+            if !name.is_empty() && !labels.iter().any(|l| l == name) {
+                labels.push(name.to_string());
+            }
+        }
+        labels
+    }
+
+    /// "1990s" for anything released 1990-1999
+    pub fn decade(&self) -> Option<String> {
+        self.production_year
+            .filter(|year| *year > 0)
+            .map(|year| format!("{}s", year / 10 * 10))
+    }
+
+    /// Jellyfin says "AudioBook", Subsonic "audiobook"
+    pub fn is_audiobook(&self) -> bool {
+        self.item_type
+            .as_deref()
+            .is_some_and(|item_type| item_type.eq_ignore_ascii_case("audiobook"))
+    }
+
+    pub fn year(&self) -> Option<String> {
+        self.production_year
+            .filter(|year| *year > 0)
+            .map(|year| year.to_string())
+    }
+
+    pub fn effective_cover_art(&self) -> &str {
+        self.cover_art
+            .as_deref()
+            .or(self.album_id.as_deref())
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum ImageType {
+    Primary,
+    Art,
+    Backdrop,
+    Banner,
+}
+
+impl ImageType {
+    pub fn as_str(&self) -> &str {
+        match self {
+            ImageType::Primary => "Primary",
+            ImageType::Art => "Art",
+            ImageType::Backdrop => "Backdrop",
+            ImageType::Banner => "Banner",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct UtcTimeResponse {
+    pub request_reception_time: String,
+    pub response_transmission_time: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct GroupInfoDto {
+    pub group_id: String,
+    pub group_name: String,
+    #[serde(default)]
+    pub state: String,
+    #[serde(default)]
+    pub participants: Vec<String>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct SyncPlayQueueItem {
+    pub item_id: String,
+    pub playlist_item_id: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct PlayQueueUpdate {
+    #[serde(default)]
+    pub reason: String,
+    #[serde(default)]
+    pub playlist: Vec<SyncPlayQueueItem>,
+    pub playing_item_index: i32,
+    #[serde(default)]
+    pub start_position_ticks: i64,
+    #[serde(default)]
+    pub is_playing: bool,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct GroupStateUpdate {
+    pub state: String,
+    #[serde(default)]
+    pub reason: String,
+}
+
+/// Playback command broadcast to every member of a SyncPlay group. `when` is
+/// the server time at which the command should take effect.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct SyncPlayCommand {
+    pub group_id: String,
+    #[serde(default)]
+    pub playlist_item_id: String,
+    pub when: String,
+    #[serde(default)]
+    pub position_ticks: Option<i64>,
+    pub command: SyncPlayCommandType,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+pub enum SyncPlayCommandType {
+    Unpause,
+    Pause,
+    Stop,
+    Seek,
+}
+
+/// "Play on" request from another client's session
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct PlayRequest {
+    pub item_ids: Vec<String>,
+    #[serde(default)]
+    pub start_position_ticks: Option<i64>,
+    pub play_command: PlayCommand,
+    #[serde(default)]
+    pub start_index: Option<usize>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+pub enum PlayCommand {
+    PlayNow,
+    PlayNext,
+    PlayLast,
+    PlayInstantMix,
+    PlayShuffle,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct PlaystateRequest {
+    pub command: PlaystateCommand,
+    #[serde(default)]
+    pub seek_position_ticks: Option<i64>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+pub enum PlaystateCommand {
+    Stop,
+    Pause,
+    Unpause,
+    NextTrack,
+    PreviousTrack,
+    Seek,
+    Rewind,
+    FastForward,
+    PlayPause,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct GeneralCommand {
+    pub name: String,
+    #[serde(default)]
+    pub arguments: HashMap<String, String>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ClientCapabilities {
+    pub playable_media_types: Vec<String>,
+    pub supported_commands: Vec<String>,
+    pub supports_media_control: bool,
+    pub supports_persistent_identifier: bool,
+}
+
+/// Batched library change notification. Ids can be any item type, not only songs.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct LibraryChangedDto {
+    #[serde(default)]
+    pub items_added: Vec<String>,
+    #[serde(default)]
+    pub items_removed: Vec<String>,
+    #[serde(default)]
+    pub items_updated: Vec<String>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct UserDataChangedDto {
+    pub user_id: String,
+    #[serde(default)]
+    pub user_data_list: Vec<UserItemDataDto>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct UserItemDataDto {
+    pub item_id: String,
+    #[serde(default)]
+    pub is_favorite: bool,
+    #[serde(default)]
+    pub play_count: u64,
+    #[serde(default)]
+    pub last_played_date: Option<String>,
+    #[serde(default)]
+    pub rating: Option<f64>,
+}
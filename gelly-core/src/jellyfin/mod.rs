@@ -0,0 +1,1330 @@
+use std::fs;
+use std::time::Duration;
+use std::{fmt::Debug, sync::OnceLock};
+
+use api::AuthenticateResponse;
+use futures::stream::{self, StreamExt};
+use log::{debug, warn};
+use reqwest::{Client, Response, StatusCode};
+use serde_json::json;
+use tokio::time::Instant;
+
+use crate::backend::BackendError;
+use crate::config;
+use crate::jellyfin::api::{
+    AlbumDetailsDtoList, AlbumInfo, ArtistInfo, ArtistItemsDto, ArtistItemsDtoList, Audiobook,
+    AudiobookDto, AudiobookDtoList, ChannelDto, ChannelDtoList, ChannelItemDtoList,
+    FavoriteDtoList, Folder, FolderItemDtoList, ImageType, LibraryDtoList, Lyric, LyricsDto,
+    LyricsResponse, MusicDtoList, NewPlaylist, NewPlaylistResponse, OverviewDtoList, PlaybackInfo,
+    PlaybackReport, PlaybackReportStatus, PlaylistAccessDto, PlaylistDetails, PlaylistDtoList,
+    PlaylistItems, PlaylistUserPermissions, PodcastChannel, PodcastEpisode, QuickConnectResponse,
+    RadioStation, RatingDtoList, SongCreditsDto, SongCreditsDtoList, SongFileDto, SongFileDtoList,
+    TrackDetailsDtoList, UpdatePlaylist, UpdatePlaylistUser, UserDto,
+};
+use crate::rating::rating_from_stars;
+
+pub mod api;
+pub mod socket;
+pub mod syncplay;
+pub mod utils;
+
+static CLIENT_ID: &str = "Gelly";
+
+#[derive(Debug, Clone)]
+pub struct Jellyfin {
+    client: Client,
+    pub host: String,
+    pub token: String,
+    pub user_id: String,
+}
+
+impl Jellyfin {
+    pub fn new(host: &str, token: &str, user_id: &str) -> Self {
+        let client = Client::builder()
+            .user_agent(format!("Gelly/{}", config::VERSION))
+            .connect_timeout(Duration::from_secs(10))
+            .timeout(Duration::from_secs(30))
+            .build()
+            .expect("Failed to create HTTP client");
+
+        Self {
+            client,
+            host: host.to_string(),
+            token: token.to_string(),
+            user_id: user_id.to_string(),
+        }
+    }
+
+    pub fn is_authenticated(&self) -> bool {
+        !self.token.is_empty() && !self.user_id.is_empty() && !self.host.is_empty()
+    }
+
+    pub async fn new_authenticate(
+        host: &str,
+        username: &str,
+        password: &str,
+    ) -> Result<Self, BackendError> {
+        let mut jellyfin = Self::new(host, "", "");
+        let resp = jellyfin.authenticate(username, password).await?;
+        jellyfin.token = resp.access_token;
+        jellyfin.user_id = resp.user.id;
+
+        Ok(jellyfin)
+    }
+
+    pub async fn new_authenticate_quick_connect(
+        host: &str,
+        secret: &str,
+    ) -> Result<Self, BackendError> {
+        let mut jellyfin = Self::new(host, "", "");
+        let params = json!({ "secret": secret });
+        let response = jellyfin
+            .post_json("Users/authenticatewithquickconnect", &params)
+            .await?;
+        let body = jellyfin.handle_response(response).await?;
+        let resp = serde_json::from_str::<AuthenticateResponse>(&body)?;
+        jellyfin.token = resp.access_token;
+        jellyfin.user_id = resp.user.id;
+
+        Ok(jellyfin)
+    }
+
+    async fn authenticate(
+        &self,
+        username: &str,
+        password: &str,
+    ) -> Result<AuthenticateResponse, BackendError> {
+        let body = json!({
+            "Username": username,
+            "Pw": password
+        });
+        let response = self.post_json("Users/authenticatebyname", &body).await?;
+        let body = self.handle_response(response).await?;
+        Ok(serde_json::from_str(&body)?)
+    }
+
+    pub async fn get_views(&self) -> Result<LibraryDtoList, BackendError> {
+        let response = self.get("UserViews", None).await?;
+        let body = self.handle_response(response).await?;
+        Ok(serde_json::from_str(&body)?)
+    }
+
+    pub async fn get_library(&self, library_id: &str) -> Result<MusicDtoList, BackendError> {
+        // Time library download so we can keep an eye on it.
+        let now = Instant::now();
+        const LIMIT: u64 = 250;
+        const MAX_CONCURRENT_REQUESTS: usize = 4;
+        const SLOW_LOAD_THRESHOLD: Duration = Duration::from_secs(1);
+
+        // Make the first request to get total count
+        // we also measure how long it takes. If the response is slow it's a
+        // sign that the server might be a potato, and we should reduce the
+        // number of concurrent requests.
+        let mut all_items = Vec::new();
+        let first_page_started = Instant::now();
+        let first_page = self.get_library_page(library_id, 0, LIMIT).await?;
+        let first_page_elapsed = first_page_started.elapsed();
+        let total_count = first_page.total_record_count;
+
+        let concurrent_requests = if first_page_elapsed >= SLOW_LOAD_THRESHOLD {
+            1
+        } else {
+            MAX_CONCURRENT_REQUESTS
+        };
+
+        debug!(
+            "Total library items: {}, fetched first {} items in {} seconds",
+            total_count,
+            first_page.items.len(),
+            first_page_elapsed.as_secs_f32()
+        );
+        all_items.extend(first_page.items);
+
+        // If we have more items to fetch, create concurrent requests for remaining pages
+        if total_count > LIMIT {
+            let remaining_items = total_count - LIMIT;
+            let additional_pages = remaining_items.div_ceil(LIMIT);
+
+            debug!(
+                "Fetching {} additional pages with max {} concurrent requests",
+                additional_pages, concurrent_requests
+            );
+
+            // Create futures for all remaining pages using the futures crate
+            let mut page_stream = stream::iter(1..=additional_pages)
+                .map(|page| {
+                    let start_index = page * LIMIT;
+                    self.get_library_page(library_id, start_index, LIMIT)
+                })
+                .buffer_unordered(concurrent_requests);
+
+            while let Some(result) = page_stream.next().await {
+                match result {
+                    Ok(page) => {
+                        debug!("Fetched page with {} items", page.items.len());
+                        all_items.extend(page.items);
+                    }
+                    Err(e) => {
+                        warn!("Failed to fetch library page: {}", e);
+                        // Continue with other pages rather than failing completely
+                    }
+                }
+            }
+        }
+
+        let elapsed = now.elapsed();
+        debug!(
+            "Total time taken to fetch library: {:?} Limit: {}",
+            elapsed, LIMIT
+        );
+
+        debug!("Total items collected: {}", all_items.len());
+
+        // Create the final result with all items
+        let final_result = MusicDtoList {
+            items: all_items,
+            total_record_count: total_count,
+        };
+
+        Ok(final_result)
+    }
+
+    async fn get_library_page(
+        &self,
+        library_id: &str,
+        start_index: u64,
+        limit: u64,
+    ) -> Result<MusicDtoList, BackendError> {
+        let start_index = start_index.to_string();
+        let limit = limit.to_string();
+        let params = vec![
+            ("parentId", library_id),
+            ("IncludeItemTypes", "Audio"),
+            ("sortBy", "DateCreated"),
+            ("sortOrder", "Descending"),
+            ("recursive", "true"),
+            ("fields", "DateCreated,Genres"),
+            ("ImageTypeLimit", "1"),
+            ("EnableImageTypes", "Primary"),
+            ("StartIndex", &start_index),
+            ("Limit", &limit),
+        ];
+
+        let response = self.get("Items", Some(&params)).await?;
+        let body = self.handle_response(response).await?;
+        Ok(serde_json::from_str(&body)?)
+    }
+
+    /// The people or studios (`fields`) of every song. They're only needed
+    /// for browsing by composer or label, so they're left out of the library.
+    pub async fn get_song_credits(
+        &self,
+        library_id: &str,
+        fields: &str,
+    ) -> Result<Vec<SongCreditsDto>, BackendError> {
+        const LIMIT: u64 = 1000;
+        const MAX_CONCURRENT_REQUESTS: usize = 4;
+
+        let first_page = self
+            .get_song_credits_page(library_id, fields, 0, LIMIT)
+            .await?;
+        let additional_pages = first_page
+            .total_record_count
+            .saturating_sub(LIMIT)
+            .div_ceil(LIMIT);
+        let mut credits = first_page.items;
+        let pages: Vec<_> = stream::iter(1..=additional_pages)
+            .map(|page| self.get_song_credits_page(library_id, fields, page * LIMIT, LIMIT))
+            .buffer_unordered(MAX_CONCURRENT_REQUESTS)
+            .collect()
+            .await;
+        // A missing page would leave songs out of their composer or label
+        for page in pages {
+            credits.extend(page?.items);
+        }
+        Ok(credits)
+    }
+
+    async fn get_song_credits_page(
+        &self,
+        library_id: &str,
+        fields: &str,
+        start_index: u64,
+        limit: u64,
+    ) -> Result<SongCreditsDtoList, BackendError> {
+        let start_index = start_index.to_string();
+        let limit = limit.to_string();
+        let params = vec![
+            ("parentId", library_id),
+            ("IncludeItemTypes", "Audio"),
+            ("recursive", "true"),
+            ("fields", fields),
+            ("EnableImages", "false"),
+            ("EnableUserData", "false"),
+            ("StartIndex", &start_index),
+            ("Limit", &limit),
+        ];
+        let response = self.get("Items", Some(&params)).await?;
+        let body = self.handle_response(response).await?;
+        Ok(serde_json::from_str(&body)?)
+    }
+
+    /// Where the given songs' files are and their MusicBrainz ids, for
+    /// exporting them to a playlist file
+    pub async fn get_song_files(
+        &self,
+        library_id: &str,
+        item_ids: &[String],
+    ) -> Result<Vec<SongFileDto>, BackendError> {
+        // Keeps the urls a sensible length
+        const IDS_PER_REQUEST: usize = 100;
+        const MAX_CONCURRENT_REQUESTS: usize = 4;
+
+        let pages: Vec<_> = stream::iter(item_ids.chunks(IDS_PER_REQUEST))
+            .map(|ids| {
+                self.get_song_files_page(library_id, Some(ids.join(",")), 0, ids.len() as u64)
+            })
+            .buffer_unordered(MAX_CONCURRENT_REQUESTS)
+            .collect()
+            .await;
+        let mut files = Vec::new();
+        for page in pages {
+            files.extend(page?.items);
+        }
+        Ok(files)
+    }
+
+    /// [`Self::get_song_files`] for the whole library, for matching imported
+    /// playlists by MusicBrainz id
+    pub async fn get_all_song_files(
+        &self,
+        library_id: &str,
+    ) -> Result<Vec<SongFileDto>, BackendError> {
+        const LIMIT: u64 = 1000;
+        const MAX_CONCURRENT_REQUESTS: usize = 4;
+
+        let first_page = self.get_song_files_page(library_id, None, 0, LIMIT).await?;
+        let additional_pages = first_page
+            .total_record_count
+            .saturating_sub(LIMIT)
+            .div_ceil(LIMIT);
+        let mut files = first_page.items;
+        let pages: Vec<_> = stream::iter(1..=additional_pages)
+            .map(|page| self.get_song_files_page(library_id, None, page * LIMIT, LIMIT))
+            .buffer_unordered(MAX_CONCURRENT_REQUESTS)
+            .collect()
+            .await;
+        for page in pages {
+            files.extend(page?.items);
+        }
+        Ok(files)
+    }
+
+    async fn get_song_files_page(
+        &self,
+        library_id: &str,
+        item_ids: Option<String>,
+        start_index: u64,
+        limit: u64,
+    ) -> Result<SongFileDtoList, BackendError> {
+        let start_index = start_index.to_string();
+        let limit = limit.to_string();
+        let mut params = vec![
+            ("parentId", library_id),
+            ("IncludeItemTypes", "Audio"),
+            ("recursive", "true"),
+            ("fields", "Path,ProviderIds"),
+            ("EnableImages", "false"),
+            ("EnableUserData", "false"),
+            ("StartIndex", &start_index),
+            ("Limit", &limit),
+        ];
+        if let Some(item_ids) = &item_ids {
+            params.push(("Ids", item_ids.as_str()));
+        }
+        let response = self.get("Items", Some(&params)).await?;
+        let body = self.handle_response(response).await?;
+        Ok(serde_json::from_str(&body)?)
+    }
+
+    /// Fetches just the given items, for applying library change
+    /// notifications. Ids that aren't songs in the library are left out.
+    /// They come with the credits and files the full download leaves out, so
+    /// those already fetched for the rest of the library stay good.
+    pub async fn get_library_items(
+        &self,
+        library_id: &str,
+        item_ids: &[String],
+    ) -> Result<MusicDtoList, BackendError> {
+        let ids = item_ids.join(",");
+        let params = vec![
+            ("parentId", library_id),
+            ("Ids", &ids),
+            ("IncludeItemTypes", "Audio"),
+            ("recursive", "true"),
+            (
+                "fields",
+                "DateCreated,Genres,People,Studios,Path,ProviderIds",
+            ),
+            ("ImageTypeLimit", "1"),
+            ("EnableImageTypes", "Primary"),
+        ];
+        let response = self.get("Items", Some(&params)).await?;
+        let body = self.handle_response(response).await?;
+        Ok(serde_json::from_str(&body)?)
+    }
+
+    pub async fn get_playlists(&self) -> Result<PlaylistDtoList, BackendError> {
+        let params = vec![
+            ("IncludeItemTypes", "Playlist"),
+            ("sortBy", "DateCreated"),
+            ("sortOrder", "Descending"),
+            ("recursive", "true"),
+            ("fields", "DateCreated,ChildCount,Overview"),
+            ("ImageTypeLimit", "1"),
+            ("EnableImageTypes", "Primary"),
+            ("StartIndex", "0"),
+        ];
+        let response = self.get("Items", Some(&params)).await?;
+        let body = self.handle_response(response).await?;
+        let final_result = serde_json::from_str(&body)?;
+
+        Ok(final_result)
+    }
+
+    pub async fn get_favorites(&self) -> Result<FavoriteDtoList, BackendError> {
+        let params = vec![
+            ("IncludeItemTypes", "Playlist,Audio,MusicAlbum,MusicArtist"),
+            ("sortBy", "DateCreated"),
+            ("sortOrder", "Descending"),
+            ("recursive", "true"),
+            ("StartIndex", "0"),
+            ("enableTotalRecordCount", "false"),
+            ("enableImages", "false"),
+            ("Filters", "IsFavorite"),
+            ("Limit", "10000"), // TODO implement pagination if this is ever an issue
+        ];
+        let response = self.get("Items", Some(&params)).await?;
+        let body = self.handle_response(response).await?;
+        let final_result = serde_json::from_str(&body)?;
+
+        Ok(final_result)
+    }
+
+    /// Every album in the library with the user's data, rated or not. There's
+    /// no filter for rated items, so they're fetched a page at a time until
+    /// one comes back short.
+    pub async fn get_album_ratings(&self, library_id: &str) -> Result<RatingDtoList, BackendError> {
+        const LIMIT: usize = 1000;
+
+        let limit = LIMIT.to_string();
+        let mut ratings = Vec::new();
+        let mut start_index = 0;
+        loop {
+            let start = start_index.to_string();
+            let params = vec![
+                ("parentId", library_id),
+                ("IncludeItemTypes", "MusicAlbum"),
+                ("recursive", "true"),
+                ("enableTotalRecordCount", "false"),
+                ("enableImages", "false"),
+                ("enableUserData", "true"),
+                ("StartIndex", &start),
+                ("Limit", &limit),
+            ];
+            let response = self.get("Items", Some(&params)).await?;
+            let body = self.handle_response(response).await?;
+            let page: RatingDtoList = serde_json::from_str(&body)?;
+            let count = page.items.len();
+            ratings.extend(page.items);
+            if count < LIMIT {
+                return Ok(RatingDtoList { items: ratings });
+            }
+            start_index += LIMIT;
+        }
+    }
+
+    pub async fn get_playlist_items(
+        &self,
+        playlist_id: &str,
+    ) -> Result<PlaylistItems, BackendError> {
+        let params = vec![("fields", "DateCreated,Genres")];
+        let path = format!("Playlists/{}/Items", playlist_id);
+        let response = self.get(&path, Some(&params)).await?;
+        let body = self.handle_response(response).await?;
+        Ok(serde_json::from_str(&body)?)
+    }
+
+    pub async fn add_playlist_items(
+        &self,
+        playlist_id: &str,
+        item_ids: &[String],
+    ) -> Result<(), BackendError> {
+        let path = format!("Playlists/{}/Items", playlist_id);
+        let ids = item_ids.join(",");
+        let params = vec![("ids", ids.as_str()), ("userId", &self.user_id)];
+        let response = self.post(&path, Some(&params), None).await?;
+        self.handle_response(response).await?;
+        Ok(())
+    }
+
+    pub async fn move_playlist_item(
+        &self,
+        playlist_id: &str,
+        item_id: &str,
+        new_index: i32,
+    ) -> Result<(), BackendError> {
+        let path = format!(
+            "Playlists/{}/Items/{}/Move/{}",
+            playlist_id, item_id, new_index
+        );
+        let response = self.post(&path, None, None).await?;
+        self.handle_response(response).await?;
+        Ok(())
+    }
+
+    pub async fn remove_playlist_item(
+        &self,
+        playlist_id: &str,
+        item_id: &str,
+    ) -> Result<(), BackendError> {
+        let path = format!("Playlists/{}/Items/", playlist_id);
+        let params = vec![("entryIds", item_id)];
+        self.delete(&path, Some(&params)).await?;
+        Ok(())
+    }
+
+    pub async fn new_playlist(
+        &self,
+        name: &str,
+        items: Vec<String>,
+    ) -> Result<String, BackendError> {
+        let path = "Playlists/";
+        let body = NewPlaylist {
+            name: name.to_string(),
+            ids: items,
+            user_id: self.user_id.clone(),
+            media_type: "Audio".to_string(),
+            users: vec![],
+            is_public: false,
+        };
+        let response = self.post_json(path, &body).await?;
+        let response = self.handle_response(response).await?;
+        let playlist_response: NewPlaylistResponse = serde_json::from_str(&response)?;
+        Ok(playlist_response.id)
+    }
+
+    pub async fn get_playlist_details(
+        &self,
+        playlist_id: &str,
+    ) -> Result<PlaylistDetails, BackendError> {
+        let item = self.get_item_json(playlist_id).await?;
+        let response = self.get(&format!("Playlists/{playlist_id}"), None).await?;
+        let body = self.handle_response(response).await?;
+        let access: PlaylistAccessDto = serde_json::from_str(&body)?;
+        Ok(PlaylistDetails {
+            name: item["Name"].as_str().unwrap_or_default().to_string(),
+            description: item["Overview"].as_str().unwrap_or_default().to_string(),
+            is_public: access.open_access,
+        })
+    }
+
+    /// The playlist endpoint only knows the name and visibility, the
+    /// description goes through `set_playlist_description`
+    pub async fn update_playlist(
+        &self,
+        playlist_id: &str,
+        details: &PlaylistDetails,
+    ) -> Result<(), BackendError> {
+        let body = UpdatePlaylist {
+            name: details.name.clone(),
+            is_public: details.is_public,
+        };
+        let response = self
+            .post_json(&format!("Playlists/{playlist_id}"), &body)
+            .await?;
+        self.handle_response(response).await.map(drop)
+    }
+
+    /// The description is the item's overview. Posting an item replaces all
+    /// of its metadata, so this sends back what the server has with only the
+    /// overview changed
+    pub async fn set_playlist_description(
+        &self,
+        playlist_id: &str,
+        description: &str,
+    ) -> Result<(), BackendError> {
+        let mut item = self.get_item_json(playlist_id).await?;
+        item["Overview"] = description.into();
+        let response = self
+            .post_json(&format!("Items/{playlist_id}"), &item)
+            .await?;
+        self.handle_response(response).await.map(drop)
+    }
+
+    /// Everyone with an account on the server
+    pub async fn get_users(&self) -> Result<Vec<UserDto>, BackendError> {
+        let response = self.get("Users", None).await?;
+        let body = self.handle_response(response).await?;
+        Ok(serde_json::from_str(&body)?)
+    }
+
+    /// Who the playlist is shared with, the owner isn't in it
+    pub async fn get_playlist_users(
+        &self,
+        playlist_id: &str,
+    ) -> Result<Vec<PlaylistUserPermissions>, BackendError> {
+        let response = self
+            .get(&format!("Playlists/{playlist_id}/Users"), None)
+            .await?;
+        let body = self.handle_response(response).await?;
+        Ok(serde_json::from_str(&body)?)
+    }
+
+    /// Only the owner and users it's shared with for editing may see who
+    /// else has it, so being refused means it's read only
+    pub async fn can_edit_playlist(&self, playlist_id: &str) -> Result<bool, BackendError> {
+        match self.get_playlist_users(playlist_id).await {
+            Ok(_) => Ok(true),
+            Err(BackendError::Http { status, .. }) if status == StatusCode::FORBIDDEN => Ok(false),
+            Err(err) => Err(err),
+        }
+    }
+
+    pub async fn share_playlist(
+        &self,
+        playlist_id: &str,
+        user_id: &str,
+        can_edit: bool,
+    ) -> Result<(), BackendError> {
+        let body = UpdatePlaylistUser { can_edit };
+        let response = self
+            .post_json(&format!("Playlists/{playlist_id}/Users/{user_id}"), &body)
+            .await?;
+        self.handle_response(response).await.map(drop)
+    }
+
+    pub async fn unshare_playlist(
+        &self,
+        playlist_id: &str,
+        user_id: &str,
+    ) -> Result<(), BackendError> {
+        let response = self
+            .delete(&format!("Playlists/{playlist_id}/Users/{user_id}"), None)
+            .await?;
+        self.handle_response(response).await.map(drop)
+    }
+
+    /// Jellyfin takes the image base64 encoded, with its own content type
+    pub async fn set_primary_image(
+        &self,
+        item_id: &str,
+        image: &[u8],
+        content_type: &str,
+    ) -> Result<(), BackendError> {
+        let url = self.format_url(&format!("Items/{item_id}/Images/Primary"));
+        debug!("Sending POST request to {}", url);
+        let response = self
+            .client
+            .post(&url)
+            .header("Authorization", self.auth_header())
+            .header("Content-Type", content_type)
+            .body(glib::base64_encode(image).to_string())
+            .send()
+            .await?;
+        self.handle_response(response).await.map(drop)
+    }
+
+    async fn get_item_json(&self, item_id: &str) -> Result<serde_json::Value, BackendError> {
+        let endpoint = format!("Users/{}/Items/{item_id}", self.user_id);
+        let response = self.get(&endpoint, None).await?;
+        let body = self.handle_response(response).await?;
+        Ok(serde_json::from_str(&body)?)
+    }
+
+    pub async fn delete_item(&self, item_id: &str) -> Result<(), BackendError> {
+        let path = format!("Items/{}", item_id);
+        self.delete(&path, None).await?;
+        Ok(())
+    }
+
+    pub async fn set_favorite(&self, item_id: &str, is_favorite: bool) -> Result<(), BackendError> {
+        let path = format!("UserFavoriteItems/{item_id}");
+        let response = if is_favorite {
+            self.post(&path, None, None).await?
+        } else {
+            self.delete(&path, None).await?
+        };
+        self.handle_response(response).await.map(drop)
+    }
+
+    /// `stars` from one to five, zero clears the rating
+    pub async fn set_rating(&self, item_id: &str, stars: u32) -> Result<(), BackendError> {
+        // Jellyfin only changes the fields that are set, so a cleared
+        // rating is sent as zero rather than left out
+        let rating = rating_from_stars(stars).unwrap_or(0.0);
+        let body = json!({ "Rating": rating });
+        let response = self
+            .post_json(&format!("UserItems/{item_id}/UserData"), &body)
+            .await?;
+        self.handle_response(response).await.map(drop)
+    }
+
+    pub async fn request_library_rescan(&self, library_id: &str) -> Result<(), BackendError> {
+        let params = vec![
+            ("itemId", library_id),
+            ("Recursive", "true"),
+            ("ImageRefreshMode", "Default"),
+            ("MetadataRefreshMode", "Default"),
+            ("ReplaceAllImages", "false"),
+            ("RegenerateTrickplay", "false"),
+            ("ReplaceAllMetadata", "false"),
+        ];
+        let response = self
+            .post(
+                &format!("Items/{}/Refresh", library_id),
+                Some(&params),
+                None,
+            )
+            .await?;
+        self.handle_response(response).await?;
+        Ok(())
+    }
+
+    pub async fn get_image(
+        &self,
+        item_id: &str,
+        image_type: ImageType,
+        scale: f32,
+    ) -> Result<Vec<u8>, BackendError> {
+        let (base_w, base_h) = match image_type {
+            ImageType::Backdrop => (1920.0, 300.0),
+            _ => (200.0, 200.0),
+        };
+        let width = ((base_w * scale).round() as u32).max(1).to_string();
+        let height = ((base_h * scale).round() as u32).max(1).to_string();
+        let params = match image_type {
+            ImageType::Backdrop => vec![
+                ("maxWidth", width.as_str()),
+                ("maxHeight", height.as_str()),
+                ("quality", "96"),
+            ],
+            _ => vec![
+                ("fillWidth", width.as_str()),
+                ("fillHeight", height.as_str()),
+                ("quality", "96"),
+            ],
+        };
+        let response = self
+            .get(
+                &format!("Items/{}/Images/{}", item_id, image_type.as_str()),
+                Some(&params),
+            )
+            .await?;
+        self.handle_binary_response(response).await
+    }
+
+    pub fn get_stream_uri(&self, item_id: &str) -> String {
+        let tc_profile = config::get_transcoding_profile();
+        let containers = "flac,opus,mp3,aac,m4a,ogg,wav,webm|opus,webm|webma,webma";
+        let tc_audio_codec = tc_profile.codec;
+        let tc_container = tc_profile.container;
+        let max_bitrate = config::get_max_bitrate().unwrap_or(999999999);
+        debug!(
+            "Stream settings: {} + {} @ {}",
+            tc_audio_codec, tc_container, max_bitrate
+        );
+        let stream_protocol = "hls";
+        format!(
+            "{}/Audio/{item_id}/universal?api_key={}&userId={}&container={}&audioCodec={}&transcodingContainer={}&transcodingProtocol={}&maxStreamingBitrate={}",
+            self.host.trim_end_matches("/"),
+            self.token,
+            self.user_id,
+            containers,
+            tc_audio_codec,
+            tc_container,
+            stream_protocol,
+            max_bitrate
+        )
+    }
+
+    pub async fn get_playback_info(&self, item_id: &str) -> Result<PlaybackInfo, BackendError> {
+        let response = self
+            .get(&format!("Items/{}/PlaybackInfo", item_id), None)
+            .await?;
+        let body = self.handle_response(response).await?;
+        Ok(serde_json::from_str(&body)?)
+    }
+
+    pub async fn playback_report(
+        &self,
+        report: &PlaybackReport,
+        state: &PlaybackReportStatus,
+    ) -> Result<(), BackendError> {
+        let path = match state {
+            PlaybackReportStatus::Started => "",
+            PlaybackReportStatus::InProgress => "Progress",
+            PlaybackReportStatus::Stopped => "Stopped",
+        };
+        let response = self
+            .post_json(&format!("Sessions/Playing/{}", path), report)
+            .await?;
+        self.handle_response(response).await?;
+        Ok(())
+    }
+
+    /// Biography and similar artists. Jellyfin only knows similar artists
+    /// from the library, and when it can't find any the biography is still
+    /// shown.
+    pub async fn get_artist_info(&self, artist_id: &str) -> Result<ArtistInfo, BackendError> {
+        let (biography, similar) = tokio::join!(
+            self.get_artist_biography(artist_id),
+            self.get_similar_artists(artist_id)
+        );
+        let similar = similar.unwrap_or_else(|e| {
+            warn!("Failed to get artists similar to {}: {}", artist_id, e);
+            Vec::new()
+        });
+        Ok(ArtistInfo::new(biography?, similar))
+    }
+
+    async fn get_artist_biography(&self, artist_id: &str) -> Result<Option<String>, BackendError> {
+        let params = vec![("Ids", artist_id), ("fields", "Overview")];
+        let response = self.get("Items", Some(&params)).await?;
+        let body = self.handle_response(response).await?;
+        let artist: OverviewDtoList = serde_json::from_str(&body)?;
+        Ok(artist.items.into_iter().next().and_then(|dto| dto.overview))
+    }
+
+    async fn get_similar_artists(
+        &self,
+        artist_id: &str,
+    ) -> Result<Vec<ArtistItemsDto>, BackendError> {
+        let params = vec![("userId", self.user_id.as_str()), ("limit", "12")];
+        let response = self
+            .get(&format!("Artists/{}/Similar", artist_id), Some(&params))
+            .await?;
+        let body = self.handle_response(response).await?;
+        let similar: ArtistItemsDtoList = serde_json::from_str(&body)?;
+        Ok(similar.items)
+    }
+
+    /// Extended album metadata. Jellyfin has no disc subtitles, and sizes and
+    /// formats come from the tracks.
+    pub async fn get_album_info(&self, album_id: &str) -> Result<AlbumInfo, BackendError> {
+        let params = vec![
+            ("Ids", album_id),
+            ("fields", "Overview,Studios,ProviderIds,People"),
+        ];
+        let response = self.get("Items", Some(&params)).await?;
+        let body = self.handle_response(response).await?;
+        let album: AlbumDetailsDtoList = serde_json::from_str(&body)?;
+        let Some(album) = album.items.into_iter().next() else {
+            return Ok(AlbumInfo::default());
+        };
+
+        let params = vec![
+            ("parentId", album_id),
+            ("IncludeItemTypes", "Audio"),
+            ("recursive", "true"),
+            ("fields", "MediaSources,People"),
+        ];
+        let response = self.get("Items", Some(&params)).await?;
+        let body = self.handle_response(response).await?;
+        let tracks: TrackDetailsDtoList = serde_json::from_str(&body)?;
+        Ok(AlbumInfo::from_jellyfin(album, tracks.items))
+    }
+
+    /// A folder with its direct children, the library itself when no folder is given.
+    pub async fn get_folder(
+        &self,
+        library_id: &str,
+        folder_id: Option<&str>,
+    ) -> Result<Folder, BackendError> {
+        let folder_id = folder_id.unwrap_or(library_id);
+        let params = vec![("Ids", folder_id), ("fields", "ParentId,Path")];
+        let response = self.get("Items", Some(&params)).await?;
+        let body = self.handle_response(response).await?;
+        let folder: FolderItemDtoList = serde_json::from_str(&body)?;
+        let Some(folder) = folder.items.into_iter().next() else {
+            return Err(BackendError::Http {
+                status: StatusCode::NOT_FOUND,
+                message: format!("Folder {folder_id} not found"),
+            });
+        };
+
+        let params = vec![
+            ("parentId", folder_id),
+            // Folders have no track numbers so they end up by name
+            ("sortBy", "ParentIndexNumber,IndexNumber,SortName"),
+        ];
+        let response = self.get("Items", Some(&params)).await?;
+        let body = self.handle_response(response).await?;
+        let children: FolderItemDtoList = serde_json::from_str(&body)?;
+        Ok(Folder::from_jellyfin(folder, library_id, children.items))
+    }
+
+    /// Every song under a folder, album by album
+    pub async fn get_folder_song_ids(
+        &self,
+        library_id: &str,
+        folder_id: Option<&str>,
+    ) -> Result<Vec<String>, BackendError> {
+        let params = vec![
+            ("parentId", folder_id.unwrap_or(library_id)),
+            ("IncludeItemTypes", "Audio"),
+            ("recursive", "true"),
+            ("sortBy", "Album,ParentIndexNumber,IndexNumber,SortName"),
+        ];
+        let response = self.get("Items", Some(&params)).await?;
+        let body = self.handle_response(response).await?;
+        let songs: FolderItemDtoList = serde_json::from_str(&body)?;
+        Ok(songs.items.into_iter().map(|song| song.id).collect())
+    }
+
+    /// The folder a song file is in
+    pub async fn get_song_folder_id(&self, song_id: &str) -> Result<Option<String>, BackendError> {
+        let params = vec![("Ids", song_id), ("fields", "ParentId")];
+        let response = self.get("Items", Some(&params)).await?;
+        let body = self.handle_response(response).await?;
+        let song: FolderItemDtoList = serde_json::from_str(&body)?;
+        Ok(song
+            .items
+            .into_iter()
+            .next()
+            .and_then(|song| song.parent_id))
+    }
+
+    /// Jellyfin serves podcasts through channel plugins, channels without
+    /// any audio are left out.
+    pub async fn get_podcasts(&self) -> Result<Vec<PodcastChannel>, BackendError> {
+        let params = vec![("userId", self.user_id.as_str())];
+        let response = self.get("Channels", Some(&params)).await?;
+        let body = self.handle_response(response).await?;
+        let channels: ChannelDtoList = serde_json::from_str(&body)?;
+
+        const MAX_CONCURRENT_REQUESTS: usize = 4;
+        // Channels keep the server's order whichever answers first
+        let podcasts = stream::iter(channels.items)
+            .map(|channel| async move {
+                let episodes = self.get_channel_episodes(&channel).await;
+                (channel, episodes)
+            })
+            .buffered(MAX_CONCURRENT_REQUESTS)
+            .filter_map(|(channel, episodes)| async move {
+                let episodes = episodes
+                    .inspect_err(|err| warn!("Failed to fetch channel {}: {}", channel.name, err))
+                    .ok()
+                    .filter(|episodes| !episodes.is_empty())?;
+                Some(PodcastChannel {
+                    image_id: Some(channel.id.clone()),
+                    id: channel.id,
+                    title: channel.name,
+                    description: channel.overview,
+                    episodes,
+                })
+            })
+            .collect()
+            .await;
+        Ok(podcasts)
+    }
+
+    async fn get_channel_episodes(
+        &self,
+        channel: &ChannelDto,
+    ) -> Result<Vec<PodcastEpisode>, BackendError> {
+        let params = vec![
+            ("userId", self.user_id.as_str()),
+            ("fields", "Overview,DateCreated"),
+            ("sortBy", "PremiereDate"),
+            ("sortOrder", "Descending"),
+        ];
+        let response = self
+            .get(&format!("Channels/{}/Items", channel.id), Some(&params))
+            .await?;
+        let body = self.handle_response(response).await?;
+        let items: ChannelItemDtoList = serde_json::from_str(&body)?;
+        Ok(items
+            .items
+            .into_iter()
+            .filter(|item| item.is_audio())
+            .map(|item| item.into_episode(Some(channel)))
+            .collect())
+    }
+
+    pub async fn get_newest_podcast_episodes(
+        &self,
+        count: u32,
+    ) -> Result<Vec<PodcastEpisode>, BackendError> {
+        let count = count.to_string();
+        let params = vec![
+            ("userId", self.user_id.as_str()),
+            ("limit", count.as_str()),
+            ("fields", "Overview,DateCreated"),
+        ];
+        let response = self.get("Channels/Items/Latest", Some(&params)).await?;
+        let body = self.handle_response(response).await?;
+        let items: ChannelItemDtoList = serde_json::from_str(&body)?;
+        Ok(items
+            .items
+            .into_iter()
+            .filter(|item| item.is_audio())
+            .map(|item| item.into_episode(None))
+            .collect())
+    }
+
+    pub async fn set_played(&self, item_id: &str, played: bool) -> Result<(), BackendError> {
+        let path = format!("UserPlayedItems/{item_id}");
+        let response = if played {
+            self.post(&path, None, None).await?
+        } else {
+            self.delete(&path, None).await?
+        };
+        self.handle_response(response).await.map(drop)
+    }
+
+    /// Remembers where we got to in a track, for picking it up on any device
+    pub async fn set_playback_position(
+        &self,
+        item_id: &str,
+        position_ticks: u64,
+    ) -> Result<(), BackendError> {
+        let body = json!({ "PlaybackPositionTicks": position_ticks });
+        let response = self
+            .post_json(&format!("UserItems/{item_id}/UserData"), &body)
+            .await?;
+        self.handle_response(response).await.map(drop)
+    }
+
+    /// Every audiobook the user can see, whichever library it's in
+    pub async fn get_audiobooks(&self) -> Result<Vec<Audiobook>, BackendError> {
+        let endpoint = format!("Users/{}/Items", self.user_id);
+        let params = vec![
+            ("IncludeItemTypes", "AudioBook"),
+            ("Recursive", "true"),
+            ("SortBy", "SortName,ParentIndexNumber,IndexNumber"),
+            ("Fields", "Chapters,Overview"),
+            ("EnableUserData", "true"),
+        ];
+        let response = self.get(&endpoint, Some(&params)).await?;
+        let body = self.handle_response(response).await?;
+        let books: AudiobookDtoList = serde_json::from_str(&body)?;
+        Ok(books.items.into_iter().map(Audiobook::from).collect())
+    }
+
+    /// Fetched again before playing, another device may have moved on since
+    pub async fn get_audiobook(&self, item_id: &str) -> Result<Audiobook, BackendError> {
+        let endpoint = format!("Users/{}/Items/{item_id}", self.user_id);
+        let response = self.get(&endpoint, None).await?;
+        let body = self.handle_response(response).await?;
+        let book: AudiobookDto = serde_json::from_str(&body)?;
+        Ok(book.into())
+    }
+
+    /// Live TV channels that are radio stations
+    pub async fn get_radio_stations(&self) -> Result<Vec<RadioStation>, BackendError> {
+        let params = vec![
+            ("userId", self.user_id.as_str()),
+            ("type", "Radio"),
+            ("sortBy", "SortName"),
+        ];
+        let response = self.get("LiveTv/Channels", Some(&params)).await?;
+        let body = self.handle_response(response).await?;
+        let channels: ChannelDtoList = serde_json::from_str(&body)?;
+        Ok(channels
+            .items
+            .into_iter()
+            .map(|channel| RadioStation {
+                stream_url: self.get_stream_uri(&channel.id),
+                id: channel.id,
+                name: channel.name,
+                homepage_url: None,
+                user_added: false,
+            })
+            .collect())
+    }
+
+    /// The original file, for keeping a copy on this computer
+    pub fn get_download_uri(&self, item_id: &str) -> String {
+        format!(
+            "{}/Items/{item_id}/Download?api_key={}",
+            self.host.trim_end_matches("/"),
+            self.token,
+        )
+    }
+
+    pub async fn fetch_lyrics(&self, item_id: &str) -> Result<LyricsResponse, BackendError> {
+        let response = self.get(&format!("Audio/{}/Lyrics", item_id), None).await?;
+        let body = self.handle_response(response).await?;
+        let lyrics: LyricsDto = serde_json::from_str(&body)?;
+        Ok(LyricsResponse {
+            lyrics: lyrics.lyrics.into_iter().map(Lyric::from).collect(),
+        })
+    }
+
+    /// Uploading lyrics needs lyric management, which admins always have.
+    pub async fn can_manage_lyrics(&self) -> Result<bool, BackendError> {
+        let response = self.get(&format!("Users/{}", self.user_id), None).await?;
+        let body = self.handle_response(response).await?;
+        let user: UserDto = serde_json::from_str(&body)?;
+        Ok(user.policy.is_administrator || user.policy.enable_lyric_management)
+    }
+
+    /// Replaces the lyrics stored for a song with an LRC file.
+    pub async fn upload_lyrics(&self, item_id: &str, lrc: String) -> Result<(), BackendError> {
+        let url = self.format_url(&format!("Audio/{}/Lyrics", item_id));
+        debug!("Sending POST request to {}", url);
+        let response = self
+            .client
+            .post(&url)
+            .query(&[("fileName", format!("{item_id}.lrc"))])
+            .header("Authorization", self.auth_header())
+            .header("Content-Type", "text/plain")
+            .body(lrc)
+            .send()
+            .await?;
+        self.handle_response(response).await.map(drop)
+    }
+
+    fn get_hostname(&self) -> &'static str {
+        static HOSTNAME: OnceLock<String> = OnceLock::new();
+        HOSTNAME.get_or_init(|| {
+            fs::read_to_string("/proc/sys/kernel/hostname")
+                .unwrap_or_else(|_| "Gelly-Device".to_string())
+                .trim()
+                .to_string()
+        })
+    }
+
+    fn auth_header(&self) -> String {
+        let device = self.get_hostname();
+        let auth = if !self.token.is_empty() {
+            format!(", Token=\"{}\"", self.token)
+        } else {
+            "".to_string()
+        };
+        let uuid = config::application_uuid();
+
+        format!(
+            "MediaBrowser Client=\"{}\", Device=\"{}\", DeviceId=\"{}\", Version=\"{}\"{}",
+            CLIENT_ID,
+            device,
+            uuid,
+            config::VERSION,
+            auth
+        )
+    }
+
+    async fn post_json<T>(&self, endpoint: &str, body: &T) -> Result<Response, BackendError>
+    where
+        T: serde::Serialize,
+    {
+        let url = format!(
+            "{}/{}",
+            self.host.trim_end_matches('/'),
+            endpoint.trim_start_matches('/')
+        );
+        debug!("Sending POST request to {}", url);
+        let request = self
+            .client
+            .post(&url)
+            .json(&body)
+            .header("Authorization", self.auth_header());
+        let response = request.send().await?;
+        Ok(response)
+    }
+
+    async fn post(
+        &self,
+        endpoint: &str,
+        params: Option<&[(&str, &str)]>,
+        body: Option<String>,
+    ) -> Result<Response, BackendError> {
+        let url = self.format_url(endpoint);
+        debug!("Sending POST request to {}", url);
+        let request = self
+            .client
+            .post(&url)
+            .query(params.unwrap_or(&[]))
+            .body(body.unwrap_or_default())
+            .header("Authorization", self.auth_header());
+        let response = request.send().await?;
+        Ok(response)
+    }
+
+    /// Any GET request
+    async fn get(
+        &self,
+        endpoint: &str,
+        params: Option<&[(&str, &str)]>,
+    ) -> Result<Response, BackendError> {
+        let url = self.format_url(endpoint);
+        debug!("Sending GET request to {}", url);
+        let request = self
+            .client
+            .get(&url)
+            .query(params.unwrap_or(&[]))
+            .header("Authorization", self.auth_header());
+        let response = request.send().await?;
+        Ok(response)
+    }
+
+    async fn delete(
+        &self,
+        endpoint: &str,
+        params: Option<&[(&str, &str)]>,
+    ) -> Result<Response, BackendError> {
+        let url = self.format_url(endpoint);
+        debug!("Sending DELETE request to {}", url);
+        let request = self
+            .client
+            .delete(&url)
+            .query(params.unwrap_or(&[]))
+            .header("Authorization", self.auth_header());
+        let response = request.send().await?;
+        Ok(response)
+    }
+
+    fn format_url(&self, endpoint: &str) -> String {
+        format!(
+            "{}/{}",
+            self.host.trim_end_matches('/'),
+            endpoint.trim_start_matches('/')
+        )
+    }
+
+    /// Responsible for error handling when reading responses from Jellyfin
+    async fn handle_response(&self, response: Response) -> Result<String, BackendError> {
+        let status = response.status();
+        if status.is_success() {
+            let response_body = response.text().await?;
+            Ok(response_body)
+        } else {
+            let message = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "Unknown Error".to_string());
+
+            match status {
+                StatusCode::UNAUTHORIZED => Err(BackendError::AuthenticationFailed { message }),
+                _ => Err(BackendError::Http { status, message }),
+            }
+        }
+    }
+
+    /// Same as handle_response but does not deserialize the response body.
+    async fn handle_binary_response(&self, response: Response) -> Result<Vec<u8>, BackendError> {
+        let status = response.status();
+        if status.is_success() {
+            let response_body = response.bytes().await?;
+            Ok(response_body.to_vec())
+        } else {
+            let message = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "Unknown Error".to_string());
+
+            match status {
+                StatusCode::UNAUTHORIZED => Err(BackendError::AuthenticationFailed { message }),
+                _ => Err(BackendError::Http { status, message }),
+            }
+        }
+    }
+}
+
+impl Default for Jellyfin {
+    fn default() -> Self {
+        Self::new("", "", "")
+    }
+}
+
+pub async fn initiate_quick_connect(host: &str) -> Result<QuickConnectResponse, BackendError> {
+    let jellyfin = Jellyfin::new(host, "", "");
+    let response = jellyfin.post("QuickConnect/Initiate", None, None).await?;
+    let body = jellyfin.handle_response(response).await?;
+    Ok(serde_json::from_str(&body)?)
+}
+
+pub async fn quick_connect_status(
+    host: &str,
+    secret: &str,
+) -> Result<QuickConnectResponse, BackendError> {
+    let jellyfin = Jellyfin::new(host, "", "");
+    let params = vec![("secret", secret)];
+    let response = jellyfin.get("QuickConnect/Connect", Some(&params)).await?;
+    let body = jellyfin.handle_response(response).await?;
+    Ok(serde_json::from_str(&body)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_server::serve_once;
+
+    #[tokio::test]
+    async fn test_album_ratings_stop_at_short_page() {
+        let (host, server) = serve_once(
+            "200 OK",
+            r#"{"Items": [{"Id": "a", "UserData": {"Rating": 8}}, {"Id": "b", "UserData": {}}]}"#,
+        )
+        .await;
+        let jellyfin = Jellyfin::new(&host, "token", "me");
+        let ratings = jellyfin.get_album_ratings("music").await.unwrap();
+        assert_eq!(ratings.items.len(), 2);
+        assert_eq!(ratings.items[0].user_data.rating, Some(8.0));
+        let request = server.await.unwrap();
+        assert!(request.contains("StartIndex=0&Limit=1000"), "{request}");
+    }
+
+    #[tokio::test]
+    async fn test_can_edit_playlist() {
+        let (host, server) =
+            serve_once("200 OK", r#"[{"UserId": "friend", "CanEdit": true}]"#).await;
+        let jellyfin = Jellyfin::new(&host, "token", "me");
+        assert!(jellyfin.can_edit_playlist("mix").await.unwrap());
+        assert_eq!(server.await.unwrap(), "GET /Playlists/mix/Users HTTP/1.1");
+
+        // Shared with us to view only
+        let (host, _server) = serve_once("403 Forbidden", "").await;
+        let jellyfin = Jellyfin::new(&host, "token", "me");
+        assert!(!jellyfin.can_edit_playlist("mix").await.unwrap());
+
+        let (host, _server) = serve_once("500 Internal Server Error", "").await;
+        let jellyfin = Jellyfin::new(&host, "token", "me");
+        assert!(matches!(
+            jellyfin.can_edit_playlist("mix").await,
+            Err(BackendError::Http { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_update_playlist_leaves_description() {
+        let (host, server) = serve_once("204 No Content", "").await;
+        let jellyfin = Jellyfin::new(&host, "token", "me");
+        let details = PlaylistDetails {
+            name: "Mix".to_string(),
+            description: "Unchanged".to_string(),
+            is_public: true,
+        };
+        jellyfin.update_playlist("mix", &details).await.unwrap();
+        assert_eq!(server.await.unwrap(), "POST /Playlists/mix HTTP/1.1");
+    }
+
+    #[test]
+    fn test_playlist_sharing_json() {
+        let users: Vec<UserDto> = serde_json::from_str(
+            r#"[{"Id": "1", "Name": "Alice", "Policy": {"IsAdministrator": true}},
+                {"Id": "2", "Name": "Bob"}]"#,
+        )
+        .unwrap();
+        assert_eq!(users[0].name, "Alice");
+        assert!(users[0].policy.is_administrator);
+        assert_eq!(users[1].id, "2");
+        assert!(!users[1].policy.is_administrator);
+
+        assert_eq!(
+            serde_json::to_value(UpdatePlaylistUser { can_edit: true }).unwrap(),
+            json!({"CanEdit": true})
+        );
+    }
+}
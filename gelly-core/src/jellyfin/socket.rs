@@ -0,0 +1,438 @@
+//! The Jellyfin session websocket (`/socket`). The server pushes SyncPlay
+//! group updates, remote control commands from other clients and library
+//! change notifications over it.
+
+use std::time::Duration;
+
+use async_channel::Sender;
+use futures_util::{SinkExt, StreamExt};
+use log::{debug, warn};
+use serde::Deserialize;
+use serde_json::Value;
+use thiserror::Error;
+use tokio_tungstenite::{connect_async, tungstenite::Message};
+
+use crate::backend::BackendError;
+use crate::config;
+use crate::jellyfin::Jellyfin;
+use crate::jellyfin::api::{
+    ClientCapabilities, GeneralCommand, GroupInfoDto, GroupStateUpdate, LibraryChangedDto,
+    PlayQueueUpdate, PlayRequest, PlaystateRequest, SyncPlayCommand, UserDataChangedDto,
+};
+
+const DEFAULT_KEEP_ALIVE: Duration = Duration::from_secs(30);
+
+/// General commands we act on, advertised so other clients show the controls
+pub const SUPPORTED_COMMANDS: &[&str] = &[
+    "VolumeUp",
+    "VolumeDown",
+    "Mute",
+    "Unmute",
+    "ToggleMute",
+    "SetVolume",
+    "SetRepeatMode",
+    "SetShuffleQueue",
+];
+
+#[derive(Error, Debug)]
+pub enum SocketError {
+    #[error("Websocket error: {0}")]
+    WebSocket(#[from] tokio_tungstenite::tungstenite::Error),
+
+    #[error("JSON parsing error: {0}")]
+    JsonParsing(#[from] serde_json::Error),
+
+    #[error("Connection closed by server")]
+    Closed,
+}
+
+#[derive(Debug, Clone)]
+pub enum GroupUpdate {
+    GroupJoined(GroupInfoDto),
+    GroupLeft,
+    UserJoined(String),
+    UserLeft(String),
+    StateUpdate(GroupStateUpdate),
+    PlayQueue(PlayQueueUpdate),
+    NotInGroup,
+    /// Any of the *Denied and GroupDoesNotExist updates
+    Rejected(String),
+    Other(String),
+}
+
+#[derive(Debug, Clone)]
+pub enum SocketMessage {
+    ForceKeepAlive(u64),
+    KeepAlive,
+    Play(PlayRequest),
+    Playstate(PlaystateRequest),
+    GeneralCommand(GeneralCommand),
+    LibraryChanged(LibraryChangedDto),
+    UserDataChanged(UserDataChangedDto),
+    SyncPlayCommand(SyncPlayCommand),
+    SyncPlayGroupUpdate {
+        group_id: String,
+        update: GroupUpdate,
+    },
+    Other(String),
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct Envelope {
+    message_type: String,
+    #[serde(default)]
+    data: Value,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct GroupUpdateEnvelope {
+    #[serde(default)]
+    group_id: String,
+    #[serde(rename = "Type")]
+    update_type: String,
+    #[serde(default)]
+    data: Value,
+}
+
+impl Jellyfin {
+    /// Address of the session websocket. The device id has to match the one
+    /// in the auth header so the server ties the socket to this session.
+    pub fn socket_url(&self) -> String {
+        let host = self.host.trim_end_matches('/');
+        let host = if let Some(rest) = host.strip_prefix("https://") {
+            format!("wss://{rest}")
+        } else if let Some(rest) = host.strip_prefix("http://") {
+            format!("ws://{rest}")
+        } else {
+            host.to_string()
+        };
+        format!(
+            "{}/socket?api_key={}&deviceId={}",
+            host,
+            self.token,
+            config::application_uuid()
+        )
+    }
+
+    /// Registers this session as a remote controllable audio player.
+    pub async fn post_capabilities(&self) -> Result<(), BackendError> {
+        let capabilities = ClientCapabilities {
+            playable_media_types: vec!["Audio".to_string()],
+            supported_commands: SUPPORTED_COMMANDS.iter().map(|c| c.to_string()).collect(),
+            supports_media_control: true,
+            supports_persistent_identifier: true,
+        };
+        let response = self
+            .post_json("Sessions/Capabilities/Full", &capabilities)
+            .await?;
+        self.handle_response(response).await?;
+        Ok(())
+    }
+}
+
+pub fn parse_message(text: &str) -> Result<SocketMessage, SocketError> {
+    let envelope: Envelope = serde_json::from_str(text)?;
+    let message = match envelope.message_type.as_str() {
+        "ForceKeepAlive" => {
+            SocketMessage::ForceKeepAlive(envelope.data.as_u64().unwrap_or_default())
+        }
+        "KeepAlive" => SocketMessage::KeepAlive,
+        "Play" => SocketMessage::Play(serde_json::from_value(envelope.data)?),
+        "Playstate" => SocketMessage::Playstate(serde_json::from_value(envelope.data)?),
+        "GeneralCommand" => SocketMessage::GeneralCommand(serde_json::from_value(envelope.data)?),
+        "LibraryChanged" => SocketMessage::LibraryChanged(serde_json::from_value(envelope.data)?),
+        "UserDataChanged" => SocketMessage::UserDataChanged(serde_json::from_value(envelope.data)?),
+        "SyncPlayCommand" => SocketMessage::SyncPlayCommand(serde_json::from_value(envelope.data)?),
+        "SyncPlayGroupUpdate" => {
+            let envelope: GroupUpdateEnvelope = serde_json::from_value(envelope.data)?;
+            let data = envelope.data;
+            let update = match envelope.update_type.as_str() {
+                "GroupJoined" => GroupUpdate::GroupJoined(serde_json::from_value(data)?),
+                "GroupLeft" => GroupUpdate::GroupLeft,
+                "UserJoined" => GroupUpdate::UserJoined(data.as_str().unwrap_or_default().into()),
+                "UserLeft" => GroupUpdate::UserLeft(data.as_str().unwrap_or_default().into()),
+                "StateUpdate" => GroupUpdate::StateUpdate(serde_json::from_value(data)?),
+                "PlayQueue" => GroupUpdate::PlayQueue(serde_json::from_value(data)?),
+                "NotInGroup" => GroupUpdate::NotInGroup,
+                "GroupDoesNotExist"
+                | "CreateGroupDenied"
+                | "JoinGroupDenied"
+                | "LibraryAccessDenied" => GroupUpdate::Rejected(envelope.update_type),
+                _ => GroupUpdate::Other(envelope.update_type),
+            };
+            SocketMessage::SyncPlayGroupUpdate {
+                group_id: envelope.group_id,
+                update,
+            }
+        }
+        _ => SocketMessage::Other(envelope.message_type),
+    };
+    Ok(message)
+}
+
+/// Connects to the session websocket and forwards server messages to `sender`
+/// until either side goes away. Keep alives are answered here and never
+/// forwarded. Returns `Ok` once the receiving end has been dropped.
+pub async fn run(url: &str, sender: Sender<SocketMessage>) -> Result<(), SocketError> {
+    let (stream, _) = connect_async(url).await?;
+    debug!("Session websocket connected");
+    let (mut write, mut read) = stream.split();
+    let mut keep_alive = tokio::time::interval(DEFAULT_KEEP_ALIVE);
+
+    loop {
+        tokio::select! {
+            message = read.next() => {
+                let Some(message) = message else {
+                    return Err(SocketError::Closed);
+                };
+                let text = match message? {
+                    Message::Text(text) => text,
+                    Message::Close(_) => return Err(SocketError::Closed),
+                    _ => continue,
+                };
+                match parse_message(text.as_str()) {
+                    Ok(SocketMessage::ForceKeepAlive(seconds)) => {
+                        // Server drops us after `seconds` of silence
+                        let period = Duration::from_secs(seconds.max(2) / 2);
+                        keep_alive = tokio::time::interval(period);
+                    }
+                    Ok(SocketMessage::KeepAlive) => {}
+                    Ok(message) => {
+                        if sender.send(message).await.is_err() {
+                            return Ok(());
+                        }
+                    }
+                    Err(e) => warn!("Failed to parse session socket message: {}", e),
+                }
+            }
+            _ = keep_alive.tick() => {
+                if sender.is_closed() {
+                    return Ok(());
+                }
+                write
+                    .send(Message::text(r#"{"MessageType":"KeepAlive"}"#))
+                    .await?;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jellyfin::api::{PlayCommand, PlaystateCommand, SyncPlayCommandType};
+    use tokio::net::TcpListener;
+
+    const GROUP_JOINED: &str = r#"{
+        "MessageType": "SyncPlayGroupUpdate",
+        "MessageId": "a",
+        "Data": {
+            "GroupId": "g1",
+            "Type": "GroupJoined",
+            "Data": {
+                "GroupId": "g1",
+                "GroupName": "Team",
+                "State": "Idle",
+                "Participants": ["alice", "bob"],
+                "LastUpdatedAt": "2025-01-02T03:04:05.0000000Z"
+            }
+        }
+    }"#;
+
+    const PLAY_QUEUE: &str = r#"{
+        "MessageType": "SyncPlayGroupUpdate",
+        "MessageId": "b",
+        "Data": {
+            "GroupId": "g1",
+            "Type": "PlayQueue",
+            "Data": {
+                "Reason": "NewPlaylist",
+                "LastUpdate": "2025-01-02T03:04:05.0000000Z",
+                "Playlist": [
+                    {"ItemId": "song1", "PlaylistItemId": "p1"},
+                    {"ItemId": "song2", "PlaylistItemId": "p2"}
+                ],
+                "PlayingItemIndex": 1,
+                "StartPositionTicks": 50000000,
+                "IsPlaying": true,
+                "ShuffleMode": "Sorted",
+                "RepeatMode": "RepeatNone"
+            }
+        }
+    }"#;
+
+    const UNPAUSE: &str = r#"{
+        "MessageType": "SyncPlayCommand",
+        "MessageId": "c",
+        "Data": {
+            "GroupId": "g1",
+            "PlaylistItemId": "p2",
+            "When": "2025-01-02T03:04:06.0000000Z",
+            "PositionTicks": 50000000,
+            "Command": "Unpause",
+            "EmittedAt": "2025-01-02T03:04:05.5000000Z"
+        }
+    }"#;
+
+    #[test]
+    fn test_parse_group_joined() {
+        let message = parse_message(GROUP_JOINED).unwrap();
+        let SocketMessage::SyncPlayGroupUpdate {
+            group_id,
+            update: GroupUpdate::GroupJoined(info),
+        } = message
+        else {
+            panic!("unexpected message {:?}", message);
+        };
+        assert_eq!(group_id, "g1");
+        assert_eq!(info.group_name, "Team");
+        assert_eq!(info.participants, vec!["alice", "bob"]);
+    }
+
+    #[test]
+    fn test_parse_play_queue() {
+        let message = parse_message(PLAY_QUEUE).unwrap();
+        let SocketMessage::SyncPlayGroupUpdate {
+            update: GroupUpdate::PlayQueue(queue),
+            ..
+        } = message
+        else {
+            panic!("unexpected message {:?}", message);
+        };
+        assert_eq!(queue.playlist.len(), 2);
+        assert_eq!(queue.playlist[1].item_id, "song2");
+        assert_eq!(queue.playing_item_index, 1);
+        assert_eq!(queue.start_position_ticks, 50_000_000);
+        assert!(queue.is_playing);
+    }
+
+    #[test]
+    fn test_parse_command() {
+        let message = parse_message(UNPAUSE).unwrap();
+        let SocketMessage::SyncPlayCommand(command) = message else {
+            panic!("unexpected message {:?}", message);
+        };
+        assert_eq!(command.command, SyncPlayCommandType::Unpause);
+        assert_eq!(command.position_ticks, Some(50_000_000));
+        assert_eq!(command.playlist_item_id, "p2");
+    }
+
+    #[test]
+    fn test_parse_user_left_and_unknown() {
+        let left = r#"{"MessageType":"SyncPlayGroupUpdate","Data":{"GroupId":"g1","Type":"UserLeft","Data":"bob"}}"#;
+        assert!(matches!(
+            parse_message(left).unwrap(),
+            SocketMessage::SyncPlayGroupUpdate { update: GroupUpdate::UserLeft(name), .. } if name == "bob"
+        ));
+        let other = r#"{"MessageType":"RefreshProgress","Data":{}}"#;
+        assert!(matches!(
+            parse_message(other).unwrap(),
+            SocketMessage::Other(kind) if kind == "RefreshProgress"
+        ));
+    }
+
+    #[test]
+    fn test_parse_remote_control() {
+        let play = r#"{"MessageType":"Play","Data":{"ItemIds":["a","b"],"StartIndex":1,"PlayCommand":"PlayNow","ControllingUserId":"u"}}"#;
+        let SocketMessage::Play(request) = parse_message(play).unwrap() else {
+            panic!("expected a play request");
+        };
+        assert_eq!(request.item_ids, vec!["a", "b"]);
+        assert_eq!(request.start_index, Some(1));
+        assert_eq!(request.play_command, PlayCommand::PlayNow);
+
+        let seek = r#"{"MessageType":"Playstate","Data":{"Command":"Seek","SeekPositionTicks":300000000}}"#;
+        let SocketMessage::Playstate(request) = parse_message(seek).unwrap() else {
+            panic!("expected a playstate request");
+        };
+        assert_eq!(request.command, PlaystateCommand::Seek);
+        assert_eq!(request.seek_position_ticks, Some(300_000_000));
+
+        let volume = r#"{"MessageType":"GeneralCommand","Data":{"Name":"SetVolume","Arguments":{"Volume":"40"}}}"#;
+        let SocketMessage::GeneralCommand(command) = parse_message(volume).unwrap() else {
+            panic!("expected a general command");
+        };
+        assert_eq!(command.name, "SetVolume");
+        assert_eq!(command.arguments["Volume"], "40");
+    }
+
+    #[test]
+    fn test_parse_library_updates() {
+        let changed = r#"{"MessageType":"LibraryChanged","Data":{"FoldersAddedTo":[],"FoldersRemovedFrom":[],"ItemsAdded":["a1","s1"],"ItemsRemoved":["s2"],"ItemsUpdated":[],"CollectionFolders":[]}}"#;
+        let SocketMessage::LibraryChanged(changes) = parse_message(changed).unwrap() else {
+            panic!("expected a library change");
+        };
+        assert_eq!(changes.items_added, vec!["a1", "s1"]);
+        assert_eq!(changes.items_removed, vec!["s2"]);
+        assert!(changes.items_updated.is_empty());
+
+        let user_data = r#"{"MessageType":"UserDataChanged","Data":{"UserId":"u1","UserDataList":[{"ItemId":"s1","IsFavorite":true,"PlayCount":3,"Played":true,"Key":"k"}]}}"#;
+        let SocketMessage::UserDataChanged(changes) = parse_message(user_data).unwrap() else {
+            panic!("expected a user data change");
+        };
+        assert_eq!(changes.user_id, "u1");
+        assert_eq!(changes.user_data_list[0].item_id, "s1");
+        assert!(changes.user_data_list[0].is_favorite);
+        assert_eq!(changes.user_data_list[0].play_count, 3);
+    }
+
+    /// Runs the client against a local stand-in for the Jellyfin socket.
+    #[tokio::test]
+    async fn test_run_against_stand_in_server() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+
+        let server = tokio::spawn(async move {
+            let (tcp, _) = listener.accept().await.unwrap();
+            let mut socket = tokio_tungstenite::accept_async(tcp).await.unwrap();
+            socket
+                .send(Message::text(
+                    r#"{"MessageType":"ForceKeepAlive","Data":2}"#,
+                ))
+                .await
+                .unwrap();
+            for message in [GROUP_JOINED, PLAY_QUEUE, UNPAUSE] {
+                socket.send(Message::text(message)).await.unwrap();
+            }
+            // The client has to keep the connection alive on its own
+            loop {
+                let Some(Ok(Message::Text(text))) = socket.next().await else {
+                    panic!("client went away without a keep alive");
+                };
+                if text.as_str().contains("KeepAlive") {
+                    break;
+                }
+            }
+        });
+
+        let (sender, receiver) = async_channel::unbounded();
+        let url = format!("ws://{address}/socket");
+        let client = tokio::spawn(async move { run(&url, sender).await });
+
+        let mut received = Vec::new();
+        for _ in 0..3 {
+            received.push(receiver.recv().await.unwrap());
+        }
+        assert!(matches!(
+            received[0],
+            SocketMessage::SyncPlayGroupUpdate {
+                update: GroupUpdate::GroupJoined(_),
+                ..
+            }
+        ));
+        assert!(matches!(
+            received[1],
+            SocketMessage::SyncPlayGroupUpdate {
+                update: GroupUpdate::PlayQueue(_),
+                ..
+            }
+        ));
+        assert!(matches!(received[2], SocketMessage::SyncPlayCommand(_)));
+
+        server.await.unwrap();
+        // Server hung up, so the client has to stop as well
+        assert!(client.await.unwrap().is_err());
+    }
+}
@@ -0,0 +1,278 @@
+//! SyncPlay lets several Jellyfin sessions listen to the same queue in step.
+//! Group state is changed with the REST calls below and the server answers
+//! every member over the session websocket (see [`super::socket`]).
+
+use std::collections::VecDeque;
+
+use log::debug;
+use serde_json::json;
+
+use crate::backend::BackendError;
+use crate::jellyfin::Jellyfin;
+use crate::jellyfin::api::{GroupInfoDto, UtcTimeResponse};
+
+/// Jellyfin ticks are 100ns
+pub const TICKS_PER_MILLISECOND: i64 = 10_000;
+
+impl Jellyfin {
+    pub async fn syncplay_list_groups(&self) -> Result<Vec<GroupInfoDto>, BackendError> {
+        let response = self.get("SyncPlay/List", None).await?;
+        let body = self.handle_response(response).await?;
+        Ok(serde_json::from_str(&body)?)
+    }
+
+    pub async fn syncplay_new_group(&self, name: &str) -> Result<(), BackendError> {
+        self.syncplay_request("New", json!({ "GroupName": name }))
+            .await
+    }
+
+    pub async fn syncplay_join_group(&self, group_id: &str) -> Result<(), BackendError> {
+        self.syncplay_request("Join", json!({ "GroupId": group_id }))
+            .await
+    }
+
+    pub async fn syncplay_leave_group(&self) -> Result<(), BackendError> {
+        self.syncplay_request("Leave", json!({})).await
+    }
+
+    pub async fn syncplay_pause(&self) -> Result<(), BackendError> {
+        self.syncplay_request("Pause", json!({})).await
+    }
+
+    pub async fn syncplay_unpause(&self) -> Result<(), BackendError> {
+        self.syncplay_request("Unpause", json!({})).await
+    }
+
+    pub async fn syncplay_seek(&self, position_ticks: i64) -> Result<(), BackendError> {
+        self.syncplay_request("Seek", json!({ "PositionTicks": position_ticks }))
+            .await
+    }
+
+    pub async fn syncplay_set_new_queue(
+        &self,
+        item_ids: &[String],
+        playing_index: usize,
+        start_position_ticks: i64,
+    ) -> Result<(), BackendError> {
+        let body = json!({
+            "PlayingQueue": item_ids,
+            "PlayingItemPosition": playing_index,
+            "StartPositionTicks": start_position_ticks,
+        });
+        self.syncplay_request("SetNewQueue", body).await
+    }
+
+    pub async fn syncplay_set_playlist_item(
+        &self,
+        playlist_item_id: &str,
+    ) -> Result<(), BackendError> {
+        let body = json!({ "PlaylistItemId": playlist_item_id });
+        self.syncplay_request("SetPlaylistItem", body).await
+    }
+
+    /// Tells the group this client is loading (`ready` false) or ready to
+    /// play (`ready` true) the given item. `when` is in server time.
+    pub async fn syncplay_report_ready(
+        &self,
+        ready: bool,
+        when: &str,
+        position_ticks: i64,
+        is_playing: bool,
+        playlist_item_id: &str,
+    ) -> Result<(), BackendError> {
+        let body = json!({
+            "When": when,
+            "PositionTicks": position_ticks,
+            "IsPlaying": is_playing,
+            "PlaylistItemId": playlist_item_id,
+        });
+        let endpoint = if ready { "Ready" } else { "Buffering" };
+        self.syncplay_request(endpoint, body).await
+    }
+
+    pub async fn syncplay_ping(&self, ping_ms: i64) -> Result<(), BackendError> {
+        self.syncplay_request("Ping", json!({ "Ping": ping_ms }))
+            .await
+    }
+
+    pub async fn get_utc_time(&self) -> Result<UtcTimeResponse, BackendError> {
+        let response = self.get("GetUtcTime", None).await?;
+        let body = self.handle_response(response).await?;
+        Ok(serde_json::from_str(&body)?)
+    }
+
+    async fn syncplay_request(
+        &self,
+        endpoint: &str,
+        body: serde_json::Value,
+    ) -> Result<(), BackendError> {
+        debug!("SyncPlay request {} {}", endpoint, body);
+        let response = self
+            .post_json(&format!("SyncPlay/{}", endpoint), &body)
+            .await?;
+        self.handle_response(response).await?;
+        Ok(())
+    }
+}
+
+/// Parses a Jellyfin timestamp into unix time in microseconds.
+pub fn parse_server_time(timestamp: &str) -> Option<i64> {
+    // Jellyfin sends 7 fractional digits, which is more than glib parses reliably
+    let timestamp = match timestamp.split_once('.') {
+        Some((seconds, fraction)) => {
+            let digits: String = fraction
+                .chars()
+                .take_while(|c| c.is_ascii_digit())
+                .take(6)
+                .collect();
+            let zone = fraction.trim_start_matches(|c: char| c.is_ascii_digit());
+            format!("{seconds}.{digits}{zone}")
+        }
+        None => timestamp.to_string(),
+    };
+    let utc = glib::TimeZone::utc();
+    let date = glib::DateTime::from_iso8601(&timestamp, Some(&utc)).ok()?;
+    Some(date.to_unix() * 1_000_000 + i64::from(date.microsecond()))
+}
+
+/// Formats unix time in microseconds the way Jellyfin expects it.
+pub fn format_server_time(unix_us: i64) -> String {
+    let seconds = unix_us.div_euclid(1_000_000);
+    let micros = unix_us.rem_euclid(1_000_000);
+    let date = glib::DateTime::from_unix_utc(seconds)
+        .map(|d| {
+            d.format("%Y-%m-%dT%H:%M:%S")
+                .unwrap_or_default()
+                .to_string()
+        })
+        .unwrap_or_default();
+    format!("{date}.{micros:06}Z")
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeSyncSample {
+    /// Local time the request left, in unix microseconds
+    pub request_sent: i64,
+    /// Server time the request arrived
+    pub request_received: i64,
+    /// Server time the response left
+    pub response_sent: i64,
+    /// Local time the response arrived
+    pub response_received: i64,
+}
+
+impl TimeSyncSample {
+    /// How far the server clock is ahead of ours.
+    pub fn offset(&self) -> i64 {
+        ((self.request_received - self.request_sent)
+            + (self.response_sent - self.response_received))
+            / 2
+    }
+
+    /// Network round trip, excluding the time the server spent on the request.
+    pub fn round_trip(&self) -> i64 {
+        (self.response_received - self.request_sent) - (self.response_sent - self.request_received)
+    }
+}
+
+/// Estimates the server clock offset from the last few `GetUtcTime` calls,
+/// trusting the sample with the shortest round trip the most.
+#[derive(Debug, Default, Clone)]
+pub struct TimeSync {
+    samples: VecDeque<TimeSyncSample>,
+}
+
+impl TimeSync {
+    const MAX_SAMPLES: usize = 8;
+
+    pub fn add_sample(&mut self, sample: TimeSyncSample) {
+        if self.samples.len() == Self::MAX_SAMPLES {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+    }
+
+    fn best_sample(&self) -> Option<&TimeSyncSample> {
+        self.samples.iter().min_by_key(|s| s.round_trip())
+    }
+
+    pub fn offset(&self) -> i64 {
+        self.best_sample().map(|s| s.offset()).unwrap_or(0)
+    }
+
+    pub fn round_trip(&self) -> i64 {
+        self.best_sample().map(|s| s.round_trip()).unwrap_or(0)
+    }
+
+    pub fn server_to_local(&self, server_us: i64) -> i64 {
+        server_us - self.offset()
+    }
+
+    pub fn local_to_server(&self, local_us: i64) -> i64 {
+        local_us + self.offset()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_server_time_truncates_ticks() {
+        let parsed = parse_server_time("2025-01-02T03:04:05.1234567Z").unwrap();
+        assert_eq!(parsed, 1_735_787_045_123_456);
+    }
+
+    #[test]
+    fn test_parse_server_time_without_fraction() {
+        let parsed = parse_server_time("2025-01-02T03:04:05Z").unwrap();
+        assert_eq!(parsed, 1_735_787_045_000_000);
+    }
+
+    #[test]
+    fn test_format_server_time_round_trips() {
+        let time = 1_735_787_045_123_456;
+        assert_eq!(format_server_time(time), "2025-01-02T03:04:05.123456Z");
+        assert_eq!(parse_server_time(&format_server_time(time)), Some(time));
+    }
+
+    #[test]
+    fn test_time_sync_sample_offset() {
+        // Server is 500ms ahead, 20ms each way on the wire, 2ms spent on the server
+        let sample = TimeSyncSample {
+            request_sent: 1_000_000,
+            request_received: 1_520_000,
+            response_sent: 1_522_000,
+            response_received: 1_042_000,
+        };
+        assert_eq!(sample.offset(), 500_000);
+        assert_eq!(sample.round_trip(), 40_000);
+    }
+
+    #[test]
+    fn test_time_sync_prefers_fastest_sample() {
+        let mut sync = TimeSync::default();
+        sync.add_sample(TimeSyncSample {
+            request_sent: 0,
+            request_received: 400_000,
+            response_sent: 400_000,
+            response_received: 600_000,
+        });
+        sync.add_sample(TimeSyncSample {
+            request_sent: 0,
+            request_received: 510_000,
+            response_sent: 510_000,
+            response_received: 20_000,
+        });
+        assert_eq!(sync.offset(), 500_000);
+        assert_eq!(sync.server_to_local(2_000_000), 1_500_000);
+        assert_eq!(sync.local_to_server(1_500_000), 2_000_000);
+    }
+
+    #[test]
+    fn test_time_sync_without_samples() {
+        let sync = TimeSync::default();
+        assert_eq!(sync.offset(), 0);
+        assert_eq!(sync.server_to_local(42), 42);
+    }
+}
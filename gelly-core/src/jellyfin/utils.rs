@@ -0,0 +1,58 @@
+pub fn format_duration(ticks: u64) -> String {
+    // Jellyfin ticks are in 100-nanosecond intervals
+    // 1 second = 10,000,000 ticks
+    let seconds = ticks / 10_000_000;
+    let minutes = seconds / 60;
+    let hours = minutes / 60;
+    let remaining_seconds = seconds % 60;
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes % 60, remaining_seconds)
+    } else {
+        format!("{}:{:02}", minutes, remaining_seconds)
+    }
+}
+
+/// Plain text from the bits of HTML servers put in biographies and notes
+pub fn strip_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('<') {
+        out.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find('>') else {
+            out.push_str(&rest[start..]);
+            rest = "";
+            break;
+        };
+        let tag = rest[start + 1..start + end].trim().to_ascii_lowercase();
+        if tag.starts_with("br") || tag == "/p" {
+            out.push('\n');
+        }
+        rest = &rest[start + end + 1..];
+    }
+    out.push_str(rest);
+    let out = out
+        .replace("&nbsp;", " ")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&apos;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&");
+    out.trim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_html() {
+        assert_eq!(
+            strip_html(
+                "A <b>band</b> from A&amp;B.<br/>Formed in 1990. <a href=\"https://www.last.fm\">Read more</a>"
+            ),
+            "A band from A&B.\nFormed in 1990. Read more"
+        );
+        assert_eq!(strip_html("  1 < 2 "), "1 < 2");
+    }
+}
@@ -0,0 +1,27 @@
+//! Shared, UI independent parts of Gelly: the server backends, the in-memory
+//! library and its on-disk cache, settings, the gstreamer audio player and
+//! network renderers.
+
+pub mod audio;
+pub mod audiobook;
+pub mod backend;
+pub mod cache;
+pub mod config;
+pub mod history;
+pub mod jellyfin;
+pub mod library;
+pub mod lyrics;
+pub mod models;
+pub mod playlist_cleanup;
+pub mod playlist_file;
+pub mod playlist_folders;
+pub mod podcast;
+pub mod radio;
+pub mod rating;
+pub mod resume;
+pub mod statistics;
+pub mod store;
+pub mod subsonic;
+#[cfg(test)]
+pub(crate) mod test_server;
+pub mod xml;
@@ -0,0 +1,2039 @@
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    rc::Rc,
+};
+
+use rand::prelude::*;
+
+use crate::{
+    jellyfin::api::{
+        ArtistItemsDto, FavoriteDto, FavoriteUserDataDto, ItemType, MusicDto, RatingDto,
+        RatingUserDataDto, SongCreditsDto, SongFileDto, UserItemDataDto,
+    },
+    models::{AlbumModel, ArtistModel, Category, CategoryModel, SongModel},
+    playlist_cleanup::{Finding, find_problems},
+    playlist_file::{EntryMatch, PlaylistEntry, match_entries},
+    rating::{rating_from_stars, stars_from_rating},
+};
+
+#[derive(Debug, Clone, Default)]
+struct Favorites {
+    song_ids: HashSet<String>,
+    album_ids: HashSet<String>,
+    artist_ids: HashSet<String>,
+    playlist_ids: HashSet<String>,
+}
+
+impl Favorites {
+    fn contains_song(&self, id: &str) -> bool {
+        self.song_ids.contains(id)
+    }
+    fn contains_album(&self, id: &str) -> bool {
+        self.album_ids.contains(id)
+    }
+    fn contains_artist(&self, id: &str) -> bool {
+        self.artist_ids.contains(id)
+    }
+    fn contains_playlist(&self, id: &str) -> bool {
+        self.playlist_ids.contains(id)
+    }
+}
+
+/// What a batch of user data changes touched
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct UserDataUpdate {
+    /// Play counts, last played dates or ratings of songs, which are cached
+    /// with the library
+    pub songs_changed: bool,
+    pub favorites_changed: bool,
+    pub ratings_changed: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Library {
+    pub songs: Rc<RefCell<Vec<MusicDto>>>, // TODO make this private
+    favorites: Rc<RefCell<Favorites>>,
+    /// Stars by album id. Song ratings are kept in the songs' user data.
+    album_ratings: Rc<RefCell<HashMap<String, u32>>>,
+    album_play_counts: Rc<RefCell<HashMap<String, u64>>>,
+    album_last_played_dates: Rc<RefCell<HashMap<String, String>>>,
+    artist_play_counts: Rc<RefCell<HashMap<String, u64>>>,
+    genres: Rc<RefCell<BTreeSet<String>>>,
+    /// Categories whose people or studios are filled in on the songs
+    credits_loaded: Rc<RefCell<HashSet<Category>>>,
+    /// Whether every song's path and provider ids are filled in
+    files_loaded: Rc<RefCell<bool>>,
+}
+
+impl Library {
+    pub fn new() -> Self {
+        Self {
+            songs: Rc::new(RefCell::new(Vec::new())),
+            favorites: Rc::new(RefCell::new(Favorites::default())),
+            album_ratings: Rc::new(RefCell::new(HashMap::new())),
+            album_play_counts: Rc::new(RefCell::new(HashMap::new())),
+            album_last_played_dates: Rc::new(RefCell::new(HashMap::new())),
+            artist_play_counts: Rc::new(RefCell::new(HashMap::new())),
+            genres: Rc::new(RefCell::new(BTreeSet::new())),
+            credits_loaded: Rc::new(RefCell::new(HashSet::new())),
+            files_loaded: Rc::new(RefCell::new(false)),
+        }
+    }
+
+    pub fn update_songs(&self, songs: Vec<MusicDto>) {
+        self.credits_loaded.borrow_mut().clear();
+        self.files_loaded.replace(false);
+        self.replace_songs(songs);
+    }
+
+    fn replace_songs(&self, songs: Vec<MusicDto>) {
+        self.songs.replace(songs);
+        self.update_song_stats();
+    }
+
+    /// Whether browsing by `category` has to wait for
+    /// [`Self::set_song_credits`]
+    pub fn needs_credits(&self, category: Category) -> bool {
+        matches!(category, Category::Composer | Category::Label)
+            && !self.credits_loaded.borrow().contains(&category)
+    }
+
+    /// Fills in the composers or labels fetched for browsing by them. `None`
+    /// means the songs came with them.
+    pub fn set_song_credits(&self, category: Category, credits: Option<Vec<SongCreditsDto>>) {
+        if let Some(credits) = credits {
+            let mut credits: HashMap<String, SongCreditsDto> = credits
+                .into_iter()
+                .map(|song_credits| (song_credits.id.clone(), song_credits))
+                .collect();
+            for dto in self.songs.borrow_mut().iter_mut() {
+                let Some(song_credits) = credits.remove(&dto.id) else {
+                    continue;
+                };
+                match category {
+                    Category::Composer => dto.people = song_credits.people,
+                    Category::Label => dto.studios = song_credits.studios,
+                    _ => {}
+                }
+            }
+        }
+        self.credits_loaded.borrow_mut().insert(category);
+    }
+
+    /// Whether matching against MusicBrainz ids has to wait for
+    /// [`Self::set_song_files`]
+    pub fn needs_song_files(&self) -> bool {
+        !*self.files_loaded.borrow()
+    }
+
+    /// Fills in the paths and provider ids fetched for playlist files,
+    /// `complete` when every song's were asked for. `None` means the songs
+    /// came with them.
+    pub fn set_song_files(&self, files: Option<Vec<SongFileDto>>, complete: bool) {
+        let Some(files) = files else {
+            self.files_loaded.replace(true);
+            return;
+        };
+        let mut files: HashMap<String, SongFileDto> = files
+            .into_iter()
+            .map(|file| (file.id.clone(), file))
+            .collect();
+        for dto in self.songs.borrow_mut().iter_mut() {
+            if let Some(file) = files.remove(&dto.id) {
+                dto.path = file.path;
+                dto.provider_ids = file.provider_ids;
+            }
+        }
+        if complete {
+            self.files_loaded.replace(true);
+        }
+    }
+
+    /// Recomputes play counts, last played dates and genres from the songs.
+    fn update_song_stats(&self) {
+        let songs = self.songs.borrow();
+        let mut album_counts = HashMap::new();
+        let mut album_played_dates = HashMap::new();
+        let mut artist_counts = HashMap::new();
+        let mut genres = self.genres.borrow_mut();
+        genres.clear();
+        for dto in songs.iter() {
+            if dto.album.is_some() {
+                let album_id = dto.effective_album_id();
+
+                *album_counts.entry(album_id.clone()).or_insert(0) += dto.user_data.play_count;
+
+                let current: &mut String = album_played_dates.entry(album_id).or_default();
+                if let Some(last_played_date) = dto.user_data.last_played_date.as_ref()
+                    && last_played_date > current
+                {
+                    current.clone_from(last_played_date);
+                }
+            }
+            for artist in &dto.album_artists {
+                *artist_counts.entry(artist.id.clone()).or_insert(0) += dto.user_data.play_count;
+            }
+            genres.extend(dto.effective_genres());
+        }
+        self.album_play_counts.replace(album_counts);
+        self.album_last_played_dates.replace(album_played_dates);
+        self.artist_play_counts.replace(artist_counts);
+    }
+
+    /// Applies an incremental change: songs in `changed` replace the ones with
+    /// the same id or are added, and `removed_ids` are dropped. The changed
+    /// songs have to come with their credits and files, so what's already been
+    /// fetched for the rest is kept.
+    pub fn merge_songs(&self, changed: Vec<MusicDto>, removed_ids: &[String]) {
+        let removed: HashSet<&str> = removed_ids.iter().map(String::as_str).collect();
+        let mut changed: HashMap<String, MusicDto> = changed
+            .into_iter()
+            .map(|dto| (dto.id.clone(), dto))
+            .collect();
+        let existing: Vec<MusicDto> = self
+            .songs
+            .borrow()
+            .iter()
+            .filter(|dto| !removed.contains(dto.id.as_str()))
+            .map(|dto| changed.remove(&dto.id).unwrap_or_else(|| dto.clone()))
+            .collect();
+        // Keep the newest first order of a full refresh
+        let mut songs: Vec<MusicDto> = changed.into_values().collect();
+        songs.sort_by(|a, b| b.date_created.cmp(&a.date_created));
+        songs.extend(existing);
+        self.replace_songs(songs);
+    }
+
+    /// Applies user data pushed by the server. Ids that aren't songs, albums
+    /// or artists in the library are treated as playlists if `is_playlist`
+    /// says so.
+    pub fn update_user_data(
+        &self,
+        changes: &[UserItemDataDto],
+        is_playlist: impl Fn(&str) -> bool,
+    ) -> UserDataUpdate {
+        let mut update = UserDataUpdate::default();
+        {
+            let mut songs = self.songs.borrow_mut();
+            let mut favorites = self.favorites.borrow_mut();
+            let mut album_ratings = self.album_ratings.borrow_mut();
+            let album_ids: HashSet<String> =
+                songs.iter().map(|dto| dto.effective_album_id()).collect();
+            let artist_ids: HashSet<String> = songs
+                .iter()
+                .flat_map(|dto| dto.album_artists.iter().map(|a| a.id.clone()))
+                .collect();
+            let song_indices: HashMap<String, usize> = songs
+                .iter()
+                .enumerate()
+                .map(|(index, dto)| (dto.id.clone(), index))
+                .collect();
+
+            for change in changes {
+                let id = change.item_id.as_str();
+                let favorite_ids = if let Some(&index) = song_indices.get(id) {
+                    let dto = &mut songs[index];
+                    if dto.user_data.play_count != change.play_count {
+                        dto.user_data.play_count = change.play_count;
+                        update.songs_changed = true;
+                    }
+                    if change.last_played_date.is_some()
+                        && dto.user_data.last_played_date != change.last_played_date
+                    {
+                        dto.user_data.last_played_date = change.last_played_date.clone();
+                        update.songs_changed = true;
+                    }
+                    let stars = stars_from_rating(change.rating);
+                    if stars != stars_from_rating(dto.user_data.rating) {
+                        dto.user_data.rating = rating_from_stars(stars);
+                        update.ratings_changed = true;
+                        update.songs_changed = true;
+                    }
+                    &mut favorites.song_ids
+                } else if album_ids.contains(id) {
+                    let stars = stars_from_rating(change.rating);
+                    let previous = if stars == 0 {
+                        album_ratings.remove(id)
+                    } else {
+                        album_ratings.insert(id.to_string(), stars)
+                    };
+                    update.ratings_changed |= previous.unwrap_or(0) != stars;
+                    &mut favorites.album_ids
+                } else if artist_ids.contains(id) {
+                    &mut favorites.artist_ids
+                } else if is_playlist(id) {
+                    &mut favorites.playlist_ids
+                } else {
+                    continue;
+                };
+                update.favorites_changed |= if change.is_favorite {
+                    favorite_ids.insert(id.to_string())
+                } else {
+                    favorite_ids.remove(id)
+                };
+            }
+        }
+        self.update_song_stats();
+        update
+    }
+
+    /// The current favorites in the shape the server returns them, for caching.
+    pub fn favorites_list(&self) -> Vec<FavoriteDto> {
+        let favorites = self.favorites.borrow();
+        let typed_ids = [
+            (ItemType::Audio, &favorites.song_ids),
+            (ItemType::MusicAlbum, &favorites.album_ids),
+            (ItemType::MusicArtist, &favorites.artist_ids),
+            (ItemType::Playlist, &favorites.playlist_ids),
+        ];
+        typed_ids
+            .into_iter()
+            .flat_map(|(item_type, ids)| {
+                ids.iter().map(move |id| FavoriteDto {
+                    id: id.clone(),
+                    item_type: item_type.clone(),
+                    user_data: FavoriteUserDataDto { is_favorite: true },
+                })
+            })
+            .collect()
+    }
+
+    pub fn update_favorites(&self, favorites_list: &[FavoriteDto]) {
+        let mut favorites = self.favorites.borrow_mut();
+        favorites.song_ids.clear();
+        favorites.album_ids.clear();
+        favorites.artist_ids.clear();
+        favorites.playlist_ids.clear();
+        for favorite in favorites_list.iter().filter(|f| f.user_data.is_favorite) {
+            match favorite.item_type {
+                ItemType::Audio => {
+                    favorites.song_ids.insert(favorite.id.clone());
+                }
+                ItemType::MusicAlbum => {
+                    favorites.album_ids.insert(favorite.id.clone());
+                }
+                ItemType::MusicArtist => {
+                    favorites.artist_ids.insert(favorite.id.clone());
+                }
+                ItemType::Playlist => {
+                    favorites.playlist_ids.insert(favorite.id.clone());
+                }
+                _ => log::warn!("Unknown favorite type: {:?}", favorite.item_type),
+            }
+        }
+    }
+
+    /// The current album ratings in the shape the server returns them, for
+    /// caching.
+    pub fn album_ratings_list(&self) -> Vec<RatingDto> {
+        self.album_ratings
+            .borrow()
+            .iter()
+            .map(|(id, stars)| RatingDto {
+                id: id.clone(),
+                user_data: RatingUserDataDto {
+                    rating: rating_from_stars(*stars),
+                },
+            })
+            .collect()
+    }
+
+    pub fn update_album_ratings(&self, ratings_list: &[RatingDto]) {
+        let ratings = ratings_list
+            .iter()
+            .map(|rating| {
+                (
+                    rating.id.clone(),
+                    stars_from_rating(rating.user_data.rating),
+                )
+            })
+            .filter(|(_, stars)| *stars > 0)
+            .collect();
+        self.album_ratings.replace(ratings);
+    }
+
+    pub fn album_rating(&self, id: &str) -> u32 {
+        self.album_ratings.borrow().get(id).copied().unwrap_or(0)
+    }
+
+    /// Records a rating the server took, without waiting for a refresh
+    pub fn set_album_rating(&self, id: &str, stars: u32) {
+        let mut ratings = self.album_ratings.borrow_mut();
+        if stars == 0 {
+            ratings.remove(id);
+        } else {
+            ratings.insert(id.to_string(), stars);
+        }
+    }
+
+    pub fn song_rating(&self, id: &str) -> u32 {
+        self.songs
+            .borrow()
+            .iter()
+            .find(|dto| dto.id == id)
+            .map_or(0, |dto| stars_from_rating(dto.user_data.rating))
+    }
+
+    /// Records a rating the server took, without waiting for a refresh
+    pub fn set_song_rating(&self, id: &str, stars: u32) {
+        if let Some(dto) = self.songs.borrow_mut().iter_mut().find(|dto| dto.id == id) {
+            dto.user_data.rating = rating_from_stars(stars);
+        }
+    }
+
+    pub fn albums_from_library(&self) -> Vec<AlbumModel> {
+        let play_counts = self.album_play_counts.borrow();
+        let last_played_dates = self.album_last_played_dates.borrow();
+        let favorites = self.favorites.borrow();
+        let album_ratings = self.album_ratings.borrow();
+        let songs = self.songs.borrow();
+
+        let mut seen_album_ids = HashSet::<String>::new();
+        let mut album_dtos: Vec<&MusicDto> = Vec::new();
+        let mut genres = HashMap::<String, BTreeSet<String>>::new();
+
+        for dto in songs.iter().filter(|dto| dto.album.is_some()) {
+            let id = dto.effective_album_id();
+            genres
+                .entry(id.clone())
+                .or_default()
+                .extend(dto.effective_genres());
+            if seen_album_ids.insert(id) {
+                album_dtos.push(dto);
+            }
+        }
+
+        let mut albums: Vec<AlbumModel> = album_dtos
+            .iter()
+            .map(|dto| {
+                let id = dto.effective_album_id();
+                let album_genres = genres
+                    .get(&id)
+                    .map(|set| set.iter().cloned().collect::<Vec<_>>())
+                    .unwrap_or_default();
+                AlbumModel::new(
+                    dto,
+                    favorites.contains_album(&id),
+                    album_ratings.get(&id).copied().unwrap_or(0),
+                    play_counts.get(&id).copied().unwrap_or(0),
+                    album_genres,
+                    last_played_dates.get(&id).map(|s| s.as_str()).unwrap_or(""),
+                )
+            })
+            .collect();
+        albums.sort_by_key(|album| std::cmp::Reverse(album.date_created()));
+        albums
+    }
+
+    pub fn artists_from_library(&self) -> Vec<ArtistModel> {
+        let play_counts = self.artist_play_counts.borrow();
+        let favorites = self.favorites.borrow();
+        let songs = self.songs.borrow();
+
+        let mut seen_artist_ids = HashSet::new();
+        let mut artist_dtos: Vec<&ArtistItemsDto> = Vec::new();
+        let mut genres = HashMap::<String, BTreeSet<String>>::new();
+
+        for song in songs.iter() {
+            for artist in &song.album_artists {
+                genres
+                    .entry(artist.id.clone())
+                    .or_default()
+                    .extend(song.effective_genres());
+                if seen_artist_ids.insert(artist.id.clone()) {
+                    artist_dtos.push(artist);
+                }
+            }
+        }
+        let mut artists: Vec<ArtistModel> = artist_dtos
+            .iter()
+            .map(|dto| {
+                let artist_genres = genres
+                    .get(&dto.id)
+                    .map(|set| set.iter().cloned().collect::<Vec<_>>())
+                    .unwrap_or_default();
+                let play_count = play_counts.get(&dto.id).copied().unwrap_or(0);
+                let favorite = favorites.contains_artist(&dto.id);
+                ArtistModel::new(dto, favorite, play_count, artist_genres)
+            })
+            .collect();
+        artists.sort_by_key(|artist| artist.name().to_lowercase());
+        artists
+    }
+
+    /// What a song files under for the category. Genres are normalized,
+    /// composers and labels are kept as tagged.
+    fn category_values(dto: &MusicDto, category: Category) -> Vec<String> {
+        match category {
+            Category::Genre => dto.effective_genres(),
+            Category::Composer => dto.composers(),
+            Category::Decade => dto.decade().into_iter().collect(),
+            Category::Year => dto.year().into_iter().collect(),
+            Category::Label => dto.labels(),
+        }
+    }
+
+    /// Every genre, composer, decade, year or label with its counts. The mosaic
+    /// shows the most played albums.
+    pub fn categories_from_library(&self, category: Category) -> Vec<CategoryModel> {
+        #[derive(Default)]
+        struct CategoryStats<'a> {
+            /// Album id to cover art id
+            albums: HashMap<String, &'a str>,
+            song_count: u32,
+            play_count: u64,
+        }
+
+        let album_play_counts = self.album_play_counts.borrow();
+        let songs = self.songs.borrow();
+        let mut categories = BTreeMap::<String, CategoryStats>::new();
+        for dto in songs.iter() {
+            // A song tagged "Rock; Rock/Indie" still counts once for rock
+            let values: BTreeSet<String> =
+                Self::category_values(dto, category).into_iter().collect();
+            for value in values {
+                let stats = categories.entry(value).or_default();
+                stats.song_count += 1;
+                stats.play_count += dto.user_data.play_count;
+                stats
+                    .albums
+                    .entry(dto.effective_album_id())
+                    .or_insert(dto.effective_cover_art());
+            }
+        }
+
+        categories
+            .into_iter()
+            .map(|(name, stats)| {
+                let mut albums: Vec<(&String, &str)> = stats
+                    .albums
+                    .iter()
+                    .map(|(id, cover_art)| (id, *cover_art))
+                    .collect();
+                let plays = |id: &str| album_play_counts.get(id).copied().unwrap_or(0);
+                albums.sort_by(|(a, _), (b, _)| {
+                    plays(b.as_str()).cmp(&plays(a.as_str())).then(a.cmp(b))
+                });
+                let cover_art = albums
+                    .iter()
+                    .take(4)
+                    .map(|(_, cover_art)| cover_art.to_string())
+                    .collect();
+                CategoryModel::new(
+                    category,
+                    &name,
+                    stats.albums.len() as u32,
+                    stats.song_count,
+                    stats.play_count,
+                    cover_art,
+                )
+            })
+            .collect()
+    }
+
+    fn in_category(dto: &MusicDto, category: Category, name: &str) -> bool {
+        Self::category_values(dto, category)
+            .iter()
+            .any(|value| value == name)
+    }
+
+    pub fn albums_for_category(&self, category: Category, name: &str) -> Vec<AlbumModel> {
+        self.albums_where(|dto| Self::in_category(dto, category, name))
+    }
+
+    pub fn artists_for_category(&self, category: Category, name: &str) -> Vec<ArtistModel> {
+        let play_counts = self.artist_play_counts.borrow();
+        let favorites = self.favorites.borrow();
+        let songs = self.songs.borrow();
+        let mut seen_artist_ids = HashSet::new();
+        let mut artists: Vec<ArtistModel> = songs
+            .iter()
+            .filter(|dto| Self::in_category(dto, category, name))
+            .flat_map(|dto| dto.album_artists.iter())
+            .filter(|artist| seen_artist_ids.insert(artist.id.clone()))
+            .map(|artist| {
+                let play_count = play_counts.get(&artist.id).copied().unwrap_or(0);
+                let favorite = favorites.contains_artist(&artist.id);
+                ArtistModel::new(artist, favorite, play_count, Vec::new())
+            })
+            .collect();
+        artists.sort_by_key(|artist| artist.name().to_lowercase());
+        artists
+    }
+
+    /// Songs in the category, album by album in track order
+    pub fn songs_for_category(&self, category: Category, name: &str) -> Vec<SongModel> {
+        let favorites = self.favorites.borrow();
+        let mut songs: Vec<SongModel> = self
+            .songs
+            .borrow()
+            .iter()
+            .filter(|dto| Self::in_category(dto, category, name))
+            .map(|dto| SongModel::new(dto, favorites.contains_song(&dto.id)))
+            .collect();
+        songs.sort_by_key(|song| {
+            (
+                song.album_id(),
+                song.parent_track_number(),
+                song.track_number(),
+            )
+        });
+        songs
+    }
+
+    pub fn all_songs(&self) -> Vec<SongModel> {
+        let favorites = self.favorites.borrow();
+        let mut songs: Vec<SongModel> = self
+            .songs
+            .borrow()
+            .iter()
+            .map(|dto| {
+                let favorite = favorites.contains_song(&dto.id);
+                SongModel::new(dto, favorite)
+            })
+            .collect();
+        songs.sort_by_key(|s| std::cmp::Reverse(s.date_created()));
+        songs
+    }
+
+    pub fn albums_for_artist(&self, artist_id: &str) -> Vec<AlbumModel> {
+        self.albums_where(|dto| {
+            dto.album_artists
+                .iter()
+                .any(|artist| artist.id == artist_id)
+        })
+    }
+
+    /// Albums by other artists that the artist only plays on some tracks of
+    pub fn appears_on_for_artist(&self, artist_id: &str) -> Vec<AlbumModel> {
+        let own_album_ids: HashSet<String> = self
+            .songs
+            .borrow()
+            .iter()
+            .filter(|dto| {
+                dto.album_artists
+                    .iter()
+                    .any(|artist| artist.id == artist_id)
+            })
+            .map(|dto| dto.effective_album_id())
+            .collect();
+        self.albums_where(|dto| {
+            !own_album_ids.contains(&dto.effective_album_id())
+                && dto.artist_items.iter().any(|artist| artist.id == artist_id)
+        })
+    }
+
+    fn albums_where(&self, filter: impl Fn(&MusicDto) -> bool) -> Vec<AlbumModel> {
+        let play_counts = self.album_play_counts.borrow();
+        let last_played_dates = self.album_last_played_dates.borrow();
+        let favorites = self.favorites.borrow();
+        let album_ratings = self.album_ratings.borrow();
+        let mut seen_album_ids = HashSet::<String>::new();
+        let mut albums: Vec<AlbumModel> = self
+            .songs
+            .borrow()
+            .iter()
+            .filter(|dto| filter(dto))
+            .filter(|dto| seen_album_ids.insert(dto.effective_album_id()))
+            .map(|dto| {
+                let id = dto.effective_album_id();
+                AlbumModel::new(
+                    dto,
+                    favorites.contains_album(&id),
+                    album_ratings.get(&id).copied().unwrap_or(0),
+                    play_counts.get(&id).copied().unwrap_or(0),
+                    Vec::new(), // Don't need this as we aren't filtering by genre here
+                    last_played_dates.get(&id).map(|s| s.as_str()).unwrap_or(""),
+                )
+            })
+            .collect();
+        albums.sort_by_key(|album| std::cmp::Reverse(album.year()));
+        albums
+    }
+
+    pub fn songs_for_album(&self, album_id: &str) -> Vec<SongModel> {
+        let favorites = self.favorites.borrow();
+        let mut tracks: Vec<SongModel> = self
+            .songs
+            .borrow()
+            .iter()
+            .filter(|dto| dto.effective_album_id() == album_id)
+            .map(|dto| {
+                let favorite = favorites.contains_song(&dto.id);
+                SongModel::new(dto, favorite)
+            })
+            .collect();
+        tracks.sort_by_key(|t| (t.parent_track_number(), t.track_number()));
+        tracks
+    }
+
+    /// `num` random songs, leaving out the ids in `exclude`
+    pub fn shuffle_songs(&self, num: u64, exclude: &HashSet<String>) -> Vec<SongModel> {
+        let favorites = self.favorites.borrow();
+        let mut rng = rand::rng();
+        let songs = self.songs.borrow();
+        let candidates: Vec<&MusicDto> = songs
+            .iter()
+            .filter(|dto| !dto.is_audiobook() && !exclude.contains(&dto.id))
+            .collect();
+        let chosen = candidates.sample(&mut rng, num as usize);
+        chosen
+            .into_iter()
+            .map(|dto| SongModel::new(dto, favorites.contains_song(&dto.id)))
+            .collect()
+    }
+
+    /// The `num` most played songs, leaving out the ids in `exclude`
+    pub fn most_played_songs(&self, num: u64, exclude: &HashSet<String>) -> Vec<SongModel> {
+        let favorites = self.favorites.borrow();
+        let mut songs: Vec<MusicDto> = self
+            .songs
+            .borrow()
+            .iter()
+            .filter(|dto| dto.user_data.play_count > 0 && !exclude.contains(&dto.id))
+            .filter(|dto| !dto.is_audiobook())
+            .cloned()
+            .collect();
+        songs.sort_by_key(|dto| std::cmp::Reverse(dto.user_data.play_count));
+        songs
+            .into_iter()
+            .take(num as usize)
+            .map(|dto| SongModel::new(&dto, favorites.contains_song(&dto.id)))
+            .collect()
+    }
+
+    /// The `num` highest rated songs, leaving out the ids in `exclude`. Songs
+    /// rated the same come most played first.
+    pub fn top_rated_songs(&self, num: u64, exclude: &HashSet<String>) -> Vec<SongModel> {
+        let favorites = self.favorites.borrow();
+        let songs = self.songs.borrow();
+        let mut rated: Vec<&MusicDto> = songs
+            .iter()
+            .filter(|dto| stars_from_rating(dto.user_data.rating) > 0)
+            .filter(|dto| !dto.is_audiobook() && !exclude.contains(&dto.id))
+            .collect();
+        rated.sort_by_key(|dto| {
+            std::cmp::Reverse((
+                stars_from_rating(dto.user_data.rating),
+                dto.user_data.play_count,
+            ))
+        });
+        rated
+            .into_iter()
+            .take(num as usize)
+            .map(|dto| SongModel::new(dto, favorites.contains_song(&dto.id)))
+            .collect()
+    }
+
+    /// The artist's most played songs, counting tracks on other artists' albums
+    pub fn top_songs_for_artist(&self, artist_id: &str, num: usize) -> Vec<SongModel> {
+        let favorites = self.favorites.borrow();
+        let songs = self.songs.borrow();
+        let mut top: Vec<&MusicDto> = songs
+            .iter()
+            .filter(|dto| dto.user_data.play_count > 0)
+            .filter(|dto| {
+                dto.artist_items
+                    .iter()
+                    .chain(dto.album_artists.iter())
+                    .any(|artist| artist.id == artist_id)
+            })
+            .collect();
+        top.sort_by_key(|dto| std::cmp::Reverse(dto.user_data.play_count));
+        top.into_iter()
+            .take(num)
+            .map(|dto| SongModel::new(dto, favorites.contains_song(&dto.id)))
+            .collect()
+    }
+
+    /// Artists from the library matching the given ones by id, or by name
+    /// since servers don't always agree on ids for artists they found online
+    pub fn artists_in_library(&self, wanted: &[ArtistItemsDto]) -> Vec<ArtistModel> {
+        let play_counts = self.artist_play_counts.borrow();
+        let favorites = self.favorites.borrow();
+        let songs = self.songs.borrow();
+        let known: Vec<&ArtistItemsDto> = songs
+            .iter()
+            .flat_map(|dto| dto.album_artists.iter())
+            .collect();
+        let mut seen_artist_ids = HashSet::new();
+        wanted
+            .iter()
+            .filter_map(|wanted| {
+                known
+                    .iter()
+                    .find(|artist| artist.id == wanted.id)
+                    .or_else(|| {
+                        known
+                            .iter()
+                            .find(|artist| artist.name.eq_ignore_ascii_case(&wanted.name))
+                    })
+            })
+            .filter(|artist| seen_artist_ids.insert(artist.id.clone()))
+            .map(|artist| {
+                let play_count = play_counts.get(&artist.id).copied().unwrap_or(0);
+                let favorite = favorites.contains_artist(&artist.id);
+                ArtistModel::new(artist, favorite, play_count, Vec::new())
+            })
+            .collect()
+    }
+
+    pub fn all_favorites(&self) -> Vec<SongModel> {
+        let favorites = self.favorites.borrow();
+        let mut rng = rand::rng();
+        let songs: Vec<MusicDto> = self
+            .songs
+            .borrow()
+            .iter()
+            .filter(|dto| !dto.is_audiobook())
+            .filter(|dto| {
+                self.song_is_favorite(&dto.id)
+                    || dto
+                        .album_id
+                        .as_ref()
+                        .is_some_and(|album_id| self.album_is_favorite(album_id))
+                    || dto
+                        .album_artists
+                        .iter()
+                        .any(|artist| self.artist_is_favorite(&artist.id))
+            })
+            .cloned()
+            .collect();
+        let chosen = songs.sample(&mut rng, songs.len());
+        chosen
+            .into_iter()
+            .map(|dto| SongModel::new(dto, favorites.contains_song(&dto.id)))
+            .collect()
+    }
+
+    pub fn songs_for_artist(&self, id: &str) -> Vec<SongModel> {
+        let albums = self.albums_for_artist(id);
+        albums
+            .iter()
+            .flat_map(|album| self.songs_for_album(&album.id()))
+            .collect()
+    }
+
+    /// Songs for the given ids in the same order, skipping ids that are not
+    /// in the library.
+    pub fn songs_for_ids(&self, ids: &[String]) -> Vec<SongModel> {
+        let favorites = self.favorites.borrow();
+        let songs = self.songs.borrow();
+        let by_id: HashMap<&str, &MusicDto> =
+            songs.iter().map(|dto| (dto.id.as_str(), dto)).collect();
+        ids.iter()
+            .filter_map(|id| by_id.get(id.as_str()))
+            .map(|dto| SongModel::new(dto, favorites.contains_song(&dto.id)))
+            .collect()
+    }
+
+    /// Playlist file entries for the given song ids in the same order,
+    /// skipping ids that are not in the library
+    pub fn playlist_entries(&self, ids: &[String]) -> Vec<PlaylistEntry> {
+        let songs = self.songs.borrow();
+        let by_id: HashMap<&str, &MusicDto> =
+            songs.iter().map(|dto| (dto.id.as_str(), dto)).collect();
+        ids.iter()
+            .filter_map(|id| by_id.get(id.as_str()))
+            .map(|dto| PlaylistEntry::from_song(dto))
+            .collect()
+    }
+
+    pub fn match_playlist_entries(&self, entries: &[PlaylistEntry]) -> Vec<Option<EntryMatch>> {
+        match_entries(entries, &self.songs.borrow())
+    }
+
+    /// Duplicates in a playlist's songs, and songs no longer in the library.
+    /// None while there are no songs to check against, as every song would
+    /// look gone.
+    pub fn playlist_problems(&self, entries: &[MusicDto]) -> Option<Vec<Finding>> {
+        let songs = self.songs.borrow();
+        if songs.is_empty() {
+            return None;
+        }
+        Some(find_problems(entries, &songs))
+    }
+
+    pub fn artist_for_item(&self, item_id: &str) -> Option<ArtistModel> {
+        let play_counts = self.artist_play_counts.borrow();
+        let favorites = self.favorites.borrow();
+        self.songs
+            .borrow()
+            .iter()
+            .find(|dto| dto.id == item_id)
+            .and_then(|dto| {
+                dto.album_artists.first().map(|artist| {
+                    let play_count = play_counts.get(&artist.id).copied().unwrap_or(0);
+                    let favorite = favorites.contains_artist(&artist.id);
+                    ArtistModel::new(artist, favorite, play_count, Vec::new())
+                })
+            })
+    }
+
+    pub fn album_for_item(&self, item_id: &str) -> Option<AlbumModel> {
+        let play_counts = self.album_play_counts.borrow();
+        let last_played_dates = self.album_last_played_dates.borrow();
+        let favorites = self.favorites.borrow();
+        let album_ratings = self.album_ratings.borrow();
+        self.songs
+            .borrow()
+            .iter()
+            .find(|dto| dto.id == item_id)
+            .map(|dto| {
+                let id = dto.effective_album_id();
+                AlbumModel::new(
+                    dto,
+                    favorites.contains_album(&id),
+                    album_ratings.get(&id).copied().unwrap_or(0),
+                    play_counts.get(&id).copied().unwrap_or(0),
+                    Vec::new(),
+                    last_played_dates.get(&id).map(|s| s.as_str()).unwrap_or(""),
+                )
+            })
+    }
+
+    pub fn playlist_is_favorite(&self, id: &str) -> bool {
+        self.favorites.borrow().contains_playlist(id)
+    }
+
+    pub fn song_is_favorite(&self, id: &str) -> bool {
+        self.favorites.borrow().contains_song(id)
+    }
+
+    pub fn artist_is_favorite(&self, id: &str) -> bool {
+        self.favorites.borrow().contains_artist(id)
+    }
+
+    pub fn album_is_favorite(&self, id: &str) -> bool {
+        self.favorites.borrow().contains_album(id)
+    }
+
+    pub fn library_size(&self) -> usize {
+        self.songs.borrow().len()
+    }
+
+    pub fn genres(&self) -> Vec<String> {
+        self.genres.borrow().iter().cloned().collect()
+    }
+
+    /// Each song's genres by song id
+    pub fn genres_by_song(&self) -> HashMap<String, Vec<String>> {
+        self.songs
+            .borrow()
+            .iter()
+            .map(|dto| (dto.id.clone(), dto.effective_genres()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jellyfin::api::{ArtistItemsDto, Audiobook, PersonDto, StudioDto, UserDataDto};
+
+    #[allow(clippy::too_many_arguments)]
+    fn create_test_music_dto(
+        id: &str,
+        name: &str,
+        album: &str,
+        album_id: &str,
+        artist_name: &str,
+        artist_id: &str,
+        index_number: Option<u32>,
+        parent_index_number: Option<u32>,
+    ) -> MusicDto {
+        MusicDto {
+            id: id.to_string(),
+            name: name.to_string(),
+            album: Some(album.to_string()),
+            album_id: Some(album_id.to_string()),
+            album_artists: vec![ArtistItemsDto {
+                name: artist_name.to_string(),
+                id: artist_id.to_string(),
+            }],
+            artist_items: vec![],
+            date_created: Some("2025-01-01".to_string()),
+            run_time_ticks: 2000000000,
+            normalization_gain: None,
+            production_year: Some(2023),
+            index_number,
+            parent_index_number,
+            has_lyrics: false,
+            user_data: UserDataDto {
+                play_count: 1,
+                last_played_date: None,
+                playback_position_ticks: 0,
+                rating: None,
+            },
+            genres: vec![],
+            cover_art: None,
+            is_compilation: false,
+            people: vec![],
+            studios: vec![],
+            provider_ids: HashMap::new(),
+            path: None,
+            item_type: None,
+        }
+    }
+
+    fn create_test_music_dto_multi_artists(
+        id: &str,
+        name: &str,
+        album: &str,
+        album_id: &str,
+        artists: Vec<(&str, &str)>,
+    ) -> MusicDto {
+        MusicDto {
+            id: id.to_string(),
+            name: name.to_string(),
+            album: Some(album.to_string()),
+            album_id: Some(album_id.to_string()),
+            album_artists: artists
+                .into_iter()
+                .map(|(name, id)| ArtistItemsDto {
+                    name: name.to_string(),
+                    id: id.to_string(),
+                })
+                .collect(),
+            artist_items: vec![],
+            date_created: Some("2025-01-01".to_string()),
+            run_time_ticks: 2000000000,
+            normalization_gain: None,
+            production_year: Some(2023),
+            index_number: Some(1),
+            parent_index_number: Some(1),
+            has_lyrics: false,
+            user_data: UserDataDto {
+                play_count: 1,
+                last_played_date: None,
+                playback_position_ticks: 0,
+                rating: None,
+            },
+            genres: vec![],
+            cover_art: None,
+            is_compilation: false,
+            people: vec![],
+            studios: vec![],
+            provider_ids: HashMap::new(),
+            path: None,
+            item_type: None,
+        }
+    }
+
+    fn create_music_dto_user_data(play_count: u64) -> MusicDto {
+        MusicDto {
+            user_data: UserDataDto {
+                play_count,
+                last_played_date: None,
+                playback_position_ticks: 0,
+                rating: None,
+            },
+            id: format!("user-data-{}", play_count),
+            name: format!("user-data-{}", play_count),
+            album: Some(format!("user-data-{}", play_count)),
+            album_id: Some(format!("user-data-{}", play_count)),
+            album_artists: vec![ArtistItemsDto {
+                name: format!("user-data-{}", play_count),
+                id: format!("user-data-{}", play_count),
+            }],
+            artist_items: vec![],
+            date_created: Some("2025-01-01".to_string()),
+            run_time_ticks: 2000000000,
+            normalization_gain: None,
+            production_year: Some(2023),
+            index_number: Some(1),
+            parent_index_number: Some(1),
+            has_lyrics: false,
+            genres: vec![],
+            cover_art: None,
+            is_compilation: false,
+            people: vec![],
+            studios: vec![],
+            provider_ids: HashMap::new(),
+            path: None,
+            item_type: None,
+        }
+    }
+
+    fn make_library(songs: Vec<MusicDto>) -> Library {
+        let lib = Library::new();
+        lib.update_songs(songs);
+        lib
+    }
+
+    #[test]
+    fn test_albums_from_library_deduplicates() {
+        let lib = make_library(vec![
+            create_test_music_dto(
+                "1",
+                "Song 1",
+                "Album A",
+                "album_1",
+                "Artist A",
+                "artist_1",
+                Some(1),
+                Some(1),
+            ),
+            create_test_music_dto(
+                "2",
+                "Song 2",
+                "Album A",
+                "album_1",
+                "Artist A",
+                "artist_1",
+                Some(2),
+                Some(1),
+            ),
+            create_test_music_dto(
+                "3",
+                "Song 3",
+                "Album B",
+                "album_2",
+                "Artist B",
+                "artist_2",
+                Some(1),
+                Some(1),
+            ),
+        ]);
+        let albums = lib.albums_from_library();
+        assert_eq!(albums.len(), 2);
+        assert!(
+            albums
+                .iter()
+                .any(|a| a.id() == "album_1" && a.name() == "Album A")
+        );
+        assert!(
+            albums
+                .iter()
+                .any(|a| a.id() == "album_2" && a.name() == "Album B")
+        );
+    }
+
+    #[test]
+    fn test_albums_from_library_empty_input() {
+        let lib = make_library(vec![]);
+        assert_eq!(lib.albums_from_library().len(), 0);
+    }
+
+    #[test]
+    fn test_artists_from_library_deduplicates_and_sorts() {
+        let lib = make_library(vec![
+            create_test_music_dto(
+                "1",
+                "Song 1",
+                "Album A",
+                "album_1",
+                "Zebra Artist",
+                "artist_z",
+                Some(1),
+                Some(1),
+            ),
+            create_test_music_dto(
+                "2",
+                "Song 2",
+                "Album A",
+                "album_1",
+                "Alpha Artist",
+                "artist_a",
+                Some(2),
+                Some(1),
+            ),
+            create_test_music_dto(
+                "3",
+                "Song 3",
+                "Album B",
+                "album_2",
+                "Zebra Artist",
+                "artist_z",
+                Some(1),
+                Some(1),
+            ),
+        ]);
+        let artists = lib.artists_from_library();
+        assert_eq!(artists.len(), 2);
+        assert_eq!(artists[0].name(), "Alpha Artist");
+        assert_eq!(artists[1].name(), "Zebra Artist");
+    }
+
+    #[test]
+    fn test_artists_from_library_case_insensitive_sort() {
+        let lib = make_library(vec![
+            create_test_music_dto(
+                "1",
+                "Song 1",
+                "Album A",
+                "album_1",
+                "zebra",
+                "artist_z",
+                Some(1),
+                Some(1),
+            ),
+            create_test_music_dto(
+                "2",
+                "Song 2",
+                "Album B",
+                "album_2",
+                "Alpha",
+                "artist_a",
+                Some(1),
+                Some(1),
+            ),
+            create_test_music_dto(
+                "3",
+                "Song 3",
+                "Album C",
+                "album_3",
+                "beta",
+                "artist_b",
+                Some(1),
+                Some(1),
+            ),
+        ]);
+        let artists = lib.artists_from_library();
+        assert_eq!(artists.len(), 3);
+        assert_eq!(artists[0].name(), "Alpha");
+        assert_eq!(artists[1].name(), "beta");
+        assert_eq!(artists[2].name(), "zebra");
+    }
+
+    #[test]
+    fn test_artists_from_library_multiple_artists_per_song() {
+        let lib = make_library(vec![
+            create_test_music_dto_multi_artists(
+                "1",
+                "Collaboration",
+                "Album A",
+                "album_1",
+                vec![("Artist A", "artist_a"), ("Artist B", "artist_b")],
+            ),
+            create_test_music_dto(
+                "2",
+                "Solo Track",
+                "Album B",
+                "album_2",
+                "Artist A",
+                "artist_a",
+                Some(1),
+                Some(1),
+            ),
+        ]);
+        let artists = lib.artists_from_library();
+        assert_eq!(artists.len(), 2);
+        assert_eq!(artists[0].name(), "Artist A");
+        assert_eq!(artists[1].name(), "Artist B");
+    }
+
+    #[test]
+    fn test_albums_for_artist_filters_correctly() {
+        let lib = make_library(vec![
+            create_test_music_dto(
+                "1",
+                "Song 1",
+                "Album A",
+                "album_1",
+                "Artist A",
+                "artist_1",
+                Some(1),
+                Some(1),
+            ),
+            create_test_music_dto(
+                "2",
+                "Song 2",
+                "Album B",
+                "album_2",
+                "Artist B",
+                "artist_2",
+                Some(1),
+                Some(1),
+            ),
+            create_test_music_dto(
+                "3",
+                "Song 3",
+                "Album A",
+                "album_1",
+                "Artist A",
+                "artist_1",
+                Some(2),
+                Some(1),
+            ),
+            create_test_music_dto(
+                "4",
+                "Song 4",
+                "Album C",
+                "album_3",
+                "Artist A",
+                "artist_1",
+                Some(1),
+                Some(1),
+            ),
+        ]);
+        let albums = lib.albums_for_artist("artist_1");
+        assert_eq!(albums.len(), 2);
+        assert!(albums.iter().any(|a| a.id() == "album_1"));
+        assert!(albums.iter().any(|a| a.id() == "album_3"));
+        assert!(!albums.iter().any(|a| a.id() == "album_2"));
+    }
+
+    #[test]
+    fn test_albums_for_artist_no_matches() {
+        let lib = make_library(vec![create_test_music_dto(
+            "1",
+            "Song 1",
+            "Album A",
+            "album_1",
+            "Artist A",
+            "artist_1",
+            Some(1),
+            Some(1),
+        )]);
+        assert_eq!(lib.albums_for_artist("nonexistent_artist").len(), 0);
+    }
+
+    #[test]
+    fn test_appears_on_and_top_songs_for_artist() {
+        let guest = ArtistItemsDto {
+            name: "Guest".to_string(),
+            id: "artist_2".to_string(),
+        };
+        let mut feature = create_test_music_dto(
+            "2",
+            "Song 2",
+            "Album A",
+            "album_1",
+            "Artist A",
+            "artist_1",
+            Some(2),
+            Some(1),
+        );
+        feature.artist_items = vec![guest.clone()];
+        feature.user_data.play_count = 5;
+        let mut own = create_test_music_dto(
+            "3",
+            "Song 3",
+            "Album B",
+            "album_2",
+            "Guest",
+            "artist_2",
+            Some(1),
+            Some(1),
+        );
+        own.artist_items = vec![guest];
+        let lib = make_library(vec![
+            create_test_music_dto(
+                "1",
+                "Song 1",
+                "Album A",
+                "album_1",
+                "Artist A",
+                "artist_1",
+                Some(1),
+                Some(1),
+            ),
+            feature,
+            own,
+        ]);
+
+        let appears_on = lib.appears_on_for_artist("artist_2");
+        assert_eq!(appears_on.len(), 1);
+        assert_eq!(appears_on[0].id(), "album_1");
+        assert!(lib.appears_on_for_artist("artist_1").is_empty());
+
+        let top: Vec<String> = lib
+            .top_songs_for_artist("artist_2", 10)
+            .iter()
+            .map(|song| song.id())
+            .collect();
+        assert_eq!(top, vec!["2", "3"]);
+    }
+
+    #[test]
+    fn test_artists_in_library() {
+        let lib = make_library(vec![create_test_music_dto(
+            "1",
+            "Song 1",
+            "Album A",
+            "album_1",
+            "Artist A",
+            "artist_1",
+            Some(1),
+            Some(1),
+        )]);
+        let wanted = |name: &str, id: &str| ArtistItemsDto {
+            name: name.to_string(),
+            id: id.to_string(),
+        };
+        let artists = lib.artists_in_library(&[
+            wanted("Someone Else", "artist_9"),
+            wanted("artist a", "mbid"),
+            wanted("Artist A", "artist_1"),
+        ]);
+        assert_eq!(artists.len(), 1);
+        assert_eq!(artists[0].id(), "artist_1");
+    }
+
+    #[test]
+    fn test_genres_from_library() {
+        let mut songs = vec![
+            create_test_music_dto(
+                "1",
+                "Song 1",
+                "Album A",
+                "album_1",
+                "Artist A",
+                "artist_1",
+                Some(1),
+                Some(1),
+            ),
+            create_test_music_dto(
+                "2",
+                "Song 2",
+                "Album A",
+                "album_1",
+                "Artist A",
+                "artist_1",
+                Some(2),
+                Some(1),
+            ),
+            create_test_music_dto(
+                "3",
+                "Song 3",
+                "Album B",
+                "album_2",
+                "Artist B",
+                "artist_2",
+                Some(1),
+                Some(1),
+            ),
+        ];
+        songs[0].genres = vec!["Rock; Rock/Indie".to_string()];
+        songs[1].genres = vec!["Rock".to_string()];
+        songs[2].genres = vec!["Indie".to_string()];
+        songs[2].user_data.play_count = 10;
+        let lib = make_library(songs);
+
+        let genres = lib.categories_from_library(Category::Genre);
+        let summary: Vec<_> = genres
+            .iter()
+            .map(|g| (g.name(), g.album_count(), g.song_count()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![("indie".to_string(), 2, 2), ("rock".to_string(), 1, 2)]
+        );
+        // Most played album first
+        assert_eq!(genres[0].cover_art(), vec!["album_2", "album_1"]);
+
+        assert_eq!(lib.albums_for_category(Category::Genre, "indie").len(), 2);
+        assert_eq!(lib.artists_for_category(Category::Genre, "rock").len(), 1);
+        let songs: Vec<String> = lib
+            .songs_for_category(Category::Genre, "rock")
+            .iter()
+            .map(|s| s.id())
+            .collect();
+        assert_eq!(songs, vec!["1", "2"]);
+    }
+
+    #[test]
+    fn test_composers_decades_and_labels() {
+        let person = |name: &str, person_type: &str| PersonDto {
+            name: name.to_string(),
+            role: None,
+            person_type: person_type.to_string(),
+        };
+        let mut songs = vec![
+            create_test_music_dto(
+                "1",
+                "Song 1",
+                "Album A",
+                "album_1",
+                "Artist A",
+                "artist_1",
+                Some(1),
+                Some(1),
+            ),
+            create_test_music_dto(
+                "2",
+                "Song 2",
+                "Album B",
+                "album_2",
+                "Artist B",
+                "artist_2",
+                Some(1),
+                Some(1),
+            ),
+        ];
+        songs[0].people = vec![
+            person("J. S. Bach", "Composer"),
+            person("Someone", "Lyricist"),
+        ];
+        songs[0].production_year = Some(1999);
+        songs[0].studios = vec![StudioDto {
+            name: "Deutsche Grammophon".to_string(),
+        }];
+        songs[1].people = vec![person("J. S. Bach", "Composer")];
+        songs[1].production_year = Some(1990);
+        let lib = make_library(songs);
+
+        let composers = lib.categories_from_library(Category::Composer);
+        assert_eq!(composers.len(), 1);
+        assert_eq!(composers[0].name(), "J. S. Bach");
+        assert_eq!(composers[0].album_count(), 2);
+        assert_eq!(composers[0].category(), Category::Composer);
+
+        let decades = lib.categories_from_library(Category::Decade);
+        assert_eq!(decades.len(), 1);
+        assert_eq!(decades[0].name(), "1990s");
+        assert_eq!(decades[0].song_count(), 2);
+
+        let years: Vec<String> = lib
+            .categories_from_library(Category::Year)
+            .iter()
+            .map(|year| year.name())
+            .collect();
+        assert_eq!(years, vec!["1990", "1999"]);
+        assert_eq!(lib.songs_for_category(Category::Year, "1999")[0].id(), "1");
+
+        let labels = lib.categories_from_library(Category::Label);
+        assert_eq!(labels.len(), 1);
+        assert_eq!(
+            lib.albums_for_category(Category::Label, "Deutsche Grammophon")[0].id(),
+            "album_1"
+        );
+    }
+
+    #[test]
+    fn test_song_credits_fetched_separately() {
+        let lib = make_library(vec![
+            create_test_music_dto(
+                "1",
+                "Song 1",
+                "Album A",
+                "album_1",
+                "Artist A",
+                "artist_1",
+                Some(1),
+                Some(1),
+            ),
+            create_test_music_dto(
+                "2",
+                "Song 2",
+                "Album A",
+                "album_1",
+                "Artist A",
+                "artist_1",
+                Some(1),
+                Some(2),
+            ),
+        ]);
+        assert!(lib.needs_credits(Category::Composer));
+        assert!(lib.needs_credits(Category::Label));
+        assert!(!lib.needs_credits(Category::Genre));
+        assert!(lib.categories_from_library(Category::Composer).is_empty());
+
+        let credits: Vec<SongCreditsDto> = serde_json::from_str(
+            r#"[{"Id":"1","People":[{"Name":"Composer A","Type":"Composer"}]},{"Id":"unknown"}]"#,
+        )
+        .unwrap();
+        lib.set_song_credits(Category::Composer, Some(credits));
+        assert!(!lib.needs_credits(Category::Composer));
+        assert!(lib.needs_credits(Category::Label));
+        let composers = lib.categories_from_library(Category::Composer);
+        assert_eq!(composers.len(), 1);
+        assert_eq!(composers[0].song_count(), 1);
+
+        // Songs that came with their credits are taken as they are
+        lib.set_song_credits(Category::Label, None);
+        assert!(!lib.needs_credits(Category::Label));
+
+        // A new download comes without them again
+        let songs = lib.songs.borrow().clone();
+        lib.update_songs(songs);
+        assert!(lib.needs_credits(Category::Composer));
+    }
+
+    #[test]
+    fn test_playlist_problems_need_songs() {
+        let song = create_music_dto_user_data(1);
+        let lib = make_library(vec![]);
+        assert_eq!(lib.playlist_problems(std::slice::from_ref(&song)), None);
+
+        let lib = make_library(vec![song.clone()]);
+        assert_eq!(lib.playlist_problems(&[song]), Some(vec![]));
+    }
+
+    #[test]
+    fn test_song_files_fetched_separately() {
+        let lib = make_library(vec![
+            create_music_dto_user_data(1),
+            create_music_dto_user_data(2),
+        ]);
+        assert!(lib.needs_song_files());
+        let file = |id: &str| SongFileDto {
+            id: id.to_string(),
+            path: Some(format!("/music/{}.flac", id)),
+            provider_ids: HashMap::from([("MusicBrainzRecording".to_string(), id.to_string())]),
+        };
+
+        // Just the songs being exported
+        lib.set_song_files(Some(vec![file("user-data-1")]), false);
+        assert!(lib.needs_song_files());
+        let entries = lib.playlist_entries(&["user-data-1".to_string()]);
+        assert_eq!(
+            entries[0].location.as_deref(),
+            Some("/music/user-data-1.flac")
+        );
+        assert_eq!(
+            entries[0].identifiers,
+            ["https://musicbrainz.org/recording/user-data-1"]
+        );
+
+        lib.set_song_files(Some(vec![file("user-data-2"), file("unknown")]), true);
+        assert!(!lib.needs_song_files());
+        assert_eq!(
+            lib.songs.borrow()[1].path.as_deref(),
+            Some("/music/user-data-2.flac")
+        );
+
+        // A refresh brings the songs back without them
+        let songs = lib.songs.borrow().clone();
+        lib.update_songs(songs);
+        assert!(lib.needs_song_files());
+        lib.set_song_files(None, false);
+        assert!(!lib.needs_song_files());
+    }
+
+    #[test]
+    fn test_songs_for_album_filters_and_sorts() {
+        let lib = make_library(vec![
+            create_test_music_dto(
+                "1",
+                "Song 1",
+                "Album A",
+                "album_1",
+                "Artist A",
+                "artist_1",
+                Some(3),
+                Some(1),
+            ),
+            create_test_music_dto(
+                "2",
+                "Song 2",
+                "Album B",
+                "album_2",
+                "Artist B",
+                "artist_2",
+                Some(1),
+                Some(1),
+            ),
+            create_test_music_dto(
+                "3",
+                "Song 3",
+                "Album A",
+                "album_1",
+                "Artist A",
+                "artist_1",
+                Some(1),
+                Some(1),
+            ),
+            create_test_music_dto(
+                "4",
+                "Song 4",
+                "Album A",
+                "album_1",
+                "Artist A",
+                "artist_1",
+                Some(2),
+                Some(1),
+            ),
+            create_test_music_dto(
+                "5",
+                "Song 5",
+                "Album A",
+                "album_1",
+                "Artist A",
+                "artist_1",
+                Some(1),
+                Some(2),
+            ),
+        ]);
+        let songs = lib.songs_for_album("album_1");
+        assert_eq!(songs.len(), 4);
+        assert_eq!(songs[0].title(), "Song 3");
+        assert_eq!(songs[1].title(), "Song 4");
+        assert_eq!(songs[2].title(), "Song 1");
+        assert_eq!(songs[3].title(), "Song 5");
+    }
+
+    #[test]
+    fn test_songs_for_album_handles_none_track_numbers() {
+        let lib = make_library(vec![
+            create_test_music_dto(
+                "1", "Song 1", "Album A", "album_1", "Artist A", "artist_1", None, None,
+            ),
+            create_test_music_dto(
+                "2",
+                "Song 2",
+                "Album A",
+                "album_1",
+                "Artist A",
+                "artist_1",
+                Some(1),
+                Some(1),
+            ),
+        ]);
+        let songs = lib.songs_for_album("album_1");
+        assert_eq!(songs.len(), 2);
+        assert_eq!(songs[0].title(), "Song 1");
+        assert_eq!(songs[1].title(), "Song 2");
+    }
+
+    #[test]
+    fn test_shuffle_songs_returns_requested_count() {
+        let lib = make_library(vec![
+            create_test_music_dto(
+                "1",
+                "Song 1",
+                "Album A",
+                "album_1",
+                "Artist A",
+                "artist_1",
+                Some(1),
+                Some(1),
+            ),
+            create_test_music_dto(
+                "2",
+                "Song 2",
+                "Album B",
+                "album_2",
+                "Artist B",
+                "artist_2",
+                Some(1),
+                Some(1),
+            ),
+            create_test_music_dto(
+                "3",
+                "Song 3",
+                "Album C",
+                "album_3",
+                "Artist C",
+                "artist_3",
+                Some(1),
+                Some(1),
+            ),
+            create_test_music_dto(
+                "4",
+                "Song 4",
+                "Album D",
+                "album_4",
+                "Artist D",
+                "artist_4",
+                Some(1),
+                Some(1),
+            ),
+            create_test_music_dto(
+                "5",
+                "Song 5",
+                "Album E",
+                "album_5",
+                "Artist E",
+                "artist_5",
+                Some(1),
+                Some(1),
+            ),
+        ]);
+        assert_eq!(lib.shuffle_songs(3, &HashSet::new()).len(), 3);
+    }
+
+    #[test]
+    fn test_shuffle_songs_handles_request_larger_than_library() {
+        let lib = make_library(vec![
+            create_test_music_dto(
+                "1",
+                "Song 1",
+                "Album A",
+                "album_1",
+                "Artist A",
+                "artist_1",
+                Some(1),
+                Some(1),
+            ),
+            create_test_music_dto(
+                "2",
+                "Song 2",
+                "Album B",
+                "album_2",
+                "Artist B",
+                "artist_2",
+                Some(1),
+                Some(1),
+            ),
+        ]);
+        assert_eq!(lib.shuffle_songs(10, &HashSet::new()).len(), 2);
+    }
+
+    #[test]
+    fn test_shuffle_songs_empty_library() {
+        let lib = make_library(vec![]);
+        assert_eq!(lib.shuffle_songs(5, &HashSet::new()).len(), 0);
+    }
+
+    #[test]
+    fn test_shuffle_songs_zero_request() {
+        let lib = make_library(vec![create_test_music_dto(
+            "1",
+            "Song 1",
+            "Album A",
+            "album_1",
+            "Artist A",
+            "artist_1",
+            Some(1),
+            Some(1),
+        )]);
+        assert_eq!(lib.shuffle_songs(0, &HashSet::new()).len(), 0);
+    }
+
+    #[test]
+    fn test_albums_from_library_preserves_artist_order() {
+        let lib = make_library(vec![create_test_music_dto_multi_artists(
+            "1",
+            "Song 1",
+            "Album A",
+            "album_1",
+            vec![
+                ("Primary Artist", "primary"),
+                ("Secondary Artist", "secondary"),
+            ],
+        )]);
+        let albums = lib.albums_from_library();
+        assert_eq!(albums.len(), 1);
+        assert_eq!(albums[0].primary_artist(), "Primary Artist");
+        assert_eq!(albums[0].artists().len(), 2);
+        assert_eq!(albums[0].artists()[0], "Primary Artist");
+        assert_eq!(albums[0].artists()[1], "Secondary Artist");
+    }
+
+    #[test]
+    fn test_most_played_songs() {
+        let lib = make_library(vec![
+            create_music_dto_user_data(1),
+            create_music_dto_user_data(2),
+            create_music_dto_user_data(3),
+            create_music_dto_user_data(0),
+        ]);
+        let most_played = lib.most_played_songs(100, &HashSet::new());
+        assert_eq!(most_played.len(), 3);
+        assert_eq!(most_played[0].id(), "user-data-3");
+        assert_eq!(most_played[1].id(), "user-data-2");
+        assert_eq!(most_played[2].id(), "user-data-1");
+
+        let skipped = HashSet::from(["user-data-3".to_string()]);
+        let most_played = lib.most_played_songs(1, &skipped);
+        assert_eq!(most_played.len(), 1);
+        assert_eq!(most_played[0].id(), "user-data-2");
+    }
+
+    #[test]
+    fn test_audiobooks_left_out_of_music_sources() {
+        let mut book = create_music_dto_user_data(5);
+        book.item_type = Some("audiobook".to_string());
+        book.user_data.rating = Some(10.0);
+        let mut song = create_music_dto_user_data(1);
+        song.item_type = Some("music".to_string());
+        song.user_data.rating = Some(8.0);
+        let lib = make_library(vec![book, song]);
+        lib.update_favorites(&[
+            FavoriteDto {
+                id: "user-data-5".to_string(),
+                item_type: ItemType::Audio,
+                user_data: FavoriteUserDataDto { is_favorite: true },
+            },
+            FavoriteDto {
+                id: "user-data-1".to_string(),
+                item_type: ItemType::Audio,
+                user_data: FavoriteUserDataDto { is_favorite: true },
+            },
+        ]);
+
+        let ids = |songs: Vec<SongModel>| songs.iter().map(|s| s.id()).collect::<Vec<_>>();
+        assert_eq!(ids(lib.shuffle_songs(10, &HashSet::new())), ["user-data-1"]);
+        assert_eq!(
+            ids(lib.most_played_songs(10, &HashSet::new())),
+            ["user-data-1"]
+        );
+        assert_eq!(
+            ids(lib.top_rated_songs(10, &HashSet::new())),
+            ["user-data-1"]
+        );
+        assert_eq!(ids(lib.all_favorites()), ["user-data-1"]);
+        assert!(
+            Audiobook {
+                id: "book".to_string(),
+                ..Default::default()
+            }
+            .to_music_dto()
+            .is_audiobook()
+        );
+    }
+
+    #[test]
+    fn test_favorites() {
+        let lib = make_library(vec![
+            create_music_dto_user_data(1),
+            create_music_dto_user_data(2),
+        ]);
+        lib.update_favorites(&[
+            FavoriteDto {
+                id: lib.all_songs()[0].id().to_string(),
+                item_type: ItemType::Audio,
+                user_data: FavoriteUserDataDto { is_favorite: true },
+            },
+            FavoriteDto {
+                id: lib.all_songs()[1].id().to_string(),
+                item_type: ItemType::Audio,
+                user_data: FavoriteUserDataDto { is_favorite: false },
+            },
+        ]);
+        assert!(
+            lib.favorites
+                .borrow()
+                .contains_song(&lib.all_songs()[0].id())
+        );
+        assert!(
+            !lib.favorites
+                .borrow()
+                .contains_song(&lib.all_songs()[1].id())
+        );
+    }
+
+    #[test]
+    fn test_merge_songs() {
+        let lib = make_library(vec![
+            create_music_dto_user_data(1),
+            create_music_dto_user_data(2),
+        ]);
+        let mut updated = create_music_dto_user_data(1);
+        updated.name = "Renamed".to_string();
+        let mut added = create_music_dto_user_data(5);
+        added.genres = vec!["Jazz".to_string()];
+        lib.merge_songs(vec![updated, added], &["user-data-2".to_string()]);
+
+        let ids: Vec<String> = lib.all_songs().iter().map(|s| s.id()).collect();
+        assert_eq!(ids, vec!["user-data-5", "user-data-1"]);
+        assert_eq!(lib.songs.borrow()[1].name, "Renamed");
+        assert_eq!(lib.genres(), vec!["Jazz"]);
+        assert_eq!(lib.album_play_counts.borrow().get("user-data-2"), None);
+    }
+
+    #[test]
+    fn test_merge_songs_keeps_fetched_extras() {
+        let lib = make_library(vec![create_music_dto_user_data(1)]);
+        lib.set_song_credits(Category::Composer, Some(vec![]));
+        lib.set_song_files(Some(vec![]), true);
+
+        lib.merge_songs(vec![create_music_dto_user_data(2)], &[]);
+        assert!(!lib.needs_credits(Category::Composer));
+        assert!(!lib.needs_song_files());
+
+        lib.update_songs(vec![create_music_dto_user_data(2)]);
+        assert!(lib.needs_credits(Category::Composer));
+        assert!(lib.needs_song_files());
+    }
+
+    #[test]
+    fn test_update_user_data() {
+        let lib = make_library(vec![create_music_dto_user_data(1)]);
+        let change = |id: &str, is_favorite: bool, play_count: u64| UserItemDataDto {
+            item_id: id.to_string(),
+            is_favorite,
+            play_count,
+            last_played_date: None,
+            rating: None,
+        };
+
+        let update = lib.update_user_data(
+            &[
+                change("user-data-1", true, 4),
+                change("playlist", true, 0),
+                change("unknown", true, 0),
+            ],
+            |id| id == "playlist",
+        );
+        assert!(update.favorites_changed);
+        assert!(!update.ratings_changed);
+        assert!(update.songs_changed);
+        assert!(lib.song_is_favorite("user-data-1"));
+        assert!(lib.playlist_is_favorite("playlist"));
+        assert_eq!(lib.songs.borrow()[0].user_data.play_count, 4);
+        assert_eq!(lib.album_play_counts.borrow()["user-data-1"], 4);
+        assert_eq!(lib.favorites_list().len(), 2);
+
+        // Only a play count change
+        let update = lib.update_user_data(&[change("user-data-1", true, 5)], |_| false);
+        assert_eq!(
+            update,
+            UserDataUpdate {
+                songs_changed: true,
+                ..Default::default()
+            }
+        );
+        let update = lib.update_user_data(&[change("user-data-1", false, 5)], |_| false);
+        assert!(update.favorites_changed);
+        assert!(!update.songs_changed);
+        assert!(!lib.song_is_favorite("user-data-1"));
+
+        // The server repeating what we have already changes nothing
+        let update = lib.update_user_data(&[change("user-data-1", false, 5)], |_| false);
+        assert_eq!(update, UserDataUpdate::default());
+    }
+
+    #[test]
+    fn test_ratings() {
+        let mut rated = create_music_dto_user_data(1);
+        rated.user_data.rating = Some(8.0);
+        let lib = make_library(vec![rated, create_music_dto_user_data(2)]);
+        assert_eq!(lib.all_songs()[0].rating(), 4);
+
+        lib.update_album_ratings(&[
+            RatingDto {
+                id: "user-data-2".to_string(),
+                user_data: RatingUserDataDto { rating: Some(6.0) },
+            },
+            RatingDto {
+                id: "user-data-1".to_string(),
+                user_data: RatingUserDataDto { rating: None },
+            },
+        ]);
+        assert_eq!(lib.album_rating("user-data-2"), 3);
+        assert_eq!(lib.album_ratings_list().len(), 1);
+        let album = lib.album_for_item("user-data-2").unwrap();
+        assert_eq!(album.rating(), 3);
+
+        lib.set_song_rating("user-data-2", 5);
+        assert_eq!(lib.song_rating("user-data-2"), 5);
+        lib.set_album_rating("user-data-2", 0);
+        assert_eq!(lib.album_rating("user-data-2"), 0);
+        let ids: Vec<String> = lib
+            .top_rated_songs(10, &HashSet::new())
+            .iter()
+            .map(|song| song.id())
+            .collect();
+        assert_eq!(ids, vec!["user-data-2", "user-data-1"]);
+        let exclude = HashSet::from(["user-data-2".to_string()]);
+        assert_eq!(lib.top_rated_songs(10, &exclude).len(), 1);
+
+        let update = lib.update_user_data(
+            &[UserItemDataDto {
+                item_id: "user-data-1".to_string(),
+                is_favorite: false,
+                play_count: 1,
+                last_played_date: None,
+                rating: Some(2.0),
+            }],
+            |_| false,
+        );
+        assert!(update.ratings_changed);
+        assert!(!update.favorites_changed);
+        assert_eq!(lib.songs.borrow()[0].user_data.rating, Some(2.0));
+    }
+}
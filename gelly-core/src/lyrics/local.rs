@@ -0,0 +1,179 @@
+//! Lyrics files in a folder picked by the user. Files are matched on their
+//! name, either "Artist - Title" anywhere in the tree or "Title" inside a
+//! folder named after the artist. `.lrc` wins over `.txt`.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::jellyfin::api::LyricsResponse;
+
+use super::{LyricsQuery, lrc};
+
+/// Deep enough for Artist/Album/Title.lrc
+const MAX_DEPTH: usize = 4;
+
+pub fn find(folder: &Path, query: &LyricsQuery) -> std::io::Result<Option<LyricsResponse>> {
+    let Some(path) = find_file(folder, query)? else {
+        return Ok(None);
+    };
+    let lyrics = lrc::parse(&fs::read_to_string(path)?);
+    Ok((!lyrics.is_empty()).then_some(LyricsResponse { lyrics }))
+}
+
+/// "Artist - Title.lrc", the name [`find`] looks for first
+pub fn file_name(query: &LyricsQuery) -> String {
+    let name = match query.artists.first() {
+        Some(artist) => format!("{} - {}", artist, query.title),
+        None => query.title.clone(),
+    };
+    // Dropped rather than replaced so the name still normalizes the same
+    let name: String = name
+        .chars()
+        .filter(|c| !matches!(c, '/' | '\\' | ':'))
+        .collect();
+    format!("{}.lrc", name.trim_start_matches('.'))
+}
+
+/// Writes edited lyrics into the lyrics folder where [`find`] picks them up.
+pub fn save(folder: &Path, query: &LyricsQuery, lrc: &str) -> std::io::Result<PathBuf> {
+    fs::create_dir_all(folder)?;
+    let path = folder.join(file_name(query));
+    fs::write(&path, lrc)?;
+    Ok(path)
+}
+
+fn find_file(folder: &Path, query: &LyricsQuery) -> std::io::Result<Option<PathBuf>> {
+    let title = normalize(&query.title);
+    if title.is_empty() {
+        return Ok(None);
+    }
+    let artists: Vec<String> = query
+        .artists
+        .iter()
+        .map(|artist| normalize(artist))
+        .filter(|artist| !artist.is_empty())
+        .collect();
+    let full_names: Vec<String> = artists
+        .iter()
+        .map(|artist| format!("{artist} {title}"))
+        .collect();
+
+    let mut best: Option<(u8, PathBuf)> = None;
+    let mut folders = vec![(folder.to_path_buf(), 0)];
+    while let Some((dir, depth)) = folders.pop() {
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                if depth < MAX_DEPTH {
+                    folders.push((path, depth + 1));
+                }
+                continue;
+            }
+            let extension_rank = match path.extension().and_then(|e| e.to_str()) {
+                Some(e) if e.eq_ignore_ascii_case("lrc") => 0,
+                Some(e) if e.eq_ignore_ascii_case("txt") => 1,
+                _ => continue,
+            };
+            let stem = path
+                .file_stem()
+                .map(|s| normalize(&s.to_string_lossy()))
+                .unwrap_or_default();
+
+            let name_rank = if full_names.contains(&stem) {
+                0
+            } else if stem == title && in_artist_folder(&path, folder, &artists) {
+                2
+            } else {
+                continue;
+            };
+            let rank = name_rank + extension_rank;
+            if best.as_ref().is_none_or(|(best_rank, _)| rank < *best_rank) {
+                best = Some((rank, path));
+            }
+        }
+    }
+    Ok(best.map(|(_, path)| path))
+}
+
+fn in_artist_folder(path: &Path, root: &Path, artists: &[String]) -> bool {
+    path.ancestors()
+        .skip(1)
+        .take_while(|dir| *dir != root)
+        .filter_map(|dir| dir.file_name())
+        .any(|name| artists.contains(&normalize(&name.to_string_lossy())))
+}
+
+/// Lowercase words with punctuation dropped, so "AC/DC - Back In Black" and
+/// "acdc back in black" match.
+fn normalize(text: &str) -> String {
+    text.to_lowercase()
+        .split(|c: char| c.is_whitespace() || c == '-' || c == '_')
+        .map(|word| {
+            word.chars()
+                .filter(|c| c.is_alphanumeric())
+                .collect::<String>()
+        })
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query(artist: &str, title: &str) -> LyricsQuery {
+        LyricsQuery {
+            title: title.to_string(),
+            artists: vec![artist.to_string()],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize("AC/DC - Back In Black"), "acdc back in black");
+        assert_eq!(normalize("  Don't_Stop  "), "dont stop");
+    }
+
+    #[test]
+    fn test_find_lyrics_files() {
+        let root = std::env::temp_dir().join(format!("gelly-lyrics-{}", uuid::Uuid::new_v4()));
+        let album = root.join("Björk").join("Homogenic");
+        fs::create_dir_all(&album).unwrap();
+        fs::write(root.join("Björk - Jóga.txt"), "Plain\n").unwrap();
+        fs::write(root.join("björk - jóga.lrc"), "[00:01.00]Synced\n").unwrap();
+        fs::write(album.join("Bachelorette.lrc"), "[00:02.00]In folder\n").unwrap();
+        fs::write(root.join("Hunter.lrc"), "[00:03.00]No artist\n").unwrap();
+
+        let found = find(&root, &query("Björk", "Jóga")).unwrap().unwrap();
+        assert_eq!(found.lyrics[0].text, "Synced");
+
+        let found = find(&root, &query("Björk", "Bachelorette"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.lyrics[0].text, "In folder");
+
+        // A bare title outside an artist folder could be anyone's song
+        assert!(find(&root, &query("Björk", "Hunter")).unwrap().is_none());
+        assert!(
+            find(&root, &query("Someone", "Bachelorette"))
+                .unwrap()
+                .is_none()
+        );
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_save_then_find() {
+        let root = std::env::temp_dir().join(format!("gelly-lyrics-{}", uuid::Uuid::new_v4()));
+        let song = query("AC/DC", "Back In Black");
+        let path = save(&root, &song, "[00:01.00]Back in black\n").unwrap();
+        assert_eq!(path, root.join("ACDC - Back In Black.lrc"));
+
+        let found = find(&root, &song).unwrap().unwrap();
+        assert_eq!(found.lyrics[0].text, "Back in black");
+        fs::remove_dir_all(root).unwrap();
+    }
+}
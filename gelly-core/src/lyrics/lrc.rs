@@ -0,0 +1,357 @@
+//! LRC parsing, including the `[offset:]` tag, several timestamps on one line
+//! and enhanced LRC `<mm:ss.xx>` word timings.
+
+use crate::jellyfin::api::{Lyric, LyricWord};
+
+use super::TICKS_PER_MS;
+
+/// Parses LRC or plain text. Text without any timestamps comes back as
+/// unsynced lines, otherwise untimed lines are dropped.
+pub fn parse(text: &str) -> Vec<Lyric> {
+    let mut offset_ms: i64 = 0;
+    let mut timed = Vec::new();
+    let mut plain = Vec::new();
+
+    for line in text.lines() {
+        let line = line.trim();
+        let (timestamps, rest) = split_tags(line, &mut offset_ms);
+        if timestamps.is_empty() {
+            // Metadata only lines like [ar:...] are consumed by split_tags
+            if rest.len() == line.len() {
+                plain.push(line.to_string());
+            }
+            continue;
+        }
+        for start in timestamps {
+            let (text, words) = parse_words(rest, start);
+            timed.push((start, text, words));
+        }
+    }
+
+    if timed.is_empty() {
+        while plain.last().is_some_and(|line| line.is_empty()) {
+            plain.pop();
+        }
+        return plain
+            .into_iter()
+            .skip_while(|line| line.is_empty())
+            .map(|text| Lyric {
+                text,
+                start: None,
+                words: Vec::new(),
+            })
+            .collect();
+    }
+
+    // A positive offset shows lyrics sooner
+    let apply_offset = |ms: i64| ((ms - offset_ms).max(0) as u64) * TICKS_PER_MS;
+    timed.sort_by_key(|(start, _, _)| *start);
+    timed
+        .into_iter()
+        .map(|(start, text, words)| Lyric {
+            text,
+            start: Some(apply_offset(start)),
+            words: words
+                .into_iter()
+                .map(|(start, text)| LyricWord {
+                    text,
+                    start: apply_offset(start),
+                })
+                .collect(),
+        })
+        .collect()
+}
+
+/// Takes the leading `[...]` tags off a line, returning its timestamps in ms
+/// and the remaining text. Reads `[offset:]` as it goes.
+fn split_tags<'a>(line: &'a str, offset_ms: &mut i64) -> (Vec<i64>, &'a str) {
+    let mut timestamps = Vec::new();
+    let mut rest = line;
+    while let Some(tag) = rest.strip_prefix('[') {
+        let Some(end) = tag.find(']') else { break };
+        let content = &tag[..end];
+        if let Some(ms) = parse_timestamp(content) {
+            timestamps.push(ms);
+        } else if let Some((key, value)) = content.split_once(':')
+            && key.chars().all(|c| c.is_ascii_alphabetic())
+        {
+            if key.eq_ignore_ascii_case("offset") {
+                *offset_ms = value.trim().parse().unwrap_or(0);
+            }
+        } else {
+            break;
+        }
+        rest = &tag[end + 1..];
+    }
+    (timestamps, rest.trim())
+}
+
+/// Splits enhanced LRC word tags out of a line starting at `line_ms`. Returns
+/// the plain text and the words with their start in ms, or no words if the
+/// line has no tags.
+fn parse_words(text: &str, line_ms: i64) -> (String, Vec<(i64, String)>) {
+    if !text.contains('<') {
+        return (text.to_string(), Vec::new());
+    }
+    let mut words: Vec<(i64, String)> = Vec::new();
+    let mut plain = String::new();
+    let mut rest = text;
+    loop {
+        let (segment, tail) = match rest.find('<') {
+            Some(start) => (&rest[..start], Some(&rest[start + 1..])),
+            None => (rest, None),
+        };
+        plain.push_str(segment);
+        match words.last_mut() {
+            Some((_, word)) => word.push_str(segment),
+            // Text before the first tag starts with the line
+            None if !segment.trim().is_empty() => words.push((line_ms, segment.to_string())),
+            None => {}
+        }
+        let Some(tail) = tail else { break };
+        let Some(end) = tail.find('>') else {
+            plain.push('<');
+            plain.push_str(tail);
+            break;
+        };
+        match parse_timestamp(&tail[..end]) {
+            Some(ms) => words.push((ms, String::new())),
+            None => plain.push_str(&tail[..=end]),
+        }
+        rest = &tail[end + 1..];
+    }
+    // A closing tag only marks where the last word ends
+    words.retain(|(_, word)| !word.trim().is_empty());
+    (plain.trim().to_string(), words)
+}
+
+/// `mm:ss`, `mm:ss.xx`, `mm:ss.xxx`, `mm:ss:xx` or `hh:mm:ss.xx` to ms
+fn parse_timestamp(text: &str) -> Option<i64> {
+    let (clock, fraction) = match text.rsplit_once('.') {
+        Some((clock, fraction)) => (clock, Some(fraction)),
+        None => (text, None),
+    };
+    let mut parts: Vec<&str> = clock.split(':').collect();
+    // Some files use a colon before the hundredths
+    let fraction = match fraction {
+        Some(fraction) => Some(fraction),
+        None if parts.len() == 3 && parts[2].len() == 2 && parts[0].len() <= 2 => {
+            let fraction = parts.pop();
+            if parts[0].parse::<i64>().ok()? >= 60 {
+                return None;
+            }
+            fraction
+        }
+        None => None,
+    };
+    if !(2..=3).contains(&parts.len()) || parts.iter().any(|part| part.is_empty()) {
+        return None;
+    }
+    let mut ms = 0;
+    for part in &parts {
+        if !part.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        ms = ms * 60 + part.parse::<i64>().ok()?;
+    }
+    ms *= 1000;
+    if let Some(fraction) = fraction {
+        if fraction.is_empty() || !fraction.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        let digits: String = fraction.chars().chain("000".chars()).take(3).collect();
+        ms += digits.parse::<i64>().ok()?;
+    }
+    Some(ms)
+}
+
+/// Writes synced lyrics back out as LRC.
+pub fn to_lrc(lyrics: &[Lyric]) -> String {
+    let mut out = String::new();
+    for lyric in lyrics {
+        if let Some(start) = lyric.start {
+            out.push_str(&format_timestamp(start / TICKS_PER_MS));
+        }
+        if lyric.words.is_empty() {
+            out.push_str(&lyric.text);
+        } else {
+            for word in &lyric.words {
+                out.push('<');
+                out.push_str(&format_clock(word.start / TICKS_PER_MS));
+                out.push('>');
+                out.push_str(&word.text);
+            }
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// Replaces the timestamps at the start of a line with one at `ms`, for
+/// stamping lines in the editor.
+pub fn stamp(line: &str, ms: u64) -> String {
+    let mut offset_ms = 0;
+    let (_, text) = split_tags(line.trim(), &mut offset_ms);
+    format!("{}{}", format_timestamp(ms), text)
+}
+
+/// Moves every line and word timestamp in a line by `delta_ms`, stopping at
+/// zero. Anything that isn't a timestamp is left alone.
+pub fn shift(line: &str, delta_ms: i64) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut rest = line;
+    while let Some(open) = rest.find(['[', '<']) {
+        out.push_str(&rest[..open]);
+        let close = if rest[open..].starts_with('[') {
+            ']'
+        } else {
+            '>'
+        };
+        let tag = &rest[open + 1..];
+        match tag
+            .find(close)
+            .and_then(|end| Some((end, parse_timestamp(&tag[..end])?)))
+        {
+            Some((end, ms)) => {
+                let shifted = (ms + delta_ms).max(0) as u64;
+                out.push_str(&rest[open..=open]);
+                out.push_str(&format_clock(shifted));
+                out.push(close);
+                rest = &tag[end + 1..];
+            }
+            _ => {
+                out.push_str(&rest[open..=open]);
+                rest = tag;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Where a line starts, in ms, if it has a timestamp
+pub fn line_start(line: &str) -> Option<u64> {
+    let mut offset_ms = 0;
+    let (timestamps, _) = split_tags(line.trim(), &mut offset_ms);
+    timestamps.first().map(|ms| *ms as u64)
+}
+
+fn format_timestamp(ms: u64) -> String {
+    format!("[{}]", format_clock(ms))
+}
+
+/// `mm:ss.xx`, with the minutes growing past two digits for long tracks
+fn format_clock(ms: u64) -> String {
+    format!(
+        "{:02}:{:02}.{:02}",
+        ms / 60_000,
+        ms / 1000 % 60,
+        ms % 1000 / 10
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn starts(lyrics: &[Lyric]) -> Vec<Option<u64>> {
+        lyrics
+            .iter()
+            .map(|l| l.start.map(|t| t / TICKS_PER_MS))
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_timestamps() {
+        assert_eq!(parse_timestamp("01:02.5"), Some(62_500));
+        assert_eq!(parse_timestamp("01:02.50"), Some(62_500));
+        assert_eq!(parse_timestamp("01:02.503"), Some(62_503));
+        assert_eq!(parse_timestamp("01:02:50"), Some(62_500));
+        assert_eq!(parse_timestamp("01:02"), Some(62_000));
+        assert_eq!(parse_timestamp("1:01:02.00"), Some(3_662_000));
+        assert_eq!(parse_timestamp("ar:Someone"), None);
+        assert_eq!(parse_timestamp("00:xx.00"), None);
+    }
+
+    #[test]
+    fn test_parse_metadata_and_multiple_timestamps() {
+        let lrc =
+            "[ar:Artist]\n[ti:Title]\n[00:10.00][01:10.00]Chorus\n[00:05.00]Verse\n\nstray line\n";
+        let lyrics = parse(lrc);
+        let text: Vec<_> = lyrics.iter().map(|l| l.text.as_str()).collect();
+        assert_eq!(text, vec!["Verse", "Chorus", "Chorus"]);
+        assert_eq!(
+            starts(&lyrics),
+            vec![Some(5_000), Some(10_000), Some(70_000)]
+        );
+    }
+
+    #[test]
+    fn test_parse_offset() {
+        let lyrics = parse("[offset:+500]\n[00:01.00]Early\n[00:00.20]Clamped\n");
+        assert_eq!(starts(&lyrics), vec![Some(0), Some(500)]);
+
+        let lyrics = parse("[offset:-250]\n[00:01.00]Late\n");
+        assert_eq!(starts(&lyrics), vec![Some(1_250)]);
+    }
+
+    #[test]
+    fn test_parse_enhanced_words() {
+        let lyrics = parse("[00:12.00]<00:12.00>Hello <00:12.50>there <00:13.10>world<00:14.00>\n");
+        assert_eq!(lyrics.len(), 1);
+        assert_eq!(lyrics[0].text, "Hello there world");
+        let words: Vec<_> = lyrics[0]
+            .words
+            .iter()
+            .map(|w| (w.text.as_str(), w.start / TICKS_PER_MS))
+            .collect();
+        assert_eq!(
+            words,
+            vec![("Hello ", 12_000), ("there ", 12_500), ("world", 13_100)]
+        );
+    }
+
+    #[test]
+    fn test_untagged_leading_words_start_with_line() {
+        let lyrics = parse("[00:12.00]Hello <00:12.50>there\n");
+        let words: Vec<_> = lyrics[0]
+            .words
+            .iter()
+            .map(|w| (w.text.as_str(), w.start / TICKS_PER_MS))
+            .collect();
+        assert_eq!(words, vec![("Hello ", 12_000), ("there", 12_500)]);
+    }
+
+    #[test]
+    fn test_parse_plain_text() {
+        let lyrics = parse("\nFirst line\n\nSecond <b>line</b>\n\n");
+        let text: Vec<_> = lyrics.iter().map(|l| l.text.as_str()).collect();
+        assert_eq!(text, vec!["First line", "", "Second <b>line</b>"]);
+        assert!(lyrics.iter().all(|l| l.start.is_none()));
+    }
+
+    #[test]
+    fn test_stamp_and_shift() {
+        assert_eq!(stamp("Plain line", 62_500), "[01:02.50]Plain line");
+        assert_eq!(stamp("[00:01.00]Restamped", 3_000), "[00:03.00]Restamped");
+        assert_eq!(line_start("[00:03.00]Restamped"), Some(3_000));
+        assert_eq!(line_start("Plain line"), None);
+
+        assert_eq!(
+            shift("[00:01.00]<00:01.00>Two <00:01.50>words", 250),
+            "[00:01.25]<00:01.25>Two <00:01.75>words"
+        );
+        assert_eq!(shift("[00:00.10]Clamped", -500), "[00:00.00]Clamped");
+        assert_eq!(shift("[ar:Someone] a < b", 100), "[ar:Someone] a < b");
+    }
+
+    #[test]
+    fn test_lrc_round_trip() {
+        let lrc = "[00:05.00]Verse\n[01:10.25]<01:10.25>Two <01:11.00>words\n";
+        assert_eq!(to_lrc(&parse(lrc)), lrc);
+
+        // Past 99 minutes the clock is wider than the usual two digits
+        let lrc = "[100:00.00]<100:00.00>Long <100:01.50>mix\n";
+        assert_eq!(to_lrc(&parse(lrc)), lrc);
+    }
+}
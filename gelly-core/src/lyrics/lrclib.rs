@@ -0,0 +1,143 @@
+//! Lookup against an LRCLIB compatible service (https://lrclib.net/docs).
+//! The base url is configurable so a self hosted or local mock server works
+//! as well.
+
+use reqwest::{Client, StatusCode};
+use serde::Deserialize;
+
+use crate::config;
+use crate::jellyfin::api::LyricsResponse;
+
+use super::{LyricsError, LyricsQuery, lrc};
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LrclibTrack {
+    #[serde(default)]
+    instrumental: bool,
+    plain_lyrics: Option<String>,
+    synced_lyrics: Option<String>,
+}
+
+pub async fn fetch(
+    base_url: &str,
+    query: &LyricsQuery,
+) -> Result<Option<LyricsResponse>, LyricsError> {
+    let Some(artist) = query.artists.first() else {
+        return Ok(None);
+    };
+    let url = format!("{}/api/get", base_url.trim_end_matches('/'));
+    let duration = query.duration_seconds.to_string();
+    let mut params = vec![
+        ("track_name", query.title.as_str()),
+        ("artist_name", artist.as_str()),
+        ("album_name", query.album.as_str()),
+    ];
+    if query.duration_seconds > 0 {
+        params.push(("duration", duration.as_str()));
+    }
+
+    let response = Client::new()
+        .get(url)
+        .header(
+            "User-Agent",
+            format!(
+                "Gelly/{} (https://github.com/Fingel/gelly)",
+                config::VERSION
+            ),
+        )
+        .query(&params)
+        .send()
+        .await?;
+    if response.status() == StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    let track: LrclibTrack = serde_json::from_str(&response.error_for_status()?.text().await?)?;
+    Ok(lyrics_from_track(track))
+}
+
+fn lyrics_from_track(track: LrclibTrack) -> Option<LyricsResponse> {
+    if track.instrumental {
+        return None;
+    }
+    let text = track
+        .synced_lyrics
+        .filter(|lyrics| !lyrics.trim().is_empty())
+        .or(track.plain_lyrics)?;
+    let lyrics = lrc::parse(&text);
+    (!lyrics.is_empty()).then_some(LyricsResponse { lyrics })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_server::serve_once;
+
+    fn query() -> LyricsQuery {
+        LyricsQuery {
+            item_id: "1".to_string(),
+            title: "Song & Dance".to_string(),
+            artists: vec!["Artist".to_string(), "Guest".to_string()],
+            album: "Album".to_string(),
+            duration_seconds: 200,
+            backend_has_lyrics: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fetch_from_server() {
+        let (base_url, server) = serve_once(
+            "200 OK",
+            r#"{"instrumental":false,"plainLyrics":"One","syncedLyrics":"[00:01.00]One"}"#,
+        )
+        .await;
+        let lyrics = fetch(&base_url, &query()).await.unwrap().unwrap().lyrics;
+        assert_eq!(lyrics.len(), 1);
+        assert_eq!(lyrics[0].start, Some(10_000_000));
+
+        let request = server.await.unwrap();
+        assert!(request.starts_with("GET /api/get?"), "{request}");
+        assert!(request.contains("track_name=Song+%26+Dance"), "{request}");
+        assert!(request.contains("artist_name=Artist&"), "{request}");
+        assert!(request.contains("duration=200"), "{request}");
+    }
+
+    #[tokio::test]
+    async fn test_fetch_not_found_and_errors() {
+        let (base_url, server) = serve_once("404 Not Found", r#"{"code":404}"#).await;
+        assert!(fetch(&base_url, &query()).await.unwrap().is_none());
+        server.await.unwrap();
+
+        let (base_url, server) = serve_once("500 Internal Server Error", "{}").await;
+        assert!(matches!(
+            fetch(&base_url, &query()).await,
+            Err(LyricsError::Http(_))
+        ));
+        server.await.unwrap();
+    }
+
+    #[test]
+    fn test_prefers_synced_lyrics() {
+        let track: LrclibTrack = serde_json::from_str(
+            r#"{"id":1,"trackName":"Song","instrumental":false,"plainLyrics":"One\nTwo","syncedLyrics":"[00:01.00] One\n[00:02.00] Two"}"#,
+        )
+        .unwrap();
+        let lyrics = lyrics_from_track(track).unwrap().lyrics;
+        assert_eq!(lyrics.len(), 2);
+        assert_eq!(lyrics[1].text, "Two");
+        assert_eq!(lyrics[1].start, Some(20_000_000));
+    }
+
+    #[test]
+    fn test_plain_and_instrumental() {
+        let track: LrclibTrack =
+            serde_json::from_str(r#"{"plainLyrics":"One\nTwo","syncedLyrics":null}"#).unwrap();
+        let lyrics = lyrics_from_track(track).unwrap().lyrics;
+        assert_eq!(lyrics[0].start, None);
+
+        let track: LrclibTrack =
+            serde_json::from_str(r#"{"instrumental":true,"plainLyrics":null,"syncedLyrics":null}"#)
+                .unwrap();
+        assert!(lyrics_from_track(track).is_none());
+    }
+}
@@ -0,0 +1,235 @@
+//! Lyrics from more places than the server: the backend is asked first, then
+//! a folder of `.lrc`/`.txt` files, then an LRCLIB compatible web service.
+//! Whatever is found is cached on disk by song id.
+
+use std::fs;
+use std::path::PathBuf;
+
+use log::{debug, warn};
+use thiserror::Error;
+
+use crate::backend::{Backend, BackendError};
+use crate::cache::{CacheError, get_cache_directory};
+use crate::config;
+use crate::jellyfin::api::LyricsResponse;
+use crate::models::SongModel;
+
+pub mod local;
+pub mod lrc;
+pub mod lrclib;
+
+/// Jellyfin ticks (100ns) per millisecond
+pub const TICKS_PER_MS: u64 = 10_000;
+
+#[derive(Error, Debug)]
+pub enum LyricsError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("HTTP error: {0}")]
+    Http(#[from] reqwest::Error),
+
+    #[error("Backend error: {0}")]
+    Backend(#[from] BackendError),
+
+    #[error("JSON parsing error: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// The song lyrics are wanted for. Built on the main thread from the
+/// `SongModel` so it can be sent to the runtime.
+#[derive(Debug, Clone, Default)]
+pub struct LyricsQuery {
+    pub item_id: String,
+    pub title: String,
+    pub artists: Vec<String>,
+    pub album: String,
+    pub duration_seconds: u64,
+    /// Whether the server says it has lyrics. Subsonic always says yes.
+    pub backend_has_lyrics: bool,
+}
+
+impl LyricsQuery {
+    pub fn from_song(song: &SongModel) -> Self {
+        Self {
+            item_id: song.id(),
+            title: song.title(),
+            artists: song.artists(),
+            album: song.album(),
+            duration_seconds: song.duration_seconds(),
+            backend_has_lyrics: song.has_lyrics(),
+        }
+    }
+}
+
+/// Which providers after the backend are turned on
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LyricsOptions {
+    pub folder: Option<PathBuf>,
+    pub online_url: Option<String>,
+}
+
+impl LyricsOptions {
+    pub fn from_settings() -> Self {
+        let folder = config::get_lyrics_folder();
+        let online_url = config::get_lyrics_provider_url();
+        Self {
+            folder: (!folder.is_empty()).then(|| PathBuf::from(folder)),
+            online_url: (config::get_lyrics_online_enabled() && !online_url.is_empty())
+                .then_some(online_url),
+        }
+    }
+
+    /// Lyrics might be found even if the server has none
+    pub fn has_fallbacks(&self) -> bool {
+        self.folder.is_some() || self.online_url.is_some()
+    }
+}
+
+/// Runs the provider chain, returning empty lyrics if nobody has any.
+pub async fn fetch(
+    backend: &Backend,
+    query: &LyricsQuery,
+    options: &LyricsOptions,
+) -> LyricsResponse {
+    let cache = LyricsCache::new().inspect_err(|e| warn!("Lyrics cache unavailable: {}", e));
+    if let Ok(cache) = &cache
+        && let Some(lyrics) = cache.load(&query.item_id)
+    {
+        return lyrics;
+    }
+
+    let lyrics = fetch_uncached(backend, query, options).await;
+    if !lyrics.lyrics.is_empty()
+        && let Ok(cache) = &cache
+        && let Err(e) = cache.save(&query.item_id, &lyrics)
+    {
+        warn!("Failed to cache lyrics for {}: {}", query.item_id, e);
+    }
+    lyrics
+}
+
+async fn fetch_uncached(
+    backend: &Backend,
+    query: &LyricsQuery,
+    options: &LyricsOptions,
+) -> LyricsResponse {
+    if query.backend_has_lyrics {
+        match backend.fetch_lyrics(&query.item_id).await {
+            Ok(lyrics) if !lyrics.lyrics.is_empty() => return lyrics,
+            Ok(_) => debug!("Backend has no lyrics for {}", query.item_id),
+            Err(e) => debug!("Backend lyrics failed for {}: {}", query.item_id, e),
+        }
+    }
+
+    if let Some(folder) = &options.folder {
+        let folder = folder.clone();
+        let search = query.clone();
+        let found = tokio::task::spawn_blocking(move || local::find(&folder, &search))
+            .await
+            .unwrap_or_else(|e| Err(std::io::Error::other(e)));
+        match found {
+            Ok(Some(lyrics)) => return lyrics,
+            Ok(None) => debug!("No lyrics file for {}", query.title),
+            Err(e) => warn!("Failed to search lyrics folder: {}", e),
+        }
+    }
+
+    if let Some(url) = &options.online_url {
+        match lrclib::fetch(url, query).await {
+            Ok(Some(lyrics)) => return lyrics,
+            Ok(None) => debug!("{} has no lyrics for {}", url, query.title),
+            Err(e) => warn!("Failed to fetch lyrics from {}: {}", url, e),
+        }
+    }
+
+    LyricsResponse::default()
+}
+
+/// Forgets all cached lyrics so the next lookup asks the providers again
+pub fn clear_cache() {
+    if let Err(e) = LyricsCache::new().and_then(|cache| cache.clear()) {
+        warn!("Failed to clear lyrics cache: {}", e);
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LyricsCache {
+    cache_dir: PathBuf,
+}
+
+impl LyricsCache {
+    pub fn new() -> Result<Self, CacheError> {
+        let cache_dir = get_cache_directory("lyrics")?;
+        fs::create_dir_all(&cache_dir)?;
+        Ok(Self { cache_dir })
+    }
+
+    fn path(&self, item_id: &str) -> PathBuf {
+        let name: String = item_id
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric() || *c == '-')
+            .collect();
+        self.cache_dir.join(format!("{name}.json"))
+    }
+
+    pub fn load(&self, item_id: &str) -> Option<LyricsResponse> {
+        let data = fs::read(self.path(item_id)).ok()?;
+        serde_json::from_slice(&data)
+            .inspect_err(|e| warn!("Ignoring bad cached lyrics for {}: {}", item_id, e))
+            .ok()
+    }
+
+    pub fn save(&self, item_id: &str, lyrics: &LyricsResponse) -> Result<(), CacheError> {
+        fs::write(self.path(item_id), serde_json::to_vec(lyrics)?)?;
+        Ok(())
+    }
+
+    pub fn remove(&self, item_id: &str) -> Result<(), CacheError> {
+        match fs::remove_file(self.path(item_id)) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn clear(&self) -> Result<(), CacheError> {
+        fs::remove_dir_all(&self.cache_dir)?;
+        fs::create_dir_all(&self.cache_dir)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_round_trip() {
+        let cache_dir = std::env::temp_dir().join(format!("gelly-lyrics-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&cache_dir).unwrap();
+        let cache = LyricsCache {
+            cache_dir: cache_dir.clone(),
+        };
+
+        let lyrics = LyricsResponse {
+            lyrics: lrc::parse("[00:01.00]<00:01.00>One <00:01.50>two\n[00:03.00]Three\n"),
+        };
+        assert!(cache.load("abc123").is_none());
+        cache.save("abc123", &lyrics).unwrap();
+        let loaded = cache.load("abc123").unwrap();
+        assert_eq!(loaded.lyrics.len(), 2);
+        assert_eq!(loaded.lyrics[0].words, lyrics.lyrics[0].words);
+        assert!(loaded.lyrics[1].words.is_empty());
+
+        // Ids never escape the cache directory
+        assert_eq!(
+            cache.path("../../etc/passwd"),
+            cache_dir.join("etcpasswd.json")
+        );
+
+        cache.remove("abc123").unwrap();
+        cache.remove("abc123").unwrap();
+        assert!(cache.load("abc123").is_none());
+        fs::remove_dir_all(cache_dir).unwrap();
+    }
+}
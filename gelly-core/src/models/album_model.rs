@@ -1,10 +1,5 @@
-use crate::{
-    application::Application, async_utils::spawn_tokio, jellyfin::api::MusicDto,
-    models::model_traits::ItemModel,
-};
-use glib::Object;
-use gtk::{glib, subclass::prelude::*};
-use log::warn;
+use crate::{jellyfin::api::MusicDto, models::model_traits::ItemModel};
+use glib::{Object, subclass::prelude::*};
 
 glib::wrapper! {
     pub struct AlbumModel(ObjectSubclass<imp::AlbumData>);
@@ -25,6 +20,7 @@ impl AlbumModel {
     pub fn new(
         dto: &MusicDto,
         favorite: bool,
+        rating: u32,
         play_count: u64,
         genres: Vec<String>,
         last_played_date: &str,
@@ -50,8 +46,10 @@ impl AlbumModel {
             .property("artists-string", artists_string)
             .property("play-count", play_count)
             .property("favorite", favorite)
+            .property("rating", rating)
             .property("genres", genres)
             .property("cover_art", dto.effective_cover_art())
+            .property("compilation", dto.effective_is_compilation())
             .build()
     }
 
@@ -68,44 +66,10 @@ impl AlbumModel {
     pub fn image_data(&self) -> Vec<u8> {
         self.imp().image_data.borrow().clone()
     }
-
-    pub fn toggle_favorite(&self, is_favorite: bool, app: &Application) {
-        self.set_favorite(is_favorite);
-        let backend = app.backend();
-        let item_id = self.id();
-        let app = app.clone();
-        spawn_tokio(
-            async move {
-                backend
-                    .set_favorite(
-                        &item_id,
-                        &crate::jellyfin::api::ItemType::MusicAlbum,
-                        is_favorite,
-                    )
-                    .await
-            },
-            glib::clone!(
-                #[weak(rename_to = album)]
-                self,
-                #[weak]
-                app,
-                move |result| {
-                    match result {
-                        Ok(()) => app.refresh_favorites(true),
-                        Err(err) => {
-                            warn!("Failed to set favorite: {err}");
-                            album.set_favorite(!is_favorite);
-                        }
-                    }
-                }
-            ),
-        );
-    }
 }
 
 mod imp {
-    use glib::Properties;
-    use gtk::{glib, prelude::*, subclass::prelude::*};
+    use glib::{Properties, prelude::*, subclass::prelude::*};
     use std::cell::{Cell, RefCell};
 
     #[derive(Properties, Default)]
@@ -144,6 +108,10 @@ mod imp {
         #[property(get, set)]
         pub favorite: Cell<bool>,
 
+        /// Stars out of five, zero when not rated
+        #[property(get, set)]
+        pub rating: Cell<u32>,
+
         #[property(get, set)]
         pub genres: RefCell<Vec<String>>,
 
@@ -151,6 +119,9 @@ mod imp {
 
         #[property(get, set)]
         pub cover_art: RefCell<String>,
+
+        #[property(get, set)]
+        pub compilation: Cell<bool>,
     }
 
     #[glib::object_subclass]
@@ -1,11 +1,6 @@
-use glib::Object;
-use gtk::{glib, subclass::prelude::*};
-use log::warn;
+use glib::{Object, subclass::prelude::*};
 
-use crate::{
-    application::Application, async_utils::spawn_tokio, jellyfin::api::ArtistItemsDto,
-    models::model_traits::ItemModel,
-};
+use crate::{jellyfin::api::ArtistItemsDto, models::model_traits::ItemModel};
 
 glib::wrapper! {
     pub struct ArtistModel(ObjectSubclass<imp::ArtistModel>);
@@ -39,44 +34,10 @@ impl ArtistModel {
     pub fn image_data(&self) -> Vec<u8> {
         self.imp().image_data.borrow().clone()
     }
-
-    pub fn toggle_favorite(&self, is_favorite: bool, app: &Application) {
-        self.set_favorite(is_favorite);
-        let backend = app.backend();
-        let item_id = self.id();
-        let app = app.clone();
-        spawn_tokio(
-            async move {
-                backend
-                    .set_favorite(
-                        &item_id,
-                        &crate::jellyfin::api::ItemType::MusicArtist,
-                        is_favorite,
-                    )
-                    .await
-            },
-            glib::clone!(
-                #[weak(rename_to = artist)]
-                self,
-                #[weak]
-                app,
-                move |result| {
-                    match result {
-                        Ok(()) => app.refresh_favorites(true),
-                        Err(err) => {
-                            warn!("Failed to set favorite: {err}");
-                            artist.set_favorite(!is_favorite);
-                        }
-                    }
-                }
-            ),
-        );
-    }
 }
 
 mod imp {
-    use glib::Properties;
-    use gtk::{glib, prelude::*, subclass::prelude::*};
+    use glib::{Properties, prelude::*, subclass::prelude::*};
     use std::cell::{Cell, RefCell};
 
     #[derive(Properties, Default)]
@@ -0,0 +1,108 @@
+use glib::{Object, subclass::prelude::*};
+
+use crate::models::model_traits::ItemModel;
+
+/// A dimension the library can be browsed by
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Category {
+    #[default]
+    Genre,
+    Composer,
+    Decade,
+    Year,
+    Label,
+}
+
+impl Category {
+    /// In the order of their stored index, which is why years come last
+    pub const ALL: [Category; 5] = [
+        Category::Genre,
+        Category::Composer,
+        Category::Decade,
+        Category::Label,
+        Category::Year,
+    ];
+
+    /// For storing in settings
+    pub fn from_index(index: u32) -> Self {
+        Self::ALL.get(index as usize).copied().unwrap_or_default()
+    }
+
+    pub fn index(self) -> u32 {
+        Self::ALL.iter().position(|c| *c == self).unwrap_or(0) as u32
+    }
+}
+
+glib::wrapper! {
+    pub struct CategoryModel(ObjectSubclass<imp::CategoryModel>);
+}
+
+impl ItemModel for CategoryModel {
+    fn display_name(&self) -> String {
+        self.name()
+    }
+
+    /// Categories have no server id, the name is used instead
+    fn item_id(&self) -> String {
+        self.name()
+    }
+}
+
+impl CategoryModel {
+    pub fn new(
+        category: Category,
+        name: &str,
+        album_count: u32,
+        song_count: u32,
+        play_count: u64,
+        cover_art: Vec<String>,
+    ) -> Self {
+        let model: Self = Object::builder()
+            .property("name", name)
+            .property("album-count", album_count)
+            .property("song-count", song_count)
+            .property("play-count", play_count)
+            .property("cover-art", cover_art)
+            .build();
+        model.imp().category.set(category);
+        model
+    }
+
+    pub fn category(&self) -> Category {
+        self.imp().category.get()
+    }
+}
+
+mod imp {
+    use glib::{Properties, prelude::*, subclass::prelude::*};
+    use std::cell::{Cell, RefCell};
+
+    use super::Category;
+
+    #[derive(Properties, Default)]
+    #[properties(wrapper_type = super::CategoryModel)]
+    pub struct CategoryModel {
+        pub category: Cell<Category>,
+        #[property(get, set)]
+        pub name: RefCell<String>,
+        #[property(get, set, name = "album-count")]
+        pub album_count: Cell<u32>,
+        #[property(get, set, name = "song-count")]
+        pub song_count: Cell<u32>,
+        #[property(get, set, name = "play-count")]
+        pub play_count: Cell<u64>,
+        /// Cover art ids of a few albums in the category, for the mosaic
+        #[property(get, set, name = "cover-art")]
+        pub cover_art: RefCell<Vec<String>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for CategoryModel {
+        const NAME: &'static str = "GellyCategoryModel";
+        type Type = super::CategoryModel;
+        type ParentType = glib::Object;
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for CategoryModel {}
+}
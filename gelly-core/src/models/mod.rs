@@ -0,0 +1,14 @@
+pub mod album_model;
+pub mod artist_model;
+pub mod category_model;
+pub mod model_traits;
+pub mod playlist_model;
+pub mod playlist_type;
+pub mod song_model;
+
+pub use album_model::AlbumModel;
+pub use artist_model::ArtistModel;
+pub use category_model::{Category, CategoryModel};
+pub use playlist_model::PlaylistModel;
+pub use playlist_type::PlaylistType;
+pub use song_model::SongModel;
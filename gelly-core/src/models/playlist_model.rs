@@ -1,11 +1,5 @@
-use crate::{
-    application::Application,
-    async_utils::spawn_tokio,
-    models::{PlaylistType, model_traits::ItemModel},
-};
+use crate::models::{PlaylistType, model_traits::ItemModel};
 use glib::Object;
-use gtk::glib;
-use log::warn;
 
 glib::wrapper! {
     pub struct PlaylistModel(ObjectSubclass<imp::PlaylistModel>);
@@ -46,44 +40,10 @@ impl PlaylistModel {
         // For regular playlists, reconstruct from the model's properties
         PlaylistType::new_regular(self.id(), self.name(), self.child_count(), self.favorite())
     }
-
-    pub fn toggle_favorite(&self, is_favorite: bool, app: &Application) {
-        self.set_favorite(is_favorite);
-        let backend = app.backend();
-        let item_id = self.id();
-        let app = app.clone();
-        spawn_tokio(
-            async move {
-                backend
-                    .set_favorite(
-                        &item_id,
-                        &crate::jellyfin::api::ItemType::Playlist,
-                        is_favorite,
-                    )
-                    .await
-            },
-            glib::clone!(
-                #[weak(rename_to = playlist)]
-                self,
-                #[weak]
-                app,
-                move |result| {
-                    match result {
-                        Ok(()) => app.refresh_favorites(true),
-                        Err(err) => {
-                            warn!("Failed to set favorite: {err}");
-                            playlist.set_favorite(!is_favorite);
-                        }
-                    }
-                }
-            ),
-        );
-    }
 }
 
 mod imp {
-    use glib::Properties;
-    use gtk::{glib, prelude::*, subclass::prelude::*};
+    use glib::{Properties, prelude::*, subclass::prelude::*};
     use std::cell::{Cell, RefCell};
 
     #[derive(Properties, Default)]
@@ -1,3 +1,5 @@
+use std::collections::HashSet;
+
 use crate::{library::Library, models::SongModel};
 
 pub const DEFAULT_SMART_COUNT: u64 = 100;
@@ -16,6 +18,9 @@ pub enum PlaylistType {
     MostPlayed {
         count: u64,
     },
+    TopRated {
+        count: u64,
+    },
     Favorites,
 }
 
@@ -36,6 +41,7 @@ impl PlaylistType {
             PlaylistType::Regular { id, .. } => id.clone(),
             PlaylistType::ShuffleLibrary { count } => format!("smart:shuffle:{count}"),
             PlaylistType::MostPlayed { count } => format!("smart:most-played:{count}"),
+            PlaylistType::TopRated { count } => format!("smart:top-rated:{count}"),
             PlaylistType::Favorites => "smart:favorites:0".to_string(),
         }
     }
@@ -60,6 +66,13 @@ impl PlaylistType {
                     .unwrap_or(DEFAULT_SMART_COUNT);
                 Some(Self::MostPlayed { count })
             }
+            Some(&"top-rated") => {
+                let count = parts
+                    .get(2)
+                    .and_then(|s| s.parse().ok())
+                    .unwrap_or(DEFAULT_SMART_COUNT);
+                Some(Self::TopRated { count })
+            }
             Some(&"favorites") => Some(Self::Favorites),
             _ => None,
         }
@@ -70,14 +83,19 @@ impl PlaylistType {
             PlaylistType::Regular { name, .. } => name.clone(),
             PlaylistType::ShuffleLibrary { .. } => "Shuffled Songs".to_string(),
             PlaylistType::MostPlayed { count } => format!("Top {} Played Songs", count),
+            PlaylistType::TopRated { count } => format!("Top {} Rated Songs", count),
             PlaylistType::Favorites => "Favorite Mix".to_string(),
         }
     }
 
-    pub fn smart_songs(&self, library: &Library) -> Vec<SongModel> {
+    /// The songs for a smart playlist. Songs in `skipped` are usually skipped
+    /// and get left out of the generated mixes, favorites are kept regardless.
+    pub fn smart_songs(&self, library: &Library, skipped: &HashSet<String>) -> Vec<SongModel> {
         match self {
-            PlaylistType::ShuffleLibrary { count } => library.shuffle_songs(*count),
-            PlaylistType::MostPlayed { count } => library.most_played_songs(*count),
+            PlaylistType::ShuffleLibrary { count } => library.shuffle_songs(*count, skipped),
+            PlaylistType::MostPlayed { count } => library.most_played_songs(*count, skipped),
+            // Rating a song is a clearer signal than skipping it
+            PlaylistType::TopRated { count } => library.top_rated_songs(*count, &HashSet::new()),
             PlaylistType::Favorites => library.all_favorites(),
             PlaylistType::Regular { .. } => vec![],
         }
@@ -88,6 +106,7 @@ impl PlaylistType {
             PlaylistType::Regular { child_count, .. } => *child_count,
             PlaylistType::ShuffleLibrary { count } => *count,
             PlaylistType::MostPlayed { count } => *count,
+            PlaylistType::TopRated { count } => *count,
             PlaylistType::Favorites => 0,
         }
     }
@@ -96,6 +115,7 @@ impl PlaylistType {
         match self {
             PlaylistType::ShuffleLibrary { .. } => "media-playlist-shuffle-symbolic",
             PlaylistType::MostPlayed { .. } => "heart-filled-symbolic",
+            PlaylistType::TopRated { .. } => "emblem-favorite-symbolic",
             PlaylistType::Favorites => "starred-symbolic",
             _ => "audio-x-generic-symbolic",
         }
@@ -404,6 +424,20 @@ mod tests {
         assert_eq!(original, parsed);
     }
 
+    #[test]
+    fn test_roundtrip_top_rated() {
+        let original = PlaylistType::TopRated { count: 25 };
+        assert_eq!(original.to_id(), "smart:top-rated:25");
+        let parsed = PlaylistType::smart_from_id(&original.to_id()).unwrap();
+        assert_eq!(original, parsed);
+        assert_eq!(
+            PlaylistType::smart_from_id("smart:top-rated"),
+            Some(PlaylistType::TopRated {
+                count: DEFAULT_SMART_COUNT
+            })
+        );
+    }
+
     #[test]
     fn test_very_long_names_and_ids() {
         let long_id = "a".repeat(1000);
@@ -1,6 +1,7 @@
-use crate::jellyfin::api::MusicDto;
+use crate::jellyfin::api::{Chapter, MusicDto, RadioStation};
+use crate::rating::stars_from_rating;
 use glib::Object;
-use gtk::glib;
+use glib::subclass::prelude::*;
 
 glib::wrapper! {
     pub struct SongModel(ObjectSubclass<imp::SongData>);
@@ -25,10 +26,38 @@ impl SongModel {
             .property("normalization-gain", dto.normalization_gain.unwrap_or(0.0))
             .property("date-created", date_created)
             .property("favorite", favorite)
+            .property("rating", stars_from_rating(dto.user_data.rating))
             .property("genres", dto.effective_genres())
+            .property("play-count", dto.user_data.play_count)
+            .property(
+                "server-position",
+                dto.user_data.playback_position_ticks / 10_000_000,
+            )
             .build()
     }
 
+    /// A radio station as a song. The station name stays in `album` while the
+    /// stream's now playing title replaces `title`.
+    pub fn new_live(station: &RadioStation) -> Self {
+        Object::builder()
+            .property("id", &station.id)
+            .property("title", &station.name)
+            .property("artists", vec![station.name.clone()])
+            .property("artists-string", &station.name)
+            .property("album", &station.name)
+            .property("is-live", true)
+            .property("stream-url", &station.stream_url)
+            .build()
+    }
+
+    pub fn chapters(&self) -> Vec<Chapter> {
+        self.imp().chapters.borrow().clone()
+    }
+
+    pub fn set_chapters(&self, chapters: Vec<Chapter>) {
+        self.imp().chapters.replace(chapters);
+    }
+
     pub fn duration_seconds(&self) -> u64 {
         self.duration() / 10_000_000 // Jellyfin ticks
     }
@@ -51,8 +80,7 @@ impl SongModel {
 }
 
 mod imp {
-    use glib::Properties;
-    use gtk::{glib, prelude::*, subclass::prelude::*};
+    use glib::{Properties, prelude::*, subclass::prelude::*};
     use std::cell::{Cell, RefCell};
 
     #[derive(Properties, Default)]
@@ -97,8 +125,37 @@ mod imp {
         #[property(get, set)]
         pub favorite: Cell<bool>,
 
+        /// Stars out of five, zero when not rated
+        #[property(get, set)]
+        pub rating: Cell<u32>,
+
         #[property(get, set)]
         pub genres: RefCell<Vec<String>>,
+
+        #[property(get, set, name = "play-count")]
+        pub play_count: Cell<u64>,
+
+        /// Seconds in, where the server last saw us stop
+        #[property(get, set, name = "server-position")]
+        pub server_position: Cell<u64>,
+
+        /// Podcast episodes play at their own speed and remember where they were
+        #[property(get, set, name = "is-episode")]
+        pub is_episode: Cell<bool>,
+
+        /// Audiobooks get chapter navigation and stay out of shuffle
+        #[property(get, set, name = "is-audiobook")]
+        pub is_audiobook: Cell<bool>,
+
+        pub chapters: RefCell<Vec<crate::jellyfin::api::Chapter>>,
+
+        /// Radio streams have no end and can't be seeked
+        #[property(get, set, name = "is-live")]
+        pub is_live: Cell<bool>,
+
+        /// Played directly rather than asking the server for a stream
+        #[property(get, set, name = "stream-url")]
+        pub stream_url: RefCell<String>,
     }
 
     #[glib::object_subclass]
@@ -0,0 +1,344 @@
+//! Tidying playlists after the library changed under them: the same song in
+//! there more than once, and songs the server no longer has, along with the
+//! closest song still around to take their place.
+
+use std::collections::{HashMap, HashSet};
+
+use reqwest::StatusCode;
+
+use crate::{
+    backend::{Backend, BackendError},
+    jellyfin::api::MusicDto,
+    playlist_file::{
+        DURATION_TOLERANCE_SECONDS, EntryMatch, PlaylistEntry, duration_difference, fold,
+        match_entries,
+    },
+};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Problem {
+    /// The same song as the entry at `original`, further up
+    Duplicate { original: usize },
+    /// Not in the library anymore, `replacement` is the closest song that is
+    Missing { replacement: Option<EntryMatch> },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Finding {
+    /// Position in the playlist
+    pub index: usize,
+    pub problem: Problem,
+}
+
+/// Checks each playlist entry against the ones before it and against the
+/// library's `songs`. A song counts as a duplicate when it has the same id,
+/// or the same title and artist and about the same length. Titles have to
+/// match in full, so a "(Live)" or "(Remix)" is a song of its own.
+pub fn find_problems(entries: &[MusicDto], songs: &[MusicDto]) -> Vec<Finding> {
+    let library_ids: HashSet<&str> = songs.iter().map(|song| song.id.as_str()).collect();
+    let playlist_ids: HashSet<&str> = entries.iter().map(|entry| entry.id.as_str()).collect();
+    let mut by_id: HashMap<&str, usize> = HashMap::new();
+    let mut by_metadata: HashMap<(String, String), Vec<usize>> = HashMap::new();
+    let mut findings = Vec::new();
+
+    for (index, entry) in entries.iter().enumerate() {
+        let details = PlaylistEntry::from_song(entry);
+        let key = (fold(&details.title), fold(&details.artist));
+        let same_song = by_metadata.get(&key).and_then(|earlier| {
+            earlier.iter().copied().find(|&other| {
+                let other = PlaylistEntry::from_song(&entries[other]);
+                duration_difference(details.duration, other.duration) <= DURATION_TOLERANCE_SECONDS
+            })
+        });
+        let original = by_id
+            .get(entry.id.as_str())
+            .copied()
+            .or(same_song.filter(|_| !key.0.is_empty()));
+
+        if let Some(original) = original {
+            findings.push(Finding {
+                index,
+                problem: Problem::Duplicate { original },
+            });
+            continue;
+        }
+        by_id.insert(&entry.id, index);
+        by_metadata.entry(key).or_default().push(index);
+
+        if !library_ids.contains(entry.id.as_str()) {
+            // A replacement that's already in the playlist would only be
+            // another duplicate
+            let replacement = match_entries(&[details], songs)
+                .pop()
+                .flatten()
+                .filter(|found| !playlist_ids.contains(found.song_id.as_str()));
+            findings.push(Finding {
+                index,
+                problem: Problem::Missing { replacement },
+            });
+        }
+    }
+    findings
+}
+
+/// The playlist's song ids once the entries at `remove` are gone and the
+/// ones in `replace` swapped for other songs
+pub fn cleaned_ids(
+    entries: &[String],
+    remove: &HashSet<usize>,
+    replace: &HashMap<usize, String>,
+) -> Vec<String> {
+    entries
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| !remove.contains(index))
+        .map(|(index, id)| replace.get(&index).unwrap_or(id).clone())
+        .collect()
+}
+
+/// Changes the playlist on the server from what it is to `wanted`, using
+/// only removing, adding and moving songs. Fails when the server won't let
+/// go of a song rather than leave the playlist half done.
+pub async fn apply(
+    backend: &Backend,
+    playlist_id: &str,
+    wanted: &[String],
+) -> Result<(), BackendError> {
+    let mut current = playlist_ids(backend, playlist_id).await?;
+    for id in surplus(&current, wanted) {
+        // Jellyfin takes out every copy of a song at once and Subsonic only
+        // the first, so see what's left after each go
+        while count(&current, &id) > count(wanted, &id) {
+            backend.remove_playlist_item(playlist_id, &id).await?;
+            let remaining = playlist_ids(backend, playlist_id).await?;
+            if remaining.len() == current.len() {
+                return Err(BackendError::Http {
+                    status: StatusCode::CONFLICT,
+                    message: format!(
+                        "Playlist {playlist_id} still has song {id} after removing it"
+                    ),
+                });
+            }
+            current = remaining;
+        }
+    }
+
+    let missing = missing(&current, wanted);
+    if !missing.is_empty() {
+        backend.add_playlist_items(playlist_id, &missing).await?;
+        current.extend(missing);
+    }
+
+    for (id, index) in moves(&current, wanted) {
+        backend
+            .move_playlist_item(playlist_id, &id, index as i32)
+            .await?;
+    }
+    Ok(())
+}
+
+async fn playlist_ids(backend: &Backend, playlist_id: &str) -> Result<Vec<String>, BackendError> {
+    let items = backend.get_playlist_items(playlist_id).await?;
+    Ok(items.items.into_iter().map(|item| item.id).collect())
+}
+
+fn count(ids: &[String], id: &str) -> usize {
+    ids.iter().filter(|other| *other == id).count()
+}
+
+/// Songs there are more copies of than wanted
+fn surplus(current: &[String], wanted: &[String]) -> Vec<String> {
+    let mut seen = HashSet::new();
+    current
+        .iter()
+        .filter(|id| seen.insert(id.as_str()) && count(current, id) > count(wanted, id))
+        .cloned()
+        .collect()
+}
+
+/// Songs that need adding, in the order they're wanted
+fn missing(current: &[String], wanted: &[String]) -> Vec<String> {
+    let mut available: HashMap<&str, usize> = HashMap::new();
+    for id in current {
+        *available.entry(id.as_str()).or_default() += 1;
+    }
+    wanted
+        .iter()
+        .filter(|id| match available.get_mut(id.as_str()) {
+            Some(left) if *left > 0 => {
+                *left -= 1;
+                false
+            }
+            _ => true,
+        })
+        .cloned()
+        .collect()
+}
+
+/// Moves putting `current` in the order of `wanted`, each as the song and
+/// the position it goes to
+fn moves(current: &[String], wanted: &[String]) -> Vec<(String, usize)> {
+    let mut current = current.to_vec();
+    let mut moves = Vec::new();
+    for (index, id) in wanted.iter().enumerate() {
+        if current.get(index) == Some(id) {
+            continue;
+        }
+        let Some(from) = current
+            .iter()
+            .skip(index)
+            .position(|other| other == id)
+            .map(|offset| index + offset)
+        else {
+            continue;
+        };
+        let moved = current.remove(from);
+        current.insert(index, moved);
+        moves.push((id.clone(), index));
+    }
+    moves
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jellyfin::Jellyfin;
+    use crate::playlist_file::{MatchKind, tests::song};
+    use crate::test_server::serve_always;
+
+    fn ids(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(|id| id.to_string()).collect()
+    }
+
+    #[test]
+    fn test_find_problems() {
+        let library = vec![
+            song("1", "Harvest Moon", "Neil Young", "Harvest Moon", 303),
+            song("2", "Helpless", "Neil Young", "Deja Vu", 333),
+            song("3", "Old Man", "Neil Young", "Harvest", 204),
+            song("new", "Heart of Gold", "Neil Young", "Harvest", 185),
+        ];
+        let entries = vec![
+            library[0].clone(),
+            library[1].clone(),
+            library[0].clone(),
+            // Re-tagged, so there's a new id with the same song
+            song(
+                "old",
+                "Heart of Gold (Remastered)",
+                "Neil Young",
+                "Harvest",
+                186,
+            ),
+            song("gone", "Cortez the Killer", "Neil Young", "Zuma", 450),
+            // Another copy of the song under a different id
+            song("4", "harvest moon", "Neil Young", "Harvest Moon", 301),
+            song("5", "Harvest Moon", "Neil Young", "Harvest Moon", 500),
+            // A different recording, even if the length is close
+            song(
+                "live",
+                "Harvest Moon (Live)",
+                "Neil Young",
+                "Harvest Moon",
+                302,
+            ),
+        ];
+        let findings = find_problems(&entries, &library);
+        assert_eq!(
+            findings,
+            vec![
+                Finding {
+                    index: 2,
+                    problem: Problem::Duplicate { original: 0 },
+                },
+                Finding {
+                    index: 3,
+                    problem: Problem::Missing {
+                        replacement: Some(EntryMatch {
+                            song_id: "new".to_string(),
+                            kind: MatchKind::Metadata,
+                        }),
+                    },
+                },
+                Finding {
+                    index: 4,
+                    problem: Problem::Missing { replacement: None },
+                },
+                Finding {
+                    index: 5,
+                    problem: Problem::Duplicate { original: 0 },
+                },
+                // Too long to be the same recording, but it isn't in the library
+                Finding {
+                    index: 6,
+                    problem: Problem::Missing { replacement: None },
+                },
+                Finding {
+                    index: 7,
+                    problem: Problem::Missing { replacement: None },
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_replacement_already_in_playlist() {
+        let library = vec![song("new", "Heart of Gold", "Neil Young", "Harvest", 185)];
+        let entries = vec![
+            library[0].clone(),
+            song("old", "Heart of Golden", "Neil Young", "Harvest", 186),
+        ];
+        assert_eq!(
+            find_problems(&entries, &library)[0].problem,
+            Problem::Missing { replacement: None }
+        );
+    }
+
+    #[test]
+    fn test_cleaned_ids() {
+        let entries = ids(&["a", "b", "a", "dead", "c"]);
+        let remove = HashSet::from([2]);
+        let replace = HashMap::from([(3, "new".to_string())]);
+        assert_eq!(
+            cleaned_ids(&entries, &remove, &replace),
+            ["a", "b", "new", "c"]
+        );
+    }
+
+    #[test]
+    fn test_plan_changes() {
+        let wanted = ids(&["a", "b", "new", "c"]);
+        // As Jellyfin leaves it, with every copy of "a" gone
+        let current = ids(&["b", "c"]);
+        assert!(surplus(&current, &wanted).is_empty());
+        let added = missing(&current, &wanted);
+        assert_eq!(added, ["a", "new"]);
+
+        let mut current = current;
+        current.extend(added);
+        let moves = moves(&current, &wanted);
+        assert_eq!(moves, vec![("a".to_string(), 0), ("new".to_string(), 2)]);
+
+        let original = ids(&["a", "b", "a", "dead", "c"]);
+        assert_eq!(surplus(&original, &wanted), ["a", "dead"]);
+    }
+
+    #[tokio::test]
+    async fn test_apply_fails_when_removal_does_nothing() {
+        // Takes the request to remove the song but keeps it anyway
+        let host = serve_always(
+            r#"{"Items": [{"Name": "Old Man", "Id": "a", "RunTimeTicks": 0, "AlbumArtists": [],
+                "ArtistItems": [], "HasLyrics": false, "Genres": [],
+                "UserData": {"PlayCount": 0, "PlaybackPositionTicks": 0}}], "TotalRecordCount": 1}"#,
+        )
+        .await;
+        let backend = Backend::Jellyfin(Jellyfin::new(&host, "token", "me"));
+        assert!(matches!(
+            apply(&backend, "mix", &[]).await,
+            Err(BackendError::Http {
+                status: StatusCode::CONFLICT,
+                ..
+            })
+        ));
+    }
+}
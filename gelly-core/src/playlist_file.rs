@@ -0,0 +1,835 @@
+//! Playlist files, for backups and for moving playlists between servers.
+//! Each entry keeps enough about its song (title, artist, album, duration,
+//! MusicBrainz ids) to find it again in another library, and importing
+//! matches entries by id first and by metadata when the ids are foreign.
+
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use thiserror::Error;
+
+use crate::{
+    jellyfin::api::MusicDto,
+    xml::{element_text, elements, escape_xml, unescape_xml},
+};
+
+/// Our own item ids go in a meta field other players will leave alone
+const ITEM_ID_REL: &str = "https://github.com/Fingel/gelly/item-id";
+const M3U_ITEM_ID: &str = "#GELLY-ID:";
+const M3U_IDENTIFIER: &str = "#GELLY-IDENTIFIER:";
+/// Songs further apart in length than this are different recordings
+pub(crate) const DURATION_TOLERANCE_SECONDS: u64 = 10;
+/// How alike titles by the same artist have to be to count as a fuzzy match
+const FUZZY_TITLE_SIMILARITY: f64 = 0.85;
+
+#[derive(Debug, Error)]
+pub enum PlaylistFileError {
+    #[error("JSON parsing error: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Not a {0} playlist")]
+    Invalid(&'static str),
+    #[error("The playlist has no songs")]
+    Empty,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlaylistFormat {
+    M3u8,
+    Xspf,
+    Jspf,
+}
+
+impl PlaylistFormat {
+    pub const ALL: [PlaylistFormat; 3] = [
+        PlaylistFormat::M3u8,
+        PlaylistFormat::Xspf,
+        PlaylistFormat::Jspf,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            PlaylistFormat::M3u8 => "M3U8",
+            PlaylistFormat::Xspf => "XSPF",
+            PlaylistFormat::Jspf => "JSPF",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            PlaylistFormat::M3u8 => "m3u8",
+            PlaylistFormat::Xspf => "xspf",
+            PlaylistFormat::Jspf => "jspf",
+        }
+    }
+
+    /// Goes by the file extension, plain M3U and JSON included
+    pub fn from_file_name(file_name: &str) -> Option<Self> {
+        let (_, extension) = file_name.rsplit_once('.')?;
+        match extension.to_lowercase().as_str() {
+            "m3u8" | "m3u" => Some(PlaylistFormat::M3u8),
+            "xspf" => Some(PlaylistFormat::Xspf),
+            "jspf" | "json" => Some(PlaylistFormat::Jspf),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PlaylistEntry {
+    pub title: String,
+    pub artist: String,
+    pub album: String,
+    /// Seconds, zero when not known
+    pub duration: u64,
+    /// The song's id on the server the playlist was exported from
+    pub item_id: Option<String>,
+    /// MusicBrainz recording and track urls
+    pub identifiers: Vec<String>,
+    /// The file's path on the server, or wherever another player's file
+    /// pointed
+    pub location: Option<String>,
+}
+
+impl PlaylistEntry {
+    pub fn from_song(dto: &MusicDto) -> Self {
+        let artist = if dto.artist_items.is_empty() {
+            &dto.album_artists
+        } else {
+            &dto.artist_items
+        };
+        Self {
+            title: dto.name.clone(),
+            artist: artist
+                .iter()
+                .map(|artist| artist.name.as_str())
+                .collect::<Vec<_>>()
+                .join(", "),
+            album: dto.album.clone().unwrap_or_default(),
+            duration: dto.run_time_ticks / 10_000_000,
+            item_id: Some(dto.id.clone()),
+            identifiers: dto.musicbrainz_urls(),
+            location: dto.path.clone(),
+        }
+    }
+
+    /// "Artist - Title", or just the title when the artist isn't known
+    pub fn display_name(&self) -> String {
+        if self.artist.is_empty() {
+            self.title.clone()
+        } else {
+            format!("{} - {}", self.artist, self.title)
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PlaylistFile {
+    pub name: String,
+    pub entries: Vec<PlaylistEntry>,
+}
+
+impl PlaylistFile {
+    pub fn export(&self, format: PlaylistFormat) -> Result<String, PlaylistFileError> {
+        match format {
+            PlaylistFormat::M3u8 => Ok(self.to_m3u8()),
+            PlaylistFormat::Xspf => Ok(self.to_xspf()),
+            PlaylistFormat::Jspf => self.to_jspf(),
+        }
+    }
+
+    pub fn parse(contents: &str, format: PlaylistFormat) -> Result<Self, PlaylistFileError> {
+        let playlist = match format {
+            PlaylistFormat::M3u8 => Self::from_m3u8(contents),
+            PlaylistFormat::Xspf => Self::from_xspf(contents)?,
+            PlaylistFormat::Jspf => Self::from_jspf(contents)?,
+        };
+        if playlist.entries.is_empty() {
+            return Err(PlaylistFileError::Empty);
+        }
+        Ok(playlist)
+    }
+
+    /// Each entry's location is the file's path on the server, or "Artist -
+    /// Title" when the server didn't say. The rest goes in comment lines.
+    fn to_m3u8(&self) -> String {
+        let mut m3u = String::from("#EXTM3U\n");
+        m3u.push_str(&format!("#PLAYLIST:{}\n", one_line(&self.name)));
+        for entry in &self.entries {
+            m3u.push_str(&format!(
+                "#EXTINF:{},{}\n",
+                entry.duration,
+                one_line(&entry.display_name())
+            ));
+            if !entry.album.is_empty() {
+                m3u.push_str(&format!("#EXTALB:{}\n", one_line(&entry.album)));
+            }
+            if let Some(item_id) = &entry.item_id {
+                m3u.push_str(&format!("{M3U_ITEM_ID}{item_id}\n"));
+            }
+            for identifier in &entry.identifiers {
+                m3u.push_str(&format!("{M3U_IDENTIFIER}{identifier}\n"));
+            }
+            let location = entry
+                .location
+                .clone()
+                .unwrap_or_else(|| entry.display_name());
+            m3u.push_str(&one_line(&location));
+            m3u.push('\n');
+        }
+        m3u
+    }
+
+    /// Takes `#EXTINF` and our own comments when they're there, and makes
+    /// what it can of the location line otherwise
+    fn from_m3u8(contents: &str) -> Self {
+        let mut playlist = Self::default();
+        let mut entry = PlaylistEntry::default();
+        for line in contents.lines().map(str::trim) {
+            if let Some(name) = line.strip_prefix("#PLAYLIST:") {
+                playlist.name = name.trim().to_string();
+            } else if let Some(info) = line.strip_prefix("#EXTINF:") {
+                let (duration, name) = info.split_once(',').unwrap_or((info, ""));
+                // Durations may be fractional and -1 means unknown
+                entry.duration = duration
+                    .split_whitespace()
+                    .next()
+                    .and_then(|duration| duration.parse::<f64>().ok())
+                    .filter(|duration| *duration > 0.0)
+                    .map_or(0, |duration| duration.round() as u64);
+                (entry.artist, entry.title) = split_display_name(name.trim());
+            } else if let Some(album) = line.strip_prefix("#EXTALB:") {
+                entry.album = album.trim().to_string();
+            } else if let Some(item_id) = line.strip_prefix(M3U_ITEM_ID) {
+                entry.item_id = Some(item_id.trim().to_string());
+            } else if let Some(identifier) = line.strip_prefix(M3U_IDENTIFIER) {
+                entry.identifiers.push(identifier.trim().to_string());
+            } else if !line.is_empty() && !line.starts_with('#') {
+                if entry.title.is_empty() {
+                    fill_from_location(&mut entry, line);
+                }
+                // Our own stand-in for a location isn't one
+                if line != entry.display_name() {
+                    entry.location = Some(line.to_string());
+                }
+                playlist.entries.push(std::mem::take(&mut entry));
+            }
+        }
+        playlist
+    }
+
+    fn to_xspf(&self) -> String {
+        let mut xspf = String::from(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<playlist version=\"1\" xmlns=\"http://xspf.org/ns/0/\">\n",
+        );
+        xspf.push_str(&format!("  <title>{}</title>\n", escape_xml(&self.name)));
+        xspf.push_str("  <trackList>\n");
+        for entry in &self.entries {
+            xspf.push_str("    <track>\n");
+            for identifier in &entry.identifiers {
+                xspf.push_str(&format!(
+                    "      <identifier>{}</identifier>\n",
+                    escape_xml(identifier)
+                ));
+            }
+            for (element, value) in [
+                ("title", &entry.title),
+                ("creator", &entry.artist),
+                ("album", &entry.album),
+            ] {
+                if !value.is_empty() {
+                    xspf.push_str(&format!(
+                        "      <{element}>{}</{element}>\n",
+                        escape_xml(value)
+                    ));
+                }
+            }
+            if entry.duration > 0 {
+                xspf.push_str(&format!(
+                    "      <duration>{}</duration>\n",
+                    entry.duration * 1000
+                ));
+            }
+            if let Some(item_id) = &entry.item_id {
+                xspf.push_str(&format!(
+                    "      <meta rel=\"{ITEM_ID_REL}\">{}</meta>\n",
+                    escape_xml(item_id)
+                ));
+            }
+            xspf.push_str("    </track>\n");
+        }
+        xspf.push_str("  </trackList>\n</playlist>\n");
+        xspf
+    }
+
+    fn from_xspf(contents: &str) -> Result<Self, PlaylistFileError> {
+        let Some(track_list) = elements(contents, "trackList").into_iter().next() else {
+            return Err(PlaylistFileError::Invalid("XSPF"));
+        };
+        // The playlist's own title comes before the tracks, which have theirs
+        let header = contents.split("trackList").next().unwrap_or_default();
+        let entries = elements(track_list, "track")
+            .into_iter()
+            .map(|track| {
+                let mut entry = PlaylistEntry {
+                    title: element_text(track, "title").unwrap_or_default(),
+                    artist: element_text(track, "creator").unwrap_or_default(),
+                    album: element_text(track, "album").unwrap_or_default(),
+                    duration: element_text(track, "duration")
+                        .and_then(|duration| duration.parse::<u64>().ok())
+                        .map_or(0, |millis| (millis + 500) / 1000),
+                    item_id: xspf_meta(track, ITEM_ID_REL),
+                    identifiers: elements(track, "identifier")
+                        .into_iter()
+                        .map(|identifier| unescape_xml(identifier.trim()))
+                        .collect(),
+                    location: element_text(track, "location"),
+                };
+                if entry.title.is_empty()
+                    && let Some(location) = entry.location.clone()
+                {
+                    fill_from_location(&mut entry, &location);
+                }
+                entry
+            })
+            .collect();
+        Ok(Self {
+            name: element_text(header, "title").unwrap_or_default(),
+            entries,
+        })
+    }
+
+    fn to_jspf(&self) -> Result<String, PlaylistFileError> {
+        let tracks: Vec<JspfTrack> = self
+            .entries
+            .iter()
+            .map(|entry| JspfTrack {
+                title: entry.title.clone(),
+                creator: entry.artist.clone(),
+                album: entry.album.clone(),
+                duration: (entry.duration > 0).then_some(entry.duration * 1000),
+                identifier: entry.identifiers.clone(),
+                location: vec![],
+                meta: entry
+                    .item_id
+                    .iter()
+                    .map(|item_id| HashMap::from([(ITEM_ID_REL.to_string(), item_id.clone())]))
+                    .collect(),
+            })
+            .collect();
+        let jspf = Jspf {
+            playlist: JspfPlaylist {
+                title: self.name.clone(),
+                track: tracks,
+            },
+        };
+        Ok(serde_json::to_string_pretty(&jspf)?)
+    }
+
+    fn from_jspf(contents: &str) -> Result<Self, PlaylistFileError> {
+        let jspf: Jspf = serde_json::from_str(contents)?;
+        let entries = jspf
+            .playlist
+            .track
+            .into_iter()
+            .map(|track| {
+                let mut entry = PlaylistEntry {
+                    title: track.title,
+                    artist: track.creator,
+                    album: track.album,
+                    duration: track.duration.map_or(0, |millis| (millis + 500) / 1000),
+                    item_id: track
+                        .meta
+                        .iter()
+                        .find_map(|meta| meta.get(ITEM_ID_REL).cloned()),
+                    identifiers: track.identifier,
+                    location: track.location.into_iter().next(),
+                };
+                if entry.title.is_empty()
+                    && let Some(location) = entry.location.clone()
+                {
+                    fill_from_location(&mut entry, &location);
+                }
+                entry
+            })
+            .collect();
+        Ok(Self {
+            name: jspf.playlist.title,
+            entries,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Jspf {
+    playlist: JspfPlaylist,
+}
+
+#[derive(Serialize, Deserialize)]
+struct JspfPlaylist {
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    track: Vec<JspfTrack>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct JspfTrack {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    title: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    creator: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    album: String,
+    /// Milliseconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    duration: Option<u64>,
+    #[serde(
+        default,
+        deserialize_with = "one_or_many",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    identifier: Vec<String>,
+    #[serde(
+        default,
+        deserialize_with = "one_or_many",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    location: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    meta: Vec<HashMap<String, String>>,
+}
+
+/// Some JSPF writers give a single identifier or location as a plain string
+fn one_or_many<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Ok(match Value::deserialize(deserializer)? {
+        Value::String(value) => vec![value],
+        Value::Array(values) => values
+            .into_iter()
+            .filter_map(|value| value.as_str().map(str::to_string))
+            .collect(),
+        _ => vec![],
+    })
+}
+
+fn xspf_meta(track: &str, rel: &str) -> Option<String> {
+    let start = track.find(&format!("<meta rel=\"{rel}\">"))?;
+    let value = &track[start..];
+    let value = &value[value.find('>')? + 1..];
+    Some(unescape_xml(value[..value.find("</meta>")?].trim()))
+}
+
+fn one_line(text: &str) -> String {
+    text.replace(['\r', '\n'], " ")
+}
+
+fn split_display_name(name: &str) -> (String, String) {
+    match name.split_once(" - ") {
+        Some((artist, title)) => (artist.trim().to_string(), title.trim().to_string()),
+        None => (String::new(), name.to_string()),
+    }
+}
+
+/// Other players write file paths, which are usually laid out as
+/// "Artist/Album/01 Title.ext", or failing that "Artist - Title.ext"
+fn fill_from_location(entry: &mut PlaylistEntry, location: &str) {
+    let location = location.strip_prefix("file://").unwrap_or(location);
+    let mut parts = location.rsplit(['/', '\\']).filter(|part| !part.is_empty());
+    let file_name = parts.next().unwrap_or_default();
+    let stem = match file_name.rsplit_once('.') {
+        Some((stem, extension)) if extension.len() <= 4 && !extension.contains(' ') => stem,
+        _ => file_name,
+    };
+    let (artist, title) = split_display_name(stem);
+    if !artist.is_empty() && !artist.chars().all(|c| c.is_ascii_digit()) {
+        (entry.artist, entry.title) = (artist, title);
+        return;
+    }
+    entry.title = title
+        .trim_start_matches(|c: char| c.is_ascii_digit() || c == '.' || c == '-' || c == ' ')
+        .to_string();
+    if entry.title.is_empty() {
+        entry.title = stem.to_string();
+    }
+    if let (Some(album), Some(artist)) = (parts.next(), parts.next()) {
+        entry.album = album.to_string();
+        entry.artist = artist.to_string();
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MatchKind {
+    /// The same server, so the same song
+    ItemId,
+    MusicBrainz,
+    /// Same title and artist once case, punctuation and "(Remastered)" and
+    /// the like are left out
+    Metadata,
+    /// A close enough title by the same artist
+    Fuzzy,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EntryMatch {
+    pub song_id: String,
+    pub kind: MatchKind,
+}
+
+/// Finds each entry's song in `songs`, `None` where nothing is close enough
+pub fn match_entries(entries: &[PlaylistEntry], songs: &[MusicDto]) -> Vec<Option<EntryMatch>> {
+    let ids: HashSet<&str> = songs.iter().map(|song| song.id.as_str()).collect();
+    let mut by_identifier: HashMap<String, &MusicDto> = HashMap::new();
+    let mut by_metadata: HashMap<(String, String), Vec<&MusicDto>> = HashMap::new();
+    let mut by_artist: HashMap<String, Vec<&MusicDto>> = HashMap::new();
+    for song in songs {
+        for identifier in song.musicbrainz_urls() {
+            by_identifier.entry(identifier).or_insert(song);
+        }
+        let title = normalize(&song.name);
+        for artist in song_artists(song) {
+            by_metadata
+                .entry((title.clone(), artist.clone()))
+                .or_default()
+                .push(song);
+            by_artist.entry(artist).or_default().push(song);
+        }
+    }
+
+    entries
+        .iter()
+        .map(|entry| {
+            let found = |song: &MusicDto, kind| {
+                Some(EntryMatch {
+                    song_id: song.id.clone(),
+                    kind,
+                })
+            };
+            if let Some(item_id) = &entry.item_id
+                && ids.contains(item_id.as_str())
+            {
+                return Some(EntryMatch {
+                    song_id: item_id.clone(),
+                    kind: MatchKind::ItemId,
+                });
+            }
+            if let Some(song) = entry
+                .identifiers
+                .iter()
+                .find_map(|identifier| by_identifier.get(identifier))
+            {
+                return found(song, MatchKind::MusicBrainz);
+            }
+
+            let title = normalize(&entry.title);
+            let artists = entry_artists(entry);
+            let candidates = artists
+                .iter()
+                .filter_map(|artist| by_metadata.get(&(title.clone(), artist.clone())))
+                .flatten()
+                .copied();
+            if let Some(song) = closest(entry, candidates) {
+                return found(song, MatchKind::Metadata);
+            }
+
+            let candidates = artists
+                .iter()
+                .filter_map(|artist| by_artist.get(artist))
+                .flatten()
+                .copied()
+                .filter(|song| {
+                    similarity(&title, &normalize(&song.name)) >= FUZZY_TITLE_SIMILARITY
+                });
+            closest(entry, candidates).and_then(|song| found(song, MatchKind::Fuzzy))
+        })
+        .collect()
+}
+
+/// The candidate nearest in length, preferring the same album. Anything
+/// too far off in length is a different recording.
+fn closest<'a>(
+    entry: &PlaylistEntry,
+    candidates: impl Iterator<Item = &'a MusicDto>,
+) -> Option<&'a MusicDto> {
+    let album = normalize(&entry.album);
+    candidates
+        .filter_map(|song| {
+            let difference = duration_difference(entry.duration, song.run_time_ticks / 10_000_000);
+            (difference <= DURATION_TOLERANCE_SECONDS).then_some((song, difference))
+        })
+        .min_by_key(|(song, difference)| {
+            let other_album =
+                album.is_empty() || normalize(song.album.as_deref().unwrap_or_default()) != album;
+            (other_album, *difference)
+        })
+        .map(|(song, _)| song)
+}
+
+/// Unknown lengths are taken to match
+pub(crate) fn duration_difference(a: u64, b: u64) -> u64 {
+    if a == 0 || b == 0 { 0 } else { a.abs_diff(b) }
+}
+
+fn song_artists(song: &MusicDto) -> Vec<String> {
+    song.artist_items
+        .iter()
+        .chain(&song.album_artists)
+        .map(|artist| normalize(&artist.name))
+        .filter(|artist| !artist.is_empty())
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+/// Exports join several artists with commas, other players often use "&"
+/// or "feat."
+fn entry_artists(entry: &PlaylistEntry) -> Vec<String> {
+    let mut artists = vec![normalize(&entry.artist)];
+    let lowercase = entry.artist.to_lowercase();
+    for separator in [",", " & ", ";", " feat. ", " ft. ", " featuring "] {
+        artists.extend(
+            lowercase
+                .split(separator)
+                .map(normalize)
+                .filter(|artist| !artist.is_empty()),
+        );
+    }
+    artists.sort();
+    artists.dedup();
+    artists
+}
+
+/// Lowercase letters and digits with single spaces, leaving out anything in
+/// brackets such as "(Remastered 2011)" or "[Live]"
+pub(crate) fn normalize(text: &str) -> String {
+    simplify(text, true)
+}
+
+/// Lowercase letters and digits with single spaces, brackets and all
+pub(crate) fn fold(text: &str) -> String {
+    simplify(text, false)
+}
+
+fn simplify(text: &str, skip_brackets: bool) -> String {
+    let mut normalized = String::new();
+    let mut depth = 0usize;
+    for c in text.to_lowercase().chars() {
+        match c {
+            '(' | '[' if skip_brackets => depth += 1,
+            ')' | ']' if skip_brackets => depth = depth.saturating_sub(1),
+            _ if depth > 0 => {}
+            c if c.is_alphanumeric() => normalized.push(c),
+            _ if !normalized.ends_with(' ') => normalized.push(' '),
+            _ => {}
+        }
+    }
+    normalized.trim().to_string()
+}
+
+/// One minus the edit distance over the longer length
+fn similarity(a: &str, b: &str) -> f64 {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let longest = a.len().max(b.len());
+    if longest == 0 {
+        return 1.0;
+    }
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.iter().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != b_char);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    1.0 - previous[b.len()] as f64 / longest as f64
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::jellyfin::api::{ArtistItemsDto, UserDataDto};
+
+    pub(crate) fn song(id: &str, title: &str, artist: &str, album: &str, seconds: u64) -> MusicDto {
+        let artist = ArtistItemsDto {
+            name: artist.to_string(),
+            id: format!("artist-{artist}"),
+        };
+        MusicDto {
+            name: title.to_string(),
+            id: id.to_string(),
+            date_created: None,
+            run_time_ticks: seconds * 10_000_000,
+            album: Some(album.to_string()),
+            album_artists: vec![artist.clone()],
+            artist_items: vec![artist],
+            album_id: None,
+            normalization_gain: None,
+            production_year: None,
+            index_number: None,
+            parent_index_number: None,
+            user_data: UserDataDto {
+                play_count: 0,
+                last_played_date: None,
+                playback_position_ticks: 0,
+                rating: None,
+            },
+            has_lyrics: false,
+            genres: vec![],
+            cover_art: None,
+            is_compilation: false,
+            people: vec![],
+            studios: vec![],
+            provider_ids: HashMap::new(),
+            path: None,
+            item_type: None,
+        }
+    }
+
+    fn sample() -> PlaylistFile {
+        let mut tagged = song("1", "Fish & Chips", "Band", "Lunch <Live>", 201);
+        tagged
+            .provider_ids
+            .insert("MusicBrainzRecording".to_string(), "mbid".to_string());
+        tagged.path = Some("/music/Band/Lunch/01 Fish & Chips.flac".to_string());
+        PlaylistFile {
+            name: "Road Trip".to_string(),
+            entries: vec![
+                PlaylistEntry::from_song(&tagged),
+                PlaylistEntry {
+                    title: "Untagged".to_string(),
+                    ..Default::default()
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let playlist = sample();
+        assert_eq!(
+            playlist.entries[0].identifiers,
+            ["https://musicbrainz.org/recording/mbid"]
+        );
+        for format in PlaylistFormat::ALL {
+            let contents = playlist.export(format).unwrap();
+            let parsed = PlaylistFile::parse(&contents, format).unwrap();
+            // Only M3U has somewhere for a plain path to go
+            let mut expected = playlist.clone();
+            if format != PlaylistFormat::M3u8 {
+                expected.entries[0].location = None;
+            }
+            assert_eq!(parsed, expected, "{}", format.name());
+        }
+        let m3u = playlist.export(PlaylistFormat::M3u8).unwrap();
+        assert!(m3u.contains("\n/music/Band/Lunch/01 Fish & Chips.flac\n"));
+        assert!(m3u.contains("\nUntagged\n"));
+    }
+
+    #[test]
+    fn test_other_players_files() {
+        let m3u = "#EXTM3U\n#EXTINF:-1,Someone - Tune\nhttp://example.com/a.mp3\n\
+                   /music/Artist/Album/03 - Song Title.flac\nC:\\Music\\Other - Thing.mp3\n";
+        let playlist = PlaylistFile::parse(m3u, PlaylistFormat::M3u8).unwrap();
+        let names: Vec<(&str, &str, u64)> = playlist
+            .entries
+            .iter()
+            .map(|entry| (entry.artist.as_str(), entry.title.as_str(), entry.duration))
+            .collect();
+        assert_eq!(
+            names,
+            [
+                ("Someone", "Tune", 0),
+                ("Artist", "Song Title", 0),
+                ("Other", "Thing", 0)
+            ]
+        );
+        assert_eq!(playlist.entries[1].album, "Album");
+
+        let jspf = r#"{"playlist": {"title": "LB", "track": [
+            {"title": "One", "creator": "A", "duration": 1499,
+             "identifier": "https://musicbrainz.org/recording/x"}]}}"#;
+        let playlist = PlaylistFile::parse(jspf, PlaylistFormat::Jspf).unwrap();
+        assert_eq!(playlist.name, "LB");
+        assert_eq!(playlist.entries[0].duration, 1);
+        assert_eq!(
+            playlist.entries[0].identifiers,
+            ["https://musicbrainz.org/recording/x"]
+        );
+
+        assert!(matches!(
+            PlaylistFile::parse("#EXTM3U\n", PlaylistFormat::M3u8),
+            Err(PlaylistFileError::Empty)
+        ));
+        assert!(PlaylistFile::parse("<html/>", PlaylistFormat::Xspf).is_err());
+    }
+
+    #[test]
+    fn test_matching() {
+        let mut tagged = song("tagged", "Tagged", "X", "A", 100);
+        tagged
+            .provider_ids
+            .insert("MusicBrainzRecording".to_string(), "mbid".to_string());
+        let songs = vec![
+            song("1", "Wonderwall", "Oasis", "Morning Glory", 258),
+            song("2", "Wonderwall", "Oasis", "Live Forever", 300),
+            song("3", "Champagne Supernova", "Oasis", "Morning Glory", 451),
+            song("4", "Wonderwall", "Ryan Adams", "Live", 250),
+            tagged,
+        ];
+        let entry = |title: &str, artist: &str, duration| PlaylistEntry {
+            title: title.to_string(),
+            artist: artist.to_string(),
+            duration,
+            ..Default::default()
+        };
+        let entries = vec![
+            PlaylistEntry {
+                item_id: Some("3".to_string()),
+                ..entry("Something Else", "", 0)
+            },
+            PlaylistEntry {
+                identifiers: vec!["https://musicbrainz.org/recording/mbid".to_string()],
+                ..entry("Renamed", "Y", 0)
+            },
+            entry("WONDERWALL (Remastered)", "oasis", 259),
+            entry("Wonderwall", "Oasis", 298),
+            entry("Champagne Supanova", "Oasis & Friends", 450),
+            entry("Wonderwall", "Oasis", 200),
+            entry("Don't Look Back in Anger", "Oasis", 0),
+        ];
+        let matches: Vec<Option<(String, MatchKind)>> = match_entries(&entries, &songs)
+            .into_iter()
+            .map(|found| found.map(|found| (found.song_id, found.kind)))
+            .collect();
+        let expected = [
+            Some(("3", MatchKind::ItemId)),
+            Some(("tagged", MatchKind::MusicBrainz)),
+            Some(("1", MatchKind::Metadata)),
+            Some(("2", MatchKind::Metadata)),
+            Some(("3", MatchKind::Fuzzy)),
+            None,
+            None,
+        ]
+        .map(|found| found.map(|(id, kind)| (id.to_string(), kind)));
+        assert_eq!(matches, expected);
+    }
+
+    #[test]
+    fn test_normalize_and_similarity() {
+        assert_eq!(
+            normalize("  Don't  Stop (2011 Remaster) [Live]! "),
+            "don t stop"
+        );
+        assert_eq!(
+            fold("  Don't  Stop (2011 Remaster) [Live]! "),
+            "don t stop 2011 remaster live"
+        );
+        assert_eq!(similarity("abc", "abc"), 1.0);
+        assert!(similarity("supernova", "supanova") > 0.7);
+        assert_eq!(similarity("", "abc"), 0.0);
+    }
+}
@@ -0,0 +1,114 @@
+//! Folders for grouping playlists. Servers have no such thing, so they're
+//! kept on this computer, keyed by playlist id.
+
+use std::collections::{BTreeSet, HashMap};
+
+use crate::cache::CacheError;
+use crate::store::StoreFile;
+
+const STATE_FILE_NAME: &str = "folders.json";
+
+/// A folder exists for as long as a playlist is filed in it
+#[derive(Debug, Default)]
+pub struct PlaylistFolders {
+    file: StoreFile,
+    folders: HashMap<String, String>,
+}
+
+impl PlaylistFolders {
+    pub fn load() -> Self {
+        let file = StoreFile::new("playlists", STATE_FILE_NAME);
+        Self {
+            folders: file.load(),
+            file,
+        }
+    }
+
+    pub fn save(&self) -> impl Future<Output = Result<(), CacheError>> + Send + use<> {
+        self.file.save(&self.folders)
+    }
+
+    pub fn save_now(&self) -> Result<(), CacheError> {
+        self.file.save_now(&self.folders)
+    }
+
+    /// Every folder name, sorted
+    pub fn folders(&self) -> Vec<String> {
+        self.folders
+            .values()
+            .cloned()
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect()
+    }
+
+    pub fn folder(&self, playlist_id: &str) -> Option<&str> {
+        self.folders.get(playlist_id).map(String::as_str)
+    }
+
+    /// A blank folder takes the playlist out of its folder
+    pub fn set_folder(&mut self, playlist_id: &str, folder: &str) {
+        let folder = folder.trim();
+        if folder.is_empty() {
+            self.folders.remove(playlist_id);
+        } else {
+            self.folders
+                .insert(playlist_id.to_string(), folder.to_string());
+        }
+    }
+
+    /// Moves everything in `from` to `to`, merging them when `to` exists
+    pub fn rename_folder(&mut self, from: &str, to: &str) {
+        let to = to.trim();
+        if to.is_empty() {
+            return;
+        }
+        for folder in self.folders.values_mut() {
+            if folder == from {
+                *folder = to.to_string();
+            }
+        }
+    }
+
+    /// Drops playlists that aren't in `playlist_ids`, after they're deleted
+    pub fn retain<'a>(&mut self, playlist_ids: impl IntoIterator<Item = &'a str>) {
+        let keep: BTreeSet<&str> = playlist_ids.into_iter().collect();
+        self.folders.retain(|id, _| keep.contains(id.as_str()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_folders() {
+        let mut store = PlaylistFolders::default();
+        store.set_folder("a", " Running ");
+        store.set_folder("b", "Focus");
+        store.set_folder("c", "Running");
+        assert_eq!(store.folder("a"), Some("Running"));
+        assert_eq!(store.folders(), ["Focus", "Running"]);
+
+        store.set_folder("b", "");
+        assert_eq!(store.folder("b"), None);
+        assert_eq!(store.folders(), ["Running"]);
+    }
+
+    #[test]
+    fn test_rename_and_retain() {
+        let mut store = PlaylistFolders::default();
+        store.set_folder("a", "Running");
+        store.set_folder("b", "Jogging");
+        store.set_folder("c", "Focus");
+
+        store.rename_folder("Jogging", "Running");
+        assert_eq!(store.folder("b"), Some("Running"));
+        store.rename_folder("Focus", "  ");
+        assert_eq!(store.folder("c"), Some("Focus"));
+
+        store.retain(["a", "c"]);
+        assert_eq!(store.folder("b"), None);
+        assert_eq!(store.folders(), ["Focus", "Running"]);
+    }
+}
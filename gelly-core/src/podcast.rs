@@ -0,0 +1,190 @@
+//! What we keep about podcast episodes on this computer: how far into each one
+//! we got, which have been played, and copies downloaded for offline
+//! listening. Episodes are keyed by the id they're streamed with.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use tokio::io::AsyncWriteExt;
+
+use crate::backend::BackendError;
+use crate::cache::CacheError;
+use crate::store::StoreFile;
+
+/// Episodes this close to the end count as played, it's usually the outro
+const FINISHED_MARGIN_SECONDS: u64 = 30;
+const STATE_FILE_NAME: &str = "episodes.json";
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct EpisodeState {
+    /// Seconds in, zero once played
+    #[serde(default)]
+    pub position: u64,
+    #[serde(default)]
+    pub played: bool,
+    /// File name in the downloads folder
+    #[serde(default)]
+    pub download: Option<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct PodcastStore {
+    file: StoreFile,
+    episodes: HashMap<String, EpisodeState>,
+}
+
+impl PodcastStore {
+    pub fn load() -> Self {
+        let file = StoreFile::new("podcasts", STATE_FILE_NAME);
+        Self {
+            episodes: file.load(),
+            file,
+        }
+    }
+
+    pub fn save(&self) -> impl Future<Output = Result<(), CacheError>> + Send + use<> {
+        self.file.save(&self.episodes)
+    }
+
+    pub fn save_now(&self) -> Result<(), CacheError> {
+        self.file.save_now(&self.episodes)
+    }
+
+    pub fn state(&self, id: &str) -> EpisodeState {
+        self.episodes.get(id).cloned().unwrap_or_default()
+    }
+
+    /// Whether anything has been recorded for the episode here. If not, what
+    /// the server has is all there is to go on.
+    pub fn contains(&self, id: &str) -> bool {
+        self.episodes.contains_key(id)
+    }
+
+    /// Records how far in we are, marking the episode played near the end.
+    /// Returns true when this finished it.
+    pub fn set_position(&mut self, id: &str, position: u64, duration: u64) -> bool {
+        let state = self.episodes.entry(id.to_string()).or_default();
+        if duration > 0 && position + FINISHED_MARGIN_SECONDS >= duration {
+            let finished = !state.played;
+            state.played = true;
+            state.position = 0;
+            return finished;
+        }
+        state.position = position;
+        false
+    }
+
+    pub fn set_played(&mut self, id: &str, played: bool) {
+        let state = self.episodes.entry(id.to_string()).or_default();
+        state.played = played;
+        state.position = 0;
+    }
+
+    /// Where a new download of the episode goes
+    pub fn download_path(&self, id: &str) -> Option<PathBuf> {
+        let name: String = id
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        self.file.dir().map(|dir| dir.join("downloads").join(name))
+    }
+
+    pub fn downloaded_file(&self, id: &str) -> Option<PathBuf> {
+        let name = self.episodes.get(id)?.download.as_ref()?;
+        let path = self.file.dir()?.join("downloads").join(name);
+        path.exists().then_some(path)
+    }
+
+    pub fn set_downloaded(&mut self, id: &str, path: &std::path::Path) {
+        let state = self.episodes.entry(id.to_string()).or_default();
+        state.download = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned());
+    }
+
+    pub fn remove_download(&mut self, id: &str) -> Result<(), CacheError> {
+        if let Some(path) = self.downloaded_file(id) {
+            fs::remove_file(path)?;
+        }
+        if let Some(state) = self.episodes.get_mut(id) {
+            state.download = None;
+        }
+        Ok(())
+    }
+}
+
+/// Saves `uri` to `path`, through a temporary file so a failed download
+/// doesn't leave half an episode behind
+pub async fn download_file(uri: String, path: PathBuf) -> Result<(), CacheError> {
+    let mut response = reqwest::get(&uri).await.map_err(BackendError::from)?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(BackendError::Http {
+            status,
+            message: "Download failed".to_string(),
+        }
+        .into());
+    }
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    let partial = path.with_extension("part");
+    let mut file = tokio::fs::File::create(&partial).await?;
+    while let Some(chunk) = response.chunk().await.map_err(BackendError::from)? {
+        file.write_all(&chunk).await?;
+    }
+    file.flush().await?;
+    tokio::fs::rename(&partial, &path).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_episode_position() {
+        let mut store = PodcastStore::default();
+        assert!(!store.contains("e-1"));
+        assert!(!store.set_position("e-1", 600, 3600));
+        assert_eq!(store.state("e-1").position, 600);
+        assert!(!store.state("e-1").played);
+
+        // Close enough to the end counts as finished, once
+        assert!(store.set_position("e-1", 3580, 3600));
+        assert!(!store.set_position("e-1", 3590, 3600));
+        assert_eq!(
+            store.state("e-1"),
+            EpisodeState {
+                position: 0,
+                played: true,
+                download: None,
+            }
+        );
+
+        store.set_played("e-1", false);
+        assert!(!store.state("e-1").played);
+        assert_eq!(store.state("e-1").position, 0);
+    }
+
+    #[test]
+    fn test_download_path() {
+        let store = PodcastStore {
+            file: StoreFile::at(PathBuf::from("/data/episodes.json")),
+            episodes: HashMap::new(),
+        };
+        assert_eq!(
+            store.download_path("pod/1:2"),
+            Some(PathBuf::from("/data/downloads/pod_1_2"))
+        );
+        assert_eq!(store.downloaded_file("pod/1:2"), None);
+    }
+}
@@ -0,0 +1,99 @@
+//! Radio stations added by hand. Server stations are fetched each time, these
+//! are kept on this computer.
+
+use reqwest::Url;
+
+use crate::cache::CacheError;
+use crate::jellyfin::api::RadioStation;
+use crate::store::StoreFile;
+
+const STATIONS_FILE_NAME: &str = "stations.json";
+
+#[derive(Debug, Default)]
+pub struct RadioStore {
+    file: StoreFile,
+    stations: Vec<RadioStation>,
+}
+
+impl RadioStore {
+    pub fn load() -> Self {
+        let file = StoreFile::new("radio", STATIONS_FILE_NAME);
+        Self {
+            stations: file.load(),
+            file,
+        }
+    }
+
+    pub fn save(&self) -> impl Future<Output = Result<(), CacheError>> + Send + use<> {
+        self.file.save(&self.stations)
+    }
+
+    pub fn save_now(&self) -> Result<(), CacheError> {
+        self.file.save_now(&self.stations)
+    }
+
+    pub fn stations(&self) -> &[RadioStation] {
+        &self.stations
+    }
+
+    pub fn add(&mut self, station: RadioStation) {
+        self.stations.push(station);
+    }
+
+    pub fn remove(&mut self, station_id: &str) {
+        self.stations.retain(|station| station.id != station_id);
+    }
+}
+
+/// A station for a stream url typed in by the user, `None` when it isn't one
+/// gstreamer can open. Without a name the host stands in.
+pub fn new_user_station(name: &str, stream_url: &str) -> Option<RadioStation> {
+    let url = Url::parse(stream_url.trim()).ok()?;
+    if !matches!(url.scheme(), "http" | "https" | "rtsp" | "mms") {
+        return None;
+    }
+    let name = match name.trim() {
+        "" => url.host_str()?.to_string(),
+        name => name.to_string(),
+    };
+    Some(RadioStation {
+        id: format!("user:{}", uuid::Uuid::new_v4()),
+        name,
+        stream_url: url.to_string(),
+        homepage_url: None,
+        user_added: true,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_user_station() {
+        let station = new_user_station("", " http://radio.example:8000/live.mp3 ").unwrap();
+        assert_eq!(station.name, "radio.example");
+        assert_eq!(station.stream_url, "http://radio.example:8000/live.mp3");
+        assert!(station.user_added);
+        assert!(station.id.starts_with("user:"));
+
+        let station = new_user_station(" Jazz ", "https://jazz.example/stream").unwrap();
+        assert_eq!(station.name, "Jazz");
+
+        assert_eq!(new_user_station("Local", "file:///music/song.mp3"), None);
+        assert_eq!(new_user_station("Broken", "not a url"), None);
+    }
+
+    #[test]
+    fn test_add_and_remove() {
+        let mut store = RadioStore::default();
+        store.add(new_user_station("Jazz", "https://jazz.example/stream").unwrap());
+        let station = new_user_station("Talk", "https://talk.example/stream").unwrap();
+        store.add(station.clone());
+        assert_eq!(store.stations().len(), 2);
+
+        store.remove(&station.id);
+        assert_eq!(store.stations().len(), 1);
+        assert_eq!(store.stations()[0].name, "Jazz");
+    }
+}
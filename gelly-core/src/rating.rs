@@ -0,0 +1,47 @@
+//! Star ratings. Songs and albums are rated from one to five stars, zero
+//! meaning not rated. Jellyfin keeps ratings out of ten and Subsonic in
+//! stars, so the server side is converted to and from Jellyfin's scale.
+
+pub const MAX_STARS: u32 = 5;
+
+/// Stars for a rating out of ten, rounded to the nearest star
+pub fn stars_from_rating(rating: Option<f64>) -> u32 {
+    match rating {
+        Some(rating) if rating.is_finite() && rating > 0.0 => {
+            ((rating / 2.0).round() as u32).clamp(1, MAX_STARS)
+        }
+        _ => 0,
+    }
+}
+
+/// A rating out of ten for a number of stars, `None` for no rating
+pub fn rating_from_stars(stars: u32) -> Option<f64> {
+    match stars.min(MAX_STARS) {
+        0 => None,
+        stars => Some(stars as f64 * 2.0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        for stars in 0..=MAX_STARS {
+            assert_eq!(stars_from_rating(rating_from_stars(stars)), stars);
+        }
+    }
+
+    #[test]
+    fn test_odd_ratings() {
+        assert_eq!(stars_from_rating(None), 0);
+        assert_eq!(stars_from_rating(Some(0.0)), 0);
+        // Anything rated at all keeps at least one star
+        assert_eq!(stars_from_rating(Some(0.5)), 1);
+        assert_eq!(stars_from_rating(Some(7.0)), 4);
+        assert_eq!(stars_from_rating(Some(12.0)), 5);
+        assert_eq!(stars_from_rating(Some(f64::NAN)), 0);
+        assert_eq!(rating_from_stars(9), Some(10.0));
+    }
+}
@@ -0,0 +1,202 @@
+//! Where we left off in long tracks like DJ mixes and live sets, and the
+//! bookmarks set in them. Kept on this computer, keyed by item id.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::cache::CacheError;
+use crate::store::StoreFile;
+
+/// Positions this close to either end aren't worth resuming from
+const RESUME_MARGIN_SECONDS: u64 = 30;
+const STATE_FILE_NAME: &str = "tracks.json";
+/// Tracks kept before the least recently played are forgotten
+const MAX_TRACKS: usize = 1000;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Bookmark {
+    pub name: String,
+    /// Seconds in
+    pub position: u64,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct TrackState {
+    /// Seconds in, zero when there's nothing to resume
+    #[serde(default)]
+    pub position: u64,
+    /// In the order they come up in the track
+    #[serde(default)]
+    pub bookmarks: Vec<Bookmark>,
+    /// Goes up every time the track is touched, the lowest go first
+    #[serde(default)]
+    pub last_used: u64,
+}
+
+#[derive(Debug, Default)]
+pub struct ResumeStore {
+    file: StoreFile,
+    tracks: HashMap<String, TrackState>,
+}
+
+/// `position` when it's far enough from both ends to be worth resuming from
+pub fn resume_position(position: u64, duration: u64) -> Option<u64> {
+    (position >= RESUME_MARGIN_SECONDS && position + RESUME_MARGIN_SECONDS < duration)
+        .then_some(position)
+}
+
+impl ResumeStore {
+    pub fn load() -> Self {
+        let file = StoreFile::new("resume", STATE_FILE_NAME);
+        Self {
+            tracks: file.load(),
+            file,
+        }
+    }
+
+    pub fn save(&self) -> impl Future<Output = Result<(), CacheError>> + Send + use<> {
+        self.file.save(&self.tracks)
+    }
+
+    pub fn save_now(&self) -> Result<(), CacheError> {
+        self.file.save_now(&self.tracks)
+    }
+
+    /// Whether a position has been recorded for the track, which then wins
+    /// over the one on the server
+    pub fn contains(&self, id: &str) -> bool {
+        self.tracks.contains_key(id)
+    }
+
+    pub fn position(&self, id: &str) -> u64 {
+        self.tracks.get(id).map_or(0, |track| track.position)
+    }
+
+    /// Records how far in we are, forgetting it near the start or the end
+    pub fn set_position(&mut self, id: &str, position: u64, duration: u64) {
+        self.track_mut(id).position = resume_position(position, duration).unwrap_or(0);
+    }
+
+    pub fn bookmarks(&self, id: &str) -> Vec<Bookmark> {
+        self.tracks
+            .get(id)
+            .map(|track| track.bookmarks.clone())
+            .unwrap_or_default()
+    }
+
+    pub fn add_bookmark(&mut self, id: &str, name: &str, position: u64) {
+        let bookmarks = &mut self.track_mut(id).bookmarks;
+        let index = bookmarks.partition_point(|bookmark| bookmark.position <= position);
+        bookmarks.insert(
+            index,
+            Bookmark {
+                name: name.trim().to_string(),
+                position,
+            },
+        );
+    }
+
+    pub fn remove_bookmark(&mut self, id: &str, index: usize) {
+        if let Some(track) = self.tracks.get_mut(id)
+            && index < track.bookmarks.len()
+        {
+            track.bookmarks.remove(index);
+        }
+    }
+
+    /// The track's state marked as just used, making room for it by
+    /// forgetting the tracks left longest. Bookmarked ones are kept longer.
+    fn track_mut(&mut self, id: &str) -> &mut TrackState {
+        let last_used = self
+            .tracks
+            .values()
+            .map(|track| track.last_used)
+            .max()
+            .unwrap_or(0)
+            + 1;
+        if !self.tracks.contains_key(id) && self.tracks.len() >= MAX_TRACKS {
+            let mut by_age: Vec<(bool, u64, String)> = self
+                .tracks
+                .iter()
+                .map(|(id, track)| (!track.bookmarks.is_empty(), track.last_used, id.clone()))
+                .collect();
+            by_age.sort();
+            for (_, _, id) in by_age.into_iter().take(self.tracks.len() + 1 - MAX_TRACKS) {
+                self.tracks.remove(&id);
+            }
+        }
+        let track = self.tracks.entry(id.to_string()).or_default();
+        track.last_used = last_used;
+        track
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_track_position() {
+        let mut store = ResumeStore::default();
+        assert!(!store.contains("mix"));
+        store.set_position("mix", 4350, 7200);
+        assert!(store.contains("mix"));
+        assert_eq!(store.position("mix"), 4350);
+
+        // Nothing to resume right at the start or once it's nearly over
+        store.set_position("mix", 10, 7200);
+        assert_eq!(store.position("mix"), 0);
+        store.set_position("mix", 7190, 7200);
+        assert_eq!(store.position("mix"), 0);
+        assert!(store.contains("mix"));
+    }
+
+    #[test]
+    fn test_bookmarks() {
+        let mut store = ResumeStore::default();
+        store.add_bookmark("set", " Second drop ", 1800);
+        store.add_bookmark("set", "Intro", 0);
+        store.add_bookmark("set", "First drop", 900);
+        let names: Vec<String> = store
+            .bookmarks("set")
+            .into_iter()
+            .map(|bookmark| bookmark.name)
+            .collect();
+        assert_eq!(names, ["Intro", "First drop", "Second drop"]);
+
+        store.remove_bookmark("set", 1);
+        store.remove_bookmark("set", 5);
+        assert_eq!(
+            store.bookmarks("set"),
+            vec![
+                Bookmark {
+                    name: "Intro".to_string(),
+                    position: 0,
+                },
+                Bookmark {
+                    name: "Second drop".to_string(),
+                    position: 1800,
+                },
+            ]
+        );
+        assert!(store.bookmarks("other").is_empty());
+    }
+
+    #[test]
+    fn test_least_recently_used_forgotten() {
+        let mut store = ResumeStore::default();
+        store.add_bookmark("bookmarked", "Drop", 600);
+        for index in 0..MAX_TRACKS - 1 {
+            store.set_position(&index.to_string(), 600, 7200);
+        }
+        // Coming back to a track keeps it around
+        store.set_position("0", 900, 7200);
+        store.set_position("new", 600, 7200);
+        assert_eq!(store.tracks.len(), MAX_TRACKS);
+        assert!(store.contains("0"));
+        assert!(!store.contains("1"));
+        assert!(store.contains("bookmarked"));
+        assert!(store.contains("new"));
+    }
+}
@@ -0,0 +1,447 @@
+//! Listening statistics worked out from the play history: what was played
+//! most over a period, how much listening that adds up to, when in the week
+//! it happens, how many days in a row it kept up and which artists were new.
+
+use std::collections::{BTreeSet, HashMap};
+
+use crate::history::PlayRecord;
+
+/// How many entries each of the top lists keeps
+pub const TOP_COUNT: usize = 5;
+const SECONDS_PER_DAY: i64 = 86_400;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Period {
+    Week,
+    Month,
+    Year,
+    AllTime,
+}
+
+impl Period {
+    /// How far back the period reaches, `None` for the whole history
+    pub fn seconds(self) -> Option<i64> {
+        match self {
+            Period::Week => Some(7 * SECONDS_PER_DAY),
+            Period::Month => Some(30 * SECONDS_PER_DAY),
+            Period::Year => Some(365 * SECONDS_PER_DAY),
+            Period::AllTime => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Period::Week => "week",
+            Period::Month => "month",
+            Period::Year => "year",
+            Period::AllTime => "all",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        [Period::Week, Period::Month, Period::Year, Period::AllTime]
+            .into_iter()
+            .find(|period| period.name() == name)
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RankedItem {
+    pub name: String,
+    /// Who a song or album is by, empty for artists and genres
+    pub detail: String,
+    pub plays: u64,
+    /// Seconds
+    pub listened: u64,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Statistics {
+    pub plays: u64,
+    /// Seconds
+    pub listened: u64,
+    pub top_songs: Vec<RankedItem>,
+    pub top_artists: Vec<RankedItem>,
+    pub top_albums: Vec<RankedItem>,
+    pub top_genres: Vec<RankedItem>,
+    /// Seconds listened by day of the week, Monday first, and hour of the day
+    pub heatmap: [[u64; 24]; 7],
+    /// Days in a row with something played, up to today or yesterday
+    pub current_streak: u64,
+    pub longest_streak: u64,
+    /// Artists first heard during the period, most played first. Always
+    /// empty for the whole history.
+    pub new_artists: Vec<RankedItem>,
+}
+
+/// A song's play count on the server. It takes in plays from other devices
+/// and from before the history was kept, but not when they happened.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ServerPlays {
+    pub song_id: String,
+    pub title: String,
+    pub artists: Vec<String>,
+    pub album: String,
+    pub album_id: String,
+    pub plays: u64,
+}
+
+/// Tallies plays and listening time per key, keeping the first name and
+/// detail seen
+#[derive(Default)]
+struct Tally(HashMap<String, RankedItem>);
+
+impl Tally {
+    fn add(&mut self, key: &str, name: &str, detail: &str, plays: u64, listened: u64) {
+        if key.is_empty() {
+            return;
+        }
+        let item = self.0.entry(key.to_string()).or_insert_with(|| RankedItem {
+            name: name.to_string(),
+            detail: detail.to_string(),
+            ..Default::default()
+        });
+        item.plays += plays;
+        item.listened += listened;
+    }
+
+    /// Most played first, then most listened to
+    fn ranked(self, count: usize) -> Vec<RankedItem> {
+        let mut items: Vec<RankedItem> = self.0.into_values().collect();
+        items.sort_by(|a, b| {
+            b.plays
+                .cmp(&a.plays)
+                .then(b.listened.cmp(&a.listened))
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        items.truncate(count);
+        items
+    }
+}
+
+/// Albums go by id so that same-named ones stay apart. Plays recorded before
+/// ids were kept fall back to the name and artist.
+fn album_key(album_id: &str, album: &str, artist: &str) -> String {
+    if album.is_empty() {
+        String::new()
+    } else if album_id.is_empty() {
+        format!("{}\u{1f}{}", artist, album)
+    } else {
+        album_id.to_string()
+    }
+}
+
+/// Everything that goes in the top lists for `plays` plays of a song
+#[derive(Default)]
+struct TopLists {
+    songs: Tally,
+    artists: Tally,
+    albums: Tally,
+    genres: Tally,
+}
+
+impl TopLists {
+    #[allow(clippy::too_many_arguments)]
+    fn add(
+        &mut self,
+        song_id: &str,
+        title: &str,
+        artists: &[String],
+        album: &str,
+        album_id: &str,
+        genres: Vec<String>,
+        plays: u64,
+        listened: u64,
+    ) {
+        // Songs are tallied by id so that same-named songs stay apart
+        self.songs
+            .add(song_id, title, &artists.join(", "), plays, listened);
+        for artist in artists {
+            self.artists.add(artist, artist, "", plays, listened);
+        }
+        let first_artist = artists.first().map_or("", String::as_str);
+        self.albums.add(
+            &album_key(album_id, album, first_artist),
+            album,
+            first_artist,
+            plays,
+            listened,
+        );
+        for genre in genres {
+            self.genres.add(&genre, &genre, "", plays, listened);
+        }
+    }
+}
+
+/// Works out the statistics for `period` up to `now`. Times are bucketed
+/// into days and hours `utc_offset` seconds off UTC, and `genres` looks up
+/// a song's genres by id. The `server` play counts only count for the whole
+/// history, where they make up for plays the history doesn't have.
+pub fn compute(
+    plays: &[PlayRecord],
+    server: &[ServerPlays],
+    period: Period,
+    now: i64,
+    utc_offset: i64,
+    genres: impl Fn(&str) -> Vec<String>,
+) -> Statistics {
+    let since = period.seconds().map(|seconds| now - seconds);
+    let in_period = |play: &PlayRecord| since.is_none_or(|since| play.played_at >= since);
+
+    let mut statistics = Statistics::default();
+    let mut top = TopLists::default();
+    let mut song_plays: HashMap<&str, u64> = HashMap::new();
+    let mut first_heard: HashMap<&str, i64> = HashMap::new();
+    let mut days = BTreeSet::new();
+
+    for play in plays {
+        for artist in &play.artists {
+            let first = first_heard.entry(artist.as_str()).or_insert(play.played_at);
+            *first = (*first).min(play.played_at);
+        }
+        let local = play.played_at + utc_offset;
+        days.insert(local.div_euclid(SECONDS_PER_DAY));
+        if !in_period(play) {
+            continue;
+        }
+
+        statistics.plays += 1;
+        statistics.listened += play.listened;
+        *song_plays.entry(&play.song_id).or_default() += 1;
+        top.add(
+            &play.song_id,
+            &play.title,
+            &play.artists,
+            &play.album,
+            &play.album_id,
+            genres(&play.song_id),
+            1,
+            play.listened,
+        );
+
+        let (weekday, hour) = weekday_and_hour(local);
+        statistics.heatmap[weekday][hour] += play.listened;
+    }
+
+    if since.is_none() {
+        for song in server {
+            let missing = song
+                .plays
+                .saturating_sub(song_plays.get(song.song_id.as_str()).copied().unwrap_or(0));
+            if missing == 0 {
+                continue;
+            }
+            statistics.plays += missing;
+            top.add(
+                &song.song_id,
+                &song.title,
+                &song.artists,
+                &song.album,
+                &song.album_id,
+                genres(&song.song_id),
+                missing,
+                0,
+            );
+        }
+    }
+
+    if let Some(since) = since {
+        let mut new_artists = Tally::default();
+        for play in plays.iter().filter(|play| in_period(play)) {
+            for artist in &play.artists {
+                if first_heard
+                    .get(artist.as_str())
+                    .is_some_and(|first| *first >= since)
+                {
+                    new_artists.add(artist, artist, "", 1, play.listened);
+                }
+            }
+        }
+        statistics.new_artists = new_artists.ranked(usize::MAX);
+    }
+
+    statistics.top_songs = top.songs.ranked(TOP_COUNT);
+    statistics.top_artists = top.artists.ranked(TOP_COUNT);
+    statistics.top_albums = top.albums.ranked(TOP_COUNT);
+    statistics.top_genres = top.genres.ranked(TOP_COUNT);
+    let today = (now + utc_offset).div_euclid(SECONDS_PER_DAY);
+    (statistics.current_streak, statistics.longest_streak) = streaks(&days, today);
+    statistics
+}
+
+/// Day of the week, Monday being 0, and hour of the day for a local unix time
+fn weekday_and_hour(local: i64) -> (usize, usize) {
+    // The first of January 1970 was a Thursday
+    let weekday = (local.div_euclid(SECONDS_PER_DAY) + 3).rem_euclid(7);
+    let hour = local.rem_euclid(SECONDS_PER_DAY) / 3600;
+    (weekday as usize, hour as usize)
+}
+
+/// The current and longest runs of consecutive days. A run that got to
+/// yesterday is still current, today just hasn't had a play yet.
+fn streaks(days: &BTreeSet<i64>, today: i64) -> (u64, u64) {
+    let mut longest = 0;
+    let mut run = 0;
+    let mut previous = None;
+    for &day in days {
+        run = if previous == Some(day - 1) {
+            run + 1
+        } else {
+            1
+        };
+        longest = longest.max(run);
+        previous = Some(day);
+    }
+    let current = match previous {
+        Some(last) if last >= today - 1 => run,
+        _ => 0,
+    };
+    (current, longest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: i64 = SECONDS_PER_DAY;
+    // Monday the 6th of January 2025, midnight UTC
+    const MONDAY: i64 = 1_736_121_600;
+
+    fn play(song_id: &str, artist: &str, played_at: i64, listened: u64) -> PlayRecord {
+        PlayRecord {
+            song_id: song_id.to_string(),
+            title: format!("Song {}", song_id),
+            artists: vec![artist.to_string()],
+            album: format!("Album by {}", artist),
+            album_id: String::new(),
+            played_at,
+            duration: 200,
+            listened,
+            completed: true,
+        }
+    }
+
+    #[test]
+    fn test_top_lists_and_totals() {
+        let plays = vec![
+            play("old", "Old Favourite", MONDAY - 60 * DAY, 200),
+            play("a", "Artist A", MONDAY, 200),
+            play("b", "Artist B", MONDAY + 3600, 100),
+            play("a", "Artist A", MONDAY + DAY, 150),
+        ];
+        let genres = |id: &str| match id {
+            "a" => vec!["Jazz".to_string()],
+            _ => vec!["Rock".to_string()],
+        };
+        let statistics = compute(&plays, &[], Period::Week, MONDAY + 2 * DAY, 0, genres);
+
+        assert_eq!(statistics.plays, 3);
+        assert_eq!(statistics.listened, 450);
+        assert_eq!(statistics.top_songs[0].name, "Song a");
+        assert_eq!(statistics.top_songs[0].detail, "Artist A");
+        assert_eq!(statistics.top_songs[0].plays, 2);
+        let artists: Vec<&str> = statistics
+            .top_artists
+            .iter()
+            .map(|artist| artist.name.as_str())
+            .collect();
+        assert_eq!(artists, ["Artist A", "Artist B"]);
+        assert_eq!(statistics.top_albums[0].name, "Album by Artist A");
+        assert_eq!(statistics.top_genres[0].name, "Jazz");
+        assert_eq!(statistics.new_artists.len(), 2);
+
+        let all_time = compute(&plays, &[], Period::AllTime, MONDAY + 2 * DAY, 0, genres);
+        assert_eq!(all_time.plays, 4);
+        assert!(all_time.new_artists.is_empty());
+    }
+
+    #[test]
+    fn test_albums_kept_apart() {
+        let mut first = play("a", "Artist A", MONDAY, 200);
+        first.album = "Greatest Hits".to_string();
+        let mut second = play("b", "Artist B", MONDAY, 200);
+        second.album = "Greatest Hits".to_string();
+        let mut with_id = play("c", "Artist A", MONDAY, 200);
+        with_id.album = "Greatest Hits".to_string();
+        with_id.album_id = "hits-a".to_string();
+        let statistics = compute(
+            &[first, second, with_id],
+            &[],
+            Period::AllTime,
+            MONDAY + DAY,
+            0,
+            |_| vec![],
+        );
+        assert_eq!(statistics.top_albums.len(), 3);
+        assert!(
+            statistics
+                .top_albums
+                .iter()
+                .all(|album| album.name == "Greatest Hits" && album.plays == 1)
+        );
+    }
+
+    #[test]
+    fn test_server_play_counts_for_all_time() {
+        let plays = vec![
+            play("a", "Artist A", MONDAY, 200),
+            play("a", "Artist A", MONDAY + 60, 200),
+        ];
+        let server = |song_id: &str, artist: &str, plays: u64| ServerPlays {
+            song_id: song_id.to_string(),
+            title: format!("Song {}", song_id),
+            artists: vec![artist.to_string()],
+            album: format!("Album by {}", artist),
+            plays,
+            ..Default::default()
+        };
+        // The history already has both of the server's plays of a
+        let server = [server("a", "Artist A", 2), server("b", "Artist B", 5)];
+
+        let all_time = compute(
+            &plays,
+            &server,
+            Period::AllTime,
+            MONDAY + DAY,
+            0,
+            |_| vec![],
+        );
+        assert_eq!(all_time.plays, 7);
+        assert_eq!(all_time.listened, 400);
+        assert_eq!(all_time.top_songs[0].name, "Song b");
+        assert_eq!(all_time.top_songs[0].plays, 5);
+        assert_eq!(all_time.top_songs[1].plays, 2);
+        assert_eq!(all_time.top_artists[0].name, "Artist B");
+        assert_eq!(all_time.top_albums[0].name, "Album by Artist B");
+
+        let week = compute(&plays, &server, Period::Week, MONDAY + DAY, 0, |_| vec![]);
+        assert_eq!(week.plays, 2);
+        assert_eq!(week.top_songs.len(), 1);
+    }
+
+    #[test]
+    fn test_heatmap_uses_local_time() {
+        // 23:30 UTC on a Monday is already Tuesday 01:30 two hours east
+        let plays = vec![play("a", "Artist", MONDAY + 23 * 3600 + 1800, 120)];
+        let statistics = compute(&plays, &[], Period::AllTime, MONDAY + DAY, 7200, |_| vec![]);
+        assert_eq!(statistics.heatmap[1][1], 120);
+        assert_eq!(statistics.heatmap[0][23], 0);
+    }
+
+    #[test]
+    fn test_streaks() {
+        let days = BTreeSet::from([1, 2, 3, 7, 8]);
+        assert_eq!(streaks(&days, 9), (2, 3));
+        assert_eq!(streaks(&days, 8), (2, 3));
+        assert_eq!(streaks(&days, 10), (0, 3));
+        assert_eq!(streaks(&BTreeSet::new(), 10), (0, 0));
+    }
+
+    #[test]
+    fn test_period_names() {
+        for period in [Period::Week, Period::Month, Period::Year, Period::AllTime] {
+            assert_eq!(Period::from_name(period.name()), Some(period));
+        }
+        assert_eq!(Period::from_name("decade"), None);
+    }
+}
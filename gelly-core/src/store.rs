@@ -0,0 +1,238 @@
+//! Files in the data directory for what we keep on this computer, like
+//! podcast episodes and track positions. They're read once at startup. Saves
+//! serialize straight away, so later changes can't slip in half way, and
+//! leave the writing to a blocking thread so the main thread never waits on
+//! the disk.
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use log::warn;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+use crate::cache::{CacheError, get_data_directory};
+
+#[derive(Debug, Clone, Default)]
+pub struct StoreFile {
+    /// Nothing is read or written without a data directory
+    path: Option<PathBuf>,
+    saves: Arc<Saves>,
+}
+
+/// Saves can finish out of order, the newest one has to win
+#[derive(Debug, Default)]
+struct Saves {
+    started: AtomicU64,
+    written: Mutex<u64>,
+}
+
+impl StoreFile {
+    pub fn new(dir_name: &str, file_name: &str) -> Self {
+        match get_data_directory(dir_name) {
+            Ok(dir) => Self::at(dir.join(file_name)),
+            Err(err) => {
+                warn!("No {} data directory: {}", dir_name, err);
+                Self::default()
+            }
+        }
+    }
+
+    pub fn at(path: PathBuf) -> Self {
+        Self {
+            path: Some(path),
+            saves: Arc::default(),
+        }
+    }
+
+    /// Where anything kept alongside the file goes
+    pub fn dir(&self) -> Option<&Path> {
+        self.path.as_deref()?.parent()
+    }
+
+    /// Starts from the default when nothing has been saved yet or the file
+    /// can't be read
+    pub fn load<T: DeserializeOwned + Default>(&self) -> T {
+        let Some(path) = &self.path else {
+            return T::default();
+        };
+        match fs::read(path) {
+            Ok(data) => serde_json::from_slice(&data).unwrap_or_else(|err| {
+                warn!("Failed to read {}: {}", path.display(), err);
+                T::default()
+            }),
+            Err(_) => T::default(),
+        }
+    }
+
+    /// Reads a JSON lines file, leaving out lines that don't parse rather
+    /// than losing the rest
+    pub fn load_lines<T: DeserializeOwned>(&self) -> Vec<T> {
+        let Some(path) = &self.path else {
+            return Vec::new();
+        };
+        let Ok(data) = fs::read_to_string(path) else {
+            return Vec::new();
+        };
+        let mut skipped = 0;
+        let items = data
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .filter_map(|line| {
+                serde_json::from_str(line)
+                    .inspect_err(|_| skipped += 1)
+                    .ok()
+            })
+            .collect();
+        if skipped > 0 {
+            warn!("Skipped {} unreadable lines in {}", skipped, path.display());
+        }
+        items
+    }
+
+    /// Serializes `value` now, the returned future writes it out
+    pub fn save<T: Serialize>(
+        &self,
+        value: &T,
+    ) -> impl Future<Output = Result<(), CacheError>> + Send + use<T> {
+        let data = serde_json::to_vec(value);
+        let store = self.clone();
+        let save = self.saves.started.fetch_add(1, Ordering::SeqCst) + 1;
+        async move {
+            let data = data?;
+            tokio::task::spawn_blocking(move || store.write(save, &data))
+                .await
+                .map_err(std::io::Error::other)?
+        }
+    }
+
+    /// [`Self::save`] on this thread, for when the app is closing
+    pub fn save_now<T: Serialize>(&self, value: &T) -> Result<(), CacheError> {
+        let data = serde_json::to_vec(value)?;
+        let save = self.saves.started.fetch_add(1, Ordering::SeqCst) + 1;
+        self.write(save, &data)
+    }
+
+    /// Adds `value` as a line to a JSON lines file
+    pub fn append<T: Serialize>(
+        &self,
+        value: &T,
+    ) -> impl Future<Output = Result<(), CacheError>> + Send + use<T> {
+        let line = serde_json::to_string(value);
+        let store = self.clone();
+        async move {
+            let line = line?;
+            tokio::task::spawn_blocking(move || store.append_line(&line))
+                .await
+                .map_err(std::io::Error::other)?
+        }
+    }
+
+    fn append_line(&self, line: &str) -> Result<(), CacheError> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let _written = self.saves.written.lock().unwrap();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        writeln!(file, "{}", line)?;
+        Ok(())
+    }
+
+    /// Deletes the file, once any write already going has finished
+    pub fn remove(&self) -> Result<(), CacheError> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let _written = self.saves.written.lock().unwrap();
+        match fs::remove_file(path) {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(err.into()),
+            _ => Ok(()),
+        }
+    }
+
+    fn write(&self, save: u64, data: &[u8]) -> Result<(), CacheError> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let mut written = self.saves.written.lock().unwrap();
+        if *written > save {
+            return Ok(());
+        }
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, data)?;
+        *written = save;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn temp_store(name: &str) -> StoreFile {
+        let dir = std::env::temp_dir().join(format!("gelly-store-{}", uuid::Uuid::new_v4()));
+        StoreFile::at(dir.join(name))
+    }
+
+    #[tokio::test]
+    async fn test_save_and_load() {
+        let store = temp_store("state.json");
+        let loaded: HashMap<String, u64> = store.load();
+        assert!(loaded.is_empty());
+
+        let first = store.save(&HashMap::from([("a".to_string(), 1u64)]));
+        let second = store.save(&HashMap::from([("a".to_string(), 2u64)]));
+        // The older save finishing last doesn't undo the newer one
+        second.await.unwrap();
+        first.await.unwrap();
+        let loaded: HashMap<String, u64> = store.load();
+        assert_eq!(loaded["a"], 2);
+
+        store
+            .save_now(&HashMap::from([("a".to_string(), 3u64)]))
+            .unwrap();
+        let loaded: HashMap<String, u64> = store.load();
+        assert_eq!(loaded["a"], 3);
+
+        fs::write(store.path.as_ref().unwrap(), "not json").unwrap();
+        let loaded: HashMap<String, u64> = store.load();
+        assert!(loaded.is_empty());
+        fs::remove_dir_all(store.dir().unwrap()).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_append_and_load_lines() {
+        let store = temp_store("log.jsonl");
+        store.append(&1u64).await.unwrap();
+        store.append(&2u64).await.unwrap();
+        let path = store.path.clone().unwrap();
+        let mut data = fs::read_to_string(&path).unwrap();
+        data.push_str("garbage\n\n");
+        fs::write(&path, data).unwrap();
+        store.append(&3u64).await.unwrap();
+
+        assert_eq!(store.load_lines::<u64>(), vec![1, 2, 3]);
+        store.remove().unwrap();
+        store.remove().unwrap();
+        assert!(store.load_lines::<u64>().is_empty());
+        fs::remove_dir_all(store.dir().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_without_data_directory() {
+        let store = StoreFile::default();
+        assert_eq!(store.load::<Vec<u64>>(), Vec::<u64>::new());
+        assert!(store.save_now(&vec![1u64]).is_ok());
+        assert!(store.dir().is_none());
+    }
+}
@@ -36,6 +36,17 @@ where
     }
 }
 
+fn deserialize_optional_id_string<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match Option::<Value>::deserialize(deserializer)? {
+        Some(Value::String(s)) => Ok(Some(s)),
+        Some(Value::Number(n)) => Ok(Some(n.to_string())),
+        _ => Ok(None),
+    }
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct SubsonicEnvelope {
     #[serde(rename = "subsonic-response")]
@@ -54,12 +65,21 @@ pub struct SubsonicResponse {
     // - getAlbum      -> album
     pub album_list2: Option<AlbumList2Payload>,
     pub album: Option<Album>,
+    pub album_info: Option<AlbumInfoPayload>,
+    pub artist_info2: Option<ArtistInfoPayload>,
 
     pub playlists: Option<PlaylistsPayload>,
     pub playlist: Option<Playlist>,
     pub song: Option<Song>,
     pub lyrics_list: Option<LyricsList>,
     pub starred2: Option<Starred2Payload>,
+
+    pub indexes: Option<IndexesPayload>,
+    pub directory: Option<Directory>,
+
+    pub podcasts: Option<PodcastsPayload>,
+    pub newest_podcasts: Option<NewestPodcastsPayload>,
+    pub internet_radio_stations: Option<InternetRadioStationsPayload>,
 }
 
 impl SubsonicResponse {
@@ -121,6 +141,8 @@ pub struct AlbumList2Payload {
 pub struct AlbumListEntry {
     #[serde(deserialize_with = "deserialize_id_string")]
     pub id: String,
+    /// One to five stars
+    pub user_rating: Option<u32>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -145,6 +167,44 @@ pub struct Album {
     #[serde(default, deserialize_with = "deserialize_items_skip_errors")]
     pub song: Vec<Song>,
     pub cover_art: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_items_skip_errors")]
+    pub disc_titles: Vec<DiscTitle>,
+    #[serde(default)]
+    pub is_compilation: bool,
+    #[serde(default)]
+    pub release_types: Vec<String>,
+    #[serde(default, deserialize_with = "deserialize_items_skip_errors")]
+    pub record_labels: Vec<RecordLabel>,
+    pub music_brainz_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RecordLabel {
+    pub name: String,
+}
+
+// https://opensubsonic.netlify.app/docs/endpoints/getalbuminfo2/
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AlbumInfoPayload {
+    pub notes: Option<String>,
+    pub music_brainz_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DiscTitle {
+    pub disc: u32,
+    #[serde(default)]
+    pub title: String,
+}
+
+// https://opensubsonic.netlify.app/docs/endpoints/getartistinfo2/
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArtistInfoPayload {
+    pub biography: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_items_skip_errors")]
+    pub similar_artist: Vec<ArtistRef>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -175,6 +235,125 @@ pub struct Song {
     pub path: Option<String>,
     pub genre: Option<String>,
     pub played: Option<String>,
+    /// One to five stars
+    pub user_rating: Option<u32>,
+    pub display_composer: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_items_skip_errors")]
+    pub contributors: Vec<Contributor>,
+    /// The directory the file is in
+    #[serde(default, deserialize_with = "deserialize_optional_id_string")]
+    pub parent: Option<String>,
+    /// The recording's MusicBrainz id, OpenSubsonic only
+    pub music_brainz_id: Option<String>,
+    /// "music", "podcast", "audiobook" or "video"
+    #[serde(rename = "type")]
+    pub media_type: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct IndexesPayload {
+    #[serde(default, deserialize_with = "deserialize_items_skip_errors")]
+    pub index: Vec<Index>,
+    /// Files at the top level of the music folders
+    #[serde(default, deserialize_with = "deserialize_items_skip_errors")]
+    pub child: Vec<DirectoryChild>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Index {
+    #[serde(default, deserialize_with = "deserialize_items_skip_errors")]
+    pub artist: Vec<ArtistRef>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Directory {
+    #[serde(deserialize_with = "deserialize_id_string")]
+    pub id: String,
+    pub name: String,
+    #[serde(default, deserialize_with = "deserialize_optional_id_string")]
+    pub parent: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_items_skip_errors")]
+    pub child: Vec<DirectoryChild>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DirectoryChild {
+    #[serde(deserialize_with = "deserialize_id_string")]
+    pub id: String,
+    #[serde(default)]
+    pub is_dir: bool,
+    #[serde(default)]
+    pub title: String,
+    pub path: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PodcastsPayload {
+    #[serde(default, deserialize_with = "deserialize_items_skip_errors")]
+    pub channel: Vec<PodcastChannel>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct NewestPodcastsPayload {
+    #[serde(default, deserialize_with = "deserialize_items_skip_errors")]
+    pub episode: Vec<PodcastEpisode>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PodcastChannel {
+    #[serde(deserialize_with = "deserialize_id_string")]
+    pub id: String,
+    #[serde(default)]
+    pub title: String,
+    pub description: Option<String>,
+    pub cover_art: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_items_skip_errors")]
+    pub episode: Vec<PodcastEpisode>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PodcastEpisode {
+    #[serde(deserialize_with = "deserialize_id_string")]
+    pub id: String,
+    /// Only there once the server has downloaded the episode
+    #[serde(default, deserialize_with = "deserialize_optional_id_string")]
+    pub stream_id: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_optional_id_string")]
+    pub channel_id: Option<String>,
+    #[serde(default)]
+    pub title: String,
+    pub description: Option<String>,
+    pub publish_date: Option<String>,
+    pub duration: Option<u64>,
+    pub cover_art: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InternetRadioStationsPayload {
+    #[serde(default, deserialize_with = "deserialize_items_skip_errors")]
+    pub internet_radio_station: Vec<InternetRadioStation>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InternetRadioStation {
+    #[serde(deserialize_with = "deserialize_id_string")]
+    pub id: String,
+    pub name: String,
+    pub stream_url: String,
+    pub home_page_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Contributor {
+    pub role: String,
+    pub sub_role: Option<String>,
+    pub artist: ArtistRef,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -190,6 +369,7 @@ pub struct PlaylistSummary {
     pub id: String,
     pub name: String,
     pub song_count: Option<u64>,
+    pub comment: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -197,6 +377,11 @@ pub struct PlaylistSummary {
 pub struct Playlist {
     #[serde(deserialize_with = "deserialize_id_string")]
     pub id: String,
+    #[serde(default)]
+    pub name: String,
+    pub comment: Option<String>,
+    #[serde(default)]
+    pub public: bool,
 
     #[serde(default, deserialize_with = "deserialize_items_skip_errors")]
     pub entry: Vec<Song>,
@@ -215,6 +400,25 @@ pub struct StructuredLyrics {
     pub synced: bool,
     #[serde(default, deserialize_with = "deserialize_items_skip_errors")]
     pub line: Vec<LyricLine>,
+    /// Word timings from the songLyrics v2 extension
+    #[serde(default, deserialize_with = "deserialize_items_skip_errors")]
+    pub cue_line: Vec<CueLine>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CueLine {
+    /// Index into `line`
+    pub index: usize,
+    #[serde(default, deserialize_with = "deserialize_items_skip_errors")]
+    pub cue: Vec<Cue>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Cue {
+    pub start: u64,
+    pub value: String,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -1,3 +1,4 @@
+use std::collections::{HashMap, HashSet};
 use std::time::Duration;
 
 use log::{debug, warn};
@@ -8,12 +9,20 @@ use serde::de::DeserializeOwned;
 use crate::backend::BackendError;
 use crate::config;
 use crate::jellyfin::api::{
-    ArtistItemsDto, FavoriteDto, FavoriteDtoList, FavoriteUserDataDto, ImageType, ItemType,
-    LibraryDto, LibraryDtoList, LyricsResponse, MediaSource, MediaStream, MusicDto, MusicDtoList,
-    PlaybackInfo, PlaybackReport, PlaybackReportStatus, PlaylistDtoList, PlaylistItems,
-    UserDataDto,
+    AlbumInfo, ArtistInfo, ArtistItemsDto, DiscTitle, FavoriteDto, FavoriteDtoList,
+    FavoriteUserDataDto, Folder, FolderEntry, ImageType, ItemType, LibraryDto, LibraryDtoList,
+    Lyric, LyricsResponse, MediaSource, MediaStream, MusicDto, MusicDtoList, PersonDto,
+    PlaybackInfo, PlaybackReport, PlaybackReportStatus, PlaylistDetails, PlaylistDtoList,
+    PlaylistItems, PodcastChannel, PodcastEpisode, RadioStation, RatingDto, RatingDtoList,
+    RatingUserDataDto, StudioDto, UserDataDto,
+};
+use crate::lyrics::TICKS_PER_MS;
+use crate::rating::{MAX_STARS, rating_from_stars};
+use crate::subsonic::api::{
+    Album, AlbumListEntry, ArtistRef, Cue, Directory, InternetRadioStation,
+    PodcastChannel as SubsonicChannel, PodcastEpisode as SubsonicEpisode, Song, SubsonicEnvelope,
+    SubsonicResponse,
 };
-use crate::subsonic::api::{ArtistRef, Song, SubsonicEnvelope, SubsonicResponse};
 
 pub mod api;
 
@@ -39,6 +48,8 @@ struct AlbumFallback {
     year: Option<u32>,
     created: Option<String>,
     cover_art: Option<String>,
+    is_compilation: bool,
+    record_labels: Vec<String>,
 }
 
 impl Subsonic {
@@ -123,13 +134,13 @@ impl Subsonic {
     pub async fn get_library(&self, library_id: &str) -> Result<MusicDtoList, BackendError> {
         debug!("Subsonic::get_library(library_id={library_id})");
 
-        let album_ids = self.get_album_ids(library_id).await?;
+        let albums = self.get_album_list(library_id).await?;
         let mut items = Vec::<MusicDto>::new();
 
-        for album_id in album_ids {
-            match self.get_album(&album_id).await {
+        for album in albums {
+            match self.get_album(&album.id).await {
                 Ok(mut songs) => items.append(&mut songs),
-                Err(err) => warn!("Failed to fetch album {}: {}", album_id, err),
+                Err(err) => warn!("Failed to fetch album {}: {}", album.id, err),
             }
         }
 
@@ -172,6 +183,17 @@ impl Subsonic {
         Ok(FavoriteDtoList { items })
     }
 
+    // https://opensubsonic.netlify.app/docs/endpoints/setrating/
+    pub async fn set_rating(&self, item_id: &str, stars: u32) -> Result<(), BackendError> {
+        debug!("Subsonic::set_rating(item_id={item_id}, stars={stars})");
+        let params = vec![
+            ("id".to_string(), item_id.to_string()),
+            ("rating".to_string(), stars.min(MAX_STARS).to_string()),
+        ];
+        let response = self.get_subsonic("setRating", &params).await?;
+        self.ensure_ok_response(&response)
+    }
+
     // https://opensubsonic.netlify.app/docs/endpoints/star/
     pub async fn set_favorite(
         &self,
@@ -193,8 +215,8 @@ impl Subsonic {
     }
 
     // https://github.com/opensubsonic/open-subsonic-api/blob/main/content/en/docs/Endpoints/getalbumlist2.md
-    async fn get_album_ids(&self, library_id: &str) -> Result<Vec<String>, BackendError> {
-        let mut album_ids = Vec::new();
+    async fn get_album_list(&self, library_id: &str) -> Result<Vec<AlbumListEntry>, BackendError> {
+        let mut albums = Vec::new();
         let mut offset: u32 = 0;
 
         loop {
@@ -218,13 +240,7 @@ impl Subsonic {
 
             let page = response
                 .album_list2
-                .map(|payload| {
-                    payload
-                        .album
-                        .into_iter()
-                        .map(|album| album.id)
-                        .collect::<Vec<_>>()
-                })
+                .map(|payload| payload.album)
                 .unwrap_or_default();
 
             if page.is_empty() {
@@ -232,7 +248,7 @@ impl Subsonic {
             }
 
             let count = page.len() as u32;
-            album_ids.extend(page);
+            albums.extend(page);
 
             if count < ALBUM_LIST_PAGE_SIZE {
                 break;
@@ -241,7 +257,24 @@ impl Subsonic {
             offset += count;
         }
 
-        Ok(album_ids)
+        Ok(albums)
+    }
+
+    pub async fn get_album_ratings(&self, library_id: &str) -> Result<RatingDtoList, BackendError> {
+        debug!("Subsonic::get_album_ratings(library_id={library_id})");
+        let items = self
+            .get_album_list(library_id)
+            .await?
+            .into_iter()
+            .filter(|album| album.user_rating.is_some_and(|stars| stars > 0))
+            .map(|album| RatingDto {
+                id: album.id,
+                user_data: RatingUserDataDto {
+                    rating: rating_from_stars(album.user_rating.unwrap_or(0)),
+                },
+            })
+            .collect();
+        Ok(RatingDtoList { items })
     }
 
     // https://github.com/opensubsonic/open-subsonic-api/blob/main/content/en/docs/Endpoints/getalbum.md
@@ -265,6 +298,12 @@ impl Subsonic {
             year: album.year,
             created: album.created.clone(),
             cover_art: album.cover_art,
+            is_compilation: album.is_compilation,
+            record_labels: album
+                .record_labels
+                .into_iter()
+                .map(|label| label.name)
+                .collect(),
         };
 
         let songs = album
@@ -276,7 +315,160 @@ impl Subsonic {
         Ok(songs)
     }
 
+    pub async fn get_album_info(&self, album_id: &str) -> Result<AlbumInfo, BackendError> {
+        debug!("Subsonic::get_album_info(album_id={album_id})");
+        let response = self
+            .get_subsonic("getAlbum", &[("id".to_string(), album_id.to_string())])
+            .await?;
+        self.ensure_ok_response(&response)?;
+
+        let Some(album) = response.album else {
+            return Ok(AlbumInfo::default());
+        };
+        let mut info = album_info_from(album);
+
+        // Notes are an extra, plenty of servers don't have any
+        match self
+            .get_subsonic("getAlbumInfo2", &[("id".to_string(), album_id.to_string())])
+            .await
+        {
+            Ok(response) if response.is_ok() => {
+                if let Some(payload) = response.album_info {
+                    info.overview = payload.notes.filter(|notes| !notes.trim().is_empty());
+                    info.musicbrainz_release_id = info
+                        .musicbrainz_release_id
+                        .or(payload.music_brainz_id.filter(|id| !id.is_empty()));
+                }
+            }
+            Ok(_) => debug!("No album info for {album_id}"),
+            Err(e) => debug!("Failed to get album info for {album_id}: {e}"),
+        }
+        Ok(info)
+    }
+
+    // https://opensubsonic.netlify.app/docs/endpoints/getartistinfo2/
+    pub async fn get_artist_info(&self, artist_id: &str) -> Result<ArtistInfo, BackendError> {
+        debug!("Subsonic::get_artist_info(artist_id={artist_id})");
+        let response = self
+            .get_subsonic(
+                "getArtistInfo2",
+                &[
+                    ("id".to_string(), artist_id.to_string()),
+                    ("count".to_string(), "12".to_string()),
+                ],
+            )
+            .await?;
+        self.ensure_ok_response(&response)?;
+
+        let Some(info) = response.artist_info2 else {
+            return Ok(ArtistInfo::default());
+        };
+        let similar_artists = info
+            .similar_artist
+            .into_iter()
+            .map(|artist| ArtistItemsDto {
+                name: artist.name,
+                id: artist.id,
+            })
+            .collect();
+        Ok(ArtistInfo::new(info.biography, similar_artists))
+    }
+
+    // https://opensubsonic.netlify.app/docs/endpoints/getindexes/
+    // https://opensubsonic.netlify.app/docs/endpoints/getmusicdirectory/
+    pub async fn get_folder(
+        &self,
+        library_id: &str,
+        folder_id: Option<&str>,
+    ) -> Result<Folder, BackendError> {
+        debug!("Subsonic::get_folder(library_id={library_id}, folder_id={folder_id:?})");
+        match folder_id {
+            Some(folder_id) => Ok(folder_from_directory(self.get_directory(folder_id).await?)),
+            None => self.get_root_folder(library_id).await,
+        }
+    }
+
+    /// The artist folders getIndexes lists are the top of the tree
+    async fn get_root_folder(&self, library_id: &str) -> Result<Folder, BackendError> {
+        let mut params = Vec::new();
+        if !library_id.is_empty() && library_id != ALL_FOLDERS_LIBRARY_ID {
+            params.push(("musicFolderId".to_string(), library_id.to_string()));
+        }
+        let response = self.get_subsonic("getIndexes", &params).await?;
+        self.ensure_ok_response(&response)?;
+
+        let indexes = response.indexes.unwrap_or_default();
+        Ok(Folder {
+            id: library_id.to_string(),
+            folders: indexes
+                .index
+                .into_iter()
+                .flat_map(|index| index.artist)
+                .map(|artist| FolderEntry {
+                    id: artist.id,
+                    name: artist.name,
+                })
+                .collect(),
+            song_ids: indexes
+                .child
+                .into_iter()
+                .filter(|child| !child.is_dir)
+                .map(|child| child.id)
+                .collect(),
+            ..Default::default()
+        })
+    }
+
+    async fn get_directory(&self, directory_id: &str) -> Result<Directory, BackendError> {
+        let response = self
+            .get_subsonic(
+                "getMusicDirectory",
+                &[("id".to_string(), directory_id.to_string())],
+            )
+            .await?;
+        self.ensure_ok_response(&response)?;
+        response.directory.ok_or_else(|| BackendError::Http {
+            status: StatusCode::BAD_GATEWAY,
+            message: "Subsonic response missing directory payload".to_string(),
+        })
+    }
+
+    /// Walks the tree one directory at a time, there's no recursive endpoint
+    pub async fn get_folder_song_ids(
+        &self,
+        library_id: &str,
+        folder_id: Option<&str>,
+    ) -> Result<Vec<String>, BackendError> {
+        let folder = self.get_folder(library_id, folder_id).await?;
+        let mut song_ids = folder.song_ids;
+        let mut pending: Vec<String> = folder.folders.into_iter().rev().map(|f| f.id).collect();
+        let mut visited = HashSet::new();
+        while let Some(directory_id) = pending.pop() {
+            if !visited.insert(directory_id.clone()) {
+                continue;
+            }
+            match self.get_directory(&directory_id).await {
+                Ok(directory) => {
+                    let subfolder = folder_from_directory(directory);
+                    song_ids.extend(subfolder.song_ids);
+                    pending.extend(subfolder.folders.into_iter().rev().map(|f| f.id));
+                }
+                Err(err) => warn!("Failed to fetch directory {}: {}", directory_id, err),
+            }
+        }
+        Ok(song_ids)
+    }
+
+    pub async fn get_song_folder_id(&self, song_id: &str) -> Result<Option<String>, BackendError> {
+        let response = self
+            .get_subsonic("getSong", &[("id".to_string(), song_id.to_string())])
+            .await?;
+        self.ensure_ok_response(&response)?;
+        Ok(response.song.and_then(|song| song.parent))
+    }
+
     fn song_to_music_dto(&self, song: Song, fallback: &AlbumFallback) -> MusicDto {
+        let people = song_people(&song);
         let album = song.album.or_else(|| fallback.album_name.clone());
         let album_id = song.album_id.or_else(|| fallback.album_id.clone());
 
@@ -348,12 +540,28 @@ impl Subsonic {
             user_data: UserDataDto {
                 play_count: song.play_count.unwrap_or(0),
                 last_played_date: song.played,
+                playback_position_ticks: 0,
+                rating: rating_from_stars(song.user_rating.unwrap_or(0)),
             },
             // there's no eqvivalent to that except for doing another API call
             // so it'll be `true` and just show an empty window for the time being
             has_lyrics: true,
             genres: song.genre.into_iter().collect(),
             cover_art: fallback.cover_art.clone(),
+            is_compilation: fallback.is_compilation,
+            people,
+            studios: fallback
+                .record_labels
+                .iter()
+                .map(|name| StudioDto { name: name.clone() })
+                .collect(),
+            provider_ids: song
+                .music_brainz_id
+                .filter(|id| !id.is_empty())
+                .map(|id| HashMap::from([("MusicBrainzRecording".to_string(), id)]))
+                .unwrap_or_default(),
+            path: song.path,
+            item_type: song.media_type,
         }
     }
 
@@ -374,6 +582,7 @@ impl Subsonic {
                         id: playlist.id,
                         name: playlist.name,
                         child_count: playlist.song_count.unwrap_or(0),
+                        overview: playlist.comment,
                     })
                     .collect::<Vec<_>>()
             })
@@ -414,6 +623,8 @@ impl Subsonic {
             year: None,
             created: None,
             cover_art: None,
+            is_compilation: false,
+            record_labels: vec![],
         };
 
         let items = playlist
@@ -557,6 +768,53 @@ impl Subsonic {
         Ok(())
     }
 
+    // https://github.com/opensubsonic/open-subsonic-api/blob/main/content/en/docs/Endpoints/getplaylist.md
+    pub async fn get_playlist_details(
+        &self,
+        playlist_id: &str,
+    ) -> Result<PlaylistDetails, BackendError> {
+        debug!("Subsonic::get_playlist_details(playlist_id={playlist_id})");
+
+        let response = self
+            .get_subsonic(
+                "getPlaylist",
+                &[("id".to_string(), playlist_id.to_string())],
+            )
+            .await?;
+        self.ensure_ok_response(&response)?;
+
+        let playlist = response.playlist.ok_or_else(|| BackendError::Http {
+            status: StatusCode::BAD_GATEWAY,
+            message: "Subsonic getPlaylist response missing playlist payload".to_string(),
+        })?;
+
+        Ok(PlaylistDetails {
+            name: playlist.name,
+            description: playlist.comment.unwrap_or_default(),
+            is_public: playlist.public,
+        })
+    }
+
+    // https://github.com/opensubsonic/open-subsonic-api/blob/main/content/en/docs/Endpoints/updateplaylist.md
+    pub async fn update_playlist(
+        &self,
+        playlist_id: &str,
+        details: &PlaylistDetails,
+    ) -> Result<(), BackendError> {
+        debug!("Subsonic::update_playlist(playlist_id={playlist_id})");
+
+        let params = vec![
+            ("playlistId".to_string(), playlist_id.to_string()),
+            ("name".to_string(), details.name.clone()),
+            ("comment".to_string(), details.description.clone()),
+            ("public".to_string(), details.is_public.to_string()),
+        ];
+
+        let response = self.get_subsonic("updatePlaylist", &params).await?;
+        self.ensure_ok_response(&response)?;
+        Ok(())
+    }
+
     // https://github.com/opensubsonic/open-subsonic-api/blob/main/content/en/docs/Endpoints/deleteplaylist.md
     pub async fn delete_item(&self, item_id: &str) -> Result<(), BackendError> {
         debug!("Subsonic::delete_item(item_id={item_id})");
@@ -705,6 +963,91 @@ impl Subsonic {
         Ok(())
     }
 
+    // https://opensubsonic.netlify.app/docs/endpoints/getpodcasts/
+    pub async fn get_podcasts(&self) -> Result<Vec<PodcastChannel>, BackendError> {
+        debug!("Subsonic::get_podcasts()");
+        let response = self
+            .get_subsonic(
+                "getPodcasts",
+                &[("includeEpisodes".to_string(), "true".to_string())],
+            )
+            .await?;
+        self.ensure_ok_response(&response)?;
+        Ok(response
+            .podcasts
+            .unwrap_or_default()
+            .channel
+            .into_iter()
+            .map(podcast_channel_from)
+            .collect())
+    }
+
+    // https://opensubsonic.netlify.app/docs/endpoints/getnewestpodcasts/
+    pub async fn get_newest_podcast_episodes(
+        &self,
+        count: u32,
+    ) -> Result<Vec<PodcastEpisode>, BackendError> {
+        debug!("Subsonic::get_newest_podcast_episodes(count={count})");
+        let response = self
+            .get_subsonic(
+                "getNewestPodcasts",
+                &[("count".to_string(), count.to_string())],
+            )
+            .await?;
+        self.ensure_ok_response(&response)?;
+        // Episodes only carry the channel id, callers fill in the title
+        Ok(response
+            .newest_podcasts
+            .unwrap_or_default()
+            .episode
+            .into_iter()
+            .map(|episode| podcast_episode_from(episode, ""))
+            .collect())
+    }
+
+    // https://opensubsonic.netlify.app/docs/endpoints/downloadpodcastepisode/
+    pub async fn download_podcast_episode(&self, episode_id: &str) -> Result<(), BackendError> {
+        debug!("Subsonic::download_podcast_episode(episode_id={episode_id})");
+        let response = self
+            .get_subsonic(
+                "downloadPodcastEpisode",
+                &[("id".to_string(), episode_id.to_string())],
+            )
+            .await?;
+        self.ensure_ok_response(&response)
+    }
+
+    // https://opensubsonic.netlify.app/docs/endpoints/getinternetradiostations/
+    pub async fn get_radio_stations(&self) -> Result<Vec<RadioStation>, BackendError> {
+        debug!("Subsonic::get_radio_stations()");
+        let response = self.get_subsonic("getInternetRadioStations", &[]).await?;
+        self.ensure_ok_response(&response)?;
+        Ok(response
+            .internet_radio_stations
+            .unwrap_or_default()
+            .internet_radio_station
+            .into_iter()
+            .map(radio_station_from)
+            .collect())
+    }
+
+    // https://opensubsonic.netlify.app/docs/endpoints/download/
+    pub fn get_download_uri(&self, item_id: &str) -> String {
+        let mut url = self.rest_url("download");
+        let mut params = self.auth_params();
+        params.retain(|(k, _)| k != "f");
+        params.push(("id".to_string(), item_id.to_string()));
+
+        {
+            let mut pairs = url.query_pairs_mut();
+            for (k, v) in &params {
+                pairs.append_pair(k, v);
+            }
+        }
+
+        url.to_string()
+    }
+
     // https://github.com/opensubsonic/open-subsonic-api/blob/main/content/en/docs/Endpoints/getLyricsBySongId.md
     pub async fn fetch_lyrics(&self, item_id: &str) -> Result<LyricsResponse, BackendError> {
         debug!("Subsonic::fetch_lyrics(item_id={item_id})");
@@ -730,10 +1073,20 @@ impl Subsonic {
                     .map(|l| {
                         l.line
                             .iter()
-                            .map(|line| crate::jellyfin::api::Lyric {
-                                text: line.value.clone(),
-                                // Subsonic timestamps are in ms; Jellyfin uses ticks (100ns).
-                                start: line.start.map(|ms| ms * 10_000),
+                            .enumerate()
+                            .map(|(index, line)| {
+                                let cues = l
+                                    .cue_line
+                                    .iter()
+                                    .find(|cue_line| cue_line.index == index)
+                                    .map(|cue_line| cue_starts(&line.value, &cue_line.cue))
+                                    .unwrap_or_default();
+                                Lyric::with_word_starts(
+                                    line.value.clone(),
+                                    // Subsonic timestamps are in ms; Jellyfin uses ticks (100ns).
+                                    line.start.map(|ms| ms * TICKS_PER_MS),
+                                    &cues,
+                                )
                             })
                             .collect::<Vec<_>>()
                     })
@@ -839,3 +1192,286 @@ impl Default for Subsonic {
         Self::new("", "", "")
     }
 }
+
+/// Cues only carry their text, so find each one in the line after the
+/// previous one. Returns char positions with starts in ticks.
+fn cue_starts(line: &str, cues: &[Cue]) -> Vec<(usize, u64)> {
+    let mut starts = Vec::new();
+    let mut from = 0;
+    for cue in cues {
+        let value = cue.value.trim();
+        if value.is_empty() {
+            continue;
+        }
+        let Some(found) = line[from..].find(value) else {
+            break;
+        };
+        let byte = from + found;
+        starts.push((line[..byte].chars().count(), cue.start * TICKS_PER_MS));
+        from += found + value.len();
+    }
+    starts
+}
+
+fn album_info_from(album: Album) -> AlbumInfo {
+    let mut info = AlbumInfo {
+        disc_titles: album
+            .disc_titles
+            .into_iter()
+            .filter(|disc| !disc.title.is_empty())
+            .map(|disc| DiscTitle {
+                disc: disc.disc,
+                title: disc.title,
+            })
+            .collect(),
+        labels: album
+            .record_labels
+            .into_iter()
+            .map(|label| label.name)
+            .filter(|name| !name.is_empty())
+            .collect(),
+        release_types: album
+            .release_types
+            .into_iter()
+            .filter(|release_type| !release_type.is_empty())
+            .collect(),
+        is_compilation: album.is_compilation,
+        musicbrainz_release_id: album.music_brainz_id.filter(|id| !id.is_empty()),
+        ..Default::default()
+    };
+    for song in &album.song {
+        if song.contributors.is_empty()
+            && let Some(composer) = &song.display_composer
+        {
+            info.add_credit(composer, "Composer");
+        }
+        for contributor in &song.contributors {
+            let role = capitalize(&contributor.role);
+            let role = match contributor.sub_role.as_deref() {
+                Some(sub_role) if !sub_role.is_empty() => format!("{role} ({sub_role})"),
+                _ => role,
+            };
+            info.add_credit(&contributor.artist.name, &role);
+        }
+        info.add_file(song.size.unwrap_or(0), song.suffix.as_deref().unwrap_or(""));
+    }
+    info
+}
+
+fn podcast_channel_from(channel: SubsonicChannel) -> PodcastChannel {
+    let episodes = channel
+        .episode
+        .into_iter()
+        .map(|episode| podcast_episode_from(episode, &channel.title))
+        .collect();
+    PodcastChannel {
+        id: channel.id,
+        title: channel.title,
+        description: channel.description,
+        image_id: channel.cover_art,
+        episodes,
+    }
+}
+
+fn podcast_episode_from(episode: SubsonicEpisode, channel_title: &str) -> PodcastEpisode {
+    PodcastEpisode {
+        id: episode.id,
+        stream_id: episode.stream_id,
+        channel_id: episode.channel_id.unwrap_or_default(),
+        channel_title: channel_title.to_string(),
+        title: episode.title,
+        description: episode.description,
+        publish_date: episode.publish_date,
+        run_time_ticks: episode.duration.unwrap_or(0) * 10_000_000,
+        image_id: episode.cover_art,
+        // Subsonic doesn't keep either, they're tracked locally
+        position_ticks: 0,
+        played: false,
+    }
+}
+
+fn radio_station_from(station: InternetRadioStation) -> RadioStation {
+    RadioStation {
+        id: station.id,
+        name: station.name,
+        stream_url: station.stream_url,
+        homepage_url: station.home_page_url.filter(|url| !url.is_empty()),
+        user_added: false,
+    }
+}
+
+fn folder_from_directory(directory: Directory) -> Folder {
+    // Directories don't have a path, but the files in them do
+    let path = directory
+        .child
+        .iter()
+        .find(|child| !child.is_dir)
+        .and_then(|child| child.path.as_deref())
+        .and_then(|path| path.rsplit_once('/'))
+        .map(|(dir, _)| dir.to_string());
+    let (folders, songs): (Vec<_>, Vec<_>) =
+        directory.child.into_iter().partition(|child| child.is_dir);
+    Folder {
+        id: directory.id,
+        name: directory.name,
+        parent_id: directory.parent,
+        path,
+        folders: folders
+            .into_iter()
+            .map(|child| FolderEntry {
+                id: child.id,
+                name: child.title,
+            })
+            .collect(),
+        song_ids: songs.into_iter().map(|child| child.id).collect(),
+    }
+}
+
+/// Contributors in the shape Jellyfin returns `People`
+fn song_people(song: &Song) -> Vec<PersonDto> {
+    if song.contributors.is_empty()
+        && let Some(composer) = &song.display_composer
+    {
+        return vec![PersonDto {
+            name: composer.clone(),
+            role: None,
+            person_type: "Composer".to_string(),
+        }];
+    }
+    song.contributors
+        .iter()
+        .map(|contributor| PersonDto {
+            name: contributor.artist.name.clone(),
+            role: contributor.sub_role.clone(),
+            person_type: capitalize(&contributor.role),
+        })
+        .collect()
+}
+
+/// OpenSubsonic roles are lowercase, Jellyfin's are not
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cue_starts() {
+        let cues: Vec<Cue> = serde_json::from_str(
+            r#"[{"start":1000,"value":"Héllo"},{"start":1500,"value":" there"},{"start":2000,"value":"missing"}]"#,
+        )
+        .unwrap();
+        let starts = cue_starts("Héllo there", &cues);
+        assert_eq!(starts, vec![(0, 10_000_000), (6, 15_000_000)]);
+
+        let lyric = Lyric::with_word_starts("Héllo there".to_string(), Some(0), &starts);
+        let words: Vec<_> = lyric.words.iter().map(|w| w.text.as_str()).collect();
+        assert_eq!(words, vec!["Héllo ", "there"]);
+    }
+
+    #[test]
+    fn test_album_info_from() {
+        let album: Album = serde_json::from_str(
+            r#"{"id":"al-1","name":"Album","isCompilation":true,"releaseTypes":["Album","Live"],
+            "recordLabels":[{"name":"Label"}],"musicBrainzId":"mbid",
+            "discTitles":[{"disc":1,"title":""},{"disc":2,"title":"Bonus"}],
+            "song":[
+              {"id":"1","title":"One","size":1000,"suffix":"flac","displayComposer":"Composer A"},
+              {"id":"2","title":"Two","size":500,"suffix":"mp3","contributors":[
+                {"role":"composer","artist":{"id":"a","name":"Composer B"}},
+                {"role":"performer","subRole":"Guitar","artist":{"id":"b","name":"Player"}}]},
+              {"id":"3","title":"Three","size":250,"suffix":"FLAC","displayComposer":"Composer A"}
+            ]}"#,
+        )
+        .unwrap();
+        let info = album_info_from(album);
+        assert!(info.is_compilation);
+        assert_eq!(info.release_types, vec!["Album", "Live"]);
+        assert_eq!(info.labels, vec!["Label"]);
+        assert_eq!(info.disc_titles.len(), 1);
+        assert_eq!(info.total_size, 1750);
+        assert_eq!(info.formats, vec!["FLAC", "MP3"]);
+        assert_eq!(
+            info.credits_by_role(),
+            vec![
+                ("Composer", vec!["Composer A", "Composer B"]),
+                ("Performer (Guitar)", vec!["Player"]),
+            ]
+        );
+        assert_eq!(
+            info.musicbrainz_url().as_deref(),
+            Some("https://musicbrainz.org/release/mbid")
+        );
+    }
+
+    #[test]
+    fn test_folder_from_directory() {
+        let directory: Directory = serde_json::from_str(
+            r#"{"id":"d-1","name":"Album","parent":"d-0","child":[
+              {"id":"d-2","isDir":true,"title":"CD2"},
+              {"id":"s-1","isDir":false,"title":"One","path":"Artist/Album/01 One.flac"},
+              {"id":"s-2","title":"Two","path":"Artist/Album/02 Two.flac"}
+            ]}"#,
+        )
+        .unwrap();
+        let folder = folder_from_directory(directory);
+        assert_eq!(folder.id, "d-1");
+        assert_eq!(folder.parent_id.as_deref(), Some("d-0"));
+        assert_eq!(folder.path.as_deref(), Some("Artist/Album"));
+        assert_eq!(folder.folders.len(), 1);
+        assert_eq!(folder.folders[0].name, "CD2");
+        assert_eq!(folder.song_ids, vec!["s-1", "s-2"]);
+    }
+
+    #[test]
+    fn test_podcast_channel_from() {
+        let channel: SubsonicChannel = serde_json::from_str(
+            r#"{"id":"c-1","title":"Show","coverArt":"pod-c-1","episode":[
+              {"id":"e-1","streamId":"s-1","channelId":"c-1","title":"One","duration":90,
+               "publishDate":"2024-01-02T00:00:00Z","status":"completed"},
+              {"id":"e-2","channelId":"c-1","title":"Two","status":"new"}
+            ]}"#,
+        )
+        .unwrap();
+        let channel = podcast_channel_from(channel);
+        assert_eq!(channel.image_id.as_deref(), Some("pod-c-1"));
+        assert_eq!(channel.episodes.len(), 2);
+        let first = &channel.episodes[0];
+        assert_eq!(first.channel_title, "Show");
+        assert_eq!(first.stream_id.as_deref(), Some("s-1"));
+        assert_eq!(first.run_time_ticks, 900_000_000);
+        assert_eq!(first.to_music_dto().id, "s-1");
+        assert_eq!(channel.episodes[1].stream_id, None);
+    }
+
+    #[test]
+    fn test_radio_stations() {
+        let response: SubsonicEnvelope = serde_json::from_str(
+            r#"{"subsonic-response":{"status":"ok","version":"1.16.1",
+              "internetRadioStations":{"internetRadioStation":[
+                {"id":"1","name":"Jazz FM","streamUrl":"http://jazz.example/stream","homePageUrl":""},
+                {"id":2,"name":"News","streamUrl":"https://news.example/live.mp3"}
+              ]}}}"#,
+        )
+        .unwrap();
+        let stations: Vec<_> = response
+            .response
+            .internet_radio_stations
+            .unwrap()
+            .internet_radio_station
+            .into_iter()
+            .map(radio_station_from)
+            .collect();
+        assert_eq!(stations.len(), 2);
+        assert_eq!(stations[0].stream_url, "http://jazz.example/stream");
+        assert_eq!(stations[0].homepage_url, None);
+        assert_eq!(stations[1].id, "2");
+        assert!(!stations[1].user_added);
+    }
+}
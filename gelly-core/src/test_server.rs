@@ -0,0 +1,72 @@
+//! A stand-in HTTP server for testing the clients against canned answers.
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+
+/// Answers one request with `status` and `body`. Gives back the server's
+/// address, like `http://127.0.0.1:1234`, and the request line it was asked.
+pub(crate) async fn serve_once(
+    status: &'static str,
+    body: &'static str,
+) -> (String, JoinHandle<String>) {
+    let (host, listener) = listen().await;
+    let server = tokio::spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        answer(&mut stream, status, body).await
+    });
+    (host, server)
+}
+
+/// Answers every request with `body` for as long as the test runs
+pub(crate) async fn serve_always(body: &'static str) -> String {
+    let (host, listener) = listen().await;
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            answer(&mut stream, "200 OK", body).await;
+        }
+    });
+    host
+}
+
+async fn listen() -> (String, TcpListener) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let host = format!("http://{}", listener.local_addr().unwrap());
+    (host, listener)
+}
+
+/// Reads the request, body and all, and sends the response, handing back
+/// the request line
+async fn answer(stream: &mut TcpStream, status: &str, body: &str) -> String {
+    let mut request = Vec::new();
+    let mut buffer = [0; 1024];
+    let mut expected = None;
+    while expected.is_none_or(|expected| request.len() < expected) {
+        let read = stream.read(&mut buffer).await.unwrap();
+        if read == 0 {
+            break;
+        }
+        request.extend_from_slice(&buffer[..read]);
+        if expected.is_none()
+            && let Some(end) = request.windows(4).position(|window| window == b"\r\n\r\n")
+        {
+            expected = Some(end + 4 + content_length(&request[..end]));
+        }
+    }
+    let response = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    stream.write_all(response.as_bytes()).await.unwrap();
+    let request = String::from_utf8_lossy(&request);
+    request.lines().next().unwrap_or_default().to_string()
+}
+
+fn content_length(headers: &[u8]) -> usize {
+    String::from_utf8_lossy(headers)
+        .lines()
+        .filter_map(|line| line.split_once(':'))
+        .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+        .and_then(|(_, value)| value.trim().parse().ok())
+        .unwrap_or(0)
+}
@@ -0,0 +1,75 @@
+//! Just enough XML for UPnP device descriptions and SOAP replies and XSPF
+//! playlists: finding elements by name and escaping text.
+
+/// Inner text of every `<name>` element, with or without a namespace prefix.
+/// Good enough for device descriptions, SOAP replies and playlists, which
+/// don't nest an element inside one of the same name.
+pub(crate) fn elements<'a>(xml: &'a str, name: &str) -> Vec<&'a str> {
+    let mut found = Vec::new();
+    let mut rest = xml;
+    while let Some(start) = rest.find('<') {
+        rest = &rest[start + 1..];
+        let Some(tag_end) = rest.find('>') else { break };
+        let tag = &rest[..tag_end];
+        let tag_name = tag.split_whitespace().next().unwrap_or_default();
+        let local_name = tag_name.rsplit(':').next().unwrap_or_default();
+        if local_name != name || tag.ends_with('/') {
+            continue;
+        }
+        let content = &rest[tag_end + 1..];
+        let close = format!("</{tag_name}>");
+        if let Some(end) = content.find(&close) {
+            found.push(&content[..end]);
+            rest = &content[end + close.len()..];
+        }
+    }
+    found
+}
+
+pub(crate) fn element_text(xml: &str, name: &str) -> Option<String> {
+    elements(xml, name)
+        .first()
+        .map(|text| unescape_xml(text.trim()))
+}
+
+pub(crate) fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+pub(crate) fn unescape_xml(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_elements_ignore_namespaces() {
+        let xml = r#"<s:Body><u:GetTransportInfoResponse><CurrentTransportState>PLAYING</CurrentTransportState><CurrentSpeed>1</CurrentSpeed></u:GetTransportInfoResponse></s:Body>"#;
+        assert_eq!(
+            element_text(xml, "CurrentTransportState").as_deref(),
+            Some("PLAYING")
+        );
+        assert_eq!(elements(xml, "GetTransportInfoResponse").len(), 1);
+        assert_eq!(element_text(xml, "Missing"), None);
+    }
+
+    #[test]
+    fn test_escape_round_trip() {
+        let text = r#"Simon & "Garfunkel" <live>"#;
+        assert_eq!(
+            escape_xml(text),
+            "Simon &amp; &quot;Garfunkel&quot; &lt;live&gt;"
+        );
+        assert_eq!(unescape_xml(&escape_xml(text)), text);
+        assert_eq!(unescape_xml("Rock &apos;n&apos; Roll"), "Rock 'n' Roll");
+    }
+}
@@ -0,0 +1,343 @@
+use async_channel::{Receiver, TryRecvError};
+use gelly_core::models::{AlbumModel, ArtistModel, SongModel};
+use log::warn;
+use ratatui::{
+    crossterm::event::{KeyCode, KeyEvent, KeyModifiers},
+    widgets::ListState,
+};
+use tokio::runtime::Runtime;
+
+use crate::{
+    player::Player,
+    session::{Fetched, Session, SessionError},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tab {
+    Albums,
+    Artists,
+    Songs,
+    Queue,
+}
+
+impl Tab {
+    pub const ALL: [Tab; 4] = [Tab::Albums, Tab::Artists, Tab::Songs, Tab::Queue];
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            Tab::Albums => "Albums",
+            Tab::Artists => "Artists",
+            Tab::Songs => "Songs",
+            Tab::Queue => "Queue",
+        }
+    }
+
+    fn index(&self) -> usize {
+        Tab::ALL.iter().position(|t| t == self).unwrap_or(0)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Item {
+    Album(AlbumModel),
+    Artist(ArtistModel),
+    Song(SongModel),
+}
+
+impl Item {
+    fn matches(&self, filter: &str) -> bool {
+        let haystack = match self {
+            Item::Album(album) => format!("{} {}", album.name(), album.artists_string()),
+            Item::Artist(artist) => artist.name(),
+            Item::Song(song) => format!(
+                "{} {} {}",
+                song.title(),
+                song.artists_string(),
+                song.album()
+            ),
+        };
+        haystack.to_lowercase().contains(filter)
+    }
+}
+
+/// Songs of an album or artist opened from one of the top level tabs.
+pub struct Detail {
+    pub title: String,
+    pub songs: Vec<SongModel>,
+}
+
+pub struct App {
+    pub session: Session,
+    pub player: Player,
+    pub tab: Tab,
+    pub detail: Option<Detail>,
+    pub list_state: ListState,
+    pub filter: String,
+    pub searching: bool,
+    pub status: Option<String>,
+    /// Set while the library downloads in the background
+    pub refreshing: Option<Receiver<Result<Fetched, SessionError>>>,
+    pub running: bool,
+    albums: Vec<AlbumModel>,
+    artists: Vec<ArtistModel>,
+    songs: Vec<SongModel>,
+}
+
+impl App {
+    pub fn new(session: Session) -> Self {
+        let player = Player::new(session.backend.clone());
+        let mut app = Self {
+            session,
+            player,
+            tab: Tab::Albums,
+            detail: None,
+            list_state: ListState::default(),
+            filter: String::new(),
+            searching: false,
+            status: None,
+            refreshing: None,
+            running: true,
+            albums: Vec::new(),
+            artists: Vec::new(),
+            songs: Vec::new(),
+        };
+        app.reload_models();
+        app
+    }
+
+    fn reload_models(&mut self) {
+        let library = &self.session.library;
+        self.albums = library.albums_from_library();
+        self.albums.sort_by_key(|a| a.name().to_lowercase());
+        self.artists = library.artists_from_library();
+        self.artists.sort_by_key(|a| a.name().to_lowercase());
+        self.songs = library.all_songs();
+        self.songs.sort_by_key(|s| s.title().to_lowercase());
+        self.reset_selection();
+    }
+
+    /// Rows shown in the current view, after applying the search filter.
+    pub fn items(&self) -> Vec<Item> {
+        let items: Vec<Item> = if let Some(detail) = &self.detail {
+            detail.songs.iter().cloned().map(Item::Song).collect()
+        } else {
+            match self.tab {
+                Tab::Albums => self.albums.iter().cloned().map(Item::Album).collect(),
+                Tab::Artists => self.artists.iter().cloned().map(Item::Artist).collect(),
+                Tab::Songs => self.songs.iter().cloned().map(Item::Song).collect(),
+                Tab::Queue => self
+                    .player
+                    .queue
+                    .items()
+                    .iter()
+                    .cloned()
+                    .map(Item::Song)
+                    .collect(),
+            }
+        };
+        // The queue keeps its indices so the selection maps back onto the player
+        if self.filter.is_empty() || self.showing_queue() {
+            return items;
+        }
+        let filter = self.filter.to_lowercase();
+        items.into_iter().filter(|i| i.matches(&filter)).collect()
+    }
+
+    pub fn showing_queue(&self) -> bool {
+        self.detail.is_none() && self.tab == Tab::Queue
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent, runtime: &Runtime) {
+        self.status = None;
+        if self.searching {
+            self.handle_search_key(key);
+            return;
+        }
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            self.running = false;
+            return;
+        }
+
+        match key.code {
+            KeyCode::Char('q') => self.running = false,
+            KeyCode::Tab => self.select_tab(Tab::ALL[(self.tab.index() + 1) % Tab::ALL.len()]),
+            KeyCode::BackTab => {
+                self.select_tab(Tab::ALL[(self.tab.index() + Tab::ALL.len() - 1) % Tab::ALL.len()])
+            }
+            KeyCode::Char(c @ '1'..='4') => {
+                self.select_tab(Tab::ALL[c as usize - '1' as usize]);
+            }
+            KeyCode::Down | KeyCode::Char('j') => self.move_selection(1),
+            KeyCode::Up | KeyCode::Char('k') => self.move_selection(-1),
+            KeyCode::PageDown => self.move_selection(10),
+            KeyCode::PageUp => self.move_selection(-10),
+            KeyCode::Home | KeyCode::Char('g') => self.list_state.select_first(),
+            KeyCode::End | KeyCode::Char('G') => self.list_state.select_last(),
+            KeyCode::Enter => self.activate_selected(),
+            KeyCode::Char('a') => self.enqueue_selected(),
+            KeyCode::Char('d') | KeyCode::Delete if self.showing_queue() => {
+                if let Some(index) = self.list_state.selected() {
+                    self.player.remove(index);
+                }
+            }
+            KeyCode::Esc | KeyCode::Backspace => self.go_back(),
+            KeyCode::Char('/') if !self.showing_queue() => {
+                self.searching = true;
+                self.filter.clear();
+                self.reset_selection();
+            }
+            KeyCode::Char(' ') => self.player.toggle_play(),
+            KeyCode::Char('n') => self.player.next(),
+            KeyCode::Char('p') => self.player.prev(),
+            KeyCode::Char('s') => self.player.stop(),
+            KeyCode::Right => self.player.seek_forward(),
+            KeyCode::Left => self.player.seek_backward(),
+            KeyCode::Char('+') | KeyCode::Char('=') => self.player.volume_up(),
+            KeyCode::Char('-') => self.player.volume_down(),
+            KeyCode::Char('r') => self.refresh_library(runtime),
+            _ => {}
+        }
+    }
+
+    fn handle_search_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Enter => self.searching = false,
+            KeyCode::Esc => {
+                self.searching = false;
+                self.filter.clear();
+            }
+            KeyCode::Backspace => {
+                self.filter.pop();
+            }
+            KeyCode::Char(c) => self.filter.push(c),
+            _ => return,
+        }
+        self.reset_selection();
+    }
+
+    fn select_tab(&mut self, tab: Tab) {
+        self.tab = tab;
+        self.detail = None;
+        self.filter.clear();
+        self.reset_selection();
+    }
+
+    fn go_back(&mut self) {
+        if self.detail.take().is_none() {
+            self.filter.clear();
+        }
+        self.reset_selection();
+    }
+
+    fn reset_selection(&mut self) {
+        let select = if self.items().is_empty() {
+            None
+        } else {
+            Some(0)
+        };
+        self.list_state.select(select);
+    }
+
+    fn move_selection(&mut self, delta: i32) {
+        let len = self.items().len();
+        if len == 0 {
+            return;
+        }
+        let current = self.list_state.selected().unwrap_or(0) as i32;
+        let next = (current + delta).clamp(0, len as i32 - 1);
+        self.list_state.select(Some(next as usize));
+    }
+
+    fn selected(&self) -> Option<(usize, Vec<Item>)> {
+        let index = self.list_state.selected()?;
+        let items = self.items();
+        (index < items.len()).then_some((index, items))
+    }
+
+    fn songs_for_item(&self, item: &Item) -> Vec<SongModel> {
+        let library = &self.session.library;
+        match item {
+            Item::Album(album) => library.songs_for_album(&album.id()),
+            Item::Artist(artist) => library.songs_for_artist(&artist.id()),
+            Item::Song(song) => vec![song.clone()],
+        }
+    }
+
+    fn activate_selected(&mut self) {
+        let Some((index, items)) = self.selected() else {
+            return;
+        };
+        match &items[index] {
+            Item::Album(album) => self.open_detail(album.name(), &items[index]),
+            Item::Artist(artist) => self.open_detail(artist.name(), &items[index]),
+            Item::Song(_) if self.showing_queue() => self.player.play_index(index),
+            Item::Song(_) => {
+                let songs = items
+                    .into_iter()
+                    .filter_map(|i| match i {
+                        Item::Song(song) => Some(song),
+                        _ => None,
+                    })
+                    .collect();
+                self.player.play_songs(songs, index);
+            }
+        }
+    }
+
+    fn open_detail(&mut self, title: String, item: &Item) {
+        let songs = self.songs_for_item(item);
+        self.detail = Some(Detail { title, songs });
+        self.filter.clear();
+        self.reset_selection();
+    }
+
+    fn enqueue_selected(&mut self) {
+        if self.showing_queue() {
+            return;
+        }
+        let Some((index, items)) = self.selected() else {
+            return;
+        };
+        let songs = self.songs_for_item(&items[index]);
+        self.status = Some(format!("Added {} songs to the queue", songs.len()));
+        self.player.enqueue(songs);
+    }
+
+    fn refresh_library(&mut self, runtime: &Runtime) {
+        if self.refreshing.is_none() {
+            self.refreshing = Some(self.session.refresh(runtime));
+        }
+    }
+
+    /// Picks up the library once a refresh has finished downloading it
+    pub fn process_refresh(&mut self) {
+        let Some(receiver) = &self.refreshing else {
+            return;
+        };
+        let result = match receiver.try_recv() {
+            Ok(result) => result,
+            Err(TryRecvError::Empty) => return,
+            Err(TryRecvError::Closed) => {
+                self.refreshing = None;
+                self.status = Some("Failed to refresh library".to_string());
+                return;
+            }
+        };
+        self.refreshing = None;
+        match result {
+            Ok(fetched) => {
+                self.session.apply(fetched);
+                self.detail = None;
+                self.reload_models();
+                self.status = Some(format!(
+                    "Library refreshed, {} songs",
+                    self.session.library.library_size()
+                ));
+            }
+            Err(e) => {
+                warn!("Failed to refresh library: {}", e);
+                self.status = Some(format!("Failed to refresh library: {e}"));
+            }
+        }
+    }
+}
@@ -0,0 +1,89 @@
+use std::{fs, io, time::Duration};
+
+use gelly_core::cache::get_cache_directory;
+use log::info;
+use ratatui::{
+    DefaultTerminal,
+    crossterm::event::{self, Event, KeyEventKind},
+};
+use tokio::runtime::Runtime;
+
+use app::App;
+use session::Session;
+
+mod app;
+mod player;
+mod session;
+mod ui;
+
+const TICK: Duration = Duration::from_millis(100);
+
+fn main() -> io::Result<()> {
+    init_logging();
+    let runtime = Runtime::new()?;
+
+    // The player sends its events from the default main context, which we
+    // iterate by hand instead of running a glib main loop.
+    let context = glib::MainContext::default();
+    let _guard = context
+        .acquire()
+        .map_err(|e| io::Error::other(e.to_string()))?;
+
+    println!("Loading library…");
+    let session = match Session::load(&runtime) {
+        Ok(session) => session,
+        Err(e) => {
+            eprintln!("{e}");
+            std::process::exit(1);
+        }
+    };
+    info!("TUI started");
+
+    let mut app = App::new(session);
+    let terminal = ratatui::init();
+    let result = run(terminal, &mut app, &runtime, &context);
+    ratatui::restore();
+    result
+}
+
+fn run(
+    mut terminal: DefaultTerminal,
+    app: &mut App,
+    runtime: &Runtime,
+    context: &glib::MainContext,
+) -> io::Result<()> {
+    while app.running {
+        while context.iteration(false) {}
+        app.player.process_events();
+        app.process_refresh();
+
+        terminal.draw(|frame| ui::draw(frame, app))?;
+
+        if event::poll(TICK)?
+            && let Event::Key(key) = event::read()?
+            && key.kind == KeyEventKind::Press
+        {
+            app.handle_key(key, runtime);
+        }
+    }
+    app.player.stop();
+    Ok(())
+}
+
+/// Logs go to a file in the cache directory, stderr would draw over the UI.
+fn init_logging() {
+    if std::env::var_os("RUST_LOG").is_none() {
+        return;
+    }
+    let Ok(path) = get_cache_directory("gelly-tui.log") else {
+        return;
+    };
+    if let Some(parent) = path.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+    if let Ok(file) = fs::File::create(path) {
+        env_logger::Builder::from_default_env()
+            .target(env_logger::Target::Pipe(Box::new(file)))
+            .init();
+    }
+}
@@ -0,0 +1,164 @@
+use async_channel::Receiver;
+use gelly_core::{
+    audio::{
+        player::{AudioPlayer, PlayerEvent, PlayerState},
+        queue::PlayQueue,
+    },
+    backend::Backend,
+    config,
+    models::SongModel,
+};
+use log::warn;
+
+const SEEK_STEP_SECONDS: u64 = 10;
+const VOLUME_STEP: f64 = 0.05;
+
+/// Play queue on top of the core gstreamer player. Events are drained from
+/// the player's channel once per tick by the main loop.
+pub struct Player {
+    player: AudioPlayer,
+    events: Receiver<PlayerEvent>,
+    backend: Backend,
+    pub queue: PlayQueue<SongModel>,
+    pub state: PlayerState,
+    pub position: u64,
+    pub duration: u64,
+    pub volume: f64,
+    pub last_error: Option<String>,
+}
+
+impl Player {
+    pub fn new(backend: Backend) -> Self {
+        let (player, events) = AudioPlayer::new();
+        let volume = config::get_volume();
+        // The setting stores the cubic volume the sliders show, the player takes linear
+        player.set_volume(volume.powi(3));
+        Self {
+            player,
+            events,
+            backend,
+            queue: PlayQueue::default(),
+            state: PlayerState::Stopped,
+            position: 0,
+            duration: 0,
+            volume,
+            last_error: None,
+        }
+    }
+
+    pub fn current_song(&self) -> Option<&SongModel> {
+        self.queue.current_item()
+    }
+
+    pub fn process_events(&mut self) {
+        while let Ok(event) = self.events.try_recv() {
+            match event {
+                PlayerEvent::StateChanged(state) => self.state = state,
+                PlayerEvent::PositionChanged(position) => self.position = position,
+                PlayerEvent::DurationChanged(duration) => self.duration = duration,
+                PlayerEvent::EndOfStream => self.next(),
+                PlayerEvent::Error(message) => {
+                    warn!("Playback error: {}", message);
+                    self.last_error = Some(message);
+                    self.stop();
+                }
+                // Live streams name what's on as they go
+                PlayerEvent::TitleChanged(title) => {
+                    if let Some(song) = self.current_song().filter(|song| song.is_live()) {
+                        song.set_title(&title);
+                    }
+                }
+                PlayerEvent::AboutToFinish | PlayerEvent::StreamStarted => {}
+            }
+        }
+    }
+
+    /// Replaces the queue and starts playing at `index`.
+    pub fn play_songs(&mut self, songs: Vec<SongModel>, index: usize) {
+        self.queue.replace(songs);
+        self.play_index(index);
+    }
+
+    pub fn enqueue(&mut self, songs: Vec<SongModel>) {
+        self.queue.extend(songs);
+    }
+
+    pub fn remove(&mut self, index: usize) {
+        if self.queue.remove(index) {
+            self.stop();
+        }
+    }
+
+    pub fn play_index(&mut self, index: usize) {
+        let Some(song) = self.queue.select(index).cloned() else {
+            self.stop();
+            return;
+        };
+        let uri = self.backend.get_stream_uri(&song.id());
+        self.player.stop();
+        self.player.set_uri(&uri);
+        self.player.play();
+        self.position = 0;
+        self.duration = song.duration_seconds();
+        self.last_error = None;
+    }
+
+    pub fn toggle_play(&mut self) {
+        match self.state {
+            PlayerState::Playing => self.player.pause(),
+            PlayerState::Paused => self.player.play(),
+            PlayerState::Stopped => {
+                if !self.queue.is_empty() {
+                    self.play_index(self.queue.current().unwrap_or(0));
+                }
+            }
+        }
+    }
+
+    pub fn next(&mut self) {
+        match self.queue.next_index() {
+            Some(next) => self.play_index(next),
+            None => self.stop(),
+        }
+    }
+
+    pub fn prev(&mut self) {
+        if let Some(prev) = self.queue.prev_index() {
+            self.play_index(prev);
+        }
+    }
+
+    pub fn stop(&mut self) {
+        self.player.stop();
+        self.state = PlayerState::Stopped;
+        self.position = 0;
+    }
+
+    pub fn seek_forward(&mut self) {
+        self.seek_to((self.position + SEEK_STEP_SECONDS).min(self.duration));
+    }
+
+    pub fn seek_backward(&mut self) {
+        self.seek_to(self.position.saturating_sub(SEEK_STEP_SECONDS));
+    }
+
+    fn seek_to(&mut self, position: u64) {
+        if self.state != PlayerState::Stopped && self.player.seek(position).is_ok() {
+            self.position = position;
+        }
+    }
+
+    fn change_volume(&mut self, delta: f64) {
+        self.volume = (self.volume + delta).clamp(0.0, 1.0);
+        self.player.set_volume(self.volume.powi(3));
+        config::set_volume(self.volume);
+    }
+
+    pub fn volume_up(&mut self) {
+        self.change_volume(VOLUME_STEP);
+    }
+
+    pub fn volume_down(&mut self) {
+        self.change_volume(-VOLUME_STEP);
+    }
+}
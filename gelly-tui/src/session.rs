@@ -0,0 +1,130 @@
+use async_channel::Receiver;
+use gelly_core::{
+    backend::{Backend, BackendError},
+    cache::{CacheError, Cacheable, LibraryCache},
+    config::settings,
+    jellyfin::api::{FavoriteDtoList, MusicDtoList},
+    library::Library,
+};
+use gio::prelude::SettingsExt;
+use log::{debug, warn};
+use std::future::Future;
+use thiserror::Error;
+use tokio::runtime::Runtime;
+
+#[derive(Error, Debug)]
+pub enum SessionError {
+    #[error("Gelly is not set up yet, log in and pick a library with the desktop app first")]
+    NotConfigured,
+
+    #[error("Backend error: {0}")]
+    Backend(#[from] BackendError),
+
+    #[error("Cache error: {0}")]
+    Cache(#[from] CacheError),
+}
+
+/// Songs and favorites straight from the server
+pub struct Fetched {
+    songs: MusicDtoList,
+    favorites: FavoriteDtoList,
+}
+
+/// Server connection and library, shared with the desktop app through the
+/// same settings, keyring entries and library cache.
+pub struct Session {
+    pub backend: Backend,
+    pub library: Library,
+    cache: Option<LibraryCache>,
+    library_id: String,
+}
+
+impl Session {
+    pub fn load(runtime: &Runtime) -> Result<Self, SessionError> {
+        let backend = Backend::from_settings();
+        let library_id: String = settings().string("library-id").into();
+        if !backend.is_authenticated() || library_id.is_empty() {
+            return Err(SessionError::NotConfigured);
+        }
+
+        let cache = LibraryCache::new()
+            .inspect_err(|e| warn!("Library cache unavailable: {}", e))
+            .ok();
+
+        let session = Self {
+            backend,
+            library: Library::new(),
+            cache,
+            library_id,
+        };
+        session.load_library(runtime)?;
+        Ok(session)
+    }
+
+    /// Loads songs and favorites from the cache, going to the server when the
+    /// cache is empty.
+    fn load_library(&self, runtime: &Runtime) -> Result<(), SessionError> {
+        let songs: MusicDtoList =
+            self.cached_or_fetch(runtime, || self.backend.get_library(&self.library_id))?;
+        let favorites: FavoriteDtoList =
+            self.cached_or_fetch(runtime, || self.backend.get_favorites())?;
+        self.apply(Fetched { songs, favorites });
+        Ok(())
+    }
+
+    /// Downloads songs and favorites again on the runtime, so the caller can
+    /// keep drawing meanwhile. The result arrives on the returned channel and
+    /// goes into the library with [`Session::apply`].
+    pub fn refresh(&self, runtime: &Runtime) -> Receiver<Result<Fetched, SessionError>> {
+        let (sender, receiver) = async_channel::bounded(1);
+        let backend = self.backend.clone();
+        let library_id = self.library_id.clone();
+        let cache = self.cache.clone();
+        runtime.spawn(async move {
+            let result = async {
+                let songs = backend.get_library(&library_id).await?;
+                save_to_cache(cache.as_ref(), &songs);
+                let favorites = backend.get_favorites().await?;
+                save_to_cache(cache.as_ref(), &favorites);
+                Ok::<_, SessionError>(Fetched { songs, favorites })
+            }
+            .await;
+            let _ = sender.send(result).await;
+        });
+        receiver
+    }
+
+    pub fn apply(&self, fetched: Fetched) {
+        self.library.update_songs(fetched.songs.items);
+        self.library.update_favorites(&fetched.favorites.items);
+    }
+
+    fn cached_or_fetch<T, F, Fut>(&self, runtime: &Runtime, fetch: F) -> Result<T, SessionError>
+    where
+        T: Cacheable,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, BackendError>>,
+    {
+        if let Some(cache) = &self.cache {
+            match cache.load::<T>() {
+                Ok(collection) => {
+                    debug!("Loaded {} from cache", T::CACHE_FILE_NAME);
+                    return Ok(collection);
+                }
+                Err(e) => debug!("Cache miss for {}: {}", T::CACHE_FILE_NAME, e),
+            }
+        }
+
+        let collection = runtime.block_on(fetch())?;
+        save_to_cache(self.cache.as_ref(), &collection);
+        Ok(collection)
+    }
+}
+
+fn save_to_cache<T: Cacheable>(cache: Option<&LibraryCache>, collection: &T) {
+    if let Some(cache) = cache
+        && let Err(e) = cache.save(collection)
+    {
+        warn!("Failed to save collection to cache: {}", e);
+    }
+}
@@ -0,0 +1,167 @@
+use gelly_core::audio::player::PlayerState;
+use ratatui::{
+    Frame,
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Modifier, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, Borders, Gauge, List, ListItem, Paragraph, Tabs},
+};
+
+use crate::app::{App, Item, Tab};
+
+const HELP: &str = "Enter play/open  a enqueue  / search  Space pause  n/p next/prev  \
+                    ←/→ seek  +/- volume  d remove  r refresh  Esc back  q quit";
+
+pub fn draw(frame: &mut Frame, app: &mut App) {
+    let [tabs_area, list_area, player_area, footer_area] = Layout::vertical([
+        Constraint::Length(1),
+        Constraint::Min(3),
+        Constraint::Length(4),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+
+    draw_tabs(frame, app, tabs_area);
+    draw_list(frame, app, list_area);
+    draw_player(frame, app, player_area);
+    draw_footer(frame, app, footer_area);
+}
+
+fn draw_tabs(frame: &mut Frame, app: &App, area: Rect) {
+    let titles = Tab::ALL
+        .iter()
+        .enumerate()
+        .map(|(i, tab)| format!("{} {}", i + 1, tab.title()));
+    let selected = Tab::ALL.iter().position(|t| *t == app.tab);
+    let tabs = Tabs::new(titles)
+        .select(selected)
+        .highlight_style(Style::new().fg(Color::Cyan).add_modifier(Modifier::BOLD));
+    frame.render_widget(tabs, area);
+}
+
+fn draw_list(frame: &mut Frame, app: &mut App, area: Rect) {
+    let items = app.items();
+    let queue_current = app
+        .showing_queue()
+        .then_some(app.player.queue.current())
+        .flatten();
+
+    let rows: Vec<ListItem> = items
+        .iter()
+        .enumerate()
+        .map(|(i, item)| {
+            let line = item_line(item);
+            if queue_current == Some(i) {
+                ListItem::new(line.bold().fg(Color::Cyan))
+            } else {
+                ListItem::new(line)
+            }
+        })
+        .collect();
+
+    let title = match &app.detail {
+        Some(detail) => detail.title.clone(),
+        None => app.tab.title().to_string(),
+    };
+    let title = format!(" {} ({}) ", title, items.len());
+
+    let list = List::new(rows)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .highlight_style(Style::new().add_modifier(Modifier::REVERSED))
+        .highlight_symbol("> ");
+    frame.render_stateful_widget(list, area, &mut app.list_state);
+}
+
+fn item_line(item: &Item) -> Line<'static> {
+    match item {
+        Item::Album(album) => Line::from(vec![
+            Span::raw(album.name()),
+            Span::raw("  "),
+            Span::styled(album.artists_string(), Style::new().dark_gray()),
+        ]),
+        Item::Artist(artist) => Line::raw(artist.name()),
+        Item::Song(song) => Line::from(vec![
+            Span::raw(song.title()),
+            Span::raw("  "),
+            Span::styled(
+                format!("{} · {}", song.artists_string(), song.album()),
+                Style::new().dark_gray(),
+            ),
+            Span::raw("  "),
+            Span::styled(
+                format_time(song.duration_seconds()),
+                Style::new().dark_gray(),
+            ),
+        ]),
+    }
+}
+
+fn draw_player(frame: &mut Frame, app: &App, area: Rect) {
+    let player = &app.player;
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(" Now Playing ");
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let [info_area, progress_area] =
+        Layout::vertical([Constraint::Length(1), Constraint::Length(1)]).areas(inner);
+
+    let state = match player.state {
+        PlayerState::Playing => "▶",
+        PlayerState::Paused => "⏸",
+        PlayerState::Stopped => "■",
+    };
+    let info = match player.current_song() {
+        Some(song) => Line::from(vec![
+            Span::raw(format!("{state} ")),
+            Span::styled(song.title(), Style::new().bold()),
+            Span::raw(format!(" — {}", song.artists_string())),
+            Span::styled(
+                format!("  vol {:.0}%", player.volume * 100.0),
+                Style::new().dark_gray(),
+            ),
+        ]),
+        None => Line::raw(format!("{state} Nothing playing")),
+    };
+    frame.render_widget(Paragraph::new(info), info_area);
+
+    let ratio = if player.duration > 0 {
+        (player.position as f64 / player.duration as f64).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    let gauge = Gauge::default()
+        .gauge_style(Style::new().fg(Color::Cyan))
+        .ratio(ratio)
+        .label(format!(
+            "{} / {}",
+            format_time(player.position),
+            format_time(player.duration)
+        ));
+    frame.render_widget(gauge, progress_area);
+}
+
+fn draw_footer(frame: &mut Frame, app: &App, area: Rect) {
+    let line = if app.searching {
+        Line::from(vec![
+            Span::styled("/", Style::new().cyan()),
+            Span::raw(&app.filter),
+        ])
+    } else if let Some(error) = &app.player.last_error {
+        Line::styled(error.clone(), Style::new().red())
+    } else if app.refreshing.is_some() {
+        Line::raw("Refreshing…")
+    } else if let Some(status) = &app.status {
+        Line::raw(status.clone())
+    } else if !app.filter.is_empty() {
+        Line::raw(format!("Filter: {}  (Esc to clear)", app.filter))
+    } else {
+        Line::styled(HELP, Style::new().dark_gray())
+    };
+    frame.render_widget(Paragraph::new(line), area);
+}
+
+fn format_time(seconds: u64) -> String {
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
@@ -10,17 +10,30 @@ use crate::backend::Backend;
 use crate::backend::BackendError;
 use crate::cache::{Cacheable, ImageCache, LibraryCache};
 use crate::cli::add_cli_options;
-use crate::config::{
-    self, BackendType, retrieve_jellyfin_api_token, retrieve_subsonic_password, settings,
+use crate::config::{self, settings};
+use crate::history::{PlayHistory, PlayRecord};
+use crate::jellyfin::api::{
+    FavoriteDtoList, MusicDtoList, PlaylistDto, PlaylistDtoList, RatingDtoList, SongCreditsDto,
+    SongFileDto,
 };
-use crate::jellyfin::Jellyfin;
-use crate::jellyfin::api::{FavoriteDtoList, MusicDtoList, PlaylistDto, PlaylistDtoList};
 use crate::library::Library;
-use crate::subsonic::Subsonic;
+use crate::models::{Category, SongModel};
+use crate::playlist_folders::PlaylistFolders;
+use crate::podcast::PodcastStore;
+use crate::radio::RadioStore;
+use crate::remote::{SessionConnection, syncplay::SyncPlay};
+use crate::resume::{ResumeStore, resume_position};
 use log::{debug, error, warn};
 use std::cell::RefCell;
 use std::rc::Rc;
 use std::sync::atomic::Ordering;
+use std::time::{Duration, Instant};
+
+/// How often to save where we are in an episode or long track while it plays
+const POSITION_SAVE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Seconds to wait for more library changes before writing the cache
+const LIBRARY_CACHE_DELAY: u32 = 10;
 
 glib::wrapper! {
     pub struct Application(ObjectSubclass<imp::Application>)
@@ -38,7 +51,13 @@ impl Application {
         app.initialize_backend();
         app.initialize_library_cache();
         app.initialize_image_cache();
+        app.initialize_podcast_store();
+        app.initialize_resume_store();
+        app.initialize_play_history();
+        app.initialize_playlist_folders();
+        app.initialize_radio_store();
         app.initialize_audio_model();
+        app.initialize_syncplay();
         app.initialize_cli();
         app
     }
@@ -54,24 +73,7 @@ impl Application {
     }
 
     pub fn initialize_backend(&self) {
-        let host = settings().string("hostname");
-
-        let backend = match config::get_backend_type() {
-            BackendType::Jellyfin => {
-                let user_id = settings().string("user-id");
-                let token = retrieve_jellyfin_api_token(host.as_str(), user_id.as_str())
-                    .unwrap_or_default();
-                Backend::Jellyfin(Jellyfin::new(host.as_str(), &token, user_id.as_str()))
-            }
-            BackendType::Subsonic => {
-                let username = settings().string("subsonic-username");
-                let password = retrieve_subsonic_password(host.as_str(), username.as_str())
-                    .unwrap_or_default();
-                Backend::Subsonic(Subsonic::new(host.as_str(), username.as_str(), &password))
-            }
-        };
-
-        self.imp().backend.replace(backend);
+        self.imp().backend.replace(Backend::from_settings());
     }
 
     pub fn initialize_library_cache(&self) {
@@ -100,6 +102,26 @@ impl Application {
         }
     }
 
+    pub fn initialize_podcast_store(&self) {
+        self.imp().podcast_store.replace(PodcastStore::load());
+    }
+
+    pub fn initialize_resume_store(&self) {
+        self.imp().resume_store.replace(ResumeStore::load());
+    }
+
+    pub fn initialize_play_history(&self) {
+        self.imp().play_history.replace(PlayHistory::load());
+    }
+
+    pub fn initialize_playlist_folders(&self) {
+        self.imp().playlist_folders.replace(PlaylistFolders::load());
+    }
+
+    pub fn initialize_radio_store(&self) {
+        self.imp().radio_store.replace(RadioStore::load());
+    }
+
     pub fn initialize_cli(&self) {
         add_cli_options(self);
     }
@@ -114,6 +136,12 @@ impl Application {
                 #[weak(rename_to = app)]
                 self,
                 move |_audio_model: AudioModel, song_id: &str| -> String {
+                    // Episodes kept for offline listening play from disk
+                    if let Some(path) = app.podcast_store().borrow().downloaded_file(song_id)
+                        && let Ok(uri) = glib::filename_to_uri(&path, None)
+                    {
+                        return uri.to_string();
+                    }
                     app.backend().get_stream_uri(song_id)
                 }
             ),
@@ -155,6 +183,9 @@ impl Application {
                 self,
                 move |_audio_model: AudioModel| {
                     app.update_inhibit(false);
+                    app.save_podcast_store();
+                    app.save_resume_store();
+                    app.sync_track_position();
                 }
             ),
         );
@@ -171,9 +202,253 @@ impl Application {
             ),
         );
 
+        audio_model.connect_notify_local(
+            Some("position"),
+            glib::clone!(
+                #[weak(rename_to = app)]
+                self,
+                move |audio_model, _| {
+                    app.record_episode_position(audio_model);
+                    app.record_track_position(audio_model);
+                }
+            ),
+        );
+
         self.imp().audio_model.replace(Some(audio_model));
     }
 
+    /// Remembers how far into a podcast episode we are
+    fn record_episode_position(&self, audio_model: &AudioModel) {
+        let Some(song) = audio_model.current_song().filter(|song| song.is_episode()) else {
+            return;
+        };
+        // Loading the episode starts at zero before it's seeked to where we were
+        let position = audio_model.position() as u64;
+        if position == 0 {
+            return;
+        }
+        let finished = self.imp().podcast_store.borrow_mut().set_position(
+            &song.id(),
+            position,
+            song.duration_seconds(),
+        );
+        if finished {
+            self.save_podcast_store();
+            self.emit_by_name::<()>("podcast-episodes-changed", &[]);
+        } else if self.position_save_due() {
+            self.save_podcast_store();
+        }
+    }
+
+    /// Whether it's been long enough since the position was last saved,
+    /// counting this as a save when it has
+    fn position_save_due(&self) -> bool {
+        let saved_at = &self.imp().position_saved_at;
+        if saved_at
+            .get()
+            .is_some_and(|saved_at| saved_at.elapsed() < POSITION_SAVE_INTERVAL)
+        {
+            return false;
+        }
+        saved_at.set(Some(Instant::now()));
+        true
+    }
+
+    pub fn podcast_store(&self) -> Rc<RefCell<PodcastStore>> {
+        self.imp().podcast_store.clone()
+    }
+
+    pub fn save_podcast_store(&self) {
+        spawn_tokio(self.imp().podcast_store.borrow().save(), |result| {
+            if let Err(err) = result {
+                warn!("Failed to save podcast episodes: {}", err);
+            }
+        });
+    }
+
+    /// Writes out what's kept on this computer before the app exits, where
+    /// saves still running in the background could be cut short
+    fn flush_stores(&self) {
+        if let Err(err) = self.imp().podcast_store.borrow().save_now() {
+            warn!("Failed to save podcast episodes: {}", err);
+        }
+        if let Err(err) = self.imp().resume_store.borrow().save_now() {
+            warn!("Failed to save track positions: {}", err);
+        }
+        if let Err(err) = self.imp().playlist_folders.borrow().save_now() {
+            warn!("Failed to save playlist folders: {}", err);
+        }
+        if let Err(err) = self.imp().radio_store.borrow().save_now() {
+            warn!("Failed to save radio stations: {}", err);
+        }
+    }
+
+    /// The current song when it's long enough to remember where it was
+    /// stopped
+    fn resumable_song(&self, audio_model: &AudioModel) -> Option<SongModel> {
+        let threshold = config::get_resume_threshold_seconds()?;
+        audio_model.current_song().filter(|song| {
+            !song.is_episode()
+                && !song.is_audiobook()
+                && !song.is_live()
+                && song.duration_seconds() >= threshold
+        })
+    }
+
+    /// Remembers how far into a long track we are
+    fn record_track_position(&self, audio_model: &AudioModel) {
+        let Some(song) = self.resumable_song(audio_model) else {
+            return;
+        };
+        // Loading the track starts at zero before any resume seek
+        let position = audio_model.position() as u64;
+        if position == 0 {
+            return;
+        }
+        self.imp().resume_store.borrow_mut().set_position(
+            &song.id(),
+            position,
+            song.duration_seconds(),
+        );
+        if self.position_save_due() {
+            self.save_resume_store();
+            self.sync_track_position();
+        }
+    }
+
+    /// Lets the server know where we are in the current long track, so other
+    /// devices can pick it up from there
+    fn sync_track_position(&self) {
+        let Some(song) = self
+            .audio_model()
+            .and_then(|audio_model| self.resumable_song(&audio_model))
+        else {
+            return;
+        };
+        let song_id = song.id();
+        let position = self.imp().resume_store.borrow().position(&song_id);
+        song.set_server_position(position);
+        let backend = self.backend();
+        spawn_tokio(
+            async move {
+                backend
+                    .set_playback_position(&song_id, position * 10_000_000)
+                    .await
+            },
+            |result| {
+                if let Err(err) = result {
+                    warn!("Failed to save playback position on the server: {}", err);
+                }
+            },
+        );
+    }
+
+    /// Where to pick a long track up from, if it was stopped partway through
+    /// here or on another device
+    pub fn resume_position(&self, song: &SongModel) -> Option<u64> {
+        let threshold = config::get_resume_threshold_seconds()?;
+        let duration = song.duration_seconds();
+        if song.is_episode() || song.is_audiobook() || duration < threshold {
+            return None;
+        }
+        let store = self.imp().resume_store.borrow();
+        let position = if store.contains(&song.id()) {
+            store.position(&song.id())
+        } else {
+            song.server_position()
+        };
+        resume_position(position, duration)
+    }
+
+    pub fn resume_store(&self) -> Rc<RefCell<ResumeStore>> {
+        self.imp().resume_store.clone()
+    }
+
+    pub fn save_resume_store(&self) {
+        spawn_tokio(self.imp().resume_store.borrow().save(), |result| {
+            if let Err(err) = result {
+                warn!("Failed to save track positions: {}", err);
+            }
+        });
+    }
+
+    pub fn playlist_folders(&self) -> Rc<RefCell<PlaylistFolders>> {
+        self.imp().playlist_folders.clone()
+    }
+
+    pub fn save_playlist_folders(&self) {
+        spawn_tokio(self.imp().playlist_folders.borrow().save(), |result| {
+            if let Err(err) = result {
+                warn!("Failed to save playlist folders: {}", err);
+            }
+        });
+    }
+
+    pub fn radio_store(&self) -> Rc<RefCell<RadioStore>> {
+        self.imp().radio_store.clone()
+    }
+
+    pub fn save_radio_store(&self) {
+        spawn_tokio(self.imp().radio_store.borrow().save(), |result| {
+            if let Err(err) = result {
+                warn!("Failed to save radio stations: {}", err);
+            }
+        });
+    }
+
+    pub fn play_history(&self) -> Rc<RefCell<PlayHistory>> {
+        self.imp().play_history.clone()
+    }
+
+    /// Adds a finished play to the history log
+    pub fn record_play(&self, play: PlayRecord) {
+        let append = self.imp().play_history.borrow_mut().record(play);
+        spawn_tokio(append, |result| {
+            if let Err(err) = result {
+                warn!("Failed to save play history: {}", err);
+            }
+        });
+        self.emit_by_name::<()>("history-changed", &[]);
+    }
+
+    pub fn clear_play_history(&self) {
+        if let Err(err) = self.imp().play_history.borrow_mut().clear() {
+            warn!("Failed to clear play history: {}", err);
+        }
+        self.emit_by_name::<()>("history-changed", &[]);
+    }
+
+    pub fn initialize_syncplay(&self) {
+        let syncplay = SyncPlay::new(self);
+        if let Some(audio_model) = self.audio_model() {
+            syncplay.bind_to_audio_model(&audio_model);
+        }
+        self.imp().syncplay.replace(Some(syncplay));
+    }
+
+    /// Opens the session websocket, which only Jellyfin servers have, and
+    /// lets other clients know they can control playback through it.
+    pub fn connect_session(&self) {
+        let Some(jellyfin) = self.backend().jellyfin().cloned() else {
+            self.imp().session_connection.replace(None);
+            return;
+        };
+        let connection = SessionConnection::connect(self, jellyfin.socket_url());
+        self.imp().session_connection.replace(Some(connection));
+        spawn_tokio(
+            async move { jellyfin.post_capabilities().await },
+            |result| {
+                if let Err(err) = result {
+                    warn!("Failed to register session capabilities: {}", err);
+                }
+            },
+        );
+    }
+
+    pub fn disconnect_session(&self) {
+        self.imp().session_connection.replace(None);
+    }
+
     pub fn backend(&self) -> Backend {
         self.imp().backend.borrow().clone()
     }
@@ -204,6 +479,14 @@ impl Application {
         self.imp().audio_model.borrow().clone()
     }
 
+    pub fn syncplay(&self) -> SyncPlay {
+        self.imp()
+            .syncplay
+            .borrow()
+            .clone()
+            .expect("SyncPlay is initialized with the application")
+    }
+
     fn handle_backend_error(&self, error: BackendError, operation: &str) {
         match error {
             BackendError::AuthenticationFailed { message } => {
@@ -259,6 +542,53 @@ impl Application {
         );
     }
 
+    /// Fetches the composers or labels the library download leaves out, then
+    /// calls `done` whether or not that worked
+    pub fn load_song_credits(&self, category: Category, done: impl Fn() + 'static) {
+        let library_id = self.library_id();
+        let backend = self.backend();
+        self.http_with_loading(
+            async move { backend.get_song_credits(&library_id, category).await },
+            glib::clone!(
+                #[weak(rename_to=app)]
+                self,
+                move |result: Result<Option<Vec<SongCreditsDto>>, BackendError>| {
+                    match result {
+                        Ok(credits) => app.imp().library.set_song_credits(category, credits),
+                        Err(err) => app.handle_backend_error(err, "load_song_credits"),
+                    }
+                    done();
+                }
+            ),
+        );
+    }
+
+    /// Fetches where songs' files are and their MusicBrainz ids, for all of
+    /// the library when `song_ids` is `None`
+    pub fn load_song_files(&self, song_ids: Option<Vec<String>>, done: impl Fn() + 'static) {
+        let library_id = self.library_id();
+        let backend = self.backend();
+        let complete = song_ids.is_none();
+        self.http_with_loading(
+            async move {
+                backend
+                    .get_song_files(&library_id, song_ids.as_deref())
+                    .await
+            },
+            glib::clone!(
+                #[weak(rename_to=app)]
+                self,
+                move |result: Result<Option<Vec<SongFileDto>>, BackendError>| {
+                    match result {
+                        Ok(files) => app.imp().library.set_song_files(files, complete),
+                        Err(err) => app.handle_backend_error(err, "load_song_files"),
+                    }
+                    done();
+                }
+            ),
+        );
+    }
+
     pub fn refresh_favorites(&self, refresh_cache: bool) {
         if !refresh_cache && let Some(cache) = self.library_cache() {
             match cache.load::<FavoriteDtoList>() {
@@ -294,6 +624,43 @@ impl Application {
         );
     }
 
+    /// Album ratings. Song ratings come along with the library.
+    pub fn refresh_ratings(&self, refresh_cache: bool) {
+        if !refresh_cache && let Some(cache) = self.library_cache() {
+            match cache.load::<RatingDtoList>() {
+                Ok(ratings) => {
+                    self.imp().library.update_album_ratings(&ratings.items);
+                    self.emit_by_name::<()>("ratings-updated", &[]);
+                    debug!("Loaded ratings from cache");
+                }
+                Err(error) => warn!("Failed to load ratings cache: {}. Refreshing.", error),
+            }
+        }
+        let library_id = self.imp().library_id.borrow().clone();
+        let backend = self.backend();
+        if !backend.is_authenticated() {
+            debug!("Not authenticated, skipping ratings refresh");
+            return;
+        }
+        self.http_with_loading(
+            async move { backend.get_album_ratings(&library_id).await },
+            glib::clone!(
+                #[weak(rename_to=app)]
+                self,
+                move |result: Result<RatingDtoList, BackendError>| {
+                    match result {
+                        Ok(ratings) => {
+                            app.imp().library.update_album_ratings(&ratings.items);
+                            app.cache_ratings();
+                            app.emit_by_name::<()>("ratings-updated", &[]);
+                        }
+                        Err(err) => app.handle_backend_error(err, "refresh_ratings"),
+                    }
+                },
+            ),
+        );
+    }
+
     pub fn refresh_playlists(&self, refresh_cache: bool) {
         if !refresh_cache && let Some(cache) = self.library_cache() {
             match cache.load::<PlaylistDtoList>() {
@@ -342,6 +709,69 @@ impl Application {
         }
     }
 
+    /// Saves the library as it is now, after applying incremental updates.
+    /// Updates come in bursts, so the write waits for them to settle and
+    /// happens off the main thread.
+    pub fn cache_library(&self) {
+        if self.imp().library_cache_pending.replace(true) {
+            return;
+        }
+        glib::timeout_add_seconds_local_once(
+            LIBRARY_CACHE_DELAY,
+            glib::clone!(
+                #[weak(rename_to = app)]
+                self,
+                move || {
+                    if !app.imp().library_cache_pending.replace(false) {
+                        return;
+                    }
+                    let Some(cache) = app.library_cache() else {
+                        return;
+                    };
+                    let library = app.library_snapshot();
+                    spawn_tokio(
+                        async move { tokio::task::spawn_blocking(move || cache.save(&library)).await },
+                        |result| match result {
+                            Ok(Ok(())) => {}
+                            Ok(Err(e)) => warn!("Failed to save library to cache: {}", e),
+                            Err(e) => warn!("Library cache write did not finish: {}", e),
+                        },
+                    );
+                }
+            ),
+        );
+    }
+
+    /// Writes a cache update that's still waiting, before quitting
+    fn flush_library_cache(&self) {
+        if self.imp().library_cache_pending.replace(false) {
+            self.cache_collection(&self.library_snapshot());
+        }
+    }
+
+    fn library_snapshot(&self) -> MusicDtoList {
+        let items = self.imp().library.songs.borrow().clone();
+        MusicDtoList {
+            total_record_count: items.len() as u64,
+            items,
+        }
+    }
+
+    pub fn cache_favorites(&self) {
+        let favorites = FavoriteDtoList {
+            items: self.imp().library.favorites_list(),
+        };
+        self.cache_collection(&favorites);
+    }
+
+    /// Only rated albums are kept, Jellyfin hands back every album
+    pub fn cache_ratings(&self) {
+        let ratings = RatingDtoList {
+            items: self.imp().library.album_ratings_list(),
+        };
+        self.cache_collection(&ratings);
+    }
+
     pub fn clear_cache(&self) {
         if let Some(cache) = self.library_cache()
             && let Err(e) = cache.clear()
@@ -350,6 +780,10 @@ impl Application {
         }
     }
 
+    pub fn library_id(&self) -> String {
+        self.imp().library_id.borrow().clone()
+    }
+
     pub fn set_library_id(&self, library_id: &str) {
         self.imp().library_id.replace(library_id.to_string());
         self.refresh_all(true);
@@ -360,6 +794,7 @@ impl Application {
 
     pub fn refresh_all(&self, refresh_cache: bool) {
         self.refresh_favorites(refresh_cache);
+        self.refresh_ratings(refresh_cache);
         self.refresh_library(refresh_cache);
         self.refresh_playlists(refresh_cache);
     }
@@ -404,6 +839,10 @@ impl Application {
 
     pub fn logout(&self) {
         let backend = Backend::default();
+        if self.syncplay().active() {
+            self.syncplay().leave_group();
+        }
+        self.disconnect_session();
         self.clear_cache();
         self.imp().backend.replace(backend);
         self.imp().library.update_songs(Vec::new());
@@ -465,12 +904,18 @@ mod imp {
     use std::rc::Rc;
     use std::sync::OnceLock;
     use std::sync::atomic::AtomicU32;
+    use std::time::Instant;
 
     use crate::audio::model::AudioModel;
     use crate::backend::Backend;
     use crate::cache::{ImageCache, LibraryCache};
     use crate::jellyfin::api::PlaylistDto;
     use crate::library::Library;
+    use crate::playlist_folders::PlaylistFolders;
+    use crate::podcast::PodcastStore;
+    use crate::radio::RadioStore;
+    use crate::remote::{SessionConnection, syncplay::SyncPlay};
+    use crate::resume::ResumeStore;
 
     #[derive(Default)]
     pub struct Application {
@@ -481,8 +926,17 @@ mod imp {
         pub library_cache: RefCell<Option<LibraryCache>>, // TODO: remove these Option<> types
         pub image_cache: RefCell<Option<ImageCache>>,
         pub audio_model: RefCell<Option<AudioModel>>,
+        pub syncplay: RefCell<Option<SyncPlay>>,
+        pub session_connection: RefCell<Option<SessionConnection>>,
         pub http_request_count: AtomicU32,
         pub inhibit_cookie: Cell<u32>,
+        pub library_cache_pending: Cell<bool>,
+        pub position_saved_at: Cell<Option<Instant>>,
+        pub podcast_store: Rc<RefCell<PodcastStore>>,
+        pub resume_store: Rc<RefCell<ResumeStore>>,
+        pub play_history: Rc<RefCell<PlayHistory>>,
+        pub playlist_folders: Rc<RefCell<PlaylistFolders>>,
+        pub radio_store: Rc<RefCell<RadioStore>>,
     }
 
     #[glib::object_subclass]
@@ -505,6 +959,7 @@ mod imp {
                         .param_types([u64::static_type()])
                         .build(),
                     Signal::builder("favorites-updated").build(),
+                    Signal::builder("ratings-updated").build(),
                     Signal::builder("library-rescan-requested").build(),
                     Signal::builder("force-logout").build(),
                     Signal::builder("global-error")
@@ -513,11 +968,19 @@ mod imp {
                     Signal::builder("http-request-start").build(),
                     Signal::builder("http-request-end").build(),
                     Signal::builder("big-player-requested").build(),
+                    Signal::builder("podcast-episodes-changed").build(),
+                    Signal::builder("history-changed").build(),
                 ]
             })
         }
     }
-    impl ApplicationImpl for Application {}
+    impl ApplicationImpl for Application {
+        fn shutdown(&self) {
+            self.obj().flush_library_cache();
+            self.obj().flush_stores();
+            self.parent_shutdown();
+        }
+    }
     impl GtkApplicationImpl for Application {}
     impl AdwApplicationImpl for Application {}
 }
@@ -1,4 +1,6 @@
 pub mod model;
 pub mod mpris;
-pub mod player;
 pub mod stream_info;
+
+pub use gelly_core::audio::player;
+pub use gelly_core::audio::renderer;
@@ -7,10 +7,16 @@ use gtk::{
 use log::warn;
 use rand::RngExt;
 use std::cell::{Cell, RefCell};
+use std::rc::Rc;
 use std::sync::OnceLock;
 
 use crate::{
-    audio::player::{AudioPlayer, PlayerEvent, PlayerState},
+    async_utils::tokio_rt,
+    audio::{
+        player::{AudioPlayer, PlayerEvent, PlayerState},
+        renderer::{RemoteRenderer, RendererDevice, TrackMetadata},
+    },
+    audiobook,
     config::{self, BackendType},
     models::SongModel,
     reporting::{PlaybackEvent, ReportingManager},
@@ -21,6 +27,9 @@ glib::wrapper! {
     pub struct AudioModel(ObjectSubclass<imp::AudioModel>);
 }
 
+/// Events are tagged with the output they came from, the local player is 0
+const LOCAL_OUTPUT: u32 = 0;
+
 impl AudioModel {
     pub fn new() -> Self {
         let obj: Self = Object::builder().build();
@@ -40,11 +49,18 @@ impl AudioModel {
             .player
             .set(player)
             .expect("Player should only be initialized once");
+        self.listen_to_player(event_reciever, LOCAL_OUTPUT);
+    }
 
+    fn listen_to_player(&self, event_reciever: async_channel::Receiver<PlayerEvent>, output: u32) {
         let obj_weak = self.downgrade();
         glib::spawn_future_local(async move {
             while let Ok(event) = event_reciever.recv().await {
                 let Some(obj) = obj_weak.upgrade() else { break };
+                // Stragglers from an output we've switched away from
+                if obj.imp().active_output.get() != output {
+                    continue;
+                }
 
                 match event {
                     PlayerEvent::StateChanged(state) => {
@@ -75,7 +91,10 @@ impl AudioModel {
                         });
 
                         match state {
-                            PlayerState::Playing => obj.emit_by_name::<()>("play", &[]),
+                            PlayerState::Playing => {
+                                obj.apply_playback_rate();
+                                obj.emit_by_name::<()>("play", &[]);
+                            }
                             PlayerState::Paused => obj.emit_by_name::<()>("pause", &[]),
                             PlayerState::Stopped => obj.emit_by_name::<()>("stop", &[]),
                         }
@@ -112,6 +131,9 @@ impl AudioModel {
                     PlayerEvent::StreamStarted => {
                         obj.apply_volume();
                     }
+                    PlayerEvent::TitleChanged(title) => {
+                        obj.update_stream_title(&title);
+                    }
                     PlayerEvent::EndOfStream => {
                         if obj.imp().gapless_playback_active.get() {
                             obj.emit_by_name::<()>("song-finished", &[]);
@@ -156,6 +178,95 @@ impl AudioModel {
             .expect("Player should be initialized")
     }
 
+    pub fn is_remote(&self) -> bool {
+        self.imp().renderer.borrow().is_some()
+    }
+
+    /// Moves playback to a network renderer, or back to this computer with
+    /// `None`. The queue stays here, the current song carries on from where it
+    /// was if it was playing.
+    pub fn set_renderer(&self, device: Option<RendererDevice>) {
+        let imp = self.imp();
+        let was_playing = self.playing();
+        let position = self.position();
+        match imp.renderer.take() {
+            Some(renderer) => renderer.stop(),
+            None => self.player().stop(),
+        }
+
+        let output_name = device.as_ref().map(|d| d.name.clone()).unwrap_or_default();
+        match device {
+            Some(device) => {
+                let output = imp.output_count.get() + 1;
+                imp.output_count.set(output);
+                let (renderer, events) = RemoteRenderer::connect(device, tokio_rt().handle());
+                imp.renderer.replace(Some(renderer));
+                imp.active_output.set(output);
+                self.listen_to_player(events, output);
+            }
+            None => imp.active_output.set(LOCAL_OUTPUT),
+        }
+        imp.output_name.replace(output_name);
+        self.notify_output_name();
+        self.apply_volume();
+
+        let index = self.queue_index();
+        if index < 0 {
+            return;
+        }
+        if was_playing {
+            self.play_song(index as usize);
+            if position > 0 {
+                self.seek_once_playing(position);
+            }
+        } else {
+            self.cue_song(index as usize);
+        }
+    }
+
+    /// Seeks once the player has started the new song, it can't be seeked
+    /// before that.
+    pub fn seek_once_playing(&self, position: u32) {
+        let handler = Rc::new(RefCell::new(None));
+        let id = self.connect_closure(
+            "play",
+            false,
+            glib::closure_local!(
+                #[strong]
+                handler,
+                move |audio_model: AudioModel| {
+                    if let Some(id) = handler.take() {
+                        audio_model.disconnect(id);
+                    }
+                    audio_model.seek(position);
+                }
+            ),
+        );
+        handler.replace(Some(id));
+    }
+
+    /// Radio stations carry their own stream, everything else comes from the
+    /// server
+    fn song_uri(&self, song: &SongModel) -> Option<String> {
+        if song.is_live() {
+            return Some(song.stream_url());
+        }
+        self.stream_uri(&song.id())
+    }
+
+    /// Shows what a radio station is playing in place of its name
+    fn update_stream_title(&self, title: &str) {
+        let Some(song) = self.current_song().filter(|song| song.is_live()) else {
+            return;
+        };
+        if song.title() == title {
+            return;
+        }
+        song.set_title(title);
+        self.emit_by_name::<()>("metadata-changed", &[]);
+        self.report_event(PlaybackEvent::MetadataChanged { song: Some(song) });
+    }
+
     fn stream_uri(&self, song_id: &str) -> Option<String> {
         let uri: String = self.emit_by_name("request-stream-uri", &[&song_id]);
         if uri.is_empty() {
@@ -177,7 +288,23 @@ impl AudioModel {
         };
         let volume = (user_linear * multiplier).clamp(0.0, 10.0); // upper bound above one in case replaygain boosts volume
 
-        self.player().set_volume(volume);
+        match self.imp().renderer.borrow().as_ref() {
+            Some(renderer) => renderer.set_volume(volume),
+            None => self.player().set_volume(volume),
+        }
+    }
+
+    /// Episodes play at the podcast speed, everything else at normal speed
+    pub fn apply_playback_rate(&self) {
+        if self.is_remote() {
+            return;
+        }
+        let rate = if self.current_song().is_some_and(|song| song.is_episode()) {
+            config::get_podcast_playback_rate()
+        } else {
+            1.0
+        };
+        self.player().set_rate(rate);
     }
 
     pub fn queue(&self) -> Vec<SongModel> {
@@ -264,7 +391,7 @@ impl AudioModel {
 
     pub fn prepend_to_queue(&self, songs: Vec<SongModel>) {
         let current_index = self.imp().queue_index.get();
-        let index = if current_index < 1 && !self.player().is_playing() {
+        let index = if current_index < 1 && !self.is_playing() {
             0
         } else {
             current_index + 1
@@ -281,6 +408,20 @@ impl AudioModel {
         self.new_shuffle_cycle();
     }
 
+    /// Plays `song` right away from `position` seconds in, leaving the rest of
+    /// the queue to follow it
+    pub fn play_from(&self, song: SongModel, position: u32) {
+        let current_index = self.imp().queue_index.get();
+        let index = if current_index < 1 && !self.is_playing() {
+            0
+        } else {
+            current_index + 1
+        } as usize;
+        self.prepend_to_queue(vec![song]);
+        self.seek_once_playing(position);
+        self.play_song(index);
+    }
+
     pub fn clear_queue(&self) {
         self.imp().queue.remove_all();
         self.set_queue_index(-1);
@@ -296,6 +437,15 @@ impl AudioModel {
         self.play();
     }
 
+    /// Loads the song at `index` and leaves it paused at the start, so it
+    /// can be started in step with other players.
+    pub fn cue_song(&self, index: usize) {
+        self.load_song(index as i32);
+        // The transition only ends on Playing, we want to hear about Paused
+        self.imp().track_transition_in_progress.set(false);
+        self.pause();
+    }
+
     fn load_song(&self, index: i32) {
         self.imp().prefetched_next_index.set(None);
         self.imp().prefetched_next_uri.replace(None);
@@ -306,18 +456,26 @@ impl AudioModel {
             .item(index as u32)
             .and_downcast::<SongModel>()
         {
-            let Some(stream_uri) = self.stream_uri(&song.id()) else {
+            if song.is_live() {
+                // Until the stream says what's on
+                song.set_title(song.album());
+            }
+            let Some(stream_uri) = self.song_uri(&song) else {
                 self.imp().track_transition_in_progress.set(false);
                 self.set_property("loading", false);
                 self.stop();
                 return;
             };
 
-            let player = self.player();
             self.imp().track_transition_in_progress.set(true);
             self.set_property("loading", true);
-            player.stop();
-            player.set_uri(&stream_uri);
+            if let Some(renderer) = self.imp().renderer.borrow().as_ref() {
+                renderer.load(&stream_uri, Self::track_metadata(&song));
+            } else {
+                let player = self.player();
+                player.stop();
+                player.set_uri(&stream_uri);
+            }
             self.update_current_song(index, song, Some(stream_uri));
         } else {
             self.imp().track_transition_in_progress.set(false);
@@ -326,13 +484,24 @@ impl AudioModel {
         }
     }
 
+    fn track_metadata(song: &SongModel) -> TrackMetadata {
+        TrackMetadata {
+            title: song.title(),
+            artist: song.artists().join(", "),
+            album: song.album(),
+            image_url: None,
+            duration_seconds: song.duration_seconds(),
+        }
+    }
+
     fn update_current_song(&self, index: i32, song: SongModel, uri: Option<String>) {
         self.report_event(PlaybackEvent::Stopped {
             position: self.position() as u64,
         });
+        // Renderers get one song at a time, so they can't do gapless
         self.imp()
             .gapless_playback_active
-            .set(config::get_gapless_playback_enabled());
+            .set(config::get_gapless_playback_enabled() && !self.is_remote());
         self.imp().uri.replace(uri);
         self.set_queue_index(index);
         self.set_property("position", 0u32);
@@ -359,7 +528,7 @@ impl AudioModel {
                 .queue
                 .item(next_index as u32)
                 .and_downcast::<SongModel>()
-            && let Some(uri) = self.stream_uri(&song.id())
+            && let Some(uri) = self.song_uri(&song)
         {
             self.imp().prefetched_next_index.set(Some(next_index));
             self.imp().prefetched_next_uri.replace(Some(uri.clone()));
@@ -395,7 +564,13 @@ impl AudioModel {
     }
 
     fn next_index_impl(&self, peek: bool) -> Option<i32> {
-        let mode = PlaybackMode::try_from(self.playback_mode()).unwrap_or(PlaybackMode::Normal);
+        let mut mode = PlaybackMode::try_from(self.playback_mode()).unwrap_or(PlaybackMode::Normal);
+        // Books are read in order, whatever the music is doing
+        if matches!(mode, PlaybackMode::Shuffle)
+            && self.current_song().is_some_and(|song| song.is_audiobook())
+        {
+            mode = PlaybackMode::Normal;
+        }
         match mode {
             PlaybackMode::Normal => {
                 let next_index = self.queue_index() + 1;
@@ -481,11 +656,25 @@ impl AudioModel {
     }
 
     pub fn play(&self) {
-        self.player().play();
+        match self.imp().renderer.borrow().as_ref() {
+            Some(renderer) => renderer.play(),
+            None => self.player().play(),
+        }
     }
 
     pub fn pause(&self) {
-        self.player().pause();
+        match self.imp().renderer.borrow().as_ref() {
+            Some(renderer) => renderer.pause(),
+            None => self.player().pause(),
+        }
+    }
+
+    fn is_playing(&self) -> bool {
+        if self.is_remote() {
+            self.playing()
+        } else {
+            self.player().is_playing()
+        }
     }
 
     pub fn stop(&self) {
@@ -493,17 +682,96 @@ impl AudioModel {
             position: self.position() as u64,
         });
         self.imp().track_transition_in_progress.set(false);
-        self.player().stop();
+        match self.imp().renderer.borrow().as_ref() {
+            Some(renderer) => renderer.stop(),
+            None => self.player().stop(),
+        }
         self.set_property("position", 0u32);
         self.set_property("duration", 0u32);
     }
 
+    /// Jumps `seconds` forward, or back when negative, staying within the song
+    pub fn skip(&self, seconds: i64) {
+        let position = (self.position() as i64 + seconds).clamp(0, self.duration() as i64);
+        self.seek(position as u32);
+    }
+
+    pub fn current_chapter(&self) -> Option<usize> {
+        let song = self.current_song()?;
+        audiobook::chapter_at(&song.chapters(), self.position() as u64)
+    }
+
+    pub fn seek_to_chapter(&self, index: usize) {
+        if let Some(chapter) = self
+            .current_song()
+            .and_then(|song| song.chapters().get(index).cloned())
+        {
+            self.seek(chapter.start_seconds() as u32);
+        }
+    }
+
+    pub fn next_chapter(&self) {
+        let Some(song) = self.current_song() else {
+            return;
+        };
+        if let Some(start) = audiobook::next_chapter_start(&song.chapters(), self.position() as u64)
+        {
+            self.seek(start as u32);
+        }
+    }
+
+    pub fn previous_chapter(&self) {
+        let Some(song) = self.current_song() else {
+            return;
+        };
+        if let Some(start) =
+            audiobook::previous_chapter_start(&song.chapters(), self.position() as u64)
+        {
+            self.seek(start as u32);
+        }
+    }
+
+    pub fn is_live(&self) -> bool {
+        self.current_song().is_some_and(|song| song.is_live())
+    }
+
     pub fn seek(&self, position: u32) {
-        if self.player().seek(position as u64).is_ok() {
+        if self.is_live() {
+            return;
+        }
+        let seeked = match self.imp().renderer.borrow().as_ref() {
+            Some(renderer) => {
+                renderer.seek_ms(position as u64 * 1000);
+                true
+            }
+            None => self.player().seek(position as u64).is_ok(),
+        };
+        self.finish_seek(seeked, position);
+    }
+
+    /// Seek that lands on the exact millisecond rather than the nearest
+    /// keyframe, for jumping to a lyric line.
+    pub fn seek_ms(&self, position_ms: u64) {
+        if self.is_live() {
+            return;
+        }
+        let seeked = match self.imp().renderer.borrow().as_ref() {
+            Some(renderer) => {
+                renderer.seek_ms(position_ms);
+                true
+            }
+            None => self.player().seek_ms(position_ms).is_ok(),
+        };
+        self.finish_seek(seeked, (position_ms / 1000) as u32);
+    }
+
+    fn finish_seek(&self, seeked: bool, position: u32) {
+        if seeked {
             self.set_property("position", position);
             self.report_event(PlaybackEvent::Seeked {
                 position: position.into(),
             });
+            self.emit_by_name::<()>("seeked", &[&position]);
         } else {
             let msg = if config::get_backend_type() == BackendType::Subsonic {
                 "Subsonic does not support seeking transcoded streams."
@@ -514,11 +782,39 @@ impl AudioModel {
         }
     }
 
+    /// Millisecond seek used to follow a remote player. Doesn't emit `seeked`
+    /// since the change didn't come from this client.
+    pub fn sync_to_position(&self, position_ms: u64) {
+        let seeked = match self.imp().renderer.borrow().as_ref() {
+            Some(renderer) => {
+                renderer.seek_ms(position_ms);
+                true
+            }
+            None => self.player().seek_ms(position_ms).is_ok(),
+        };
+        if seeked {
+            let position = position_ms / 1000;
+            self.set_property("position", position as u32);
+            self.report_event(PlaybackEvent::Seeked { position });
+        }
+    }
+
+    pub fn get_position_ms(&self) -> u64 {
+        // Renderers only report whole seconds
+        if self.is_remote() {
+            return self.position() as u64 * 1000;
+        }
+        self.player().get_position_ms().unwrap_or(0)
+    }
+
     pub fn get_uri(&self) -> Option<String> {
         self.imp().uri.borrow().clone()
     }
 
     pub fn get_position(&self) -> u64 {
+        if self.is_remote() {
+            return self.position() as u64;
+        }
         self.player().get_position().unwrap_or(0)
     }
 
@@ -595,7 +891,10 @@ mod imp {
 
     use mpris_server::LocalServer;
 
-    use crate::{audio::player::AudioPlayer, models::SongModel};
+    use crate::{
+        audio::{player::AudioPlayer, renderer::RemoteRenderer},
+        models::SongModel,
+    };
 
     use super::*;
 
@@ -629,13 +928,20 @@ mod imp {
         #[property(get, set = Self::set_volume)]
         pub volume: Cell<f64>,
 
-        #[property(get, set)]
+        #[property(get, set = Self::set_muted)]
         pub muted: Cell<bool>,
 
         #[property(get, set = Self::set_playback_mode)]
         pub playback_mode: Cell<u32>,
 
+        /// Name of the renderer playing, empty when playing on this computer
+        #[property(get)]
+        pub output_name: RefCell<String>,
+
         pub player: OnceCell<AudioPlayer>,
+        pub renderer: RefCell<Option<RemoteRenderer>>,
+        pub active_output: Cell<u32>,
+        pub output_count: Cell<u32>,
         pub queue: gio::ListStore,
         pub mpris_server: OnceCell<LocalServer<super::AudioModel>>,
         pub reporting_manager: OnceCell<ReportingManager>,
@@ -662,7 +968,11 @@ mod imp {
                 volume: Cell::new(1.0),
                 muted: Cell::new(false),
                 playback_mode: Cell::new(0),
+                output_name: RefCell::new(String::new()),
                 player: OnceCell::new(),
+                renderer: RefCell::new(None),
+                active_output: Cell::new(LOCAL_OUTPUT),
+                output_count: Cell::new(LOCAL_OUTPUT),
                 queue: gio::ListStore::new::<SongModel>(),
                 mpris_server: OnceCell::new(),
                 reporting_manager: OnceCell::new(),
@@ -698,6 +1008,13 @@ mod imp {
                         .param_types([String::static_type()])
                         .build(),
                     glib::subclass::Signal::builder("queue-finished").build(),
+                    // The current song's title changed while it played
+                    glib::subclass::Signal::builder("metadata-changed").build(),
+                    // Bookmarks were added to or removed from the current song
+                    glib::subclass::Signal::builder("bookmarks-changed").build(),
+                    glib::subclass::Signal::builder("seeked")
+                        .param_types([u32::static_type()])
+                        .build(),
                     glib::subclass::Signal::builder("error")
                         .param_types([String::static_type()])
                         .build(),
@@ -744,7 +1061,9 @@ mod imp {
         pub fn set_muted(&self, muted: bool) {
             self.muted.set(muted);
 
-            if let Some(player) = self.player.get() {
+            if let Some(renderer) = self.renderer.borrow().as_ref() {
+                renderer.set_mute(muted);
+            } else if let Some(player) = self.player.get() {
                 player.set_mute(muted);
             }
         }
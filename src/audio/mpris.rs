@@ -252,7 +252,7 @@ impl LocalPlayerInterface for AudioModel {
     }
 
     async fn can_seek(&self) -> fdo::Result<bool> {
-        Ok(true)
+        Ok(!self.is_live())
     }
 
     async fn can_control(&self) -> fdo::Result<bool> {
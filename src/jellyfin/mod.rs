@@ -1,610 +0,0 @@
-use std::fs;
-use std::time::Duration;
-use std::{fmt::Debug, sync::OnceLock};
-
-use api::AuthenticateResponse;
-use futures::stream::{self, StreamExt};
-use log::{debug, warn};
-use reqwest::{Client, Response, StatusCode};
-use serde_json::json;
-use tokio::time::Instant;
-
-use crate::backend::BackendError;
-use crate::config;
-use crate::jellyfin::api::{
-    FavoriteDtoList, ImageType, LibraryDtoList, LyricsResponse, MusicDtoList, NewPlaylist,
-    NewPlaylistResponse, PlaybackInfo, PlaybackReport, PlaybackReportStatus, PlaylistDtoList,
-    PlaylistItems, QuickConnectResponse,
-};
-
-pub mod api;
-pub mod utils;
-
-static CLIENT_ID: &str = "Gelly";
-
-#[derive(Debug, Clone)]
-pub struct Jellyfin {
-    client: Client,
-    pub host: String,
-    pub token: String,
-    pub user_id: String,
-}
-
-impl Jellyfin {
-    pub fn new(host: &str, token: &str, user_id: &str) -> Self {
-        let client = Client::builder()
-            .user_agent(format!("Gelly/{}", config::VERSION))
-            .connect_timeout(Duration::from_secs(10))
-            .timeout(Duration::from_secs(30))
-            .build()
-            .expect("Failed to create HTTP client");
-
-        Self {
-            client,
-            host: host.to_string(),
-            token: token.to_string(),
-            user_id: user_id.to_string(),
-        }
-    }
-
-    pub fn is_authenticated(&self) -> bool {
-        !self.token.is_empty() && !self.user_id.is_empty() && !self.host.is_empty()
-    }
-
-    pub async fn new_authenticate(
-        host: &str,
-        username: &str,
-        password: &str,
-    ) -> Result<Self, BackendError> {
-        let mut jellyfin = Self::new(host, "", "");
-        let resp = jellyfin.authenticate(username, password).await?;
-        jellyfin.token = resp.access_token;
-        jellyfin.user_id = resp.user.id;
-
-        Ok(jellyfin)
-    }
-
-    pub async fn new_authenticate_quick_connect(
-        host: &str,
-        secret: &str,
-    ) -> Result<Self, BackendError> {
-        let mut jellyfin = Self::new(host, "", "");
-        let params = json!({ "secret": secret });
-        let response = jellyfin
-            .post_json("Users/authenticatewithquickconnect", &params)
-            .await?;
-        let body = jellyfin.handle_response(response).await?;
-        let resp = serde_json::from_str::<AuthenticateResponse>(&body)?;
-        jellyfin.token = resp.access_token;
-        jellyfin.user_id = resp.user.id;
-
-        Ok(jellyfin)
-    }
-
-    async fn authenticate(
-        &self,
-        username: &str,
-        password: &str,
-    ) -> Result<AuthenticateResponse, BackendError> {
-        let body = json!({
-            "Username": username,
-            "Pw": password
-        });
-        let response = self.post_json("Users/authenticatebyname", &body).await?;
-        let body = self.handle_response(response).await?;
-        Ok(serde_json::from_str(&body)?)
-    }
-
-    pub async fn get_views(&self) -> Result<LibraryDtoList, BackendError> {
-        let response = self.get("UserViews", None).await?;
-        let body = self.handle_response(response).await?;
-        Ok(serde_json::from_str(&body)?)
-    }
-
-    pub async fn get_library(&self, library_id: &str) -> Result<MusicDtoList, BackendError> {
-        // Time library download so we can keep an eye on it.
-        let now = Instant::now();
-        const LIMIT: u64 = 250;
-        const MAX_CONCURRENT_REQUESTS: usize = 4;
-        const SLOW_LOAD_THRESHOLD: Duration = Duration::from_secs(1);
-
-        // Make the first request to get total count
-        // we also measure how long it takes. If the response is slow it's a
-        // sign that the server might be a potato, and we should reduce the
-        // number of concurrent requests.
-        let mut all_items = Vec::new();
-        let first_page_started = Instant::now();
-        let first_page = self.get_library_page(library_id, 0, LIMIT).await?;
-        let first_page_elapsed = first_page_started.elapsed();
-        let total_count = first_page.total_record_count;
-
-        let concurrent_requests = if first_page_elapsed >= SLOW_LOAD_THRESHOLD {
-            1
-        } else {
-            MAX_CONCURRENT_REQUESTS
-        };
-
-        debug!(
-            "Total library items: {}, fetched first {} items in {} seconds",
-            total_count,
-            first_page.items.len(),
-            first_page_elapsed.as_secs_f32()
-        );
-        all_items.extend(first_page.items);
-
-        // If we have more items to fetch, create concurrent requests for remaining pages
-        if total_count > LIMIT {
-            let remaining_items = total_count - LIMIT;
-            let additional_pages = remaining_items.div_ceil(LIMIT);
-
-            debug!(
-                "Fetching {} additional pages with max {} concurrent requests",
-                additional_pages, concurrent_requests
-            );
-
-            // Create futures for all remaining pages using the futures crate
-            let mut page_stream = stream::iter(1..=additional_pages)
-                .map(|page| {
-                    let start_index = page * LIMIT;
-                    self.get_library_page(library_id, start_index, LIMIT)
-                })
-                .buffer_unordered(concurrent_requests);
-
-            while let Some(result) = page_stream.next().await {
-                match result {
-                    Ok(page) => {
-                        debug!("Fetched page with {} items", page.items.len());
-                        all_items.extend(page.items);
-                    }
-                    Err(e) => {
-                        warn!("Failed to fetch library page: {}", e);
-                        // Continue with other pages rather than failing completely
-                    }
-                }
-            }
-        }
-
-        let elapsed = now.elapsed();
-        debug!(
-            "Total time taken to fetch library: {:?} Limit: {}",
-            elapsed, LIMIT
-        );
-
-        debug!("Total items collected: {}", all_items.len());
-
-        // Create the final result with all items
-        let final_result = MusicDtoList {
-            items: all_items,
-            total_record_count: total_count,
-        };
-
-        Ok(final_result)
-    }
-
-    async fn get_library_page(
-        &self,
-        library_id: &str,
-        start_index: u64,
-        limit: u64,
-    ) -> Result<MusicDtoList, BackendError> {
-        let start_index = start_index.to_string();
-        let limit = limit.to_string();
-        let params = vec![
-            ("parentId", library_id),
-            ("IncludeItemTypes", "Audio"),
-            ("sortBy", "DateCreated"),
-            ("sortOrder", "Descending"),
-            ("recursive", "true"),
-            ("fields", "DateCreated,Genres"),
-            ("ImageTypeLimit", "1"),
-            ("EnableImageTypes", "Primary"),
-            ("StartIndex", &start_index),
-            ("Limit", &limit),
-        ];
-
-        let response = self.get("Items", Some(&params)).await?;
-        let body = self.handle_response(response).await?;
-        Ok(serde_json::from_str(&body)?)
-    }
-
-    pub async fn get_playlists(&self) -> Result<PlaylistDtoList, BackendError> {
-        let params = vec![
-            ("IncludeItemTypes", "Playlist"),
-            ("sortBy", "DateCreated"),
-            ("sortOrder", "Descending"),
-            ("recursive", "true"),
-            ("fields", "DateCreated,ChildCount"),
-            ("ImageTypeLimit", "1"),
-            ("EnableImageTypes", "Primary"),
-            ("StartIndex", "0"),
-        ];
-        let response = self.get("Items", Some(&params)).await?;
-        let body = self.handle_response(response).await?;
-        let final_result = serde_json::from_str(&body)?;
-
-        Ok(final_result)
-    }
-
-    pub async fn get_favorites(&self) -> Result<FavoriteDtoList, BackendError> {
-        let params = vec![
-            ("IncludeItemTypes", "Playlist,Audio,MusicAlbum,MusicArtist"),
-            ("sortBy", "DateCreated"),
-            ("sortOrder", "Descending"),
-            ("recursive", "true"),
-            ("StartIndex", "0"),
-            ("enableTotalRecordCount", "false"),
-            ("enableImages", "false"),
-            ("Filters", "IsFavorite"),
-            ("Limit", "10000"), // TODO implement pagination if this is ever an issue
-        ];
-        let response = self.get("Items", Some(&params)).await?;
-        let body = self.handle_response(response).await?;
-        let final_result = serde_json::from_str(&body)?;
-
-        Ok(final_result)
-    }
-
-    pub async fn get_playlist_items(
-        &self,
-        playlist_id: &str,
-    ) -> Result<PlaylistItems, BackendError> {
-        let params = vec![("fields", "DateCreated,Genres")];
-        let path = format!("Playlists/{}/Items", playlist_id);
-        let response = self.get(&path, Some(&params)).await?;
-        let body = self.handle_response(response).await?;
-        Ok(serde_json::from_str(&body)?)
-    }
-
-    pub async fn add_playlist_items(
-        &self,
-        playlist_id: &str,
-        item_ids: &[String],
-    ) -> Result<(), BackendError> {
-        let path = format!("Playlists/{}/Items", playlist_id);
-        let ids = item_ids.join(",");
-        let params = vec![("ids", ids.as_str()), ("userId", &self.user_id)];
-        let response = self.post(&path, Some(&params), None).await?;
-        self.handle_response(response).await?;
-        Ok(())
-    }
-
-    pub async fn move_playlist_item(
-        &self,
-        playlist_id: &str,
-        item_id: &str,
-        new_index: i32,
-    ) -> Result<(), BackendError> {
-        let path = format!(
-            "Playlists/{}/Items/{}/Move/{}",
-            playlist_id, item_id, new_index
-        );
-        let response = self.post(&path, None, None).await?;
-        self.handle_response(response).await?;
-        Ok(())
-    }
-
-    pub async fn remove_playlist_item(
-        &self,
-        playlist_id: &str,
-        item_id: &str,
-    ) -> Result<(), BackendError> {
-        let path = format!("Playlists/{}/Items/", playlist_id);
-        let params = vec![("entryIds", item_id)];
-        self.delete(&path, Some(&params)).await?;
-        Ok(())
-    }
-
-    pub async fn new_playlist(
-        &self,
-        name: &str,
-        items: Vec<String>,
-    ) -> Result<String, BackendError> {
-        let path = "Playlists/";
-        let body = NewPlaylist {
-            name: name.to_string(),
-            ids: items,
-            user_id: self.user_id.clone(),
-            media_type: "Audio".to_string(),
-            users: vec![],
-            is_public: false,
-        };
-        let response = self.post_json(path, &body).await?;
-        let response = self.handle_response(response).await?;
-        let playlist_response: NewPlaylistResponse = serde_json::from_str(&response)?;
-        Ok(playlist_response.id)
-    }
-
-    pub async fn delete_item(&self, item_id: &str) -> Result<(), BackendError> {
-        let path = format!("Items/{}", item_id);
-        self.delete(&path, None).await?;
-        Ok(())
-    }
-
-    pub async fn set_favorite(&self, item_id: &str, is_favorite: bool) -> Result<(), BackendError> {
-        let path = format!("UserFavoriteItems/{item_id}");
-        let response = if is_favorite {
-            self.post(&path, None, None).await?
-        } else {
-            self.delete(&path, None).await?
-        };
-        self.handle_response(response).await.map(drop)
-    }
-
-    pub async fn request_library_rescan(&self, library_id: &str) -> Result<(), BackendError> {
-        let params = vec![
-            ("itemId", library_id),
-            ("Recursive", "true"),
-            ("ImageRefreshMode", "Default"),
-            ("MetadataRefreshMode", "Default"),
-            ("ReplaceAllImages", "false"),
-            ("RegenerateTrickplay", "false"),
-            ("ReplaceAllMetadata", "false"),
-        ];
-        let response = self
-            .post(
-                &format!("Items/{}/Refresh", library_id),
-                Some(&params),
-                None,
-            )
-            .await?;
-        self.handle_response(response).await?;
-        Ok(())
-    }
-
-    pub async fn get_image(
-        &self,
-        item_id: &str,
-        image_type: ImageType,
-        scale: f32,
-    ) -> Result<Vec<u8>, BackendError> {
-        let (base_w, base_h) = match image_type {
-            ImageType::Backdrop => (1920.0, 300.0),
-            _ => (200.0, 200.0),
-        };
-        let width = ((base_w * scale).round() as u32).max(1).to_string();
-        let height = ((base_h * scale).round() as u32).max(1).to_string();
-        let params = match image_type {
-            ImageType::Backdrop => vec![
-                ("maxWidth", width.as_str()),
-                ("maxHeight", height.as_str()),
-                ("quality", "96"),
-            ],
-            _ => vec![
-                ("fillWidth", width.as_str()),
-                ("fillHeight", height.as_str()),
-                ("quality", "96"),
-            ],
-        };
-        let response = self
-            .get(
-                &format!("Items/{}/Images/{}", item_id, image_type.as_str()),
-                Some(&params),
-            )
-            .await?;
-        self.handle_binary_response(response).await
-    }
-
-    pub fn get_stream_uri(&self, item_id: &str) -> String {
-        let tc_profile = config::get_transcoding_profile();
-        let containers = "flac,opus,mp3,aac,m4a,ogg,wav,webm|opus,webm|webma,webma";
-        let tc_audio_codec = tc_profile.codec;
-        let tc_container = tc_profile.container;
-        let max_bitrate = config::get_max_bitrate().unwrap_or(999999999);
-        debug!(
-            "Stream settings: {} + {} @ {}",
-            tc_audio_codec, tc_container, max_bitrate
-        );
-        let stream_protocol = "hls";
-        format!(
-            "{}/Audio/{item_id}/universal?api_key={}&userId={}&container={}&audioCodec={}&transcodingContainer={}&transcodingProtocol={}&maxStreamingBitrate={}",
-            self.host.trim_end_matches("/"),
-            self.token,
-            self.user_id,
-            containers,
-            tc_audio_codec,
-            tc_container,
-            stream_protocol,
-            max_bitrate
-        )
-    }
-
-    pub async fn get_playback_info(&self, item_id: &str) -> Result<PlaybackInfo, BackendError> {
-        let response = self
-            .get(&format!("Items/{}/PlaybackInfo", item_id), None)
-            .await?;
-        let body = self.handle_response(response).await?;
-        Ok(serde_json::from_str(&body)?)
-    }
-
-    pub async fn playback_report(
-        &self,
-        report: &PlaybackReport,
-        state: &PlaybackReportStatus,
-    ) -> Result<(), BackendError> {
-        let path = match state {
-            PlaybackReportStatus::Started => "",
-            PlaybackReportStatus::InProgress => "Progress",
-            PlaybackReportStatus::Stopped => "Stopped",
-        };
-        let response = self
-            .post_json(&format!("Sessions/Playing/{}", path), report)
-            .await?;
-        self.handle_response(response).await?;
-        Ok(())
-    }
-
-    pub async fn fetch_lyrics(&self, item_id: &str) -> Result<LyricsResponse, BackendError> {
-        let response = self.get(&format!("Audio/{}/Lyrics", item_id), None).await?;
-        let body = self.handle_response(response).await?;
-        Ok(serde_json::from_str(&body)?)
-    }
-
-    fn get_hostname(&self) -> &'static str {
-        static HOSTNAME: OnceLock<String> = OnceLock::new();
-        HOSTNAME.get_or_init(|| {
-            fs::read_to_string("/proc/sys/kernel/hostname")
-                .unwrap_or_else(|_| "Gelly-Device".to_string())
-                .trim()
-                .to_string()
-        })
-    }
-
-    fn auth_header(&self) -> String {
-        let device = self.get_hostname();
-        let auth = if !self.token.is_empty() {
-            format!(", Token=\"{}\"", self.token)
-        } else {
-            "".to_string()
-        };
-        let uuid = config::application_uuid();
-
-        format!(
-            "MediaBrowser Client=\"{}\", Device=\"{}\", DeviceId=\"{}\", Version=\"{}\"{}",
-            CLIENT_ID,
-            device,
-            uuid,
-            config::VERSION,
-            auth
-        )
-    }
-
-    async fn post_json<T>(&self, endpoint: &str, body: &T) -> Result<Response, BackendError>
-    where
-        T: serde::Serialize,
-    {
-        let url = format!(
-            "{}/{}",
-            self.host.trim_end_matches('/'),
-            endpoint.trim_start_matches('/')
-        );
-        debug!("Sending POST request to {}", url);
-        let request = self
-            .client
-            .post(&url)
-            .json(&body)
-            .header("Authorization", self.auth_header());
-        let response = request.send().await?;
-        Ok(response)
-    }
-
-    async fn post(
-        &self,
-        endpoint: &str,
-        params: Option<&[(&str, &str)]>,
-        body: Option<String>,
-    ) -> Result<Response, BackendError> {
-        let url = self.format_url(endpoint);
-        debug!("Sending POST request to {}", url);
-        let request = self
-            .client
-            .post(&url)
-            .query(params.unwrap_or(&[]))
-            .body(body.unwrap_or_default())
-            .header("Authorization", self.auth_header());
-        let response = request.send().await?;
-        Ok(response)
-    }
-
-    /// Any GET request
-    async fn get(
-        &self,
-        endpoint: &str,
-        params: Option<&[(&str, &str)]>,
-    ) -> Result<Response, BackendError> {
-        let url = self.format_url(endpoint);
-        debug!("Sending GET request to {}", url);
-        let request = self
-            .client
-            .get(&url)
-            .query(params.unwrap_or(&[]))
-            .header("Authorization", self.auth_header());
-        let response = request.send().await?;
-        Ok(response)
-    }
-
-    async fn delete(
-        &self,
-        endpoint: &str,
-        params: Option<&[(&str, &str)]>,
-    ) -> Result<Response, BackendError> {
-        let url = self.format_url(endpoint);
-        debug!("Sending DELETE request to {}", url);
-        let request = self
-            .client
-            .delete(&url)
-            .query(params.unwrap_or(&[]))
-            .header("Authorization", self.auth_header());
-        let response = request.send().await?;
-        Ok(response)
-    }
-
-    fn format_url(&self, endpoint: &str) -> String {
-        format!(
-            "{}/{}",
-            self.host.trim_end_matches('/'),
-            endpoint.trim_start_matches('/')
-        )
-    }
-
-    /// Responsible for error handling when reading responses from Jellyfin
-    async fn handle_response(&self, response: Response) -> Result<String, BackendError> {
-        let status = response.status();
-        if status.is_success() {
-            let response_body = response.text().await?;
-            Ok(response_body)
-        } else {
-            let message = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unknown Error".to_string());
-
-            match status {
-                StatusCode::UNAUTHORIZED => Err(BackendError::AuthenticationFailed { message }),
-                _ => Err(BackendError::Http { status, message }),
-            }
-        }
-    }
-
-    /// Same as handle_response but does not deserialize the response body.
-    async fn handle_binary_response(&self, response: Response) -> Result<Vec<u8>, BackendError> {
-        let status = response.status();
-        if status.is_success() {
-            let response_body = response.bytes().await?;
-            Ok(response_body.to_vec())
-        } else {
-            let message = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unknown Error".to_string());
-
-            match status {
-                StatusCode::UNAUTHORIZED => Err(BackendError::AuthenticationFailed { message }),
-                _ => Err(BackendError::Http { status, message }),
-            }
-        }
-    }
-}
-
-impl Default for Jellyfin {
-    fn default() -> Self {
-        Self::new("", "", "")
-    }
-}
-
-pub async fn initiate_quick_connect(host: &str) -> Result<QuickConnectResponse, BackendError> {
-    let jellyfin = Jellyfin::new(host, "", "");
-    let response = jellyfin.post("QuickConnect/Initiate", None, None).await?;
-    let body = jellyfin.handle_response(response).await?;
-    Ok(serde_json::from_str(&body)?)
-}
-
-pub async fn quick_connect_status(
-    host: &str,
-    secret: &str,
-) -> Result<QuickConnectResponse, BackendError> {
-    let jellyfin = Jellyfin::new(host, "", "");
-    let params = vec![("secret", secret)];
-    let response = jellyfin.get("QuickConnect/Connect", Some(&params)).await?;
-    let body = jellyfin.handle_response(response).await?;
-    Ok(serde_json::from_str(&body)?)
-}
@@ -1,6 +1,8 @@
+use rand::seq::SliceRandom;
+
 use crate::application::Application;
 use crate::backend::BackendError;
-use crate::models::{PlaylistModel, SongModel};
+use crate::models::{Category, PlaylistModel, SongModel};
 
 pub fn songs_for_playlist(
     playlist_model: &PlaylistModel,
@@ -12,7 +14,8 @@ pub fn songs_for_playlist(
 
     // Smart playlists work on the main thread - return immediately
     if playlist_type.is_smart() {
-        cb(Ok(playlist_type.smart_songs(&library)));
+        let skipped = app.play_history().borrow().often_skipped();
+        cb(Ok(playlist_type.smart_songs(&library, &skipped)));
         return;
     }
 
@@ -51,6 +54,19 @@ pub fn play_artist(id: &str, app: &Application) {
     }
 }
 
+/// Plays every song in a genre, composer, decade, year or label, shuffled or album by album
+pub fn play_category(category: Category, name: &str, shuffle: bool, app: &Application) {
+    let mut songs = app.library().songs_for_category(category, name);
+    if shuffle {
+        songs.shuffle(&mut rand::rng());
+    }
+    if let Some(audio_model) = app.audio_model() {
+        audio_model.set_queue(songs, 0, shuffle);
+    } else {
+        log::warn!("No audio model found");
+    }
+}
+
 pub fn play_song(id: &str, app: &Application) {
     let songs = app.library().all_songs();
     let song = songs.iter().find(|s| s.id() == id);
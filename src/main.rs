@@ -1,4 +1,8 @@
 use application::Application;
+use gelly_core::{
+    audiobook, backend, config, history, jellyfin, library, lyrics, playlist_cleanup,
+    playlist_file, playlist_folders, podcast, radio, rating, resume, statistics, subsonic,
+};
 use gtk::CssProvider;
 use gtk::gdk::Display;
 use gtk::gio;
@@ -10,17 +14,13 @@ use ui::window::Window;
 mod application;
 mod async_utils;
 mod audio;
-mod backend;
 mod cache;
 mod cli;
-mod config;
 mod i18n;
-mod jellyfin;
-mod library;
 mod library_utils;
 mod models;
+mod remote;
 mod reporting;
-mod subsonic;
 mod ui;
 
 fn main() -> glib::ExitCode {
@@ -41,6 +41,12 @@ fn main() -> glib::ExitCode {
     app.set_accels_for_action("win.show-artist-list", &["<Ctrl>2"]);
     app.set_accels_for_action("win.show-playlist-list", &["<Ctrl>3"]);
     app.set_accels_for_action("win.show-song-list", &["<Ctrl>4"]);
+    app.set_accels_for_action("win.show-category-list", &["<Ctrl>5"]);
+    app.set_accels_for_action("win.show-folder-browser", &["<Ctrl>6"]);
+    app.set_accels_for_action("win.show-podcast-list", &["<Ctrl>7"]);
+    app.set_accels_for_action("win.show-radio-list", &["<Ctrl>8"]);
+    app.set_accels_for_action("win.show-audiobook-list", &["<Ctrl>9"]);
+    app.set_accels_for_action("win.show-history-list", &["<Ctrl>0"]);
     app.set_accels_for_action("window.close", &["<Ctrl>q"]);
     info!("Application started");
     app.run()
@@ -0,0 +1,87 @@
+use gtk::glib::{self, prelude::*};
+use log::warn;
+
+use crate::{
+    application::Application,
+    async_utils::spawn_tokio,
+    jellyfin::api::ItemType,
+    models::{AlbumModel, ArtistModel, PlaylistModel},
+};
+
+/// Favorite toggling needs the application's backend, so it lives here rather
+/// than on the models themselves in gelly-core.
+pub trait ToggleFavorite {
+    fn toggle_favorite(&self, is_favorite: bool, app: &Application);
+}
+
+impl ToggleFavorite for AlbumModel {
+    fn toggle_favorite(&self, is_favorite: bool, app: &Application) {
+        self.set_favorite(is_favorite);
+        set_backend_favorite(
+            self.upcast_ref(),
+            self.id(),
+            ItemType::MusicAlbum,
+            is_favorite,
+            app,
+        );
+    }
+}
+
+impl ToggleFavorite for ArtistModel {
+    fn toggle_favorite(&self, is_favorite: bool, app: &Application) {
+        self.set_favorite(is_favorite);
+        set_backend_favorite(
+            self.upcast_ref(),
+            self.id(),
+            ItemType::MusicArtist,
+            is_favorite,
+            app,
+        );
+    }
+}
+
+impl ToggleFavorite for PlaylistModel {
+    fn toggle_favorite(&self, is_favorite: bool, app: &Application) {
+        self.set_favorite(is_favorite);
+        set_backend_favorite(
+            self.upcast_ref(),
+            self.id(),
+            ItemType::Playlist,
+            is_favorite,
+            app,
+        );
+    }
+}
+
+/// Reverts the model's `favorite` property if the server rejects the change.
+fn set_backend_favorite(
+    model: &glib::Object,
+    item_id: String,
+    item_type: ItemType,
+    is_favorite: bool,
+    app: &Application,
+) {
+    let backend = app.backend();
+    spawn_tokio(
+        async move {
+            backend
+                .set_favorite(&item_id, &item_type, is_favorite)
+                .await
+        },
+        glib::clone!(
+            #[weak]
+            model,
+            #[weak]
+            app,
+            move |result| {
+                match result {
+                    Ok(()) => app.refresh_favorites(true),
+                    Err(err) => {
+                        warn!("Failed to set favorite: {err}");
+                        model.set_property("favorite", !is_favorite);
+                    }
+                }
+            }
+        ),
+    );
+}
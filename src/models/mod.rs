@@ -1,12 +1,6 @@
-pub mod album_model;
-pub mod artist_model;
-pub mod model_traits;
-pub mod playlist_model;
-pub mod playlist_type;
-pub mod song_model;
+pub mod favorite;
+pub mod rating;
 
-pub use album_model::AlbumModel;
-pub use artist_model::ArtistModel;
-pub use playlist_model::PlaylistModel;
-pub use playlist_type::PlaylistType;
-pub use song_model::SongModel;
+pub use favorite::ToggleFavorite;
+pub use gelly_core::models::*;
+pub use rating::Rate;
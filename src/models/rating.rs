@@ -0,0 +1,86 @@
+use gtk::glib::{self, prelude::*};
+use log::warn;
+
+use crate::{
+    application::Application,
+    async_utils::spawn_tokio,
+    jellyfin::api::ItemType,
+    models::{AlbumModel, SongModel},
+};
+
+/// Like [`super::ToggleFavorite`], rating needs the application's backend so
+/// it lives here rather than on the models in gelly-core.
+pub trait Rate {
+    /// `stars` from one to five, zero clears the rating
+    fn rate(&self, stars: u32, app: &Application);
+}
+
+impl Rate for SongModel {
+    fn rate(&self, stars: u32, app: &Application) {
+        let previous = self.rating();
+        self.set_rating(stars);
+        set_backend_rating(
+            self.upcast_ref(),
+            self.id(),
+            ItemType::Audio,
+            stars,
+            previous,
+            app,
+        );
+    }
+}
+
+impl Rate for AlbumModel {
+    fn rate(&self, stars: u32, app: &Application) {
+        let previous = self.rating();
+        self.set_rating(stars);
+        set_backend_rating(
+            self.upcast_ref(),
+            self.id(),
+            ItemType::MusicAlbum,
+            stars,
+            previous,
+            app,
+        );
+    }
+}
+
+/// The library and its cache only take the rating once the server has, the
+/// model goes back to `previous` if the server rejects it.
+fn set_backend_rating(
+    model: &glib::Object,
+    item_id: String,
+    item_type: ItemType,
+    stars: u32,
+    previous: u32,
+    app: &Application,
+) {
+    let backend = app.backend();
+    let backend_item_id = item_id.clone();
+    spawn_tokio(
+        async move { backend.set_rating(&backend_item_id, stars).await },
+        glib::clone!(
+            #[weak]
+            model,
+            #[weak]
+            app,
+            move |result| match result {
+                Ok(()) => {
+                    let library = app.library();
+                    if matches!(item_type, ItemType::MusicAlbum) {
+                        library.set_album_rating(&item_id, stars);
+                        app.cache_ratings();
+                    } else {
+                        library.set_song_rating(&item_id, stars);
+                        app.cache_library();
+                    }
+                    app.emit_by_name::<()>("ratings-updated", &[]);
+                }
+                Err(err) => {
+                    warn!("Failed to set rating: {err}");
+                    model.set_property("rating", previous);
+                }
+            }
+        ),
+    );
+}
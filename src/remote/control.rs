@@ -0,0 +1,166 @@
+//! Remote control of this session from other Jellyfin clients, e.g. the
+//! web UI's "Play On" menu and its remote player controls.
+
+use log::{debug, warn};
+use rand::seq::SliceRandom;
+
+use crate::{
+    application::Application,
+    jellyfin::api::{GeneralCommand, PlayCommand, PlayRequest, PlaystateCommand, PlaystateRequest},
+    ui::playback_mode::PlaybackMode,
+};
+
+const TICKS_PER_SECOND: i64 = 10_000_000;
+const SKIP_SECONDS: u32 = 10;
+const VOLUME_STEP: f64 = 0.05;
+
+pub fn handle_play(app: &Application, request: PlayRequest) {
+    let Some(audio_model) = app.audio_model() else {
+        return;
+    };
+    let mut songs = app.library().songs_for_ids(&request.item_ids);
+    if songs.is_empty() {
+        warn!(
+            "None of the {} requested items are in the library",
+            request.item_ids.len()
+        );
+        return;
+    }
+    debug!("Remote {:?} of {} songs", request.play_command, songs.len());
+
+    match request.play_command {
+        PlayCommand::PlayNow | PlayCommand::PlayInstantMix => {
+            let start_index = request.start_index.unwrap_or(0).min(songs.len() - 1);
+            audio_model.set_queue(songs, start_index, true);
+            if let Some(ticks) = request.start_position_ticks.filter(|t| *t > 0) {
+                audio_model.seek_once_playing((ticks / TICKS_PER_SECOND) as u32);
+            }
+        }
+        PlayCommand::PlayShuffle => {
+            songs.shuffle(&mut rand::rng());
+            audio_model.set_queue(songs, 0, true);
+        }
+        PlayCommand::PlayNext => audio_model.prepend_to_queue(songs),
+        PlayCommand::PlayLast => audio_model.append_to_queue(songs),
+    }
+}
+
+pub fn handle_playstate(app: &Application, request: PlaystateRequest) {
+    let Some(audio_model) = app.audio_model() else {
+        return;
+    };
+    match request.command {
+        PlaystateCommand::Stop => audio_model.stop(),
+        PlaystateCommand::Pause => audio_model.pause(),
+        PlaystateCommand::Unpause => audio_model.play(),
+        PlaystateCommand::PlayPause => audio_model.toggle_play_pause(),
+        PlaystateCommand::NextTrack => audio_model.next(),
+        PlaystateCommand::PreviousTrack => audio_model.prev(),
+        PlaystateCommand::Seek => {
+            if let Some(ticks) = request.seek_position_ticks {
+                audio_model.seek((ticks.max(0) / TICKS_PER_SECOND) as u32);
+            }
+        }
+        PlaystateCommand::Rewind => {
+            audio_model.seek(audio_model.position().saturating_sub(SKIP_SECONDS));
+        }
+        PlaystateCommand::FastForward => {
+            let position = audio_model.position() + SKIP_SECONDS;
+            if position < audio_model.duration() {
+                audio_model.seek(position);
+            }
+        }
+    }
+}
+
+pub fn handle_general_command(app: &Application, command: GeneralCommand) {
+    let Some(audio_model) = app.audio_model() else {
+        return;
+    };
+    let argument = |name: &str| command.arguments.get(name).map(String::as_str);
+    match command.name.as_str() {
+        "VolumeUp" => audio_model.set_volume(audio_model.volume() + VOLUME_STEP),
+        "VolumeDown" => audio_model.set_volume(audio_model.volume() - VOLUME_STEP),
+        "Mute" => audio_model.set_muted(true),
+        "Unmute" => audio_model.set_muted(false),
+        "ToggleMute" => audio_model.set_muted(!audio_model.muted()),
+        "SetVolume" => {
+            // Jellyfin volume is 0-100
+            if let Some(volume) = argument("Volume").and_then(|v| v.parse::<f64>().ok()) {
+                audio_model.set_volume(volume / 100.0);
+            }
+        }
+        "SetRepeatMode" => {
+            let current =
+                PlaybackMode::try_from(audio_model.playback_mode()).unwrap_or(PlaybackMode::Normal);
+            let mode = repeat_mode(current, argument("RepeatMode"));
+            audio_model.set_playback_mode(mode as u32);
+        }
+        "SetShuffleQueue" => {
+            let current =
+                PlaybackMode::try_from(audio_model.playback_mode()).unwrap_or(PlaybackMode::Normal);
+            let mode = shuffle_mode(current, argument("ShuffleMode"));
+            audio_model.set_playback_mode(mode as u32);
+        }
+        other => debug!("Ignoring general command {}", other),
+    }
+}
+
+/// Shuffle and repeat share one mode, so turning repeat off only touches
+/// the mode when it's repeating
+fn repeat_mode(current: PlaybackMode, requested: Option<&str>) -> PlaybackMode {
+    match requested {
+        Some("RepeatAll") => PlaybackMode::Repeat,
+        Some("RepeatOne") => PlaybackMode::RepeatOne,
+        _ if matches!(current, PlaybackMode::Repeat | PlaybackMode::RepeatOne) => {
+            PlaybackMode::Normal
+        }
+        _ => current,
+    }
+}
+
+/// Like [`repeat_mode`], turning shuffle off leaves repeat alone
+fn shuffle_mode(current: PlaybackMode, requested: Option<&str>) -> PlaybackMode {
+    match requested {
+        Some("Shuffle") => PlaybackMode::Shuffle,
+        _ if current == PlaybackMode::Shuffle => PlaybackMode::Normal,
+        _ => current,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shuffle_off_keeps_repeat() {
+        assert_eq!(
+            shuffle_mode(PlaybackMode::Repeat, Some("Sorted")),
+            PlaybackMode::Repeat
+        );
+        assert_eq!(
+            shuffle_mode(PlaybackMode::Shuffle, Some("Sorted")),
+            PlaybackMode::Normal
+        );
+        assert_eq!(
+            shuffle_mode(PlaybackMode::RepeatOne, Some("Shuffle")),
+            PlaybackMode::Shuffle
+        );
+    }
+
+    #[test]
+    fn test_repeat_off_keeps_shuffle() {
+        assert_eq!(
+            repeat_mode(PlaybackMode::Shuffle, Some("RepeatNone")),
+            PlaybackMode::Shuffle
+        );
+        assert_eq!(
+            repeat_mode(PlaybackMode::RepeatOne, Some("RepeatNone")),
+            PlaybackMode::Normal
+        );
+        assert_eq!(
+            repeat_mode(PlaybackMode::Normal, Some("RepeatOne")),
+            PlaybackMode::RepeatOne
+        );
+    }
+}
@@ -0,0 +1,90 @@
+//! Keeps the library current from the server's change notifications instead
+//! of waiting for the next full refresh.
+
+use gtk::{glib, prelude::*, subclass::prelude::*};
+use log::{debug, warn};
+
+use crate::{
+    application::Application,
+    backend::BackendError,
+    jellyfin::api::{LibraryChangedDto, MusicDto, MusicDtoList, UserDataChangedDto},
+};
+
+pub fn handle_library_changed(app: &Application, changes: LibraryChangedDto) {
+    let Some(jellyfin) = app.backend().jellyfin().cloned() else {
+        return;
+    };
+    debug!(
+        "Library changed: {} added, {} updated, {} removed",
+        changes.items_added.len(),
+        changes.items_updated.len(),
+        changes.items_removed.len()
+    );
+    let removed = changes.items_removed;
+    let changed: Vec<String> = changes
+        .items_added
+        .into_iter()
+        .chain(changes.items_updated)
+        .collect();
+    if changed.is_empty() {
+        apply_library_changes(app, Vec::new(), &removed);
+        return;
+    }
+
+    let library_id = app.imp().library_id.borrow().clone();
+    app.http_with_loading(
+        async move { jellyfin.get_library_items(&library_id, &changed).await },
+        glib::clone!(
+            #[weak]
+            app,
+            move |result: Result<MusicDtoList, BackendError>| match result {
+                Ok(items) => apply_library_changes(&app, items.items, &removed),
+                Err(e) => warn!("Failed to fetch changed library items: {}", e),
+            }
+        ),
+    );
+}
+
+fn apply_library_changes(app: &Application, changed: Vec<MusicDto>, removed: &[String]) {
+    let library = app.library();
+    let before = library.library_size();
+    let has_changes = !changed.is_empty();
+    library.merge_songs(changed, removed);
+    let size = library.library_size();
+    if !has_changes && size == before {
+        // Nothing we show, e.g. items from another library
+        return;
+    }
+    debug!("Library went from {} to {} songs", before, size);
+    app.cache_library();
+    app.emit_by_name::<()>("library-refreshed", &[&(size as u64)]);
+}
+
+pub fn handle_user_data_changed(app: &Application, changes: UserDataChangedDto) {
+    let backend = app.backend();
+    if backend
+        .jellyfin()
+        .is_none_or(|jellyfin| jellyfin.user_id != changes.user_id)
+    {
+        return;
+    }
+    let playlists = app.playlists();
+    let update = app
+        .library()
+        .update_user_data(&changes.user_data_list, |id| {
+            playlists.borrow().iter().any(|playlist| playlist.id == id)
+        });
+    // Play counts change after every song, so they're kept current without
+    // redrawing every page. Favorites and ratings are rare and shown everywhere.
+    if update.songs_changed {
+        app.cache_library();
+    }
+    if update.favorites_changed {
+        app.cache_favorites();
+        app.emit_by_name::<()>("favorites-updated", &[]);
+    }
+    if update.ratings_changed {
+        app.cache_ratings();
+        app.emit_by_name::<()>("ratings-updated", &[]);
+    }
+}
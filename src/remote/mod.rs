@@ -0,0 +1,90 @@
+use std::time::Duration;
+
+use async_channel::Receiver;
+use gtk::{glib, prelude::*};
+use log::{debug, warn};
+
+use crate::{
+    application::Application,
+    async_utils::tokio_rt,
+    jellyfin::socket::{self, SocketMessage},
+};
+
+pub mod control;
+pub mod library_updates;
+pub mod syncplay;
+
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(120);
+
+/// Keeps the Jellyfin session websocket open for as long as it is alive and
+/// hands incoming messages to the parts of the app that care about them.
+#[derive(Debug)]
+pub struct SessionConnection {
+    receiver: Receiver<SocketMessage>,
+}
+
+impl SessionConnection {
+    pub fn connect(app: &Application, url: String) -> Self {
+        let (sender, receiver) = async_channel::unbounded();
+
+        tokio_rt().spawn(async move {
+            let mut delay = RECONNECT_DELAY;
+            loop {
+                match socket::run(&url, sender.clone()).await {
+                    Ok(()) => break,
+                    Err(e) => warn!("Session websocket disconnected: {}", e),
+                }
+                if sender.is_closed() {
+                    break;
+                }
+                tokio::time::sleep(delay).await;
+                delay = (delay * 2).min(MAX_RECONNECT_DELAY);
+            }
+            debug!("Session websocket closed");
+        });
+
+        let app_weak = app.downgrade();
+        let messages = receiver.clone();
+        glib::spawn_future_local(async move {
+            while let Ok(message) = messages.recv().await {
+                let Some(app) = app_weak.upgrade() else { break };
+                handle_message(&app, message);
+            }
+        });
+
+        Self { receiver }
+    }
+
+    pub fn disconnect(&self) {
+        self.receiver.close();
+    }
+}
+
+impl Drop for SessionConnection {
+    fn drop(&mut self) {
+        self.disconnect();
+    }
+}
+
+fn handle_message(app: &Application, message: SocketMessage) {
+    match message {
+        SocketMessage::Play(request) => control::handle_play(app, request),
+        SocketMessage::Playstate(request) => control::handle_playstate(app, request),
+        SocketMessage::GeneralCommand(command) => control::handle_general_command(app, command),
+        SocketMessage::LibraryChanged(changes) => {
+            library_updates::handle_library_changed(app, changes)
+        }
+        SocketMessage::UserDataChanged(changes) => {
+            library_updates::handle_user_data_changed(app, changes)
+        }
+        SocketMessage::SyncPlayCommand(command) => app.syncplay().handle_command(command),
+        SocketMessage::SyncPlayGroupUpdate { group_id, update } => {
+            app.syncplay().handle_group_update(&group_id, update)
+        }
+        SocketMessage::Other(message_type) => {
+            debug!("Ignoring session message {}", message_type);
+        }
+        SocketMessage::ForceKeepAlive(_) | SocketMessage::KeepAlive => {}
+    }
+}
@@ -0,0 +1,597 @@
+use std::future::Future;
+use std::time::Duration;
+
+use gtk::{
+    glib::{self, Object},
+    prelude::*,
+    subclass::prelude::*,
+};
+use log::{debug, warn};
+
+use crate::{
+    application::Application,
+    async_utils::spawn_tokio,
+    audio::model::AudioModel,
+    backend::BackendError,
+    i18n::tr,
+    jellyfin::{
+        Jellyfin,
+        api::{GroupInfoDto, PlayQueueUpdate, SyncPlayCommand, SyncPlayCommandType},
+        socket::GroupUpdate,
+        syncplay::{TICKS_PER_MILLISECOND, TimeSyncSample, format_server_time, parse_server_time},
+    },
+};
+
+const TIME_SYNC_INTERVAL_SECONDS: u32 = 30;
+
+glib::wrapper! {
+    pub struct SyncPlay(ObjectSubclass<imp::SyncPlay>);
+}
+
+/// Client side of a Jellyfin SyncPlay group. Applies the group's queue and
+/// playback commands to the `AudioModel` and forwards local play, pause, seek
+/// and track changes to the group.
+impl SyncPlay {
+    pub fn new(app: &Application) -> Self {
+        let obj: Self = Object::builder().build();
+        obj.imp().app.set(Some(app));
+        obj
+    }
+
+    fn app(&self) -> Option<Application> {
+        self.imp().app.upgrade()
+    }
+
+    fn audio_model(&self) -> Option<AudioModel> {
+        self.app().and_then(|app| app.audio_model())
+    }
+
+    fn jellyfin(&self) -> Option<Jellyfin> {
+        self.app().and_then(|app| app.backend().jellyfin().cloned())
+    }
+
+    pub fn is_supported(&self) -> bool {
+        self.jellyfin().is_some()
+    }
+
+    pub fn bind_to_audio_model(&self, audio_model: &AudioModel) {
+        audio_model.connect_closure(
+            "play",
+            false,
+            glib::closure_local!(
+                #[weak(rename_to = syncplay)]
+                self,
+                move |_audio_model: AudioModel| {
+                    syncplay.on_local_play();
+                }
+            ),
+        );
+        audio_model.connect_closure(
+            "pause",
+            false,
+            glib::closure_local!(
+                #[weak(rename_to = syncplay)]
+                self,
+                move |_audio_model: AudioModel| {
+                    syncplay.on_local_pause();
+                }
+            ),
+        );
+        audio_model.connect_closure(
+            "seeked",
+            false,
+            glib::closure_local!(
+                #[weak(rename_to = syncplay)]
+                self,
+                move |_audio_model: AudioModel, position: u32| {
+                    syncplay.on_local_seek(position);
+                }
+            ),
+        );
+        audio_model.connect_closure(
+            "song-changed",
+            false,
+            glib::closure_local!(
+                #[weak(rename_to = syncplay)]
+                self,
+                move |_audio_model: AudioModel, _song_id: &str| {
+                    syncplay.on_local_song_changed();
+                }
+            ),
+        );
+    }
+
+    pub fn list_groups(&self, cb: impl FnOnce(Result<Vec<GroupInfoDto>, BackendError>) + 'static) {
+        let Some(jellyfin) = self.jellyfin() else {
+            cb(Ok(Vec::new()));
+            return;
+        };
+        spawn_tokio(async move { jellyfin.syncplay_list_groups().await }, cb);
+    }
+
+    pub fn create_group(&self, name: &str) {
+        let name = name.to_string();
+        self.request("create group", move |jellyfin| async move {
+            jellyfin.syncplay_new_group(&name).await
+        });
+    }
+
+    pub fn join_group(&self, group_id: &str) {
+        let group_id = group_id.to_string();
+        self.request("join group", move |jellyfin| async move {
+            jellyfin.syncplay_join_group(&group_id).await
+        });
+    }
+
+    pub fn leave_group(&self) {
+        self.request("leave group", |jellyfin| async move {
+            jellyfin.syncplay_leave_group().await
+        });
+        self.reset();
+    }
+
+    pub fn handle_group_update(&self, group_id: &str, update: GroupUpdate) {
+        // Only the group we're in gets to change what we show
+        let our_group = *self.imp().group_id.borrow() == group_id;
+        match update {
+            GroupUpdate::GroupJoined(info) => self.on_group_joined(info),
+            GroupUpdate::GroupLeft | GroupUpdate::NotInGroup if our_group => self.reset(),
+            GroupUpdate::UserJoined(name) if our_group => {
+                let mut members = self.members();
+                members.push(name);
+                self.set_members(members);
+            }
+            GroupUpdate::UserLeft(name) if our_group => {
+                let mut members = self.members();
+                if let Some(index) = members.iter().position(|m| *m == name) {
+                    members.remove(index);
+                }
+                self.set_members(members);
+            }
+            GroupUpdate::PlayQueue(queue) if our_group => self.apply_play_queue(queue),
+            GroupUpdate::StateUpdate(state) => {
+                debug!("SyncPlay group is {} ({})", state.state, state.reason);
+            }
+            GroupUpdate::Rejected(reason) => {
+                warn!("SyncPlay request rejected: {}", reason);
+                self.show_error(&tr("The listening group could not be joined"));
+            }
+            GroupUpdate::GroupLeft
+            | GroupUpdate::NotInGroup
+            | GroupUpdate::UserJoined(_)
+            | GroupUpdate::UserLeft(_)
+            | GroupUpdate::PlayQueue(_)
+            | GroupUpdate::Other(_) => {}
+        }
+    }
+
+    pub fn handle_command(&self, command: SyncPlayCommand) {
+        if !self.active() || *self.imp().group_id.borrow() != command.group_id {
+            return;
+        }
+        let now = glib::real_time();
+        let when = parse_server_time(&command.when)
+            .map(|t| self.imp().time_sync.borrow().server_to_local(t))
+            .unwrap_or(now);
+        let position_ms = command
+            .position_ticks
+            .map(|ticks| (ticks / TICKS_PER_MILLISECOND).max(0) as u64);
+        debug!(
+            "SyncPlay {:?} at {:?}ms in {}ms",
+            command.command,
+            position_ms,
+            (when - now) / 1000
+        );
+
+        self.cancel_scheduled();
+        match command.command {
+            SyncPlayCommandType::Unpause => {
+                self.expect_playing(true);
+                self.schedule(when - now, move |audio_model, late_ms| {
+                    if let Some(position_ms) = position_ms {
+                        audio_model.sync_to_position(position_ms + late_ms);
+                    }
+                    audio_model.play();
+                });
+            }
+            SyncPlayCommandType::Pause => {
+                self.expect_playing(false);
+                self.schedule(when - now, move |audio_model, _| {
+                    audio_model.pause();
+                    if let Some(position_ms) = position_ms {
+                        audio_model.sync_to_position(position_ms);
+                    }
+                });
+            }
+            SyncPlayCommandType::Seek => {
+                let Some(audio_model) = self.audio_model() else {
+                    return;
+                };
+                self.expect_playing(false);
+                audio_model.pause();
+                if let Some(position_ms) = position_ms {
+                    audio_model.sync_to_position(position_ms);
+                }
+                self.report_ready(true);
+            }
+            SyncPlayCommandType::Stop => {
+                if let Some(audio_model) = self.audio_model() {
+                    audio_model.stop();
+                }
+            }
+        }
+    }
+
+    fn on_group_joined(&self, info: GroupInfoDto) {
+        let imp = self.imp();
+        imp.group_id.replace(info.group_id);
+        self.set_group_name(info.group_name);
+        self.set_members(info.participants);
+        self.set_active(true);
+        self.start_time_sync();
+
+        // Whoever starts a group brings their queue along
+        if info.state == "Idle"
+            && let Some(audio_model) = self.audio_model()
+            && audio_model.queue_len() > 0
+        {
+            self.send_local_queue(&audio_model);
+        }
+    }
+
+    fn reset(&self) {
+        let imp = self.imp();
+        self.cancel_scheduled();
+        if let Some(source) = imp.time_sync_source.take() {
+            source.remove();
+        }
+        imp.group_id.replace(String::new());
+        imp.playlist.replace(Vec::new());
+        imp.playlist_item_id.replace(String::new());
+        imp.pending_ready.set(false);
+        imp.expected_playing.set(None);
+        self.set_members(Vec::<String>::new());
+        self.set_group_name("");
+        self.set_active(false);
+    }
+
+    fn apply_play_queue(&self, queue: PlayQueueUpdate) {
+        let (Some(app), Some(audio_model)) = (self.app(), self.audio_model()) else {
+            return;
+        };
+        let imp = self.imp();
+        let current = usize::try_from(queue.playing_item_index)
+            .ok()
+            .and_then(|index| queue.playlist.get(index))
+            .cloned();
+        let ids: Vec<String> = queue.playlist.iter().map(|i| i.item_id.clone()).collect();
+        imp.playlist.replace(queue.playlist);
+
+        let Some(current) = current else {
+            imp.playlist_item_id.replace(String::new());
+            return;
+        };
+        imp.playlist_item_id.replace(current.playlist_item_id);
+
+        let local_ids: Vec<String> = audio_model.queue().iter().map(|s| s.id()).collect();
+        if local_ids == ids && audio_model.queue_index() == queue.playing_item_index {
+            // Our own change coming back, or just a shuffle/repeat mode change
+            self.report_ready(true);
+            return;
+        }
+
+        let songs = app.library().songs_for_ids(&ids);
+        if songs.len() != ids.len() {
+            warn!(
+                "{} SyncPlay queue items are not in the library",
+                ids.len() - songs.len()
+            );
+        }
+        let Some(index) = songs.iter().position(|s| s.id() == current.item_id) else {
+            self.show_error(&tr(
+                "The group is playing a song that is not in your library",
+            ));
+            return;
+        };
+
+        imp.start_position_ms
+            .set((queue.start_position_ticks / TICKS_PER_MILLISECOND).max(0) as u64);
+        imp.pending_ready.set(true);
+        imp.applying_remote.set(true);
+        audio_model.replace_queue(songs);
+        audio_model.cue_song(index);
+        imp.applying_remote.set(false);
+        self.report_ready(false);
+    }
+
+    /// Only a change that's going to happen is expected, the player sends
+    /// nothing when it's already in that state and the flag would otherwise
+    /// swallow the user's next play or pause
+    fn expect_playing(&self, playing: bool) {
+        let changes = self
+            .audio_model()
+            .is_some_and(|audio_model| audio_model.playing() != playing);
+        self.imp().expected_playing.set(changes.then_some(playing));
+    }
+
+    fn on_local_play(&self) {
+        let expected = self.imp().expected_playing.take();
+        if !self.active() || expected == Some(true) {
+            return;
+        }
+        self.request("unpause", |jellyfin| async move {
+            jellyfin.syncplay_unpause().await
+        });
+    }
+
+    fn on_local_pause(&self) {
+        let imp = self.imp();
+        if imp.pending_ready.replace(false) {
+            // The group's song finished loading
+            let Some(audio_model) = self.audio_model() else {
+                return;
+            };
+            let start = imp.start_position_ms.take();
+            if start > 0 {
+                audio_model.sync_to_position(start);
+            }
+            self.report_ready(true);
+            return;
+        }
+        let expected = imp.expected_playing.take();
+        if !self.active() || expected == Some(false) {
+            return;
+        }
+        self.request("pause", |jellyfin| async move {
+            jellyfin.syncplay_pause().await
+        });
+    }
+
+    fn on_local_seek(&self, position: u32) {
+        if !self.active() {
+            return;
+        }
+        let position_ticks = i64::from(position) * 1000 * TICKS_PER_MILLISECOND;
+        self.request("seek", move |jellyfin| async move {
+            jellyfin.syncplay_seek(position_ticks).await
+        });
+    }
+
+    fn on_local_song_changed(&self) {
+        let imp = self.imp();
+        if !self.active() || imp.applying_remote.get() {
+            return;
+        }
+        let Some(audio_model) = self.audio_model() else {
+            return;
+        };
+        let local_ids: Vec<String> = audio_model.queue().iter().map(|s| s.id()).collect();
+        let playlist_item = {
+            let playlist = imp.playlist.borrow();
+            let group_ids: Vec<&str> = playlist.iter().map(|i| i.item_id.as_str()).collect();
+            if group_ids == local_ids.iter().map(String::as_str).collect::<Vec<_>>() {
+                usize::try_from(audio_model.queue_index())
+                    .ok()
+                    .and_then(|index| playlist.get(index))
+                    .map(|item| item.playlist_item_id.clone())
+            } else {
+                None
+            }
+        };
+
+        match playlist_item {
+            Some(playlist_item_id) => {
+                imp.playlist_item_id.replace(playlist_item_id.clone());
+                self.request("change song", move |jellyfin| async move {
+                    jellyfin.syncplay_set_playlist_item(&playlist_item_id).await
+                });
+            }
+            None => self.send_local_queue(&audio_model),
+        }
+    }
+
+    fn send_local_queue(&self, audio_model: &AudioModel) {
+        let ids: Vec<String> = audio_model.queue().iter().map(|s| s.id()).collect();
+        let index = audio_model.queue_index().max(0) as usize;
+        let position_ticks = audio_model.get_position_ms() as i64 * TICKS_PER_MILLISECOND;
+        self.request("set queue", move |jellyfin| async move {
+            jellyfin
+                .syncplay_set_new_queue(&ids, index, position_ticks)
+                .await
+        });
+    }
+
+    fn report_ready(&self, ready: bool) {
+        let Some(audio_model) = self.audio_model() else {
+            return;
+        };
+        let now = self
+            .imp()
+            .time_sync
+            .borrow()
+            .local_to_server(glib::real_time());
+        let when = format_server_time(now);
+        let position_ticks = audio_model.get_position_ms() as i64 * TICKS_PER_MILLISECOND;
+        let is_playing = audio_model.playing();
+        let playlist_item_id = self.imp().playlist_item_id.borrow().clone();
+        self.request("report ready", move |jellyfin| async move {
+            jellyfin
+                .syncplay_report_ready(ready, &when, position_ticks, is_playing, &playlist_item_id)
+                .await
+        });
+    }
+
+    /// Runs `action` `delay_us` from now, or right away with how late we
+    /// already are in milliseconds.
+    fn schedule(&self, delay_us: i64, action: impl FnOnce(&AudioModel, u64) + 'static) {
+        let Some(audio_model) = self.audio_model() else {
+            return;
+        };
+        if delay_us <= 0 {
+            action(&audio_model, (-delay_us / 1000) as u64);
+            return;
+        }
+        let source = glib::timeout_add_local_once(
+            Duration::from_micros(delay_us as u64),
+            glib::clone!(
+                #[weak(rename_to = syncplay)]
+                self,
+                move || {
+                    syncplay.imp().scheduled.take();
+                    action(&audio_model, 0);
+                }
+            ),
+        );
+        self.imp().scheduled.replace(Some(source));
+    }
+
+    fn cancel_scheduled(&self) {
+        if let Some(source) = self.imp().scheduled.take() {
+            source.remove();
+        }
+    }
+
+    fn start_time_sync(&self) {
+        if let Some(source) = self.imp().time_sync_source.take() {
+            source.remove();
+        }
+        self.sync_time();
+        let source = glib::timeout_add_seconds_local(
+            TIME_SYNC_INTERVAL_SECONDS,
+            glib::clone!(
+                #[weak(rename_to = syncplay)]
+                self,
+                #[upgrade_or]
+                glib::ControlFlow::Break,
+                move || {
+                    syncplay.sync_time();
+                    glib::ControlFlow::Continue
+                }
+            ),
+        );
+        self.imp().time_sync_source.replace(Some(source));
+    }
+
+    fn sync_time(&self) {
+        let Some(jellyfin) = self.jellyfin() else {
+            return;
+        };
+        spawn_tokio(
+            async move {
+                let request_sent = glib::real_time();
+                let result = jellyfin.get_utc_time().await;
+                (request_sent, result, glib::real_time())
+            },
+            glib::clone!(
+                #[weak(rename_to = syncplay)]
+                self,
+                move |(request_sent, result, response_received)| {
+                    let utc = match result {
+                        Ok(utc) => utc,
+                        Err(e) => {
+                            warn!("SyncPlay time sync failed: {}", e);
+                            return;
+                        }
+                    };
+                    let (Some(request_received), Some(response_sent)) = (
+                        parse_server_time(&utc.request_reception_time),
+                        parse_server_time(&utc.response_transmission_time),
+                    ) else {
+                        warn!("SyncPlay time sync got unreadable timestamps");
+                        return;
+                    };
+                    let mut time_sync = syncplay.imp().time_sync.borrow_mut();
+                    time_sync.add_sample(TimeSyncSample {
+                        request_sent,
+                        request_received,
+                        response_sent,
+                        response_received,
+                    });
+                    let ping_ms = time_sync.round_trip() / 1000;
+                    drop(time_sync);
+                    syncplay.request("ping", move |jellyfin| async move {
+                        jellyfin.syncplay_ping(ping_ms).await
+                    });
+                }
+            ),
+        );
+    }
+
+    fn request<F, Fut>(&self, operation: &'static str, request: F)
+    where
+        F: FnOnce(Jellyfin) -> Fut,
+        Fut: Future<Output = Result<(), BackendError>> + Send + 'static,
+    {
+        let Some(jellyfin) = self.jellyfin() else {
+            return;
+        };
+        spawn_tokio(
+            request(jellyfin),
+            glib::clone!(
+                #[weak(rename_to = syncplay)]
+                self,
+                move |result| {
+                    if let Err(e) = result {
+                        warn!("SyncPlay failed to {}: {}", operation, e);
+                        syncplay.show_error(&format!("{} {}", tr("Listening group:"), e));
+                    }
+                }
+            ),
+        );
+    }
+
+    fn show_error(&self, message: &str) {
+        if let Some(app) = self.app() {
+            app.emit_by_name::<()>("global-error", &[&message.to_string()]);
+        }
+    }
+}
+
+mod imp {
+    use std::cell::{Cell, RefCell};
+
+    use gtk::glib::{self, Properties, WeakRef, prelude::*, subclass::prelude::*};
+
+    use crate::{
+        application::Application,
+        jellyfin::{api::SyncPlayQueueItem, syncplay::TimeSync},
+    };
+
+    #[derive(Properties, Default)]
+    #[properties(wrapper_type = super::SyncPlay)]
+    pub struct SyncPlay {
+        #[property(get, set)]
+        pub active: Cell<bool>,
+
+        #[property(get, set, name = "group-name")]
+        pub group_name: RefCell<String>,
+
+        #[property(get, set)]
+        pub members: RefCell<Vec<String>>,
+
+        pub app: WeakRef<Application>,
+        pub group_id: RefCell<String>,
+        pub playlist: RefCell<Vec<SyncPlayQueueItem>>,
+        pub playlist_item_id: RefCell<String>,
+        pub time_sync: RefCell<TimeSync>,
+        pub time_sync_source: RefCell<Option<glib::SourceId>>,
+        pub scheduled: RefCell<Option<glib::SourceId>>,
+        /// Set when a state change we asked for is on its way, so it isn't
+        /// sent back to the group as if the user did it.
+        pub expected_playing: Cell<Option<bool>>,
+        /// The group's song is loading; report ready once it is paused.
+        pub pending_ready: Cell<bool>,
+        pub start_position_ms: Cell<u64>,
+        pub applying_remote: Cell<bool>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for SyncPlay {
+        const NAME: &'static str = "GellySyncPlay";
+        type Type = super::SyncPlay;
+        type ParentType = glib::Object;
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for SyncPlay {}
+}
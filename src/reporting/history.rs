@@ -0,0 +1,121 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::{
+    application::Application,
+    history::{PlayRecord, is_completed},
+    models::SongModel,
+};
+
+use super::PlaybackEvent;
+use gtk::glib::{self, object::ObjectExt};
+use log::warn;
+
+/// Position updates come about once a second, bigger jumps are seeks and
+/// don't count as listening
+const MAX_POSITION_STEP_SECONDS: u64 = 2;
+
+#[derive(Debug)]
+struct CurrentPlay {
+    song: SongModel,
+    started_at: i64,
+    listened: u64,
+    position: u64,
+}
+
+/// Writes each play to the local history log once it's over
+#[derive(Debug)]
+pub struct HistoryReporter {
+    app: glib::WeakRef<Application>,
+    current: Option<CurrentPlay>,
+}
+
+impl HistoryReporter {
+    pub fn new(app: &Application) -> Self {
+        Self {
+            app: app.downgrade(),
+            current: None,
+        }
+    }
+
+    pub async fn handle_event(&mut self, event: PlaybackEvent) -> Result<(), ()> {
+        match event {
+            PlaybackEvent::TrackChanged { song, position, .. } => {
+                let song = song.filter(|song| !song.is_live());
+                // The same track announced again partway through is still the
+                // same play, starting it over from the top is a new one
+                if position > 0
+                    && self.current.as_ref().map(|play| play.song.id())
+                        == song.as_ref().map(|song| song.id())
+                {
+                    return Ok(());
+                }
+                self.finish_play()?;
+                self.current = song.map(|song| CurrentPlay {
+                    song,
+                    started_at: unix_now(),
+                    listened: 0,
+                    position,
+                });
+            }
+            PlaybackEvent::PositionChanged { position } => {
+                if let Some(play) = &mut self.current {
+                    if position > play.position
+                        && position - play.position <= MAX_POSITION_STEP_SECONDS
+                    {
+                        play.listened += position - play.position;
+                    }
+                    play.position = position;
+                }
+            }
+            PlaybackEvent::Seeked { position } => {
+                if let Some(play) = &mut self.current {
+                    play.position = position;
+                }
+            }
+            PlaybackEvent::Stopped { position } => {
+                // Moving on to the next track can report a reset position
+                if let Some(play) = &mut self.current
+                    && position > 0
+                {
+                    play.position = position;
+                }
+                self.finish_play()?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Records the current play, unless it never really got going
+    fn finish_play(&mut self) -> Result<(), ()> {
+        let Some(play) = self.current.take() else {
+            return Ok(());
+        };
+        if play.listened == 0 {
+            return Ok(());
+        }
+        let Some(app) = self.app.upgrade() else {
+            warn!("HistoryReporter: Unable to access application instance");
+            return Err(());
+        };
+        let duration = play.song.duration_seconds();
+        app.record_play(PlayRecord {
+            song_id: play.song.id(),
+            title: play.song.title(),
+            artists: play.song.artists(),
+            album: play.song.album(),
+            album_id: play.song.album_id(),
+            played_at: play.started_at,
+            duration,
+            listened: play.listened,
+            completed: is_completed(play.position, duration),
+        });
+        Ok(())
+    }
+}
+
+fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_secs() as i64)
+}
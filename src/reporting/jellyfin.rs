@@ -36,7 +36,7 @@ impl JellyfinReporter {
                 paused,
                 position,
                 ..
-            } if position > 0 => {
+            } if position > 0 && self.last_song_id.borrow().is_some() => {
                 // these events fire off a few times at track transitions, we only want those
                 // that happen mid-listen here
                 let item_id = self.last_song_id.borrow().clone().unwrap_or_default();
@@ -49,9 +49,10 @@ impl JellyfinReporter {
                     self.report(play_report, PlaybackReportStatus::InProgress);
                 }
             }
+            // Radio streams aren't library items, there's nothing to report
             PlaybackEvent::TrackChanged {
                 song: Some(song), ..
-            } => {
+            } if !song.is_live() => {
                 let song_id = song.id();
                 let mut last_song_id = self.last_song_id.borrow_mut();
                 if last_song_id.as_ref() != Some(&song_id) {
@@ -69,7 +70,9 @@ impl JellyfinReporter {
                     self.playback_id.replace(Uuid::new_v4().to_string());
                 }
             }
-            PlaybackEvent::PositionChanged { position } if position % 5 == 0 => {
+            PlaybackEvent::PositionChanged { position }
+                if position % 5 == 0 && self.last_song_id.borrow().is_some() =>
+            {
                 let position_ticks = position * 10_000_000;
                 let item_id = self.last_song_id.borrow().clone().unwrap_or_default();
                 let report = self.new_report(item_id, true, false, position_ticks);
@@ -6,9 +6,10 @@ use std::rc::Rc;
 use crate::{
     audio::model::AudioModel,
     models::SongModel,
-    reporting::{jellyfin::JellyfinReporter, mpris::MprisReporter},
+    reporting::{history::HistoryReporter, jellyfin::JellyfinReporter, mpris::MprisReporter},
 };
 
+pub mod history;
 pub mod jellyfin;
 pub mod mpris;
 
@@ -53,26 +54,32 @@ pub enum PlaybackEvent {
 pub struct ReportingManager {
     mpris_reporter: Rc<Mutex<MprisReporter>>,
     jellyfin_reporter: Option<Rc<Mutex<JellyfinReporter>>>,
+    history_reporter: Option<Rc<Mutex<HistoryReporter>>>,
 }
 
 impl ReportingManager {
     pub fn new(audio_model: &AudioModel) -> Self {
         let mpris_reporter = Rc::new(Mutex::new(MprisReporter::new(audio_model)));
-        let jellyfin_reporter = if let Some(app) = audio_model.application() {
-            Some(Rc::new(Mutex::new(JellyfinReporter::new(&app))))
+        let (jellyfin_reporter, history_reporter) = if let Some(app) = audio_model.application() {
+            (
+                Some(Rc::new(Mutex::new(JellyfinReporter::new(&app)))),
+                Some(Rc::new(Mutex::new(HistoryReporter::new(&app)))),
+            )
         } else {
-            warn!("Could not instantiate Jellyfin reporter");
-            None
+            warn!("Could not instantiate Jellyfin and history reporters");
+            (None, None)
         };
         Self {
             mpris_reporter,
             jellyfin_reporter,
+            history_reporter,
         }
     }
 
     pub fn report_event(&self, event: PlaybackEvent) {
         let mpris_reporter = self.mpris_reporter.clone();
         let jellyfin_reporter = self.jellyfin_reporter.clone();
+        let history_reporter = self.history_reporter.clone();
 
         glib::spawn_future_local(async move {
             if let Err(e) = mpris_reporter
@@ -93,6 +100,16 @@ impl ReportingManager {
             {
                 warn!("Jellyfin reporter failed to handle event");
             }
+
+            if let Some(history_reporter) = history_reporter
+                && let Err(_) = history_reporter
+                    .lock()
+                    .await
+                    .handle_event(event.clone())
+                    .await
+            {
+                warn!("History reporter failed to handle event");
+            }
         });
     }
 }
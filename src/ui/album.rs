@@ -1,4 +1,8 @@
-use crate::{library_utils::play_album, models::AlbumModel, ui::widget_ext::WidgetApplicationExt};
+use crate::{
+    library_utils::play_album,
+    models::{AlbumModel, ToggleFavorite},
+    ui::widget_ext::WidgetApplicationExt,
+};
 use glib::Object;
 use gtk::{self, gio, glib, subclass::prelude::*};
 
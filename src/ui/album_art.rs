@@ -71,6 +71,13 @@ impl AlbumArt {
             .set_paintable(None::<&gdk::Paintable>);
     }
 
+    /// Loads the same item's image again, once it's been replaced
+    pub fn reload(&self) {
+        self.cancel_loading();
+        self.imp().is_loaded.set(false);
+        self.load_image();
+    }
+
     pub fn load_image(&self) {
         let item_id = if self.imp().item_id.borrow().is_empty() {
             return;
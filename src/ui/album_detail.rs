@@ -1,9 +1,13 @@
 use crate::{
     async_utils::spawn_tokio,
     i18n::{ngettext, tr},
-    jellyfin::utils::format_duration,
-    models::{AlbumModel, SongModel},
+    jellyfin::{
+        api::{AlbumInfo, DiscTitle},
+        utils::format_duration,
+    },
+    models::{AlbumModel, Rate, SongModel, ToggleFavorite},
     ui::{
+        disc_header::DiscHeader,
         music_context_menu::{ContextActions, add_to_playlist_dialog, construct_menu},
         page_traits::DetailPage,
         song::Song,
@@ -11,8 +15,9 @@ use crate::{
         widget_ext::WidgetApplicationExt,
     },
 };
+use adw::prelude::*;
 use glib::Object;
-use gtk::{gio, glib, prelude::*, subclass::prelude::*};
+use gtk::{gio, glib, subclass::prelude::*};
 use log::warn;
 
 glib::wrapper! {
@@ -40,7 +45,23 @@ impl DetailPage for AlbumDetail {
             .sync_create()
             .build();
         self.imp().favorite_binding.replace(Some(binding));
+        // The old album's binding has to go, or rating it would rate this one
+        let binding = model
+            .bind_property("rating", self, "rating")
+            .sync_create()
+            .build();
+        if let Some(old) = imp.rating_binding.replace(Some(binding)) {
+            old.unbind();
+        }
+        imp.compilation_badge.set_visible(model.compilation());
+        imp.disc_titles.replace(Vec::new());
+        self.reset_album_info();
         self.pull_tracks();
+        // Disc titles are part of the album info, otherwise wait for the
+        // details to be opened since artist pages show many albums at once
+        if imp.discs.borrow().len() > 1 {
+            self.request_album_info();
+        }
     }
 
     fn get_model(&self) -> Option<Self::Model> {
@@ -74,7 +95,31 @@ impl AlbumDetail {
             warn!("No audio model set, aborting");
             return;
         };
-        let selection_model = gtk::NoSelection::new(Some(store));
+        // Filtered down to one disc when one is picked, with a section per disc
+        let disc_filter = gtk::CustomFilter::new(glib::clone!(
+            #[weak(rename_to = album_detail)]
+            self,
+            #[upgrade_or]
+            true,
+            move |item| {
+                let Some(disc) = album_detail.imp().selected_disc.get() else {
+                    return true;
+                };
+                item.downcast_ref::<SongModel>()
+                    .is_some_and(|song| song.parent_track_number() == disc)
+            }
+        ));
+        let filter_model = gtk::FilterListModel::new(Some(store), Some(disc_filter.clone()));
+        let _ = imp.disc_filter.set(disc_filter);
+        let disc_model = gtk::SortListModel::new(Some(filter_model), None::<gtk::Sorter>);
+        disc_model.set_section_sorter(Some(&gtk::CustomSorter::new(|a, b| {
+            let disc = |item: &glib::Object| {
+                item.downcast_ref::<SongModel>()
+                    .map(|song| song.parent_track_number())
+            };
+            disc(a).cmp(&disc(b)).into()
+        })));
+        let selection_model = gtk::NoSelection::new(Some(disc_model));
         let factory = gtk::SignalListItemFactory::new();
 
         factory.connect_setup(move |_, list_item| {
@@ -141,9 +186,254 @@ impl AlbumDetail {
             }
         ));
 
+        let header_factory = gtk::SignalListItemFactory::new();
+        header_factory.connect_setup(|_, list_header| {
+            let list_header = list_header
+                .downcast_ref::<gtk::ListHeader>()
+                .expect("Item should be a ListHeader");
+            list_header.set_child(Some(&DiscHeader::new()));
+        });
+        header_factory.connect_bind(glib::clone!(
+            #[weak(rename_to = album_detail)]
+            self,
+            move |_, list_header| {
+                let list_header = list_header
+                    .downcast_ref::<gtk::ListHeader>()
+                    .expect("Item should be a ListHeader");
+                let Some(song) = list_header.item().and_downcast::<SongModel>() else {
+                    return;
+                };
+                let header = list_header
+                    .child()
+                    .and_downcast::<DiscHeader>()
+                    .expect("Child has to be DiscHeader");
+                let disc = song.parent_track_number();
+                let songs = album_detail.songs_on_disc(disc);
+                let duration = songs.iter().map(|song| song.duration()).sum::<u64>();
+                let titles = album_detail.imp().disc_titles.borrow();
+                let title = titles
+                    .iter()
+                    .find(|title| title.disc == disc)
+                    .map(|title| title.title.as_str());
+                header.set_disc(disc, title, songs.len(), duration);
+            }
+        ));
+        let _ = imp.header_factory.set(header_factory);
+
         imp.track_list.set_model(Some(&selection_model));
         imp.track_list.set_factory(Some(&factory));
         imp.track_list.set_single_click_activate(true);
+
+        imp.disc_selector.connect_selected_notify(glib::clone!(
+            #[weak(rename_to = album_detail)]
+            self,
+            move |selector| {
+                let imp = album_detail.imp();
+                let discs = imp.discs.borrow();
+                let selected = selector.selected() as usize;
+                // The first entry shows every disc
+                let disc = selected.checked_sub(1).and_then(|i| discs.get(i).copied());
+                if imp.selected_disc.replace(disc) != disc
+                    && let Some(filter) = imp.disc_filter.get()
+                {
+                    filter.changed(gtk::FilterChange::Different);
+                }
+            }
+        ));
+    }
+
+    fn songs_on_disc(&self, disc: u32) -> Vec<SongModel> {
+        self.imp()
+            .songs
+            .borrow()
+            .iter()
+            .filter(|song| song.parent_track_number() == disc)
+            .cloned()
+            .collect()
+    }
+
+    /// Disc headers and the disc picker only show up for multi-disc albums
+    fn update_discs(&self) {
+        let imp = self.imp();
+        let mut discs: Vec<u32> = imp
+            .songs
+            .borrow()
+            .iter()
+            .map(|song| song.parent_track_number())
+            .collect();
+        discs.dedup();
+        let multi_disc = discs.len() > 1;
+
+        imp.disc_count.set_visible(multi_disc);
+        imp.disc_count.set_text(&format!(
+            "• {}",
+            ngettext("{} disc", "{} discs", discs.len() as u32)
+                .replace("{}", &discs.len().to_string())
+        ));
+
+        let titles = imp.disc_titles.borrow();
+        let mut labels = vec![tr("All Discs")];
+        labels.extend(discs.iter().map(|disc| {
+            let label = tr("Disc {}").replace("{}", &disc.to_string());
+            match titles.iter().find(|title| title.disc == *disc) {
+                Some(title) => format!("{label} – {}", title.title),
+                None => label,
+            }
+        }));
+        drop(titles);
+        imp.discs.replace(discs);
+        let labels: Vec<&str> = labels.iter().map(String::as_str).collect();
+        imp.disc_selector
+            .set_model(Some(&gtk::StringList::new(&labels)));
+        imp.disc_selector.set_selected(0);
+        imp.disc_selector.set_visible(multi_disc);
+
+        // Setting the factory again rebuilds headers with the new titles
+        imp.track_list
+            .set_header_factory(None::<&gtk::ListItemFactory>);
+        if multi_disc {
+            imp.track_list.set_header_factory(imp.header_factory.get());
+        }
+    }
+
+    fn reset_album_info(&self) {
+        let imp = self.imp();
+        imp.album_info_requested.set(false);
+        imp.details_expander.set_expanded(false);
+        imp.overview_label.set_visible(false);
+        imp.details_list.remove_all();
+        imp.details_list.set_visible(false);
+        imp.details_status.set_text(&tr("Loading..."));
+        imp.details_status.set_visible(true);
+    }
+
+    fn request_album_info(&self) {
+        if !self.imp().album_info_requested.replace(true) {
+            self.fetch_album_info();
+        }
+    }
+
+    fn fetch_album_info(&self) {
+        let album_id = self.id();
+        let backend = self.get_application().backend();
+        let request_id = album_id.clone();
+        spawn_tokio(
+            async move { backend.get_album_info(&request_id).await },
+            glib::clone!(
+                #[weak(rename_to = album_detail)]
+                self,
+                move |result: Result<AlbumInfo, _>| {
+                    // The page may have moved on to another album
+                    if album_detail.id() != album_id {
+                        return;
+                    }
+                    match result {
+                        Ok(info) => {
+                            album_detail.show_album_info(&info);
+                            album_detail.set_disc_titles(info.disc_titles);
+                        }
+                        Err(e) => {
+                            warn!("Failed to fetch album info: {}", e);
+                            album_detail
+                                .imp()
+                                .details_status
+                                .set_text(&tr("Failed to load album details"));
+                        }
+                    }
+                }
+            ),
+        );
+    }
+
+    fn show_album_info(&self, info: &AlbumInfo) {
+        let imp = self.imp();
+        if let Some(overview) = &info.overview {
+            imp.overview_label.set_text(overview);
+            imp.overview_label.set_visible(true);
+        }
+        if info.is_compilation {
+            imp.compilation_badge.set_visible(true);
+        }
+
+        let mut properties = Vec::new();
+        if !info.release_types.is_empty() {
+            properties.push((
+                ngettext(
+                    "Release Type",
+                    "Release Types",
+                    info.release_types.len() as u32,
+                ),
+                info.release_types.join(", "),
+            ));
+        }
+        if !info.labels.is_empty() {
+            properties.push((
+                ngettext("Label", "Labels", info.labels.len() as u32),
+                info.labels.join(", "),
+            ));
+        }
+        for (role, names) in info.credits_by_role() {
+            properties.push((role.to_string(), names.join(", ")));
+        }
+        if info.total_size > 0 {
+            properties.push((tr("Size"), glib::format_size(info.total_size).to_string()));
+        }
+        if !info.formats.is_empty() {
+            properties.push((
+                ngettext("Format", "Formats", info.formats.len() as u32),
+                info.formats.join(", "),
+            ));
+        }
+
+        imp.details_list.remove_all();
+        for (title, value) in &properties {
+            let row = adw::ActionRow::builder()
+                .title(title)
+                .subtitle(value)
+                .use_markup(false)
+                .subtitle_selectable(true)
+                .css_classes(["property"])
+                .build();
+            imp.details_list.append(&row);
+        }
+        if let Some(url) = info.musicbrainz_url() {
+            let row = adw::ActionRow::builder()
+                .title("MusicBrainz")
+                .subtitle(tr("Open the release page"))
+                .activatable(true)
+                .build();
+            row.add_suffix(&gtk::Image::from_icon_name("adw-external-link-symbolic"));
+            row.connect_activated(glib::clone!(
+                #[weak(rename_to = album_detail)]
+                self,
+                move |_| {
+                    gtk::UriLauncher::new(&url).launch(
+                        album_detail.get_gtk_window().as_ref(),
+                        None::<&gio::Cancellable>,
+                        |result| {
+                            if let Err(e) = result {
+                                warn!("Failed to open MusicBrainz: {}", e);
+                            }
+                        },
+                    );
+                }
+            ));
+            imp.details_list.append(&row);
+        }
+
+        let has_rows = imp.details_list.first_child().is_some();
+        imp.details_list.set_visible(has_rows);
+        imp.details_status.set_text(&tr("No details available"));
+        imp.details_status
+            .set_visible(!has_rows && info.overview.is_none());
+    }
+
+    fn set_disc_titles(&self, disc_titles: Vec<DiscTitle>) {
+        if disc_titles.is_empty() {
+            return;
+        }
+        self.imp().disc_titles.replace(disc_titles);
+        self.update_discs();
     }
 
     pub fn pull_tracks(&self) {
@@ -153,11 +443,27 @@ impl AlbumDetail {
         store.remove_all();
         store.extend_from_slice(&songs);
         self.imp().songs.replace(songs);
+        self.imp().selected_disc.set(None);
+        if let Some(filter) = self.imp().disc_filter.get() {
+            filter.changed(gtk::FilterChange::LessStrict);
+        }
         self.update_track_metadata();
+        self.update_discs();
     }
 
-    pub fn song_selected(&self, index: usize) {
+    /// `position` is in the visible list, which may be a single disc
+    pub fn song_selected(&self, position: u32) {
+        let Some(song) = self
+            .imp()
+            .track_list
+            .model()
+            .and_then(|model| model.item(position))
+            .and_downcast::<SongModel>()
+        else {
+            return;
+        };
         let songs = self.imp().songs.borrow().clone();
+        let index = songs.iter().position(|s| s.id() == song.id()).unwrap_or(0);
         if let Some(audio_model) = self.get_application().audio_model() {
             audio_model.set_queue(songs, index, true);
         } else {
@@ -176,8 +482,22 @@ impl AlbumDetail {
         }
     }
 
+    fn play_disc(&self, disc: u32) {
+        let songs = self.songs_on_disc(disc);
+        if let Some(audio_model) = self.get_application().audio_model() {
+            audio_model.set_queue(songs, 0, false);
+        } else {
+            self.toast(&tr("Audio model not initialized, please restart"), None);
+            warn!("No audio model found");
+        }
+    }
+
     fn enqueue_album(&self, to_end: bool) {
         let songs = self.imp().songs.borrow().clone();
+        self.enqueue_songs(songs, to_end);
+    }
+
+    fn enqueue_songs(&self, songs: Vec<SongModel>, to_end: bool) {
         if let Some(audio_model) = self.get_application().audio_model() {
             let song_cnt = songs.len();
             if to_end {
@@ -225,7 +545,12 @@ impl AlbumDetail {
             action_prefix: "album".to_string(),
             go_to_artist: true,
             go_to_album: false,
+            go_to_folder: false,
             show_info_dialog: false,
+            bookmarks: false,
+            rating: true,
+            export_playlist: false,
+            clean_up_playlist: false,
         };
         let popover_menu = construct_menu(&options);
         self.imp().action_menu.set_popover(Some(&popover_menu));
@@ -293,6 +618,16 @@ impl AlbumDetail {
         let app = self.get_application();
         model.toggle_favorite(is_favorite, &app);
     }
+
+    /// Rates the album when the rating differs from the model's, so the
+    /// binding from the model doesn't send it back to the server
+    fn rate(&self, stars: u32) {
+        if let Some(model) = self.get_model()
+            && model.rating() != stars
+        {
+            model.rate(stars, &self.get_application());
+        }
+    }
 }
 
 impl Default for AlbumDetail {
@@ -313,8 +648,9 @@ mod imp {
     };
 
     use crate::{
+        jellyfin::api::DiscTitle,
         models::{AlbumModel, SongModel},
-        ui::album_art::AlbumArt,
+        ui::{album_art::AlbumArt, star_rating::StarRating},
     };
 
     #[derive(CompositeTemplate, Default, Properties)]
@@ -341,13 +677,40 @@ mod imp {
         pub favorite_button: TemplateChild<gtk::ToggleButton>,
         #[template_child]
         pub star_icon: TemplateChild<gtk::Image>,
+        #[template_child]
+        pub disc_count: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub disc_selector: TemplateChild<gtk::DropDown>,
+        #[template_child]
+        pub compilation_badge: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub details_expander: TemplateChild<gtk::Expander>,
+        #[template_child]
+        pub overview_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub details_list: TemplateChild<gtk::ListBox>,
+        #[template_child]
+        pub details_status: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub star_rating: TemplateChild<StarRating>,
 
         pub model: RefCell<Option<AlbumModel>>,
         pub songs: RefCell<Vec<SongModel>>,
         pub store: OnceCell<gio::ListStore>,
+        pub disc_filter: OnceCell<gtk::CustomFilter>,
+        pub header_factory: OnceCell<gtk::SignalListItemFactory>,
+        /// Disc numbers in order, matching the disc picker after "All Discs"
+        pub discs: RefCell<Vec<u32>>,
+        pub selected_disc: Cell<Option<u32>>,
+        pub disc_titles: RefCell<Vec<DiscTitle>>,
+        /// Album info is fetched once per album, when it's first needed
+        pub album_info_requested: Cell<bool>,
         pub favorite_binding: RefCell<Option<glib::Binding>>,
         #[property(get, set = Self::set_favorite)]
         favorite: Cell<bool>,
+        pub rating_binding: RefCell<Option<glib::Binding>>,
+        #[property(get, set = Self::set_rating)]
+        rating: Cell<u32>,
     }
 
     #[glib::object_subclass]
@@ -364,6 +727,7 @@ mod imp {
             klass.install_action("album.toggle-favorite", None, |album, _, _| {
                 album.toggle_favorite(!album.favorite());
             });
+            klass.install_property_action("album.rate", "rating");
             klass.install_action("album.add_to_playlist_dialog", None, |album, _, _| {
                 album.on_add_to_playlist_dialog();
             });
@@ -373,6 +737,24 @@ mod imp {
             klass.install_action("album.queue_last", None, |album, _, _| {
                 album.enqueue_album(true);
             });
+            klass.install_action(
+                "album.play-disc",
+                Some(glib::VariantTy::UINT32),
+                |album, _, disc| {
+                    if let Some(disc) = disc.and_then(|disc| disc.get::<u32>()) {
+                        album.play_disc(disc);
+                    }
+                },
+            );
+            klass.install_action(
+                "album.queue-disc",
+                Some(glib::VariantTy::UINT32),
+                |album, _, disc| {
+                    if let Some(disc) = disc.and_then(|disc| disc.get::<u32>()) {
+                        album.enqueue_songs(album.songs_on_disc(disc), true);
+                    }
+                },
+            );
             klass.install_action("album.copy_id", None, |album, _, _| {
                 album.copy_id();
             });
@@ -403,7 +785,23 @@ mod imp {
                 #[weak(rename_to=imp)]
                 self,
                 move |_track_list, position| {
-                    imp.obj().song_selected(position as usize);
+                    imp.obj().song_selected(position);
+                }
+            ));
+            self.star_rating.connect_rated(glib::clone!(
+                #[weak(rename_to=imp)]
+                self,
+                move |_, stars| {
+                    imp.obj().set_rating(stars);
+                }
+            ));
+            self.details_expander.connect_expanded_notify(glib::clone!(
+                #[weak(rename_to=imp)]
+                self,
+                move |expander| {
+                    if expander.is_expanded() {
+                        imp.obj().request_album_info();
+                    }
                 }
             ));
         }
@@ -417,5 +815,11 @@ mod imp {
                 "non-starred-symbolic"
             }));
         }
+
+        fn set_rating(&self, stars: u32) {
+            self.rating.set(stars);
+            self.star_rating.set_rating(stars);
+            self.obj().rate(stars);
+        }
     }
 }
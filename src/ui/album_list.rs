@@ -71,6 +71,7 @@ impl TopPage for AlbumList {
             SortType::Year,
             SortType::PlayCount,
             SortType::LastPlayed,
+            SortType::Rating,
         ]
     }
 
@@ -165,6 +166,33 @@ impl AlbumList {
                 }
             ),
         );
+
+        app.connect_closure(
+            "ratings-updated",
+            false,
+            glib::closure_local!(
+                #[weak(rename_to = album_list)]
+                self,
+                move |_app: Application| {
+                    album_list.update_ratings();
+                }
+            ),
+        );
+    }
+
+    /// Ratings can change on the server without a library refresh, so the
+    /// albums already in the grid are updated in place
+    fn update_ratings(&self) {
+        let library = self.get_application().library();
+        let Some(store) = self.imp().store.get() else {
+            return;
+        };
+        for album in store.iter::<AlbumModel>().flatten() {
+            let rating = library.album_rating(&album.id());
+            if album.rating() != rating {
+                album.set_rating(rating);
+            }
+        }
     }
 
     fn build_sorter(&self) -> gtk::CustomSorter {
@@ -193,6 +221,7 @@ impl AlbumList {
                 SortType::PlayCount => a.play_count().cmp(&b.play_count()).into(),
                 // Reverse order for more recent first.
                 SortType::LastPlayed => b.last_played_date().cmp(&a.last_played_date()).into(),
+                SortType::Rating => a.rating().cmp(&b.rating()).into(),
                 _ => gtk::Ordering::Equal,
             }
         })
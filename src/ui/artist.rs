@@ -1,5 +1,7 @@
 use crate::{
-    library_utils::play_artist, models::ArtistModel, ui::widget_ext::WidgetApplicationExt,
+    library_utils::play_artist,
+    models::{ArtistModel, ToggleFavorite},
+    ui::widget_ext::WidgetApplicationExt,
 };
 use glib::Object;
 use gtk::{gio, glib, subclass::prelude::*};
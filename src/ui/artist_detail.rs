@@ -1,9 +1,9 @@
 use crate::{
     async_utils::spawn_tokio,
     i18n::{ngettext, tr},
-    jellyfin::api::ImageType,
+    jellyfin::api::{ArtistInfo, ImageType},
     library_utils::play_artist,
-    models::{AlbumModel, ArtistModel},
+    models::{AlbumModel, ArtistModel, ToggleFavorite},
     ui::{
         album_detail::AlbumDetail,
         music_context_menu::{ContextActions, add_to_playlist_dialog, construct_menu},
@@ -11,10 +11,15 @@ use crate::{
         widget_ext::WidgetApplicationExt,
     },
 };
+use adw::prelude::*;
 use glib::Object;
-use gtk::{gdk::Texture, gio, glib, prelude::*, subclass::prelude::*};
+use gtk::{gdk::Texture, gio, glib, subclass::prelude::*};
 use log::warn;
 
+/// How many of the artist's most played songs to show
+const TOP_TRACKS: usize = 5;
+const BIOGRAPHY_LINES: i32 = 4;
+
 glib::wrapper! {
     pub struct ArtistDetail(ObjectSubclass<imp::ArtistDetail>)
     @extends gtk::Widget, gtk::Box,
@@ -35,6 +40,8 @@ impl DetailPage for ArtistDetail {
             .build();
         self.imp().favorite_binding.replace(Some(binding));
         self.pull_albums();
+        self.pull_top_tracks();
+        self.fetch_artist_info();
     }
 
     fn get_model(&self) -> Option<Self::Model> {
@@ -56,11 +63,174 @@ impl ArtistDetail {
         while let Some(child) = self.imp().albums_box.first_child() {
             self.imp().albums_box.remove(&child);
         }
-        for album in self.imp().albums.borrow().iter() {
-            let album_widget = AlbumDetail::new();
-            self.imp().albums_box.append(&album_widget);
-            album_widget.set_model(album);
-            album_widget.imp().artist_label.set_visible(false);
+        // Compilations go after the artist's own albums, under a heading
+        let albums = self.imp().albums.borrow();
+        let (compilations, studio_albums): (Vec<_>, Vec<_>) =
+            albums.iter().partition(|album| album.compilation());
+        for album in studio_albums {
+            self.append_album(album, false);
+        }
+        if !compilations.is_empty() {
+            self.append_heading(&tr("Compilations"));
+        }
+        for album in compilations {
+            self.append_album(album, false);
+        }
+
+        // Albums by others the artist only has some tracks on
+        let appears_on = self
+            .get_application()
+            .library()
+            .appears_on_for_artist(&self.id());
+        if !appears_on.is_empty() {
+            self.append_heading(&tr("Appears On"));
+        }
+        for album in &appears_on {
+            self.append_album(album, true);
+        }
+    }
+
+    fn append_heading(&self, text: &str) {
+        let heading = gtk::Label::builder()
+            .label(text)
+            .halign(gtk::Align::Start)
+            .margin_top(12)
+            .css_classes(["title-2"])
+            .build();
+        self.imp().albums_box.append(&heading);
+    }
+
+    fn append_album(&self, album: &AlbumModel, show_artist: bool) {
+        let album_widget = AlbumDetail::new();
+        self.imp().albums_box.append(&album_widget);
+        album_widget.set_model(album);
+        album_widget.imp().artist_label.set_visible(show_artist);
+    }
+
+    fn pull_top_tracks(&self) {
+        let imp = self.imp();
+        let songs = self
+            .get_application()
+            .library()
+            .top_songs_for_artist(&self.id(), TOP_TRACKS);
+        imp.top_tracks.remove_all();
+        for (index, song) in songs.iter().enumerate() {
+            let row = adw::ActionRow::builder()
+                .title(song.title())
+                .subtitle(song.album())
+                .use_markup(false)
+                .activatable(true)
+                .build();
+            let plays = song.play_count();
+            row.add_suffix(
+                &gtk::Label::builder()
+                    .label(
+                        ngettext("{} play", "{} plays", plays as u32)
+                            .replace("{}", &plays.to_string()),
+                    )
+                    .css_classes(["dimmed"])
+                    .build(),
+            );
+            row.connect_activated(glib::clone!(
+                #[weak(rename_to = artist_detail)]
+                self,
+                move |_| artist_detail.play_top_tracks(index)
+            ));
+            imp.top_tracks.append(&row);
+        }
+        imp.top_tracks_heading.set_visible(!songs.is_empty());
+        imp.top_tracks.set_visible(!songs.is_empty());
+        imp.top_songs.replace(songs);
+    }
+
+    fn play_top_tracks(&self, index: usize) {
+        let songs = self.imp().top_songs.borrow().clone();
+        if let Some(audio_model) = self.get_application().audio_model() {
+            audio_model.set_queue(songs, index, true);
+        } else {
+            self.toast(&tr("Audio model not initialized, please restart"), None);
+            warn!("No audio model found");
+        }
+    }
+
+    fn fetch_artist_info(&self) {
+        let imp = self.imp();
+        imp.biography_label.set_visible(false);
+        imp.biography_toggle.set_visible(false);
+        imp.similar_heading.set_visible(false);
+        imp.similar_artists.set_visible(false);
+        imp.similar_artists.remove_all();
+
+        let artist_id = self.id();
+        let request_id = artist_id.clone();
+        let backend = self.get_application().backend();
+        spawn_tokio(
+            async move { backend.get_artist_info(&request_id).await },
+            glib::clone!(
+                #[weak(rename_to = artist_detail)]
+                self,
+                move |result: Result<ArtistInfo, _>| {
+                    // The page may have moved on to another artist
+                    if artist_detail.id() != artist_id {
+                        return;
+                    }
+                    match result {
+                        Ok(info) => artist_detail.show_artist_info(info),
+                        Err(e) => warn!("Failed to fetch artist info: {}", e),
+                    }
+                }
+            ),
+        );
+    }
+
+    fn show_artist_info(&self, info: ArtistInfo) {
+        let imp = self.imp();
+        if let Some(biography) = &info.biography {
+            imp.biography_label.set_text(biography);
+            imp.biography_label.set_lines(BIOGRAPHY_LINES);
+            imp.biography_label
+                .set_ellipsize(gtk::pango::EllipsizeMode::End);
+            imp.biography_label.set_visible(true);
+            imp.biography_toggle.set_label(&tr("Read More"));
+            imp.biography_toggle.set_visible(true);
+        }
+
+        // Only artists we can actually open
+        let similar = self
+            .get_application()
+            .library()
+            .artists_in_library(&info.similar_artists);
+        imp.similar_heading.set_visible(!similar.is_empty());
+        imp.similar_artists.set_visible(!similar.is_empty());
+        for artist in similar {
+            let button = gtk::Button::builder()
+                .label(artist.name())
+                .css_classes(["pill"])
+                .build();
+            button.connect_clicked(glib::clone!(
+                #[weak(rename_to = artist_detail)]
+                self,
+                #[strong]
+                artist,
+                move |_| artist_detail.get_root_window().show_artist_detail(&artist)
+            ));
+            imp.similar_artists.append(&button);
+        }
+    }
+
+    fn toggle_biography(&self) {
+        let imp = self.imp();
+        let expanded = imp.biography_label.lines() < 0;
+        if expanded {
+            imp.biography_label.set_lines(BIOGRAPHY_LINES);
+            imp.biography_label
+                .set_ellipsize(gtk::pango::EllipsizeMode::End);
+            imp.biography_toggle.set_label(&tr("Read More"));
+        } else {
+            imp.biography_label.set_lines(-1);
+            imp.biography_label
+                .set_ellipsize(gtk::pango::EllipsizeMode::None);
+            imp.biography_toggle.set_label(&tr("Show Less"));
         }
     }
 
@@ -110,8 +280,13 @@ impl ArtistDetail {
             in_queue: false,
             action_prefix: "artist".to_string(),
             go_to_album: false,
+            go_to_folder: false,
             go_to_artist: false,
             show_info_dialog: false,
+            bookmarks: false,
+            rating: false,
+            export_playlist: false,
+            clean_up_playlist: false,
         };
         let popover_menu = construct_menu(&options);
         self.imp().action_menu.set_popover(Some(&popover_menu));
@@ -227,7 +402,7 @@ mod imp {
         prelude::*,
     };
 
-    use crate::models::{AlbumModel, ArtistModel};
+    use crate::models::{AlbumModel, ArtistModel, SongModel};
 
     #[derive(CompositeTemplate, Default, Properties)]
     #[template(resource = "/io/m51/Gelly/ui/artist_detail.ui")]
@@ -247,9 +422,22 @@ mod imp {
         pub favorite_button: TemplateChild<gtk::ToggleButton>,
         #[template_child]
         pub star_icon: TemplateChild<gtk::Image>,
+        #[template_child]
+        pub biography_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub biography_toggle: TemplateChild<gtk::Button>,
+        #[template_child]
+        pub top_tracks_heading: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub top_tracks: TemplateChild<gtk::ListBox>,
+        #[template_child]
+        pub similar_heading: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub similar_artists: TemplateChild<gtk::FlowBox>,
 
         pub model: RefCell<Option<ArtistModel>>,
         pub albums: RefCell<Vec<AlbumModel>>,
+        pub top_songs: RefCell<Vec<SongModel>>,
         pub favorite_binding: RefCell<Option<glib::Binding>>,
         #[property(get, set = Self::set_favorite)]
         favorite: Cell<bool>,
@@ -295,6 +483,11 @@ mod imp {
         fn constructed(&self) {
             self.parent_constructed();
             self.obj().setup_menu();
+            self.biography_toggle.connect_clicked(glib::clone!(
+                #[weak(rename_to = imp)]
+                self,
+                move |_| imp.obj().toggle_biography()
+            ));
         }
     }
     impl WidgetImpl for ArtistDetail {}
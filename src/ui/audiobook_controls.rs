@@ -0,0 +1,208 @@
+use glib::Object;
+use gtk::{gio, glib, prelude::*, subclass::prelude::*};
+use log::debug;
+
+use crate::{
+    audio::model::AudioModel, audiobook::SKIP_SECONDS, ui::player_bar::common::format_time,
+};
+
+glib::wrapper! {
+    pub struct AudiobookControls(ObjectSubclass<imp::AudiobookControls>)
+    @extends gtk::Widget, gtk::Box,
+        @implements gio::ActionMap, gio::ActionGroup, gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget;
+}
+
+impl AudiobookControls {
+    pub fn new() -> Self {
+        Object::builder().build()
+    }
+
+    pub fn bind_to_audio_model(&self, audio_model: &AudioModel) {
+        if let Err(err) = self.imp().audio_model.set(audio_model.clone()) {
+            debug!("Audio model already set in AudiobookControls: {:?}", err);
+            return;
+        }
+
+        audio_model.connect_closure(
+            "song-changed",
+            false,
+            glib::closure_local!(
+                #[weak(rename_to = controls)]
+                self,
+                move |_audio_model: AudioModel, _song_id: &str| {
+                    controls.update_book();
+                }
+            ),
+        );
+
+        audio_model.connect_notify_local(
+            Some("position"),
+            glib::clone!(
+                #[weak(rename_to = controls)]
+                self,
+                move |_, _| controls.update_chapter()
+            ),
+        );
+
+        self.update_book();
+    }
+
+    fn audio_model(&self) -> Option<&AudioModel> {
+        self.imp().audio_model.get()
+    }
+
+    /// Only shown while a book plays, with its chapters in the menu
+    fn update_book(&self) {
+        let imp = self.imp();
+        let song = self
+            .audio_model()
+            .and_then(|audio_model| audio_model.current_song())
+            .filter(|song| song.is_audiobook());
+        self.set_visible(song.is_some());
+
+        imp.chapter_list.remove_all();
+        imp.current_chapter.set(None);
+        let chapters = song.map(|song| song.chapters()).unwrap_or_default();
+        for chapter in &chapters {
+            let row = gtk::Box::builder()
+                .orientation(gtk::Orientation::Horizontal)
+                .spacing(12)
+                .build();
+            row.append(
+                &gtk::Label::builder()
+                    .label(&chapter.name)
+                    .xalign(0.0)
+                    .hexpand(true)
+                    .ellipsize(gtk::pango::EllipsizeMode::End)
+                    .build(),
+            );
+            let start = gtk::Label::new(Some(&format_time(chapter.start_seconds() as u32)));
+            start.add_css_class("dimmed");
+            start.add_css_class("numeric");
+            row.append(&start);
+            imp.chapter_list.append(&row);
+        }
+
+        let has_chapters = !chapters.is_empty();
+        imp.chapter_button.set_visible(has_chapters);
+        imp.previous_chapter_button.set_visible(has_chapters);
+        imp.next_chapter_button.set_visible(has_chapters);
+        self.update_chapter();
+    }
+
+    /// Marks the chapter we're in, as the position moves through the book
+    fn update_chapter(&self) {
+        let imp = self.imp();
+        if !self.is_visible() {
+            return;
+        }
+        let current = self
+            .audio_model()
+            .and_then(|audio_model| audio_model.current_chapter());
+        if imp.current_chapter.replace(current) == current {
+            return;
+        }
+        match current.and_then(|index| imp.chapter_list.row_at_index(index as i32)) {
+            Some(row) => {
+                imp.chapter_list.select_row(Some(&row));
+                let name = self
+                    .audio_model()
+                    .and_then(|audio_model| audio_model.current_song())
+                    .and_then(|song| song.chapters().get(row.index() as usize).cloned())
+                    .map(|chapter| chapter.name);
+                imp.chapter_button.set_tooltip_text(name.as_deref());
+            }
+            None => imp.chapter_list.unselect_all(),
+        }
+    }
+}
+
+impl Default for AudiobookControls {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+mod imp {
+    use std::cell::{Cell, OnceCell};
+
+    use adw::subclass::prelude::*;
+    use gtk::{
+        CompositeTemplate, TemplateChild,
+        glib::{self, subclass::InitializingObject},
+        prelude::*,
+    };
+
+    use crate::audio::model::AudioModel;
+
+    #[derive(CompositeTemplate, Default)]
+    #[template(resource = "/io/m51/Gelly/ui/audiobook_controls.ui")]
+    pub struct AudiobookControls {
+        #[template_child]
+        pub previous_chapter_button: TemplateChild<gtk::Button>,
+        #[template_child]
+        pub next_chapter_button: TemplateChild<gtk::Button>,
+        #[template_child]
+        pub chapter_button: TemplateChild<gtk::MenuButton>,
+        #[template_child]
+        pub chapter_popover: TemplateChild<gtk::Popover>,
+        #[template_child]
+        pub chapter_list: TemplateChild<gtk::ListBox>,
+        pub audio_model: OnceCell<AudioModel>,
+        pub current_chapter: Cell<Option<usize>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for AudiobookControls {
+        const NAME: &'static str = "GellyAudiobookControls";
+        type Type = super::AudiobookControls;
+        type ParentType = gtk::Box;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+            klass.install_action("audiobook.back", None, |controls, _, _| {
+                if let Some(audio_model) = controls.audio_model() {
+                    audio_model.skip(-(super::SKIP_SECONDS as i64));
+                }
+            });
+            klass.install_action("audiobook.forward", None, |controls, _, _| {
+                if let Some(audio_model) = controls.audio_model() {
+                    audio_model.skip(super::SKIP_SECONDS as i64);
+                }
+            });
+            klass.install_action("audiobook.previous_chapter", None, |controls, _, _| {
+                if let Some(audio_model) = controls.audio_model() {
+                    audio_model.previous_chapter();
+                }
+            });
+            klass.install_action("audiobook.next_chapter", None, |controls, _, _| {
+                if let Some(audio_model) = controls.audio_model() {
+                    audio_model.next_chapter();
+                }
+            });
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for AudiobookControls {
+        fn constructed(&self) {
+            self.parent_constructed();
+            self.chapter_list.connect_row_activated(glib::clone!(
+                #[weak(rename_to = imp)]
+                self,
+                move |_, row| {
+                    if let Some(audio_model) = imp.audio_model.get() {
+                        audio_model.seek_to_chapter(row.index() as usize);
+                    }
+                    imp.chapter_popover.popdown();
+                }
+            ));
+        }
+    }
+
+    impl WidgetImpl for AudiobookControls {}
+    impl BoxImpl for AudiobookControls {}
+}
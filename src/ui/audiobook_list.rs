@@ -0,0 +1,397 @@
+use crate::{
+    application::Application,
+    async_utils::spawn_tokio,
+    backend::BackendError,
+    i18n::{ngettext, tr},
+    jellyfin::api::Audiobook,
+    models::SongModel,
+    ui::{
+        page_traits::{SortDirection, SortType, TopPage},
+        widget_ext::WidgetApplicationExt,
+    },
+};
+use adw::prelude::*;
+use glib::Object;
+use gtk::{gio, glib, subclass::prelude::*};
+use log::warn;
+
+const TICKS_PER_SECOND: u64 = 10_000_000;
+
+glib::wrapper! {
+    pub struct AudiobookList(ObjectSubclass<imp::AudiobookList>)
+    @extends gtk::Widget, gtk::Box,
+        @implements gio::ActionMap, gio::ActionGroup, gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget;
+}
+
+impl TopPage for AudiobookList {
+    fn can_new(&self) -> bool {
+        false
+    }
+
+    fn has_genres(&self) -> bool {
+        false
+    }
+
+    fn play_selected(&self) {
+        let book = self
+            .imp()
+            .books
+            .borrow()
+            .iter()
+            .find(|book| book.position_ticks > 0 && !book.played)
+            .cloned();
+        if let Some(book) = book {
+            self.play_book(&book);
+        }
+    }
+
+    fn create_new(&self) {}
+
+    fn search_changed(&self, query: &str) {
+        let imp = self.imp();
+        imp.search.replace(query.to_lowercase());
+        imp.in_progress_list.invalidate_filter();
+        imp.books_list.invalidate_filter();
+    }
+
+    fn genre_changed(&self, _genre: Option<&str>) {}
+
+    fn sort_options(&self) -> &[SortType] {
+        &[SortType::Name]
+    }
+
+    fn current_sort_by(&self) -> u32 {
+        0
+    }
+
+    fn current_sort_direction(&self) -> u32 {
+        self.imp().sort_direction.get()
+    }
+
+    fn apply_sort(&self, _sort_by: u32, direction: u32) {
+        self.imp().sort_direction.set(direction);
+        self.show_books();
+    }
+
+    fn supports_favorites(&self) -> bool {
+        false
+    }
+
+    fn filter_favorites(&self, _active: bool) {}
+
+    fn reset_position(&self) {
+        self.imp().scrolled_window.vadjustment().set_value(0.0);
+    }
+}
+
+impl AudiobookList {
+    pub fn new() -> Self {
+        Object::builder().build()
+    }
+
+    pub fn refresh(&self) {
+        let backend = self.get_application().backend();
+        self.set_is_loading(true);
+        spawn_tokio(
+            async move { backend.get_audiobooks().await },
+            glib::clone!(
+                #[weak(rename_to = audiobook_list)]
+                self,
+                move |result: Result<Vec<Audiobook>, BackendError>| {
+                    audiobook_list.set_is_loading(false);
+                    match result {
+                        Ok(books) => {
+                            audiobook_list.imp().books.replace(books);
+                        }
+                        Err(err) => {
+                            warn!("Failed to load audiobooks: {}", err);
+                            audiobook_list.toast(&tr("Could not load audiobooks"), None);
+                        }
+                    }
+                    audiobook_list.show_books();
+                }
+            ),
+        );
+    }
+
+    fn show_books(&self) {
+        let imp = self.imp();
+        let mut books = imp.books.borrow().clone();
+        books.sort_by_key(|book| book.title.to_lowercase());
+        if matches!(
+            SortDirection::try_from(imp.sort_direction.get()),
+            Ok(SortDirection::Descending)
+        ) {
+            books.reverse();
+        }
+
+        imp.in_progress_list.remove_all();
+        let in_progress: Vec<&Audiobook> = books
+            .iter()
+            .filter(|book| book.position_ticks > 0 && !book.played)
+            .collect();
+        for book in &in_progress {
+            imp.in_progress_list.append(&self.book_row(book));
+        }
+        imp.in_progress_heading.set_visible(!in_progress.is_empty());
+        imp.in_progress_list.set_visible(!in_progress.is_empty());
+
+        imp.books_list.remove_all();
+        for book in &books {
+            imp.books_list.append(&self.book_row(book));
+        }
+        imp.books_heading.set_visible(!books.is_empty());
+        imp.books_list.set_visible(!books.is_empty());
+
+        imp.empty.set_visible(books.is_empty());
+        imp.scrolled_window.set_visible(!books.is_empty());
+    }
+
+    fn book_row(&self, book: &Audiobook) -> adw::ActionRow {
+        let mut details = vec![];
+        if !book.authors.is_empty() {
+            details.push(book.authors.join(", "));
+        }
+        if book.played {
+            details.push(tr("Finished"));
+        } else {
+            let left = book.run_time_ticks.saturating_sub(book.position_ticks) / TICKS_PER_SECOND;
+            let minutes = left.div_ceil(60);
+            if minutes > 0 {
+                details.push(
+                    ngettext("1 min left", "{} min left", minutes as u32)
+                        .replace("{}", &minutes.to_string()),
+                );
+            }
+        }
+        if !book.chapters.is_empty() {
+            details.push(
+                ngettext("1 chapter", "{} chapters", book.chapters.len() as u32)
+                    .replace("{}", &book.chapters.len().to_string()),
+            );
+        }
+
+        let row = adw::ActionRow::builder()
+            .title(&book.title)
+            .subtitle(details.join(" · "))
+            .use_markup(false)
+            .activatable(true)
+            .build();
+        row.add_prefix(&gtk::Image::from_icon_name(if book.played {
+            "object-select-symbolic"
+        } else {
+            "accessories-dictionary-symbolic"
+        }));
+        if let Some(overview) = &book.overview {
+            row.set_tooltip_text(Some(overview));
+        }
+
+        if book.run_time_ticks > 0 && book.position_ticks > 0 && !book.played {
+            let progress = gtk::ProgressBar::builder()
+                .fraction(book.position_ticks as f64 / book.run_time_ticks as f64)
+                .valign(gtk::Align::Center)
+                .width_request(60)
+                .build();
+            row.add_suffix(&progress);
+        }
+
+        let button = gtk::Button::builder()
+            .icon_name(if book.played {
+                "edit-undo-symbolic"
+            } else {
+                "object-select-symbolic"
+            })
+            .tooltip_text(if book.played {
+                tr("Mark as Unfinished")
+            } else {
+                tr("Mark as Finished")
+            })
+            .valign(gtk::Align::Center)
+            .css_classes(["flat"])
+            .build();
+        let book_id = book.id.clone();
+        let played = book.played;
+        button.connect_clicked(glib::clone!(
+            #[weak(rename_to = audiobook_list)]
+            self,
+            move |_| audiobook_list.set_played(&book_id, !played)
+        ));
+        row.add_suffix(&button);
+
+        let book = book.clone();
+        row.connect_activated(glib::clone!(
+            #[weak(rename_to = audiobook_list)]
+            self,
+            move |_| audiobook_list.play_book(&book)
+        ));
+        row
+    }
+
+    /// Asks the server where we got to first, the book may have been listened
+    /// to elsewhere since the list was loaded
+    fn play_book(&self, book: &Audiobook) {
+        let Some(jellyfin) = self.get_application().backend().jellyfin().cloned() else {
+            self.start_book(book.clone());
+            return;
+        };
+        let book_id = book.id.clone();
+        let fallback = book.clone();
+        spawn_tokio(
+            async move { jellyfin.get_audiobook(&book_id).await },
+            glib::clone!(
+                #[weak(rename_to = audiobook_list)]
+                self,
+                move |result: Result<Audiobook, BackendError>| {
+                    let book = result.unwrap_or_else(|err| {
+                        warn!("Failed to refresh audiobook position: {}", err);
+                        fallback
+                    });
+                    audiobook_list.start_book(book);
+                }
+            ),
+        );
+    }
+
+    fn start_book(&self, book: Audiobook) {
+        let Some(audio_model) = self.get_application().audio_model() else {
+            self.toast(&tr("Audio model not initialized, please restart"), None);
+            warn!("No audio model found");
+            return;
+        };
+        let song = SongModel::new(&book.to_music_dto(), false);
+        song.set_is_audiobook(true);
+        song.set_chapters(book.chapters.clone());
+        audio_model.set_queue(vec![song], 0, true);
+        let position = book.position_ticks / TICKS_PER_SECOND;
+        if !book.played && position > 0 {
+            audio_model.seek_once_playing(position as u32);
+        }
+    }
+
+    fn set_played(&self, book_id: &str, played: bool) {
+        let backend = self.get_application().backend();
+        let book_id = book_id.to_string();
+        spawn_tokio(
+            async move { backend.set_played(&book_id, played).await },
+            glib::clone!(
+                #[weak(rename_to = audiobook_list)]
+                self,
+                move |result: Result<(), BackendError>| {
+                    if let Err(err) = result {
+                        warn!("Failed to update audiobook on the server: {}", err);
+                        audiobook_list.toast(&tr("Could not update audiobook"), None);
+                    }
+                    audiobook_list.refresh();
+                }
+            ),
+        );
+    }
+
+    pub fn setup_library_connection(&self) {
+        let app = self.get_application();
+        app.connect_closure(
+            "library-refreshed",
+            false,
+            glib::closure_local!(
+                #[weak(rename_to = audiobook_list)]
+                self,
+                move |_app: Application, _total_record_count: u64| {
+                    audiobook_list.refresh();
+                }
+            ),
+        );
+    }
+}
+
+impl Default for AudiobookList {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+mod imp {
+    use std::cell::{Cell, RefCell};
+
+    use adw::subclass::prelude::*;
+    use glib::subclass::InitializingObject;
+
+    use crate::jellyfin::api::Audiobook;
+    use gtk::{
+        CompositeTemplate,
+        glib::{self, Properties},
+        prelude::*,
+    };
+
+    #[derive(CompositeTemplate, Default, Properties)]
+    #[template(resource = "/io/m51/Gelly/ui/audiobook_list.ui")]
+    #[properties(wrapper_type = super::AudiobookList)]
+    pub struct AudiobookList {
+        #[template_child]
+        pub scrolled_window: TemplateChild<gtk::ScrolledWindow>,
+        #[template_child]
+        pub in_progress_heading: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub in_progress_list: TemplateChild<gtk::ListBox>,
+        #[template_child]
+        pub books_heading: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub books_list: TemplateChild<gtk::ListBox>,
+        #[template_child]
+        pub empty: TemplateChild<adw::StatusPage>,
+
+        pub books: RefCell<Vec<Audiobook>>,
+        pub search: RefCell<String>,
+        pub sort_direction: Cell<u32>,
+
+        #[property(get, set, default = false)]
+        pub compact_mode: Cell<bool>,
+        #[property(get, set, default = false)]
+        pub is_loading: Cell<bool>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for AudiobookList {
+        const NAME: &'static str = "GellyAudiobookList";
+        type Type = super::AudiobookList;
+        type ParentType = gtk::Box;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+            klass.install_action("audiobooks.refresh", None, |audiobook_list, _, _| {
+                audiobook_list.refresh();
+            });
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for AudiobookList {
+        fn constructed(&self) {
+            self.parent_constructed();
+            for list_box in [&*self.in_progress_list, &*self.books_list] {
+                list_box.set_filter_func(glib::clone!(
+                    #[weak(rename_to = audiobook_list)]
+                    self.obj(),
+                    #[upgrade_or]
+                    true,
+                    move |row| {
+                        let search = audiobook_list.imp().search.borrow();
+                        search.is_empty()
+                            || row.downcast_ref::<adw::ActionRow>().is_some_and(|row| {
+                                row.title().to_lowercase().contains(&*search)
+                                    || row.subtitle().is_some_and(|subtitle| {
+                                        subtitle.to_lowercase().contains(&*search)
+                                    })
+                            })
+                    }
+                ));
+            }
+        }
+    }
+
+    impl WidgetImpl for AudiobookList {}
+    impl BoxImpl for AudiobookList {}
+}
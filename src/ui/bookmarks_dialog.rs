@@ -0,0 +1,163 @@
+use adw::prelude::*;
+use gtk::glib;
+
+use crate::{
+    audio::model::AudioModel, i18n::tr, jellyfin::utils::format_duration, models::SongModel,
+};
+
+fn format_position(position: u64) -> String {
+    format_duration(position * 10_000_000)
+}
+
+/// Songs that can be bookmarked, radio streams have no positions to return to
+fn bookmarkable_song(audio_model: &AudioModel) -> Option<SongModel> {
+    audio_model.current_song().filter(|song| !song.is_live())
+}
+
+fn save_bookmarks(audio_model: &AudioModel) {
+    if let Some(app) = audio_model.application() {
+        app.save_resume_store();
+    }
+    audio_model.emit_by_name::<()>("bookmarks-changed", &[]);
+}
+
+/// Asks for a name for a bookmark where the current song is at
+pub fn add_bookmark(parent: Option<&gtk::Window>, audio_model: &AudioModel) {
+    let (Some(song), Some(app)) = (bookmarkable_song(audio_model), audio_model.application())
+    else {
+        return;
+    };
+    let position = audio_model.position() as u64;
+
+    let name_entry = adw::EntryRow::builder().title(tr("Name")).build();
+    let entry_box = gtk::ListBox::builder()
+        .margin_top(12)
+        .margin_bottom(12)
+        .build();
+    entry_box.add_css_class("boxed-list");
+    entry_box.append(&name_entry);
+
+    let dialog = adw::AlertDialog::builder()
+        .heading(tr("Add Bookmark"))
+        .body(tr("At {}").replace("{}", &format_position(position)))
+        .extra_child(&entry_box)
+        .build();
+    dialog.add_responses(&[("cancel", &tr("Cancel")), ("add", &tr("Add"))]);
+    dialog.set_response_appearance("add", adw::ResponseAppearance::Suggested);
+    dialog.set_default_response(Some("add"));
+    dialog.set_close_response("cancel");
+
+    let audio_model = audio_model.clone();
+    dialog.connect_response(
+        Some("add"),
+        glib::clone!(
+            #[weak]
+            name_entry,
+            move |_, _| {
+                // Unnamed bookmarks go by where they are
+                let name = match name_entry.text().trim() {
+                    "" => format_position(position),
+                    name => name.to_string(),
+                };
+                app.resume_store()
+                    .borrow_mut()
+                    .add_bookmark(&song.id(), &name, position);
+                save_bookmarks(&audio_model);
+            }
+        ),
+    );
+
+    dialog.present(parent);
+    glib::idle_add_local_once(glib::clone!(
+        #[weak]
+        name_entry,
+        move || {
+            name_entry.grab_focus();
+        }
+    ));
+}
+
+/// The current song's bookmarks, to jump to or remove
+pub fn show_bookmarks(parent: Option<&gtk::Window>, audio_model: &AudioModel) {
+    let (Some(song), Some(app)) = (bookmarkable_song(audio_model), audio_model.application())
+    else {
+        return;
+    };
+    let bookmarks = app.resume_store().borrow().bookmarks(&song.id());
+    let audio_model = audio_model.clone();
+
+    let dialog = adw::AlertDialog::builder().heading(tr("Bookmarks")).build();
+    dialog.add_responses(&[("close", &tr("Close")), ("add", &tr("Add Bookmark"))]);
+    dialog.set_default_response(Some("close"));
+    dialog.set_close_response("close");
+
+    if bookmarks.is_empty() {
+        dialog.set_body(&tr("This song has no bookmarks yet."));
+    } else {
+        let list = gtk::ListBox::builder()
+            .selection_mode(gtk::SelectionMode::None)
+            .css_classes(["boxed-list"])
+            .build();
+        for bookmark in &bookmarks {
+            let row = adw::ActionRow::builder()
+                .title(&bookmark.name)
+                .subtitle(format_position(bookmark.position))
+                .use_markup(false)
+                .activatable(true)
+                .build();
+            let remove_button = gtk::Button::builder()
+                .icon_name("user-trash-symbolic")
+                .tooltip_text(tr("Remove Bookmark"))
+                .valign(gtk::Align::Center)
+                .css_classes(["flat"])
+                .build();
+            remove_button.connect_clicked(glib::clone!(
+                #[weak]
+                list,
+                #[weak]
+                row,
+                #[strong]
+                app,
+                #[strong]
+                song,
+                #[strong]
+                audio_model,
+                move |_| {
+                    // Rows stay in the same order as the bookmarks
+                    app.resume_store()
+                        .borrow_mut()
+                        .remove_bookmark(&song.id(), row.index() as usize);
+                    list.remove(&row);
+                    save_bookmarks(&audio_model);
+                }
+            ));
+            row.add_suffix(&remove_button);
+
+            let position = bookmark.position;
+            row.connect_activated(glib::clone!(
+                #[weak]
+                dialog,
+                #[strong]
+                audio_model,
+                move |_| {
+                    audio_model.seek(position as u32);
+                    dialog.close();
+                }
+            ));
+            list.append(&row);
+        }
+        let scrolled_window = gtk::ScrolledWindow::builder()
+            .hscrollbar_policy(gtk::PolicyType::Never)
+            .propagate_natural_height(true)
+            .max_content_height(360)
+            .child(&list)
+            .build();
+        dialog.set_extra_child(Some(&scrolled_window));
+    }
+
+    let window = parent.cloned();
+    dialog.connect_response(Some("add"), move |_, _| {
+        add_bookmark(window.as_ref(), &audio_model);
+    });
+    dialog.present(parent);
+}
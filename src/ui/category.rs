@@ -0,0 +1,107 @@
+use crate::{
+    i18n::ngettext, library_utils::play_category, models::CategoryModel,
+    ui::widget_ext::WidgetApplicationExt,
+};
+use glib::Object;
+use gtk::{gio, glib, subclass::prelude::*};
+
+glib::wrapper! {
+    pub struct CategoryCard(ObjectSubclass<imp::CategoryCard>)
+    @extends gtk::Widget, gtk::Box,
+        @implements gio::ActionMap, gio::ActionGroup, gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget;
+}
+impl CategoryCard {
+    pub fn new() -> Self {
+        Object::builder().build()
+    }
+
+    pub fn set_category_model(&self, category_model: &CategoryModel) {
+        let imp = self.imp();
+        let card = &imp.media_card;
+        card.set_primary_text(&category_model.name());
+        card.set_secondary_text(&category_counts(category_model));
+        card.set_mosaic_ids(&category_model.cover_art());
+        imp.category_model.replace(Some(category_model.clone()));
+    }
+
+    /// The play button shuffles, a category has no natural order
+    pub fn play(&self) {
+        if let Some(category_model) = self.imp().category_model.borrow().as_ref() {
+            play_category(
+                category_model.category(),
+                &category_model.name(),
+                true,
+                &self.get_application(),
+            );
+        }
+    }
+
+    pub fn unbind(&self) {
+        self.imp().media_card.unbind_image();
+    }
+}
+
+/// "3 albums • 40 songs"
+pub fn category_counts(category_model: &CategoryModel) -> String {
+    let albums = ngettext("{} album", "{} albums", category_model.album_count())
+        .replace("{}", &category_model.album_count().to_string());
+    let songs = ngettext("{} song", "{} songs", category_model.song_count())
+        .replace("{}", &category_model.song_count().to_string());
+    format!("{albums} • {songs}")
+}
+
+impl Default for CategoryCard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+mod imp {
+    use std::cell::RefCell;
+
+    use crate::{models::CategoryModel, ui::media_card::MediaCard};
+    use adw::subclass::prelude::*;
+    use glib::subclass::InitializingObject;
+    use gtk::{
+        CompositeTemplate,
+        glib::{self},
+    };
+
+    #[derive(CompositeTemplate, Default)]
+    #[template(resource = "/io/m51/Gelly/ui/category.ui")]
+    pub struct CategoryCard {
+        #[template_child]
+        pub media_card: TemplateChild<MediaCard>,
+
+        pub category_model: RefCell<Option<CategoryModel>>,
+        pub compact_mode_binding: RefCell<Option<glib::Binding>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for CategoryCard {
+        const NAME: &'static str = "GellyCategoryCard";
+        type Type = super::CategoryCard;
+        type ParentType = gtk::Box;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+    impl BoxImpl for CategoryCard {}
+    impl ObjectImpl for CategoryCard {
+        fn constructed(&self) {
+            self.parent_constructed();
+            self.media_card.connect_play_clicked(glib::clone!(
+                #[weak(rename_to = category)]
+                self.obj(),
+                move || {
+                    category.play();
+                }
+            ));
+        }
+    }
+    impl WidgetImpl for CategoryCard {}
+}
@@ -0,0 +1,242 @@
+use crate::{
+    i18n::{ngettext, tr},
+    library_utils::play_category,
+    models::{CategoryModel, SongModel},
+    ui::{
+        album::Album, category::category_counts, page_traits::DetailPage,
+        widget_ext::WidgetApplicationExt,
+    },
+};
+use adw::prelude::*;
+use glib::Object;
+use gtk::{gio, glib, subclass::prelude::*};
+use log::warn;
+
+/// How many of the most played songs to show
+const TOP_SONGS: usize = 10;
+
+glib::wrapper! {
+    pub struct CategoryDetail(ObjectSubclass<imp::CategoryDetail>)
+    @extends gtk::Widget, gtk::Box,
+        @implements gio::ActionMap, gio::ActionGroup, gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget;
+}
+
+impl DetailPage for CategoryDetail {
+    type Model = CategoryModel;
+
+    fn set_model(&self, model: &CategoryModel) {
+        let imp = self.imp();
+        imp.model.replace(Some(model.clone()));
+        imp.category_name.set_text(&model.name());
+        imp.category_counts.set_text(&category_counts(model));
+        self.pull_artists();
+        self.pull_top_songs();
+        self.pull_albums();
+    }
+
+    fn get_model(&self) -> Option<Self::Model> {
+        self.imp().model.borrow().clone()
+    }
+}
+
+impl CategoryDetail {
+    pub fn new() -> Self {
+        Object::builder().build()
+    }
+
+    fn pull_artists(&self) {
+        let imp = self.imp();
+        imp.artists_box.remove_all();
+        let Some(model) = self.get_model() else {
+            return;
+        };
+        let artists = self
+            .get_application()
+            .library()
+            .artists_for_category(model.category(), &model.name());
+        imp.artists_heading.set_visible(!artists.is_empty());
+        imp.artists_box.set_visible(!artists.is_empty());
+        for artist in artists {
+            let button = gtk::Button::builder()
+                .label(artist.name())
+                .css_classes(["pill"])
+                .build();
+            button.connect_clicked(glib::clone!(
+                #[weak(rename_to = category_detail)]
+                self,
+                #[strong]
+                artist,
+                move |_| category_detail
+                    .get_root_window()
+                    .show_artist_detail(&artist)
+            ));
+            imp.artists_box.append(&button);
+        }
+    }
+
+    fn pull_top_songs(&self) {
+        let imp = self.imp();
+        imp.top_songs_list.remove_all();
+        let Some(model) = self.get_model() else {
+            return;
+        };
+        let mut songs = self
+            .get_application()
+            .library()
+            .songs_for_category(model.category(), &model.name());
+        songs.retain(|song| song.play_count() > 0);
+        songs.sort_by_key(|song| std::cmp::Reverse(song.play_count()));
+        songs.truncate(TOP_SONGS);
+        for (index, song) in songs.iter().enumerate() {
+            let row = adw::ActionRow::builder()
+                .title(song.title())
+                .subtitle(format!("{} • {}", song.artists_string(), song.album()))
+                .use_markup(false)
+                .activatable(true)
+                .build();
+            let plays = song.play_count();
+            row.add_suffix(
+                &gtk::Label::builder()
+                    .label(
+                        ngettext("{} play", "{} plays", plays as u32)
+                            .replace("{}", &plays.to_string()),
+                    )
+                    .css_classes(["dimmed"])
+                    .build(),
+            );
+            row.connect_activated(glib::clone!(
+                #[weak(rename_to = category_detail)]
+                self,
+                move |_| category_detail.play_top_songs(index)
+            ));
+            imp.top_songs_list.append(&row);
+        }
+        imp.top_songs_heading.set_visible(!songs.is_empty());
+        imp.top_songs_list.set_visible(!songs.is_empty());
+        imp.top_songs.replace(songs);
+    }
+
+    fn play_top_songs(&self, index: usize) {
+        let songs: Vec<SongModel> = self.imp().top_songs.borrow().clone();
+        if let Some(audio_model) = self.get_application().audio_model() {
+            audio_model.set_queue(songs, index, true);
+        } else {
+            self.toast(&tr("Audio model not initialized, please restart"), None);
+            warn!("No audio model found");
+        }
+    }
+
+    fn pull_albums(&self) {
+        let imp = self.imp();
+        imp.albums_box.remove_all();
+        let Some(model) = self.get_model() else {
+            return;
+        };
+        let mut albums = self
+            .get_application()
+            .library()
+            .albums_for_category(model.category(), &model.name());
+        albums.sort_by_key(|album| (album.year(), album.name().to_lowercase()));
+        imp.albums_heading.set_visible(!albums.is_empty());
+        imp.albums_box.set_visible(!albums.is_empty());
+        for album in &albums {
+            let widget = Album::new();
+            widget.set_album_model(album);
+            imp.albums_box.append(&widget);
+        }
+        imp.albums.replace(albums);
+    }
+
+    fn activate_album(&self, index: usize) {
+        let album = self.imp().albums.borrow().get(index).cloned();
+        if let Some(album) = album {
+            self.get_root_window().show_album_detail(&album);
+        }
+    }
+
+    fn play(&self, shuffle: bool) {
+        if let Some(model) = self.get_model() {
+            play_category(
+                model.category(),
+                &model.name(),
+                shuffle,
+                &self.get_application(),
+            );
+        }
+    }
+}
+
+impl Default for CategoryDetail {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+mod imp {
+    use std::cell::RefCell;
+
+    use adw::subclass::prelude::*;
+    use glib::subclass::InitializingObject;
+    use gtk::{CompositeTemplate, glib, prelude::*};
+
+    use crate::models::{AlbumModel, CategoryModel, SongModel};
+
+    #[derive(CompositeTemplate, Default)]
+    #[template(resource = "/io/m51/Gelly/ui/category_detail.ui")]
+    pub struct CategoryDetail {
+        #[template_child]
+        pub category_name: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub category_counts: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub artists_heading: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub artists_box: TemplateChild<gtk::FlowBox>,
+        #[template_child]
+        pub top_songs_heading: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub top_songs_list: TemplateChild<gtk::ListBox>,
+        #[template_child]
+        pub albums_heading: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub albums_box: TemplateChild<gtk::FlowBox>,
+
+        pub model: RefCell<Option<CategoryModel>>,
+        pub albums: RefCell<Vec<AlbumModel>>,
+        pub top_songs: RefCell<Vec<SongModel>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for CategoryDetail {
+        const NAME: &'static str = "GellyCategoryDetail";
+        type Type = super::CategoryDetail;
+        type ParentType = gtk::Box;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+            klass.install_action("category.play", None, |category, _, _| {
+                category.play(false);
+            });
+            klass.install_action("category.shuffle", None, |category, _, _| {
+                category.play(true);
+            });
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for CategoryDetail {
+        fn constructed(&self) {
+            self.parent_constructed();
+            self.albums_box.connect_child_activated(glib::clone!(
+                #[weak(rename_to = imp)]
+                self,
+                move |_, child| imp.obj().activate_album(child.index() as usize)
+            ));
+        }
+    }
+    impl WidgetImpl for CategoryDetail {}
+    impl BoxImpl for CategoryDetail {}
+}
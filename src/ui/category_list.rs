@@ -0,0 +1,402 @@
+use crate::{
+    application::Application,
+    config,
+    i18n::tr,
+    library_utils::play_category,
+    models::{Category, CategoryModel},
+    ui::{
+        category::CategoryCard,
+        list_helpers::{create_string_filter, handle_grid_activation},
+        page_traits::{SortDirection, SortType, TopPage},
+        widget_ext::WidgetApplicationExt,
+    },
+};
+use glib::Object;
+use gtk::{
+    gio,
+    glib::{self},
+    prelude::*,
+    subclass::prelude::*,
+};
+
+glib::wrapper! {
+    pub struct CategoryList(ObjectSubclass<imp::CategoryList>)
+    @extends gtk::Widget, gtk::Box,
+        @implements gio::ActionMap, gio::ActionGroup, gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget;
+}
+
+impl TopPage for CategoryList {
+    fn can_new(&self) -> bool {
+        false
+    }
+
+    fn has_genres(&self) -> bool {
+        false
+    }
+
+    fn play_selected(&self) {
+        if let Some(selection) = self.imp().grid_view.model()
+            && let Some(single_selection) = selection.downcast_ref::<gtk::SingleSelection>()
+            && let Some(selected_item) = single_selection.selected_item()
+            && let Some(category_model) = selected_item.downcast_ref::<CategoryModel>()
+        {
+            play_category(
+                category_model.category(),
+                &category_model.name(),
+                true,
+                &self.get_application(),
+            );
+        }
+    }
+
+    fn search_changed(&self, query: &str) {
+        let search = if query.is_empty() { None } else { Some(query) };
+        self.imp().name_filter.get().unwrap().set_search(search);
+    }
+
+    fn genre_changed(&self, _genre: Option<&str>) {}
+
+    fn sort_options(&self) -> &[SortType] {
+        &[SortType::Name, SortType::NumSongs, SortType::PlayCount]
+    }
+
+    fn current_sort_by(&self) -> u32 {
+        config::get_categories_sort_by().min(self.sort_options().len() as u32 - 1)
+    }
+
+    fn current_sort_direction(&self) -> u32 {
+        config::get_categories_sort_direction()
+    }
+
+    fn apply_sort(&self, sort_by: u32, direction: u32) {
+        config::set_categories_sort_by(sort_by);
+        config::set_categories_sort_direction(direction);
+        self.imp().sort_state.set((sort_by, direction));
+        self.imp()
+            .sorter
+            .get()
+            .unwrap()
+            .changed(gtk::SorterChange::Different);
+        self.reset_position();
+    }
+
+    fn supports_favorites(&self) -> bool {
+        false
+    }
+
+    fn filter_favorites(&self, _active: bool) {}
+
+    fn reset_position(&self) {
+        let imp = self.imp();
+        if imp.grid_view.model().is_some_and(|m| m.n_items() > 0) {
+            imp.grid_view
+                .scroll_to(0, gtk::ListScrollFlags::NONE, None::<gtk::ScrollInfo>);
+        }
+    }
+}
+
+impl CategoryList {
+    pub fn new() -> Self {
+        Object::builder().build()
+    }
+
+    pub fn category(&self) -> Category {
+        self.imp().category.get()
+    }
+
+    pub fn set_category(&self, category: Category) {
+        let imp = self.imp();
+        if imp.category.get() == category {
+            return;
+        }
+        imp.category.set(category);
+        config::set_browse_category(category.index());
+        self.update_category_widgets();
+        self.pull_categories();
+    }
+
+    fn update_category_widgets(&self) {
+        let imp = self.imp();
+        let category = self.category();
+        imp.category_toggles
+            .set_active_name(Some(Self::toggle_name(category)));
+        imp.empty.set_title(&match category {
+            Category::Genre => tr("No Genres"),
+            Category::Composer => tr("No Composers"),
+            Category::Decade => tr("No Decades"),
+            Category::Year => tr("No Years"),
+            Category::Label => tr("No Labels"),
+        });
+    }
+
+    fn toggle_name(category: Category) -> &'static str {
+        match category {
+            Category::Genre => "genre",
+            Category::Composer => "composer",
+            Category::Decade => "decade",
+            Category::Year => "year",
+            Category::Label => "label",
+        }
+    }
+
+    pub fn pull_categories(&self) {
+        let category = self.category();
+        let app = self.get_application();
+        let library = app.library();
+        let store = self
+            .imp()
+            .store
+            .get()
+            .expect("CategoryList store should be initialized.");
+        store.remove_all();
+        if library.needs_credits(category) {
+            self.set_is_loading(true);
+            app.load_song_credits(
+                category,
+                glib::clone!(
+                    #[weak(rename_to = category_list)]
+                    self,
+                    move || {
+                        category_list.set_is_loading(false);
+                        // Left empty if they couldn't be fetched
+                        if category_list.category() == category && !library.needs_credits(category)
+                        {
+                            category_list.pull_categories();
+                        }
+                    }
+                ),
+            );
+            return;
+        }
+        store.extend_from_slice(&library.categories_from_library(category));
+        self.apply_sort(self.current_sort_by(), self.current_sort_direction());
+    }
+
+    pub fn activate_category(&self, index: u32) {
+        let window = self.get_root_window();
+        handle_grid_activation::<CategoryModel, _>(
+            &self.imp().grid_view,
+            index,
+            |category_model| {
+                window.show_category_detail(category_model);
+            },
+        );
+    }
+
+    pub fn setup_library_connection(&self) {
+        let app = self.get_application();
+        app.connect_closure(
+            "library-refreshed",
+            false,
+            glib::closure_local!(
+                #[weak(rename_to = category_list)]
+                self,
+                move |_app: Application, _total_record_count: u64| {
+                    category_list.pull_categories();
+                    category_list.set_is_loading(false);
+                }
+            ),
+        );
+
+        app.connect_closure(
+            "library-refresh-requested",
+            false,
+            glib::closure_local!(
+                #[weak(rename_to = category_list)]
+                self,
+                move |_app: Application| {
+                    category_list.set_is_loading(true);
+                }
+            ),
+        );
+    }
+
+    fn build_sorter(&self) -> gtk::CustomSorter {
+        let sort_state = self.imp().sort_state.clone();
+        let options: Vec<SortType> = self.sort_options().to_vec();
+        gtk::CustomSorter::new(move |a, b| {
+            let (sort_by, direction_raw) = sort_state.get();
+            let direction =
+                SortDirection::try_from(direction_raw).unwrap_or(SortDirection::Ascending);
+            let (a, b) = match direction {
+                SortDirection::Ascending => (a, b),
+                SortDirection::Descending => (b, a),
+            };
+            let a = a.downcast_ref::<CategoryModel>().unwrap();
+            let b = b.downcast_ref::<CategoryModel>().unwrap();
+            match options[sort_by as usize] {
+                SortType::Name => a.name().to_lowercase().cmp(&b.name().to_lowercase()).into(),
+                SortType::NumSongs => a.song_count().cmp(&b.song_count()).into(),
+                SortType::PlayCount => a.play_count().cmp(&b.play_count()).into(),
+                _ => gtk::Ordering::Equal,
+            }
+        })
+    }
+
+    fn setup_model(&self) {
+        let store = gio::ListStore::new::<CategoryModel>();
+
+        let name_filter = create_string_filter::<CategoryModel>("name");
+        let search_model =
+            gtk::FilterListModel::new(Some(store.clone()), Some(name_filter.clone()));
+
+        let sorter = self.build_sorter();
+        let sort_model = gtk::SortListModel::new(Some(search_model), Some(sorter.clone()));
+        let selection = gtk::SingleSelection::new(Some(sort_model));
+
+        selection.connect_items_changed(glib::clone!(
+            #[weak(rename_to = category_list)]
+            self,
+            move |sel, _, _, _| {
+                category_list.set_empty(sel.n_items() == 0);
+            }
+        ));
+
+        let factory = gtk::SignalListItemFactory::new();
+        factory.connect_setup(glib::clone!(
+            #[weak(rename_to = category_list)]
+            self,
+            move |_, list_item| {
+                let item = list_item.downcast_ref::<gtk::ListItem>().unwrap();
+                let widget = CategoryCard::new();
+                let binding = category_list
+                    .bind_property(
+                        "compact-mode",
+                        &widget.imp().media_card.get(),
+                        "compact-mode",
+                    )
+                    .sync_create()
+                    .build();
+                widget.imp().compact_mode_binding.replace(Some(binding));
+                item.set_child(Some(&widget));
+            }
+        ));
+        factory.connect_bind(move |_, list_item| {
+            let list_item = list_item.downcast_ref::<gtk::ListItem>().unwrap();
+            let category_model = list_item.item().and_downcast::<CategoryModel>().unwrap();
+            let category_widget = list_item.child().and_downcast::<CategoryCard>().unwrap();
+            category_widget.set_category_model(&category_model);
+        });
+        factory.connect_unbind(move |_, list_item| {
+            let list_item = list_item.downcast_ref::<gtk::ListItem>().unwrap();
+            if let Some(category) = list_item.child().and_downcast::<CategoryCard>() {
+                category.unbind();
+            }
+        });
+        factory.connect_teardown(move |_, list_item| {
+            let list_item = list_item.downcast_ref::<gtk::ListItem>().unwrap();
+            if let Some(w) = list_item.child().and_downcast::<CategoryCard>() {
+                w.imp().compact_mode_binding.replace(None);
+            }
+        });
+
+        let imp = self.imp();
+        imp.grid_view.set_model(Some(&selection));
+        imp.grid_view.set_factory(Some(&factory));
+        imp.store.set(store).unwrap();
+        imp.name_filter.set(name_filter).unwrap();
+        imp.sorter.set(sorter).unwrap();
+    }
+
+    fn set_empty(&self, empty: bool) {
+        self.imp().empty.set_visible(empty);
+        self.imp().grid_view.set_visible(!empty);
+    }
+}
+
+impl Default for CategoryList {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+mod imp {
+    use std::cell::{Cell, OnceCell};
+    use std::rc::Rc;
+
+    use adw::subclass::prelude::*;
+    use glib::subclass::InitializingObject;
+
+    use crate::{config, models::Category};
+    use gtk::{
+        CompositeTemplate, gio,
+        glib::{self, Properties},
+        prelude::*,
+    };
+
+    #[derive(CompositeTemplate, Default, Properties)]
+    #[template(resource = "/io/m51/Gelly/ui/category_list.ui")]
+    #[properties(wrapper_type = super::CategoryList)]
+    pub struct CategoryList {
+        #[template_child]
+        pub category_toggles: TemplateChild<adw::ToggleGroup>,
+        #[template_child]
+        pub grid_view: TemplateChild<gtk::GridView>,
+        #[template_child]
+        pub empty: TemplateChild<adw::StatusPage>,
+
+        pub store: OnceCell<gio::ListStore>,
+        pub name_filter: OnceCell<gtk::StringFilter>,
+        pub sorter: OnceCell<gtk::CustomSorter>,
+        pub sort_state: Rc<Cell<(u32, u32)>>,
+        pub category: Cell<Category>,
+
+        #[property(get, set, default = false)]
+        pub compact_mode: Cell<bool>,
+        #[property(get, set, default = false)]
+        pub is_loading: Cell<bool>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for CategoryList {
+        const NAME: &'static str = "GellyCategoryList";
+        type Type = super::CategoryList;
+        type ParentType = gtk::Box;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for CategoryList {
+        fn constructed(&self) {
+            self.parent_constructed();
+            self.obj().setup_model();
+            self.category
+                .set(Category::from_index(config::get_browse_category()));
+            self.obj().update_category_widgets();
+
+            self.grid_view.connect_activate(glib::clone!(
+                #[weak(rename_to = category_list)]
+                self.obj(),
+                move |_, position| {
+                    category_list.activate_category(position);
+                }
+            ));
+
+            self.category_toggles
+                .connect_active_name_notify(glib::clone!(
+                    #[weak(rename_to = category_list)]
+                    self.obj(),
+                    move |toggles| {
+                        let category = Category::ALL
+                            .into_iter()
+                            .find(|c| {
+                                toggles.active_name().as_deref()
+                                    == Some(super::CategoryList::toggle_name(*c))
+                            })
+                            .unwrap_or_default();
+                        category_list.set_category(category);
+                    }
+                ));
+        }
+    }
+
+    impl WidgetImpl for CategoryList {}
+    impl BoxImpl for CategoryList {}
+}
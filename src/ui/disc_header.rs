@@ -0,0 +1,85 @@
+use glib::Object;
+use gtk::{gio, glib, prelude::*, subclass::prelude::*};
+
+use crate::{
+    i18n::{ngettext, tr},
+    jellyfin::utils::format_duration,
+};
+
+glib::wrapper! {
+    pub struct DiscHeader(ObjectSubclass<imp::DiscHeader>)
+    @extends gtk::Widget, gtk::Box,
+        @implements gio::ActionMap, gio::ActionGroup, gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget;
+}
+
+/// Section header above each disc of a multi-disc album. The buttons fire
+/// `album.play-disc` and `album.queue-disc` with the disc number.
+impl DiscHeader {
+    pub fn new() -> Self {
+        Object::builder().build()
+    }
+
+    pub fn set_disc(&self, disc: u32, title: Option<&str>, track_count: usize, duration: u64) {
+        let imp = self.imp();
+        let heading = match disc {
+            0 => tr("Unknown Disc"),
+            disc => tr("Disc {}").replace("{}", &disc.to_string()),
+        };
+        match title {
+            Some(title) => imp.title_label.set_text(&format!("{heading} – {title}")),
+            None => imp.title_label.set_text(&heading),
+        }
+        let tracks = ngettext("{} track", "{} tracks", track_count as u32)
+            .replace("{}", &track_count.to_string());
+        imp.summary_label
+            .set_text(&format!("{} • {}", tracks, format_duration(duration)));
+
+        let target = disc.to_variant();
+        imp.play_button.set_action_target_value(Some(&target));
+        imp.queue_button.set_action_target_value(Some(&target));
+    }
+}
+
+impl Default for DiscHeader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+mod imp {
+    use adw::subclass::prelude::*;
+    use glib::subclass::InitializingObject;
+    use gtk::{CompositeTemplate, glib};
+
+    #[derive(CompositeTemplate, Default)]
+    #[template(resource = "/io/m51/Gelly/ui/disc_header.ui")]
+    pub struct DiscHeader {
+        #[template_child]
+        pub title_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub summary_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub play_button: TemplateChild<gtk::Button>,
+        #[template_child]
+        pub queue_button: TemplateChild<gtk::Button>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for DiscHeader {
+        const NAME: &'static str = "GellyDiscHeader";
+        type Type = super::DiscHeader;
+        type ParentType = gtk::Box;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for DiscHeader {}
+    impl WidgetImpl for DiscHeader {}
+    impl BoxImpl for DiscHeader {}
+}
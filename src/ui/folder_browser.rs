@@ -0,0 +1,454 @@
+use crate::{
+    application::Application,
+    async_utils::spawn_tokio,
+    backend::BackendError,
+    i18n::{ngettext, tr},
+    jellyfin::api::Folder,
+    models::SongModel,
+    ui::{
+        page_traits::{SortDirection, SortType, TopPage},
+        widget_ext::WidgetApplicationExt,
+    },
+};
+use adw::prelude::*;
+use glib::Object;
+use gtk::{gio, glib, subclass::prelude::*};
+use log::warn;
+use rand::seq::SliceRandom;
+
+glib::wrapper! {
+    pub struct FolderBrowser(ObjectSubclass<imp::FolderBrowser>)
+    @extends gtk::Widget, gtk::Box,
+        @implements gio::ActionMap, gio::ActionGroup, gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget;
+}
+
+/// What to do with a folder's songs once they've been fetched
+#[derive(Debug, Clone, Copy)]
+enum FolderAction {
+    Play,
+    Shuffle,
+    QueueNext,
+    QueueLast,
+}
+
+impl TopPage for FolderBrowser {
+    fn can_new(&self) -> bool {
+        false
+    }
+
+    fn has_genres(&self) -> bool {
+        false
+    }
+
+    fn play_selected(&self) {
+        self.play_folder(FolderAction::Play);
+    }
+
+    fn search_changed(&self, query: &str) {
+        self.imp().search.replace(query.to_lowercase());
+        self.imp().list_box.invalidate_filter();
+    }
+
+    fn genre_changed(&self, _genre: Option<&str>) {}
+
+    fn sort_options(&self) -> &[SortType] {
+        &[SortType::Name]
+    }
+
+    fn current_sort_by(&self) -> u32 {
+        0
+    }
+
+    fn current_sort_direction(&self) -> u32 {
+        self.imp().sort_direction.get()
+    }
+
+    fn apply_sort(&self, _sort_by: u32, direction: u32) {
+        self.imp().sort_direction.set(direction);
+        self.show_entries();
+    }
+
+    fn supports_favorites(&self) -> bool {
+        false
+    }
+
+    fn filter_favorites(&self, _active: bool) {}
+
+    fn reset_position(&self) {
+        self.imp().scrolled_window.vadjustment().set_value(0.0);
+    }
+}
+
+impl FolderBrowser {
+    pub fn new() -> Self {
+        Object::builder().build()
+    }
+
+    fn folder_id(&self) -> Option<String> {
+        self.imp().folder.borrow().as_ref().map(|f| f.id.clone())
+    }
+
+    /// Opens a folder, or the top of the library when `folder_id` is `None`
+    pub fn open_folder(&self, folder_id: Option<String>) {
+        let app = self.get_application();
+        let library_id = app.library_id();
+        let backend = app.backend();
+        self.imp().requested.replace(folder_id.clone());
+        let request_id = folder_id.clone();
+        app.http_with_loading(
+            async move { backend.get_folder(&library_id, request_id.as_deref()).await },
+            glib::clone!(
+                #[weak(rename_to = folder_browser)]
+                self,
+                move |result: Result<Folder, BackendError>| {
+                    // Another folder was opened while this one loaded
+                    if *folder_browser.imp().requested.borrow() != folder_id {
+                        return;
+                    }
+                    match result {
+                        Ok(folder) => folder_browser.show_folder(folder),
+                        Err(err) if folder_id.is_some() => {
+                            // Subsonic doesn't let us open the folders above the artists
+                            warn!("Failed to open folder {:?}: {}", folder_id, err);
+                            folder_browser.open_folder(None);
+                        }
+                        Err(err) => {
+                            warn!("Failed to open library folder: {}", err);
+                            folder_browser.toast(&tr("Could not load folders"), None);
+                        }
+                    }
+                }
+            ),
+        );
+    }
+
+    /// Opens the folder a song's file is in
+    pub fn open_song_folder(&self, song_id: &str) {
+        let backend = self.get_application().backend();
+        let song_id = song_id.to_string();
+        spawn_tokio(
+            async move { backend.get_song_folder_id(&song_id).await },
+            glib::clone!(
+                #[weak(rename_to = folder_browser)]
+                self,
+                move |result: Result<Option<String>, BackendError>| match result {
+                    Ok(Some(folder_id)) => folder_browser.open_folder(Some(folder_id)),
+                    Ok(None) => folder_browser.toast(&tr("Song has no folder"), None),
+                    Err(err) => {
+                        warn!("Failed to find song folder: {}", err);
+                        folder_browser.toast(&tr("Could not find the song's folder"), None);
+                    }
+                }
+            ),
+        );
+    }
+
+    fn go_up(&self) {
+        let parent_id = self
+            .imp()
+            .folder
+            .borrow()
+            .as_ref()
+            .and_then(|f| f.parent_id.clone());
+        self.open_folder(parent_id);
+    }
+
+    fn show_folder(&self, folder: Folder) {
+        let imp = self.imp();
+        let songs = self
+            .get_application()
+            .library()
+            .songs_for_ids(&folder.song_ids);
+        let is_root = folder.parent_id.is_none();
+        imp.up_button.set_sensitive(!is_root);
+        imp.folder_name.set_text(&if is_root {
+            tr("All Folders")
+        } else {
+            folder.name.clone()
+        });
+        imp.folder_path
+            .set_text(folder.path.as_deref().unwrap_or_default());
+        imp.folder_path.set_visible(folder.path.is_some());
+        imp.songs.replace(songs);
+        imp.folder.replace(Some(folder));
+        self.show_entries();
+        self.reset_position();
+    }
+
+    /// Subfolders by name, then the songs in the order the server keeps them
+    fn show_entries(&self) {
+        let imp = self.imp();
+        imp.list_box.remove_all();
+        let Some(folder) = imp.folder.borrow().clone() else {
+            return;
+        };
+
+        let mut folders = folder.folders;
+        folders.sort_by_key(|f| f.name.to_lowercase());
+        if matches!(
+            SortDirection::try_from(imp.sort_direction.get()),
+            Ok(SortDirection::Descending)
+        ) {
+            folders.reverse();
+        }
+        for entry in folders {
+            let row = adw::ActionRow::builder()
+                .title(&entry.name)
+                .use_markup(false)
+                .activatable(true)
+                .build();
+            row.add_prefix(&gtk::Image::from_icon_name("folder-symbolic"));
+            row.add_suffix(&gtk::Image::from_icon_name("go-next-symbolic"));
+            row.connect_activated(glib::clone!(
+                #[weak(rename_to = folder_browser)]
+                self,
+                move |_| folder_browser.open_folder(Some(entry.id.clone()))
+            ));
+            imp.list_box.append(&row);
+        }
+
+        for (index, song) in imp.songs.borrow().iter().enumerate() {
+            let row = adw::ActionRow::builder()
+                .title(song.title())
+                .subtitle(song.artists_string())
+                .use_markup(false)
+                .activatable(true)
+                .build();
+            row.add_prefix(&gtk::Image::from_icon_name("audio-x-generic-symbolic"));
+            row.connect_activated(glib::clone!(
+                #[weak(rename_to = folder_browser)]
+                self,
+                move |_| folder_browser.play_song_at(index)
+            ));
+            imp.list_box.append(&row);
+        }
+
+        self.set_empty(imp.list_box.first_child().is_none());
+    }
+
+    fn set_empty(&self, empty: bool) {
+        self.imp().empty.set_visible(empty);
+        self.imp().scrolled_window.set_visible(!empty);
+    }
+
+    fn play_song_at(&self, index: usize) {
+        let songs = self.imp().songs.borrow().clone();
+        if let Some(audio_model) = self.get_application().audio_model() {
+            audio_model.set_queue(songs, index, true);
+        } else {
+            self.toast(&tr("Audio model not initialized, please restart"), None);
+            warn!("No audio model found");
+        }
+    }
+
+    /// Fetches every song under the folder, subfolders included
+    fn play_folder(&self, action: FolderAction) {
+        let app = self.get_application();
+        let library_id = app.library_id();
+        let backend = app.backend();
+        let folder_id = self.folder_id().filter(|id| *id != library_id);
+        app.http_with_loading(
+            async move {
+                backend
+                    .get_folder_song_ids(&library_id, folder_id.as_deref())
+                    .await
+            },
+            glib::clone!(
+                #[weak(rename_to = folder_browser)]
+                self,
+                move |result: Result<Vec<String>, BackendError>| match result {
+                    Ok(song_ids) => {
+                        let songs = folder_browser
+                            .get_application()
+                            .library()
+                            .songs_for_ids(&song_ids);
+                        folder_browser.apply_action(songs, action);
+                    }
+                    Err(err) => {
+                        warn!("Failed to fetch folder songs: {}", err);
+                        folder_browser.toast(&tr("Could not load folder songs"), None);
+                    }
+                }
+            ),
+        );
+    }
+
+    fn apply_action(&self, mut songs: Vec<SongModel>, action: FolderAction) {
+        let Some(audio_model) = self.get_application().audio_model() else {
+            self.toast(&tr("Audio model not initialized, please restart"), None);
+            warn!("No audio model found");
+            return;
+        };
+        if songs.is_empty() {
+            self.toast(&tr("No songs in this folder"), None);
+            return;
+        }
+        match action {
+            FolderAction::Play => audio_model.set_queue(songs, 0, false),
+            FolderAction::Shuffle => {
+                songs.shuffle(&mut rand::rng());
+                audio_model.set_queue(songs, 0, true);
+            }
+            FolderAction::QueueNext | FolderAction::QueueLast => {
+                let song_cnt = songs.len();
+                if matches!(action, FolderAction::QueueLast) {
+                    audio_model.append_to_queue(songs);
+                } else {
+                    audio_model.prepend_to_queue(songs);
+                }
+                self.toast(
+                    &ngettext(
+                        "1 song added to queue",
+                        "{} songs added to queue",
+                        song_cnt as u32,
+                    )
+                    .replace("{}", &song_cnt.to_string()),
+                    None,
+                );
+            }
+        }
+    }
+
+    pub fn setup_library_connection(&self) {
+        let app = self.get_application();
+        app.connect_closure(
+            "library-refreshed",
+            false,
+            glib::closure_local!(
+                #[weak(rename_to = folder_browser)]
+                self,
+                move |app: Application, _total_record_count: u64| {
+                    // Start over when the library changed underneath us
+                    let loaded_library = folder_browser.imp().library_id.replace(app.library_id());
+                    if folder_browser.imp().folder.borrow().is_none()
+                        || loaded_library != app.library_id()
+                    {
+                        folder_browser.open_folder(None);
+                    } else if let Some(folder) = folder_browser.imp().folder.borrow().as_ref() {
+                        // Pick up songs that weren't in the library before
+                        let songs = app.library().songs_for_ids(&folder.song_ids);
+                        folder_browser.imp().songs.replace(songs);
+                    }
+                    folder_browser.show_entries();
+                    folder_browser.set_is_loading(false);
+                }
+            ),
+        );
+
+        app.connect_closure(
+            "library-refresh-requested",
+            false,
+            glib::closure_local!(
+                #[weak(rename_to = folder_browser)]
+                self,
+                move |_app: Application| {
+                    folder_browser.set_is_loading(true);
+                }
+            ),
+        );
+    }
+}
+
+impl Default for FolderBrowser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+mod imp {
+    use std::cell::{Cell, RefCell};
+
+    use adw::subclass::prelude::*;
+    use glib::subclass::InitializingObject;
+
+    use crate::{jellyfin::api::Folder, models::SongModel};
+    use gtk::{
+        CompositeTemplate,
+        glib::{self, Properties},
+        prelude::*,
+    };
+
+    #[derive(CompositeTemplate, Default, Properties)]
+    #[template(resource = "/io/m51/Gelly/ui/folder_browser.ui")]
+    #[properties(wrapper_type = super::FolderBrowser)]
+    pub struct FolderBrowser {
+        #[template_child]
+        pub up_button: TemplateChild<gtk::Button>,
+        #[template_child]
+        pub folder_name: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub folder_path: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub scrolled_window: TemplateChild<gtk::ScrolledWindow>,
+        #[template_child]
+        pub list_box: TemplateChild<gtk::ListBox>,
+        #[template_child]
+        pub empty: TemplateChild<adw::StatusPage>,
+
+        pub folder: RefCell<Option<Folder>>,
+        pub songs: RefCell<Vec<SongModel>>,
+        /// The folder we're waiting on, `None` for the top of the library
+        pub requested: RefCell<Option<String>>,
+        pub library_id: RefCell<String>,
+        pub search: RefCell<String>,
+        pub sort_direction: Cell<u32>,
+
+        #[property(get, set, default = false)]
+        pub compact_mode: Cell<bool>,
+        #[property(get, set, default = false)]
+        pub is_loading: Cell<bool>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for FolderBrowser {
+        const NAME: &'static str = "GellyFolderBrowser";
+        type Type = super::FolderBrowser;
+        type ParentType = gtk::Box;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+            klass.install_action("folder.up", None, |folder_browser, _, _| {
+                folder_browser.go_up();
+            });
+            klass.install_action("folder.play", None, |folder_browser, _, _| {
+                folder_browser.play_folder(super::FolderAction::Play);
+            });
+            klass.install_action("folder.shuffle", None, |folder_browser, _, _| {
+                folder_browser.play_folder(super::FolderAction::Shuffle);
+            });
+            klass.install_action("folder.queue_next", None, |folder_browser, _, _| {
+                folder_browser.play_folder(super::FolderAction::QueueNext);
+            });
+            klass.install_action("folder.queue_last", None, |folder_browser, _, _| {
+                folder_browser.play_folder(super::FolderAction::QueueLast);
+            });
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for FolderBrowser {
+        fn constructed(&self) {
+            self.parent_constructed();
+            self.list_box.set_filter_func(glib::clone!(
+                #[weak(rename_to = folder_browser)]
+                self.obj(),
+                #[upgrade_or]
+                true,
+                move |row| {
+                    let search = folder_browser.imp().search.borrow();
+                    search.is_empty()
+                        || row
+                            .downcast_ref::<adw::ActionRow>()
+                            .is_some_and(|row| row.title().to_lowercase().contains(&*search))
+                }
+            ));
+        }
+    }
+
+    impl WidgetImpl for FolderBrowser {}
+    impl BoxImpl for FolderBrowser {}
+}
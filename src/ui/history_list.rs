@@ -0,0 +1,401 @@
+use crate::{
+    application::Application,
+    history::PlayRecord,
+    i18n::tr,
+    ui::{
+        page_traits::{SortDirection, SortType, TopPage},
+        widget_ext::WidgetApplicationExt,
+    },
+};
+use adw::prelude::*;
+use glib::Object;
+use gtk::{gio, glib, subclass::prelude::*};
+use log::warn;
+
+/// The page only shows this many of the most recent plays, exports have all
+/// of them
+const MAX_SHOWN_PLAYS: usize = 500;
+
+glib::wrapper! {
+    pub struct HistoryList(ObjectSubclass<imp::HistoryList>)
+    @extends gtk::Widget, gtk::Box,
+        @implements gio::ActionMap, gio::ActionGroup, gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget;
+}
+
+impl TopPage for HistoryList {
+    fn can_new(&self) -> bool {
+        false
+    }
+
+    fn has_genres(&self) -> bool {
+        false
+    }
+
+    fn play_selected(&self) {
+        let last = self
+            .get_application()
+            .play_history()
+            .borrow()
+            .plays()
+            .last()
+            .cloned();
+        if let Some(play) = last {
+            self.play_again(&play);
+        }
+    }
+
+    fn create_new(&self) {}
+
+    fn search_changed(&self, query: &str) {
+        self.imp().search.replace(query.to_lowercase());
+        self.show_history();
+    }
+
+    fn genre_changed(&self, _genre: Option<&str>) {}
+
+    fn sort_options(&self) -> &[SortType] {
+        &[SortType::LastPlayed]
+    }
+
+    fn current_sort_by(&self) -> u32 {
+        0
+    }
+
+    fn current_sort_direction(&self) -> u32 {
+        self.imp().sort_direction.get()
+    }
+
+    fn apply_sort(&self, _sort_by: u32, direction: u32) {
+        self.imp().sort_direction.set(direction);
+        self.show_history();
+    }
+
+    fn supports_favorites(&self) -> bool {
+        false
+    }
+
+    fn filter_favorites(&self, _active: bool) {}
+
+    fn reset_position(&self) {
+        self.imp().scrolled_window.vadjustment().set_value(0.0);
+    }
+}
+
+impl HistoryList {
+    pub fn new() -> Self {
+        Object::builder().build()
+    }
+
+    /// Lays the plays out a day at a time, each day with its own list
+    fn show_history(&self) {
+        let imp = self.imp();
+        while let Some(child) = imp.days_box.first_child() {
+            imp.days_box.remove(&child);
+        }
+
+        let search = imp.search.borrow().clone();
+        let history = self.get_application().play_history();
+        let history = history.borrow();
+        let mut plays: Vec<&PlayRecord> = history
+            .plays()
+            .iter()
+            .rev()
+            .filter(|play| {
+                search.is_empty()
+                    || play.title.to_lowercase().contains(&search)
+                    || play.album.to_lowercase().contains(&search)
+                    || play
+                        .artists
+                        .iter()
+                        .any(|artist| artist.to_lowercase().contains(&search))
+            })
+            .take(MAX_SHOWN_PLAYS)
+            .collect();
+        if matches!(
+            SortDirection::try_from(imp.sort_direction.get()),
+            Ok(SortDirection::Ascending)
+        ) {
+            plays.reverse();
+        }
+
+        let mut current_day = None;
+        let mut day_list = None;
+        for play in &plays {
+            let Ok(time) = glib::DateTime::from_unix_local(play.played_at) else {
+                continue;
+            };
+            let day = (time.year(), time.day_of_year());
+            if current_day != Some(day) {
+                current_day = Some(day);
+                let heading = gtk::Label::builder()
+                    .label(day_label(&time))
+                    .halign(gtk::Align::Start)
+                    .css_classes(["heading"])
+                    .build();
+                let list = gtk::ListBox::builder()
+                    .selection_mode(gtk::SelectionMode::None)
+                    .css_classes(["boxed-list"])
+                    .build();
+                imp.days_box.append(&heading);
+                imp.days_box.append(&list);
+                day_list = Some(list);
+            }
+            if let Some(list) = &day_list {
+                list.append(&self.play_row(play, &time));
+            }
+        }
+
+        imp.empty.set_visible(plays.is_empty());
+        imp.scrolled_window.set_visible(!plays.is_empty());
+    }
+
+    fn play_row(&self, play: &PlayRecord, time: &glib::DateTime) -> adw::ActionRow {
+        let mut details = vec![];
+        if !play.artists.is_empty() {
+            details.push(play.artists.join(", "));
+        }
+        if let Ok(time) = time.format("%R") {
+            details.push(time.to_string());
+        }
+        if !play.completed {
+            details.push(tr("Skipped"));
+        }
+
+        let row = adw::ActionRow::builder()
+            .title(&play.title)
+            .subtitle(details.join(" · "))
+            .use_markup(false)
+            .activatable(true)
+            .build();
+        row.add_prefix(&gtk::Image::from_icon_name(if play.completed {
+            "object-select-symbolic"
+        } else {
+            "media-skip-forward-symbolic"
+        }));
+
+        let button = gtk::Button::builder()
+            .icon_name("media-playback-start-symbolic")
+            .tooltip_text(tr("Play Again"))
+            .valign(gtk::Align::Center)
+            .css_classes(["flat"])
+            .build();
+        let button_play = play.clone();
+        button.connect_clicked(glib::clone!(
+            #[weak(rename_to = history_list)]
+            self,
+            move |_| history_list.play_again(&button_play)
+        ));
+        row.add_suffix(&button);
+
+        let play = play.clone();
+        row.connect_activated(glib::clone!(
+            #[weak(rename_to = history_list)]
+            self,
+            move |_| history_list.play_again(&play)
+        ));
+        row
+    }
+
+    fn play_again(&self, play: &PlayRecord) {
+        let app = self.get_application();
+        let Some(audio_model) = app.audio_model() else {
+            self.toast(&tr("Audio model not initialized, please restart"), None);
+            warn!("No audio model found");
+            return;
+        };
+        let songs = app
+            .library()
+            .songs_for_ids(std::slice::from_ref(&play.song_id));
+        if songs.is_empty() {
+            self.toast(&tr("This song is no longer in the library"), None);
+            return;
+        }
+        audio_model.set_queue(songs, 0, true);
+    }
+
+    /// Saves the whole history, as JSON when the file name asks for it and
+    /// CSV otherwise
+    fn export(&self) {
+        let filters = gio::ListStore::new::<gtk::FileFilter>();
+        for (name, pattern) in [("CSV", "*.csv"), ("JSON", "*.json")] {
+            let filter = gtk::FileFilter::new();
+            filter.set_name(Some(name));
+            filter.add_pattern(pattern);
+            filters.append(&filter);
+        }
+        let dialog = gtk::FileDialog::builder()
+            .title(tr("Export History"))
+            .initial_name("listening-history.csv")
+            .filters(&filters)
+            .modal(true)
+            .build();
+        dialog.save(
+            self.get_gtk_window().as_ref(),
+            gio::Cancellable::NONE,
+            glib::clone!(
+                #[weak(rename_to = history_list)]
+                self,
+                move |result| {
+                    let Some(path) = result.ok().and_then(|file| file.path()) else {
+                        return;
+                    };
+                    let history = history_list.get_application().play_history();
+                    let history = history.borrow();
+                    let is_json = path
+                        .extension()
+                        .is_some_and(|extension| extension.eq_ignore_ascii_case("json"));
+                    let contents = if is_json {
+                        history.to_json()
+                    } else {
+                        Ok(history.to_csv())
+                    };
+                    let result = contents
+                        .map_err(|err| err.to_string())
+                        .and_then(|contents| {
+                            std::fs::write(&path, contents).map_err(|err| err.to_string())
+                        });
+                    match result {
+                        Ok(()) => history_list.toast(&tr("History exported"), None),
+                        Err(err) => {
+                            warn!("Failed to export play history: {}", err);
+                            history_list.toast(&tr("Could not export history"), None);
+                        }
+                    }
+                }
+            ),
+        );
+    }
+
+    fn clear_dialog(&self) {
+        let dialog = adw::AlertDialog::builder()
+            .heading(tr("Clear History?"))
+            .body(tr(
+                "Every play recorded on this computer will be forgotten. Play counts on the server stay as they are.",
+            ))
+            .build();
+        dialog.add_responses(&[("cancel", &tr("Cancel")), ("clear", &tr("Clear"))]);
+        dialog.set_response_appearance("clear", adw::ResponseAppearance::Destructive);
+        dialog.set_default_response(Some("cancel"));
+        dialog.set_close_response("cancel");
+        dialog.connect_response(
+            Some("clear"),
+            glib::clone!(
+                #[weak(rename_to = history_list)]
+                self,
+                move |_, _| history_list.get_application().clear_play_history()
+            ),
+        );
+        dialog.present(Some(&self.get_root_window()));
+    }
+
+    pub fn setup_library_connection(&self) {
+        self.show_history();
+        let app = self.get_application();
+        app.connect_closure(
+            "history-changed",
+            false,
+            glib::closure_local!(
+                #[weak(rename_to = history_list)]
+                self,
+                move |_app: Application| {
+                    history_list.show_history();
+                }
+            ),
+        );
+    }
+}
+
+/// "Today", "Yesterday" or the date
+fn day_label(time: &glib::DateTime) -> String {
+    let Ok(now) = glib::DateTime::now_local() else {
+        return String::new();
+    };
+    let same_day = |a: &glib::DateTime, b: &glib::DateTime| {
+        a.year() == b.year() && a.day_of_year() == b.day_of_year()
+    };
+    if same_day(time, &now) {
+        tr("Today")
+    } else if now
+        .add_days(-1)
+        .is_ok_and(|yesterday| same_day(time, &yesterday))
+    {
+        tr("Yesterday")
+    } else {
+        time.format("%A, %x")
+            .map(|date| date.to_string())
+            .unwrap_or_default()
+    }
+}
+
+impl Default for HistoryList {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+mod imp {
+    use std::cell::{Cell, RefCell};
+
+    use adw::subclass::prelude::*;
+    use glib::subclass::InitializingObject;
+
+    use crate::ui::page_traits::SortDirection;
+    use gtk::{
+        CompositeTemplate,
+        glib::{self, Properties},
+        prelude::*,
+    };
+
+    #[derive(CompositeTemplate, Default, Properties)]
+    #[template(resource = "/io/m51/Gelly/ui/history_list.ui")]
+    #[properties(wrapper_type = super::HistoryList)]
+    pub struct HistoryList {
+        #[template_child]
+        pub scrolled_window: TemplateChild<gtk::ScrolledWindow>,
+        #[template_child]
+        pub days_box: TemplateChild<gtk::Box>,
+        #[template_child]
+        pub empty: TemplateChild<adw::StatusPage>,
+
+        pub search: RefCell<String>,
+        pub sort_direction: Cell<u32>,
+
+        #[property(get, set, default = false)]
+        pub compact_mode: Cell<bool>,
+        #[property(get, set, default = false)]
+        pub is_loading: Cell<bool>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for HistoryList {
+        const NAME: &'static str = "GellyHistoryList";
+        type Type = super::HistoryList;
+        type ParentType = gtk::Box;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+            klass.install_action("history.export", None, |history_list, _, _| {
+                history_list.export();
+            });
+            klass.install_action("history.clear", None, |history_list, _, _| {
+                history_list.clear_dialog();
+            });
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for HistoryList {
+        fn constructed(&self) {
+            self.parent_constructed();
+            // Most recent plays first
+            self.sort_direction.set(SortDirection::Descending.into());
+        }
+    }
+
+    impl WidgetImpl for HistoryList {}
+    impl BoxImpl for HistoryList {}
+}
@@ -1,9 +1,16 @@
+use std::time::Duration;
+
 use glib::Object;
 use gtk::{gdk, gio, glib, prelude::*, subclass::prelude::*};
 use log::debug;
 
 use crate::{
-    async_utils::spawn_tokio, audio::model::AudioModel, backend::Backend, jellyfin::api::Lyric,
+    async_utils::spawn_tokio,
+    audio::model::AudioModel,
+    backend::Backend,
+    jellyfin::api::{Lyric, LyricWord},
+    lyrics::{self, LyricsOptions, LyricsQuery, TICKS_PER_MS},
+    ui::lyrics_editor,
 };
 
 glib::wrapper! {
@@ -12,9 +19,9 @@ glib::wrapper! {
         @implements gio::ActionMap, gio::ActionGroup, gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget;
 }
 
-// Since we only have seconds precision on audio position, but lyrics are synced to ms,
-// we add a small fudge because being slightly early feels better than being slightly late.
-const LYRICS_FUDGE: u64 = 10_000_000;
+// How often the position is polled while the lyrics are on screen, fine
+// enough for word timings to look smooth.
+const POSITION_INTERVAL: Duration = Duration::from_millis(80);
 
 impl Lyrics {
     pub fn new() -> Self {
@@ -50,13 +57,66 @@ impl Lyrics {
             ),
         );
 
-        audio_model.connect_notify_local(
-            Some("position"),
+        // The timer only runs while playing, catch seeks made when paused
+        audio_model.connect_closure(
+            "seeked",
+            false,
+            glib::closure_local!(
+                #[weak(rename_to = lyrics)]
+                self,
+                move |audio_model: AudioModel, _position: u32| {
+                    lyrics.update_lyrics_position(audio_model.get_position_ms());
+                }
+            ),
+        );
+    }
+
+    fn start_position_timer(&self) {
+        let source_id = glib::timeout_add_local(
+            POSITION_INTERVAL,
             glib::clone!(
                 #[weak(rename_to = lyrics)]
                 self,
-                move |audio_model, _| {
-                    lyrics.update_lyrics_position(audio_model.position());
+                #[upgrade_or]
+                glib::ControlFlow::Break,
+                move || {
+                    if let Some(audio_model) = lyrics.imp().audio_model.get()
+                        && audio_model.playing()
+                    {
+                        lyrics.update_lyrics_position(audio_model.get_position_ms());
+                    }
+                    glib::ControlFlow::Continue
+                }
+            ),
+        );
+        if let Some(previous) = self.imp().position_timer.replace(Some(source_id)) {
+            previous.remove();
+        }
+    }
+
+    fn stop_position_timer(&self) {
+        if let Some(source_id) = self.imp().position_timer.take() {
+            source_id.remove();
+        }
+    }
+
+    fn edit_lyrics(&self) {
+        let imp = self.imp();
+        let (Some(audio_model), Some(backend)) = (imp.audio_model.get(), imp.jellyfin.get()) else {
+            return;
+        };
+        let parent = self.root().and_downcast::<gtk::Window>();
+        lyrics_editor::show(
+            parent.as_ref(),
+            audio_model,
+            backend,
+            glib::clone!(
+                #[weak(rename_to = lyrics)]
+                self,
+                move || {
+                    if let Some(audio_model) = lyrics.imp().audio_model.get() {
+                        lyrics.fetch_lyrics(&audio_model.current_song_id());
+                    }
                 }
             ),
         );
@@ -79,23 +139,34 @@ impl Lyrics {
             debug!("Jellyfin client not set, cannot fetch lyrics");
             return;
         };
+        let Some(song) = imp
+            .audio_model
+            .get()
+            .and_then(|audio_model| audio_model.current_song())
+            .filter(|song| song.id() == item_id)
+        else {
+            debug!("Song {} is no longer playing, not fetching lyrics", item_id);
+            return;
+        };
 
-        let item_id = item_id.to_string();
+        let query = LyricsQuery::from_song(&song);
+        let options = LyricsOptions::from_settings();
         let jellyfin = jellyfin.clone();
 
         spawn_tokio(
-            async move { jellyfin.fetch_lyrics(&item_id).await },
+            async move { lyrics::fetch(&jellyfin, &query, &options).await },
             glib::clone!(
                 #[weak (rename_to = obj)]
                 self,
-                move |result| {
-                    match result {
-                        Ok(lyrics_resp) => {
-                            obj.create_lyrics_widgets(lyrics_resp.lyrics);
-                            obj.update_lyrics_position(0u32);
-                        }
-                        Err(_) => obj.create_lyrics_widgets(vec![]),
-                    }
+                move |lyrics_resp| {
+                    obj.create_lyrics_widgets(lyrics_resp.lyrics);
+                    let position_ms = obj
+                        .imp()
+                        .audio_model
+                        .get()
+                        .map(|audio_model| audio_model.get_position_ms())
+                        .unwrap_or(0);
+                    obj.update_lyrics_position(position_ms);
                 }
             ),
         );
@@ -130,9 +201,8 @@ impl Lyrics {
                     #[weak(rename_to = lyrics)]
                     self,
                     move |_, _, _, _| {
-                        let seconds = (ticks / 10_000_000) as u32;
                         if let Some(audio_model) = lyrics.imp().audio_model.get() {
-                            audio_model.seek(seconds);
+                            audio_model.seek_ms(ticks / TICKS_PER_MS);
                         }
                     }
                 ));
@@ -145,18 +215,59 @@ impl Lyrics {
 
         imp.lyrics.replace(lyrics);
         imp.lyrics_labels.replace(labels);
+        imp.current_line.set(None);
     }
 
-    fn update_lyrics_position(&self, position: u32) {
-        let ticks = u64::from(position).saturating_mul(10_000_000); // Jellyfin ticks
-        let lyrics = self.imp().lyrics.borrow();
-        let labels = self.imp().lyrics_labels.borrow();
+    fn update_lyrics_position(&self, position_ms: u64) {
+        let imp = self.imp();
+        let ticks = position_ms.saturating_mul(TICKS_PER_MS);
+        let lyrics = imp.lyrics.borrow();
+        let labels = imp.lyrics_labels.borrow();
 
         // Find the index of the currently playing lyric (last lyric that has started)
-        let current_lyric_index = lyrics.iter().rposition(|lyric| {
-            lyric.start.unwrap_or(u64::MAX) <= ticks.saturating_add(LYRICS_FUDGE)
-        });
+        let current_lyric_index = lyrics
+            .iter()
+            .rposition(|lyric| lyric.start.unwrap_or(u64::MAX) <= ticks);
+
+        if current_lyric_index != imp.current_line.get() {
+            if let Some(previous) = imp.current_line.get()
+                && let (Some(label), Some(lyric)) = (labels.get(previous), lyrics.get(previous))
+                && !lyric.words.is_empty()
+            {
+                label.set_text(&lyric.text);
+            }
+            imp.current_line.set(current_lyric_index);
+            imp.sung_words.set(usize::MAX);
+            self.highlight_line(&labels, current_lyric_index);
+        }
 
+        // Karaoke: sung words at full strength, the rest of the line faded
+        if let Some(index) = current_lyric_index
+            && let (Some(label), Some(lyric)) = (labels.get(index), lyrics.get(index))
+            && !lyric.words.is_empty()
+        {
+            let sung = lyric
+                .words
+                .iter()
+                .take_while(|word| word.start <= ticks)
+                .count();
+            if sung != imp.sung_words.get() {
+                imp.sung_words.set(sung);
+                let (done, rest) = lyric.words.split_at(sung);
+                let escape = |words: &[LyricWord]| {
+                    let text: String = words.iter().map(|word| word.text.as_str()).collect();
+                    glib::markup_escape_text(&text)
+                };
+                label.set_markup(&format!(
+                    "{}<span alpha=\"50%\">{}</span>",
+                    escape(done),
+                    escape(rest)
+                ));
+            }
+        }
+    }
+
+    fn highlight_line(&self, labels: &[gtk::Label], current_lyric_index: Option<usize>) {
         for (i, label) in labels.iter().enumerate() {
             let is_current = Some(i) == current_lyric_index;
             let is_past = current_lyric_index.is_some_and(|idx| i < idx);
@@ -201,10 +312,11 @@ mod imp {
     use gtk::{
         CompositeTemplate,
         glib::{self},
+        prelude::*,
     };
 
     use crate::{audio::model::AudioModel, backend::Backend, jellyfin::api::Lyric};
-    use std::cell::{OnceCell, RefCell};
+    use std::cell::{Cell, OnceCell, RefCell};
 
     #[derive(CompositeTemplate, Default)]
     #[template(resource = "/io/m51/Gelly/ui/lyrics.ui")]
@@ -221,10 +333,16 @@ mod imp {
         pub lyrics_box: TemplateChild<gtk::Box>,
         #[template_child]
         pub lyrics_label_empty: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub edit_button: TemplateChild<gtk::Button>,
         pub audio_model: OnceCell<AudioModel>,
         pub jellyfin: OnceCell<Backend>,
         pub lyrics: RefCell<Vec<Lyric>>,
         pub lyrics_labels: RefCell<Vec<gtk::Label>>,
+        pub position_timer: RefCell<Option<glib::SourceId>>,
+        pub current_line: Cell<Option<usize>>,
+        /// Words of the current line already highlighted
+        pub sung_words: Cell<usize>,
     }
 
     #[glib::object_subclass]
@@ -245,9 +363,24 @@ mod imp {
     impl ObjectImpl for Lyrics {
         fn constructed(&self) {
             self.parent_constructed();
+            self.edit_button.connect_clicked(glib::clone!(
+                #[weak(rename_to = lyrics)]
+                self.obj(),
+                move |_| lyrics.edit_lyrics()
+            ));
         }
     }
 
-    impl WidgetImpl for Lyrics {}
+    impl WidgetImpl for Lyrics {
+        fn map(&self) {
+            self.parent_map();
+            self.obj().start_position_timer();
+        }
+
+        fn unmap(&self) {
+            self.obj().stop_position_timer();
+            self.parent_unmap();
+        }
+    }
     impl BoxImpl for Lyrics {}
 }
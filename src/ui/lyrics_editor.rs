@@ -0,0 +1,493 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+
+use adw::prelude::*;
+use gtk::{Window, gdk, gio, glib};
+use log::warn;
+
+use crate::async_utils::spawn_tokio;
+use crate::audio::model::AudioModel;
+use crate::backend::Backend;
+use crate::i18n::tr;
+use crate::jellyfin::api::{Lyric, LyricsResponse};
+use crate::lyrics::{self, LyricsCache, LyricsOptions, LyricsQuery, TICKS_PER_MS, local, lrc};
+
+const PREVIEW_INTERVAL: Duration = Duration::from_millis(100);
+const NUDGE_MS: i64 = 100;
+
+/// Lines under the selection, or the line with the cursor
+fn selected_lines(buffer: &gtk::TextBuffer) -> (i32, i32) {
+    match buffer.selection_bounds() {
+        Some((start, end)) => (start.line(), end.line()),
+        None => {
+            let line = buffer.iter_at_mark(&buffer.get_insert()).line();
+            (line, line)
+        }
+    }
+}
+
+fn line_bounds(buffer: &gtk::TextBuffer, line: i32) -> Option<(gtk::TextIter, gtk::TextIter)> {
+    let start = buffer.iter_at_line(line)?;
+    let mut end = start;
+    if !end.ends_line() {
+        end.forward_to_line_end();
+    }
+    Some((start, end))
+}
+
+fn line_text(buffer: &gtk::TextBuffer, line: i32) -> Option<String> {
+    let (start, end) = line_bounds(buffer, line)?;
+    Some(buffer.text(&start, &end, false).to_string())
+}
+
+fn replace_line(buffer: &gtk::TextBuffer, line: i32, text: &str) {
+    if let Some((mut start, mut end)) = line_bounds(buffer, line) {
+        buffer.delete(&mut start, &mut end);
+        buffer.insert(&mut start, text);
+    }
+}
+
+fn buffer_text(buffer: &gtk::TextBuffer) -> String {
+    let mut text = buffer
+        .text(&buffer.start_iter(), &buffer.end_iter(), false)
+        .to_string();
+    if !text.ends_with('\n') {
+        text.push('\n');
+    }
+    text
+}
+
+/// Synced lyrics are edited as LRC, plain ones as one line per row ready to
+/// be stamped.
+fn editable_text(lyrics: &[Lyric]) -> String {
+    if lyrics.iter().any(|lyric| lyric.start.is_some()) {
+        lrc::to_lrc(lyrics)
+    } else {
+        lyrics
+            .iter()
+            .map(|lyric| format!("{}\n", lyric.text))
+            .collect()
+    }
+}
+
+fn stamp_line(buffer: &gtk::TextBuffer, text_view: &gtk::TextView, audio_model: &AudioModel) {
+    let line = buffer.iter_at_mark(&buffer.get_insert()).line();
+    let Some(text) = line_text(buffer, line) else {
+        return;
+    };
+    replace_line(
+        buffer,
+        line,
+        &lrc::stamp(&text, audio_model.get_position_ms()),
+    );
+
+    // Move on so the next tap stamps the next line
+    let next = buffer
+        .iter_at_line(line + 1)
+        .unwrap_or_else(|| buffer.end_iter());
+    buffer.place_cursor(&next);
+    text_view.scroll_mark_onscreen(&buffer.get_insert());
+}
+
+fn nudge_lines(buffer: &gtk::TextBuffer, delta_ms: i64) {
+    let had_selection = buffer.has_selection();
+    let (first, last) = selected_lines(buffer);
+    for line in first..=last {
+        if let Some(text) = line_text(buffer, line) {
+            replace_line(buffer, line, &lrc::shift(&text, delta_ms));
+        }
+    }
+    if had_selection
+        && let Some((start, _)) = line_bounds(buffer, first)
+        && let Some((_, end)) = line_bounds(buffer, last)
+    {
+        buffer.select_range(&start, &end);
+    }
+}
+
+fn play_from_line(buffer: &gtk::TextBuffer, audio_model: &AudioModel) {
+    let line = buffer.iter_at_mark(&buffer.get_insert()).line();
+    let Some(start) = line_text(buffer, line).and_then(|text| lrc::line_start(&text)) else {
+        return;
+    };
+    audio_model.seek_ms(start);
+    if !audio_model.playing() {
+        audio_model.play();
+    }
+}
+
+fn tool_button(label: &str, tooltip: &str) -> gtk::Button {
+    let button = gtk::Button::with_label(label);
+    button.set_tooltip_text(Some(tooltip));
+    button
+}
+
+/// Remembers the new lyrics so the lyrics view shows them without waiting
+/// for a library refresh.
+fn cache_lyrics(item_id: &str, text: &str) {
+    let lyrics = LyricsResponse {
+        lyrics: lrc::parse(text),
+    };
+    if let Err(e) = LyricsCache::new().and_then(|cache| cache.save(item_id, &lyrics)) {
+        warn!("Failed to cache edited lyrics: {}", e);
+    }
+}
+
+fn save_file(
+    dialog: &adw::Dialog,
+    toasts: &adw::ToastOverlay,
+    query: &LyricsQuery,
+    text: String,
+    on_saved: Rc<dyn Fn()>,
+) {
+    if let Some(folder) = LyricsOptions::from_settings().folder {
+        match local::save(&folder, query, &text) {
+            Ok(path) => {
+                cache_lyrics(&query.item_id, &text);
+                toasts.add_toast(adw::Toast::new(&format!(
+                    "{} {}",
+                    tr("Saved to"),
+                    path.display()
+                )));
+                on_saved();
+            }
+            Err(e) => {
+                warn!("Failed to save lyrics: {}", e);
+                toasts.add_toast(adw::Toast::new(&tr("Failed to save lyrics")));
+            }
+        }
+        return;
+    }
+
+    // Without a lyrics folder, ask where the file should go
+    let file_dialog = gtk::FileDialog::builder()
+        .title(tr("Save Lyrics"))
+        .initial_name(local::file_name(query))
+        .build();
+    let parent = dialog.root().and_downcast::<Window>();
+    let item_id = query.item_id.clone();
+    file_dialog.save(
+        parent.as_ref(),
+        None::<&gio::Cancellable>,
+        glib::clone!(
+            #[weak]
+            toasts,
+            move |result| {
+                let Some(path) = result.ok().and_then(|file| file.path()) else {
+                    return;
+                };
+                match std::fs::write(&path, &text) {
+                    Ok(()) => {
+                        cache_lyrics(&item_id, &text);
+                        toasts.add_toast(adw::Toast::new(&tr("Lyrics saved")));
+                        on_saved();
+                    }
+                    Err(e) => {
+                        warn!("Failed to save lyrics: {}", e);
+                        toasts.add_toast(adw::Toast::new(&tr("Failed to save lyrics")));
+                    }
+                }
+            }
+        ),
+    );
+}
+
+fn upload(
+    backend: &Backend,
+    toasts: &adw::ToastOverlay,
+    item_id: String,
+    text: String,
+    on_saved: Rc<dyn Fn()>,
+) {
+    let Some(jellyfin) = backend.jellyfin().cloned() else {
+        return;
+    };
+    let upload_text = text.clone();
+    let upload_id = item_id.clone();
+    spawn_tokio(
+        async move { jellyfin.upload_lyrics(&upload_id, upload_text).await },
+        glib::clone!(
+            #[weak]
+            toasts,
+            move |result| match result {
+                Ok(()) => {
+                    cache_lyrics(&item_id, &text);
+                    toasts.add_toast(adw::Toast::new(&tr("Lyrics uploaded")));
+                    on_saved();
+                }
+                Err(e) => {
+                    warn!("Failed to upload lyrics: {}", e);
+                    toasts.add_toast(adw::Toast::new(&tr("Failed to upload lyrics")));
+                }
+            }
+        ),
+    );
+}
+
+/// Shows the current line of the edited lyrics while the song plays
+fn start_preview(
+    dialog: &adw::Dialog,
+    label: &gtk::Label,
+    audio_model: &AudioModel,
+    lyrics: Rc<RefCell<Vec<Lyric>>>,
+) {
+    let source_id = glib::timeout_add_local(
+        PREVIEW_INTERVAL,
+        glib::clone!(
+            #[weak]
+            label,
+            #[weak]
+            audio_model,
+            #[upgrade_or]
+            glib::ControlFlow::Break,
+            move || {
+                if audio_model.playing() {
+                    let ticks = audio_model.get_position_ms() * TICKS_PER_MS;
+                    let lyrics = lyrics.borrow();
+                    let current = lyrics
+                        .iter()
+                        .rfind(|lyric| lyric.start.is_some_and(|start| start <= ticks));
+                    label.set_text(current.map(|lyric| lyric.text.as_str()).unwrap_or(""));
+                }
+                glib::ControlFlow::Continue
+            }
+        ),
+    );
+    let source_id = RefCell::new(Some(source_id));
+    dialog.connect_closed(move |_| {
+        if let Some(source_id) = source_id.take() {
+            source_id.remove();
+        }
+    });
+}
+
+/// Editor for the playing song's lyrics. `on_saved` runs after they are
+/// written to a file or uploaded.
+pub fn show(
+    parent: Option<&Window>,
+    audio_model: &AudioModel,
+    backend: &Backend,
+    on_saved: impl Fn() + 'static,
+) {
+    let Some(song) = audio_model.current_song() else {
+        return;
+    };
+    let query = LyricsQuery::from_song(&song);
+    let on_saved: Rc<dyn Fn()> = Rc::new(on_saved);
+
+    let header_bar = adw::HeaderBar::new();
+    header_bar.set_title_widget(Some(&adw::WindowTitle::new(
+        &tr("Edit Lyrics"),
+        &query.title,
+    )));
+
+    let toolbar_view = adw::ToolbarView::new();
+    toolbar_view.add_top_bar(&header_bar);
+
+    let content = gtk::Box::new(gtk::Orientation::Vertical, 12);
+    content.set_margin_top(12);
+    content.set_margin_bottom(12);
+    content.set_margin_start(12);
+    content.set_margin_end(12);
+
+    let toasts = adw::ToastOverlay::new();
+    toasts.set_child(Some(&content));
+    toolbar_view.set_content(Some(&toasts));
+
+    let dialog = adw::Dialog::builder()
+        .can_close(true)
+        .child(&toolbar_view)
+        .build();
+    dialog.set_content_width(600);
+    dialog.set_content_height(700);
+
+    let preview_label = gtk::Label::new(None);
+    preview_label.set_wrap(true);
+    preview_label.set_justify(gtk::Justification::Center);
+    preview_label.set_css_classes(&["title-3"]);
+    content.append(&preview_label);
+
+    let text_view = gtk::TextView::new();
+    text_view.set_monospace(true);
+    text_view.set_wrap_mode(gtk::WrapMode::WordChar);
+    text_view.set_top_margin(12);
+    text_view.set_bottom_margin(12);
+    text_view.set_left_margin(12);
+    text_view.set_right_margin(12);
+    let buffer = text_view.buffer();
+
+    let scrolled_window = gtk::ScrolledWindow::new();
+    scrolled_window.set_policy(gtk::PolicyType::Never, gtk::PolicyType::Automatic);
+    scrolled_window.set_vexpand(true);
+    scrolled_window.set_css_classes(&["card"]);
+    scrolled_window.set_child(Some(&text_view));
+    content.append(&scrolled_window);
+
+    let help = gtk::Label::new(Some(&tr(
+        "Paste the lyrics, then press Ctrl+Enter as each line starts to sing.",
+    )));
+    help.set_wrap(true);
+    help.set_css_classes(&["dim-label", "caption"]);
+    content.append(&help);
+
+    let tools = gtk::Box::new(gtk::Orientation::Horizontal, 6);
+    tools.set_halign(gtk::Align::Center);
+    let stamp_button = tool_button(
+        &tr("Stamp Line"),
+        &tr("Set the line to the current position"),
+    );
+    let earlier_button = tool_button("-0.1 s", &tr("Move the selected lines earlier"));
+    let later_button = tool_button("+0.1 s", &tr("Move the selected lines later"));
+    let play_button = gtk::Button::from_icon_name("media-playback-start-symbolic");
+    play_button.set_tooltip_text(Some(&tr("Play From Line")));
+    tools.append(&stamp_button);
+    tools.append(&earlier_button);
+    tools.append(&later_button);
+    tools.append(&play_button);
+    content.append(&tools);
+
+    let actions = gtk::Box::new(gtk::Orientation::Horizontal, 12);
+    actions.set_halign(gtk::Align::Center);
+    let upload_button = gtk::Button::with_label(&tr("Upload to Server"));
+    upload_button.set_css_classes(&["pill"]);
+    upload_button.set_visible(false);
+    let save_button = gtk::Button::with_label(&tr("Save LRC File"));
+    save_button.set_css_classes(&["pill", "suggested-action"]);
+    actions.append(&upload_button);
+    actions.append(&save_button);
+    content.append(&actions);
+
+    stamp_button.connect_clicked(glib::clone!(
+        #[weak]
+        buffer,
+        #[weak]
+        text_view,
+        #[weak]
+        audio_model,
+        move |_| stamp_line(&buffer, &text_view, &audio_model)
+    ));
+    earlier_button.connect_clicked(glib::clone!(
+        #[weak]
+        buffer,
+        move |_| nudge_lines(&buffer, -NUDGE_MS)
+    ));
+    later_button.connect_clicked(glib::clone!(
+        #[weak]
+        buffer,
+        move |_| nudge_lines(&buffer, NUDGE_MS)
+    ));
+    play_button.connect_clicked(glib::clone!(
+        #[weak]
+        buffer,
+        #[weak]
+        audio_model,
+        move |_| play_from_line(&buffer, &audio_model)
+    ));
+
+    let key_controller = gtk::EventControllerKey::new();
+    key_controller.set_propagation_phase(gtk::PropagationPhase::Capture);
+    key_controller.connect_key_pressed(glib::clone!(
+        #[weak]
+        buffer,
+        #[weak]
+        text_view,
+        #[weak]
+        audio_model,
+        #[upgrade_or]
+        glib::Propagation::Proceed,
+        move |_, key, _, modifiers| {
+            let is_enter = matches!(key, gdk::Key::Return | gdk::Key::KP_Enter);
+            if is_enter && modifiers.contains(gdk::ModifierType::CONTROL_MASK) {
+                stamp_line(&buffer, &text_view, &audio_model);
+                glib::Propagation::Stop
+            } else {
+                glib::Propagation::Proceed
+            }
+        }
+    ));
+    text_view.add_controller(key_controller);
+
+    let parsed: Rc<RefCell<Vec<Lyric>>> = Rc::default();
+    buffer.connect_changed(glib::clone!(
+        #[strong]
+        parsed,
+        move |buffer| {
+            parsed.replace(lrc::parse(&buffer_text(buffer)));
+        }
+    ));
+    start_preview(&dialog, &preview_label, audio_model, parsed);
+
+    save_button.connect_clicked(glib::clone!(
+        #[weak]
+        dialog,
+        #[weak]
+        toasts,
+        #[weak]
+        buffer,
+        #[strong]
+        query,
+        #[strong]
+        on_saved,
+        move |_| save_file(
+            &dialog,
+            &toasts,
+            &query,
+            buffer_text(&buffer),
+            on_saved.clone()
+        )
+    ));
+    let item_id = query.item_id.clone();
+    upload_button.connect_clicked(glib::clone!(
+        #[weak]
+        toasts,
+        #[weak]
+        buffer,
+        #[strong]
+        backend,
+        #[strong]
+        on_saved,
+        move |_| upload(
+            &backend,
+            &toasts,
+            item_id.clone(),
+            buffer_text(&buffer),
+            on_saved.clone()
+        )
+    ));
+
+    // Uploading replaces the file on the server, so only offer it to users
+    // allowed to manage lyrics
+    if let Some(jellyfin) = backend.jellyfin().cloned() {
+        spawn_tokio(
+            async move { jellyfin.can_manage_lyrics().await },
+            glib::clone!(
+                #[weak]
+                upload_button,
+                move |result| match result {
+                    Ok(allowed) => upload_button.set_visible(allowed),
+                    Err(e) => warn!("Failed to check lyrics permission: {}", e),
+                }
+            ),
+        );
+    }
+
+    // Start from the lyrics already shown, if any
+    let backend = backend.clone();
+    let options = LyricsOptions::from_settings();
+    spawn_tokio(
+        async move { lyrics::fetch(&backend, &query, &options).await },
+        glib::clone!(
+            #[weak]
+            buffer,
+            move |lyrics: LyricsResponse| {
+                if buffer.char_count() == 0 && !lyrics.lyrics.is_empty() {
+                    buffer.set_text(&editable_text(&lyrics.lyrics));
+                    buffer.place_cursor(&buffer.start_iter());
+                }
+            }
+        ),
+    );
+
+    dialog.present(parent);
+}
@@ -27,8 +27,30 @@ impl MediaCard {
         self.imp().image.set_item_id(id, None);
     }
 
+    /// Shows a 2x2 grid of album covers instead of one image. With fewer
+    /// than four covers the first one is shown on its own.
+    pub fn set_mosaic_ids(&self, ids: &[String]) {
+        let imp = self.imp();
+        let tiles = imp.mosaic_tiles.borrow();
+        let use_mosaic = ids.len() >= tiles.len();
+        imp.mosaic.set_visible(use_mosaic);
+        imp.image.set_visible(!use_mosaic && !ids.is_empty());
+        imp.static_icon.set_visible(ids.is_empty());
+        if use_mosaic {
+            for (tile, id) in tiles.iter().zip(ids) {
+                tile.set_item_id(id, None);
+            }
+        } else if let Some(id) = ids.first() {
+            imp.image.set_item_id(id, None);
+        }
+    }
+
     pub fn unbind_image(&self) {
-        self.imp().image.unbind();
+        let imp = self.imp();
+        imp.image.unbind();
+        for tile in imp.mosaic_tiles.borrow().iter() {
+            tile.unbind();
+        }
     }
 
     pub fn set_static_icon(&self, icon_name: &str) {
@@ -62,7 +84,7 @@ mod imp {
         glib::{self, Properties},
         prelude::*,
     };
-    use std::cell::Cell;
+    use std::cell::{Cell, RefCell};
 
     use crate::ui::album_art::AlbumArt;
 
@@ -86,6 +108,9 @@ mod imp {
         pub overlay_play: TemplateChild<gtk::Button>,
         #[template_child]
         pub static_icon: TemplateChild<gtk::Image>,
+        #[template_child]
+        pub mosaic: TemplateChild<gtk::Grid>,
+        pub mosaic_tiles: RefCell<Vec<AlbumArt>>,
 
         #[property(get, set)]
         has_play_button: Cell<bool>,
@@ -114,6 +139,11 @@ mod imp {
     impl ObjectImpl for MediaCard {
         fn constructed(&self) {
             self.parent_constructed();
+            for position in 0..4 {
+                let tile = AlbumArt::new();
+                self.mosaic.attach(&tile, position % 2, position / 2, 1, 1);
+                self.mosaic_tiles.borrow_mut().push(tile);
+            }
             self.set_compact_mode(false);
             self.setup_revealer_signals();
             self.obj().connect_map(|widget| {
@@ -137,6 +167,9 @@ mod imp {
                 Self::DEFAULT_CARD_SIZE
             };
             self.image.set_size(size);
+            for tile in self.mosaic_tiles.borrow().iter() {
+                tile.set_size(size / 2);
+            }
             self.static_icon.set_width_request(size as i32);
             self.static_icon.set_height_request(size as i32);
 
@@ -7,26 +7,47 @@ pub mod album_list;
 pub mod artist;
 pub mod artist_detail;
 pub mod artist_list;
+pub mod audiobook_controls;
+pub mod audiobook_list;
 pub mod auto_scroll_window;
+pub mod bookmarks_dialog;
+pub mod category;
+pub mod category_detail;
+pub mod category_list;
+pub mod disc_header;
+pub mod folder_browser;
+pub mod history_list;
 pub mod image_utils;
 pub mod list_helpers;
 pub mod lyrics;
+pub mod lyrics_editor;
 pub mod media_card;
 pub mod music_context_menu;
+pub mod output_dialog;
 pub mod page_traits;
 pub mod playback_mode;
 pub mod player_bar;
 pub mod playlist;
+pub mod playlist_cleanup_dialog;
 pub mod playlist_detail;
 pub mod playlist_dialogs;
+pub mod playlist_edit_dialog;
+pub mod playlist_files;
 pub mod playlist_list;
+pub mod playlist_share_dialog;
+pub mod podcast_list;
 pub mod preferences;
 pub mod queue;
+pub mod radio_list;
 pub mod setup;
 pub mod shortcuts_dialog;
 pub mod song;
 pub mod song_list;
 pub mod song_utils;
+pub mod star_rating;
+pub mod statistics_charts;
+pub mod statistics_dialog;
 pub mod stream_info_dialog;
+pub mod syncplay_dialog;
 pub mod widget_ext;
 pub mod window;
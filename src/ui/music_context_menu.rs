@@ -2,7 +2,14 @@ use adw::prelude::{AdwDialogExt, AlertDialogExt, AlertDialogExtManual};
 use gtk::{self, gio, prelude::*};
 use log::warn;
 
-use crate::{async_utils::spawn_tokio, backend::Backend, i18n::tr, jellyfin::api::PlaylistDto};
+use crate::{
+    async_utils::spawn_tokio,
+    backend::Backend,
+    i18n::tr,
+    jellyfin::{api::PlaylistDto, utils::format_duration},
+    rating::MAX_STARS,
+    ui::star_rating::rating_label,
+};
 
 #[derive(Debug, Clone)]
 pub struct ContextActions {
@@ -11,15 +18,39 @@ pub struct ContextActions {
     pub action_prefix: String,
     pub go_to_artist: bool,
     pub go_to_album: bool,
+    pub go_to_folder: bool,
     pub show_info_dialog: bool,
+    pub bookmarks: bool,
+    /// A "Rate" submenu for `{action_prefix}.rate`, which takes the stars
+    pub rating: bool,
+    pub export_playlist: bool,
+    pub clean_up_playlist: bool,
 }
 
 pub fn construct_menu(config: &ContextActions) -> gtk::PopoverMenu {
+    construct_menu_with_resume(config, || None)
+}
+
+/// Like `construct_menu`, topped with "Resume from" whenever `resume_position`
+/// has somewhere to pick the song up from
+pub fn construct_menu_with_resume(
+    config: &ContextActions,
+    resume_position: impl Fn() -> Option<u64> + 'static,
+) -> gtk::PopoverMenu {
     let empty_menu = gio::Menu::new();
     let popover_menu = gtk::PopoverMenu::from_model(Some(&empty_menu));
     let config = config.clone();
     popover_menu.connect_show(move |popover| {
         let menu_model = create_menu_model(&config);
+        if let Some(position) = resume_position() {
+            let label = tr("Resume from {}").replace("{}", &format_duration(position * 10_000_000));
+            let resume_section = gio::Menu::new();
+            resume_section.append(
+                Some(&label),
+                Some(&format!("{}.resume", config.action_prefix)),
+            );
+            menu_model.prepend_section(None, &resume_section);
+        }
         popover.set_menu_model(Some(&menu_model));
     });
     popover_menu
@@ -133,6 +164,21 @@ fn create_menu_model(config: &ContextActions) -> gio::Menu {
 
     menu.append_section(None, &playlist_section);
 
+    if config.rating {
+        let rating_menu = gio::Menu::new();
+        for stars in (0..=MAX_STARS).rev() {
+            let item = gio::MenuItem::new(Some(&rating_label(stars)), None);
+            item.set_action_and_target_value(
+                Some(&format!("{}.rate", config.action_prefix)),
+                Some(&stars.to_variant()),
+            );
+            rating_menu.append_item(&item);
+        }
+        let rating_section = gio::Menu::new();
+        rating_section.append_submenu(Some(&tr("Rate")), &rating_menu);
+        menu.append_section(None, &rating_section);
+    }
+
     let navigation_section = gio::Menu::new();
     if config.go_to_album {
         navigation_section.append(
@@ -147,8 +193,28 @@ fn create_menu_model(config: &ContextActions) -> gio::Menu {
             Some(&format!("{}.go_to_artist", config.action_prefix)),
         );
     }
+
+    if config.go_to_folder {
+        navigation_section.append(
+            Some(&tr("Go to Folder")),
+            Some(&format!("{}.go_to_folder", config.action_prefix)),
+        );
+    }
     menu.append_section(None, &navigation_section);
 
+    if config.bookmarks {
+        let bookmark_section = gio::Menu::new();
+        bookmark_section.append(
+            Some(&tr("Add Bookmark")),
+            Some(&format!("{}.add_bookmark", config.action_prefix)),
+        );
+        bookmark_section.append(
+            Some(&tr("Bookmarks")),
+            Some(&format!("{}.show_bookmarks", config.action_prefix)),
+        );
+        menu.append_section(None, &bookmark_section);
+    }
+
     let other_section = gio::Menu::new();
     if config.show_info_dialog {
         other_section.append(
@@ -161,6 +227,18 @@ fn create_menu_model(config: &ContextActions) -> gio::Menu {
             Some(&format!("{}.copy_id", config.action_prefix)),
         )
     }
+    if config.export_playlist {
+        other_section.append(
+            Some(&tr("Export Playlist...")),
+            Some(&format!("{}.export", config.action_prefix)),
+        );
+    }
+    if config.clean_up_playlist {
+        other_section.append(
+            Some(&tr("Clean Up Playlist...")),
+            Some(&format!("{}.clean_up", config.action_prefix)),
+        );
+    }
     menu.append_section(None, &other_section);
 
     menu
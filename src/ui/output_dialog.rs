@@ -0,0 +1,132 @@
+use std::time::Duration;
+
+use adw::prelude::*;
+use gtk::{Window, glib};
+
+use crate::async_utils::spawn_tokio;
+use crate::audio::model::AudioModel;
+use crate::audio::renderer::{self, RendererDevice, RendererKind};
+use crate::i18n::tr;
+
+const DISCOVERY_TIMEOUT: Duration = Duration::from_secs(3);
+
+fn output_row(title: &str, subtitle: &str, icon: &str, selected: bool) -> adw::ActionRow {
+    let row = adw::ActionRow::builder()
+        .title(title)
+        .subtitle(subtitle)
+        .activatable(true)
+        .build();
+    row.set_use_markup(false);
+    row.add_prefix(&gtk::Image::from_icon_name(icon));
+    if selected {
+        row.add_suffix(&gtk::Image::from_icon_name("object-select-symbolic"));
+    }
+    row
+}
+
+fn device_row(
+    dialog: &adw::Dialog,
+    audio_model: &AudioModel,
+    device: RendererDevice,
+) -> adw::ActionRow {
+    let (subtitle, icon) = match device.kind {
+        RendererKind::Upnp => (tr("UPnP/DLNA"), "audio-speakers-symbolic"),
+        RendererKind::Chromecast => (tr("Chromecast"), "video-display-symbolic"),
+    };
+    let selected = audio_model.output_name() == device.name;
+    let row = output_row(&device.name, &subtitle, icon, selected);
+    row.connect_activated(glib::clone!(
+        #[weak]
+        dialog,
+        #[weak]
+        audio_model,
+        move |_| {
+            audio_model.set_renderer(Some(device.clone()));
+            dialog.close();
+        }
+    ));
+    row
+}
+
+pub fn show(parent: Option<&Window>, audio_model: &AudioModel) {
+    let header_bar = adw::HeaderBar::new();
+    header_bar.set_title_widget(Some(&adw::WindowTitle::new(&tr("Play On"), "")));
+
+    let toolbar_view = adw::ToolbarView::new();
+    toolbar_view.add_top_bar(&header_bar);
+
+    let scrolled_window = gtk::ScrolledWindow::new();
+    scrolled_window.set_policy(gtk::PolicyType::Never, gtk::PolicyType::Automatic);
+
+    let content = gtk::Box::new(gtk::Orientation::Vertical, 12);
+    content.set_margin_top(12);
+    content.set_margin_bottom(12);
+    content.set_margin_start(12);
+    content.set_margin_end(12);
+    scrolled_window.set_child(Some(&content));
+    toolbar_view.set_content(Some(&scrolled_window));
+
+    let dialog = adw::Dialog::builder()
+        .can_close(true)
+        .child(&toolbar_view)
+        .build();
+    dialog.set_content_width(400);
+    dialog.set_content_height(400);
+
+    let outputs = gtk::ListBox::new();
+    outputs.set_css_classes(&["boxed-list"]);
+    outputs.set_selection_mode(gtk::SelectionMode::None);
+    let local_row = output_row(
+        &tr("This Computer"),
+        "",
+        "computer-symbolic",
+        !audio_model.is_remote(),
+    );
+    local_row.connect_activated(glib::clone!(
+        #[weak]
+        dialog,
+        #[weak]
+        audio_model,
+        move |_| {
+            if audio_model.is_remote() {
+                audio_model.set_renderer(None);
+            }
+            dialog.close();
+        }
+    ));
+    outputs.append(&local_row);
+
+    let spinner = adw::Spinner::new();
+    spinner.set_margin_top(12);
+    spinner.set_margin_bottom(12);
+    outputs.append(&spinner);
+    content.append(&outputs);
+
+    spawn_tokio(
+        renderer::discover(DISCOVERY_TIMEOUT),
+        glib::clone!(
+            #[weak]
+            dialog,
+            #[weak]
+            audio_model,
+            #[weak]
+            outputs,
+            #[weak]
+            spinner,
+            move |devices: Vec<RendererDevice>| {
+                outputs.remove(&spinner);
+                if devices.is_empty() {
+                    let row = adw::ActionRow::builder()
+                        .title(tr("No other devices found"))
+                        .build();
+                    outputs.append(&row);
+                }
+                for device in devices {
+                    outputs.append(&device_row(&dialog, &audio_model, device));
+                }
+            }
+        ),
+    );
+
+    dialog.present(parent);
+}
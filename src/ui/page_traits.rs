@@ -14,6 +14,7 @@ pub enum SortType {
     LastPlayed,
     NumSongs,
     Album,
+    Rating,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, TryFromPrimitive, IntoPrimitive)]
@@ -34,6 +35,7 @@ impl SortType {
             SortType::NumSongs => "Num. Songs",
             SortType::Album => "Album",
             Self::LastPlayed => "Last Played",
+            SortType::Rating => "Rating",
         }
     }
 }
@@ -6,7 +6,7 @@ use num_enum::TryFromPrimitive;
 
 use crate::audio::model::AudioModel;
 
-#[derive(Debug, TryFromPrimitive)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, TryFromPrimitive)]
 #[repr(u32)]
 pub enum PlaybackMode {
     Normal = 0,
@@ -1,4 +1,9 @@
-use crate::{audio::model::AudioModel, ui::player_bar::common::PlayerImp};
+use crate::{
+    application::Application,
+    audio::model::AudioModel,
+    models::Rate,
+    ui::{player_bar::common::PlayerImp, widget_ext::WidgetApplicationExt},
+};
 use adw::prelude::*;
 use glib::Object;
 use gtk::{gio, glib, subclass::prelude::*};
@@ -24,6 +29,7 @@ impl BigPlayer {
         };
 
         imp.playback_mode_menu.bind_to_audio_model(audio_model);
+        imp.audiobook_controls.bind_to_audio_model(audio_model);
 
         audio_model
             .bind_property("volume", &*imp.volume_control, "value")
@@ -88,6 +94,55 @@ impl BigPlayer {
             ),
         );
 
+        audio_model.connect_closure(
+            "metadata-changed",
+            false,
+            glib::closure_local!(
+                #[weak(rename_to = player)]
+                self,
+                move |_audio_model: AudioModel| {
+                    player.imp().update_song_info();
+                }
+            ),
+        );
+
+        audio_model.connect_closure(
+            "bookmarks-changed",
+            false,
+            glib::closure_local!(
+                #[weak(rename_to = player)]
+                self,
+                move |_audio_model: AudioModel| {
+                    player.imp().update_bookmark_marks();
+                }
+            ),
+        );
+
+        // The current song may have been rated from somewhere else
+        if let Some(app) = audio_model.application() {
+            app.connect_closure(
+                "ratings-updated",
+                false,
+                glib::closure_local!(
+                    #[weak(rename_to = player)]
+                    self,
+                    move |_app: Application| {
+                        player.imp().sync_song_rating();
+                    }
+                ),
+            );
+        }
+
+        imp.star_rating.connect_rated(glib::clone!(
+            #[weak(rename_to = player)]
+            self,
+            move |_, stars| {
+                if let Some(song) = player.imp().audio_model().current_song() {
+                    song.rate(stars, &player.get_application());
+                }
+            }
+        ));
+
         // Initial update
         self.imp().update_song_info();
         self.imp().update_play_pause_button(audio_model.playing());
@@ -105,7 +160,11 @@ mod imp {
 
     use crate::{
         audio::model::AudioModel,
-        ui::{album_art::AlbumArt, playback_mode::PlaybackModeMenu, player_bar::common::PlayerImp},
+        ui::{
+            album_art::AlbumArt, audiobook_controls::AudiobookControls,
+            playback_mode::PlaybackModeMenu, player_bar::common::PlayerImp,
+            star_rating::StarRating,
+        },
     };
     use adw::{prelude::*, subclass::prelude::*};
     use glib::{Properties, WeakRef};
@@ -147,12 +206,17 @@ mod imp {
         #[template_child]
         pub playback_mode_menu: TemplateChild<PlaybackModeMenu>,
         #[template_child]
+        pub audiobook_controls: TemplateChild<AudiobookControls>,
+        #[template_child]
         pub action_menu: TemplateChild<gtk::MenuButton>,
+        #[template_child]
+        pub star_rating: TemplateChild<StarRating>,
 
         pub audio_model: OnceCell<AudioModel>,
         pub lyrics_window: RefCell<Option<WeakRef<adw::Dialog>>>,
         pub seek_debounce_id: RefCell<Option<glib::SourceId>>,
         pub favorite_binding: RefCell<Option<glib::Binding>>,
+        pub rating_binding: RefCell<Option<glib::Binding>>,
 
         #[property(get, set)]
         pub position: RefCell<u32>,
@@ -271,9 +335,31 @@ mod imp {
         fn action_menu(&self) -> &gtk::MenuButton {
             &self.action_menu
         }
+        fn extra_song_update(&self) {
+            let song = self.audio_model().current_song();
+            let binding = song.as_ref().map(|song| {
+                song.bind_property("rating", &*self.star_rating, "rating")
+                    .sync_create()
+                    .build()
+            });
+            self.star_rating.set_visible(song.is_some());
+            if let Some(old) = self.rating_binding.replace(binding) {
+                old.unbind();
+            }
+        }
     }
 
     impl BigPlayer {
+        /// Song models in the queue aren't the library's, so they're brought
+        /// up to date by hand
+        pub fn sync_song_rating(&self) {
+            if let Some(song) = self.audio_model().current_song()
+                && let Some(app) = self.audio_model().application()
+            {
+                song.set_rating(app.library().song_rating(&song.id()));
+            }
+        }
+
         pub fn update_album_art_paintable(&self, has_album_art: bool) {
             let paintable = if has_album_art {
                 self.album_art.imp().album_image.paintable()
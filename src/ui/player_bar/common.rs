@@ -3,8 +3,11 @@ use crate::{
     audio::{model::AudioModel, stream_info::discover_stream_info},
     i18n::tr,
     jellyfin::api::ItemType,
+    lyrics::LyricsOptions,
+    models::Rate,
     ui::{
         album_art::AlbumArt,
+        bookmarks_dialog,
         lyrics::Lyrics,
         music_context_menu::{
             ContextActions, add_to_playlist_dialog, add_to_playlist_dup_check, construct_menu,
@@ -100,6 +103,32 @@ where
             self.load_album_art(&song.album_id(), &song.id());
         }
         self.update_favorite_binding();
+        self.update_seekable(!audio_model.is_live());
+        self.update_bookmark_marks();
+        self.extra_song_update();
+    }
+
+    /// Bookmarks in the current song show as marks along the seek bar
+    fn update_bookmark_marks(&self) {
+        let scale = self.position_scale();
+        scale.clear_marks();
+        let (Some(song), Some(app)) = (
+            self.audio_model().current_song(),
+            self.audio_model().application(),
+        ) else {
+            return;
+        };
+        for bookmark in app.resume_store().borrow().bookmarks(&song.id()) {
+            scale.add_mark(bookmark.position as f64, gtk::PositionType::Bottom, None);
+        }
+    }
+
+    /// Live streams have no length to seek through or server item to favorite
+    fn update_seekable(&self, seekable: bool) {
+        self.position_scale().set_visible(seekable);
+        self.duration_label().set_visible(seekable);
+        self.favorite_button().set_sensitive(seekable);
+        self.extra_seekable_update(seekable);
     }
 
     fn update_favorite_binding(&self) {
@@ -148,6 +177,8 @@ where
     }
 
     fn toggle_lyrics(&self, has_lyrics: bool) {
+        // A lyrics folder or online lookup may have lyrics the server doesn't
+        let has_lyrics = has_lyrics || LyricsOptions::from_settings().has_fallbacks();
         self.lyrics_button().set_sensitive(has_lyrics);
     }
 
@@ -159,6 +190,8 @@ where
     // allowing subclasses to do additional updates or logic.
     fn extra_position_update(&self, _position: u32) {}
     fn extra_duration_update(&self, _duration: u32) {}
+    fn extra_seekable_update(&self, _seekable: bool) {}
+    fn extra_song_update(&self) {}
 
     fn show_info_dialog(&self) {
         if let Some(uri) = self.audio_model().get_uri()
@@ -240,6 +273,13 @@ where
         }
     }
 
+    fn on_go_to_folder(&self) {
+        let song_id = self.audio_model().current_song_id();
+        let window = self.obj().get_root_window();
+        window.show_song_folder(&song_id);
+        window.imp().player_bar.close_sheet();
+    }
+
     fn add_song_to_playlist(&self, playlist_id: String, song_id: String) {
         let app = self.obj().get_application();
         let backend = app.backend();
@@ -297,6 +337,12 @@ where
         );
     }
 
+    fn on_rate(&self, stars: u32) {
+        if let Some(song_model) = self.audio_model().current_song() {
+            song_model.rate(stars, &self.obj().get_application());
+        }
+    }
+
     fn on_queue_next(&self) {
         if let Some(song_model) = self.audio_model().current_song() {
             self.audio_model().prepend_to_queue(vec![song_model]);
@@ -316,7 +362,12 @@ where
             action_prefix: "song".to_string(),
             go_to_artist: true,
             go_to_album: true,
+            go_to_folder: true,
             show_info_dialog: true,
+            bookmarks: true,
+            rating: true,
+            export_playlist: false,
+            clean_up_playlist: false,
         };
         let menu = construct_menu(&options);
         self.action_menu().set_popover(Some(&menu));
@@ -344,12 +395,36 @@ where
         klass.install_action("song.go_to_artist", None, |player, _, _| {
             player.imp().on_go_to_artist();
         });
+        klass.install_action("song.go_to_folder", None, |player, _, _| {
+            player.imp().on_go_to_folder();
+        });
         klass.install_action("song.add_to_playlist_dialog", None, |player, _, _| {
             player.imp().on_add_to_playlist_dialog();
         });
         klass.install_action("song.show_info_dialog", None, |player, _, _| {
             player.imp().show_info_dialog();
         });
+        klass.install_action("song.add_bookmark", None, |player, _, _| {
+            bookmarks_dialog::add_bookmark(
+                player.get_gtk_window().as_ref(),
+                player.imp().audio_model(),
+            );
+        });
+        klass.install_action("song.show_bookmarks", None, |player, _, _| {
+            bookmarks_dialog::show_bookmarks(
+                player.get_gtk_window().as_ref(),
+                player.imp().audio_model(),
+            );
+        });
+        klass.install_action(
+            "song.rate",
+            Some(glib::VariantTy::UINT32),
+            |player, _, stars| {
+                if let Some(stars) = stars.and_then(|stars| stars.get::<u32>()) {
+                    player.imp().on_rate(stars);
+                }
+            },
+        );
     }
 
     fn setup_volume_icons(&self) {
@@ -87,6 +87,30 @@ impl CompactPlayerBar {
             ),
         );
 
+        audio_model.connect_closure(
+            "metadata-changed",
+            false,
+            glib::closure_local!(
+                #[weak(rename_to = player)]
+                self,
+                move |_audio_model: AudioModel| {
+                    player.imp().update_song_info();
+                }
+            ),
+        );
+
+        audio_model.connect_closure(
+            "bookmarks-changed",
+            false,
+            glib::closure_local!(
+                #[weak(rename_to = player)]
+                self,
+                move |_audio_model: AudioModel| {
+                    player.imp().update_bookmark_marks();
+                }
+            ),
+        );
+
         self.imp().update_song_info();
         self.imp().update_play_pause_button(audio_model.playing());
     }
@@ -23,6 +23,7 @@ impl FullPlayerBar {
         }
 
         imp.playback_mode_menu.bind_to_audio_model(audio_model);
+        imp.audiobook_controls.bind_to_audio_model(audio_model);
 
         audio_model
             .bind_property("volume", &*imp.volume_control, "value")
@@ -87,6 +88,30 @@ impl FullPlayerBar {
             ),
         );
 
+        audio_model.connect_closure(
+            "metadata-changed",
+            false,
+            glib::closure_local!(
+                #[weak(rename_to = player)]
+                self,
+                move |_audio_model: AudioModel| {
+                    player.imp().update_song_info();
+                }
+            ),
+        );
+
+        audio_model.connect_closure(
+            "bookmarks-changed",
+            false,
+            glib::closure_local!(
+                #[weak(rename_to = player)]
+                self,
+                move |_audio_model: AudioModel| {
+                    player.imp().update_bookmark_marks();
+                }
+            ),
+        );
+
         self.imp().update_song_info();
         self.imp().update_play_pause_button(audio_model.playing());
     }
@@ -103,7 +128,10 @@ mod imp {
 
     use crate::{
         audio::model::AudioModel,
-        ui::{album_art::AlbumArt, playback_mode::PlaybackModeMenu, player_bar::common::PlayerImp},
+        ui::{
+            album_art::AlbumArt, audiobook_controls::AudiobookControls,
+            playback_mode::PlaybackModeMenu, player_bar::common::PlayerImp,
+        },
     };
     use adw::{prelude::*, subclass::prelude::*};
     use glib::{Properties, WeakRef, subclass::InitializingObject};
@@ -146,6 +174,8 @@ mod imp {
         #[template_child]
         pub playback_mode_menu: TemplateChild<PlaybackModeMenu>,
         #[template_child]
+        pub audiobook_controls: TemplateChild<AudiobookControls>,
+        #[template_child]
         pub action_menu: TemplateChild<gtk::MenuButton>,
 
         pub audio_model: OnceCell<AudioModel>,
@@ -261,6 +291,10 @@ mod imp {
             self.scale_duration_label
                 .set_text(&crate::ui::player_bar::common::format_time(duration));
         }
+
+        fn extra_seekable_update(&self, seekable: bool) {
+            self.scale_duration_label.set_visible(seekable);
+        }
         fn action_menu(&self) -> &gtk::MenuButton {
             &self.action_menu
         }
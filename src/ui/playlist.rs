@@ -2,7 +2,7 @@ use crate::{
     backend::BackendError,
     i18n::{ngettext, tr},
     library_utils::songs_for_playlist,
-    models::{PlaylistModel, SongModel},
+    models::{PlaylistModel, SongModel, ToggleFavorite},
     ui::widget_ext::WidgetApplicationExt,
 };
 use glib::Object;
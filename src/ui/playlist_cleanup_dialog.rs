@@ -0,0 +1,206 @@
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+use adw::prelude::*;
+use gtk::glib;
+use log::{error, warn};
+
+use crate::{
+    async_utils::spawn_tokio,
+    i18n::{ngettext, tr},
+    jellyfin::api::MusicDto,
+    playlist_cleanup::{self, Finding, Problem},
+    playlist_file::PlaylistEntry,
+    ui::{widget_ext::WidgetApplicationExt, window::Window},
+};
+
+/// Looks through the playlist for duplicates and songs that are gone from
+/// the library, and lets the fixes be picked before they're sent
+pub fn clean_up_playlist(window: &Window, playlist_id: String) {
+    let backend = window.get_application().backend();
+    let id = playlist_id.clone();
+    spawn_tokio(
+        async move { backend.get_playlist_items(&id).await },
+        glib::clone!(
+            #[weak]
+            window,
+            move |result| match result {
+                Ok(items) => {
+                    let findings = window
+                        .get_application()
+                        .library()
+                        .playlist_problems(&items.items);
+                    match findings {
+                        None => window.toast(
+                            &tr("The library has no songs to check the playlist against"),
+                            None,
+                        ),
+                        Some(findings) if findings.is_empty() => {
+                            window.toast(&tr("Nothing to clean up"), None)
+                        }
+                        Some(findings) => present(&window, playlist_id, items.items, findings),
+                    }
+                }
+                Err(err) => {
+                    window.toast(&tr("Could not load playlist, please try again."), None);
+                    warn!("Failed to load playlist for clean up: {}", err);
+                }
+            }
+        ),
+    );
+}
+
+fn present(window: &Window, playlist_id: String, entries: Vec<MusicDto>, findings: Vec<Finding>) {
+    let library = window.get_application().library();
+
+    let duplicates = adw::PreferencesGroup::builder()
+        .title(tr("Duplicates"))
+        .description(tr("The first copy of each song stays"))
+        .build();
+    let replaced = adw::PreferencesGroup::builder()
+        .title(tr("Replacements"))
+        .description(tr(
+            "Songs no longer in the library, swapped for the closest match",
+        ))
+        .build();
+    let missing = adw::PreferencesGroup::builder()
+        .title(tr("Not in Library"))
+        .description(tr("Nothing close was found, these can only be removed"))
+        .build();
+    let groups = [duplicates, replaced, missing];
+    let mut used = [false; 3];
+
+    // Nothing is changed unless it's picked
+    let picked: Rc<RefCell<HashSet<usize>>> = Rc::new(RefCell::new(HashSet::new()));
+    for finding in &findings {
+        let entry = PlaylistEntry::from_song(&entries[finding.index]);
+        let (group, subtitle) = match &finding.problem {
+            Problem::Duplicate { original } => (
+                0,
+                tr("Also at position {}").replace("{}", &(original + 1).to_string()),
+            ),
+            Problem::Missing {
+                replacement: Some(found),
+            } => {
+                let song = library.songs_for_ids(std::slice::from_ref(&found.song_id));
+                let matched = song.first().map_or_else(String::new, |song| {
+                    format!("{} - {}", song.artists_string(), song.title())
+                });
+                (1, tr("Replace with {}").replace("{}", &matched))
+            }
+            Problem::Missing { replacement: None } => (2, entry.album.clone()),
+        };
+        let row = adw::ActionRow::builder()
+            .title(glib::markup_escape_text(&entry.display_name()))
+            .subtitle(glib::markup_escape_text(&subtitle))
+            .build();
+        let check = gtk::CheckButton::builder()
+            .active(false)
+            .valign(gtk::Align::Center)
+            .build();
+        let index = finding.index;
+        check.connect_toggled(glib::clone!(
+            #[strong]
+            picked,
+            move |check| {
+                if check.is_active() {
+                    picked.borrow_mut().insert(index);
+                } else {
+                    picked.borrow_mut().remove(&index);
+                }
+            }
+        ));
+        row.add_suffix(&check);
+        row.set_activatable_widget(Some(&check));
+        groups[group].add(&row);
+        used[group] = true;
+    }
+
+    let page = adw::PreferencesPage::new();
+    for (group, used) in groups.iter().zip(used) {
+        if used {
+            page.add(group);
+        }
+    }
+
+    let apply_button = gtk::Button::builder()
+        .label(tr("Apply"))
+        .css_classes(["suggested-action"])
+        .build();
+    let header_bar = adw::HeaderBar::new();
+    header_bar.set_title_widget(Some(&adw::WindowTitle::new(
+        &tr("Clean Up Playlist"),
+        &ngettext(
+            "1 problem found",
+            "{} problems found",
+            findings.len() as u32,
+        )
+        .replace("{}", &findings.len().to_string()),
+    )));
+    header_bar.pack_end(&apply_button);
+    let toolbar_view = adw::ToolbarView::new();
+    toolbar_view.add_top_bar(&header_bar);
+    toolbar_view.set_content(Some(&page));
+
+    let dialog = adw::Dialog::builder()
+        .can_close(true)
+        .child(&toolbar_view)
+        .build();
+    dialog.set_content_width(500);
+    dialog.set_content_height(600);
+
+    apply_button.connect_clicked(glib::clone!(
+        #[weak]
+        window,
+        #[weak]
+        dialog,
+        move |_| {
+            let picked = picked.borrow();
+            let mut remove = HashSet::new();
+            let mut replace = HashMap::new();
+            for finding in findings.iter().filter(|f| picked.contains(&f.index)) {
+                match &finding.problem {
+                    Problem::Missing {
+                        replacement: Some(found),
+                    } => {
+                        replace.insert(finding.index, found.song_id.clone());
+                    }
+                    _ => {
+                        remove.insert(finding.index);
+                    }
+                }
+            }
+            if !remove.is_empty() || !replace.is_empty() {
+                let ids: Vec<String> = entries.iter().map(|entry| entry.id.clone()).collect();
+                let wanted = playlist_cleanup::cleaned_ids(&ids, &remove, &replace);
+                apply(&window, playlist_id.clone(), wanted);
+            }
+            dialog.close();
+        }
+    ));
+    dialog.present(Some(window));
+}
+
+fn apply(window: &Window, playlist_id: String, wanted: Vec<String>) {
+    let app = window.get_application();
+    let backend = app.backend();
+    spawn_tokio(
+        async move { playlist_cleanup::apply(&backend, &playlist_id, &wanted).await },
+        glib::clone!(
+            #[weak]
+            window,
+            move |result| {
+                match result {
+                    Ok(()) => window.toast(&tr("Playlist cleaned up"), None),
+                    Err(err) => {
+                        window.toast(&tr("Failed to clean up playlist"), None);
+                        error!("Failed to clean up playlist: {}", err);
+                    }
+                }
+                // Some of it may have gone through even when it failed
+                app.refresh_playlists(true);
+            }
+        ),
+    );
+}
@@ -5,11 +5,12 @@ use crate::{
     i18n::{ngettext, tr},
     jellyfin::utils::format_duration,
     library_utils::songs_for_playlist,
-    models::{PlaylistModel, SongModel},
+    models::{PlaylistModel, SongModel, ToggleFavorite},
     ui::{
         music_context_menu::{ContextActions, add_to_playlist_dialog, construct_menu},
         page_traits::DetailPage,
-        playlist_dialogs,
+        playlist_cleanup_dialog, playlist_dialogs, playlist_edit_dialog, playlist_files,
+        playlist_share_dialog,
         song::{Song, SongOptions},
         song_utils::{self, connect_song_navigation},
         widget_ext::WidgetApplicationExt,
@@ -32,7 +33,11 @@ impl DetailPage for PlaylistDetail {
         let imp = self.imp();
         imp.model.replace(Some(model.clone()));
         imp.name_label.set_text(&model.name());
-        imp.delete.set_visible(!model.is_smart());
+        imp.description_label.set_visible(false);
+        // Jellyfin playlists stay read only until the server says otherwise
+        let is_jellyfin = self.get_application().backend().jellyfin().is_some();
+        imp.can_edit.set(!model.is_smart() && !is_jellyfin);
+        self.update_edit_actions();
         imp.favorite_button.set_visible(!model.is_smart());
         if model.is_smart() {
             self.use_static_icon(model.playlist_type().icon_name());
@@ -44,7 +49,12 @@ impl DetailPage for PlaylistDetail {
             .sync_create()
             .build();
         imp.favorite_binding.replace(Some(binding));
-        self.pull_tracks();
+        self.update_details();
+        if model.is_smart() {
+            self.pull_tracks();
+        } else {
+            self.check_can_edit();
+        }
     }
 
     fn get_model(&self) -> Option<Self::Model> {
@@ -93,11 +103,7 @@ impl PlaylistDetail {
             #[weak(rename_to = playlist_detail)]
             self,
             move |_, list_item| {
-                let is_smart_playlist = playlist_detail
-                    .get_model()
-                    .map(|p| p.is_smart())
-                    .unwrap_or(true);
-                let song_widget = if is_smart_playlist {
+                let song_widget = if !playlist_detail.imp().can_edit.get() {
                     Song::new()
                 } else {
                     Song::new_with(SongOptions {
@@ -152,12 +158,7 @@ impl PlaylistDetail {
                     connect_song_navigation(&song_widget, &playlist_detail.get_root_window());
                 handlers.extend(nav_handlers);
 
-                let is_smart_playlist = playlist_detail
-                    .get_model()
-                    .map(|p| p.is_smart())
-                    .unwrap_or(true);
-
-                if !is_smart_playlist {
+                if playlist_detail.imp().can_edit.get() {
                     let moved_handler = song_widget.connect_closure(
                         "widget-moved",
                         false,
@@ -276,13 +277,118 @@ impl PlaylistDetail {
             in_queue: false,
             action_prefix: "playlist_detail".to_string(),
             go_to_album: false,
+            go_to_folder: false,
             go_to_artist: false,
             show_info_dialog: false,
+            bookmarks: false,
+            rating: false,
+            export_playlist: true,
+            clean_up_playlist: true,
         };
         let popover_menu = construct_menu(&options);
         self.imp().action_menu.set_popover(Some(&popover_menu));
     }
 
+    fn export(&self) {
+        let Some(model) = self.get_model() else {
+            return;
+        };
+        let song_ids: Vec<String> = self
+            .imp()
+            .songs
+            .borrow()
+            .iter()
+            .map(|song| song.id())
+            .collect();
+        playlist_files::export(&self.get_root_window(), &model.name(), &song_ids);
+    }
+
+    /// Jellyfin playlists can be shared read only, the songs are only loaded
+    /// once it's known whether they can be moved and removed
+    fn check_can_edit(&self) {
+        let Some(jellyfin) = self.get_application().backend().jellyfin().cloned() else {
+            self.pull_tracks();
+            return;
+        };
+        let playlist_id = self.id();
+        let id = playlist_id.clone();
+        spawn_tokio(
+            async move { jellyfin.can_edit_playlist(&id).await },
+            glib::clone!(
+                #[weak(rename_to = playlist_detail)]
+                self,
+                move |result| {
+                    if playlist_detail.id() != playlist_id {
+                        return;
+                    }
+                    match result {
+                        Ok(can_edit) => playlist_detail.imp().can_edit.set(can_edit),
+                        Err(err) => warn!("Failed to check playlist permissions: {}", err),
+                    }
+                    playlist_detail.update_edit_actions();
+                    playlist_detail.pull_tracks();
+                }
+            ),
+        );
+    }
+
+    fn update_edit_actions(&self) {
+        let imp = self.imp();
+        let can_edit = imp.can_edit.get();
+        let is_jellyfin = self.get_application().backend().jellyfin().is_some();
+        imp.edit.set_visible(can_edit);
+        imp.delete.set_visible(can_edit);
+        imp.share.set_visible(can_edit && is_jellyfin);
+        self.action_set_enabled("playlist_detail.clean_up", can_edit);
+    }
+
+    fn share(&self) {
+        if self.imp().can_edit.get() {
+            playlist_share_dialog::share_playlist(&self.get_root_window(), self.id());
+        }
+    }
+
+    fn clean_up(&self) {
+        if self.imp().can_edit.get() {
+            playlist_cleanup_dialog::clean_up_playlist(&self.get_root_window(), self.id());
+        }
+    }
+
+    /// Name and description as the server last gave them, they can change
+    /// after the page is opened
+    fn update_details(&self) {
+        let Some(model) = self.get_model().filter(|model| !model.is_smart()) else {
+            return;
+        };
+        let id = model.id();
+        let app = self.get_application();
+        let playlists = app.playlists();
+        let Some(playlist) = playlists.borrow().iter().find(|p| p.id == id).cloned() else {
+            return;
+        };
+        let imp = self.imp();
+        imp.name_label.set_text(&playlist.name);
+        let description = playlist.overview.unwrap_or_default();
+        imp.description_label.set_text(description.trim());
+        imp.description_label
+            .set_visible(!description.trim().is_empty());
+    }
+
+    fn edit(&self) {
+        let Some(model) = self.get_model().filter(|model| !model.is_smart()) else {
+            return;
+        };
+        playlist_edit_dialog::edit_playlist(
+            &self.get_root_window(),
+            model.id(),
+            glib::clone!(
+                #[weak(rename_to = playlist_detail)]
+                self,
+                move || playlist_detail.imp().album_image.reload()
+            ),
+        );
+    }
+
     fn copy_id(&self) {
         if let Some(model) = self.get_model() {
             self.clipboard().set_text(&model.id());
@@ -547,14 +653,17 @@ impl PlaylistDetail {
         let backend = app.backend();
         let window = self.get_root_window();
         let item_id = self.id();
+        let id = item_id.clone();
         spawn_tokio(
-            async move { backend.delete_item(&item_id).await },
+            async move { backend.delete_item(&id).await },
             glib::clone!(
                 #[weak (rename_to = playlist_detail)]
                 self,
                 move |result| {
                     match result {
                         Ok(()) => {
+                            app.playlist_folders().borrow_mut().set_folder(&item_id, "");
+                            app.save_playlist_folders();
                             app.refresh_playlists(true);
                             window.go_back();
                             playlist_detail.toast(&tr("Playlist deleted"), None);
@@ -587,6 +696,7 @@ impl PlaylistDetail {
                 self,
                 move |_app: Application, _cnt: u64| {
                     if playlist_detail.get_model().is_some() {
+                        playlist_detail.update_details();
                         playlist_detail.pull_tracks();
                     }
                 }
@@ -628,6 +738,8 @@ mod imp {
         #[template_child]
         pub name_label: TemplateChild<gtk::Label>,
         #[template_child]
+        pub description_label: TemplateChild<gtk::Label>,
+        #[template_child]
         pub track_list: TemplateChild<gtk::ListView>,
         #[template_child]
         pub track_count: TemplateChild<gtk::Label>,
@@ -640,6 +752,10 @@ mod imp {
         #[template_child]
         pub star_icon: TemplateChild<gtk::Image>,
         #[template_child]
+        pub edit: TemplateChild<gtk::Button>,
+        #[template_child]
+        pub share: TemplateChild<gtk::Button>,
+        #[template_child]
         pub delete: TemplateChild<gtk::Button>,
         #[template_child]
         pub empty: TemplateChild<adw::StatusPage>,
@@ -648,6 +764,8 @@ mod imp {
         pub songs: RefCell<Vec<SongModel>>,
         pub store: OnceCell<gio::ListStore>,
         pub favorite_binding: RefCell<Option<glib::Binding>>,
+        /// False for smart playlists and ones only shared with us to view
+        pub can_edit: Cell<bool>,
         #[property(get, set = Self::set_favorite)]
         favorite: Cell<bool>,
     }
@@ -680,6 +798,18 @@ mod imp {
             klass.install_action("playlist_detail.copy_id", None, |playlist, _, _| {
                 playlist.copy_id();
             });
+            klass.install_action("playlist_detail.edit", None, |playlist, _, _| {
+                playlist.edit();
+            });
+            klass.install_action("playlist_detail.share", None, |playlist, _, _| {
+                playlist.share();
+            });
+            klass.install_action("playlist_detail.export", None, |playlist, _, _| {
+                playlist.export();
+            });
+            klass.install_action("playlist_detail.clean_up", None, |playlist, _, _| {
+                playlist.clean_up();
+            });
         }
 
         fn instance_init(obj: &InitializingObject<Self>) {
@@ -75,3 +75,47 @@ pub fn confirm_delete(parent: Option<&Window>, cb: impl Fn(bool) + 'static) {
     );
     dialog.present(parent);
 }
+
+pub fn rename_folder(parent: Option<&Window>, folder: &str, cb: impl Fn(String) + 'static) {
+    let name_entry = adw::EntryRow::builder()
+        .title(tr("Folder name"))
+        .text(folder)
+        .build();
+    let entry_box = gtk::ListBox::builder()
+        .margin_top(12)
+        .margin_bottom(12)
+        .build();
+    entry_box.add_css_class("boxed-list");
+    entry_box.append(&name_entry);
+
+    let dialog = adw::AlertDialog::builder()
+        .heading(tr("Rename Folder"))
+        .extra_child(&entry_box)
+        .build();
+
+    dialog.add_responses(&[("cancel", &tr("Cancel")), ("rename", &tr("Rename"))]);
+    dialog.set_response_appearance("rename", adw::ResponseAppearance::Suggested);
+    dialog.set_default_response(Some("rename"));
+    dialog.set_close_response("cancel");
+
+    dialog.connect_response(
+        None,
+        glib::clone!(
+            #[weak]
+            dialog,
+            #[weak]
+            name_entry,
+            move |_, response| {
+                if response == "rename" {
+                    let name = name_entry.text().trim().to_string();
+                    if !name.is_empty() {
+                        cb(name);
+                    }
+                }
+                dialog.close();
+            }
+        ),
+    );
+
+    dialog.present(parent);
+}
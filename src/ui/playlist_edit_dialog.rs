@@ -0,0 +1,323 @@
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use adw::prelude::*;
+use gtk::{gio, glib};
+use log::{error, warn};
+
+use crate::{
+    async_utils::spawn_tokio,
+    backend::BackendError,
+    i18n::tr,
+    jellyfin::api::{ImageType, PlaylistDetails},
+    ui::{widget_ext::WidgetApplicationExt, window::Window},
+};
+
+/// A new cover, read before it's sent
+struct Cover {
+    image: Vec<u8>,
+    content_type: String,
+}
+
+/// The description is saved last, so when only it fails the rest of the
+/// edit is already on the server
+enum SaveError {
+    Playlist(BackendError),
+    Description(BackendError),
+}
+
+/// Loads the playlist's details from the server and lets them be edited,
+/// along with its local folder. `on_saved` runs once the server has them.
+pub fn edit_playlist(window: &Window, playlist_id: String, on_saved: impl Fn() + 'static) {
+    let backend = window.get_application().backend();
+    let id = playlist_id.clone();
+    spawn_tokio(
+        async move { backend.get_playlist_details(&id).await },
+        glib::clone!(
+            #[weak]
+            window,
+            move |result| match result {
+                Ok(details) => present(&window, playlist_id, details, on_saved),
+                Err(err) => {
+                    window.toast(&tr("Could not load playlist details"), None);
+                    warn!("Failed to load playlist details: {}", err);
+                }
+            }
+        ),
+    );
+}
+
+fn present(
+    window: &Window,
+    playlist_id: String,
+    details: PlaylistDetails,
+    on_saved: impl Fn() + 'static,
+) {
+    let app = window.get_application();
+    let on_saved: Rc<dyn Fn()> = Rc::new(on_saved);
+    let original_description = details.description.clone();
+
+    let name_row = adw::EntryRow::builder()
+        .title(tr("Name"))
+        .text(&details.name)
+        .build();
+    let description_row = adw::EntryRow::builder()
+        .title(tr("Description"))
+        .text(&details.description)
+        .build();
+    let public_row = adw::SwitchRow::builder()
+        .title(tr("Public"))
+        .subtitle(tr("Everyone on the server can see and play it"))
+        .active(details.is_public)
+        .build();
+    let details_group = adw::PreferencesGroup::new();
+    details_group.add(&name_row);
+    details_group.add(&description_row);
+    details_group.add(&public_row);
+
+    let page = adw::PreferencesPage::new();
+    page.add(&details_group);
+
+    // Subsonic servers have no way to upload images
+    let cover: Rc<RefCell<Option<PathBuf>>> = Rc::default();
+    if app.backend().jellyfin().is_some() {
+        let cover_row = adw::ActionRow::builder()
+            .title(tr("Cover Image"))
+            .subtitle(tr("Unchanged"))
+            .build();
+        let choose_button = gtk::Button::builder()
+            .label(tr("Choose…"))
+            .valign(gtk::Align::Center)
+            .build();
+        choose_button.connect_clicked(glib::clone!(
+            #[weak]
+            window,
+            #[weak]
+            cover_row,
+            #[strong]
+            cover,
+            move |_| choose_cover(&window, &cover_row, cover.clone())
+        ));
+        cover_row.add_suffix(&choose_button);
+        cover_row.set_activatable_widget(Some(&choose_button));
+        details_group.add(&cover_row);
+    }
+
+    // Folders are only kept here, so they're the same whichever server it is
+    let folders = app.playlist_folders().borrow().folders();
+    let current_folder = app
+        .playlist_folders()
+        .borrow()
+        .folder(&playlist_id)
+        .map(str::to_string);
+    let mut folder_names = vec![tr("No Folder")];
+    folder_names.extend(folders.iter().cloned());
+    let folder_names: Vec<&str> = folder_names.iter().map(String::as_str).collect();
+    let folder_row = adw::ComboRow::builder()
+        .title(tr("Folder"))
+        .model(&gtk::StringList::new(&folder_names))
+        .build();
+    if let Some(index) = current_folder
+        .as_ref()
+        .and_then(|current| folders.iter().position(|folder| folder == current))
+    {
+        folder_row.set_selected(index as u32 + 1);
+    }
+    let new_folder_row = adw::EntryRow::builder().title(tr("New folder")).build();
+    let folder_group = adw::PreferencesGroup::builder()
+        .title(tr("Folder"))
+        .description(tr("Folders are only kept on this computer"))
+        .build();
+    folder_group.add(&folder_row);
+    folder_group.add(&new_folder_row);
+    page.add(&folder_group);
+
+    let save_button = gtk::Button::builder()
+        .label(tr("Save"))
+        .css_classes(["suggested-action"])
+        .build();
+    let header_bar = adw::HeaderBar::new();
+    header_bar.set_title_widget(Some(&adw::WindowTitle::new(&tr("Edit Playlist"), "")));
+    header_bar.pack_end(&save_button);
+    let toolbar_view = adw::ToolbarView::new();
+    toolbar_view.add_top_bar(&header_bar);
+    toolbar_view.set_content(Some(&page));
+
+    let dialog = adw::Dialog::builder()
+        .can_close(true)
+        .child(&toolbar_view)
+        .build();
+    dialog.set_content_width(500);
+    dialog.set_content_height(560);
+
+    save_button.connect_clicked(glib::clone!(
+        #[weak]
+        window,
+        #[weak]
+        dialog,
+        #[weak]
+        name_row,
+        #[weak]
+        description_row,
+        #[weak]
+        public_row,
+        #[weak]
+        folder_row,
+        #[weak]
+        new_folder_row,
+        move |_| {
+            let name = name_row.text().trim().to_string();
+            if name.is_empty() {
+                name_row.grab_focus();
+                return;
+            }
+            let new_folder = new_folder_row.text().trim().to_string();
+            let folder = if !new_folder.is_empty() {
+                new_folder
+            } else {
+                folder_row
+                    .selected()
+                    .checked_sub(1)
+                    .and_then(|index| folders.get(index as usize))
+                    .cloned()
+                    .unwrap_or_default()
+            };
+            let app = window.get_application();
+            app.playlist_folders()
+                .borrow_mut()
+                .set_folder(&playlist_id, &folder);
+            app.save_playlist_folders();
+
+            let cover = match cover
+                .borrow()
+                .as_ref()
+                .map(|path| read_cover(path))
+                .transpose()
+            {
+                Ok(cover) => cover,
+                Err(err) => {
+                    window.toast(&tr("Could not read the cover image"), None);
+                    warn!("Failed to read cover image: {}", err);
+                    return;
+                }
+            };
+            let details = PlaylistDetails {
+                name,
+                description: description_row.text().trim().to_string(),
+                is_public: public_row.is_active(),
+            };
+            let description_changed = details.description != original_description;
+            save(
+                &window,
+                playlist_id.clone(),
+                details,
+                description_changed,
+                cover,
+                on_saved.clone(),
+            );
+            dialog.close();
+        }
+    ));
+    dialog.present(Some(window));
+}
+
+fn choose_cover(window: &Window, cover_row: &adw::ActionRow, cover: Rc<RefCell<Option<PathBuf>>>) {
+    let filter = gtk::FileFilter::new();
+    filter.set_name(Some(&tr("Images")));
+    filter.add_mime_type("image/jpeg");
+    filter.add_mime_type("image/png");
+    filter.add_mime_type("image/webp");
+    let dialog = gtk::FileDialog::builder()
+        .title(tr("Choose Cover Image"))
+        .default_filter(&filter)
+        .modal(true)
+        .build();
+    dialog.open(
+        Some(window),
+        gio::Cancellable::NONE,
+        glib::clone!(
+            #[weak]
+            cover_row,
+            move |result| {
+                let Some(path) = result.ok().and_then(|file| file.path()) else {
+                    return;
+                };
+                let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+                cover_row.set_subtitle(&glib::markup_escape_text(&file_name));
+                cover.replace(Some(path));
+            }
+        ),
+    );
+}
+
+fn read_cover(path: &Path) -> std::io::Result<Cover> {
+    let image = std::fs::read(path)?;
+    let (content_type, _) = gio::content_type_guess(Some(path), &image);
+    let content_type = gio::content_type_get_mime_type(&content_type)
+        .map(|mime| mime.to_string())
+        .unwrap_or_else(|| "image/jpeg".to_string());
+    Ok(Cover {
+        image,
+        content_type,
+    })
+}
+
+fn save(
+    window: &Window,
+    playlist_id: String,
+    details: PlaylistDetails,
+    description_changed: bool,
+    cover: Option<Cover>,
+    on_saved: Rc<dyn Fn()>,
+) {
+    let app = window.get_application();
+    let backend = app.backend();
+    let has_cover = cover.is_some();
+    let id = playlist_id.clone();
+    spawn_tokio(
+        async move {
+            backend
+                .update_playlist(&id, &details)
+                .await
+                .map_err(SaveError::Playlist)?;
+            if let Some(cover) = cover {
+                backend
+                    .set_playlist_image(&id, &cover.image, &cover.content_type)
+                    .await
+                    .map_err(SaveError::Playlist)?;
+            }
+            if description_changed {
+                backend
+                    .set_playlist_description(&id, &details.description)
+                    .await
+                    .map_err(SaveError::Description)?;
+            }
+            Ok(())
+        },
+        glib::clone!(
+            #[weak]
+            window,
+            move |result: Result<(), SaveError>| {
+                let message = match result {
+                    Ok(()) => tr("Playlist updated"),
+                    Err(SaveError::Description(err)) => {
+                        error!("Failed to update playlist description: {}", err);
+                        tr("Playlist updated, but its description could not be saved")
+                    }
+                    Err(SaveError::Playlist(err)) => {
+                        window.toast(&tr("Failed to update playlist"), None);
+                        error!("Failed to update playlist: {}", err);
+                        return;
+                    }
+                };
+                if has_cover && let Some(image_cache) = app.image_cache() {
+                    image_cache.forget(&playlist_id, ImageType::Primary);
+                }
+                app.refresh_playlists(true);
+                window.toast(&message, None);
+                on_saved();
+            }
+        ),
+    );
+}
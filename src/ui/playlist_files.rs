@@ -0,0 +1,315 @@
+use std::cell::RefCell;
+use std::path::Path;
+use std::rc::Rc;
+
+use adw::prelude::*;
+use gtk::{gio, glib};
+use log::{error, warn};
+
+use crate::{
+    async_utils::spawn_tokio,
+    i18n::{ngettext, tr},
+    playlist_file::{EntryMatch, MatchKind, PlaylistEntry, PlaylistFile, PlaylistFormat},
+    ui::{widget_ext::WidgetApplicationExt, window::Window},
+};
+
+fn format_filters() -> gio::ListStore {
+    let filters = gio::ListStore::new::<gtk::FileFilter>();
+    for format in PlaylistFormat::ALL {
+        let filter = gtk::FileFilter::new();
+        filter.set_name(Some(format.name()));
+        filter.add_suffix(format.extension());
+        filters.append(&filter);
+    }
+    filters
+}
+
+/// Saves the songs as a playlist file, in the format the file name asks for
+/// and M3U8 when it doesn't say. Where the files are on the server is
+/// fetched first, for the locations and MusicBrainz ids.
+pub fn export(window: &Window, name: &str, song_ids: &[String]) {
+    let name = name.to_string();
+    let song_ids = song_ids.to_vec();
+    let file_name = name.replace(['/', '\\'], "-");
+    let dialog = gtk::FileDialog::builder()
+        .title(tr("Export Playlist"))
+        .initial_name(format!("{file_name}.{}", PlaylistFormat::M3u8.extension()))
+        .filters(&format_filters())
+        .modal(true)
+        .build();
+    dialog.save(
+        Some(window),
+        gio::Cancellable::NONE,
+        glib::clone!(
+            #[weak]
+            window,
+            move |result| {
+                let Some(path) = result.ok().and_then(|file| file.path()) else {
+                    return;
+                };
+                let app = window.get_application();
+                if !app.library().needs_song_files() {
+                    write_playlist(&window, &path, &name, &song_ids);
+                    return;
+                }
+                app.load_song_files(
+                    Some(song_ids.clone()),
+                    glib::clone!(
+                        #[weak]
+                        window,
+                        move || write_playlist(&window, &path, &name, &song_ids)
+                    ),
+                );
+            }
+        ),
+    );
+}
+
+fn write_playlist(window: &Window, path: &Path, name: &str, song_ids: &[String]) {
+    let playlist = PlaylistFile {
+        name: name.to_string(),
+        entries: window
+            .get_application()
+            .library()
+            .playlist_entries(song_ids),
+    };
+    let format = path
+        .file_name()
+        .and_then(|name| PlaylistFormat::from_file_name(&name.to_string_lossy()))
+        .unwrap_or(PlaylistFormat::M3u8);
+    let result = playlist
+        .export(format)
+        .map_err(|err| err.to_string())
+        .and_then(|contents| std::fs::write(path, contents).map_err(|err| err.to_string()));
+    match result {
+        Ok(()) => window.toast(&tr("Playlist exported"), None),
+        Err(err) => {
+            warn!("Failed to export playlist: {}", err);
+            window.toast(&tr("Could not export playlist"), None);
+        }
+    }
+}
+
+/// Picks a playlist file, matches it against the library and shows what was
+/// found before creating the playlist
+pub fn import(window: &Window) {
+    let filter = gtk::FileFilter::new();
+    filter.set_name(Some(&tr("Playlists")));
+    for extension in ["m3u8", "m3u", "xspf", "jspf", "json"] {
+        filter.add_suffix(extension);
+    }
+    let dialog = gtk::FileDialog::builder()
+        .title(tr("Import Playlist"))
+        .default_filter(&filter)
+        .modal(true)
+        .build();
+    dialog.open(
+        Some(window),
+        gio::Cancellable::NONE,
+        glib::clone!(
+            #[weak]
+            window,
+            move |result| {
+                let Some(path) = result.ok().and_then(|file| file.path()) else {
+                    return;
+                };
+                match read_playlist(&path) {
+                    Ok(playlist) => review_with_song_files(&window, playlist),
+                    Err(err) => {
+                        warn!("Failed to read playlist {}: {}", path.display(), err);
+                        window.toast(&tr("Could not read playlist"), None);
+                    }
+                }
+            }
+        ),
+    );
+}
+
+/// Matching by MusicBrainz id needs the library's ids, which are only
+/// fetched when the playlist has some
+fn review_with_song_files(window: &Window, playlist: PlaylistFile) {
+    let app = window.get_application();
+    let has_identifiers = playlist
+        .entries
+        .iter()
+        .any(|entry| !entry.identifiers.is_empty());
+    if !has_identifiers || !app.library().needs_song_files() {
+        review(window, playlist);
+        return;
+    }
+    app.load_song_files(
+        None,
+        glib::clone!(
+            #[weak]
+            window,
+            move || review(&window, playlist.clone())
+        ),
+    );
+}
+
+/// Unnamed playlists are named after the file
+fn read_playlist(path: &Path) -> Result<PlaylistFile, String> {
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    let format = PlaylistFormat::from_file_name(&file_name).unwrap_or(PlaylistFormat::M3u8);
+    let contents = std::fs::read_to_string(path).map_err(|err| err.to_string())?;
+    let mut playlist = PlaylistFile::parse(&contents, format).map_err(|err| err.to_string())?;
+    if playlist.name.is_empty() {
+        playlist.name = path
+            .file_stem()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+    }
+    Ok(playlist)
+}
+
+fn entry_row(entry: &PlaylistEntry, subtitle: &str) -> adw::ActionRow {
+    adw::ActionRow::builder()
+        .title(glib::markup_escape_text(&entry.display_name()))
+        .subtitle(glib::markup_escape_text(subtitle))
+        .build()
+}
+
+fn review(window: &Window, playlist: PlaylistFile) {
+    let library = window.get_application().library();
+    let matches = library.match_playlist_entries(&playlist.entries);
+    let found = matches.iter().flatten().count();
+
+    let name_row = adw::EntryRow::builder()
+        .title(tr("Playlist name"))
+        .text(&playlist.name)
+        .build();
+    let name_group = adw::PreferencesGroup::builder()
+        .description(
+            ngettext(
+                "Found {} of 1 song in the library",
+                "Found {} of {} songs in the library",
+                playlist.entries.len() as u32,
+            )
+            .replacen("{}", &found.to_string(), 1)
+            .replacen("{}", &playlist.entries.len().to_string(), 1),
+        )
+        .build();
+    name_group.add(&name_row);
+
+    let page = adw::PreferencesPage::new();
+    page.add(&name_group);
+
+    // Fuzzy matches are only a guess, so each can be left out
+    let included: Rc<RefCell<Vec<bool>>> = Rc::new(RefCell::new(vec![true; matches.len()]));
+    let guesses = adw::PreferencesGroup::builder()
+        .title(tr("Close Matches"))
+        .description(tr(
+            "Songs that only roughly match, check they're the right ones",
+        ))
+        .build();
+    let missing = adw::PreferencesGroup::builder()
+        .title(tr("Not Found"))
+        .description(tr("These songs won't be in the playlist"))
+        .build();
+    for (index, (entry, found)) in playlist.entries.iter().zip(&matches).enumerate() {
+        match found {
+            Some(EntryMatch {
+                song_id,
+                kind: MatchKind::Fuzzy,
+            }) => {
+                let song = library.songs_for_ids(std::slice::from_ref(song_id));
+                let matched = song.first().map_or_else(String::new, |song| {
+                    format!("{} - {}", song.artists_string(), song.title())
+                });
+                let row = entry_row(entry, &tr("Matched to {}").replace("{}", &matched));
+                let check = gtk::CheckButton::builder()
+                    .active(true)
+                    .valign(gtk::Align::Center)
+                    .build();
+                check.connect_toggled(glib::clone!(
+                    #[strong]
+                    included,
+                    move |check| included.borrow_mut()[index] = check.is_active()
+                ));
+                row.add_suffix(&check);
+                row.set_activatable_widget(Some(&check));
+                guesses.add(&row);
+            }
+            Some(_) => {}
+            None => missing.add(&entry_row(entry, &entry.album)),
+        }
+    }
+    if matches
+        .iter()
+        .flatten()
+        .any(|found| found.kind == MatchKind::Fuzzy)
+    {
+        page.add(&guesses);
+    }
+    if found < matches.len() {
+        page.add(&missing);
+    }
+
+    let create_button = gtk::Button::builder()
+        .label(tr("Create"))
+        .sensitive(found > 0)
+        .css_classes(["suggested-action"])
+        .build();
+    let header_bar = adw::HeaderBar::new();
+    header_bar.set_title_widget(Some(&adw::WindowTitle::new(&tr("Import Playlist"), "")));
+    header_bar.pack_end(&create_button);
+    let toolbar_view = adw::ToolbarView::new();
+    toolbar_view.add_top_bar(&header_bar);
+    toolbar_view.set_content(Some(&page));
+
+    let dialog = adw::Dialog::builder()
+        .can_close(true)
+        .child(&toolbar_view)
+        .build();
+    dialog.set_content_width(500);
+    dialog.set_content_height(600);
+
+    create_button.connect_clicked(glib::clone!(
+        #[weak]
+        window,
+        #[weak]
+        dialog,
+        #[weak]
+        name_row,
+        move |_| {
+            let name = name_row.text().trim().to_string();
+            if name.is_empty() {
+                name_row.grab_focus();
+                return;
+            }
+            let included = included.borrow();
+            let song_ids: Vec<String> = matches
+                .iter()
+                .zip(included.iter())
+                .filter(|(_, included)| **included)
+                .filter_map(|(found, _)| found.as_ref().map(|found| found.song_id.clone()))
+                .collect();
+            create_playlist(&window, name, song_ids);
+            dialog.close();
+        }
+    ));
+    dialog.present(Some(window));
+}
+
+fn create_playlist(window: &Window, name: String, song_ids: Vec<String>) {
+    let app = window.get_application();
+    let backend = app.backend();
+    spawn_tokio(
+        async move { backend.new_playlist(&name, song_ids).await },
+        glib::clone!(
+            #[weak]
+            window,
+            move |result| match result {
+                Ok(_id) => {
+                    app.refresh_playlists(true);
+                    window.toast(&tr("Playlist imported"), None);
+                }
+                Err(err) => {
+                    window.toast(&tr("Failed to create playlist"), None);
+                    error!("Failed to import playlist: {}", err);
+                }
+            }
+        ),
+    );
+}
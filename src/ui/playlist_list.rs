@@ -205,6 +205,12 @@ impl PlaylistList {
             };
             store.append(&PlaylistModel::new(most_played_type));
         }
+        if config::get_playlist_top_rated_enabled() {
+            let top_rated_type = PlaylistType::TopRated {
+                count: DEFAULT_SMART_COUNT,
+            };
+            store.append(&PlaylistModel::new(top_rated_type));
+        }
 
         if config::get_playlist_favorites_enabled() {
             store.append(&PlaylistModel::new(PlaylistType::Favorites {}));
@@ -219,9 +225,90 @@ impl PlaylistList {
             );
             store.append(&PlaylistModel::new(playlist_type));
         }
+        self.update_folders(self.selected_folder());
         self.apply_sort(self.current_sort_by(), self.current_sort_direction());
     }
 
+    /// `None` for every playlist, `Some(None)` for those not in a folder
+    fn selected_folder(&self) -> Option<Option<String>> {
+        let imp = self.imp();
+        match imp.folder_dropdown.selected() {
+            0 | gtk::INVALID_LIST_POSITION => None,
+            1 => Some(None),
+            index => imp
+                .folder_names
+                .borrow()
+                .get(index as usize - 2)
+                .cloned()
+                .map(Some),
+        }
+    }
+
+    /// Folders come and go with the playlists in them, so the choices are
+    /// rebuilt each time, keeping `selected` when it's still there
+    fn update_folders(&self, selected: Option<Option<String>>) {
+        let imp = self.imp();
+        let folders = self.get_application().playlist_folders().borrow().folders();
+        let index = match &selected {
+            None => 0,
+            Some(None) => 1,
+            Some(Some(name)) => folders
+                .iter()
+                .position(|folder| folder == name)
+                .map_or(0, |index| index as u32 + 2),
+        };
+        let mut names = vec![tr("All Playlists"), tr("Not in a Folder")];
+        names.extend(folders.iter().cloned());
+        let names: Vec<&str> = names.iter().map(String::as_str).collect();
+        imp.folder_bar.set_visible(!folders.is_empty());
+        imp.folder_names.replace(folders);
+        imp.folder_dropdown
+            .set_model(Some(&gtk::StringList::new(&names)));
+        imp.folder_dropdown.set_selected(index);
+        self.filter_folder();
+    }
+
+    fn filter_folder(&self) {
+        let imp = self.imp();
+        let filter = imp.folder_filter.get().unwrap();
+        let selected = self.selected_folder();
+        imp.rename_folder
+            .set_visible(selected.as_ref().is_some_and(Option::is_some));
+        let Some(folder) = selected else {
+            filter.unset_filter_func();
+            return;
+        };
+        let folders = self.get_application().playlist_folders();
+        filter.set_filter_func(move |obj| {
+            obj.downcast_ref::<PlaylistModel>()
+                .is_some_and(|m| folders.borrow().folder(&m.id()) == folder.as_deref())
+        });
+    }
+
+    fn rename_folder(&self) {
+        let Some(Some(folder)) = self.selected_folder() else {
+            return;
+        };
+        playlist_dialogs::rename_folder(
+            Some(&self.get_root_window()),
+            &folder,
+            glib::clone!(
+                #[weak(rename_to = playlist_list)]
+                self,
+                #[strong]
+                folder,
+                move |name| {
+                    let app = playlist_list.get_application();
+                    app.playlist_folders()
+                        .borrow_mut()
+                        .rename_folder(&folder, &name);
+                    app.save_playlist_folders();
+                    playlist_list.update_folders(Some(Some(name)));
+                }
+            ),
+        );
+    }
+
     pub fn activate_playlist(&self, index: u32) {
         let window = self.get_root_window();
         handle_grid_activation::<PlaylistModel, _>(
@@ -296,8 +383,11 @@ impl PlaylistList {
         let fav_model =
             gtk::FilterListModel::new(Some(store.clone()), Some(favorites_filter.clone()));
 
+        let folder_filter = gtk::CustomFilter::new(|_| true);
+        let folder_model = gtk::FilterListModel::new(Some(fav_model), Some(folder_filter.clone()));
+
         let name_filter = create_string_filter::<PlaylistModel>("name");
-        let search_model = gtk::FilterListModel::new(Some(fav_model), Some(name_filter.clone()));
+        let search_model = gtk::FilterListModel::new(Some(folder_model), Some(name_filter.clone()));
 
         let sorter = self.build_sorter();
         let sort_model = gtk::SortListModel::new(Some(search_model), Some(sorter.clone()));
@@ -348,6 +438,7 @@ impl PlaylistList {
         imp.grid_view.set_factory(Some(&factory));
         imp.store.set(store).unwrap();
         imp.favorites_filter.set(favorites_filter).unwrap();
+        imp.folder_filter.set(folder_filter).unwrap();
         imp.name_filter.set(name_filter).unwrap();
         imp.sorter.set(sorter).unwrap();
     }
@@ -365,7 +456,7 @@ impl Default for PlaylistList {
 }
 
 mod imp {
-    use std::cell::{Cell, OnceCell};
+    use std::cell::{Cell, OnceCell, RefCell};
     use std::rc::Rc;
 
     use crate::config::settings;
@@ -385,9 +476,17 @@ mod imp {
         pub grid_view: TemplateChild<gtk::GridView>,
         #[template_child]
         pub empty: TemplateChild<adw::StatusPage>,
+        #[template_child]
+        pub folder_bar: TemplateChild<gtk::Box>,
+        #[template_child]
+        pub folder_dropdown: TemplateChild<gtk::DropDown>,
+        #[template_child]
+        pub rename_folder: TemplateChild<gtk::Button>,
 
         pub store: OnceCell<gio::ListStore>,
         pub favorites_filter: OnceCell<gtk::CustomFilter>,
+        pub folder_filter: OnceCell<gtk::CustomFilter>,
+        pub folder_names: RefCell<Vec<String>>,
         pub name_filter: OnceCell<gtk::StringFilter>,
         pub sorter: OnceCell<gtk::CustomSorter>,
         pub sort_state: Rc<Cell<(u32, u32)>>,
@@ -404,6 +503,13 @@ mod imp {
 
         fn class_init(klass: &mut Self::Class) {
             klass.bind_template();
+            klass.install_action(
+                "playlist_list.rename-folder",
+                None,
+                |playlist_list, _, _| {
+                    playlist_list.rename_folder();
+                },
+            );
         }
 
         fn instance_init(obj: &InitializingObject<Self>) {
@@ -417,6 +523,14 @@ mod imp {
             self.parent_constructed();
             self.obj().setup_model();
 
+            self.folder_dropdown.connect_selected_notify(glib::clone!(
+                #[weak(rename_to = playlist_list)]
+                self.obj(),
+                move |_| {
+                    playlist_list.filter_folder();
+                }
+            ));
+
             self.grid_view.connect_activate(glib::clone!(
                 #[weak(rename_to = playlist_list)]
                 self.obj(),
@@ -447,6 +561,17 @@ mod imp {
                 ),
             );
 
+            settings().connect_changed(
+                Some("playlist-top-rated-enabled"),
+                glib::clone!(
+                    #[weak(rename_to = playlist_list)]
+                    self.obj(),
+                    move |_settings, _key| {
+                        playlist_list.pull_playlists();
+                    }
+                ),
+            );
+
             settings().connect_changed(
                 Some("playlist-favorites-enabled"),
                 glib::clone!(
@@ -0,0 +1,167 @@
+use std::cell::Cell;
+use std::rc::Rc;
+
+use adw::prelude::*;
+use gtk::glib;
+use log::{error, warn};
+
+use crate::{
+    async_utils::spawn_tokio,
+    backend::BackendError,
+    i18n::tr,
+    jellyfin::{
+        Jellyfin,
+        api::{PlaylistUserPermissions, UserDto},
+    },
+    ui::{widget_ext::WidgetApplicationExt, window::Window},
+};
+
+/// Positions in each user's access dropdown
+const NOT_SHARED: u32 = 0;
+const CAN_VIEW: u32 = 1;
+const CAN_EDIT: u32 = 2;
+
+/// Lists the server's users with what they can do with the playlist. Only
+/// Jellyfin playlists can be shared.
+pub fn share_playlist(window: &Window, playlist_id: String) {
+    let Some(jellyfin) = window.get_application().backend().jellyfin().cloned() else {
+        return;
+    };
+    let id = playlist_id.clone();
+    spawn_tokio(
+        async move {
+            let users = jellyfin.get_users().await?;
+            let shares = jellyfin.get_playlist_users(&id).await?;
+            Ok((users, shares))
+        },
+        glib::clone!(
+            #[weak]
+            window,
+            move |result: Result<(Vec<UserDto>, Vec<PlaylistUserPermissions>), BackendError>| {
+                match result {
+                    Ok((users, shares)) => present(&window, playlist_id, users, shares),
+                    Err(err) => {
+                        window.toast(&tr("Could not load who the playlist is shared with"), None);
+                        warn!("Failed to load playlist users: {}", err);
+                    }
+                }
+            }
+        ),
+    );
+}
+
+fn present(
+    window: &Window,
+    playlist_id: String,
+    mut users: Vec<UserDto>,
+    shares: Vec<PlaylistUserPermissions>,
+) {
+    let Some(jellyfin) = window.get_application().backend().jellyfin().cloned() else {
+        return;
+    };
+    users.retain(|user| user.id != jellyfin.user_id);
+    users.sort_by_key(|user| user.name.to_lowercase());
+
+    let group = adw::PreferencesGroup::builder()
+        .title(tr("People"))
+        .description(tr(
+            "Anyone who can edit may add, remove and reorder songs, and share it further",
+        ))
+        .build();
+    if users.is_empty() {
+        group.set_description(Some(&tr("There's nobody else on the server to share with")));
+    }
+    let access = [tr("Not Shared"), tr("Can View"), tr("Can Edit")];
+    let access: Vec<&str> = access.iter().map(String::as_str).collect();
+    for user in users {
+        let selected = match shares.iter().find(|share| share.user_id == user.id) {
+            Some(share) if share.can_edit => CAN_EDIT,
+            Some(_) => CAN_VIEW,
+            None => NOT_SHARED,
+        };
+        let row = adw::ComboRow::builder()
+            .title(glib::markup_escape_text(&user.name))
+            .model(&gtk::StringList::new(&access))
+            .selected(selected)
+            .build();
+        // What the server has, for going back to when a change fails
+        let saved = Rc::new(Cell::new(selected));
+        row.connect_selected_notify(glib::clone!(
+            #[weak]
+            window,
+            #[strong]
+            jellyfin,
+            #[strong]
+            playlist_id,
+            move |row| {
+                if row.selected() != saved.get() {
+                    set_access(&window, row, &saved, &jellyfin, &playlist_id, &user.id);
+                }
+            }
+        ));
+        group.add(&row);
+    }
+
+    let page = adw::PreferencesPage::new();
+    page.add(&group);
+    let header_bar = adw::HeaderBar::new();
+    header_bar.set_title_widget(Some(&adw::WindowTitle::new(&tr("Share Playlist"), "")));
+    let toolbar_view = adw::ToolbarView::new();
+    toolbar_view.add_top_bar(&header_bar);
+    toolbar_view.set_content(Some(&page));
+
+    let dialog = adw::Dialog::builder()
+        .can_close(true)
+        .child(&toolbar_view)
+        .build();
+    dialog.set_content_width(460);
+    dialog.set_content_height(520);
+    dialog.present(Some(window));
+}
+
+/// Changes are sent as soon as they're picked, and undone on the row when
+/// the server turns them down
+fn set_access(
+    window: &Window,
+    row: &adw::ComboRow,
+    saved: &Rc<Cell<u32>>,
+    jellyfin: &Jellyfin,
+    playlist_id: &str,
+    user_id: &str,
+) {
+    let access = row.selected();
+    let jellyfin = jellyfin.clone();
+    let playlist_id = playlist_id.to_string();
+    let user_id = user_id.to_string();
+    spawn_tokio(
+        async move {
+            match access {
+                NOT_SHARED => jellyfin.unshare_playlist(&playlist_id, &user_id).await,
+                _ => {
+                    jellyfin
+                        .share_playlist(&playlist_id, &user_id, access == CAN_EDIT)
+                        .await
+                }
+            }
+        },
+        glib::clone!(
+            #[weak]
+            window,
+            #[weak]
+            row,
+            #[strong]
+            saved,
+            move |result| match result {
+                Ok(()) => saved.set(access),
+                Err(err) => {
+                    window.toast(
+                        &tr("Failed to change who the playlist is shared with"),
+                        None,
+                    );
+                    error!("Failed to update playlist sharing: {}", err);
+                    row.set_selected(saved.get());
+                }
+            }
+        ),
+    );
+}
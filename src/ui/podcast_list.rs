@@ -0,0 +1,690 @@
+use crate::{
+    application::Application,
+    async_utils::spawn_tokio,
+    backend::BackendError,
+    cache::CacheError,
+    config,
+    i18n::{ngettext, tr},
+    jellyfin::{
+        api::{PodcastChannel, PodcastEpisode},
+        utils::{format_duration, strip_html},
+    },
+    models::SongModel,
+    podcast::download_file,
+    ui::{
+        page_traits::{SortDirection, SortType, TopPage},
+        widget_ext::WidgetApplicationExt,
+    },
+};
+use adw::prelude::*;
+use glib::Object;
+use gtk::{gio, glib, subclass::prelude::*};
+use log::warn;
+
+glib::wrapper! {
+    pub struct PodcastList(ObjectSubclass<imp::PodcastList>)
+    @extends gtk::Widget, gtk::Box,
+        @implements gio::ActionMap, gio::ActionGroup, gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget;
+}
+
+/// Playback speeds offered for episodes, as shown in the speed menu
+const PLAYBACK_RATES: [f64; 6] = [0.75, 1.0, 1.25, 1.5, 1.75, 2.0];
+const NEWEST_EPISODE_COUNT: u32 = 10;
+
+impl TopPage for PodcastList {
+    fn can_new(&self) -> bool {
+        false
+    }
+
+    fn has_genres(&self) -> bool {
+        false
+    }
+
+    fn play_selected(&self) {
+        let episode = match self.channel() {
+            Some(channel) => channel.episodes.first().cloned(),
+            None => self.imp().newest.borrow().first().cloned(),
+        };
+        if let Some(episode) = episode {
+            self.play_episode(&episode);
+        }
+    }
+
+    fn search_changed(&self, query: &str) {
+        let imp = self.imp();
+        imp.search.replace(query.to_lowercase());
+        imp.newest_list.invalidate_filter();
+        imp.channel_list.invalidate_filter();
+        imp.episode_list.invalidate_filter();
+    }
+
+    fn genre_changed(&self, _genre: Option<&str>) {}
+
+    fn sort_options(&self) -> &[SortType] {
+        &[SortType::Name]
+    }
+
+    fn current_sort_by(&self) -> u32 {
+        0
+    }
+
+    fn current_sort_direction(&self) -> u32 {
+        self.imp().sort_direction.get()
+    }
+
+    fn apply_sort(&self, _sort_by: u32, direction: u32) {
+        self.imp().sort_direction.set(direction);
+        self.show_channels();
+    }
+
+    fn supports_favorites(&self) -> bool {
+        false
+    }
+
+    fn filter_favorites(&self, _active: bool) {}
+
+    fn reset_position(&self) {
+        self.imp().scrolled_window.vadjustment().set_value(0.0);
+    }
+}
+
+impl PodcastList {
+    pub fn new() -> Self {
+        Object::builder().build()
+    }
+
+    fn channel(&self) -> Option<PodcastChannel> {
+        let channel_id = self.imp().channel_id.borrow().clone()?;
+        self.imp()
+            .channels
+            .borrow()
+            .iter()
+            .find(|channel| channel.id == channel_id)
+            .cloned()
+    }
+
+    fn find_episode(&self, episode_id: &str) -> Option<PodcastEpisode> {
+        let imp = self.imp();
+        imp.channels
+            .borrow()
+            .iter()
+            .flat_map(|channel| channel.episodes.iter())
+            .chain(imp.newest.borrow().iter())
+            .find(|episode| episode.id == episode_id)
+            .cloned()
+    }
+
+    pub fn refresh(&self) {
+        let backend = self.get_application().backend();
+        self.set_is_loading(true);
+        spawn_tokio(
+            async move {
+                let channels = backend.get_podcasts().await?;
+                let newest = backend
+                    .get_newest_podcast_episodes(NEWEST_EPISODE_COUNT)
+                    .await?;
+                Ok((channels, newest))
+            },
+            glib::clone!(
+                #[weak(rename_to = podcast_list)]
+                self,
+                move |result: Result<(Vec<PodcastChannel>, Vec<PodcastEpisode>), BackendError>| {
+                    podcast_list.set_is_loading(false);
+                    match result {
+                        Ok((channels, newest)) => podcast_list.set_podcasts(channels, newest),
+                        Err(err) => {
+                            warn!("Failed to load podcasts: {}", err);
+                            podcast_list.toast(&tr("Could not load podcasts"), None);
+                        }
+                    }
+                }
+            ),
+        );
+    }
+
+    fn set_podcasts(&self, channels: Vec<PodcastChannel>, mut newest: Vec<PodcastEpisode>) {
+        // Subsonic only gives us the channel id for the newest episodes
+        for episode in newest.iter_mut().filter(|e| e.channel_title.is_empty()) {
+            if let Some(channel) = channels.iter().find(|c| c.id == episode.channel_id) {
+                episode.channel_title = channel.title.clone();
+            }
+        }
+        let imp = self.imp();
+        imp.channels.replace(channels);
+        imp.newest.replace(newest);
+        if self.channel().is_none() {
+            imp.channel_id.replace(None);
+        }
+        self.show_channels();
+    }
+
+    pub fn open_channel(&self, channel_id: Option<String>) {
+        self.imp().channel_id.replace(channel_id);
+        self.show_channels();
+        self.reset_position();
+    }
+
+    /// Either the overview of newest episodes and channels, or one channel's
+    /// episodes
+    fn show_channels(&self) {
+        let imp = self.imp();
+        imp.newest_list.remove_all();
+        imp.channel_list.remove_all();
+        imp.episode_list.remove_all();
+
+        let channel = self.channel();
+        let in_channel = channel.is_some();
+        imp.back_button.set_visible(in_channel);
+        imp.overview.set_visible(!in_channel);
+        imp.channel_view.set_visible(in_channel);
+
+        if let Some(channel) = channel {
+            imp.title.set_text(&channel.title);
+            let description = channel
+                .description
+                .as_deref()
+                .map(strip_html)
+                .unwrap_or_default();
+            imp.channel_description.set_text(&description);
+            imp.channel_description.set_visible(!description.is_empty());
+            for episode in &channel.episodes {
+                imp.episode_list.append(&self.episode_row(episode, false));
+            }
+            self.set_empty(channel.episodes.is_empty());
+            return;
+        }
+
+        imp.title.set_text(&tr("Podcasts"));
+        let newest = imp.newest.borrow();
+        for episode in newest.iter() {
+            imp.newest_list.append(&self.episode_row(episode, true));
+        }
+        imp.newest_heading.set_visible(!newest.is_empty());
+        imp.newest_list.set_visible(!newest.is_empty());
+
+        let mut channels = imp.channels.borrow().clone();
+        channels.sort_by_key(|c| c.title.to_lowercase());
+        if matches!(
+            SortDirection::try_from(imp.sort_direction.get()),
+            Ok(SortDirection::Descending)
+        ) {
+            channels.reverse();
+        }
+        for channel in &channels {
+            let row = adw::ActionRow::builder()
+                .title(&channel.title)
+                .subtitle(
+                    ngettext("1 episode", "{} episodes", channel.episodes.len() as u32)
+                        .replace("{}", &channel.episodes.len().to_string()),
+                )
+                .use_markup(false)
+                .activatable(true)
+                .build();
+            row.add_prefix(&gtk::Image::from_icon_name(
+                "audio-input-microphone-symbolic",
+            ));
+            row.add_suffix(&gtk::Image::from_icon_name("go-next-symbolic"));
+            let channel_id = channel.id.clone();
+            row.connect_activated(glib::clone!(
+                #[weak(rename_to = podcast_list)]
+                self,
+                move |_| podcast_list.open_channel(Some(channel_id.clone()))
+            ));
+            imp.channel_list.append(&row);
+        }
+        imp.channels_heading.set_visible(!channels.is_empty());
+        self.set_empty(channels.is_empty() && newest.is_empty());
+    }
+
+    fn set_empty(&self, empty: bool) {
+        self.imp().empty.set_visible(empty);
+        self.imp().scrolled_window.set_visible(!empty);
+    }
+
+    /// Played state and progress, ours once we've listened locally,
+    /// otherwise what the server remembers
+    fn episode_progress(&self, episode: &PodcastEpisode) -> (bool, u64) {
+        let store = self.get_application().podcast_store();
+        let store = store.borrow();
+        if store.contains(episode.play_id()) {
+            let state = store.state(episode.play_id());
+            (state.played, state.position)
+        } else {
+            (episode.played, episode.position_ticks / 10_000_000)
+        }
+    }
+
+    fn episode_row(&self, episode: &PodcastEpisode, show_channel: bool) -> adw::ActionRow {
+        let (played, position) = self.episode_progress(episode);
+        let duration = episode.run_time_ticks / 10_000_000;
+
+        let mut details = vec![];
+        if show_channel && !episode.channel_title.is_empty() {
+            details.push(episode.channel_title.clone());
+        }
+        if let Some(date) = &episode.publish_date {
+            details.push(date.chars().take(10).collect());
+        }
+        if duration > 0 {
+            details.push(format_duration(episode.run_time_ticks));
+        }
+        if played {
+            details.push(tr("Played"));
+        } else if position > 0 && duration > position {
+            let minutes = (duration - position).div_ceil(60);
+            details.push(
+                ngettext("1 min left", "{} min left", minutes as u32)
+                    .replace("{}", &minutes.to_string()),
+            );
+        }
+
+        let row = adw::ActionRow::builder()
+            .title(&episode.title)
+            .subtitle(details.join(" • "))
+            .use_markup(false)
+            .activatable(true)
+            .build();
+        let icon = if played {
+            "object-select-symbolic"
+        } else {
+            "media-playback-start-symbolic"
+        };
+        row.add_prefix(&gtk::Image::from_icon_name(icon));
+        if self
+            .get_application()
+            .podcast_store()
+            .borrow()
+            .downloaded_file(episode.play_id())
+            .is_some()
+        {
+            let downloaded = gtk::Image::from_icon_name("folder-download-symbolic");
+            downloaded.set_tooltip_text(Some(&tr("Downloaded")));
+            row.add_suffix(&downloaded);
+        }
+        row.add_suffix(&self.episode_menu_button(episode, played));
+
+        let episode = episode.clone();
+        row.connect_activated(glib::clone!(
+            #[weak(rename_to = podcast_list)]
+            self,
+            move |_| podcast_list.play_episode(&episode)
+        ));
+        row
+    }
+
+    fn episode_menu_button(&self, episode: &PodcastEpisode, played: bool) -> gtk::MenuButton {
+        let menu = gio::Menu::new();
+        let target = episode.id.to_variant();
+        let add_item = |label: String, action: &str| {
+            let item = gio::MenuItem::new(Some(&label), None);
+            item.set_action_and_target_value(Some(action), Some(&target));
+            menu.append_item(&item);
+        };
+
+        if played {
+            add_item(tr("Mark as Unplayed"), "podcast.toggle_played");
+        } else {
+            add_item(tr("Mark as Played"), "podcast.toggle_played");
+        }
+        let is_subsonic = self.get_application().backend().subsonic().is_some();
+        if episode.stream_id.is_none() {
+            if is_subsonic {
+                add_item(tr("Download to Server"), "podcast.download_to_server");
+            }
+        } else if self
+            .get_application()
+            .podcast_store()
+            .borrow()
+            .downloaded_file(episode.play_id())
+            .is_some()
+        {
+            add_item(tr("Remove Download"), "podcast.remove_download");
+        } else {
+            add_item(tr("Download to This Computer"), "podcast.download");
+        }
+
+        gtk::MenuButton::builder()
+            .icon_name("view-more-symbolic")
+            .tooltip_text(tr("More"))
+            .valign(gtk::Align::Center)
+            .menu_model(&menu)
+            .css_classes(["flat"])
+            .build()
+    }
+
+    fn play_episode(&self, episode: &PodcastEpisode) {
+        if episode.stream_id.is_none() {
+            self.toast(&tr("The server hasn't downloaded this episode yet"), None);
+            return;
+        }
+        let Some(audio_model) = self.get_application().audio_model() else {
+            self.toast(&tr("Audio model not initialized, please restart"), None);
+            warn!("No audio model found");
+            return;
+        };
+        let (played, position) = self.episode_progress(episode);
+        let song = SongModel::new(&episode.to_music_dto(), false);
+        song.set_is_episode(true);
+        audio_model.set_queue(vec![song], 0, true);
+        if !played && position > 0 {
+            audio_model.seek_once_playing(position as u32);
+        }
+    }
+
+    fn toggle_played(&self, episode_id: &str) {
+        let Some(episode) = self.find_episode(episode_id) else {
+            return;
+        };
+        let app = self.get_application();
+        let (played, _) = self.episode_progress(&episode);
+        app.podcast_store()
+            .borrow_mut()
+            .set_played(episode.play_id(), !played);
+        app.save_podcast_store();
+        self.show_channels();
+
+        let backend = app.backend();
+        let episode_id = episode.id.clone();
+        spawn_tokio(
+            async move { backend.set_played(&episode_id, !played).await },
+            glib::clone!(
+                #[weak(rename_to = podcast_list)]
+                self,
+                move |result: Result<(), BackendError>| {
+                    if let Err(err) = result {
+                        warn!("Failed to update played state on the server: {}", err);
+                        podcast_list.toast(&tr("Could not update the server"), None);
+                    }
+                }
+            ),
+        );
+    }
+
+    fn download_to_server(&self, episode_id: &str) {
+        let backend = self.get_application().backend();
+        let episode_id = episode_id.to_string();
+        spawn_tokio(
+            async move {
+                match backend.subsonic() {
+                    Some(subsonic) => subsonic.download_podcast_episode(&episode_id).await,
+                    None => Ok(()),
+                }
+            },
+            glib::clone!(
+                #[weak(rename_to = podcast_list)]
+                self,
+                move |result: Result<(), BackendError>| match result {
+                    Ok(()) => podcast_list.toast(
+                        &tr("The server is downloading the episode, refresh in a moment"),
+                        None,
+                    ),
+                    Err(err) => {
+                        warn!("Failed to request episode download: {}", err);
+                        podcast_list.toast(&tr("Could not download the episode"), None);
+                    }
+                }
+            ),
+        );
+    }
+
+    fn download(&self, episode_id: &str) {
+        let Some(episode) = self.find_episode(episode_id) else {
+            return;
+        };
+        let app = self.get_application();
+        let play_id = episode.play_id().to_string();
+        let Some(path) = app.podcast_store().borrow().download_path(&play_id) else {
+            self.toast(&tr("Could not download the episode"), None);
+            return;
+        };
+        let uri = app.backend().get_download_uri(&play_id);
+        self.toast(&tr("Downloading episode…"), None);
+        let target = path.clone();
+        spawn_tokio(
+            async move { download_file(uri, target).await },
+            glib::clone!(
+                #[weak(rename_to = podcast_list)]
+                self,
+                move |result: Result<(), CacheError>| match result {
+                    Ok(()) => {
+                        let app = podcast_list.get_application();
+                        app.podcast_store()
+                            .borrow_mut()
+                            .set_downloaded(&play_id, &path);
+                        app.save_podcast_store();
+                        podcast_list.show_channels();
+                        podcast_list.toast(&tr("Episode downloaded"), None);
+                    }
+                    Err(err) => {
+                        warn!("Failed to download episode {}: {}", play_id, err);
+                        podcast_list.toast(&tr("Could not download the episode"), None);
+                    }
+                }
+            ),
+        );
+    }
+
+    fn remove_download(&self, episode_id: &str) {
+        let Some(episode) = self.find_episode(episode_id) else {
+            return;
+        };
+        let app = self.get_application();
+        let result = app
+            .podcast_store()
+            .borrow_mut()
+            .remove_download(episode.play_id());
+        if let Err(err) = result {
+            warn!("Failed to remove episode download: {}", err);
+            self.toast(&tr("Could not remove the download"), None);
+        }
+        app.save_podcast_store();
+        self.show_channels();
+    }
+
+    fn setup_playback_rate(&self) {
+        let imp = self.imp();
+        let labels: Vec<String> = PLAYBACK_RATES
+            .iter()
+            .map(|rate| format!("{rate}×"))
+            .collect();
+        let labels: Vec<&str> = labels.iter().map(String::as_str).collect();
+        imp.speed_selector
+            .set_model(Some(&gtk::StringList::new(&labels)));
+        let current = config::get_podcast_playback_rate();
+        let selected = PLAYBACK_RATES
+            .iter()
+            .position(|rate| (rate - current).abs() < f64::EPSILON)
+            .unwrap_or(1);
+        imp.speed_selector.set_selected(selected as u32);
+        imp.speed_selector.connect_selected_notify(glib::clone!(
+            #[weak(rename_to = podcast_list)]
+            self,
+            move |dropdown| {
+                let Some(rate) = PLAYBACK_RATES.get(dropdown.selected() as usize) else {
+                    return;
+                };
+                config::set_podcast_playback_rate(*rate);
+                if let Some(audio_model) = podcast_list.get_application().audio_model() {
+                    audio_model.apply_playback_rate();
+                }
+            }
+        ));
+    }
+
+    pub fn setup_library_connection(&self) {
+        let app = self.get_application();
+        app.connect_closure(
+            "library-refreshed",
+            false,
+            glib::closure_local!(
+                #[weak(rename_to = podcast_list)]
+                self,
+                move |_app: Application, _total_record_count: u64| {
+                    podcast_list.refresh();
+                }
+            ),
+        );
+
+        app.connect_closure(
+            "podcast-episodes-changed",
+            false,
+            glib::closure_local!(
+                #[weak(rename_to = podcast_list)]
+                self,
+                move |_app: Application| {
+                    podcast_list.show_channels();
+                }
+            ),
+        );
+    }
+}
+
+impl Default for PodcastList {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+mod imp {
+    use std::cell::{Cell, RefCell};
+
+    use adw::subclass::prelude::*;
+    use glib::subclass::InitializingObject;
+
+    use crate::jellyfin::api::{PodcastChannel, PodcastEpisode};
+    use gtk::{
+        CompositeTemplate,
+        glib::{self, Properties},
+        prelude::*,
+    };
+
+    #[derive(CompositeTemplate, Default, Properties)]
+    #[template(resource = "/io/m51/Gelly/ui/podcast_list.ui")]
+    #[properties(wrapper_type = super::PodcastList)]
+    pub struct PodcastList {
+        #[template_child]
+        pub back_button: TemplateChild<gtk::Button>,
+        #[template_child]
+        pub title: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub speed_selector: TemplateChild<gtk::DropDown>,
+        #[template_child]
+        pub scrolled_window: TemplateChild<gtk::ScrolledWindow>,
+        #[template_child]
+        pub overview: TemplateChild<gtk::Box>,
+        #[template_child]
+        pub newest_heading: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub newest_list: TemplateChild<gtk::ListBox>,
+        #[template_child]
+        pub channels_heading: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub channel_list: TemplateChild<gtk::ListBox>,
+        #[template_child]
+        pub channel_view: TemplateChild<gtk::Box>,
+        #[template_child]
+        pub channel_description: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub episode_list: TemplateChild<gtk::ListBox>,
+        #[template_child]
+        pub empty: TemplateChild<adw::StatusPage>,
+
+        pub channels: RefCell<Vec<PodcastChannel>>,
+        pub newest: RefCell<Vec<PodcastEpisode>>,
+        /// The channel being shown, `None` for the overview
+        pub channel_id: RefCell<Option<String>>,
+        pub search: RefCell<String>,
+        pub sort_direction: Cell<u32>,
+
+        #[property(get, set, default = false)]
+        pub compact_mode: Cell<bool>,
+        #[property(get, set, default = false)]
+        pub is_loading: Cell<bool>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for PodcastList {
+        const NAME: &'static str = "GellyPodcastList";
+        type Type = super::PodcastList;
+        type ParentType = gtk::Box;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+            klass.install_action("podcast.back", None, |podcast_list, _, _| {
+                podcast_list.open_channel(None);
+            });
+            klass.install_action("podcast.refresh", None, |podcast_list, _, _| {
+                podcast_list.refresh();
+            });
+            klass.install_action(
+                "podcast.toggle_played",
+                Some(glib::VariantTy::STRING),
+                |podcast_list, _, param| {
+                    if let Some(id) = param.and_then(|p| p.get::<String>()) {
+                        podcast_list.toggle_played(&id);
+                    }
+                },
+            );
+            klass.install_action(
+                "podcast.download_to_server",
+                Some(glib::VariantTy::STRING),
+                |podcast_list, _, param| {
+                    if let Some(id) = param.and_then(|p| p.get::<String>()) {
+                        podcast_list.download_to_server(&id);
+                    }
+                },
+            );
+            klass.install_action(
+                "podcast.download",
+                Some(glib::VariantTy::STRING),
+                |podcast_list, _, param| {
+                    if let Some(id) = param.and_then(|p| p.get::<String>()) {
+                        podcast_list.download(&id);
+                    }
+                },
+            );
+            klass.install_action(
+                "podcast.remove_download",
+                Some(glib::VariantTy::STRING),
+                |podcast_list, _, param| {
+                    if let Some(id) = param.and_then(|p| p.get::<String>()) {
+                        podcast_list.remove_download(&id);
+                    }
+                },
+            );
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for PodcastList {
+        fn constructed(&self) {
+            self.parent_constructed();
+            self.obj().setup_playback_rate();
+            for list_box in [&*self.newest_list, &*self.channel_list, &*self.episode_list] {
+                list_box.set_filter_func(glib::clone!(
+                    #[weak(rename_to = podcast_list)]
+                    self.obj(),
+                    #[upgrade_or]
+                    true,
+                    move |row| {
+                        let search = podcast_list.imp().search.borrow();
+                        search.is_empty()
+                            || row.downcast_ref::<adw::ActionRow>().is_some_and(|row| {
+                                row.title().to_lowercase().contains(&*search)
+                                    || row
+                                        .subtitle()
+                                        .is_some_and(|s| s.to_lowercase().contains(&*search))
+                            })
+                    }
+                ));
+            }
+        }
+    }
+
+    impl WidgetImpl for PodcastList {}
+    impl BoxImpl for PodcastList {}
+}
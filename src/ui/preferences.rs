@@ -1,4 +1,4 @@
-use adw::prelude::ComboRowExt;
+use adw::prelude::{ActionRowExt, AdwDialogExt, ComboRowExt};
 use gtk::{
     self, gio,
     glib::{self, Object},
@@ -7,6 +7,8 @@ use gtk::{
 };
 
 use crate::config::{self, TranscodingProfile};
+use crate::i18n::tr;
+use crate::lyrics::{self, LyricsOptions};
 
 glib::wrapper! {
     pub struct Preferences(ObjectSubclass<imp::Preferences>)
@@ -52,6 +54,14 @@ impl Preferences {
             .bind("inhibit-suspend", &*imp.inhibit_suspend_row, "active")
             .build();
 
+        settings
+            .bind(
+                "resume-threshold-minutes",
+                &*imp.resume_threshold_row,
+                "value",
+            )
+            .build();
+
         // Smart Playlists
         settings
             .bind(
@@ -74,6 +84,13 @@ impl Preferences {
                 "active",
             )
             .build();
+        settings
+            .bind(
+                "playlist-top-rated-enabled",
+                &*imp.playlist_top_rated_enabled_row,
+                "active",
+            )
+            .build();
         settings
             .bind(
                 "album-art-window-background",
@@ -82,9 +99,42 @@ impl Preferences {
             )
             .build();
 
+        // Lyrics
+        settings
+            .bind("lyrics-online-enabled", &*imp.lyrics_online_row, "active")
+            .build();
+        settings
+            .bind("lyrics-provider-url", &*imp.lyrics_provider_row, "text")
+            .build();
+        imp.lyrics_online_row
+            .bind_property("active", &*imp.lyrics_provider_row, "sensitive")
+            .sync_create()
+            .build();
+        self.update_lyrics_folder_row();
+        imp.lyrics_folder_button.connect_clicked(glib::clone!(
+            #[weak(rename_to = preferences)]
+            self,
+            move |_| preferences.choose_lyrics_folder()
+        ));
+        imp.lyrics_folder_clear_button.connect_clicked(glib::clone!(
+            #[weak(rename_to = preferences)]
+            self,
+            move |_| {
+                config::set_lyrics_folder("");
+                preferences.update_lyrics_folder_row();
+            }
+        ));
+        // Lyrics cached from the old providers would hide the new ones
+        let lyrics_options = LyricsOptions::from_settings();
+        self.connect_closed(move |_| {
+            if LyricsOptions::from_settings() != lyrics_options {
+                lyrics::clear_cache();
+            }
+        });
+
         // Transcoding Profile
         imp.transcoding_profile_row
-            .set_model(Some(&TranscodingProfile::as_string_list()));
+            .set_model(Some(&transcoding_profile_names()));
 
         let current_profile = config::get_transcoding_profile();
         let initial_index = TranscodingProfile::PROFILES
@@ -101,6 +151,50 @@ impl Preferences {
                 }
             });
     }
+
+    fn update_lyrics_folder_row(&self) {
+        let imp = self.imp();
+        let folder = config::get_lyrics_folder();
+        if folder.is_empty() {
+            imp.lyrics_folder_row.set_subtitle(&tr("Not set"));
+        } else {
+            imp.lyrics_folder_row.set_subtitle(&folder);
+        }
+        imp.lyrics_folder_clear_button
+            .set_visible(!folder.is_empty());
+    }
+
+    fn choose_lyrics_folder(&self) {
+        let dialog = gtk::FileDialog::builder()
+            .title(tr("Lyrics Folder"))
+            .modal(true)
+            .build();
+        let window = self.root().and_downcast::<gtk::Window>();
+        dialog.select_folder(
+            window.as_ref(),
+            gio::Cancellable::NONE,
+            glib::clone!(
+                #[weak(rename_to = preferences)]
+                self,
+                move |result| {
+                    if let Ok(folder) = result
+                        && let Some(path) = folder.path()
+                    {
+                        config::set_lyrics_folder(&path.to_string_lossy());
+                        preferences.update_lyrics_folder_row();
+                    }
+                }
+            ),
+        );
+    }
+}
+
+fn transcoding_profile_names() -> gtk::StringList {
+    let names: Vec<&str> = TranscodingProfile::PROFILES
+        .iter()
+        .map(|p| p.name)
+        .collect();
+    gtk::StringList::new(&names)
 }
 
 impl Default for Preferences {
@@ -132,13 +226,27 @@ mod imp {
         #[template_child]
         pub inhibit_suspend_row: TemplateChild<adw::SwitchRow>,
         #[template_child]
+        pub resume_threshold_row: TemplateChild<adw::SpinRow>,
+        #[template_child]
         pub playlist_favorites_enabled_row: TemplateChild<adw::SwitchRow>,
         #[template_child]
         pub playlist_shuffle_enabled_row: TemplateChild<adw::SwitchRow>,
         #[template_child]
         pub playlist_most_played_enabled_row: TemplateChild<adw::SwitchRow>,
         #[template_child]
+        pub playlist_top_rated_enabled_row: TemplateChild<adw::SwitchRow>,
+        #[template_child]
         pub album_art_background_row: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        pub lyrics_folder_row: TemplateChild<adw::ActionRow>,
+        #[template_child]
+        pub lyrics_folder_button: TemplateChild<gtk::Button>,
+        #[template_child]
+        pub lyrics_folder_clear_button: TemplateChild<gtk::Button>,
+        #[template_child]
+        pub lyrics_online_row: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        pub lyrics_provider_row: TemplateChild<adw::EntryRow>,
     }
 
     #[glib::object_subclass]
@@ -0,0 +1,415 @@
+use crate::{
+    application::Application,
+    async_utils::spawn_tokio,
+    backend::BackendError,
+    i18n::tr,
+    jellyfin::api::RadioStation,
+    models::SongModel,
+    radio::new_user_station,
+    ui::{
+        page_traits::{SortDirection, SortType, TopPage},
+        widget_ext::WidgetApplicationExt,
+    },
+};
+use adw::prelude::*;
+use glib::Object;
+use gtk::{gio, glib, subclass::prelude::*};
+use log::warn;
+
+glib::wrapper! {
+    pub struct RadioList(ObjectSubclass<imp::RadioList>)
+    @extends gtk::Widget, gtk::Box,
+        @implements gio::ActionMap, gio::ActionGroup, gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget;
+}
+
+impl TopPage for RadioList {
+    fn can_new(&self) -> bool {
+        true
+    }
+
+    fn has_genres(&self) -> bool {
+        false
+    }
+
+    fn play_selected(&self) {
+        let radio_store = self.get_application().radio_store();
+        let station = self
+            .imp()
+            .server_stations
+            .borrow()
+            .first()
+            .or(radio_store.borrow().stations().first())
+            .cloned();
+        if let Some(station) = station {
+            self.play_station(&station);
+        }
+    }
+
+    fn create_new(&self) {
+        self.add_station_dialog();
+    }
+
+    fn search_changed(&self, query: &str) {
+        let imp = self.imp();
+        imp.search.replace(query.to_lowercase());
+        imp.server_list.invalidate_filter();
+        imp.user_list.invalidate_filter();
+    }
+
+    fn genre_changed(&self, _genre: Option<&str>) {}
+
+    fn sort_options(&self) -> &[SortType] {
+        &[SortType::Name]
+    }
+
+    fn current_sort_by(&self) -> u32 {
+        0
+    }
+
+    fn current_sort_direction(&self) -> u32 {
+        self.imp().sort_direction.get()
+    }
+
+    fn apply_sort(&self, _sort_by: u32, direction: u32) {
+        self.imp().sort_direction.set(direction);
+        self.show_stations();
+    }
+
+    fn supports_favorites(&self) -> bool {
+        false
+    }
+
+    fn filter_favorites(&self, _active: bool) {}
+
+    fn reset_position(&self) {
+        self.imp().scrolled_window.vadjustment().set_value(0.0);
+    }
+}
+
+impl RadioList {
+    pub fn new() -> Self {
+        Object::builder().build()
+    }
+
+    pub fn refresh(&self) {
+        let backend = self.get_application().backend();
+        self.set_is_loading(true);
+        spawn_tokio(
+            async move { backend.get_radio_stations().await },
+            glib::clone!(
+                #[weak(rename_to = radio_list)]
+                self,
+                move |result: Result<Vec<RadioStation>, BackendError>| {
+                    radio_list.set_is_loading(false);
+                    match result {
+                        Ok(stations) => {
+                            radio_list.imp().server_stations.replace(stations);
+                        }
+                        Err(err) => {
+                            // Plenty of servers have no live TV set up at all
+                            warn!("Failed to load radio stations: {}", err);
+                            radio_list.imp().server_stations.replace(vec![]);
+                        }
+                    }
+                    radio_list.show_stations();
+                }
+            ),
+        );
+    }
+
+    fn show_stations(&self) {
+        let imp = self.imp();
+        let descending = matches!(
+            SortDirection::try_from(imp.sort_direction.get()),
+            Ok(SortDirection::Descending)
+        );
+        let sorted = |stations: &[RadioStation]| {
+            let mut stations = stations.to_vec();
+            stations.sort_by_key(|s| s.name.to_lowercase());
+            if descending {
+                stations.reverse();
+            }
+            stations
+        };
+
+        imp.server_list.remove_all();
+        let server_stations = sorted(&imp.server_stations.borrow());
+        for station in &server_stations {
+            imp.server_list.append(&self.station_row(station));
+        }
+        imp.server_heading.set_visible(!server_stations.is_empty());
+        imp.server_list.set_visible(!server_stations.is_empty());
+
+        imp.user_list.remove_all();
+        let user_stations = sorted(self.get_application().radio_store().borrow().stations());
+        for station in &user_stations {
+            imp.user_list.append(&self.station_row(station));
+        }
+        imp.user_heading.set_visible(!user_stations.is_empty());
+        imp.user_list.set_visible(!user_stations.is_empty());
+
+        let empty = server_stations.is_empty() && user_stations.is_empty();
+        imp.empty.set_visible(empty);
+        imp.scrolled_window.set_visible(!empty);
+    }
+
+    fn station_row(&self, station: &RadioStation) -> adw::ActionRow {
+        let row = adw::ActionRow::builder()
+            .title(&station.name)
+            .subtitle(
+                station
+                    .homepage_url
+                    .as_deref()
+                    .unwrap_or(&station.stream_url),
+            )
+            .use_markup(false)
+            .activatable(true)
+            .build();
+        row.add_prefix(&gtk::Image::from_icon_name("network-wireless-symbolic"));
+
+        if let Some(homepage) = station.homepage_url.clone() {
+            let button = gtk::Button::builder()
+                .icon_name("adw-external-link-symbolic")
+                .tooltip_text(tr("Open Homepage"))
+                .valign(gtk::Align::Center)
+                .css_classes(["flat"])
+                .build();
+            button.connect_clicked(glib::clone!(
+                #[weak(rename_to = radio_list)]
+                self,
+                move |_| {
+                    gtk::UriLauncher::new(&homepage).launch(
+                        radio_list.get_gtk_window().as_ref(),
+                        None::<&gio::Cancellable>,
+                        |result| {
+                            if let Err(e) = result {
+                                warn!("Failed to open station homepage: {}", e);
+                            }
+                        },
+                    );
+                }
+            ));
+            row.add_suffix(&button);
+        }
+
+        if station.user_added {
+            let button = gtk::Button::builder()
+                .icon_name("user-trash-symbolic")
+                .tooltip_text(tr("Remove Station"))
+                .valign(gtk::Align::Center)
+                .css_classes(["flat"])
+                .build();
+            let station_id = station.id.clone();
+            button.connect_clicked(glib::clone!(
+                #[weak(rename_to = radio_list)]
+                self,
+                move |_| radio_list.remove_station(&station_id)
+            ));
+            row.add_suffix(&button);
+        }
+
+        let station = station.clone();
+        row.connect_activated(glib::clone!(
+            #[weak(rename_to = radio_list)]
+            self,
+            move |_| radio_list.play_station(&station)
+        ));
+        row
+    }
+
+    fn play_station(&self, station: &RadioStation) {
+        let Some(audio_model) = self.get_application().audio_model() else {
+            self.toast(&tr("Audio model not initialized, please restart"), None);
+            warn!("No audio model found");
+            return;
+        };
+        audio_model.set_queue(vec![SongModel::new_live(station)], 0, true);
+    }
+
+    fn add_station_dialog(&self) {
+        let name_entry = adw::EntryRow::builder().title(tr("Name")).build();
+        let url_entry = adw::EntryRow::builder()
+            .title(tr("Stream URL"))
+            .input_purpose(gtk::InputPurpose::Url)
+            .build();
+        let entry_box = gtk::ListBox::builder()
+            .margin_top(12)
+            .margin_bottom(12)
+            .build();
+        entry_box.add_css_class("boxed-list");
+        entry_box.append(&name_entry);
+        entry_box.append(&url_entry);
+
+        let dialog = adw::AlertDialog::builder()
+            .heading(tr("Add Station"))
+            .extra_child(&entry_box)
+            .build();
+        dialog.add_responses(&[("cancel", &tr("Cancel")), ("add", &tr("Add"))]);
+        dialog.set_response_appearance("add", adw::ResponseAppearance::Suggested);
+        dialog.set_default_response(Some("add"));
+        dialog.set_close_response("cancel");
+
+        dialog.connect_response(
+            None,
+            glib::clone!(
+                #[weak(rename_to = radio_list)]
+                self,
+                #[weak]
+                dialog,
+                #[weak]
+                name_entry,
+                #[weak]
+                url_entry,
+                move |_, response| {
+                    if response == "add" {
+                        radio_list.add_station(&name_entry.text(), &url_entry.text());
+                    }
+                    dialog.close();
+                }
+            ),
+        );
+
+        dialog.present(Some(&self.get_root_window()));
+        glib::idle_add_local_once(glib::clone!(
+            #[weak]
+            name_entry,
+            move || {
+                name_entry.grab_focus();
+            }
+        ));
+    }
+
+    fn add_station(&self, name: &str, stream_url: &str) {
+        let Some(station) = new_user_station(name, stream_url) else {
+            self.toast(&tr("Not a stream URL"), None);
+            return;
+        };
+        self.get_application()
+            .radio_store()
+            .borrow_mut()
+            .add(station);
+        self.save_stations();
+    }
+
+    fn remove_station(&self, station_id: &str) {
+        self.get_application()
+            .radio_store()
+            .borrow_mut()
+            .remove(station_id);
+        self.save_stations();
+    }
+
+    fn save_stations(&self) {
+        self.get_application().save_radio_store();
+        self.show_stations();
+    }
+
+    pub fn setup_library_connection(&self) {
+        self.show_stations();
+        let app = self.get_application();
+        app.connect_closure(
+            "library-refreshed",
+            false,
+            glib::closure_local!(
+                #[weak(rename_to = radio_list)]
+                self,
+                move |_app: Application, _total_record_count: u64| {
+                    radio_list.refresh();
+                }
+            ),
+        );
+    }
+}
+
+impl Default for RadioList {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+mod imp {
+    use std::cell::{Cell, RefCell};
+
+    use adw::subclass::prelude::*;
+    use glib::subclass::InitializingObject;
+
+    use crate::jellyfin::api::RadioStation;
+    use gtk::{
+        CompositeTemplate,
+        glib::{self, Properties},
+        prelude::*,
+    };
+
+    #[derive(CompositeTemplate, Default, Properties)]
+    #[template(resource = "/io/m51/Gelly/ui/radio_list.ui")]
+    #[properties(wrapper_type = super::RadioList)]
+    pub struct RadioList {
+        #[template_child]
+        pub scrolled_window: TemplateChild<gtk::ScrolledWindow>,
+        #[template_child]
+        pub server_heading: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub server_list: TemplateChild<gtk::ListBox>,
+        #[template_child]
+        pub user_heading: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub user_list: TemplateChild<gtk::ListBox>,
+        #[template_child]
+        pub empty: TemplateChild<adw::StatusPage>,
+
+        pub server_stations: RefCell<Vec<RadioStation>>,
+        pub search: RefCell<String>,
+        pub sort_direction: Cell<u32>,
+
+        #[property(get, set, default = false)]
+        pub compact_mode: Cell<bool>,
+        #[property(get, set, default = false)]
+        pub is_loading: Cell<bool>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for RadioList {
+        const NAME: &'static str = "GellyRadioList";
+        type Type = super::RadioList;
+        type ParentType = gtk::Box;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+            klass.install_action("radio.add", None, |radio_list, _, _| {
+                radio_list.add_station_dialog();
+            });
+            klass.install_action("radio.refresh", None, |radio_list, _, _| {
+                radio_list.refresh();
+            });
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for RadioList {
+        fn constructed(&self) {
+            self.parent_constructed();
+            for list_box in [&*self.server_list, &*self.user_list] {
+                list_box.set_filter_func(glib::clone!(
+                    #[weak(rename_to = radio_list)]
+                    self.obj(),
+                    #[upgrade_or]
+                    true,
+                    move |row| {
+                        let search = radio_list.imp().search.borrow();
+                        search.is_empty()
+                            || row
+                                .downcast_ref::<adw::ActionRow>()
+                                .is_some_and(|row| row.title().to_lowercase().contains(&*search))
+                    }
+                ));
+            }
+        }
+    }
+
+    impl WidgetImpl for RadioList {}
+    impl BoxImpl for RadioList {}
+}
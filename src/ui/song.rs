@@ -13,10 +13,11 @@ use crate::{
     audio::stream_info::discover_stream_info,
     i18n::tr,
     jellyfin::{api::ItemType, utils::format_duration},
-    models::SongModel,
+    models::{Rate, SongModel},
     ui::{
         music_context_menu::{
-            ContextActions, add_to_playlist_dialog, add_to_playlist_dup_check, construct_menu,
+            ContextActions, add_to_playlist_dialog, add_to_playlist_dup_check,
+            construct_menu_with_resume,
         },
         stream_info_dialog,
         widget_ext::WidgetApplicationExt,
@@ -63,6 +64,7 @@ impl Song {
         imp.artist_label.set_label(&song.artists_string());
         imp.duration_label
             .set_label(&format_duration(song.duration()));
+        self.set_rating(song.rating());
     }
 
     pub fn set_starred(&self, is_favorite: bool) {
@@ -128,6 +130,16 @@ impl Song {
         );
     }
 
+    /// Rates the song when the rating differs from the model's, which is how
+    /// the "song.rate" menu action reaches the server
+    fn rate(&self, stars: u32) {
+        if let Some(song_model) = self.imp().song_model.borrow().clone()
+            && song_model.rating() != stars
+        {
+            song_model.rate(stars, &self.get_application());
+        }
+    }
+
     pub fn set_playing(&self, playing: bool) {
         if playing {
             self.add_css_class("song-playing");
@@ -211,10 +223,24 @@ impl Song {
             in_queue: self.imp().in_queue.get(),
             action_prefix: "song".to_string(),
             go_to_album: true,
+            go_to_folder: true,
             go_to_artist: true,
             show_info_dialog: true,
+            bookmarks: false,
+            rating: true,
+            export_playlist: false,
+            clean_up_playlist: false,
         };
-        let popover_menu = construct_menu(&options);
+        let popover_menu = construct_menu_with_resume(
+            &options,
+            glib::clone!(
+                #[weak(rename_to = song)]
+                self,
+                #[upgrade_or]
+                None,
+                move || song.resume_position()
+            ),
+        );
         self.imp().song_menu.set_popover(Some(&popover_menu));
     }
 
@@ -299,6 +325,21 @@ impl Song {
         self.emit_by_name::<()>("remove-from-playlist", &[&self.song_id()]);
     }
 
+    fn resume_position(&self) -> Option<u64> {
+        let song_model = self.imp().song_model.borrow().clone()?;
+        self.get_application().resume_position(&song_model)
+    }
+
+    fn on_resume(&self) {
+        let app = self.get_application();
+        if let Some(audio_model) = app.audio_model()
+            && let Some(song_model) = self.imp().song_model.borrow().clone()
+            && let Some(position) = app.resume_position(&song_model)
+        {
+            audio_model.play_from(song_model, position as u32);
+        }
+    }
+
     fn on_queue_next(&self) {
         let app = self.get_application();
         if let Some(audio_model) = app.audio_model()
@@ -324,6 +365,10 @@ impl Song {
     fn on_go_to_artist(&self) {
         self.emit_by_name::<()>("artist-clicked", &[&self.song_id()]);
     }
+
+    fn on_go_to_folder(&self) {
+        self.emit_by_name::<()>("folder-clicked", &[&self.song_id()]);
+    }
 }
 
 impl Default for Song {
@@ -377,6 +422,9 @@ mod imp {
 
         #[property(get, set = Self::set_position)]
         pub position: Cell<i32>,
+        /// Mirrors the model's rating so the rating menu can show it
+        #[property(get, set = Self::set_rating)]
+        pub rating: Cell<u32>,
         pub song_model: RefCell<Option<SongModel>>,
         pub favorite_indicator_handler:
             RefCell<Option<(glib::SignalHandlerId, WeakRef<Application>)>>,
@@ -400,6 +448,11 @@ mod imp {
             self.position.set(position);
             self.number_label.set_label(&(position + 1).to_string());
         }
+
+        fn set_rating(&self, stars: u32) {
+            self.rating.set(stars);
+            self.obj().rate(stars);
+        }
     }
 
     #[glib::object_subclass]
@@ -416,6 +469,9 @@ mod imp {
             klass.install_action("song.remove_playlist", None, |song, _, _| {
                 song.on_remove_from_playlist();
             });
+            klass.install_action("song.resume", None, |song, _, _| {
+                song.on_resume();
+            });
             klass.install_action("song.queue_next", None, |song, _, _| {
                 song.on_queue_next();
             });
@@ -428,12 +484,16 @@ mod imp {
             klass.install_action("song.go_to_artist", None, |song, _, _| {
                 song.on_go_to_artist();
             });
+            klass.install_action("song.go_to_folder", None, |song, _, _| {
+                song.on_go_to_folder();
+            });
             klass.install_action("song.add_to_playlist_dialog", None, |song, _, _| {
                 song.on_add_to_playlist_dialog();
             });
             klass.install_action("song.show_info_dialog", None, |song, _, _| {
                 song.show_info_dialog();
             });
+            klass.install_property_action("song.rate", "rating");
         }
 
         fn instance_init(obj: &InitializingObject<Self>) {
@@ -459,6 +519,9 @@ mod imp {
                     Signal::builder("album-clicked")
                         .param_types([String::static_type()])
                         .build(),
+                    Signal::builder("folder-clicked")
+                        .param_types([String::static_type()])
+                        .build(),
                 ]
             })
         }
@@ -64,7 +64,12 @@ impl TopPage for SongList {
     }
 
     fn sort_options(&self) -> &[SortType] {
-        &[SortType::DateAdded, SortType::Name, SortType::Artist]
+        &[
+            SortType::DateAdded,
+            SortType::Name,
+            SortType::Artist,
+            SortType::Rating,
+        ]
     }
 
     // Saving a non-default sort order would cause an expensive sort on every app load
@@ -203,6 +208,7 @@ impl SongList {
                     .cmp(&b.artists_string().to_lowercase())
                     .into(),
                 SortType::DateAdded => b.date_created().cmp(&a.date_created()).into(),
+                SortType::Rating => a.rating().cmp(&b.rating()).into(),
                 _ => gtk::Ordering::Equal,
             }
         })
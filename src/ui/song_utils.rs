@@ -40,7 +40,19 @@ pub fn connect_song_navigation(song: &Song, window: &Window) -> Vec<glib::Signal
         ),
     );
 
-    vec![handler1, handler2]
+    let handler3 = song.connect_closure(
+        "folder-clicked",
+        false,
+        glib::closure_local!(
+            #[weak]
+            window,
+            move |_song: Song, song_id: &str| {
+                window.show_song_folder(song_id);
+            }
+        ),
+    );
+
+    vec![handler1, handler2, handler3]
 }
 
 pub fn connect_playing_indicator(
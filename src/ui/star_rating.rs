@@ -0,0 +1,136 @@
+use glib::Object;
+use gtk::{glib, prelude::*, subclass::prelude::*};
+
+use crate::{
+    i18n::{ngettext, tr},
+    rating::MAX_STARS,
+};
+
+glib::wrapper! {
+    /// A row of stars for rating a song or album. Clicking the star that's
+    /// already the rating clears it.
+    pub struct StarRating(ObjectSubclass<imp::StarRating>)
+    @extends gtk::Widget, gtk::Box,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget, gtk::Orientable;
+}
+
+impl StarRating {
+    pub fn new() -> Self {
+        Object::builder().build()
+    }
+
+    /// Called with the new rating when the user picks one, not when the
+    /// `rating` property is set
+    pub fn connect_rated<F: Fn(&Self, u32) + 'static>(&self, f: F) -> glib::SignalHandlerId {
+        self.connect_closure(
+            "rated",
+            false,
+            glib::closure_local!(move |rating: &Self, stars: u32| f(rating, stars)),
+        )
+    }
+
+    fn on_star_clicked(&self, star: u32) {
+        let stars = if star == self.rating() { 0 } else { star };
+        self.set_rating(stars);
+        self.emit_by_name::<()>("rated", &[&stars]);
+    }
+}
+
+impl Default for StarRating {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// "No Rating" or how many stars, for menus and tooltips
+pub fn rating_label(stars: u32) -> String {
+    if stars == 0 {
+        tr("No Rating")
+    } else {
+        ngettext("1 Star", "{} Stars", stars).replace("{}", &stars.to_string())
+    }
+}
+
+mod imp {
+    use std::{
+        cell::{Cell, RefCell},
+        sync::OnceLock,
+    };
+
+    use gtk::{
+        glib::{self, Properties, subclass::Signal},
+        prelude::*,
+        subclass::prelude::*,
+    };
+
+    use super::{MAX_STARS, rating_label};
+
+    #[derive(Default, Properties)]
+    #[properties(wrapper_type = super::StarRating)]
+    pub struct StarRating {
+        pub buttons: RefCell<Vec<gtk::Button>>,
+        /// Stars out of five, zero when not rated
+        #[property(get, set = Self::set_rating)]
+        pub rating: Cell<u32>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for StarRating {
+        const NAME: &'static str = "GellyStarRating";
+        type Type = super::StarRating;
+        type ParentType = gtk::Box;
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for StarRating {
+        fn signals() -> &'static [Signal] {
+            static SIGNALS: OnceLock<Vec<Signal>> = OnceLock::new();
+            SIGNALS.get_or_init(|| {
+                vec![
+                    Signal::builder("rated")
+                        .param_types([u32::static_type()])
+                        .build(),
+                ]
+            })
+        }
+
+        fn constructed(&self) {
+            self.parent_constructed();
+            let obj = self.obj();
+            for star in 1..=MAX_STARS {
+                let button = gtk::Button::builder()
+                    .icon_name("non-starred-symbolic")
+                    .tooltip_text(rating_label(star))
+                    .valign(gtk::Align::Center)
+                    .css_classes(["flat", "circular"])
+                    .build();
+                button.connect_clicked(glib::clone!(
+                    #[weak]
+                    obj,
+                    move |_| obj.on_star_clicked(star)
+                ));
+                obj.append(&button);
+                self.buttons.borrow_mut().push(button);
+            }
+        }
+    }
+
+    impl WidgetImpl for StarRating {}
+    impl BoxImpl for StarRating {}
+
+    impl StarRating {
+        fn set_rating(&self, stars: u32) {
+            let stars = stars.min(MAX_STARS);
+            self.rating.set(stars);
+            for (index, button) in self.buttons.borrow().iter().enumerate() {
+                button.set_icon_name(if (index as u32) < stars {
+                    "starred-symbolic"
+                } else {
+                    "non-starred-symbolic"
+                });
+            }
+            self.obj()
+                .update_property(&[gtk::accessible::Property::Description(&rating_label(stars))]);
+        }
+    }
+}
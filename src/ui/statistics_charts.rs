@@ -0,0 +1,254 @@
+//! Charts for the listening statistics, drawn straight onto a snapshot so
+//! they come out the same on screen and in exported images.
+
+use glib::Object;
+use gtk::{gdk, glib, graphene, gsk, pango, prelude::*, subclass::prelude::*};
+
+const BAR_ROW_HEIGHT: f32 = 28.0;
+const BAR_HEIGHT: f32 = 14.0;
+const BAR_VALUE_WIDTH: f32 = 80.0;
+/// Share of the width given to the bar labels
+const BAR_LABEL_FRACTION: f32 = 0.4;
+const SPACING: f32 = 8.0;
+
+const HEATMAP_LABEL_WIDTH: f32 = 44.0;
+const HEATMAP_CELL_HEIGHT: f32 = 18.0;
+const HEATMAP_CELL_GAP: f32 = 2.0;
+const HEATMAP_HOURS_HEIGHT: f32 = 20.0;
+// Monday the 6th of January 2025, for the names of the days
+const A_MONDAY: i64 = 1_736_121_600;
+
+#[derive(Clone, Debug)]
+pub struct BarItem {
+    pub label: String,
+    /// Shown at the end of the bar
+    pub value_label: String,
+    pub value: f64,
+}
+
+glib::wrapper! {
+    pub struct BarChart(ObjectSubclass<imp::BarChart>)
+    @extends gtk::Widget,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget;
+}
+
+impl BarChart {
+    /// Bars are scaled against the largest value
+    pub fn new(items: Vec<BarItem>) -> Self {
+        let chart: Self = Object::builder().build();
+        chart.imp().items.replace(items);
+        chart
+    }
+}
+
+glib::wrapper! {
+    pub struct ListeningHeatmap(ObjectSubclass<imp::ListeningHeatmap>)
+    @extends gtk::Widget,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget;
+}
+
+impl ListeningHeatmap {
+    /// Values by day of the week, Monday first, then hour of the day
+    pub fn new(values: [[u64; 24]; 7]) -> Self {
+        let heatmap: Self = Object::builder().build();
+        heatmap.imp().values.set(values);
+        heatmap
+    }
+}
+
+fn accent_color() -> gdk::RGBA {
+    adw::StyleManager::default().accent_color_rgba()
+}
+
+/// Draws one line of text vertically centred on `center_y`, ellipsized to
+/// fit `width`
+fn draw_text(
+    widget: &gtk::Widget,
+    snapshot: &gtk::Snapshot,
+    text: &str,
+    (x, center_y): (f32, f32),
+    width: f32,
+    alignment: pango::Alignment,
+    dimmed: bool,
+) {
+    let layout = widget.create_pango_layout(Some(text));
+    layout.set_width((width * pango::SCALE as f32) as i32);
+    layout.set_ellipsize(pango::EllipsizeMode::End);
+    layout.set_alignment(alignment);
+    let (_, height) = layout.pixel_size();
+    let color = widget.color();
+    let color = if dimmed {
+        color.with_alpha(color.alpha() * 0.55)
+    } else {
+        color
+    };
+    snapshot.save();
+    snapshot.translate(&graphene::Point::new(x, center_y - height as f32 / 2.0));
+    snapshot.append_layout(&layout, &color);
+    snapshot.restore();
+}
+
+fn fill_rounded(snapshot: &gtk::Snapshot, rect: graphene::Rect, radius: f32, color: &gdk::RGBA) {
+    snapshot.push_rounded_clip(&gsk::RoundedRect::from_rect(rect, radius));
+    snapshot.append_color(color, &rect);
+    snapshot.pop();
+}
+
+mod imp {
+    use std::cell::{Cell, RefCell};
+
+    use super::*;
+
+    #[derive(Default)]
+    pub struct BarChart {
+        pub items: RefCell<Vec<BarItem>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for BarChart {
+        const NAME: &'static str = "GellyBarChart";
+        type Type = super::BarChart;
+        type ParentType = gtk::Widget;
+    }
+
+    impl ObjectImpl for BarChart {}
+
+    impl WidgetImpl for BarChart {
+        fn measure(&self, orientation: gtk::Orientation, _for_size: i32) -> (i32, i32, i32, i32) {
+            match orientation {
+                gtk::Orientation::Vertical => {
+                    let height = (self.items.borrow().len() as f32 * BAR_ROW_HEIGHT) as i32;
+                    (height, height, -1, -1)
+                }
+                _ => (240, 480, -1, -1),
+            }
+        }
+
+        fn snapshot(&self, snapshot: &gtk::Snapshot) {
+            let widget = self.obj();
+            let widget = widget.upcast_ref::<gtk::Widget>();
+            let width = widget.width() as f32;
+            let items = self.items.borrow();
+            let max = items.iter().map(|item| item.value).fold(0.0, f64::max);
+            let label_width = width * BAR_LABEL_FRACTION;
+            let bar_space = (width - label_width - BAR_VALUE_WIDTH - 2.0 * SPACING).max(0.0);
+            let accent = accent_color();
+
+            for (index, item) in items.iter().enumerate() {
+                let center_y = (index as f32 + 0.5) * BAR_ROW_HEIGHT;
+                draw_text(
+                    widget,
+                    snapshot,
+                    &item.label,
+                    (0.0, center_y),
+                    label_width,
+                    pango::Alignment::Left,
+                    false,
+                );
+                let fraction = if max > 0.0 { item.value / max } else { 0.0 };
+                let rect = graphene::Rect::new(
+                    label_width + SPACING,
+                    center_y - BAR_HEIGHT / 2.0,
+                    (bar_space * fraction as f32).max(BAR_HEIGHT / 2.0),
+                    BAR_HEIGHT,
+                );
+                fill_rounded(snapshot, rect, 4.0, &accent);
+                draw_text(
+                    widget,
+                    snapshot,
+                    &item.value_label,
+                    (width - BAR_VALUE_WIDTH, center_y),
+                    BAR_VALUE_WIDTH,
+                    pango::Alignment::Right,
+                    true,
+                );
+            }
+        }
+    }
+
+    #[derive(Default)]
+    pub struct ListeningHeatmap {
+        pub values: Cell<[[u64; 24]; 7]>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for ListeningHeatmap {
+        const NAME: &'static str = "GellyListeningHeatmap";
+        type Type = super::ListeningHeatmap;
+        type ParentType = gtk::Widget;
+    }
+
+    impl ObjectImpl for ListeningHeatmap {}
+
+    impl WidgetImpl for ListeningHeatmap {
+        fn measure(&self, orientation: gtk::Orientation, _for_size: i32) -> (i32, i32, i32, i32) {
+            match orientation {
+                gtk::Orientation::Vertical => {
+                    let height = (7.0 * (HEATMAP_CELL_HEIGHT + HEATMAP_CELL_GAP)
+                        + HEATMAP_HOURS_HEIGHT) as i32;
+                    (height, height, -1, -1)
+                }
+                _ => (320, 480, -1, -1),
+            }
+        }
+
+        fn snapshot(&self, snapshot: &gtk::Snapshot) {
+            let widget = self.obj();
+            let widget = widget.upcast_ref::<gtk::Widget>();
+            let width = widget.width() as f32;
+            let values = self.values.get();
+            let max = values.iter().flatten().copied().max().unwrap_or(0);
+            let cell_width = ((width - HEATMAP_LABEL_WIDTH) / 24.0 - HEATMAP_CELL_GAP).max(1.0);
+            let accent = accent_color();
+            let empty = widget.color().with_alpha(0.08);
+
+            for (day, hours) in values.iter().enumerate() {
+                let top = day as f32 * (HEATMAP_CELL_HEIGHT + HEATMAP_CELL_GAP);
+                let day_name = glib::DateTime::from_unix_utc(A_MONDAY + day as i64 * 86_400)
+                    .and_then(|date| date.format("%a"))
+                    .map(|name| name.to_string())
+                    .unwrap_or_default();
+                draw_text(
+                    widget,
+                    snapshot,
+                    &day_name,
+                    (0.0, top + HEATMAP_CELL_HEIGHT / 2.0),
+                    HEATMAP_LABEL_WIDTH - SPACING,
+                    pango::Alignment::Left,
+                    true,
+                );
+                for (hour, &value) in hours.iter().enumerate() {
+                    let rect = graphene::Rect::new(
+                        HEATMAP_LABEL_WIDTH + hour as f32 * (cell_width + HEATMAP_CELL_GAP),
+                        top,
+                        cell_width,
+                        HEATMAP_CELL_HEIGHT,
+                    );
+                    let color = if value == 0 || max == 0 {
+                        empty
+                    } else {
+                        accent.with_alpha(0.2 + 0.8 * (value as f32 / max as f32))
+                    };
+                    fill_rounded(snapshot, rect, 3.0, &color);
+                }
+            }
+
+            let hours_center =
+                7.0 * (HEATMAP_CELL_HEIGHT + HEATMAP_CELL_GAP) + HEATMAP_HOURS_HEIGHT / 2.0;
+            for hour in (0..24).step_by(6) {
+                draw_text(
+                    widget,
+                    snapshot,
+                    &format!("{:02}", hour),
+                    (
+                        HEATMAP_LABEL_WIDTH + hour as f32 * (cell_width + HEATMAP_CELL_GAP),
+                        hours_center,
+                    ),
+                    4.0 * cell_width,
+                    pango::Alignment::Left,
+                    true,
+                );
+            }
+        }
+    }
+}
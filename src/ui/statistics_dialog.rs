@@ -0,0 +1,317 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use adw::prelude::*;
+use gtk::{Window, gio, glib, graphene};
+use log::warn;
+
+use crate::application::Application;
+use crate::i18n::{ngettext, tr};
+use crate::statistics::{self, Period, RankedItem, ServerPlays, Statistics, TOP_COUNT};
+use crate::ui::statistics_charts::{BarChart, BarItem, ListeningHeatmap};
+
+/// Listening time as hours and minutes
+fn format_listened(seconds: u64) -> String {
+    let minutes = seconds / 60;
+    if minutes >= 60 {
+        tr("{} h {} min")
+            .replacen("{}", &(minutes / 60).to_string(), 1)
+            .replacen("{}", &(minutes % 60).to_string(), 1)
+    } else {
+        tr("{} min").replace("{}", &minutes.to_string())
+    }
+}
+
+fn format_plays(plays: u64) -> String {
+    ngettext("1 play", "{} plays", plays as u32).replace("{}", &plays.to_string())
+}
+
+fn heading(text: &str) -> gtk::Label {
+    gtk::Label::builder()
+        .label(text)
+        .halign(gtk::Align::Start)
+        .margin_top(12)
+        .css_classes(["heading"])
+        .build()
+}
+
+fn summary_card(value: &str, caption: &str) -> gtk::Box {
+    let card = gtk::Box::builder()
+        .orientation(gtk::Orientation::Vertical)
+        .spacing(3)
+        .css_classes(["card"])
+        .build();
+    card.append(
+        &gtk::Label::builder()
+            .label(value)
+            .margin_top(12)
+            .margin_start(12)
+            .margin_end(12)
+            .css_classes(["title-2", "numeric"])
+            .build(),
+    );
+    card.append(
+        &gtk::Label::builder()
+            .label(caption)
+            .margin_bottom(12)
+            .margin_start(12)
+            .margin_end(12)
+            .css_classes(["caption", "dimmed"])
+            .build(),
+    );
+    card
+}
+
+fn ranked_chart(items: &[RankedItem]) -> BarChart {
+    BarChart::new(
+        items
+            .iter()
+            .map(|item| BarItem {
+                label: if item.detail.is_empty() {
+                    item.name.clone()
+                } else {
+                    format!("{} · {}", item.name, item.detail)
+                },
+                value_label: format_plays(item.plays),
+                value: item.plays as f64,
+            })
+            .collect(),
+    )
+}
+
+/// Fills `content` with the statistics for a period
+fn rebuild(content: &gtk::Box, statistics: &Statistics) {
+    while let Some(child) = content.first_child() {
+        content.remove(&child);
+    }
+
+    if statistics.plays == 0 {
+        content.append(
+            &adw::StatusPage::builder()
+                .title(tr("Nothing Played Yet"))
+                .description(tr("Statistics build up as you listen"))
+                .icon_name("document-open-recent-symbolic")
+                .vexpand(true)
+                .build(),
+        );
+    } else {
+        let summary = gtk::FlowBox::builder()
+            .homogeneous(true)
+            .min_children_per_line(2)
+            .max_children_per_line(5)
+            .row_spacing(12)
+            .column_spacing(12)
+            .selection_mode(gtk::SelectionMode::None)
+            .build();
+        let days =
+            |days: u64| ngettext("1 day", "{} days", days as u32).replace("{}", &days.to_string());
+        let cards = [
+            (format_listened(statistics.listened), tr("Listened")),
+            (statistics.plays.to_string(), tr("Plays")),
+            (days(statistics.current_streak), tr("Current Streak")),
+            (days(statistics.longest_streak), tr("Longest Streak")),
+            (statistics.new_artists.len().to_string(), tr("New Artists")),
+        ];
+        for (value, caption) in &cards {
+            summary.append(&summary_card(value, caption));
+        }
+        content.append(&summary);
+
+        let sections = [
+            (tr("Top Songs"), &statistics.top_songs),
+            (tr("Top Artists"), &statistics.top_artists),
+            (tr("Top Albums"), &statistics.top_albums),
+            (tr("Top Genres"), &statistics.top_genres),
+        ];
+        for (title, items) in sections {
+            if !items.is_empty() {
+                content.append(&heading(&title));
+                content.append(&ranked_chart(items));
+            }
+        }
+        if !statistics.new_artists.is_empty() {
+            let new_artists: Vec<RankedItem> = statistics
+                .new_artists
+                .iter()
+                .take(TOP_COUNT)
+                .cloned()
+                .collect();
+            content.append(&heading(&tr("New Discoveries")));
+            content.append(&ranked_chart(&new_artists));
+        }
+
+        content.append(&heading(&tr("When You Listen")));
+        content.append(&ListeningHeatmap::new(statistics.heatmap));
+    }
+}
+
+/// Renders the widget as it is on screen to a PNG file
+fn save_png(widget: &gtk::Widget, path: &Path) -> Result<(), String> {
+    let (width, height) = (widget.width(), widget.height());
+    let paintable = gtk::WidgetPaintable::new(Some(widget));
+    let snapshot = gtk::Snapshot::new();
+    paintable.snapshot(&snapshot, width as f64, height as f64);
+    let node = snapshot.to_node().ok_or("Nothing to render")?;
+    let renderer = widget
+        .native()
+        .and_then(|native| native.renderer())
+        .ok_or("No renderer")?;
+    let rect = graphene::Rect::new(0.0, 0.0, width as f32, height as f32);
+    renderer
+        .render_texture(&node, Some(&rect))
+        .save_to_png(path)
+        .map_err(|err| err.to_string())
+}
+
+fn export(toasts: &adw::ToastOverlay, content: &gtk::Box) {
+    let filter = gtk::FileFilter::new();
+    filter.set_name(Some("PNG"));
+    filter.add_mime_type("image/png");
+    let dialog = gtk::FileDialog::builder()
+        .title(tr("Export Image"))
+        .initial_name("listening-statistics.png")
+        .default_filter(&filter)
+        .modal(true)
+        .build();
+    dialog.save(
+        toasts.root().and_downcast::<Window>().as_ref(),
+        gio::Cancellable::NONE,
+        glib::clone!(
+            #[weak]
+            toasts,
+            #[weak]
+            content,
+            move |result| {
+                let Some(path) = result.ok().and_then(|file| file.path()) else {
+                    return;
+                };
+                match save_png(content.upcast_ref(), &path) {
+                    Ok(()) => toasts.add_toast(adw::Toast::new(&tr("Image exported"))),
+                    Err(err) => {
+                        warn!("Failed to export statistics image: {}", err);
+                        toasts.add_toast(adw::Toast::new(&tr("Could not export image")));
+                    }
+                }
+            }
+        ),
+    );
+}
+
+pub fn show(parent: Option<&Window>, app: &Application) {
+    let library = app.library();
+    let genres: HashMap<String, Vec<String>> = library.genres_by_song();
+    let server: Vec<ServerPlays> = library
+        .all_songs()
+        .iter()
+        .filter(|song| song.play_count() > 0)
+        .map(|song| ServerPlays {
+            song_id: song.id(),
+            title: song.title(),
+            artists: song.artists(),
+            album: song.album(),
+            album_id: song.album_id(),
+            plays: song.play_count(),
+        })
+        .collect();
+    let history = app.play_history();
+
+    let periods = adw::ToggleGroup::builder()
+        .halign(gtk::Align::Center)
+        .margin_top(6)
+        .margin_bottom(6)
+        .build();
+    for (period, label) in [
+        (Period::Week, tr("Week")),
+        (Period::Month, tr("Month")),
+        (Period::Year, tr("Year")),
+        (Period::AllTime, tr("All Time")),
+    ] {
+        periods.add(
+            adw::Toggle::builder()
+                .name(period.name())
+                .label(label)
+                .build(),
+        );
+    }
+    periods.set_active_name(Some(Period::Month.name()));
+
+    let export_button = gtk::Button::builder()
+        .icon_name("document-save-symbolic")
+        .tooltip_text(tr("Export Image"))
+        .build();
+    let header_bar = adw::HeaderBar::new();
+    header_bar.set_title_widget(Some(&adw::WindowTitle::new(
+        &tr("Listening Statistics"),
+        "",
+    )));
+    header_bar.pack_end(&export_button);
+
+    let toolbar_view = adw::ToolbarView::new();
+    toolbar_view.add_top_bar(&header_bar);
+    toolbar_view.add_top_bar(&periods);
+
+    // The background comes along when the content is exported as an image
+    let content = gtk::Box::builder()
+        .orientation(gtk::Orientation::Vertical)
+        .spacing(12)
+        .css_classes(["background"])
+        .build();
+    let clamp = adw::Clamp::builder()
+        .maximum_size(700)
+        .margin_top(12)
+        .margin_bottom(12)
+        .margin_start(12)
+        .margin_end(12)
+        .child(&content)
+        .build();
+    let scrolled_window = gtk::ScrolledWindow::new();
+    scrolled_window.set_policy(gtk::PolicyType::Never, gtk::PolicyType::Automatic);
+    scrolled_window.set_child(Some(&clamp));
+    let toasts = adw::ToastOverlay::new();
+    toasts.set_child(Some(&scrolled_window));
+    toolbar_view.set_content(Some(&toasts));
+
+    let update = glib::clone!(
+        #[weak]
+        content,
+        move |periods: &adw::ToggleGroup| {
+            let period = periods
+                .active_name()
+                .and_then(|name| Period::from_name(&name))
+                .unwrap_or(Period::Month);
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |time| time.as_secs() as i64);
+            let utc_offset =
+                glib::DateTime::now_local().map_or(0, |time| time.utc_offset().as_seconds());
+            let statistics = statistics::compute(
+                history.borrow().plays(),
+                &server,
+                period,
+                now,
+                utc_offset,
+                |id| genres.get(id).cloned().unwrap_or_default(),
+            );
+            rebuild(&content, &statistics);
+        }
+    );
+    update(&periods);
+    periods.connect_active_name_notify(update);
+
+    export_button.connect_clicked(glib::clone!(
+        #[weak]
+        toasts,
+        #[weak]
+        content,
+        move |_| export(&toasts, &content)
+    ));
+
+    let dialog = adw::Dialog::builder()
+        .can_close(true)
+        .child(&toolbar_view)
+        .build();
+    dialog.set_content_width(600);
+    dialog.set_content_height(700);
+    dialog.present(parent);
+}
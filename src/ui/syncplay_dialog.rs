@@ -0,0 +1,185 @@
+use std::cell::RefCell;
+
+use adw::prelude::*;
+use gtk::{Window, glib};
+use log::warn;
+
+use crate::i18n::tr;
+use crate::jellyfin::api::GroupInfoDto;
+use crate::remote::syncplay::SyncPlay;
+
+fn heading(text: &str) -> gtk::Label {
+    let label = gtk::Label::new(Some(text));
+    label.set_halign(gtk::Align::Start);
+    label.set_css_classes(&["heading"]);
+    label
+}
+
+fn boxed_list() -> gtk::ListBox {
+    let list_box = gtk::ListBox::new();
+    list_box.set_css_classes(&["boxed-list"]);
+    list_box.set_selection_mode(gtk::SelectionMode::None);
+    list_box
+}
+
+fn build_group_page(syncplay: &SyncPlay, content: &gtk::Box) {
+    content.append(&heading(&syncplay.group_name()));
+
+    let members = boxed_list();
+    for member in syncplay.members() {
+        let row = adw::ActionRow::builder().title(member).build();
+        row.set_use_markup(false);
+        row.add_prefix(&gtk::Image::from_icon_name("avatar-default-symbolic"));
+        members.append(&row);
+    }
+    content.append(&members);
+
+    let leave_button = gtk::Button::with_label(&tr("Leave Group"));
+    leave_button.set_css_classes(&["pill", "destructive-action"]);
+    leave_button.set_halign(gtk::Align::Center);
+    leave_button.connect_clicked(glib::clone!(
+        #[weak]
+        syncplay,
+        move |_| syncplay.leave_group()
+    ));
+    content.append(&leave_button);
+}
+
+fn build_join_page(syncplay: &SyncPlay, content: &gtk::Box) {
+    content.append(&heading(&tr("New Group")));
+    let create_list = boxed_list();
+    let name_row = adw::EntryRow::builder()
+        .title(tr("Group Name"))
+        .show_apply_button(true)
+        .build();
+    name_row.connect_apply(glib::clone!(
+        #[weak]
+        syncplay,
+        move |row| {
+            let name = row.text();
+            if !name.trim().is_empty() {
+                syncplay.create_group(name.trim());
+            }
+        }
+    ));
+    create_list.append(&name_row);
+    content.append(&create_list);
+
+    content.append(&heading(&tr("Groups on This Server")));
+    let groups_list = boxed_list();
+    let spinner = adw::Spinner::new();
+    spinner.set_margin_top(12);
+    spinner.set_margin_bottom(12);
+    groups_list.append(&spinner);
+    content.append(&groups_list);
+
+    syncplay.list_groups(glib::clone!(
+        #[weak]
+        syncplay,
+        #[weak]
+        groups_list,
+        move |result| {
+            groups_list.remove_all();
+            match result {
+                Ok(groups) if groups.is_empty() => {
+                    let row = adw::ActionRow::builder().title(tr("No groups yet")).build();
+                    groups_list.append(&row);
+                }
+                Ok(groups) => {
+                    for group in groups {
+                        groups_list.append(&group_row(&syncplay, &group));
+                    }
+                }
+                Err(e) => {
+                    warn!("Failed to list SyncPlay groups: {}", e);
+                    let row = adw::ActionRow::builder()
+                        .title(tr("Could not load groups"))
+                        .build();
+                    groups_list.append(&row);
+                }
+            }
+        }
+    ));
+}
+
+fn group_row(syncplay: &SyncPlay, group: &GroupInfoDto) -> adw::ActionRow {
+    let row = adw::ActionRow::builder()
+        .title(&group.group_name)
+        .subtitle(group.participants.join(", "))
+        .build();
+    row.set_use_markup(false);
+    let join_button = gtk::Button::with_label(&tr("Join"));
+    join_button.set_valign(gtk::Align::Center);
+    let group_id = group.group_id.clone();
+    join_button.connect_clicked(glib::clone!(
+        #[weak]
+        syncplay,
+        move |_| syncplay.join_group(&group_id)
+    ));
+    row.add_suffix(&join_button);
+    row.set_activatable_widget(Some(&join_button));
+    row
+}
+
+fn rebuild(syncplay: &SyncPlay, content: &gtk::Box) {
+    while let Some(child) = content.first_child() {
+        content.remove(&child);
+    }
+    if syncplay.active() {
+        build_group_page(syncplay, content);
+    } else {
+        build_join_page(syncplay, content);
+    }
+}
+
+pub fn show(parent: Option<&Window>, syncplay: &SyncPlay) {
+    let header_bar = adw::HeaderBar::new();
+    header_bar.set_title_widget(Some(&adw::WindowTitle::new(&tr("Listen Together"), "")));
+
+    let toolbar_view = adw::ToolbarView::new();
+    toolbar_view.add_top_bar(&header_bar);
+
+    let scrolled_window = gtk::ScrolledWindow::new();
+    scrolled_window.set_policy(gtk::PolicyType::Never, gtk::PolicyType::Automatic);
+
+    let content = gtk::Box::new(gtk::Orientation::Vertical, 12);
+    content.set_margin_top(12);
+    content.set_margin_bottom(12);
+    content.set_margin_start(12);
+    content.set_margin_end(12);
+    scrolled_window.set_child(Some(&content));
+    toolbar_view.set_content(Some(&scrolled_window));
+
+    rebuild(syncplay, &content);
+    let handlers: Vec<_> = ["active", "members", "group-name"]
+        .into_iter()
+        .map(|property| {
+            syncplay.connect_notify_local(
+                Some(property),
+                glib::clone!(
+                    #[weak]
+                    content,
+                    move |syncplay, _| rebuild(syncplay, &content)
+                ),
+            )
+        })
+        .collect();
+    let handlers = RefCell::new(handlers);
+
+    let dialog = adw::Dialog::builder()
+        .can_close(true)
+        .child(&toolbar_view)
+        .build();
+    dialog.set_content_width(400);
+    dialog.set_content_height(500);
+    dialog.connect_closed(glib::clone!(
+        #[weak]
+        syncplay,
+        move |_| {
+            for handler in handlers.take() {
+                syncplay.disconnect(handler);
+            }
+        }
+    ));
+    dialog.present(parent);
+}
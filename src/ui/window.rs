@@ -2,11 +2,14 @@ use crate::async_utils::spawn_tokio;
 use crate::config::{self, settings};
 use crate::i18n::tr;
 use crate::jellyfin::api::LibraryDtoList;
-use crate::models::{AlbumModel, ArtistModel, PlaylistModel};
+use crate::models::{AlbumModel, ArtistModel, CategoryModel, PlaylistModel};
 use crate::ui::album_art_background::create_blur_paintable;
 use crate::ui::page_traits::{DetailPage, TopPage};
 use crate::ui::preferences::Preferences;
-use crate::ui::{about_dialog, shortcuts_dialog};
+use crate::ui::{
+    about_dialog, lyrics_editor, output_dialog, playlist_files, shortcuts_dialog,
+    statistics_dialog, syncplay_dialog,
+};
 use crate::{application::Application, ui::widget_ext::WidgetApplicationExt};
 use adw::{prelude::*, subclass::prelude::ObjectSubclassIsExt};
 use glib::Object;
@@ -57,10 +60,17 @@ impl Window {
         imp.main_navigation.replace(&[imp.main_window.get()]);
         imp.album_list.setup_library_connection();
         imp.artist_list.setup_library_connection();
+        imp.category_list.setup_library_connection();
+        imp.folder_browser.setup_library_connection();
+        imp.podcast_list.setup_library_connection();
+        imp.radio_list.setup_library_connection();
+        imp.audiobook_list.setup_library_connection();
+        imp.history_list.setup_library_connection();
         imp.playlist_list.setup_library_connection();
         imp.song_list.setup_library_connection();
         imp.playlist_detail.setup_library_connection();
         // Library is refreshed down at the end of the connect_map signal
+        self.get_application().connect_session();
 
         // Initialize player bar with audio model
         if let Some(audio_model) = self.get_application().audio_model() {
@@ -80,8 +90,20 @@ impl Window {
                 self.show_page(&imp.album_list.get());
             } else if visible_child == imp.artist_list.get().upcast::<gtk::Widget>() {
                 self.show_page(&imp.artist_list.get());
+            } else if visible_child == imp.category_list.get().upcast::<gtk::Widget>() {
+                self.show_page(&imp.category_list.get());
+            } else if visible_child == imp.folder_browser.get().upcast::<gtk::Widget>() {
+                self.show_page(&imp.folder_browser.get());
             } else if visible_child == imp.playlist_list.get().upcast::<gtk::Widget>() {
                 self.show_page(&imp.playlist_list.get());
+            } else if visible_child == imp.podcast_list.get().upcast::<gtk::Widget>() {
+                self.show_page(&imp.podcast_list.get());
+            } else if visible_child == imp.radio_list.get().upcast::<gtk::Widget>() {
+                self.show_page(&imp.radio_list.get());
+            } else if visible_child == imp.audiobook_list.get().upcast::<gtk::Widget>() {
+                self.show_page(&imp.audiobook_list.get());
+            } else if visible_child == imp.history_list.get().upcast::<gtk::Widget>() {
+                self.show_page(&imp.history_list.get());
             } else if visible_child == imp.song_list.get().upcast::<gtk::Widget>() {
                 self.show_page(&imp.song_list.get());
             } else {
@@ -156,6 +178,21 @@ impl Window {
         );
     }
 
+    pub fn show_category_detail(&self, category_model: &CategoryModel) {
+        self.show_detail_page(
+            &self.imp().category_detail_page.get(),
+            &self.imp().category_detail.get(),
+            category_model,
+        );
+    }
+
+    /// Switches to the folder browser, opened at the folder the song is in
+    pub fn show_song_folder(&self, song_id: &str) {
+        let imp = self.imp();
+        imp.stack.set_visible_child(&imp.folder_browser.get());
+        imp.folder_browser.open_song_folder(song_id);
+    }
+
     pub fn show_playlist_detail(&self, playlist_model: &PlaylistModel) {
         self.show_detail_page(
             &self.imp().playlist_detail_page.get(),
@@ -177,6 +214,42 @@ impl Window {
         preferences_dialog.present(Some(self));
     }
 
+    pub fn show_syncplay_dialog(&self) {
+        let syncplay = self.get_application().syncplay();
+        if !syncplay.is_supported() {
+            self.toast(&tr("Listening together needs a Jellyfin server"), None);
+            return;
+        }
+        syncplay_dialog::show(Some(self.upcast_ref()), &syncplay);
+    }
+
+    pub fn show_output_dialog(&self) {
+        let Some(audio_model) = self.get_application().audio_model() else {
+            return;
+        };
+        output_dialog::show(Some(self.upcast_ref()), &audio_model);
+    }
+
+    pub fn import_playlist(&self) {
+        playlist_files::import(self);
+    }
+
+    pub fn show_statistics_dialog(&self) {
+        statistics_dialog::show(Some(self.upcast_ref()), &self.get_application());
+    }
+
+    pub fn show_lyrics_editor(&self) {
+        let app = self.get_application();
+        let Some(audio_model) = app.audio_model() else {
+            return;
+        };
+        if audio_model.current_song().is_none() {
+            self.toast(&tr("Play a song to edit its lyrics"), None);
+            return;
+        }
+        lyrics_editor::show(Some(self.upcast_ref()), &audio_model, &app.backend(), || {});
+    }
+
     fn show_library_dialog(&self, libraries: &LibraryDtoList) {
         let app = self.get_application();
         let libraries = libraries.items.clone();
@@ -292,8 +365,20 @@ impl Window {
                 action(&imp.album_list.get());
             } else if visible_child == imp.artist_list.get().upcast::<gtk::Widget>() {
                 action(&imp.artist_list.get());
+            } else if visible_child == imp.category_list.get().upcast::<gtk::Widget>() {
+                action(&imp.category_list.get());
+            } else if visible_child == imp.folder_browser.get().upcast::<gtk::Widget>() {
+                action(&imp.folder_browser.get());
             } else if visible_child == imp.playlist_list.get().upcast::<gtk::Widget>() {
                 action(&imp.playlist_list.get());
+            } else if visible_child == imp.podcast_list.get().upcast::<gtk::Widget>() {
+                action(&imp.podcast_list.get());
+            } else if visible_child == imp.radio_list.get().upcast::<gtk::Widget>() {
+                action(&imp.radio_list.get());
+            } else if visible_child == imp.audiobook_list.get().upcast::<gtk::Widget>() {
+                action(&imp.audiobook_list.get());
+            } else if visible_child == imp.history_list.get().upcast::<gtk::Widget>() {
+                action(&imp.history_list.get());
             } else if visible_child == imp.song_list.get().upcast::<gtk::Widget>() {
                 action(&imp.song_list.get());
             } else {
@@ -311,7 +396,15 @@ impl Window {
         let compact_mode = imp.split_view.is_collapsed() || config::get_compact_mode_enabled();
         imp.album_list.set_property("compact-mode", compact_mode);
         imp.artist_list.set_property("compact-mode", compact_mode);
+        imp.category_list.set_property("compact-mode", compact_mode);
+        imp.folder_browser
+            .set_property("compact-mode", compact_mode);
         imp.playlist_list.set_property("compact-mode", compact_mode);
+        imp.podcast_list.set_property("compact-mode", compact_mode);
+        imp.radio_list.set_property("compact-mode", compact_mode);
+        imp.audiobook_list
+            .set_property("compact-mode", compact_mode);
+        imp.history_list.set_property("compact-mode", compact_mode);
         imp.player_bar.compact_mode(compact_mode);
     }
 
@@ -381,15 +474,22 @@ mod imp {
     use crate::ui::{
         album_art_background::BlurBackground,
         artist_detail::ArtistDetail,
+        audiobook_list::AudiobookList,
+        category_detail::CategoryDetail,
+        category_list::CategoryList,
+        folder_browser::FolderBrowser,
+        history_list::HistoryList,
         page_traits::TopPage,
         player_bar::{big_player::BigPlayer, mini_player::MiniPlayerBar},
+        podcast_list::PodcastList,
+        radio_list::RadioList,
         song_list::SongList,
     };
     use crate::ui::{playlist_list::PlaylistList, widget_ext::WidgetApplicationExt};
     use crate::ui::{queue::Queue, setup::Setup};
     use crate::{application::Application, ui::album_detail::AlbumDetail};
     use crate::{
-        config,
+        config, lyrics,
         ui::{album_list::AlbumList, artist_list::ArtistList, playlist_detail::PlaylistDetail},
     };
 
@@ -421,8 +521,24 @@ mod imp {
         #[template_child]
         pub artist_detail: TemplateChild<ArtistDetail>,
         #[template_child]
+        pub category_list: TemplateChild<CategoryList>,
+        #[template_child]
+        pub category_detail_page: TemplateChild<adw::NavigationPage>,
+        #[template_child]
+        pub category_detail: TemplateChild<CategoryDetail>,
+        #[template_child]
+        pub folder_browser: TemplateChild<FolderBrowser>,
+        #[template_child]
         pub playlist_list: TemplateChild<PlaylistList>,
         #[template_child]
+        pub podcast_list: TemplateChild<PodcastList>,
+        #[template_child]
+        pub radio_list: TemplateChild<RadioList>,
+        #[template_child]
+        pub audiobook_list: TemplateChild<AudiobookList>,
+        #[template_child]
+        pub history_list: TemplateChild<HistoryList>,
+        #[template_child]
         pub playlist_detail: TemplateChild<PlaylistDetail>,
         #[template_child]
         pub playlist_detail_page: TemplateChild<adw::NavigationPage>,
@@ -515,6 +631,7 @@ mod imp {
                         } else {
                             warn!("No image cache found");
                         }
+                        lyrics::clear_cache();
                     }
                 ))
                 .build();
@@ -650,7 +767,13 @@ mod imp {
 
             self.album_list.connect_search(&self.search_entry);
             self.artist_list.connect_search(&self.search_entry);
+            self.category_list.connect_search(&self.search_entry);
+            self.folder_browser.connect_search(&self.search_entry);
             self.playlist_list.connect_search(&self.search_entry);
+            self.podcast_list.connect_search(&self.search_entry);
+            self.radio_list.connect_search(&self.search_entry);
+            self.audiobook_list.connect_search(&self.search_entry);
+            self.history_list.connect_search(&self.search_entry);
             self.song_list.connect_search(&self.search_entry);
 
             self.album_list.connect_favorite(&self.favorite_button);
@@ -716,6 +839,56 @@ mod imp {
                 ))
                 .build();
 
+            let action_syncplay = ActionEntry::builder("syncplay")
+                .activate(glib::clone!(
+                    #[weak(rename_to=window)]
+                    self,
+                    move |_, _, _| {
+                        window.obj().show_syncplay_dialog();
+                    }
+                ))
+                .build();
+
+            let action_select_output = ActionEntry::builder("select-output")
+                .activate(glib::clone!(
+                    #[weak(rename_to=window)]
+                    self,
+                    move |_, _, _| {
+                        window.obj().show_output_dialog();
+                    }
+                ))
+                .build();
+
+            let action_edit_lyrics = ActionEntry::builder("edit-lyrics")
+                .activate(glib::clone!(
+                    #[weak(rename_to=window)]
+                    self,
+                    move |_, _, _| {
+                        window.obj().show_lyrics_editor();
+                    }
+                ))
+                .build();
+
+            let action_import_playlist = ActionEntry::builder("import-playlist")
+                .activate(glib::clone!(
+                    #[weak(rename_to=window)]
+                    self,
+                    move |_, _, _| {
+                        window.obj().import_playlist();
+                    }
+                ))
+                .build();
+
+            let action_statistics = ActionEntry::builder("statistics")
+                .activate(glib::clone!(
+                    #[weak(rename_to=window)]
+                    self,
+                    move |_, _, _| {
+                        window.obj().show_statistics_dialog();
+                    }
+                ))
+                .build();
+
             let action_change_library = ActionEntry::builder("change-library")
                 .activate(glib::clone!(
                     #[weak(rename_to=window)]
@@ -746,6 +919,26 @@ mod imp {
                 ))
                 .build();
 
+            let action_category_list = ActionEntry::builder("show-category-list")
+                .activate(glib::clone!(
+                    #[weak(rename_to=window)]
+                    self,
+                    move |_, _, _| {
+                        window.stack.set_visible_child(&window.category_list.get());
+                    }
+                ))
+                .build();
+
+            let action_folder_browser = ActionEntry::builder("show-folder-browser")
+                .activate(glib::clone!(
+                    #[weak(rename_to=window)]
+                    self,
+                    move |_, _, _| {
+                        window.stack.set_visible_child(&window.folder_browser.get());
+                    }
+                ))
+                .build();
+
             let action_playlist_list = ActionEntry::builder("show-playlist-list")
                 .activate(glib::clone!(
                     #[weak(rename_to=window)]
@@ -756,6 +949,46 @@ mod imp {
                 ))
                 .build();
 
+            let action_podcast_list = ActionEntry::builder("show-podcast-list")
+                .activate(glib::clone!(
+                    #[weak(rename_to=window)]
+                    self,
+                    move |_, _, _| {
+                        window.stack.set_visible_child(&window.podcast_list.get());
+                    }
+                ))
+                .build();
+
+            let action_radio_list = ActionEntry::builder("show-radio-list")
+                .activate(glib::clone!(
+                    #[weak(rename_to=window)]
+                    self,
+                    move |_, _, _| {
+                        window.stack.set_visible_child(&window.radio_list.get());
+                    }
+                ))
+                .build();
+
+            let action_audiobook_list = ActionEntry::builder("show-audiobook-list")
+                .activate(glib::clone!(
+                    #[weak(rename_to=window)]
+                    self,
+                    move |_, _, _| {
+                        window.stack.set_visible_child(&window.audiobook_list.get());
+                    }
+                ))
+                .build();
+
+            let action_history_list = ActionEntry::builder("show-history-list")
+                .activate(glib::clone!(
+                    #[weak(rename_to=window)]
+                    self,
+                    move |_, _, _| {
+                        window.stack.set_visible_child(&window.history_list.get());
+                    }
+                ))
+                .build();
+
             let action_song_list = ActionEntry::builder("show-song-list")
                 .activate(glib::clone!(
                     #[weak(rename_to=window)]
@@ -778,10 +1011,21 @@ mod imp {
                 action_about,
                 action_shortcuts,
                 action_preferences,
+                action_syncplay,
+                action_select_output,
+                action_edit_lyrics,
+                action_statistics,
+                action_import_playlist,
                 action_change_library,
                 action_album_list,
                 action_artist_list,
+                action_category_list,
+                action_folder_browser,
                 action_playlist_list,
+                action_podcast_list,
+                action_radio_list,
+                action_audiobook_list,
+                action_history_list,
                 action_song_list,
                 action_sidebar,
             ]);